@@ -0,0 +1,61 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Only offer the days that exist in the selected month. The server checks the date either way,
+// this just catches mistakes before the form is submitted.
+(function () {
+  function daysInMonth(year, month0) {
+    // Day 0 of the next month is the last day of this one
+    return new Date(year, month0 + 1, 0).getDate();
+  }
+
+  function constrain(form, prefix) {
+    var year = form.elements[prefix + '_year'];
+    var month = form.elements[prefix + '_month'];
+    var day = form.elements[prefix + '_day'];
+
+    if (!year || !month || !day) {
+      return;
+    }
+
+    function update() {
+      var days = daysInMonth(parseInt(year.value, 10), parseInt(month.value, 10));
+
+      for (var i = 0; i < day.options.length; i++) {
+        day.options[i].disabled = parseInt(day.options[i].value, 10) > days;
+      }
+
+      if (parseInt(day.value, 10) > days) {
+        day.value = String(days);
+      }
+    }
+
+    year.addEventListener('change', update);
+    month.addEventListener('change', update);
+    update();
+  }
+
+  document.addEventListener('DOMContentLoaded', function () {
+    var form = document.getElementById('event');
+
+    if (form) {
+      ['start', 'end', 'publish'].forEach(function (prefix) {
+        constrain(form, prefix);
+      });
+    }
+  });
+})();
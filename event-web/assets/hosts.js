@@ -0,0 +1,54 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Keep the hosts field in sync with the host checkboxes. The field is submitted as a single
+// comma-separated list of user IDs, since the form parser doesn't handle repeated fields.
+(function () {
+  function sync(form, field) {
+    var boxes = form.querySelectorAll('input.host');
+    var ids = [];
+
+    for (var i = 0; i < boxes.length; i++) {
+      if (boxes[i].checked) {
+        ids.push(boxes[i].value);
+      }
+    }
+
+    field.value = ids.join(',');
+  }
+
+  document.addEventListener('DOMContentLoaded', function () {
+    var form = document.getElementById('event');
+
+    if (!form || !form.elements['hosts']) {
+      return;
+    }
+
+    var field = form.elements['hosts'];
+    var boxes = form.querySelectorAll('input.host');
+
+    for (var i = 0; i < boxes.length; i++) {
+      boxes[i].addEventListener('change', function () {
+        sync(form, field);
+      });
+    }
+
+    form.addEventListener('submit', function () {
+      sync(form, field);
+    });
+  });
+})();
@@ -0,0 +1,83 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Fill in the start date from a suggested time. The end date moves with it, so the event keeps
+// the length it had before the suggestion was picked.
+(function () {
+  function field(form, prefix, part) {
+    return form.elements[prefix + '_' + part];
+  }
+
+  function read(form, prefix) {
+    return new Date(
+      parseInt(field(form, prefix, 'year').value, 10),
+      parseInt(field(form, prefix, 'month').value, 10),
+      parseInt(field(form, prefix, 'day').value, 10),
+      parseInt(field(form, prefix, 'hour').value, 10),
+      parseInt(field(form, prefix, 'minute').value, 10)
+    );
+  }
+
+  function write(form, prefix, date) {
+    var values = {
+      year: date.getFullYear(),
+      month: date.getMonth(),
+      day: date.getDate(),
+      hour: date.getHours(),
+      minute: date.getMinutes()
+    };
+
+    ['year', 'month', 'day', 'hour', 'minute'].forEach(function (part) {
+      var element = field(form, prefix, part);
+
+      element.value = String(values[part]);
+      // Let dates.js update the days offered for the new month
+      element.dispatchEvent(new Event('change'));
+    });
+  }
+
+  function pick(form, button) {
+    var length = Math.max(read(form, 'end') - read(form, 'start'), 0);
+
+    var start = new Date(
+      parseInt(button.getAttribute('data-year'), 10),
+      parseInt(button.getAttribute('data-month'), 10),
+      parseInt(button.getAttribute('data-day'), 10),
+      parseInt(button.getAttribute('data-hour'), 10),
+      0
+    );
+
+    write(form, 'start', start);
+    write(form, 'end', new Date(start.getTime() + length));
+  }
+
+  document.addEventListener('DOMContentLoaded', function () {
+    var form = document.getElementById('event');
+
+    if (!form) {
+      return;
+    }
+
+    var buttons = form.querySelectorAll('button.suggestion');
+
+    for (var i = 0; i < buttons.length; i++) {
+      buttons[i].addEventListener('click', function (e) {
+        pick(form, e.target);
+      });
+    }
+  });
+})();
@@ -0,0 +1,130 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Logging each request the Web UI answers, for finding slow pages and spotting scanners
+
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use actix_web::middleware::{Middleware, Response, Started};
+use actix_web::{HttpRequest, HttpResponse, Result};
+
+use proxy::client_ip;
+
+/// Path segments at least this long are hidden in the access log
+///
+/// Event links, channel tokens, and the Telegram webhook carry secrets in their paths, and those
+/// are much longer than any other part of a path.
+const SECRET_LENGTH: usize = 20;
+
+/// Requests that take longer than this many milliseconds are logged as warnings
+const SLOW_MILLIS: u64 = 1000;
+
+/// When the Web UI started answering a request
+struct RequestStart(Instant);
+
+/// `AccessLog` logs each request's client address, method, path, status, and how long it took
+///
+/// Requests from the trusted proxies are logged with the client address they forwarded.
+pub struct AccessLog {
+    trusted: Vec<IpAddr>,
+}
+
+impl AccessLog {
+    pub fn new(trusted: Vec<IpAddr>) -> Self {
+        AccessLog { trusted }
+    }
+}
+
+impl<S> Middleware<S> for AccessLog {
+    fn start(&self, req: &mut HttpRequest<S>) -> Result<Started> {
+        req.extensions_mut().insert(RequestStart(Instant::now()));
+
+        Ok(Started::Done)
+    }
+
+    fn response(&self, req: &mut HttpRequest<S>, resp: HttpResponse) -> Result<Response> {
+        let elapsed = req.extensions()
+            .get::<RequestStart>()
+            .map(|started| millis(started.0.elapsed()))
+            .unwrap_or(0);
+
+        let forwarded_for = req.headers()
+            .get("X-Forwarded-For")
+            .and_then(|forwarded_for| forwarded_for.to_str().ok());
+
+        let client = client_ip(req.peer_addr().map(|addr| addr.ip()), forwarded_for, &self.trusted)
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "-".to_owned());
+
+        let line = format!(
+            "{} {} {} {} {}ms",
+            client,
+            req.method(),
+            redact(req.path()),
+            resp.status().as_u16(),
+            elapsed
+        );
+
+        if elapsed > SLOW_MILLIS {
+            warn!("Slow request: {}", line);
+        } else {
+            info!("{}", line);
+        }
+
+        Ok(Response::Done(resp))
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos()) / 1_000_000
+}
+
+/// Hide the secrets in a request's path before it's logged
+fn redact(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if segment.len() >= SECRET_LENGTH {
+                "[hidden]"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{millis, redact};
+
+    #[test]
+    fn hides_secrets_in_logged_paths() {
+        let secret = "a".repeat(64);
+
+        assert_eq!(redact(&format!("/events/edit/{}", secret)), "/events/edit/[hidden]");
+        assert_eq!(redact("/directory"), "/directory");
+    }
+
+    #[test]
+    fn counts_whole_milliseconds() {
+        assert_eq!(millis(Duration::new(2, 345_678_901)), 2345);
+        assert_eq!(millis(Duration::from_millis(999)), 999);
+    }
+}
@@ -0,0 +1,244 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Laying out a channel's events on a month's calendar
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use event::{DirectoryEvent, MONTH_NAMES};
+
+/// The years a calendar can show, which keeps turning its pages well within the dates chrono can
+/// represent
+const YEARS: (i32, i32) = (1970, 9999);
+
+/// `CalendarMonth` is the month a calendar page shows
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CalendarMonth {
+    year: i32,
+    month: u32,
+}
+
+/// `CalendarDay` is a day of the month, along with the events happening on it
+#[derive(Clone, Debug)]
+pub struct CalendarDay<'a> {
+    day: u32,
+    events: Vec<&'a DirectoryEvent>,
+}
+
+impl CalendarMonth {
+    /// The month a date is in
+    pub fn containing(date: NaiveDate) -> Self {
+        CalendarMonth {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    /// Parse a month from a calendar's URL, like `2018-07`
+    ///
+    /// Years outside of 1970 to 9999 aren't calendars anyone is looking for, so they're refused
+    pub fn parse(month: &str) -> Option<Self> {
+        let mut parts = month.trim().splitn(2, '-');
+
+        let year = parts.next().and_then(|year| year.parse::<i32>().ok());
+        let month = parts.next().and_then(|month| month.parse::<u32>().ok());
+
+        match (year, month) {
+            (Some(year), Some(month)) if YEARS.0 <= year && year <= YEARS.1 => {
+                NaiveDate::from_ymd_opt(year, month, 1).map(CalendarMonth::containing)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn name(&self) -> &'static str {
+        MONTH_NAMES[self.month as usize - 1]
+    }
+
+    pub fn previous(&self) -> Self {
+        CalendarMonth::containing(self.first_day() - Duration::days(1))
+    }
+
+    pub fn next(&self) -> Self {
+        CalendarMonth::containing(self.first_day() + Duration::days(31))
+    }
+
+    /// The month as it's written in a calendar's URL
+    pub fn query(&self) -> String {
+        format!("{:04}-{:02}", self.year, self.month)
+    }
+
+    /// The date of a day in the month
+    pub fn date(&self, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(self.year, self.month, day)
+    }
+
+    fn first_day(&self) -> NaiveDate {
+        self.date(1)
+    }
+
+    fn days(&self) -> u32 {
+        let next = self.next().first_day();
+
+        next.signed_duration_since(self.first_day()).num_days() as u32
+    }
+
+    /// Lay the month out in weeks starting on Monday, with each day's events
+    ///
+    /// Days from the months before and after are `None`. Events are on every day they cover, in
+    /// the timezones they were made in, and events ending at midnight aren't on the next day.
+    pub fn weeks<'a>(&self, events: &'a [DirectoryEvent]) -> Vec<Vec<Option<CalendarDay<'a>>>> {
+        let leading = self.first_day().weekday().num_days_from_monday();
+
+        let mut days = (0..leading).map(|_| None).collect::<Vec<_>>();
+
+        days.extend((1..self.days() + 1).map(|day| {
+            let date = self.date(day);
+
+            let events = events
+                .iter()
+                .filter(|directory_event| {
+                    let event = directory_event.event();
+                    let start = event.start_date().naive_local().date();
+                    let end = (event.end_date() - Duration::seconds(1))
+                        .naive_local()
+                        .date();
+
+                    start <= date && date <= end.max(start)
+                })
+                .collect();
+
+            Some(CalendarDay { day, events })
+        }));
+
+        while days.len() % 7 != 0 {
+            days.push(None);
+        }
+
+        days.chunks(7).map(|week| week.to_vec()).collect()
+    }
+}
+
+impl<'a> CalendarDay<'a> {
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn events(&self) -> &[&'a DirectoryEvent] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+    use chrono_tz::Tz;
+
+    use super::CalendarMonth;
+    use event::{DirectoryEvent, Event, PublicEvent};
+
+    fn event(start: (u32, u32, u32), end: (u32, u32, u32)) -> DirectoryEvent {
+        let tz = Tz::US__Central;
+
+        let event = Event::from_parts(
+            "Picnic".to_owned(),
+            "".to_owned(),
+            tz.ymd(2018, start.0, start.1).and_hms(start.2, 0, 0),
+            tz.ymd(2018, end.0, end.1).and_hms(end.2, 0, 0),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+        );
+
+        DirectoryEvent::new("abc".to_owned(), PublicEvent::new(event, vec![]))
+    }
+
+    #[test]
+    fn reads_months_from_urls() {
+        let july = CalendarMonth::parse("2018-07").unwrap();
+
+        assert_eq!(july, CalendarMonth::containing(NaiveDate::from_ymd(2018, 7, 4)));
+        assert_eq!(july.query(), "2018-07");
+        assert_eq!(CalendarMonth::parse("2018-13"), None);
+        assert_eq!(CalendarMonth::parse("July"), None);
+    }
+
+    #[test]
+    fn refuses_years_out_of_range() {
+        assert_eq!(CalendarMonth::parse("262143-12"), None);
+        assert_eq!(CalendarMonth::parse("-262144-01"), None);
+        assert_eq!(CalendarMonth::parse("1969-12"), None);
+
+        let last = CalendarMonth::parse("9999-12").unwrap();
+
+        assert_eq!(last.next().query(), "10000-01");
+        assert_eq!(CalendarMonth::parse("1970-01").unwrap().previous().query(), "1969-12");
+    }
+
+    #[test]
+    fn turns_pages_across_years() {
+        let january = CalendarMonth::parse("2019-01").unwrap();
+
+        assert_eq!(january.previous().query(), "2018-12");
+        assert_eq!(january.previous().next(), january);
+        assert_eq!(CalendarMonth::parse("2018-01").unwrap().next().query(), "2018-02");
+    }
+
+    #[test]
+    fn starts_weeks_on_monday() {
+        // July 1st, 2018 was a Sunday, and July 31st a Tuesday
+        let weeks = CalendarMonth::parse("2018-07").unwrap().weeks(&[]);
+
+        assert_eq!(weeks.len(), 6);
+        assert!(weeks[0][..6].iter().all(|day| day.is_none()));
+        assert_eq!(weeks[0][6].as_ref().map(|day| day.day()), Some(1));
+        assert_eq!(weeks[5][1].as_ref().map(|day| day.day()), Some(31));
+        assert!(weeks[5][2..].iter().all(|day| day.is_none()));
+    }
+
+    #[test]
+    fn puts_events_on_every_day_they_cover() {
+        let events = [event((7, 3, 18), (7, 5, 0)), event((6, 30, 12), (7, 1, 12))];
+        let weeks = CalendarMonth::parse("2018-07").unwrap().weeks(&events);
+
+        let count = |week: usize, weekday: usize| {
+            weeks[week][weekday]
+                .as_ref()
+                .map(|day| day.events().len())
+                .unwrap_or(0)
+        };
+
+        // July 1st is still part of the picnic that started in June
+        assert_eq!(count(0, 6), 1);
+        assert_eq!(count(1, 0), 0);
+        assert_eq!(count(1, 1), 1);
+        assert_eq!(count(1, 2), 1);
+        // The event ends at midnight, so it isn't on the 5th
+        assert_eq!(count(1, 3), 0);
+    }
+}
@@ -15,16 +15,31 @@
  * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::cell::RefCell;
 use std::fmt;
 
 use actix_web::error::ResponseError;
+use actix_web::http::StatusCode;
 use actix_web::*;
 use failure::{Backtrace, Context, Fail};
+use http::header;
 use views::error;
+use FormSettings;
+
+thread_local! {
+    /// The settings for error pages, which are rendered without the app's state
+    static SETTINGS: RefCell<FormSettings> = RefCell::new(FormSettings::default());
+}
+
+/// Use these settings for the error pages rendered on the current thread
+pub fn install(settings: &FormSettings) {
+    SETTINGS.with(|current| *current.borrow_mut() = settings.clone());
+}
 
 #[derive(Debug)]
 pub struct FrontendError {
     context: Context<FrontendErrorKind>,
+    back: Option<String>,
 }
 
 impl fmt::Display for FrontendError {
@@ -33,6 +48,23 @@ impl fmt::Display for FrontendError {
     }
 }
 
+impl FrontendError {
+    pub fn kind(&self) -> FrontendErrorKind {
+        *self.context.get_context()
+    }
+
+    /// Link back to the form that was being used from the error page
+    pub fn back_to(mut self, url: String) -> Self {
+        self.back = Some(url);
+        self
+    }
+
+    /// The form the error page links back to, if the error came from one
+    pub fn back(&self) -> Option<&str> {
+        self.back.as_ref().map(|s| s.as_str())
+    }
+}
+
 impl Fail for FrontendError {
     fn cause(&self) -> Option<&Fail> {
         self.context.cause()
@@ -45,7 +77,11 @@ impl Fail for FrontendError {
 
 impl ResponseError for FrontendError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::BadRequest().body(error(self).into_string())
+        let page = SETTINGS.with(|settings| error(self, &settings.borrow()));
+
+        HttpResponse::build(self.kind().status())
+            .header(header::CONTENT_TYPE, "text/html")
+            .body(page.into_string())
     }
 }
 
@@ -73,27 +109,91 @@ pub enum FrontendErrorKind {
     BadHour,
     #[fail(display = "Invalid minute")]
     BadMinute,
+    #[fail(display = "Time is skipped or repeated by a daylight saving change")]
+    BadLocalTime,
     #[fail(display = "Invalid second")]
     BadSecond,
+    #[fail(display = "Invalid capacity")]
+    BadCapacity,
+    #[fail(display = "Invalid boost threshold")]
+    BadBoostThreshold,
+    #[fail(display = "Invalid date")]
+    BadDate,
+    #[fail(display = "An event has to last at least a minute")]
+    BadDuration,
+    #[fail(display = "Invalid coordinates")]
+    BadCoordinates,
+    #[fail(display = "Invalid image URL")]
+    BadImageUrl,
+    #[fail(display = "Announcements must be scheduled before the event starts")]
+    BadPublishAt,
+    #[fail(display = "RSVPs must close before the event starts")]
+    BadRsvpDeadline,
+    #[fail(display = "Invalid hosts")]
+    BadHosts,
+    #[fail(display = "An event needs at least one host")]
+    NoHosts,
+    #[fail(display = "Invalid API token")]
+    BadToken,
+    #[fail(display = "Tags can only have letters, numbers, and underscores")]
+    BadTag,
+    #[fail(display = "Error creating event")]
+    Creation,
     #[fail(display = "Could not find requested route")]
     NoRoute,
+    #[fail(display = "Could not find requested event")]
+    NoEvent,
     #[fail(display = "Could not interact with session")]
     Session,
     #[fail(display = "Message from backend canceled")]
     Canceled,
+    #[fail(display = "The bot is busy right now, please try again shortly")]
+    Busy,
+    #[fail(display = "Could not load the HTTPS certificate")]
+    Tls,
+    #[fail(display = "Could not listen for connections to the Web UI")]
+    Bind,
+}
+
+impl FrontendErrorKind {
+    /// The status of the error page for this kind of error
+    ///
+    /// Problems with what was sent are 4xx errors, and problems with the Web UI or the bot are 5xx
+    /// errors
+    pub fn status(&self) -> StatusCode {
+        match *self {
+            FrontendErrorKind::NoEvent | FrontendErrorKind::NoRoute => StatusCode::NOT_FOUND,
+            FrontendErrorKind::Verification => StatusCode::FORBIDDEN,
+            FrontendErrorKind::BadToken => StatusCode::UNAUTHORIZED,
+            FrontendErrorKind::Busy => StatusCode::SERVICE_UNAVAILABLE,
+            FrontendErrorKind::Generation
+            | FrontendErrorKind::Body
+            | FrontendErrorKind::Response
+            | FrontendErrorKind::Creation
+            | FrontendErrorKind::Session
+            | FrontendErrorKind::Canceled
+            | FrontendErrorKind::Tls
+            | FrontendErrorKind::Bind => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
 }
 
 impl From<FrontendErrorKind> for FrontendError {
     fn from(e: FrontendErrorKind) -> Self {
         FrontendError {
             context: Context::new(e),
+            back: None,
         }
     }
 }
 
 impl From<Context<FrontendErrorKind>> for FrontendError {
     fn from(e: Context<FrontendErrorKind>) -> Self {
-        FrontendError { context: e }
+        FrontendError {
+            context: e,
+            back: None,
+        }
     }
 }
 
@@ -102,3 +202,42 @@ impl From<Context<FrontendErrorKind>> for FrontendError {
 pub struct MissingField {
     pub field: &'static str,
 }
+
+/// A day that doesn't exist in the chosen month, like February 30th
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[fail(display = "{} {} only has {} days", month, year, days)]
+pub struct DayOutOfRange {
+    pub month: &'static str,
+    pub year: i32,
+    pub days: u32,
+}
+
+/// A problem with one part of the web form, shown next to that part when the form is shown again
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[fail(display = "{}", message)]
+pub struct InvalidField {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// A time that's skipped when the clocks go forward for daylight saving time
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[fail(
+    display = "{} doesn't happen in {}, because the clocks skip it for daylight saving time. Please pick a time before or after the change.",
+    time, timezone
+)]
+pub struct SkippedTime {
+    pub time: String,
+    pub timezone: &'static str,
+}
+
+/// A time that happens twice when the clocks go back at the end of daylight saving time
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[fail(
+    display = "{} happens twice in {}, because the clocks go back for daylight saving time. Please pick a time that only happens once.",
+    time, timezone
+)]
+pub struct RepeatedTime {
+    pub time: String,
+    pub timezone: &'static str,
+}
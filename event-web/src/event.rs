@@ -15,14 +15,45 @@
  * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::cmp;
 use std::str::FromStr;
 
 use chrono::offset::Utc;
-use chrono::{DateTime, Datelike, Timelike};
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike};
 use chrono_tz::Tz;
 use failure::{Fail, ResultExt};
 
-use error::{FrontendError, FrontendErrorKind, MissingField};
+use error::{
+    DayOutOfRange, FrontendError, FrontendErrorKind, InvalidField, MissingField, RepeatedTime,
+    SkippedTime,
+};
+
+/// The months offered in the form, in order
+pub const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+pub const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
 
 #[derive(Clone, Debug)]
 pub struct Event {
@@ -30,6 +61,16 @@ pub struct Event {
     description: String,
     start_date: DateTime<Tz>,
     end_date: DateTime<Tz>,
+    publish_at: Option<DateTime<Tz>>,
+    capacity: Option<i32>,
+    boost_threshold: Option<i32>,
+    location: Option<String>,
+    location_hint: Option<String>,
+    coordinates: Option<(f64, f64)>,
+    image_url: Option<String>,
+    rsvp_deadline: Option<DateTime<Tz>>,
+    members_only: bool,
+    unlisted: bool,
 }
 
 impl Event {
@@ -38,12 +79,32 @@ impl Event {
         description: String,
         start_date: DateTime<Tz>,
         end_date: DateTime<Tz>,
+        publish_at: Option<DateTime<Tz>>,
+        capacity: Option<i32>,
+        boost_threshold: Option<i32>,
+        location: Option<String>,
+        location_hint: Option<String>,
+        coordinates: Option<(f64, f64)>,
+        image_url: Option<String>,
+        rsvp_deadline: Option<DateTime<Tz>>,
+        members_only: bool,
+        unlisted: bool,
     ) -> Self {
         Event {
             title,
             description,
             start_date,
             end_date,
+            publish_at,
+            capacity,
+            boost_threshold,
+            location,
+            location_hint,
+            coordinates,
+            image_url,
+            rsvp_deadline,
+            members_only,
+            unlisted,
         }
     }
     pub fn from_option(option_event: OptionEvent) -> Result<Self, FrontendError> {
@@ -65,6 +126,334 @@ impl Event {
     pub fn end_date(&self) -> DateTime<Tz> {
         self.end_date
     }
+
+    pub fn publish_at(&self) -> Option<DateTime<Tz>> {
+        self.publish_at
+    }
+
+    pub fn capacity(&self) -> Option<i32> {
+        self.capacity
+    }
+
+    /// The number of RSVPs below which the event is announced again two days before it starts,
+    /// if the event overrides its channel's setting
+    pub fn boost_threshold(&self) -> Option<i32> {
+        self.boost_threshold
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn location_hint(&self) -> Option<&str> {
+        self.location_hint.as_ref().map(|s| s.as_str())
+    }
+
+    /// The latitude and longitude of the location, if they were given
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        self.coordinates
+    }
+
+    /// A link to the event's coordinates on OpenStreetMap
+    pub fn map_url(&self) -> Option<String> {
+        self.coordinates.map(|(latitude, longitude)| map_url(latitude, longitude))
+    }
+
+    /// The picture to post with the event's announcement, if one was given
+    pub fn image_url(&self) -> Option<&str> {
+        self.image_url.as_ref().map(|s| s.as_str())
+    }
+
+    /// When RSVPs close, if they close before the event starts
+    pub fn rsvp_deadline(&self) -> Option<DateTime<Tz>> {
+        self.rsvp_deadline
+    }
+
+    /// Whether the RSVP deadline has passed
+    pub fn rsvps_closed(&self) -> bool {
+        self.rsvp_deadline
+            .map(|deadline| deadline.with_timezone(&Utc) <= Utc::now())
+            .unwrap_or(false)
+    }
+
+    pub fn members_only(&self) -> bool {
+        self.members_only
+    }
+
+    /// Whether the event is only announced in its channel, and left off its public page, the
+    /// directory, and search
+    pub fn unlisted(&self) -> bool {
+        self.unlisted
+    }
+
+    /// The hashtags in the title and description, lowercased and without the `#`
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+
+        for text in &[&self.title, &self.description] {
+            for word in text.split('#').skip(1) {
+                let tag = word.chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect::<String>()
+                    .to_lowercase();
+
+                if tag.len() > 0 && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        tags
+    }
+}
+
+/// `PublicEvent` is an event as shown on its public page, along with the usernames of its hosts
+///
+/// Only the location that can be shown to anyone should be included in the event
+#[derive(Clone, Debug)]
+pub struct PublicEvent {
+    event: Event,
+    hosts: Vec<String>,
+}
+
+impl PublicEvent {
+    pub fn new(event: Event, hosts: Vec<String>) -> Self {
+        PublicEvent { event, hosts }
+    }
+
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+}
+
+/// `DirectoryChannel` is a channel listed in the directory, along with its upcoming events
+///
+/// `username` is the channel's public username, if it has one, and is used to link to the channel
+#[derive(Clone, Debug)]
+pub struct DirectoryChannel {
+    title: String,
+    username: Option<String>,
+    events: Vec<DirectoryEvent>,
+}
+
+impl DirectoryChannel {
+    pub fn new(title: String, username: Option<String>, events: Vec<DirectoryEvent>) -> Self {
+        DirectoryChannel {
+            title,
+            username,
+            events,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// A link to the channel on Telegram, if it has a public username
+    pub fn url(&self) -> Option<String> {
+        self.username
+            .as_ref()
+            .map(|username| format!("https://t.me/{}", username))
+    }
+
+    pub fn events(&self) -> &[DirectoryEvent] {
+        &self.events
+    }
+}
+
+/// `SuggestedTime` is a weekday and hour when a channel's past events drew the most people
+///
+/// `weekday` counts days from Monday, and `hour` is in the timezone the suggestions were asked for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SuggestedTime {
+    weekday: u32,
+    hour: u32,
+    average_attendance: f64,
+}
+
+impl SuggestedTime {
+    pub fn new(weekday: u32, hour: u32, average_attendance: f64) -> Self {
+        SuggestedTime {
+            weekday,
+            hour,
+            average_attendance,
+        }
+    }
+
+    pub fn weekday(&self) -> u32 {
+        self.weekday
+    }
+
+    pub fn hour(&self) -> u32 {
+        self.hour
+    }
+
+    /// The average number of people who went to events at this time
+    pub fn average_attendance(&self) -> f64 {
+        self.average_attendance
+    }
+}
+
+/// `DirectoryEvent` is an event listed in the directory, which links to its public page
+#[derive(Clone, Debug)]
+pub struct DirectoryEvent {
+    public_id: String,
+    public_event: PublicEvent,
+}
+
+impl DirectoryEvent {
+    pub fn new(public_id: String, public_event: PublicEvent) -> Self {
+        DirectoryEvent {
+            public_id,
+            public_event,
+        }
+    }
+
+    pub fn public_id(&self) -> &str {
+        &self.public_id
+    }
+
+    pub fn event(&self) -> &Event {
+        self.public_event.event()
+    }
+}
+
+/// `Host` is a user who can be picked as one of an event's hosts
+#[derive(Clone, Debug)]
+pub struct Host {
+    id: i32,
+    username: String,
+}
+
+impl Host {
+    pub fn new(id: i32, username: String) -> Self {
+        Host { id, username }
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+/// `StoredEvent` is an event as it's currently saved, shown above the form while it's being edited
+///
+/// `members` are the users in the event's chats, who can be picked as hosts. `updated_at` is
+/// `None` if the event hasn't been edited since it was created
+#[derive(Clone, Debug)]
+pub struct StoredEvent {
+    event: Event,
+    hosts: Vec<Host>,
+    members: Vec<Host>,
+    channel: Option<String>,
+    updated_at: Option<DateTime<Tz>>,
+    cancelled: bool,
+}
+
+impl StoredEvent {
+    pub fn new(
+        event: Event,
+        hosts: Vec<Host>,
+        members: Vec<Host>,
+        channel: Option<String>,
+        updated_at: Option<DateTime<Tz>>,
+        cancelled: bool,
+    ) -> Self {
+        StoredEvent {
+            event,
+            hosts,
+            members,
+            channel,
+            updated_at,
+            cancelled,
+        }
+    }
+
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    pub fn hosts(&self) -> &[Host] {
+        &self.hosts
+    }
+
+    /// The users who can be picked as hosts, including the current hosts
+    pub fn members(&self) -> Vec<&Host> {
+        let mut members: Vec<&Host> = self.hosts.iter().collect();
+
+        for member in self.members.iter() {
+            if !self.hosts.iter().any(|host| host.id == member.id) {
+                members.push(member);
+            }
+        }
+
+        members.sort_by(|a, b| a.username.to_lowercase().cmp(&b.username.to_lowercase()));
+        members
+    }
+
+    /// The title of the channel the event is announced in, if the bot knows it
+    pub fn channel(&self) -> Option<&str> {
+        self.channel.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Tz>> {
+        self.updated_at
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+/// `ApiEvent` is an event as returned from the API
+///
+/// Dates are RFC 3339 timestamps in the event's timezone
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiEvent {
+    id: i32,
+    public_id: String,
+    title: String,
+    description: String,
+    start_date: String,
+    end_date: String,
+    timezone: String,
+    location: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    image_url: Option<String>,
+    capacity: Option<i32>,
+    rsvp_deadline: Option<String>,
+    hosts: Vec<String>,
+}
+
+impl ApiEvent {
+    pub fn new(id: i32, public_id: String, public_event: PublicEvent) -> Self {
+        let PublicEvent { event, hosts } = public_event;
+
+        ApiEvent {
+            id,
+            public_id,
+            timezone: event.start_date.timezone().name().to_owned(),
+            start_date: event.start_date.to_rfc3339(),
+            end_date: event.end_date.to_rfc3339(),
+            title: event.title,
+            description: event.description,
+            location: event.location,
+            latitude: event.coordinates.map(|(latitude, _)| latitude),
+            longitude: event.coordinates.map(|(_, longitude)| longitude),
+            image_url: event.image_url,
+            capacity: event.capacity,
+            rsvp_deadline: event.rsvp_deadline.map(|deadline| deadline.to_rfc3339()),
+            hosts,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -81,74 +470,181 @@ pub struct OptionEvent {
     end_day: Option<u32>,
     end_hour: Option<u32>,
     end_minute: Option<u32>,
+    use_duration: Option<bool>,
+    duration_hours: Option<u32>,
+    duration_minutes: Option<u32>,
     timezone: Option<String>,
+    announce_later: Option<bool>,
+    publish_year: Option<i32>,
+    publish_month: Option<u32>,
+    publish_day: Option<u32>,
+    publish_hour: Option<u32>,
+    publish_minute: Option<u32>,
+    close_rsvps: Option<bool>,
+    rsvp_year: Option<i32>,
+    rsvp_month: Option<u32>,
+    rsvp_day: Option<u32>,
+    rsvp_hour: Option<u32>,
+    rsvp_minute: Option<u32>,
+    capacity: Option<String>,
+    boost_threshold: Option<String>,
+    location: Option<String>,
+    location_hint: Option<String>,
+    latitude: Option<String>,
+    longitude: Option<String>,
+    image_url: Option<String>,
+    members_only: Option<bool>,
+    unlisted: Option<bool>,
+    hosts: Option<String>,
+    csrf_token: Option<String>,
 }
 
 impl OptionEvent {
-    pub fn missing_keys(&self) -> Vec<&'static str> {
-        let mut v = Vec::new();
-
-        if self.title.is_none() {
-            v.push("title");
-        } else if let Some(ref title) = self.title {
-            if title.trim().len() == 0 {
-                v.push("title");
-            }
-        }
+    /// The CSRF token the form was rendered with
+    pub fn csrf_token(&self) -> Option<&str> {
+        self.csrf_token.as_ref().map(|s| s.as_str())
+    }
 
-        if self.description.is_none() {
-            v.push("description");
-        } else if let Some(ref description) = self.description {
-            if description.trim().len() == 0 {
-                v.push("description");
-            }
-        }
+    /// The comma-separated host IDs, as submitted
+    pub fn hosts(&self) -> Option<&str> {
+        self.hosts.as_ref().map(|s| s.as_str())
+    }
 
-        if self.start_year.is_none() {
-            v.push("start year");
-        }
+    /// The IDs of the hosts picked in the form, or `None` if the form didn't include a host picker
+    ///
+    /// The picker submits the IDs as a single comma-separated field
+    pub fn host_ids(&self) -> Result<Option<Vec<i32>>, FrontendError> {
+        let hosts = match self.hosts {
+            Some(ref hosts) => hosts,
+            None => return Ok(None),
+        };
 
-        if self.start_month.is_none() {
-            v.push("start month");
-        }
+        let ids = hosts
+            .split(',')
+            .map(|id| id.trim())
+            .filter(|id| id.len() > 0)
+            .map(|id| id.parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .context(FrontendErrorKind::BadHosts)
+            .map_err(|e| in_field("hosts")(e.into()))?;
 
-        if self.start_day.is_none() {
-            v.push("start day");
+        if ids.is_empty() {
+            return Err(in_field("hosts")(FrontendErrorKind::NoHosts.into()));
         }
 
-        if self.start_hour.is_none() {
-            v.push("start hour");
-        }
+        Ok(Some(ids))
+    }
 
-        if self.start_minute.is_none() {
-            v.push("start minute");
-        }
+    /// The fields left out of the submitted form, keyed by the part of the form they're shown in
+    pub fn field_errors(&self) -> FieldErrors {
+        let mut errors = FieldErrors::default();
 
-        if self.end_year.is_none() {
-            v.push("end year");
+        if self.title.as_ref().map(|t| t.trim().len() == 0).unwrap_or(true) {
+            errors.add("title", "Give the event a title".to_owned());
         }
 
-        if self.end_month.is_none() {
-            v.push("end month");
+        if self.description
+            .as_ref()
+            .map(|d| d.trim().len() == 0)
+            .unwrap_or(true)
+        {
+            errors.add("description", "Describe the event".to_owned());
         }
 
-        if self.end_day.is_none() {
-            v.push("end day");
+        missing_date_parts(
+            &mut errors,
+            "start",
+            (
+                self.start_year.is_none(),
+                self.start_month.is_none(),
+                self.start_day.is_none(),
+                self.start_hour.is_none(),
+                self.start_minute.is_none(),
+            ),
+        );
+
+        if self.use_duration.unwrap_or(false) {
+            if self.duration_hours.is_none() {
+                errors.add("duration", "Pick how many hours the event lasts".to_owned());
+            }
+
+            if self.duration_minutes.is_none() {
+                errors.add("duration", "Pick how many minutes the event lasts".to_owned());
+            }
+        } else {
+            missing_date_parts(
+                &mut errors,
+                "end",
+                (
+                    self.end_year.is_none(),
+                    self.end_month.is_none(),
+                    self.end_day.is_none(),
+                    self.end_hour.is_none(),
+                    self.end_minute.is_none(),
+                ),
+            );
         }
 
-        if self.end_hour.is_none() {
-            v.push("end hour");
+        if self.timezone.is_none() {
+            errors.add("timezone", "Pick a timezone".to_owned());
         }
 
-        if self.end_minute.is_none() {
-            v.push("end minute");
+        if self.announce_later.unwrap_or(false) {
+            missing_date_parts(
+                &mut errors,
+                "publish",
+                (
+                    self.publish_year.is_none(),
+                    self.publish_month.is_none(),
+                    self.publish_day.is_none(),
+                    self.publish_hour.is_none(),
+                    self.publish_minute.is_none(),
+                ),
+            );
         }
 
-        if self.timezone.is_none() {
-            v.push("timezone");
+        if self.close_rsvps.unwrap_or(false) {
+            missing_date_parts(
+                &mut errors,
+                "rsvp",
+                (
+                    self.rsvp_year.is_none(),
+                    self.rsvp_month.is_none(),
+                    self.rsvp_day.is_none(),
+                    self.rsvp_hour.is_none(),
+                    self.rsvp_minute.is_none(),
+                ),
+            );
         }
 
-        v
+        errors
+    }
+}
+
+/// Problems with a submitted form, each shown next to the part of the form it's about
+///
+/// Parts are named after their fields, like `title`, or after the prefix of a group of date fields,
+/// like `start`.
+#[derive(Clone, Debug, Default)]
+pub struct FieldErrors {
+    errors: Vec<(&'static str, String)>,
+}
+
+impl FieldErrors {
+    pub fn add(&mut self, field: &'static str, message: String) {
+        self.errors.push((field, message));
+    }
+
+    /// The problems with one part of the form
+    pub fn get<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.errors
+            .iter()
+            .filter(move |&&(f, _)| f == field)
+            .map(|&(_, ref message)| message.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
     }
 }
 
@@ -165,7 +661,32 @@ pub struct CreateEvent {
     pub end_day: u32,
     pub end_hour: u32,
     pub end_minute: u32,
+    /// Whether the end date is worked out from the start date and a duration, rather than picked
+    pub use_duration: bool,
+    pub duration_hours: u32,
+    pub duration_minutes: u32,
     pub timezone: String,
+    pub announce_later: bool,
+    pub publish_year: i32,
+    pub publish_month: u32,
+    pub publish_day: u32,
+    pub publish_hour: u32,
+    pub publish_minute: u32,
+    pub close_rsvps: bool,
+    pub rsvp_year: i32,
+    pub rsvp_month: u32,
+    pub rsvp_day: u32,
+    pub rsvp_hour: u32,
+    pub rsvp_minute: u32,
+    pub capacity: String,
+    pub boost_threshold: String,
+    pub location: String,
+    pub location_hint: String,
+    pub latitude: String,
+    pub longitude: String,
+    pub image_url: String,
+    pub members_only: bool,
+    pub unlisted: bool,
 }
 
 impl CreateEvent {
@@ -183,7 +704,31 @@ impl CreateEvent {
             end_day: date.day() as u32,
             end_hour: date.hour() as u32,
             end_minute: date.minute() as u32,
+            use_duration: false,
+            duration_hours: 1,
+            duration_minutes: 0,
             timezone: date.timezone().name().to_owned(),
+            announce_later: false,
+            publish_year: date.year(),
+            publish_month: date.month() - 1,
+            publish_day: date.day() as u32,
+            publish_hour: date.hour() as u32,
+            publish_minute: date.minute() as u32,
+            close_rsvps: false,
+            rsvp_year: date.year(),
+            rsvp_month: date.month() - 1,
+            rsvp_day: date.day() as u32,
+            rsvp_hour: date.hour() as u32,
+            rsvp_minute: date.minute() as u32,
+            capacity: "".to_owned(),
+            boost_threshold: "".to_owned(),
+            location: "".to_owned(),
+            location_hint: "".to_owned(),
+            latitude: "".to_owned(),
+            longitude: "".to_owned(),
+            image_url: "".to_owned(),
+            members_only: false,
+            unlisted: false,
         }
     }
 
@@ -236,9 +781,91 @@ impl CreateEvent {
             self.end_minute = end_minute;
         }
 
+        if let Some(duration_hours) = option_event.duration_hours {
+            self.duration_hours = duration_hours;
+        }
+
+        if let Some(duration_minutes) = option_event.duration_minutes {
+            self.duration_minutes = duration_minutes;
+        }
+
         if let Some(ref timezone) = option_event.timezone {
             self.timezone = timezone.to_owned();
         }
+
+        self.use_duration = option_event.use_duration.unwrap_or(false);
+        self.announce_later = option_event.announce_later.unwrap_or(false);
+        self.close_rsvps = option_event.close_rsvps.unwrap_or(false);
+        self.members_only = option_event.members_only.unwrap_or(false);
+        self.unlisted = option_event.unlisted.unwrap_or(false);
+
+        if let Some(publish_year) = option_event.publish_year {
+            self.publish_year = publish_year;
+        }
+
+        if let Some(publish_month) = option_event.publish_month {
+            self.publish_month = publish_month;
+        }
+
+        if let Some(publish_day) = option_event.publish_day {
+            self.publish_day = publish_day;
+        }
+
+        if let Some(publish_hour) = option_event.publish_hour {
+            self.publish_hour = publish_hour;
+        }
+
+        if let Some(publish_minute) = option_event.publish_minute {
+            self.publish_minute = publish_minute;
+        }
+
+        if let Some(rsvp_year) = option_event.rsvp_year {
+            self.rsvp_year = rsvp_year;
+        }
+
+        if let Some(rsvp_month) = option_event.rsvp_month {
+            self.rsvp_month = rsvp_month;
+        }
+
+        if let Some(rsvp_day) = option_event.rsvp_day {
+            self.rsvp_day = rsvp_day;
+        }
+
+        if let Some(rsvp_hour) = option_event.rsvp_hour {
+            self.rsvp_hour = rsvp_hour;
+        }
+
+        if let Some(rsvp_minute) = option_event.rsvp_minute {
+            self.rsvp_minute = rsvp_minute;
+        }
+
+        if let Some(ref capacity) = option_event.capacity {
+            self.capacity = capacity.to_owned();
+        }
+
+        if let Some(ref boost_threshold) = option_event.boost_threshold {
+            self.boost_threshold = boost_threshold.to_owned();
+        }
+
+        if let Some(ref location) = option_event.location {
+            self.location = location.to_owned();
+        }
+
+        if let Some(ref location_hint) = option_event.location_hint {
+            self.location_hint = location_hint.to_owned();
+        }
+
+        if let Some(ref latitude) = option_event.latitude {
+            self.latitude = latitude.to_owned();
+        }
+
+        if let Some(ref longitude) = option_event.longitude {
+            self.longitude = longitude.to_owned();
+        }
+
+        if let Some(ref image_url) = option_event.image_url {
+            self.image_url = image_url.to_owned();
+        }
     }
 
     fn from_option(option_event: OptionEvent) -> Result<Self, FrontendError> {
@@ -252,12 +879,58 @@ impl CreateEvent {
         let start_day = maybe_field(option_event.start_day, "start_day")?;
         let start_hour = maybe_field(option_event.start_hour, "start_hour")?;
         let start_minute = maybe_field(option_event.start_minute, "start_minute")?;
-        let end_year = maybe_field(option_event.end_year, "end_year")?;
-        let end_month = maybe_field(option_event.end_month, "end_month")?;
-        let end_day = maybe_field(option_event.end_day, "end_day")?;
-        let end_hour = maybe_field(option_event.end_hour, "end_hour")?;
-        let end_minute = maybe_field(option_event.end_minute, "end_minute")?;
+        let use_duration = option_event.use_duration.unwrap_or(false);
+
+        // The end date isn't needed when it's worked out from a duration
+        let (end_year, end_month, end_day, end_hour, end_minute) = if use_duration {
+            (start_year, start_month, start_day, start_hour, start_minute)
+        } else {
+            (
+                maybe_field(option_event.end_year, "end_year")?,
+                maybe_field(option_event.end_month, "end_month")?,
+                maybe_field(option_event.end_day, "end_day")?,
+                maybe_field(option_event.end_hour, "end_hour")?,
+                maybe_field(option_event.end_minute, "end_minute")?,
+            )
+        };
+
+        let (duration_hours, duration_minutes) = if use_duration {
+            (
+                maybe_field(option_event.duration_hours, "duration_hours")?,
+                maybe_field(option_event.duration_minutes, "duration_minutes")?,
+            )
+        } else {
+            (0, 0)
+        };
         let timezone = maybe_field(option_event.timezone, "timezone")?;
+        let announce_later = option_event.announce_later.unwrap_or(false);
+
+        let (publish_year, publish_month, publish_day, publish_hour, publish_minute) =
+            if announce_later {
+                (
+                    maybe_field(option_event.publish_year, "publish_year")?,
+                    maybe_field(option_event.publish_month, "publish_month")?,
+                    maybe_field(option_event.publish_day, "publish_day")?,
+                    maybe_field(option_event.publish_hour, "publish_hour")?,
+                    maybe_field(option_event.publish_minute, "publish_minute")?,
+                )
+            } else {
+                (start_year, start_month, start_day, start_hour, start_minute)
+            };
+
+        let close_rsvps = option_event.close_rsvps.unwrap_or(false);
+
+        let (rsvp_year, rsvp_month, rsvp_day, rsvp_hour, rsvp_minute) = if close_rsvps {
+            (
+                maybe_field(option_event.rsvp_year, "rsvp_year")?,
+                maybe_field(option_event.rsvp_month, "rsvp_month")?,
+                maybe_field(option_event.rsvp_day, "rsvp_day")?,
+                maybe_field(option_event.rsvp_hour, "rsvp_hour")?,
+                maybe_field(option_event.rsvp_minute, "rsvp_minute")?,
+            )
+        } else {
+            (start_year, start_month, start_day, start_hour, start_minute)
+        };
 
         Ok(CreateEvent {
             title,
@@ -272,55 +945,161 @@ impl CreateEvent {
             end_day,
             end_hour,
             end_minute,
+            use_duration,
+            duration_hours,
+            duration_minutes,
             timezone,
+            announce_later,
+            publish_year,
+            publish_month,
+            publish_day,
+            publish_hour,
+            publish_minute,
+            close_rsvps,
+            rsvp_year,
+            rsvp_month,
+            rsvp_day,
+            rsvp_hour,
+            rsvp_minute,
+            capacity: option_event.capacity.unwrap_or_default(),
+            boost_threshold: option_event.boost_threshold.unwrap_or_default(),
+            location: option_event.location.unwrap_or_default(),
+            location_hint: option_event.location_hint.unwrap_or_default(),
+            latitude: option_event.latitude.unwrap_or_default(),
+            longitude: option_event.longitude.unwrap_or_default(),
+            image_url: option_event.image_url.unwrap_or_default(),
+            members_only: option_event.members_only.unwrap_or(false),
+            unlisted: option_event.unlisted.unwrap_or(false),
         })
     }
 
     fn try_to_event(self) -> Result<Event, FrontendError> {
-        let timezone = Tz::from_str(&self.timezone).map_err(|_| FrontendErrorKind::BadTimeZone)?;
-
-        let now = Utc::now();
-
-        let datetime = now.with_timezone(&timezone);
-        let start_datetime = datetime
-            .with_year(self.start_year)
-            .ok_or(FrontendErrorKind::BadYear)?
-            .with_month0(self.start_month)
-            .ok_or(FrontendErrorKind::BadMonth)?
-            .with_day(self.start_day)
-            .ok_or(FrontendErrorKind::BadDay)?
-            .with_hour(self.start_hour)
-            .ok_or(FrontendErrorKind::BadHour)?
-            .with_minute(self.start_minute)
-            .ok_or(FrontendErrorKind::BadMinute)?
-            .with_second(0)
-            .ok_or(FrontendErrorKind::BadSecond)?;
-
-        let end_datetime = datetime
-            .with_year(self.end_year)
-            .ok_or(FrontendErrorKind::BadYear)?
-            .with_month0(self.end_month)
-            .ok_or(FrontendErrorKind::BadMonth)?
-            .with_day(self.end_day)
-            .ok_or(FrontendErrorKind::BadDay)?
-            .with_hour(self.end_hour)
-            .ok_or(FrontendErrorKind::BadHour)?
-            .with_minute(self.end_minute)
-            .ok_or(FrontendErrorKind::BadMinute)?
-            .with_second(0)
-            .ok_or(FrontendErrorKind::BadSecond)?;
+        let timezone = Tz::from_str(&self.timezone)
+            .map_err(|_| in_field("timezone")(FrontendErrorKind::BadTimeZone.into()))?;
+
+        let start_datetime = form_date(
+            &timezone,
+            self.start_year,
+            self.start_month,
+            self.start_day,
+            self.start_hour,
+            self.start_minute,
+        ).map_err(in_field("start"))?;
+
+        let end_datetime = if self.use_duration {
+            start_datetime
+                + form_duration(self.duration_hours, self.duration_minutes)
+                    .map_err(in_field("duration"))?
+        } else {
+            form_date(
+                &timezone,
+                self.end_year,
+                self.end_month,
+                self.end_day,
+                self.end_hour,
+                self.end_minute,
+            ).map_err(in_field("end"))?
+        };
+
+        let publish_at = if self.announce_later {
+            Some(form_date(
+                &timezone,
+                self.publish_year,
+                self.publish_month,
+                self.publish_day,
+                self.publish_hour,
+                self.publish_minute,
+            ).map_err(in_field("publish"))?)
+        } else {
+            None
+        };
+
+        check_publish_at(publish_at, start_datetime).map_err(in_field("publish"))?;
+
+        let rsvp_deadline = if self.close_rsvps {
+            Some(form_date(
+                &timezone,
+                self.rsvp_year,
+                self.rsvp_month,
+                self.rsvp_day,
+                self.rsvp_hour,
+                self.rsvp_minute,
+            ).map_err(in_field("rsvp"))?)
+        } else {
+            None
+        };
+
+        check_rsvp_deadline(rsvp_deadline, start_datetime).map_err(in_field("rsvp"))?;
+
+        // An empty capacity means the event has no limit
+        let capacity = if self.capacity.trim().len() == 0 {
+            None
+        } else {
+            match self.capacity.trim().parse::<i32>() {
+                Ok(capacity) if capacity > 0 => Some(capacity),
+                _ => return Err(in_field("capacity")(FrontendErrorKind::BadCapacity.into())),
+            }
+        };
+
+        // An empty threshold means the channel's setting is used. Zero turns boosts off.
+        let boost_threshold = if self.boost_threshold.trim().len() == 0 {
+            None
+        } else {
+            match self.boost_threshold.trim().parse::<i32>() {
+                Ok(boost_threshold) if boost_threshold >= 0 => Some(boost_threshold),
+                _ => {
+                    let e = FrontendErrorKind::BadBoostThreshold.into();
+                    return Err(in_field("boost_threshold")(e));
+                }
+            }
+        };
+
+        let location = maybe_string(self.location);
+
+        // A hint is only useful when there's a location to hide
+        let location_hint = if location.is_some() {
+            maybe_string(self.location_hint)
+        } else {
+            None
+        };
+
+        // Coordinates pinpoint the location, so they're only kept when there is one
+        let coordinates = if location.is_some() {
+            form_coordinates(&self.latitude, &self.longitude).map_err(in_field("coordinates"))?
+        } else {
+            None
+        };
+
+        let image_url = match maybe_string(self.image_url) {
+            Some(image_url) => Some(check_image_url(image_url).map_err(in_field("image_url"))?),
+            None => None,
+        };
 
         Ok(Event {
             title: self.title,
             description: self.description,
             start_date: start_datetime,
             end_date: end_datetime,
+            publish_at,
+            capacity,
+            boost_threshold,
+            location,
+            location_hint,
+            coordinates,
+            image_url,
+            rsvp_deadline,
+            members_only: self.members_only,
+            unlisted: self.unlisted,
         })
     }
 }
 
 impl From<Event> for CreateEvent {
     fn from(e: Event) -> Self {
+        let publish_date = e.publish_at.unwrap_or(e.start_date);
+        let rsvp_date = e.rsvp_deadline.unwrap_or(e.start_date);
+        let length = cmp::max(e.end_date.signed_duration_since(e.start_date).num_minutes(), 0);
+
         CreateEvent {
             title: e.title,
             description: e.description,
@@ -334,17 +1113,381 @@ impl From<Event> for CreateEvent {
             end_day: e.end_date.day(),
             end_hour: e.end_date.hour(),
             end_minute: e.end_date.minute(),
+            use_duration: false,
+            duration_hours: cmp::min(length / 60, 23) as u32,
+            duration_minutes: (length % 60) as u32,
             timezone: e.end_date.timezone().name().to_owned(),
+            announce_later: e.publish_at.is_some(),
+            publish_year: publish_date.year(),
+            publish_month: publish_date.month0(),
+            publish_day: publish_date.day(),
+            publish_hour: publish_date.hour(),
+            publish_minute: publish_date.minute(),
+            close_rsvps: e.rsvp_deadline.is_some(),
+            rsvp_year: rsvp_date.year(),
+            rsvp_month: rsvp_date.month0(),
+            rsvp_day: rsvp_date.day(),
+            rsvp_hour: rsvp_date.hour(),
+            rsvp_minute: rsvp_date.minute(),
+            capacity: e.capacity
+                .map(|capacity| capacity.to_string())
+                .unwrap_or_default(),
+            boost_threshold: e.boost_threshold
+                .map(|boost_threshold| boost_threshold.to_string())
+                .unwrap_or_default(),
+            location: e.location.unwrap_or_default(),
+            location_hint: e.location_hint.unwrap_or_default(),
+            latitude: e.coordinates
+                .map(|(latitude, _)| latitude.to_string())
+                .unwrap_or_default(),
+            longitude: e.coordinates
+                .map(|(_, longitude)| longitude.to_string())
+                .unwrap_or_default(),
+            image_url: e.image_url.unwrap_or_default(),
+            members_only: e.members_only,
+            unlisted: e.unlisted,
         }
     }
 }
 
+/// `PushedEvent` is the JSON body external systems post to add an event to a channel
+///
+/// Dates are RFC 3339 timestamps, like `2018-06-01T19:00:00-05:00`, and are shown in `timezone`,
+/// which defaults to UTC.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PushedEvent {
+    title: String,
+    #[serde(default)]
+    description: String,
+    start_date: String,
+    end_date: String,
+    timezone: Option<String>,
+    publish_at: Option<String>,
+    rsvp_deadline: Option<String>,
+    capacity: Option<i32>,
+    boost_threshold: Option<i32>,
+    location: Option<String>,
+    location_hint: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    image_url: Option<String>,
+    #[serde(default)]
+    members_only: bool,
+    #[serde(default)]
+    unlisted: bool,
+}
+
+impl PushedEvent {
+    pub fn try_to_event(self) -> Result<Event, FrontendError> {
+        let timezone = match self.timezone {
+            Some(ref timezone) => {
+                Tz::from_str(timezone).map_err(|_| FrontendErrorKind::BadTimeZone)?
+            }
+            None => Tz::UTC,
+        };
+
+        let title = maybe_empty_string(self.title, "title")?;
+        let start_date = parse_date(&self.start_date, &timezone)?;
+        let end_date = parse_date(&self.end_date, &timezone)?;
+
+        if end_date < start_date {
+            return Err(FrontendErrorKind::BadDate.into());
+        }
+
+        let publish_at = match self.publish_at {
+            Some(ref publish_at) => Some(parse_date(publish_at, &timezone)?),
+            None => None,
+        };
+
+        check_publish_at(publish_at, start_date)?;
+
+        let rsvp_deadline = match self.rsvp_deadline {
+            Some(ref rsvp_deadline) => Some(parse_date(rsvp_deadline, &timezone)?),
+            None => None,
+        };
+
+        check_rsvp_deadline(rsvp_deadline, start_date)?;
+
+        let capacity = match self.capacity {
+            Some(capacity) if capacity <= 0 => return Err(FrontendErrorKind::BadCapacity.into()),
+            capacity => capacity,
+        };
+
+        let boost_threshold = match self.boost_threshold {
+            Some(boost_threshold) if boost_threshold < 0 => {
+                return Err(FrontendErrorKind::BadBoostThreshold.into())
+            }
+            boost_threshold => boost_threshold,
+        };
+
+        let location = self.location.and_then(maybe_string);
+
+        // A hint is only useful when there's a location to hide
+        let location_hint = if location.is_some() {
+            self.location_hint.and_then(maybe_string)
+        } else {
+            None
+        };
+
+        let coordinates = match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => Some(check_coordinates(latitude, longitude)?),
+            (None, None) => None,
+            _ => return Err(FrontendErrorKind::BadCoordinates.into()),
+        };
+
+        // Coordinates pinpoint the location, so they're only kept when there is one
+        let coordinates = if location.is_some() {
+            coordinates
+        } else {
+            None
+        };
+
+        let image_url = match self.image_url.and_then(maybe_string) {
+            Some(image_url) => Some(check_image_url(image_url)?),
+            None => None,
+        };
+
+        Ok(Event {
+            title,
+            description: self.description.trim().to_owned(),
+            start_date,
+            end_date,
+            publish_at,
+            capacity,
+            boost_threshold,
+            location,
+            location_hint,
+            coordinates,
+            image_url,
+            rsvp_deadline,
+            members_only: self.members_only,
+            unlisted: self.unlisted,
+        })
+    }
+}
+
+/// Build a link to a point on OpenStreetMap
+fn map_url(latitude: f64, longitude: f64) -> String {
+    format!(
+        "https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=17/{lat}/{lon}",
+        lat = latitude,
+        lon = longitude
+    )
+}
+
+/// Parse the coordinates from the form. Both fields can be left empty, but not just one.
+fn form_coordinates(
+    latitude: &str,
+    longitude: &str,
+) -> Result<Option<(f64, f64)>, FrontendError> {
+    let latitude = latitude.trim();
+    let longitude = longitude.trim();
+
+    if latitude.len() == 0 && longitude.len() == 0 {
+        return Ok(None);
+    }
+
+    match (latitude.parse::<f64>(), longitude.parse::<f64>()) {
+        (Ok(latitude), Ok(longitude)) => check_coordinates(latitude, longitude).map(Some),
+        _ => Err(FrontendErrorKind::BadCoordinates.into()),
+    }
+}
+
+/// Check that a latitude and longitude are on the map
+fn check_coordinates(latitude: f64, longitude: f64) -> Result<(f64, f64), FrontendError> {
+    if latitude.abs() <= 90.0 && longitude.abs() <= 180.0 {
+        Ok((latitude, longitude))
+    } else {
+        Err(FrontendErrorKind::BadCoordinates.into())
+    }
+}
+
+/// Check that an announcement is scheduled for before the event starts
+fn check_publish_at(
+    publish_at: Option<DateTime<Tz>>,
+    start_date: DateTime<Tz>,
+) -> Result<(), FrontendError> {
+    match publish_at {
+        Some(publish_at) if publish_at >= start_date => {
+            Err(FrontendErrorKind::BadPublishAt.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that RSVPs don't close after the event has already started
+fn check_rsvp_deadline(
+    rsvp_deadline: Option<DateTime<Tz>>,
+    start_date: DateTime<Tz>,
+) -> Result<(), FrontendError> {
+    match rsvp_deadline {
+        Some(rsvp_deadline) if rsvp_deadline > start_date => {
+            Err(FrontendErrorKind::BadRsvpDeadline.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that an image URL is a web address Telegram can fetch the picture from
+fn check_image_url(image_url: String) -> Result<String, FrontendError> {
+    let is_web_url = image_url.starts_with("https://") || image_url.starts_with("http://");
+
+    if is_web_url && !image_url.contains(char::is_whitespace) {
+        Ok(image_url)
+    } else {
+        Err(FrontendErrorKind::BadImageUrl.into())
+    }
+}
+
+fn parse_date(date: &str, timezone: &Tz) -> Result<DateTime<Tz>, FrontendError> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| timezone.from_utc_datetime(&date.naive_utc()))
+        .context(FrontendErrorKind::BadDate)
+        .map_err(FrontendError::from)
+}
+
+/// Build a date from the form's fields. Months are counted from zero, like in the form.
+///
+/// Days that don't exist in the chosen month are rejected with a `DayOutOfRange` explaining how
+/// long the month is, rather than rolling over into the next month. Times that a daylight saving
+/// change skips or repeats are rejected with a `SkippedTime` or `RepeatedTime`, rather than
+/// guessing which time was meant.
+fn form_date(
+    timezone: &Tz,
+    year: i32,
+    month0: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+) -> Result<DateTime<Tz>, FrontendError> {
+    let month_name = MONTH_NAMES
+        .get(month0 as usize)
+        .ok_or(FrontendErrorKind::BadMonth)?;
+
+    let days = days_in_month(year, month0 + 1).ok_or(FrontendErrorKind::BadYear)?;
+
+    if day == 0 || day > days {
+        let e = DayOutOfRange {
+            month: month_name,
+            year,
+            days,
+        };
+
+        return Err(e.context(FrontendErrorKind::BadDay).into());
+    }
+
+    if minute > 59 {
+        return Err(FrontendErrorKind::BadMinute.into());
+    }
+
+    let date = NaiveDate::from_ymd_opt(year, month0 + 1, day).ok_or(FrontendErrorKind::BadDay)?;
+    let time = NaiveTime::from_hms_opt(hour, minute, 0).ok_or(FrontendErrorKind::BadHour)?;
+    let local = date.and_time(time);
+
+    match timezone.from_local_datetime(&local) {
+        LocalResult::Single(datetime) => Ok(datetime),
+        LocalResult::None => {
+            let e = SkippedTime {
+                time: local.format("%-H:%M on %B %-d, %Y").to_string(),
+                timezone: timezone.name(),
+            };
+
+            Err(e.context(FrontendErrorKind::BadLocalTime).into())
+        }
+        LocalResult::Ambiguous(_, _) => {
+            let e = RepeatedTime {
+                time: local.format("%-H:%M on %B %-d, %Y").to_string(),
+                timezone: timezone.name(),
+            };
+
+            Err(e.context(FrontendErrorKind::BadLocalTime).into())
+        }
+    }
+}
+
+/// Note which parts of a group of date fields were left out of the form, like
+/// `Pick the day and hour`
+fn missing_date_parts(
+    errors: &mut FieldErrors,
+    field: &'static str,
+    missing: (bool, bool, bool, bool, bool),
+) {
+    let (year, month, day, hour, minute) = missing;
+
+    let parts = [
+        (year, "year"),
+        (month, "month"),
+        (day, "day"),
+        (hour, "hour"),
+        (minute, "minute"),
+    ].iter()
+        .filter(|&&(missing, _)| missing)
+        .map(|&(_, part)| part)
+        .collect::<Vec<_>>();
+
+    let message = match parts.split_last() {
+        None => return,
+        Some((last, rest)) if rest.is_empty() => format!("Pick the {}", last),
+        Some((last, rest)) => format!("Pick the {} and {}", rest.join(", "), last),
+    };
+
+    errors.add(field, message);
+}
+
+/// Tie an error to the part of the form it came from, so it can be shown next to that part
+///
+/// The error keeps its kind. Its cause is replaced with an `InvalidField` describing it.
+fn in_field(field: &'static str) -> impl Fn(FrontendError) -> FrontendError {
+    move |e| {
+        let message = e.cause()
+            .map(|cause| cause.to_string())
+            .unwrap_or_else(|| e.to_string());
+
+        InvalidField { field, message }.context(e.kind()).into()
+    }
+}
+
+/// Build the length of an event from the form's duration fields
+///
+/// Events have to last at least a minute, so a duration of zero is rejected.
+fn form_duration(hours: u32, minutes: u32) -> Result<Duration, FrontendError> {
+    if minutes > 59 {
+        return Err(FrontendErrorKind::BadMinute.into());
+    }
+
+    match i64::from(hours) * 60 + i64::from(minutes) {
+        0 => Err(FrontendErrorKind::BadDuration.into()),
+        total => Ok(Duration::minutes(total)),
+    }
+}
+
+/// Get the number of days in a month, counting months from one
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    NaiveDate::from_ymd_opt(next_year, next_month, 1).map(|first| first.pred().day())
+}
+
 fn maybe_field<T>(maybe: Option<T>, field: &'static str) -> Result<T, FrontendError> {
     Ok(maybe
         .ok_or(MissingField { field })
         .context(FrontendErrorKind::MissingField)?)
 }
 
+fn maybe_string(s: String) -> Option<String> {
+    let s = s.trim().to_owned();
+
+    if s.len() == 0 {
+        None
+    } else {
+        Some(s)
+    }
+}
+
 fn maybe_empty_string(s: String, field: &'static str) -> Result<String, FrontendError> {
     let s = s.trim().to_owned();
 
@@ -356,3 +1499,90 @@ fn maybe_empty_string(s: String, field: &'static str) -> Result<String, Frontend
         Ok(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Timelike;
+    use chrono_tz::Tz;
+    use failure::Fail;
+
+    use super::{check_publish_at, form_date, in_field, missing_date_parts, FieldErrors};
+    use error::{FrontendError, FrontendErrorKind, InvalidField};
+
+    #[test]
+    fn rejects_times_skipped_by_daylight_saving() {
+        // Clocks in Chicago went from 2:00 to 3:00 on March 11th, 2018
+        let e = form_date(&Tz::US__Central, 2018, 2, 11, 2, 30).unwrap_err();
+
+        assert_eq!(e.kind(), FrontendErrorKind::BadLocalTime);
+        assert!(
+            e.cause()
+                .unwrap()
+                .to_string()
+                .starts_with("2:30 on March 11, 2018 doesn't happen")
+        );
+    }
+
+    #[test]
+    fn rejects_times_repeated_by_daylight_saving() {
+        // Clocks in Chicago went from 2:00 back to 1:00 on November 4th, 2018
+        let e = form_date(&Tz::US__Central, 2018, 10, 4, 1, 30).unwrap_err();
+
+        assert_eq!(e.kind(), FrontendErrorKind::BadLocalTime);
+        assert!(e.cause().unwrap().to_string().contains("happens twice in US/Central"));
+    }
+
+    #[test]
+    fn lists_missing_date_parts_together() {
+        let mut errors = FieldErrors::default();
+
+        missing_date_parts(&mut errors, "start", (false, false, true, true, true));
+        missing_date_parts(&mut errors, "end", (false, false, false, true, false));
+        missing_date_parts(&mut errors, "rsvp", (false, false, false, false, false));
+
+        assert_eq!(
+            errors.get("start").collect::<Vec<_>>(),
+            vec!["Pick the day, hour and minute"]
+        );
+        assert_eq!(errors.get("end").collect::<Vec<_>>(), vec!["Pick the hour"]);
+        assert_eq!(errors.get("rsvp").count(), 0);
+    }
+
+    #[test]
+    fn ties_errors_to_their_field() {
+        let e = form_date(&Tz::US__Central, 2018, 1, 30, 19, 0)
+            .map_err(in_field("end"))
+            .unwrap_err();
+        let invalid = e.cause()
+            .and_then(|cause| cause.downcast_ref::<InvalidField>())
+            .unwrap();
+
+        assert_eq!(e.kind(), FrontendErrorKind::BadDay);
+        assert_eq!(invalid.field, "end");
+        assert_eq!(invalid.message, "February 2018 only has 28 days");
+
+        let e = in_field("capacity")(FrontendError::from(FrontendErrorKind::BadCapacity));
+
+        assert_eq!(e.cause().unwrap().to_string(), "Invalid capacity");
+    }
+
+    #[test]
+    fn rejects_announcements_once_the_event_starts() {
+        let start = form_date(&Tz::US__Central, 2018, 6, 1, 19, 0).unwrap();
+        let before = form_date(&Tz::US__Central, 2018, 6, 1, 9, 0).unwrap();
+
+        assert!(check_publish_at(Some(before), start).is_ok());
+        assert!(check_publish_at(None, start).is_ok());
+        assert_eq!(
+            check_publish_at(Some(start), start).unwrap_err().kind(),
+            FrontendErrorKind::BadPublishAt
+        );
+    }
+
+    #[test]
+    fn accepts_times_around_daylight_saving() {
+        let date = form_date(&Tz::US__Central, 2018, 2, 11, 3, 30).unwrap();
+
+        assert_eq!((date.hour(), date.minute()), (3, 30));
+    }
+}
@@ -20,40 +20,76 @@
 
 extern crate actix;
 extern crate actix_web;
-extern crate bcrypt;
 extern crate chrono;
 extern crate chrono_tz;
 extern crate failure;
 extern crate futures;
 extern crate http;
+#[macro_use]
+extern crate log;
 extern crate maud;
+extern crate openssl;
+extern crate rand;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate sha2;
+
+use std::net::IpAddr;
 
 use actix::dev::{MessageResponse, ResponseChannel};
 use actix::{Actor, Addr, Context, Handler, Message, Syn};
-use actix_web::http::Method;
+use actix_web::http::{Cookie, Method};
 use actix_web::server::HttpServer;
 use actix_web::*;
-use chrono::offset::Utc;
-use chrono::Datelike;
+use chrono::offset::{TimeZone, Utc};
+use chrono::{DateTime, Datelike, Duration, Timelike};
 use chrono_tz::Tz;
 use failure::{Fail, ResultExt};
 use futures::future::Either;
 use futures::{Future, IntoFuture};
 use http::header;
+use rand::os::OsRng;
+use rand::Rng;
+use sha2::{Digest, Sha256};
 
+mod access_log;
+mod calendar;
 mod error;
 mod event;
+pub mod locale;
+mod proxy;
+mod security;
+mod theme;
+mod tls;
 mod views;
 
+use access_log::AccessLog;
+use calendar::CalendarMonth;
 pub use error::{FrontendError, FrontendErrorKind, MissingField};
-pub use event::{CreateEvent, Event, OptionEvent};
-use views::{form, success};
+use error::InvalidField;
+pub use event::{
+    ApiEvent, CreateEvent, DirectoryChannel, DirectoryEvent, Event, Host, OptionEvent,
+    PublicEvent, PushedEvent, StoredEvent, SuggestedTime,
+};
+use event::{FieldErrors, MONTH_NAMES};
+use locale::{Language, TimeFormat};
+pub use proxy::client_ip;
+use proxy::url_path;
+use security::SecurityHeaders;
+pub use theme::Theme;
+pub use tls::TlsSettings;
+use views::{
+    calendar_page, directory_page, event_page, form, sitemap_page, success, webhook_docs_page,
+};
 
 pub type SendFuture<T, E> = Box<Future<Item = T, Error = E> + Send>;
 
+/// The name of the cookie holding the CSRF token for the form at the cookie's path
+const CSRF_COOKIE: &str = "csrf_token";
+
+const CSRF_NOTICE: &str = "This form expired, please check your event and submit it again";
+
 pub struct SendFutResponse<M>
 where
     M: Message,
@@ -88,6 +124,101 @@ where
     }
 }
 
+/// `FormSettings` controls the dates offered in the event forms, which sites can embed the Web
+/// UI, and how its pages look
+#[derive(Clone, Debug)]
+pub struct FormSettings {
+    timezone: Tz,
+    years: i32,
+    frame_origins: Vec<String>,
+    site_url: Option<String>,
+    robots: Option<String>,
+    theme: Theme,
+    report_only: bool,
+    trusted_proxies: Vec<IpAddr>,
+}
+
+impl FormSettings {
+    /// Offer `years` years in the forms, starting from the current year in `timezone`
+    ///
+    /// New events default to starting now in `timezone`, so this should be the timezone the
+    /// channels using the bot are in.
+    pub fn new(timezone: Tz, years: u32) -> Self {
+        FormSettings {
+            timezone,
+            years: years.max(1) as i32,
+            frame_origins: Vec::new(),
+            site_url: None,
+            robots: None,
+            theme: Theme::default(),
+            report_only: false,
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    /// Let pages requested with `?embed=1` be shown in frames on these origins, like
+    /// `https://example.com`
+    pub fn embeddable_by(mut self, origins: Vec<String>) -> Self {
+        self.frame_origins = origins;
+        self
+    }
+
+    /// Build the sitemap's links and the forms' actions from this URL, instead of the host each
+    /// request was made to
+    ///
+    /// URLs without an `http` or `https` scheme are ignored, since browsers wouldn't treat them as
+    /// web addresses, and links stay relative to the host each request was made to.
+    pub fn served_at(mut self, site_url: &str) -> Self {
+        if site_url.starts_with("https://") || site_url.starts_with("http://") {
+            self.site_url = Some(site_url.trim_right_matches('/').to_owned());
+        }
+        self
+    }
+
+    /// Serve these contents as robots.txt, instead of the default rules
+    pub fn robots(mut self, robots: String) -> Self {
+        self.robots = Some(robots);
+        self
+    }
+
+    /// Brand the forms, the page shown after saving an event, and error pages with this theme
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Have browsers report what the `Content-Security-Policy` would block, instead of blocking
+    /// it, while trying out a policy
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    /// Believe the `X-Forwarded-For`, `X-Forwarded-Host`, and `X-Forwarded-Proto` headers on
+    /// requests from these reverse proxies
+    pub fn trust_proxies(mut self, proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = proxies;
+        self
+    }
+
+    /// The URL browsers use for a path on the Web UI
+    ///
+    /// Behind a reverse proxy, this has the proxy's scheme and host, and any path it serves the
+    /// Web UI under.
+    fn external_url(&self, path: &str) -> String {
+        match self.site_url {
+            Some(ref site_url) => format!("{}{}", site_url, path),
+            None => path.to_owned(),
+        }
+    }
+}
+
+impl Default for FormSettings {
+    fn default() -> Self {
+        FormSettings::new(Tz::US__Central, 4)
+    }
+}
+
 #[derive(Clone)]
 pub struct EventHandler<T>
 where
@@ -95,9 +226,18 @@ where
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
     handler: Addr<Syn, T>,
+    settings: FormSettings,
 }
 
 impl<T> EventHandler<T>
@@ -106,17 +246,25 @@ where
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
-    pub fn new(handler: Addr<Syn, T>) -> Self {
-        EventHandler { handler }
+    pub fn new(handler: Addr<Syn, T>, settings: FormSettings) -> Self {
+        EventHandler { handler, settings }
     }
 
     pub fn notify(
         &self,
         event: Event,
         id: String,
-    ) -> impl Future<Item = (), Error = FrontendError> {
+    ) -> impl Future<Item = (Event, TimeFormat), Error = FrontendError> {
         self.handler
             .send(NewEvent(event, id))
             .then(|msg_res| match msg_res {
@@ -127,7 +275,30 @@ where
             })
     }
 
-    fn request_event(&self, id: String) -> impl Future<Item = Event, Error = FrontendError> {
+    fn push_event(
+        &self,
+        channel_id: i64,
+        event: Event,
+        token: String,
+    ) -> impl Future<Item = (), Error = FrontendError> {
+        self.handler
+            .send(PushEvent {
+                channel_id,
+                event,
+                token,
+            })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn request_event(
+        &self,
+        id: String,
+    ) -> impl Future<Item = StoredEvent, Error = FrontendError> {
         self.handler
             .send(LookupEvent(id))
             .then(|msg_res| match msg_res {
@@ -138,13 +309,117 @@ where
             })
     }
 
+    fn request_public_event(
+        &self,
+        public_id: String,
+    ) -> impl Future<Item = PublicEvent, Error = FrontendError> {
+        self.handler
+            .send(LookupPublicEvent(public_id))
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn list_api_events(
+        &self,
+        channel_id: i64,
+        token: String,
+    ) -> impl Future<Item = Vec<ApiEvent>, Error = FrontendError> {
+        self.handler
+            .send(ListApiEvents { channel_id, token })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn request_api_event(
+        &self,
+        event_id: i32,
+        token: String,
+    ) -> impl Future<Item = ApiEvent, Error = FrontendError> {
+        self.handler
+            .send(LookupApiEvent { event_id, token })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn list_directory(
+        &self,
+        terms: String,
+        tag: String,
+    ) -> impl Future<Item = Vec<DirectoryChannel>, Error = FrontendError> {
+        self.handler
+            .send(ListDirectory { terms, tag })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn telegram_update(
+        &self,
+        token: String,
+        body: String,
+    ) -> impl Future<Item = (), Error = FrontendError> {
+        self.handler
+            .send(TelegramUpdate { token, body })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn suggest_times(
+        &self,
+        id: String,
+        timezone: Tz,
+    ) -> impl Future<Item = Vec<SuggestedTime>, Error = FrontendError> {
+        self.handler
+            .send(SuggestTimes { id, timezone })
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
+    fn lookup_calendar(
+        &self,
+        token: String,
+    ) -> impl Future<Item = DirectoryChannel, Error = FrontendError> {
+        self.handler
+            .send(LookupCalendar(token))
+            .then(|msg_res| match msg_res {
+                Ok(res) => Either::A(res),
+                Err(e) => Either::B(
+                    Err(FrontendError::from(e.context(FrontendErrorKind::Canceled))).into_future(),
+                ),
+            })
+    }
+
     fn edit_event(
         &self,
         event: Event,
         id: String,
-    ) -> impl Future<Item = (), Error = FrontendError> {
+        hosts: Option<Vec<i32>>,
+    ) -> impl Future<Item = TimeFormat, Error = FrontendError> {
         self.handler
-            .send(EditEvent(event.clone(), id))
+            .send(EditEvent(event.clone(), id, hosts))
             .then(|msg_res| match msg_res {
                 Ok(res) => Either::A(res),
                 Err(e) => Either::B(
@@ -154,61 +429,285 @@ where
     }
 }
 
+/// An event submitted on the new event form, along with the ID of the form's link
+///
+/// The result is the saved event, and how its channel shows times. A form that was submitted twice
+/// gets the event saved the first time.
 pub struct NewEvent(pub Event, pub String);
 
 impl Message for NewEvent {
-    type Result = SendFuture<(), FrontendError>;
+    type Result = SendFuture<(Event, TimeFormat), FrontendError>;
 }
 
-pub struct EditEvent(pub Event, pub String);
+/// An edited event, along with the IDs of its hosts if they were picked in the form
+///
+/// The result is how the event's channel shows times
+pub struct EditEvent(pub Event, pub String, pub Option<Vec<i32>>);
 
 impl Message for EditEvent {
+    type Result = SendFuture<TimeFormat, FrontendError>;
+}
+
+/// An event pushed to the API for a channel, along with the channel's API token
+pub struct PushEvent {
+    pub channel_id: i64,
+    pub event: Event,
+    pub token: String,
+}
+
+impl Message for PushEvent {
     type Result = SendFuture<(), FrontendError>;
 }
 
+/// A request for an event to edit, by the ID from its edit link
+///
+/// The result has the event's hosts, the people who can be picked as hosts, and its channel
 pub struct LookupEvent(pub String);
 
 impl Message for LookupEvent {
-    type Result = SendFuture<Event, FrontendError>;
+    type Result = SendFuture<StoredEvent, FrontendError>;
+}
+
+/// A request for an event's public page. Unlike `LookupEvent`, this doesn't need a secret, so
+/// the handler should refuse events that aren't meant to be public.
+pub struct LookupPublicEvent(pub String);
+
+impl Message for LookupPublicEvent {
+    type Result = SendFuture<PublicEvent, FrontendError>;
+}
+
+/// An API request for the events in a channel, along with the channel's API token
+pub struct ListApiEvents {
+    pub channel_id: i64,
+    pub token: String,
 }
 
-pub fn generate_secret(id: &str) -> Result<String, FrontendError> {
-    bcrypt::hash(id, bcrypt::DEFAULT_COST)
+impl Message for ListApiEvents {
+    type Result = SendFuture<Vec<ApiEvent>, FrontendError>;
+}
+
+/// An API request for a single event, along with the API token of the event's channel
+pub struct LookupApiEvent {
+    pub event_id: i32,
+    pub token: String,
+}
+
+impl Message for LookupApiEvent {
+    type Result = SendFuture<ApiEvent, FrontendError>;
+}
+
+/// A request for the directory of listed channels, along with the words searched for and the tag
+/// picked. Empty terms or an empty tag match every event.
+pub struct ListDirectory {
+    pub terms: String,
+    pub tag: String,
+}
+
+impl Message for ListDirectory {
+    type Result = SendFuture<Vec<DirectoryChannel>, FrontendError>;
+}
+
+/// An update Telegram sent to the bot's webhook, along with the token from the webhook's URL
+pub struct TelegramUpdate {
+    pub token: String,
+    pub body: String,
+}
+
+impl Message for TelegramUpdate {
+    type Result = SendFuture<(), FrontendError>;
+}
+
+/// A request for the best times to hold an event, along with the secret from the new event link
+/// and the timezone the times should be in
+pub struct SuggestTimes {
+    pub id: String,
+    pub timezone: Tz,
+}
+
+impl Message for SuggestTimes {
+    type Result = SendFuture<Vec<SuggestedTime>, FrontendError>;
+}
+
+/// A request for a channel's public events, along with the token from the channel's calendar link
+pub struct LookupCalendar(pub String);
+
+impl Message for LookupCalendar {
+    type Result = SendFuture<DirectoryChannel, FrontendError>;
+}
+
+/// The search and tag filter in the directory's URL
+#[derive(Clone, Debug, Deserialize)]
+struct DirectoryQuery {
+    q: Option<String>,
+    tag: Option<String>,
+    embed: Option<String>,
+}
+
+/// Whether a page was asked for with `?embed=1`, to be shown in another site's frame
+#[derive(Clone, Debug, Deserialize)]
+struct EmbedQuery {
+    embed: Option<String>,
+}
+
+/// The month a calendar shows, like `?month=2018-07`, which defaults to the current month
+#[derive(Clone, Debug, Deserialize)]
+struct CalendarQuery {
+    month: Option<String>,
+    embed: Option<String>,
+}
+
+fn is_embed(embed: Option<&str>) -> bool {
+    match embed {
+        Some("1") | Some("true") => true,
+        _ => false,
+    }
+}
+
+/// Build the frame rules of the `Content-Security-Policy` for an HTML page
+///
+/// Pages can always be framed by the Web UI itself. Embedded pages can also be framed by the
+/// configured origins. `SecurityHeaders` adds the rest of the policy.
+fn frame_policy(settings: &FormSettings, embed: bool) -> String {
+    let mut policy = security::SELF_FRAMES.to_owned();
+
+    if embed {
+        for origin in settings.frame_origins.iter() {
+            policy.push(' ');
+            policy.push_str(origin);
+        }
+    }
+
+    policy
+}
+
+/// Build the `Set-Cookie` header holding a form's CSRF token
+///
+/// Browsers hold back cookies from frames on other sites unless they're `SameSite=None`, which
+/// they only accept on `Secure` cookies, so embedded forms get both. The cookie crate can't write
+/// `SameSite=None` yet, so it's added to the header here.
+fn csrf_cookie(settings: &FormSettings, embed: bool, csrf_token: String, path: &str) -> String {
+    let embedded = embed && !settings.frame_origins.is_empty();
+
+    let cookie = Cookie::build(CSRF_COOKIE, csrf_token)
+        .path(path.to_owned())
+        .http_only(true)
+        .secure(embedded)
+        .finish();
+
+    if embedded {
+        format!("{}; SameSite=None", cookie)
+    } else {
+        cookie.to_string()
+    }
+}
+
+/// Generate a random 256 bit token for a link or an API token
+///
+/// This returns the token to give out, and the secret to store. The secret is a SHA-256 hash of
+/// the token, so the stored secrets can't be used as tokens. Tokens are random rather than
+/// chosen, so a fast hash is enough, and checking one doesn't cost more than looking it up.
+pub fn generate_secret() -> Result<(String, String), FrontendError> {
+    let token = random_hex(32)?;
+    let secret = hash_token(&token);
+
+    Ok((token, secret))
+}
+
+/// Check a token against the secret stored for it
+pub fn verify_secret(token: &str, secret: &str) -> bool {
+    constant_time_eq(&hash_token(token), secret)
+}
+
+fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn random_hex(len: usize) -> Result<String, FrontendError> {
+    let mut rng = OsRng::new()
         .context(FrontendErrorKind::Generation)
-        .map_err(FrontendError::from)
+        .map_err(FrontendError::from)?;
+
+    let mut bytes = vec![0; len];
+    rng.fill_bytes(&mut bytes);
+
+    Ok(to_hex(&bytes))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Generate a random token to protect a form against cross-site submissions
+///
+/// Each time a form is rendered, the token is both embedded in the form and set as a cookie for
+/// the form's URL. Another site can make a browser submit the form, but it can't read the cookie
+/// to learn what to put in the form.
+fn generate_csrf_token() -> Result<String, FrontendError> {
+    random_hex(32)
+}
+
+/// Check that the token submitted with a form matches the token in the form's cookie
+fn verify_csrf_token<S>(req: &HttpRequest<S>, option_event: &OptionEvent) -> bool {
+    match (req.cookie(CSRF_COOKIE), option_event.csrf_token()) {
+        (Some(cookie), Some(token)) => constant_time_eq(cookie.value(), token),
+        _ => false,
+    }
+}
+
+/// Compare two strings without returning early, so the time taken doesn't reveal how much of a
+/// token or secret was guessed correctly
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Describe why a submitted form couldn't be saved
+///
+/// Missing fields and problems with a single field are shown next to those fields, so they don't
+/// need a notice.
+fn form_notice(e: &FrontendError) -> Option<String> {
+    if e.kind() == FrontendErrorKind::MissingField || invalid_field(e).is_some() {
+        None
+    } else {
+        Some(e.to_string())
+    }
 }
 
-pub fn verify_secret(id: &str, secret: &str) -> Result<bool, FrontendError> {
-    bcrypt::verify(id, secret)
-        .context(FrontendErrorKind::Verification)
-        .map_err(FrontendError::from)
+/// Get the part of the form a submitted form's error is about, if it's about one part
+fn invalid_field(e: &FrontendError) -> Option<InvalidField> {
+    e.cause()
+        .and_then(|cause| cause.downcast_ref::<InvalidField>())
+        .cloned()
 }
 
 fn load_form(
+    settings: FormSettings,
     form_event: Option<CreateEvent>,
     form_id: String,
     form_url: String,
     form_title: &str,
     option_event: Option<OptionEvent>,
-) -> HttpResponse {
-    let date = Utc::now().with_timezone(&Tz::US__Central);
-
-    let years = (date.year()..date.year() + 4).collect::<Vec<_>>();
-
-    let months = [
-        "January",
-        "February",
-        "March",
-        "April",
-        "May",
-        "June",
-        "July",
-        "August",
-        "September",
-        "October",
-        "November",
-        "December",
-    ].into_iter()
+    notice: Option<&str>,
+    invalid: Option<InvalidField>,
+    stored_event: Option<&StoredEvent>,
+    suggestions: &[SuggestedTime],
+    embed: bool,
+) -> Result<HttpResponse, FrontendError> {
+    let csrf_token = generate_csrf_token()?;
+
+    let date = Utc::now().with_timezone(&settings.timezone);
+
+    let suggestions = suggestions
+        .iter()
+        .filter_map(|suggestion| {
+            next_occurrence(date, suggestion).map(|start| (*suggestion, start))
+        })
+        .collect::<Vec<_>>();
+
+    let months = MONTH_NAMES
+        .into_iter()
         .enumerate()
         .map(|(u, m)| (u as u32, m))
         .collect::<Vec<_>>();
@@ -227,7 +726,25 @@ fn load_form(
         create_event.merge(o);
     }
 
-    let timezones = [
+    let mut errors = option_event
+        .as_ref()
+        .map(|o| o.field_errors())
+        .unwrap_or_default();
+
+    if let Some(invalid) = invalid {
+        errors.add(invalid.field, invalid.message);
+    }
+
+    // Events being edited might be in a year that's no longer offered, so make sure their years
+    // are still in the list
+    let first_year = date.year().min(create_event.start_year);
+    let last_year = (date.year() + settings.years - 1)
+        .max(create_event.end_year)
+        .max(create_event.publish_year);
+
+    let years = (first_year..last_year + 1).collect::<Vec<_>>();
+
+    let mut timezones = [
         Tz::US__Eastern,
         Tz::US__Central,
         Tz::US__Mountain,
@@ -236,13 +753,28 @@ fn load_form(
         .map(|tz| tz.name())
         .collect::<Vec<_>>();
 
-    HttpResponse::Ok()
+    if !timezones.contains(&settings.timezone.name()) {
+        timezones.insert(0, settings.timezone.name());
+    }
+
+    let cookie = csrf_cookie(&settings, embed, csrf_token.clone(), url_path(&form_url));
+
+    // Embedded forms stay embedded once they're submitted
+    let submit_url = if embed {
+        format!("{}?embed=1", form_url)
+    } else {
+        form_url
+    };
+
+    Ok(HttpResponse::Ok()
         .header(header::CONTENT_TYPE, "text/html")
+        .header(header::CONTENT_SECURITY_POLICY, frame_policy(&settings, embed))
+        .header(header::SET_COOKIE, cookie)
         .body(
             form(
                 create_event,
                 option_event,
-                form_url,
+                submit_url,
                 years,
                 months,
                 days,
@@ -250,15 +782,81 @@ fn load_form(
                 minutes,
                 timezones,
                 form_id,
+                csrf_token,
                 form_title,
+                notice,
+                &errors,
+                stored_event,
+                &suggestions,
+                embed,
+                &settings,
             ).into_string(),
-        )
+        ))
+}
+
+/// Find the next time that falls on the suggested weekday and hour, starting from `now`
+fn next_occurrence(now: DateTime<Tz>, suggestion: &SuggestedTime) -> Option<DateTime<Tz>> {
+    let today = now.weekday().num_days_from_monday();
+    let mut days = (suggestion.weekday() + 7 - today) % 7;
+
+    // Today's slot has already started
+    if days == 0 && suggestion.hour() <= now.hour() {
+        days = 7;
+    }
+
+    let date = (now + Duration::days(i64::from(days))).date().naive_local();
+
+    now.timezone()
+        .from_local_datetime(&date.and_hms_opt(suggestion.hour(), 0, 0)?)
+        .single()
 }
 
-fn new_form(secret: Path<String>) -> HttpResponse {
+fn new_form<T>(
+    secret: Path<String>,
+    query: Query<EmbedQuery>,
+    state: State<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
     let id = secret.into_inner();
-    let submit_url = format!("/events/new/{}", id);
-    load_form(None, id, submit_url, "Event Bot | New Event", None)
+    let submit_url = state.settings.external_url(&format!("/events/new/{}", id));
+    let embed = is_embed(query.embed.as_ref().map(|embed| embed.as_str()));
+
+    let settings = state.settings.clone();
+
+    // The form is still shown if there aren't any suggestions, or they can't be looked up
+    Box::new(
+        state
+            .suggest_times(id.clone(), settings.timezone)
+            .then(move |suggestions| {
+                load_form(
+                    settings,
+                    None,
+                    id,
+                    submit_url,
+                    "Event Bot | New Event",
+                    None,
+                    None,
+                    None,
+                    None,
+                    &suggestions.unwrap_or_else(|_| Vec::new()),
+                    embed,
+                )
+            }),
+    )
 }
 
 fn edit_form<T>(
@@ -270,32 +868,201 @@ where
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
     let id = path.into_inner();
-    let submit_url = format!("/events/edit/{}", id);
+    let submit_url = state.settings.external_url(&format!("/events/edit/{}", id));
+
+    let settings = state.settings.clone();
 
-    Box::new(state.request_event(id.clone()).map(move |event| {
+    Box::new(state.request_event(id.clone()).and_then(move |stored_event| {
         load_form(
-            Some(event.into()),
+            settings,
+            Some(stored_event.event().clone().into()),
             id,
             submit_url,
             "Event Bot | Edit Event",
             None,
+            None,
+            None,
+            Some(&stored_event),
+            &[],
+            false,
         )
     }))
 }
 
+fn view_event<T>(
+    req: HttpRequest<EventHandler<T>>,
+    path: Path<String>,
+    query: Query<EmbedQuery>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let embed = is_embed(query.embed.as_ref().map(|embed| embed.as_str()));
+    let policy = frame_policy(&req.state().settings, embed);
+    let settings = req.state().settings.clone();
+    let language = req.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Language::from_accept_language)
+        .unwrap_or_default();
+
+    let public_id = path.into_inner();
+
+    Box::new(
+        req.state()
+            .request_public_event(public_id.clone())
+            .map(move |public_event| {
+                let page = event_page(&public_id, public_event, embed, language, &settings);
+
+                HttpResponse::Ok()
+                    .header(header::CONTENT_TYPE, "text/html")
+                    .header(header::CONTENT_SECURITY_POLICY, policy)
+                    .header(header::CONTENT_LANGUAGE, language.code())
+                    .body(page.into_string())
+            }),
+    )
+}
+
+fn directory<T>(
+    query: Query<DirectoryQuery>,
+    state: State<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let DirectoryQuery { q, tag, embed } = query.into_inner();
+
+    let terms = q.unwrap_or_default().trim().to_owned();
+    let tag = tag.unwrap_or_default()
+        .trim()
+        .trim_left_matches('#')
+        .to_lowercase();
+    let embed = is_embed(embed.as_ref().map(|embed| embed.as_str()));
+    let policy = frame_policy(&state.settings, embed);
+    let settings = state.settings.clone();
+
+    // The tag ends up in a regular expression, so it can only have the characters tags are made of
+    if !valid_tag(&tag) {
+        return Box::new(
+            Err::<HttpResponse, _>(FrontendError::from(FrontendErrorKind::BadTag)).into_future(),
+        );
+    }
+
+    Box::new(
+        state
+            .list_directory(terms.clone(), tag.clone())
+            .map(move |channels| {
+                HttpResponse::Ok()
+                    .header(header::CONTENT_TYPE, "text/html")
+                    .header(header::CONTENT_SECURITY_POLICY, policy)
+                    .body(directory_page(channels, &terms, &tag, embed, &settings).into_string())
+            }),
+    )
+}
+
+/// Check that a tag only has letters, numbers, and underscores, like the hashtags in events
+fn valid_tag(tag: &str) -> bool {
+    tag.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn calendar<T>(
+    path: Path<String>,
+    query: Query<CalendarQuery>,
+    state: State<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let CalendarQuery { month, embed } = query.into_inner();
+
+    let today = Utc::now()
+        .with_timezone(&state.settings.timezone)
+        .naive_local()
+        .date();
+    let month = month
+        .and_then(|month| CalendarMonth::parse(&month))
+        .unwrap_or_else(|| CalendarMonth::containing(today));
+    let embed = is_embed(embed.as_ref().map(|embed| embed.as_str()));
+    let policy = frame_policy(&state.settings, embed);
+    let settings = state.settings.clone();
+
+    Box::new(
+        state
+            .lookup_calendar(path.into_inner())
+            .map(move |channel| {
+                HttpResponse::Ok()
+                    .header(header::CONTENT_TYPE, "text/html")
+                    .header(header::CONTENT_SECURITY_POLICY, policy)
+                    .body(calendar_page(&channel, month, today, embed, &settings).into_string())
+            }),
+    )
+}
+
 fn updated<T>(
+    req: HttpRequest<EventHandler<T>>,
     path: Path<String>,
     form: Form<OptionEvent>,
-    state: State<EventHandler<T>>,
 ) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
 where
     T: Actor<Context = Context<T>>
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
     let id = path.into_inner();
@@ -303,65 +1070,499 @@ where
 
     let option_event = form.into_inner();
 
+    if !verify_csrf_token(&req, &option_event) {
+        let submit_url = req.state().settings.external_url(&format!("/events/edit/{}", id));
+        return Box::new(
+            load_form(
+                req.state().settings.clone(),
+                None,
+                id,
+                submit_url,
+                "Event Bot | Edit Event",
+                Some(option_event),
+                Some(CSRF_NOTICE),
+                None,
+                None,
+                &[],
+                false,
+            ).into_future(),
+        );
+    }
+
+    let state = req.state().clone();
+    let state2 = state.clone();
+    let settings = state.settings.clone();
+    let policy = frame_policy(&settings, false);
+    let page_settings = settings.clone();
+
     Box::new(
         Event::from_option(option_event.clone())
+            .and_then(|event| option_event.host_ids().map(|hosts| (event, hosts)))
             .into_future()
-            .and_then(move |event| {
-                state.edit_event(event.clone(), id).map(|_| {
-                    HttpResponse::Created()
-                        .header(header::CONTENT_TYPE, "text/html")
-                        .body(success(event, "Event Bot | Updated Event").into_string())
-                })
+            .and_then(move |(event, hosts)| {
+                state
+                    .edit_event(event.clone(), id, hosts)
+                    .map(move |time_format| {
+                        let page = success(
+                            event,
+                            "Event Bot | Updated Event",
+                            false,
+                            time_format,
+                            &page_settings,
+                        );
+
+                        HttpResponse::Created()
+                            .header(header::CONTENT_TYPE, "text/html")
+                            .header(header::CONTENT_SECURITY_POLICY, policy)
+                            .body(page.into_string())
+                    })
             })
-            .or_else(move |_| {
-                let submit_url = format!("/events/edit/{}", id2);
-                Ok(load_form(
-                    None,
-                    id2,
-                    submit_url,
-                    "Event Bot | Edit Event",
-                    Some(option_event),
-                ))
+            .or_else(move |e| {
+                let submit_url = settings.external_url(&format!("/events/edit/{}", id2));
+                let notice = form_notice(&e);
+                let invalid = invalid_field(&e);
+
+                // The form is still shown if the event can't be looked up, just without the
+                // summary of what's saved
+                state2.request_event(id2.clone()).then(move |stored_event| {
+                    load_form(
+                        settings,
+                        None,
+                        id2,
+                        submit_url.clone(),
+                        "Event Bot | Edit Event",
+                        Some(option_event),
+                        notice.as_ref().map(|notice| notice.as_str()),
+                        invalid,
+                        stored_event.ok().as_ref(),
+                        &[],
+                        false,
+                    ).map_err(|e| e.back_to(submit_url))
+                })
             }),
     )
 }
 
 fn submitted<T>(
+    req: HttpRequest<EventHandler<T>>,
     path: Path<String>,
     form: Form<OptionEvent>,
-    state: State<EventHandler<T>>,
 ) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
 where
     T: Actor<Context = Context<T>>
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
     let id = path.into_inner();
     let id2 = id.clone();
+    let embed = is_embed(req.query().get("embed").map(|embed| embed.as_str()));
 
     let option_event = form.into_inner();
 
+    if !verify_csrf_token(&req, &option_event) {
+        let submit_url = req.state().settings.external_url(&format!("/events/new/{}", id));
+        return Box::new(
+            load_form(
+                req.state().settings.clone(),
+                None,
+                id,
+                submit_url,
+                "Event Bot | New Event",
+                Some(option_event),
+                Some(CSRF_NOTICE),
+                None,
+                None,
+                &[],
+                embed,
+            ).into_future(),
+        );
+    }
+
+    let state = req.state().clone();
+    let settings = state.settings.clone();
+    let policy = frame_policy(&settings, embed);
+    let page_settings = settings.clone();
+
     Box::new(
         Event::from_option(option_event.clone())
             .into_future()
-            .map(move |event| {
-                state.handler.do_send(NewEvent(event.clone(), id));
+            .and_then(move |event| state.notify(event, id))
+            .map(move |(event, time_format)| {
+                let page = success(
+                    event,
+                    "Event Bot | Created Event",
+                    embed,
+                    time_format,
+                    &page_settings,
+                );
 
                 HttpResponse::Created()
                     .header(header::CONTENT_TYPE, "text/html")
-                    .body(success(event, "Event Bot | Created Event").into_string())
+                    .header(header::CONTENT_SECURITY_POLICY, policy)
+                    .body(page.into_string())
             })
-            .or_else(move |_| {
-                let submit_url = format!("/events/new/{}", id2);
-                Ok(load_form(
+            .or_else(move |e| {
+                let submit_url = settings.external_url(&format!("/events/new/{}", id2));
+                load_form(
+                    settings,
                     None,
                     id2,
-                    submit_url,
+                    submit_url.clone(),
                     "Event Bot | New Event",
                     Some(option_event),
-                ))
+                    form_notice(&e).as_ref().map(|notice| notice.as_str()),
+                    invalid_field(&e),
+                    None,
+                    &[],
+                    embed,
+                ).map_err(|e| e.back_to(submit_url))
+            }),
+    )
+}
+
+/// The robots.txt served when none is configured
+///
+/// Links to create and edit events are secrets, so crawlers are kept away from them along with
+/// the API.
+const DEFAULT_ROBOTS: &str = "User-agent: *
+Disallow: /events/new/
+Disallow: /events/edit/
+Disallow: /api/
+Disallow: /telegram/
+";
+
+/// Get the URL the Web UI is reached at, like `https://events.example.com`
+fn site_url<T>(req: &HttpRequest<EventHandler<T>>) -> String
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let settings = &req.state().settings;
+
+    match settings.site_url {
+        Some(ref site_url) => site_url.clone(),
+        None => {
+            let proxied = req
+                .peer_addr()
+                .map(|peer| settings.trusted_proxies.contains(&peer.ip()))
+                .unwrap_or(false);
+
+            if proxied {
+                let info = req.connection_info();
+                format!("{}://{}", info.scheme(), info.host())
+            } else {
+                // Anyone can send X-Forwarded-Host, so it's only believed from trusted proxies
+                let server = req.server_settings();
+                let scheme = if server.secure() { "https" } else { "http" };
+                let host = req.headers()
+                    .get(header::HOST)
+                    .and_then(|host| host.to_str().ok())
+                    .unwrap_or_else(|| server.host());
+
+                format!("{}://{}", scheme, host)
+            }
+        }
+    }
+}
+
+fn robots<T>(req: HttpRequest<EventHandler<T>>) -> HttpResponse
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let body = match req.state().settings.robots {
+        Some(ref robots) => robots.clone(),
+        None => format!("{}Sitemap: {}/sitemap.xml\n", DEFAULT_ROBOTS, site_url(&req)),
+    };
+
+    HttpResponse::Ok()
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(body)
+}
+
+/// Explain how to check that a webhook request came from the bot
+fn webhook_docs<T>(req: HttpRequest<EventHandler<T>>) -> HttpResponse
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    HttpResponse::Ok()
+        .header(header::CONTENT_TYPE, "text/html")
+        .header(
+            header::CONTENT_SECURITY_POLICY,
+            frame_policy(&req.state().settings, false),
+        )
+        .body(webhook_docs_page(&req.state().settings).into_string())
+}
+
+/// List the directory and the public page of every event in it, for search engines
+fn sitemap<T>(
+    req: HttpRequest<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let site_url = site_url(&req);
+
+    Box::new(
+        req.state()
+            .list_directory(String::new(), String::new())
+            .map(move |channels| {
+                let mut urls = vec![format!("{}/directory", site_url)];
+
+                urls.extend(
+                    channels
+                        .iter()
+                        .flat_map(|channel| channel.events().iter())
+                        .map(|event| format!("{}/events/view/{}", site_url, event.public_id())),
+                );
+
+                HttpResponse::Ok()
+                    .header(header::CONTENT_TYPE, "application/xml")
+                    .body(sitemap_page(&urls).into_string())
+            }),
+    )
+}
+
+/// The JSON body of a response from the API
+#[derive(Serialize)]
+struct ApiResponse {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn api_error(e: &FrontendError) -> HttpResponse {
+    let mut response = match e.kind() {
+        FrontendErrorKind::BadToken | FrontendErrorKind::Verification => {
+            HttpResponse::Unauthorized()
+        }
+        FrontendErrorKind::NoEvent | FrontendErrorKind::NoRoute => HttpResponse::NotFound(),
+        FrontendErrorKind::Creation | FrontendErrorKind::Canceled => {
+            HttpResponse::InternalServerError()
+        }
+        _ => HttpResponse::BadRequest(),
+    };
+
+    response.json(ApiResponse {
+        status: "error",
+        error: Some(e.to_string()),
+    })
+}
+
+/// Get the API token from a request's `Authorization: Bearer` header
+fn bearer_token<S>(req: &HttpRequest<S>) -> Result<String, FrontendError> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            if value.starts_with("Bearer ") {
+                Some(value.trim_left_matches("Bearer ").trim().to_owned())
+            } else {
+                None
+            }
+        })
+        .ok_or(FrontendErrorKind::BadToken.into())
+}
+
+fn api_events<T>(
+    req: HttpRequest<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let channel_id = req.match_info()
+        .query::<i64>("channel")
+        .map_err(|_| FrontendError::from(FrontendErrorKind::NoRoute));
+
+    let state = req.state().clone();
+
+    Box::new(
+        bearer_token(&req)
+            .and_then(|token| channel_id.map(|channel_id| (channel_id, token)))
+            .into_future()
+            .and_then(move |(channel_id, token)| state.list_api_events(channel_id, token))
+            .then(|res| match res {
+                Ok(events) => Ok(HttpResponse::Ok().json(events)),
+                Err(e) => Ok(api_error(&e)),
+            }),
+    )
+}
+
+fn api_event<T>(
+    req: HttpRequest<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let event_id = req.match_info()
+        .query::<i32>("id")
+        .map_err(|_| FrontendError::from(FrontendErrorKind::NoEvent));
+
+    let state = req.state().clone();
+
+    Box::new(
+        bearer_token(&req)
+            .and_then(|token| event_id.map(|event_id| (event_id, token)))
+            .into_future()
+            .and_then(move |(event_id, token)| state.request_api_event(event_id, token))
+            .then(|res| match res {
+                Ok(event) => Ok(HttpResponse::Ok().json(event)),
+                Err(e) => Ok(api_error(&e)),
+            }),
+    )
+}
+
+fn pushed<T>(
+    req: HttpRequest<EventHandler<T>>,
+    path: Path<i64>,
+    json: Json<PushedEvent>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    let channel_id = path.into_inner();
+    let state = req.state().clone();
+
+    Box::new(
+        bearer_token(&req)
+            .and_then(|token| {
+                json.into_inner()
+                    .try_to_event()
+                    .map(|event| (event, token))
+            })
+            .into_future()
+            .and_then(move |(event, token)| state.push_event(channel_id, event, token))
+            .then(|res| match res {
+                Ok(_) => Ok(HttpResponse::Created().json(ApiResponse {
+                    status: "created",
+                    error: None,
+                })),
+                Err(e) => Ok(api_error(&e)),
+            }),
+    )
+}
+
+/// Pass an update from Telegram on to the bot
+///
+/// The body is left for the bot to read, so the Web UI doesn't need to know what updates look like
+fn telegram_update<T>(
+    path: Path<String>,
+    body: String,
+    state: State<EventHandler<T>>,
+) -> Box<Future<Item = HttpResponse, Error = FrontendError>>
+where
+    T: Actor<Context = Context<T>>
+        + Handler<LookupEvent>
+        + Handler<NewEvent>
+        + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
+        + Clone,
+{
+    Box::new(
+        state
+            .telegram_update(path.into_inner(), body)
+            .then(|res| match res {
+                Ok(_) => Ok(HttpResponse::Ok().finish()),
+                Err(e) => Ok(api_error(&e)),
             }),
     )
 }
@@ -372,9 +1573,22 @@ where
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
-    let app = App::with_state(event_handler);
+    let report_only = event_handler.settings.report_only;
+    let trusted_proxies = event_handler.settings.trusted_proxies.clone();
+
+    let app = App::with_state(event_handler)
+        .middleware(AccessLog::new(trusted_proxies))
+        .middleware(SecurityHeaders::new(report_only));
 
     let app = if let Some(prefix) = prefix {
         app.prefix(prefix)
@@ -383,33 +1597,260 @@ where
     };
 
     app.resource("/events/new/{secret}", |r| {
-        r.method(Method::GET).with(new_form);
+        r.method(Method::GET).with3(new_form);
         r.method(Method::POST).with3(submitted);
     }).resource("/events/edit/{secret}", |r| {
             r.method(Method::GET).with2(edit_form);
             r.method(Method::POST).with3(updated);
         })
+        .resource("/events/view/{public_id}", |r| {
+            r.method(Method::GET).with3(view_event);
+        })
+        .resource("/directory", |r| {
+            r.method(Method::GET).with2(directory);
+        })
+        .resource("/events/calendar/{token}", |r| {
+            r.method(Method::GET).with3(calendar);
+        })
+        .resource("/api/v1/channels/{channel}/events", |r| {
+            // The channel's token is sent in a header, so it stays out of access logs
+            r.method(Method::GET).with(api_events);
+            r.method(Method::POST).with3(pushed);
+        })
+        .resource("/api/v1/events/{id}", |r| {
+            r.method(Method::GET).with(api_event);
+        })
+        .resource("/telegram/{token}", |r| {
+            r.method(Method::POST).with3(telegram_update);
+        })
+        .resource("/robots.txt", |r| {
+            r.method(Method::GET).with(robots);
+        })
+        .resource("/sitemap.xml", |r| {
+            r.method(Method::GET).with(sitemap);
+        })
+        .resource("/docs/webhooks", |r| {
+            r.method(Method::GET).with(webhook_docs);
+        })
         .handler("/assets/", fs::StaticFiles::new("assets/"))
+        .default_resource(|r| {
+            // Unknown pages get the same error page as any other error
+            r.f(|_| Err::<HttpResponse, _>(FrontendError::from(FrontendErrorKind::NoRoute)))
+        })
 }
 
-pub fn start<T>(handler: Addr<Syn, T>, addr: &str, prefix: Option<&'static str>)
+/// Serve the Web UI over HTTP on `addr`, and over HTTPS too when `tls` is given
+///
+/// Nothing is served if the HTTPS certificate can't be loaded, and an address that can't be
+/// listened on, like a port that's already in use, is reported rather than panicking.
+pub fn start<T>(
+    handler: Addr<Syn, T>,
+    addr: &str,
+    prefix: Option<&'static str>,
+    settings: FormSettings,
+    tls: Option<TlsSettings>,
+) -> Result<(), FrontendError>
 where
     T: Actor<Context = Context<T>>
         + Handler<LookupEvent>
         + Handler<NewEvent>
         + Handler<EditEvent>
+        + Handler<PushEvent>
+        + Handler<LookupPublicEvent>
+        + Handler<ListApiEvents>
+        + Handler<LookupApiEvent>
+        + Handler<ListDirectory>
+        + Handler<TelegramUpdate>
+        + Handler<SuggestTimes>
+        + Handler<LookupCalendar>
         + Clone,
 {
-    HttpServer::new(move || build(EventHandler::new(handler.clone()), prefix))
-        .bind(addr)
-        .unwrap()
-        .start();
+    // Load the certificate before binding anything, so a bad one stops the Web UI from starting
+    let https = match tls {
+        Some(ref https) => Some((https.address().to_owned(), tls::acceptor(https)?)),
+        None => None,
+    };
+
+    let server = HttpServer::new(move || {
+        // Each worker renders its own error pages, which don't have the app's state
+        error::install(&settings);
+
+        build(EventHandler::new(handler.clone(), settings.clone()), prefix)
+    }).bind(addr)
+        .context(FrontendErrorKind::Bind)?;
+
+    let server = match https {
+        Some((address, acceptor)) => server
+            .bind_ssl(address, acceptor)
+            .context(FrontendErrorKind::Bind)?,
+        None => server,
+    };
+
+    server.start();
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::offset::TimeZone;
+    use chrono::{Datelike, Timelike, Weekday};
+    use chrono_tz::Tz;
+
+    use super::{csrf_cookie, next_occurrence, url_path, valid_tag, FormSettings};
+    use actix_web::http::StatusCode;
+    use error::FrontendErrorKind;
+    use event::{Event, PublicEvent, SuggestedTime};
+    use locale::Language;
+    use theme::Theme;
+    use views::{event_page, webhook_docs_page};
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn embedded_forms_send_their_csrf_cookie_across_sites() {
+        let settings = FormSettings::new(Tz::US__Central, 2)
+            .embeddable_by(vec!["https://example.com".to_owned()]);
+
+        let cookie = csrf_cookie(&settings, true, "token".to_owned(), "/events");
+        assert!(cookie.contains("Secure"));
+        assert!(cookie.ends_with("; SameSite=None"));
+
+        let cookie = csrf_cookie(&settings, false, "token".to_owned(), "/events");
+        assert!(!cookie.contains("Secure"));
+        assert!(!cookie.contains("SameSite"));
+
+        let settings = FormSettings::new(Tz::US__Central, 2);
+        let cookie = csrf_cookie(&settings, true, "token".to_owned(), "/events");
+        assert!(!cookie.contains("SameSite"));
+    }
+
+    #[test]
+    fn forms_are_submitted_to_the_external_url() {
+        let settings = FormSettings::default().served_at("https://example.com/events-bot/");
+        let form_url = settings.external_url("/events/new/abc");
+
+        assert_eq!(form_url, "https://example.com/events-bot/events/new/abc");
+        assert_eq!(url_path(&form_url), "/events-bot/events/new/abc");
+    }
+
+    #[test]
+    fn pages_load_assets_through_the_external_url() {
+        let settings = FormSettings::default().served_at("https://example.com/events-bot");
+        let page = webhook_docs_page(&settings).into_string();
+
+        assert!(page.contains("\"https://example.com/events-bot/assets/styles.css\""));
+
+        let page = webhook_docs_page(&FormSettings::default()).into_string();
+
+        assert!(page.contains("\"/assets/styles.css\""));
+    }
+
+    #[test]
+    fn event_pages_describe_their_link_previews() {
+        let tz = Tz::US__Central;
+        let settings = FormSettings::default()
+            .served_at("https://example.com")
+            .theme(Theme::default().logo_url("https://example.com/logo.png"));
+
+        let event = |image_url: Option<&str>| {
+            let event = Event::from_parts(
+                "Picnic".to_owned(),
+                "Bring a blanket".to_owned(),
+                tz.ymd(2018, 7, 4).and_hms(12, 0, 0),
+                tz.ymd(2018, 7, 4).and_hms(15, 0, 0),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                image_url.map(|s| s.to_owned()),
+                None,
+                false,
+                false,
+            );
+
+            PublicEvent::new(event, vec![])
+        };
+
+        let page =
+            event_page("abc", event(None), false, Language::default(), &settings).into_string();
+
+        assert!(page.contains("property=\"og:title\" content=\"Picnic\""));
+        assert!(page.contains("property=\"og:description\" content=\"Bring a blanket\""));
+        assert!(page.contains("content=\"https://example.com/events/view/abc\""));
+        assert!(page.contains("content=\"https://example.com/logo.png\""));
+
+        let picture = Some("https://example.com/picnic.png");
+        let page =
+            event_page("abc", event(picture), false, Language::default(), &settings).into_string();
+
+        assert!(page.contains("property=\"og:image\" content=\"https://example.com/picnic.png\""));
+    }
+
+    #[test]
+    fn forms_stay_relative_without_a_scheme() {
+        let settings = FormSettings::default().served_at("localhost:8000");
+        let form_url = settings.external_url("/events/new/abc");
+
+        assert_eq!(form_url, "/events/new/abc");
+        assert_eq!(url_path(&form_url), "/events/new/abc");
+    }
+
+    #[test]
+    fn next_occurrence_is_later_this_week() {
+        // A Wednesday afternoon
+        let now = Tz::US__Central.ymd(2018, 6, 27).and_hms(15, 30, 0);
+
+        let start = next_occurrence(now, &SuggestedTime::new(4, 19, 8.0)).unwrap();
+
+        assert_eq!(start.weekday(), Weekday::Fri);
+        assert_eq!(start.day(), 29);
+        assert_eq!(start.hour(), 19);
+        assert_eq!(start.minute(), 0);
+    }
+
+    #[test]
+    fn next_occurrence_skips_slots_that_have_started() {
+        let now = Tz::US__Central.ymd(2018, 6, 27).and_hms(15, 30, 0);
+
+        let later = next_occurrence(now, &SuggestedTime::new(2, 18, 8.0)).unwrap();
+        let started = next_occurrence(now, &SuggestedTime::new(2, 15, 8.0)).unwrap();
+
+        assert_eq!(later.day(), 27);
+        assert_eq!(started.day(), 4);
+        assert_eq!(started.month(), 7);
+    }
+
+    #[test]
+    fn tags_only_have_word_characters() {
+        assert!(valid_tag(""));
+        assert!(valid_tag("board_games"));
+        assert!(valid_tag("café2"));
+        assert!(!valid_tag("a|b"));
+        assert!(!valid_tag(".*"));
+        assert!(!valid_tag("games("));
+    }
+
+    #[test]
+    fn error_pages_blame_the_right_side() {
+        assert_eq!(FrontendErrorKind::NoEvent.status(), StatusCode::NOT_FOUND);
+        assert_eq!(FrontendErrorKind::BadCapacity.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            FrontendErrorKind::Verification.status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            FrontendErrorKind::Canceled.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            FrontendErrorKind::Busy.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
 }
@@ -0,0 +1,1745 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use chrono::Weekday;
+
+use event::{MONTH_NAMES, WEEKDAY_NAMES};
+
+/// The languages the bot's messages and the event pages can be shown in
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Language {
+    English,
+    Spanish,
+    German,
+}
+
+/// Every language, in the order they're listed by `/language`
+pub const LANGUAGES: &[Language] = &[Language::English, Language::Spanish, Language::German];
+
+/// How times of day are shown, like `19:05` or `7:05 PM`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TimeFormat {
+    TwentyFourHour,
+    TwelveHour,
+}
+
+const SPANISH_MONTHS: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+const SPANISH_WEEKDAYS: [&str; 7] = [
+    "lunes",
+    "martes",
+    "miércoles",
+    "jueves",
+    "viernes",
+    "sábado",
+    "domingo",
+];
+
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+const GERMAN_WEEKDAYS: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+
+/// The pieces of text shown to users that have been translated
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Text {
+    Channel,
+    When,
+    /// Followed by when an event that's already started ends
+    HappeningNow,
+    /// Comes before how long it is until an event starts, like the `starts in` in
+    /// `starts in 3 Hours`
+    StartsIn,
+    /// Shown instead of how long it is until an event starts when it's less than a minute away
+    StartingNow,
+    /// Comes before a time of day, like the `at` in `ends at 21:00`
+    At,
+    Ends,
+    Duration,
+    Where,
+    /// Follows a location that's only a hint
+    HiddenLocation,
+    Map,
+    Description,
+    Hosts,
+    Capacity,
+    MembersOnly,
+    MoreInfo,
+    Week,
+    Weeks,
+    Day,
+    Days,
+    Hour,
+    Hours,
+    Minute,
+    Minutes,
+    NoTime,
+    EventHeading,
+    CancelledHeading,
+    NewEvent,
+    StillRoom,
+    Cancelled,
+    ThisWeeksEvents,
+    UpcomingEvents,
+    NoUpcomingEvents,
+    YourEvents,
+    NotHosting,
+    UnknownChannel,
+    Start,
+    End,
+    Location,
+    ViewMap,
+    RegistrationClosed,
+    RsvpsClose,
+    Busy,
+    /// Sent when a user the channel's admins banned tries to create an event
+    Banned,
+    Quota,
+    NotAdmin,
+    ChannelRights,
+    ChatNotFound,
+    Blocked,
+    TooLong,
+    ChannelsLookupFailed,
+    EventsLookupFailed,
+    UserNotFound,
+    FetchEventsFailed,
+    SearchFailed,
+    AdminCheckFailed,
+    ChannelNotUpdated,
+    UnbanFailed,
+    BanFailed,
+    GreetingFailed,
+    ChannelNotFound,
+    InitFailed,
+    NewLinkFailed,
+    EditLinkFailed,
+    WebhookAddFailed,
+    RotateAdminsOnly,
+    WebhookRemoveFailed,
+    WebhooksLookupFailed,
+    CreateEventFailed,
+    RsvpFailed,
+    CancelRsvpFailed,
+    EventLookupFailed,
+    NotificationsLookupFailed,
+    NothingStored,
+    BroadcastFailed,
+    DeleteEventFailed,
+    CancelEventFailed,
+    TransferFailed,
+    ReviewFailed,
+    AdminChannelsLookupFailed,
+    TokenAdminsOnly,
+    SecretAdminsOnly,
+    PoolStatusFailed,
+    FailedMessagesLookupFailed,
+    ReplayFailed,
+    CalendarFailed,
+    TokenFailed,
+    SecretFailed,
+    NotLinked,
+    NotPrivate,
+    NoChannels,
+    NoAdminChannels,
+    PickChannelForNew,
+    ConfirmNewEvent,
+    PickEventToEdit,
+    PickEventToDelete,
+    PickEventToCancel,
+    PickEventToTransfer,
+    PickEventToBroadcast,
+    PickChannelForToken,
+    PickChannelForReadToken,
+    PickChannelForSecret,
+    PickChannelForRotate,
+    NoTitle,
+    /// Filled in with `/mute` or `/unmute`
+    MuteUsage,
+    /// Follows the list of notifications and whether each is on
+    Preferences,
+    On,
+    Off,
+    NotificationsMenu,
+    RemindersLabel,
+    BroadcastsLabel,
+    HostNudgesLabel,
+    WaitlistLabel,
+    /// Followed by the languages the bot speaks
+    LanguageUsage,
+    UserLanguageSet,
+    UserTimeFormatSet,
+    TimeFormatUsage,
+    UpdatesAdminsOnly,
+    ReceivingUpdates,
+    SwitchingUpdates,
+    UpdatesUsage,
+    SnoozeAdminsOnly,
+    Snoozed,
+    Unsnoozed,
+    SnoozeUsage,
+    FailedAdminsOnly,
+    FailedUsage,
+    PoolAdminsOnly,
+    QuickInPast,
+    /// Follows the event that was understood
+    QuickPickChannel,
+    QuickUsage,
+    QuickExpired,
+    TokenUsage,
+    WebhookPrivateUsage,
+    UpgradeToSupergroup,
+    NotSupergroup,
+    FetchOnlyInSupergroup,
+    PinOnlyInSupergroup,
+    NextEvent,
+    TodaysEvents,
+    NoEventsToday,
+    EventsMatching,
+    NoEventsMatch,
+    SearchUsage,
+    SearchOnlyInGroups,
+    /// Filled in with the command, like `/init`
+    ChannelsOnly,
+    Initialized,
+    InitBeforeLinking,
+    /// Filled in with the channel's ID and the IDs of the chats linked to it
+    Linked,
+    Protected,
+    Unprotected,
+    ChannelLanguageSet,
+    ChannelTimeFormatSet,
+    Moderated,
+    Unmoderated,
+    UnbanUsage,
+    HostUnbanned,
+    NotBanned,
+    BanUsage,
+    BanUsernameNotFound,
+    AlreadyBanned,
+    HostBanned,
+    /// Names a banned user without a username by their ID
+    UserById,
+    DigestDaySet,
+    DigestOff,
+    DigestUsage,
+    DigestPinned,
+    DigestUnpinned,
+    AnnouncementsPinned,
+    AnnouncementsUnpinned,
+    Listed,
+    Unlisted,
+    GreetingOff,
+    GreetingUsage,
+    /// Filled in with the channel's templates and the placeholders they can use
+    Templates,
+    /// Shown for a template the channel hasn't set
+    DefaultTemplate,
+    TemplateSet,
+    TemplateReset,
+    /// Filled in with the unknown placeholder's name, which is wrapped in braces, and the
+    /// placeholders templates can use
+    UnknownPlaceholder,
+    TemplateUsage,
+    /// Follows the list of features and whether each is on
+    Features,
+    FeaturesUsage,
+    BoostSet,
+    BoostOff,
+    BoostUsage,
+    /// Filled in with the events created this month, the average attendance, the linked chats, the
+    /// users in them, and the most active hosts
+    Stats,
+    People,
+    NoEventsEnded,
+    NoHostsYet,
+    NoUsername,
+    TokenInChannel,
+    SecretInChannel,
+    /// Filled in with the bot's URL and the two parts of the calendar's path
+    CalendarLink,
+    WebhookUsage,
+    /// Filled in with the token, the bot's URL, and the channel's ID
+    TokenIssued,
+    /// Filled in with the token, the bot's URL, the channel's ID, and the bot's URL again
+    ReadTokenIssued,
+    WebhookScheme,
+    WebhookPublic,
+    /// Filled in with the webhook's URL and the URL of the webhook docs
+    WebhookAdded,
+    SecretRotated,
+    SharedSecret,
+    /// Followed by each webhook's URL and secret
+    WebhookSecrets,
+    /// Follows the secrets a channel's webhooks are signed with
+    SaveSecret,
+    NoWebhooks,
+    WebhookRemoved,
+    NotAWebhook,
+    WebhookList,
+    NoFailedMessages,
+    FailedAnnouncement,
+    FailedMessage,
+    /// Filled in with the message's ID, whether it's an announcement, the event's ID, the chat's
+    /// ID, when it failed, why, and the start of its text
+    FailedMessageLine,
+    FailedMessages,
+    Replaying,
+    PoolStatus,
+    /// Filled in with the link and when it expires
+    NewEventLink,
+    /// Filled in with the link and when it expires
+    EditEventLink,
+    NewEventLinkExpired,
+    EditEventLinkExpired,
+    NewLink,
+    Created,
+    CreatedPending,
+    EventDeletedReply,
+    EventDeletedNotice,
+    EventCancelledReply,
+    EventCancelledNotice,
+    EventUpdated,
+    /// Filled in with the time, its timezone, and the events
+    UpcomingEventsAsOf,
+    EditButton,
+    DeleteButton,
+    GoingButton,
+    CantGoButton,
+    /// Filled in with the event's title and its location, if that's only sent to attendees
+    Going,
+    Waitlisted,
+    AlreadyResponded,
+    /// Sent when someone RSVPs to a cancelled event
+    RsvpCancelled,
+    RsvpsClosed,
+    RsvpsOff,
+    RsvpMembersOnly,
+    NotGoing,
+    /// Filled in with the event's title and its location, if that's only sent to attendees
+    Promoted,
+    DeleteIt,
+    CancelInstead,
+    ConfirmDelete,
+    NoRsvps,
+    /// Follows one, like `1 person is going`
+    PersonIs,
+    /// Follows a number other than one, like `2 people are going`
+    PeopleAre,
+    /// Filled in with how many people are going and their usernames
+    RsvpsGoing,
+    /// Filled in with how many people are on the waitlist and their usernames
+    RsvpsWaitlisted,
+    RsvpsDeleted,
+    CancelInsteadHint,
+    ReallyDelete,
+    Yes,
+    No,
+    ReallyForget,
+    ExportCaption,
+    /// Filled in with how many chat memberships, host entries, RSVPs, and links were deleted
+    Forgotten,
+    NothingChanged,
+    Nevermind,
+    AskBroadcast,
+    /// Filled in with how many minutes apart attendees can be messaged, and how many minutes are
+    /// left
+    BroadcastTooSoon,
+    /// Filled in with how many people are going and the event's title
+    BroadcastSending,
+    BroadcastNobody,
+    /// Filled in with the event's title and the host's message
+    BroadcastMessage,
+    PickNewHost,
+    NoNewHosts,
+    /// Filled in with the old host's username and the event's title
+    MadeHost,
+    Transferred,
+    StartingSoon,
+    /// Filled in with the event's title and when it starts
+    AttendeeReminder,
+    AwaitingApproval,
+    Approve,
+    TurnDown,
+    Approved,
+    TurnedDown,
+    PendingApproval,
+    ApprovedNotice,
+    TurnedDownNotice,
+    /// The reminder template used when a channel hasn't set its own
+    DefaultReminder,
+    /// The message sent when an event starts. Channels can't replace this one.
+    DefaultStarted,
+    /// The ended template used when a channel hasn't set its own
+    DefaultEnded,
+    /// Follows the admins' greeting in a channel's pinned greeting
+    HowItWorks,
+    GreetingDigestPinned,
+    GreetingDigest,
+    GreetingPinned,
+    /// The `/help` message, filled in with the group, private, and admin commands
+    Help,
+    /// Comes before how a command is used in `/help`
+    HelpUsage,
+    HelpEvents,
+    HelpPinEvents,
+    HelpNext,
+    HelpToday,
+    HelpSearch,
+    HelpNew,
+    HelpQuick,
+    HelpEdit,
+    HelpCancel,
+    HelpTransfer,
+    HelpDelete,
+    HelpMyEvents,
+    HelpAnnounce,
+    HelpNotifications,
+    HelpMute,
+    HelpUnmute,
+    HelpLanguage,
+    HelpTimeFormat,
+    HelpExportMe,
+    HelpForgetMe,
+    HelpSearchPrivate,
+    HelpHelp,
+    HelpInit,
+    HelpLink,
+    HelpProtect,
+    HelpModerate,
+    HelpChannelLanguage,
+    HelpChannelTimeFormat,
+    HelpBan,
+    HelpUnban,
+    HelpDigest,
+    HelpPinDigest,
+    HelpPinAnnouncements,
+    HelpDirectory,
+    HelpSetGreeting,
+    HelpTemplate,
+    HelpFeatures,
+    HelpBoost,
+    HelpStats,
+    HelpToken,
+    HelpCalendar,
+    HelpWebhook,
+    HelpId,
+    HelpUpdates,
+    HelpPool,
+    HelpSnooze,
+    HelpFailed,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Language::English
+    }
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat::TwentyFourHour
+    }
+}
+
+impl TimeFormat {
+    /// Get the time format from the name used with `/timeformat`, `12` or `24`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().trim_right_matches('h') {
+            "24" => Some(TimeFormat::TwentyFourHour),
+            "12" => Some(TimeFormat::TwelveHour),
+            _ => None,
+        }
+    }
+
+    /// Get the time format stored as whether it has 12 hours
+    pub fn from_twelve_hour(twelve_hour: bool) -> Self {
+        if twelve_hour {
+            TimeFormat::TwelveHour
+        } else {
+            TimeFormat::TwentyFourHour
+        }
+    }
+
+    /// Check whether the time format has 12 hours, which is how it's stored
+    pub fn is_twelve_hour(&self) -> bool {
+        *self == TimeFormat::TwelveHour
+    }
+
+    /// Get the name used for the time format with `/timeformat`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TimeFormat::TwentyFourHour => "24",
+            TimeFormat::TwelveHour => "12",
+        }
+    }
+
+    /// Format a time of day, given the hour from 0 to 23
+    pub fn format_time(&self, hour: u32, minute: u32) -> String {
+        match *self {
+            TimeFormat::TwentyFourHour => format!("{}:{:02}", hour, minute),
+            TimeFormat::TwelveHour => {
+                let period = if hour < 12 { "AM" } else { "PM" };
+                let hour = match hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+
+                format!("{}:{:02} {}", hour, minute, period)
+            }
+        }
+    }
+}
+
+impl Language {
+    /// Get the language from its code, like `es` or `es-MX`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        let primary = code.split(|c| c == '-' || c == '_').next().unwrap_or("");
+
+        LANGUAGES
+            .iter()
+            .find(|language| language.code() == primary)
+            .map(|language| *language)
+    }
+
+    /// Pick the language for an `Accept-Language` header, like `de-DE,de;q=0.9,en;q=0.8`
+    ///
+    /// The supported language the browser prefers most is used, or English if it prefers none of
+    /// them.
+    pub fn from_accept_language(header: &str) -> Self {
+        let mut ranges = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let language = parts.next().and_then(Language::from_code)?;
+                let quality = parts
+                    .filter_map(|part| {
+                        let part = part.trim();
+
+                        if part.starts_with("q=") {
+                            part[2..].parse::<f32>().ok()
+                        } else {
+                            None
+                        }
+                    })
+                    .next()
+                    .unwrap_or(1.0);
+
+                Some((language, quality))
+            })
+            .filter(|&(_, quality)| quality > 0.0)
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so languages the browser likes equally keep the order it sent them in
+        ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+        ranges
+            .first()
+            .map(|&(language, _)| language)
+            .unwrap_or_default()
+    }
+
+    /// Get the code stored for the language, and used with `/language`
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::German => "de",
+        }
+    }
+
+    /// Get the name of the language, in the language itself
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// Get the name of the day of the week
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        let names = match *self {
+            Language::English => &WEEKDAY_NAMES,
+            Language::Spanish => &SPANISH_WEEKDAYS,
+            Language::German => &GERMAN_WEEKDAYS,
+        };
+
+        names[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Get the name of the month, counting January as 1
+    pub fn month(&self, month: u32) -> &'static str {
+        let names = match *self {
+            Language::English => &MONTH_NAMES,
+            Language::Spanish => &SPANISH_MONTHS,
+            Language::German => &GERMAN_MONTHS,
+        };
+
+        month
+            .checked_sub(1)
+            .and_then(|index| names.get(index as usize))
+            .map(|name| *name)
+            .unwrap_or("")
+    }
+
+    /// Get the text in the language
+    pub fn text(&self, text: Text) -> &'static str {
+        match *self {
+            Language::English => english(text),
+            Language::Spanish => spanish(text),
+            Language::German => german(text),
+        }
+    }
+
+    /// Get the text in the language, filling in each `{}` with the next of `args`
+    ///
+    /// Other braces, like the ones around template placeholders, are left as they are.
+    pub fn fill(&self, text: Text, args: &[&Display]) -> String {
+        let mut pieces = self.text(text).split("{}");
+        let mut filled = pieces.next().unwrap_or("").to_owned();
+
+        for (index, piece) in pieces.enumerate() {
+            match args.get(index) {
+                Some(arg) => filled.push_str(&arg.to_string()),
+                None => filled.push_str("{}"),
+            }
+
+            filled.push_str(piece);
+        }
+
+        filled
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Channel => "Channel",
+        Text::When => "When",
+        Text::HappeningNow => "Happening now, ends",
+        Text::StartsIn => "starts in",
+        Text::StartingNow => "starting now",
+        Text::At => "at",
+        Text::Ends => "Ends",
+        Text::Duration => "Duration",
+        Text::Where => "Where",
+        Text::HiddenLocation => "the exact location is sent to you when you RSVP",
+        Text::Map => "Map",
+        Text::Description => "Description",
+        Text::Hosts => "Hosts",
+        Text::Capacity => "Capacity",
+        Text::MembersOnly => "Members only",
+        Text::MoreInfo => "More info",
+        Text::Week => "Week",
+        Text::Weeks => "Weeks",
+        Text::Day => "Day",
+        Text::Days => "Days",
+        Text::Hour => "Hour",
+        Text::Hours => "Hours",
+        Text::Minute => "Minute",
+        Text::Minutes => "Minutes",
+        Text::NoTime => "No time",
+        Text::EventHeading => "----Event----",
+        Text::CancelledHeading => "----Cancelled----",
+        Text::NewEvent => "New Event!",
+        Text::StillRoom => "There's still room!",
+        Text::Cancelled => "CANCELLED",
+        Text::ThisWeeksEvents => "This Week's Events:",
+        Text::UpcomingEvents => "Upcoming Events:",
+        Text::NoUpcomingEvents => "No upcoming events",
+        Text::YourEvents => "Your Events:",
+        Text::NotHosting => "You aren't hosting any events",
+        Text::UnknownChannel => "Unknown channel",
+        Text::Start => "Start",
+        Text::End => "End",
+        Text::Location => "Location",
+        Text::ViewMap => "View on OpenStreetMap",
+        Text::RegistrationClosed => "Registration closed",
+        Text::RsvpsClose => "RSVPs close",
+        Text::Busy => "The bot is busy right now, please try again shortly",
+        Text::Banned => "The channel's admins have stopped you from creating events for it",
+        Text::Quota => "You're already hosting as many upcoming events for this channel as the bot allows",
+        Text::NotAdmin => "I need to be an admin of the channel for that. Make me an admin and try again",
+        Text::ChannelRights => "I need to be an admin of this channel who can post and edit messages. Give me those rights in the channel's admin settings and try again",
+        Text::ChatNotFound => "I couldn't find that chat. Check its ID, make sure I've been added to it, and try again",
+        Text::Blocked => "I can't message you privately. Open a private chat with me, send /start, and try again",
+        Text::TooLong => "That's too long for a Telegram message. Try again with a shorter description",
+        Text::ChannelsLookupFailed => "Failed to get event channels for user",
+        Text::EventsLookupFailed => "Failed to get events for user",
+        Text::UserNotFound => "Could not find you, have you sent a message in one of your group chats?",
+        Text::FetchEventsFailed => "Failed to fetch events",
+        Text::SearchFailed => "Failed to search events",
+        Text::AdminCheckFailed => "Could not determine if you are an admin of provided chats",
+        Text::ChannelNotUpdated => "Could not update the channel, has it been initialized?",
+        Text::UnbanFailed => "Could not unban the user",
+        Text::BanFailed => "Could not ban the user, has the channel been initialized?",
+        Text::GreetingFailed => "Could not post the greeting",
+        Text::ChannelNotFound => "Could not find the channel, has it been initialized?",
+        Text::InitFailed => "Could not initialize the chat",
+        Text::NewLinkFailed => "Failed to generate new event link",
+        Text::EditLinkFailed => "Unable to generate edit link",
+        Text::WebhookAddFailed => "Could not add the webhook, has the channel been initialized?",
+        Text::RotateAdminsOnly => "Only the channel's admins can replace its webhook secret",
+        Text::WebhookRemoveFailed => "Could not remove the webhook",
+        Text::WebhooksLookupFailed => "Could not look up webhooks",
+        Text::CreateEventFailed => "Failed to create event",
+        Text::RsvpFailed => "Failed to RSVP to event",
+        Text::CancelRsvpFailed => "Failed to cancel RSVP",
+        Text::EventLookupFailed => "Failed to look up event",
+        Text::NotificationsLookupFailed => "Failed to look up your notifications",
+        Text::NothingStored => "The bot doesn't have anything stored about you",
+        Text::BroadcastFailed => "Failed to message attendees",
+        Text::DeleteEventFailed => "Failed to delete event",
+        Text::CancelEventFailed => "Failed to cancel event",
+        Text::TransferFailed => "Failed to transfer event",
+        Text::ReviewFailed => "Could not review the event, has another admin already?",
+        Text::AdminChannelsLookupFailed => "Could not find your channels",
+        Text::TokenAdminsOnly => "Only the channel's admins can get its API token",
+        Text::SecretAdminsOnly => "Only the channel's admins can get its webhook secret",
+        Text::PoolStatusFailed => "Could not check the database connections",
+        Text::FailedMessagesLookupFailed => "Could not look up the messages that couldn't be sent",
+        Text::ReplayFailed => "Could not send the message again, has the event been deleted?",
+        Text::CalendarFailed => "Could not create a calendar link",
+        Text::TokenFailed => "Could not create a token",
+        Text::SecretFailed => "Could not create a secret",
+        Text::NotLinked => "You can only create events for channels linked to a chat you're in. If you believe this a mistake, please send a message in the chat first, then try again",
+        Text::NotPrivate => "Please send this command as a private message",
+        Text::NoChannels => "You aren't in any chats with an associated events channel. If you believe this a mistake, please send a message in the associated chat first, then try again",
+        Text::NoAdminChannels => "You aren't an admin of any channel linked to a chat you're in",
+        Text::PickChannelForNew => "Which channel would you like to create an event for?",
+        Text::ConfirmNewEvent => "Would you like to create an event for this channel?",
+        Text::PickEventToEdit => "Which event would you like to edit?",
+        Text::PickEventToDelete => "Which event would you like to delete?",
+        Text::PickEventToCancel => "Which event would you like to cancel?",
+        Text::PickEventToTransfer => "Which event would you like to transfer?",
+        Text::PickEventToBroadcast => "Which event's attendees would you like to message?",
+        Text::PickChannelForToken => "Which channel would you like a new API token for?",
+        Text::PickChannelForReadToken => "Which channel would you like a new read-only API token for?",
+        Text::PickChannelForSecret => "Which channel's webhook secret would you like?",
+        Text::PickChannelForRotate => "Which channel's webhook secret would you like to replace?",
+        Text::NoTitle => "No title",
+        Text::MuteUsage => "Usage: {} [reminders|broadcasts|hosting|waitlist]",
+        Text::Preferences => "{}\n\nTurn one off or on with /mute or /unmute, or tap through them with /notifications",
+        Text::On => "on",
+        Text::Off => "off",
+        Text::NotificationsMenu => "Tap a kind of message to turn it on or off",
+        Text::RemindersLabel => "Reminders",
+        Text::BroadcastsLabel => "Messages from hosts",
+        Text::HostNudgesLabel => "Updates about events you host",
+        Text::WaitlistLabel => "Waitlist spots opening up",
+        Text::LanguageUsage => "Usage: /language [code]\n\n{}",
+        Text::UserLanguageSet => "Your events will be listed in {}",
+        Text::UserTimeFormatSet => "Your events will be listed with {}-hour times",
+        Text::TimeFormatUsage => "Usage: /timeformat [12|24]",
+        Text::UpdatesAdminsOnly => "Only the bot's admins can change how it receives updates",
+        Text::ReceivingUpdates => "Receiving updates by {}",
+        Text::SwitchingUpdates => "Switching to {} for updates",
+        Text::UpdatesUsage => "Usage: /updates [polling|webhook]",
+        Text::SnoozeAdminsOnly => "Only the bot's admins can snooze alerts",
+        Text::Snoozed => "Alerts are snoozed for {} minutes",
+        Text::Unsnoozed => "Alerts are no longer snoozed",
+        Text::SnoozeUsage => "Usage: /snooze [minutes|off]",
+        Text::FailedAdminsOnly => "Only the bot's admins can see messages that couldn't be sent",
+        Text::FailedUsage => "Usage: /failed [replay id]",
+        Text::PoolAdminsOnly => "Only the bot's admins can check its database connections",
+        Text::QuickInPast => "That event starts in the past, please pick a later time",
+        Text::QuickPickChannel => "{}\n\nWhich channel should this event be created in?",
+        Text::QuickUsage => "Couldn't understand that event, try something like /quick Pizza night tomorrow 7pm-9pm",
+        Text::QuickExpired => "That event has expired, please use /quick again",
+        Text::TokenUsage => "Usage: /token or /token read",
+        Text::WebhookPrivateUsage => "Webhooks are added in channels. Send /webhook secret to get the secret a channel's webhooks are signed with, or /webhook rotate to replace it",
+        Text::UpgradeToSupergroup => "Please upgrade this group to a supergroup before linking",
+        Text::NotSupergroup => "Cannot link non-supergroup chat",
+        Text::FetchOnlyInSupergroup => "Can only fetch events in a supergroup",
+        Text::PinOnlyInSupergroup => "Can only pin events in a supergroup",
+        Text::NextEvent => "Next Event:",
+        Text::TodaysEvents => "Today's Events:",
+        Text::NoEventsToday => "No events today",
+        Text::EventsMatching => "Events matching {}:",
+        Text::NoEventsMatch => "No upcoming events match {}",
+        Text::SearchUsage => "Usage: /search [terms], for example /search pizza",
+        Text::SearchOnlyInGroups => "Can only search events in a supergroup or a private chat",
+        Text::ChannelsOnly => "The {} command can only be used in channels",
+        Text::Initialized => "Initialized",
+        Text::InitBeforeLinking => "Please /init the channel before linking",
+        Text::Linked => "Linked channel '{}' to chats ({})",
+        Text::Protected => "New announcements will be protected from forwarding",
+        Text::Unprotected => "New announcements can be forwarded",
+        Text::ChannelLanguageSet => "Events will be announced in {}",
+        Text::ChannelTimeFormatSet => "Events will be announced with {}-hour times",
+        Text::Moderated => "New events from hosts who aren't admins will wait for an admin to approve them",
+        Text::Unmoderated => "New events will be announced without waiting for approval",
+        Text::UnbanUsage => "Usage: /unban [@username|user id]",
+        Text::HostUnbanned => "{} can create events again",
+        Text::NotBanned => "{} wasn't banned",
+        Text::BanUsage => "Usage: /ban [@username|user id]",
+        Text::BanUsernameNotFound => "Couldn't find {} in this channel's chats, or they're already banned. Users who haven't talked in the chats can be banned by their user ID",
+        Text::AlreadyBanned => "{} is already banned",
+        Text::HostBanned => "{} can no longer create events for this channel",
+        Text::UserById => "User {}",
+        Text::DigestDaySet => "The weekly digest will be posted every {}",
+        Text::DigestOff => "The weekly digest is off",
+        Text::DigestUsage => "Usage: /digest [day|off], for example /digest monday",
+        Text::DigestPinned => "The weekly digest will be pinned",
+        Text::DigestUnpinned => "The weekly digest will not be pinned",
+        Text::AnnouncementsPinned => "New announcements will be pinned until their event ends",
+        Text::AnnouncementsUnpinned => "New announcements will not be pinned",
+        Text::Listed => "This channel and its public events are now listed at {}",
+        Text::Unlisted => "This channel is no longer listed in the directory",
+        Text::GreetingOff => "The greeting is off",
+        Text::GreetingUsage => "Usage: /setgreeting [text|off], for example /setgreeting Welcome to our events!",
+        Text::Templates => "{}\n\nChange one with /template [announcement|reminder|ended] [text|default]. Templates can use {}",
+        Text::DefaultTemplate => "(default)",
+        Text::TemplateSet => "The {} template is set",
+        Text::TemplateReset => "The {} template is back to the default",
+        Text::UnknownPlaceholder => "{{}} isn't a placeholder, try one of {}",
+        Text::TemplateUsage => "Usage: /template [announcement|reminder|ended] [text|default], for example /template reminder {title} starts soon, see you there!",
+        Text::Features => "{}\n\nTurn one on or off with /features [rsvp|digest|public_page] [on|off]",
+        Text::FeaturesUsage => "Usage: /features [rsvp|digest|public_page] [on|off]",
+        Text::BoostSet => "Events with fewer than {} RSVPs two days before they start will be announced again",
+        Text::BoostOff => "Events won't be announced again",
+        Text::BoostUsage => "Usage: /boost [number|off], for example /boost 5",
+        Text::Stats => "Events created this month: {}\nAverage attendance: {}\nLinked chats: {}\nUsers in those chats: {}\n\nMost active hosts:\n{}",
+        Text::People => "{} people",
+        Text::NoEventsEnded => "No events have ended yet",
+        Text::NoHostsYet => "Nobody has hosted an event yet",
+        Text::NoUsername => "Someone without a username: {}",
+        Text::TokenInChannel => "API tokens are only sent privately. Send /token to me in a private chat to get one for this channel",
+        Text::SecretInChannel => "Webhook secrets are only sent privately. Send /webhook secret or /webhook rotate to me in a private chat",
+        Text::CalendarLink => "This channel's calendar is at {}/events/calendar/{}={}\n\nAny previous calendar link no longer works.",
+        Text::WebhookUsage => "Usage: /webhook [add|remove] [url]",
+        Text::TokenIssued => "The channel's new API token is {}\n\nExternal calendars can add events to the channel by posting them to {}/api/v1/channels/{}/events with the token in an \"Authorization: Bearer\" header. Websites that only show the channel's events should use a read-only token from /token read instead.\n\nAny previous token no longer works. Delete this message once you've saved the token somewhere safe.",
+        Text::ReadTokenIssued => "The channel's new read-only API token is {}\n\nWebsites can list the channel's events from {}/api/v1/channels/{}/events and get single events from {}/api/v1/events/{id}. Send the token in an \"Authorization: Bearer\" header. It can't add events.\n\nAny previous read-only token no longer works.",
+        Text::WebhookScheme => "Webhook URLs must start with http:// or https://",
+        Text::WebhookPublic => "Webhooks can only be sent to public addresses",
+        Text::WebhookAdded => "{} will be notified when events change\n\nRequests are signed with this channel's webhook secret. Send /webhook secret to me in a private chat to get it. {} explains how to check the signatures.",
+        Text::SecretRotated => "The channel's webhooks are now signed with the secret {}\n\nDelete this message once you've saved the secret somewhere safe.",
+        Text::SharedSecret => "The channel's webhooks are signed with the secret {}",
+        Text::WebhookSecrets => "The channel's webhooks are signed with these secrets:",
+        Text::SaveSecret => "{}\n\nDelete this message once you've saved the secret somewhere safe.",
+        Text::NoWebhooks => "This channel has no webhooks",
+        Text::WebhookRemoved => "{} will no longer be notified",
+        Text::NotAWebhook => "{} isn't a webhook for this channel",
+        Text::WebhookList => "This channel's webhooks:\n{}",
+        Text::NoFailedMessages => "No messages have failed to send",
+        Text::FailedAnnouncement => "Announcement",
+        Text::FailedMessage => "Message",
+        Text::FailedMessageLine => "{}. {} for event {} to chat {}, {}\n{}\n{}",
+        Text::FailedMessages => "Messages that couldn't be sent, newest first:\n\n{}\n\nSend /failed replay [id] to send one again",
+        Text::Replaying => "Sending message {} again",
+        Text::PoolStatus => "Database connections:\nIdle: {}\nIn use: {}\nConnecting: {}\nWaiting requests: {}",
+        Text::NewEventLink => "Use this link to create your event: {}\nThis link expires at {}",
+        Text::EditEventLink => "Use this link to update your event: {}\nThis link expires at {}",
+        Text::NewEventLinkExpired => "Your link to create your event has expired",
+        Text::EditEventLinkExpired => "Your link to update your event has expired",
+        Text::NewLink => "Generate new link",
+        Text::Created => "Created {}!",
+        Text::CreatedPending => "Created {}! It will be announced once the channel's admins approve it",
+        Text::EventDeletedReply => "Deleted event!",
+        Text::EventDeletedNotice => "Event deleted: {}",
+        Text::EventCancelledReply => "Cancelled event!",
+        Text::EventCancelledNotice => "Event cancelled: {}",
+        Text::EventUpdated => "Event Updated!",
+        Text::UpcomingEventsAsOf => "Upcoming Events, as of {} {}:\n\n{}",
+        Text::EditButton => "Edit {}",
+        Text::DeleteButton => "Delete {}",
+        Text::GoingButton => "Going",
+        Text::CantGoButton => "Can't go",
+        Text::Going => "You're going to {}!{}",
+        Text::Waitlisted => "{} is full, you've been added to the waitlist",
+        Text::AlreadyResponded => "You've already responded to {}",
+        Text::RsvpCancelled => "{} has been cancelled",
+        Text::RsvpsClosed => "Sorry, RSVPs for {} have closed",
+        Text::RsvpsOff => "RSVPs are turned off in this channel",
+        Text::RsvpMembersOnly => "{} is members only, you need to be in one of its linked groups to RSVP",
+        Text::NotGoing => "You're no longer going to {}",
+        Text::Promoted => "A spot opened up! You're now going to {}{}",
+        Text::DeleteIt => "Delete it",
+        Text::CancelInstead => "Cancel it instead",
+        Text::ConfirmDelete => "Are you sure you want to delete {}?\n",
+        Text::NoRsvps => "\nNobody has RSVP'd yet.",
+        Text::PersonIs => "person is",
+        Text::PeopleAre => "people are",
+        Text::RsvpsGoing => "\n{} going: {}",
+        Text::RsvpsWaitlisted => "\n{} on the waitlist: {}",
+        Text::RsvpsDeleted => "\nTheir RSVPs will be deleted with the event.",
+        Text::CancelInsteadHint => "\n\nCancelling it instead keeps the event and its RSVPs, and lets everyone know it's off.",
+        Text::ReallyDelete => "Really delete '{}'? This can't be undone.",
+        Text::Yes => "Yes",
+        Text::No => "No",
+        Text::ReallyForget => "Really forget everything the bot knows about you? You'll be taken off the events you're going to and hosting. This can't be undone.",
+        Text::ExportCaption => "Here's everything the bot has stored about you",
+        Text::Forgotten => "Done, the bot has forgotten you. It deleted:\n\n{} chat memberships\n{} host entries\n{} RSVPs\n{} links for creating or editing events\n\nIf you keep talking in chats that use this bot, it will start remembering you again.",
+        Text::NothingChanged => "Okay, nothing was changed",
+        Text::Nevermind => "Nevermind",
+        Text::AskBroadcast => "What would you like to tell everyone going to {}? Your next message will be sent to them.",
+        Text::BroadcastTooSoon => "Attendees can only be messaged once every {} minutes, try again in {} minutes",
+        Text::BroadcastSending => "Sending your message to the {} people going to {}",
+        Text::BroadcastNobody => "Nobody going to {} can be messaged right now",
+        Text::BroadcastMessage => "A message from the host of {}:\n\n{}\n\nSend /mute broadcasts to stop getting messages from hosts",
+        Text::PickNewHost => "Who should host {}?",
+        Text::NoNewHosts => "Nobody else in {}'s chats can host it",
+        Text::MadeHost => "@{} made you the host of {}",
+        Text::Transferred => "Transferred {}!",
+        Text::StartingSoon => "Starting soon:\n\n{}",
+        Text::AttendeeReminder => "Reminder: {} starts at {}\n\nSend /mute reminders to stop getting reminders",
+        Text::AwaitingApproval => "Waiting for approval",
+        Text::Approve => "Approve",
+        Text::TurnDown => "Turn down",
+        Text::Approved => "Approved {}",
+        Text::TurnedDown => "Turned down {}",
+        Text::PendingApproval => "{} will be announced once the channel's admins approve it",
+        Text::ApprovedNotice => "{} was approved by the channel's admins",
+        Text::TurnedDownNotice => "{} was turned down by the channel's admins",
+        Text::DefaultReminder => "Don't forget! {title} is starting soon!",
+        Text::DefaultStarted => "{title} has started!",
+        Text::DefaultEnded => "{title} has ended!",
+        Text::HowItWorks => "\n\nHow this channel works:\n- Upcoming events are announced here. Tap \"Going\" on an announcement to RSVP.\n- Members of the linked groups can create events by sending /new to the bot in a private chat.\n- Send /events in a linked group to see what's coming up.",
+        Text::GreetingDigestPinned => "\n- A digest of the week's events is posted and pinned every {}.",
+        Text::GreetingDigest => "\n- A digest of the week's events is posted every {}.",
+        Text::GreetingPinned => "\n- New announcements are pinned until their event ends.",
+        Text::Help => "Event Bot is a telegram bot to help groups manage events.\n\nIn group chats, the following commands are available:\n{}\n\nIn private chats, the following commands are available:\n{}\n\nIf you're an admin wanting to add this bot to a chat, the following commands will be interesting to you:\n{}\n\nKeep in mind that this bot only works in supergroups, not regular groups.\n\nIf you have any questions or need help setting up or using the bot, contact @asonix\n\nThis bot is released under the GNU General Public License version 3 or later. If you would like a copy of the code, check here:\nhttp://github.com/asonix/telegram-event-bot\n",
+        Text::HelpUsage => "usage",
+        Text::HelpEvents => "get a list of events for the current chat",
+        Text::HelpPinEvents => "pin a list of upcomming events in the current group",
+        Text::HelpNext => "get the next upcoming event for the current chat",
+        Text::HelpToday => "get the events starting today for the current chat",
+        Text::HelpSearch => "search the current chat's upcoming events",
+        Text::HelpNew => "Create a new event",
+        Text::HelpQuick => "Create an event from a description",
+        Text::HelpEdit => "Edit an event you're hosting",
+        Text::HelpCancel => "Cancel an event you're hosting, keeping it and its RSVPs around",
+        Text::HelpTransfer => "Hand an event you're hosting over to someone else",
+        Text::HelpDelete => "Delete an event you're hosting",
+        Text::HelpMyEvents => "List the events you're hosting",
+        Text::HelpAnnounce => "Send a message to everyone going to an event you're hosting",
+        Text::HelpNotifications => "Pick which private messages you get from the bot",
+        Text::HelpMute => "Stop getting private messages from the bot",
+        Text::HelpUnmute => "Start getting private messages from the bot again",
+        Text::HelpLanguage => "Pick the language your events are listed in",
+        Text::HelpTimeFormat => "Pick whether your events are listed with 12 or 24-hour times",
+        Text::HelpExportMe => "Get a copy of everything the bot knows about you",
+        Text::HelpForgetMe => "Delete everything the bot knows about you, including your RSVPs",
+        Text::HelpSearchPrivate => "Search the upcoming events in all your channels",
+        Text::HelpHelp => "Print this help message",
+        Text::HelpInit => "Initialize an event channel",
+        Text::HelpLink => "in an event channel, link a group chat",
+        Text::HelpProtect => "in an event channel, toggle whether announcements can be forwarded",
+        Text::HelpModerate => "in an event channel, toggle whether events from hosts who aren't admins need an admin's approval before they're announced",
+        Text::HelpChannelLanguage => "in an event channel, pick the language events are announced in",
+        Text::HelpChannelTimeFormat => "in an event channel, pick whether events are announced with 12 or 24-hour times",
+        Text::HelpBan => "in an event channel, stop a user from creating events for it",
+        Text::HelpUnban => "in an event channel, let a banned user create events again",
+        Text::HelpDigest => "in an event channel, set the day the weekly digest is posted",
+        Text::HelpPinDigest => "in an event channel, toggle whether the weekly digest is pinned",
+        Text::HelpPinAnnouncements => "in an event channel, toggle whether new event announcements are pinned",
+        Text::HelpDirectory => "in an event channel, toggle whether the channel and its public events are listed in the Web UI's directory",
+        Text::HelpSetGreeting => "in an event channel, set an introduction the bot pins and keeps up to date",
+        Text::HelpTemplate => "in an event channel, list or change the announcement, reminder, and ended messages",
+        Text::HelpFeatures => "in an event channel, list or turn on and off RSVPs, the weekly digest, and public pages",
+        Text::HelpBoost => "in an event channel, announce events again two days before they start if fewer than this many people have RSVP'd",
+        Text::HelpStats => "in an event channel, show how many events were created this month, the most active hosts, average attendance, and how many chats and users are linked",
+        Text::HelpToken => "in a private chat, create a token that lets other websites and bots add events to a channel you're an admin of, or a read-only token that lets them list its events",
+        Text::HelpCalendar => "in an event channel, create a link to a calendar of the channel's public events",
+        Text::HelpWebhook => "in an event channel, list, add, or remove URLs notified when events change. In a private chat, get or replace the secret they're signed with",
+        Text::HelpId => "get the id of a group chat",
+        Text::HelpUpdates => "switch how the bot gets updates from Telegram",
+        Text::HelpPool => "check the bot's database connections",
+        Text::HelpSnooze => "hold back alerts for a while",
+        Text::HelpFailed => "see messages that couldn't be sent, or send one again",
+    }
+}
+
+fn spanish(text: Text) -> &'static str {
+    match text {
+        Text::Channel => "Canal",
+        Text::When => "Cuándo",
+        Text::HappeningNow => "En curso, termina",
+        Text::StartsIn => "empieza en",
+        Text::StartingNow => "empieza ya",
+        Text::At => "a las",
+        Text::Ends => "Termina",
+        Text::Duration => "Duración",
+        Text::Where => "Dónde",
+        Text::HiddenLocation => "recibirás la ubicación exacta cuando confirmes tu asistencia",
+        Text::Map => "Mapa",
+        Text::Description => "Descripción",
+        Text::Hosts => "Anfitriones",
+        Text::Capacity => "Capacidad",
+        Text::MembersOnly => "Solo para miembros",
+        Text::MoreInfo => "Más información",
+        Text::Week => "semana",
+        Text::Weeks => "semanas",
+        Text::Day => "día",
+        Text::Days => "días",
+        Text::Hour => "hora",
+        Text::Hours => "horas",
+        Text::Minute => "minuto",
+        Text::Minutes => "minutos",
+        Text::NoTime => "Nada de tiempo",
+        Text::EventHeading => "----Evento----",
+        Text::CancelledHeading => "----Cancelado----",
+        Text::NewEvent => "¡Nuevo evento!",
+        Text::StillRoom => "¡Todavía hay lugar!",
+        Text::Cancelled => "CANCELADO",
+        Text::ThisWeeksEvents => "Eventos de esta semana:",
+        Text::UpcomingEvents => "Próximos eventos:",
+        Text::NoUpcomingEvents => "No hay próximos eventos",
+        Text::YourEvents => "Tus eventos:",
+        Text::NotHosting => "No eres anfitrión de ningún evento",
+        Text::UnknownChannel => "Canal desconocido",
+        Text::Start => "Inicio",
+        Text::End => "Fin",
+        Text::Location => "Ubicación",
+        Text::ViewMap => "Ver en OpenStreetMap",
+        Text::RegistrationClosed => "Inscripción cerrada",
+        Text::RsvpsClose => "Las confirmaciones cierran",
+        Text::Busy => "El bot está ocupado ahora mismo, inténtalo de nuevo en un momento",
+        Text::Banned => "Los administradores del canal no te permiten crear eventos en él",
+        Text::Quota => "Ya eres anfitrión de tantos próximos eventos en este canal como permite el bot",
+        Text::NotAdmin => "Para eso necesito ser administrador del canal. Hazme administrador e inténtalo de nuevo",
+        Text::ChannelRights => "Necesito ser administrador de este canal con permiso para publicar y editar mensajes. Dame esos permisos en la configuración de administradores del canal e inténtalo de nuevo",
+        Text::ChatNotFound => "No encontré ese chat. Revisa su ID, asegúrate de que me hayan añadido y inténtalo de nuevo",
+        Text::Blocked => "No puedo escribirte en privado. Abre un chat privado conmigo, envía /start e inténtalo de nuevo",
+        Text::TooLong => "Es demasiado largo para un mensaje de Telegram. Inténtalo de nuevo con una descripción más corta",
+        Text::ChannelsLookupFailed => "No se pudieron obtener tus canales de eventos",
+        Text::EventsLookupFailed => "No se pudieron obtener tus eventos",
+        Text::UserNotFound => "No te encontré, ¿has enviado un mensaje en alguno de tus grupos?",
+        Text::FetchEventsFailed => "No se pudieron obtener los eventos",
+        Text::SearchFailed => "No se pudieron buscar los eventos",
+        Text::AdminCheckFailed => "No se pudo comprobar si eres administrador de esos chats",
+        Text::ChannelNotUpdated => "No se pudo actualizar el canal, ¿ya se inicializó?",
+        Text::UnbanFailed => "No se pudo desbloquear al usuario",
+        Text::BanFailed => "No se pudo bloquear al usuario, ¿ya se inicializó el canal?",
+        Text::GreetingFailed => "No se pudo publicar el saludo",
+        Text::ChannelNotFound => "No se encontró el canal, ¿ya se inicializó?",
+        Text::InitFailed => "No se pudo inicializar el chat",
+        Text::NewLinkFailed => "No se pudo generar el enlace para el evento",
+        Text::EditLinkFailed => "No se pudo generar el enlace para editar",
+        Text::WebhookAddFailed => "No se pudo añadir el webhook, ¿ya se inicializó el canal?",
+        Text::RotateAdminsOnly => "Solo los administradores del canal pueden reemplazar su secreto de webhooks",
+        Text::WebhookRemoveFailed => "No se pudo quitar el webhook",
+        Text::WebhooksLookupFailed => "No se pudieron consultar los webhooks",
+        Text::CreateEventFailed => "No se pudo crear el evento",
+        Text::RsvpFailed => "No se pudo confirmar tu asistencia",
+        Text::CancelRsvpFailed => "No se pudo cancelar tu asistencia",
+        Text::EventLookupFailed => "No se pudo consultar el evento",
+        Text::NotificationsLookupFailed => "No se pudieron consultar tus notificaciones",
+        Text::NothingStored => "El bot no tiene nada guardado sobre ti",
+        Text::BroadcastFailed => "No se pudo escribir a los asistentes",
+        Text::DeleteEventFailed => "No se pudo eliminar el evento",
+        Text::CancelEventFailed => "No se pudo cancelar el evento",
+        Text::TransferFailed => "No se pudo transferir el evento",
+        Text::ReviewFailed => "No se pudo revisar el evento, ¿ya lo revisó otro administrador?",
+        Text::AdminChannelsLookupFailed => "No se encontraron tus canales",
+        Text::TokenAdminsOnly => "Solo los administradores del canal pueden obtener su token de API",
+        Text::SecretAdminsOnly => "Solo los administradores del canal pueden obtener su secreto de webhooks",
+        Text::PoolStatusFailed => "No se pudieron comprobar las conexiones a la base de datos",
+        Text::FailedMessagesLookupFailed => "No se pudieron consultar los mensajes que no se enviaron",
+        Text::ReplayFailed => "No se pudo volver a enviar el mensaje, ¿se eliminó el evento?",
+        Text::CalendarFailed => "No se pudo crear un enlace al calendario",
+        Text::TokenFailed => "No se pudo crear un token",
+        Text::SecretFailed => "No se pudo crear un secreto",
+        Text::NotLinked => "Solo puedes crear eventos en canales vinculados a un chat en el que estés. Si crees que es un error, envía primero un mensaje en el chat y luego inténtalo de nuevo",
+        Text::NotPrivate => "Envía este comando en un mensaje privado",
+        Text::NoChannels => "No estás en ningún chat con un canal de eventos asociado. Si crees que es un error, envía primero un mensaje en el chat asociado y luego inténtalo de nuevo",
+        Text::NoAdminChannels => "No eres administrador de ningún canal vinculado a un chat en el que estés",
+        Text::PickChannelForNew => "¿Para qué canal quieres crear un evento?",
+        Text::ConfirmNewEvent => "¿Quieres crear un evento para este canal?",
+        Text::PickEventToEdit => "¿Qué evento quieres editar?",
+        Text::PickEventToDelete => "¿Qué evento quieres eliminar?",
+        Text::PickEventToCancel => "¿Qué evento quieres cancelar?",
+        Text::PickEventToTransfer => "¿Qué evento quieres transferir?",
+        Text::PickEventToBroadcast => "¿A los asistentes de qué evento quieres escribir?",
+        Text::PickChannelForToken => "¿Para qué canal quieres un nuevo token de API?",
+        Text::PickChannelForReadToken => "¿Para qué canal quieres un nuevo token de API de solo lectura?",
+        Text::PickChannelForSecret => "¿De qué canal quieres el secreto de webhooks?",
+        Text::PickChannelForRotate => "¿De qué canal quieres reemplazar el secreto de webhooks?",
+        Text::NoTitle => "Sin título",
+        Text::MuteUsage => "Uso: {} [reminders|broadcasts|hosting|waitlist]",
+        Text::Preferences => "{}\n\nDesactiva o activa una con /mute o /unmute, o revísalas con /notifications",
+        Text::On => "activado",
+        Text::Off => "desactivado",
+        Text::NotificationsMenu => "Toca un tipo de mensaje para activarlo o desactivarlo",
+        Text::RemindersLabel => "Recordatorios",
+        Text::BroadcastsLabel => "Mensajes de los anfitriones",
+        Text::HostNudgesLabel => "Novedades de los eventos que organizas",
+        Text::WaitlistLabel => "Lugares libres en la lista de espera",
+        Text::LanguageUsage => "Uso: /language [código]\n\n{}",
+        Text::UserLanguageSet => "Tus eventos se mostrarán en {}",
+        Text::UserTimeFormatSet => "Tus eventos se mostrarán con horas en formato de {} horas",
+        Text::TimeFormatUsage => "Uso: /timeformat [12|24]",
+        Text::UpdatesAdminsOnly => "Solo los administradores del bot pueden cambiar cómo recibe actualizaciones",
+        Text::ReceivingUpdates => "Recibiendo actualizaciones por {}",
+        Text::SwitchingUpdates => "Cambiando a {} para las actualizaciones",
+        Text::UpdatesUsage => "Uso: /updates [polling|webhook]",
+        Text::SnoozeAdminsOnly => "Solo los administradores del bot pueden posponer las alertas",
+        Text::Snoozed => "Las alertas están pospuestas por {} minutos",
+        Text::Unsnoozed => "Las alertas ya no están pospuestas",
+        Text::SnoozeUsage => "Uso: /snooze [minutos|off]",
+        Text::FailedAdminsOnly => "Solo los administradores del bot pueden ver los mensajes que no se enviaron",
+        Text::FailedUsage => "Uso: /failed [replay id]",
+        Text::PoolAdminsOnly => "Solo los administradores del bot pueden comprobar sus conexiones a la base de datos",
+        Text::QuickInPast => "Ese evento empieza en el pasado, elige una hora posterior",
+        Text::QuickPickChannel => "{}\n\n¿En qué canal se debe crear este evento?",
+        Text::QuickUsage => "No entendí ese evento, prueba algo como /quick Pizza night tomorrow 7pm-9pm",
+        Text::QuickExpired => "Ese evento caducó, usa /quick de nuevo",
+        Text::TokenUsage => "Uso: /token o /token read",
+        Text::WebhookPrivateUsage => "Los webhooks se añaden en los canales. Envía /webhook secret para obtener el secreto con el que se firman los webhooks de un canal, o /webhook rotate para reemplazarlo",
+        Text::UpgradeToSupergroup => "Convierte este grupo en un supergrupo antes de vincularlo",
+        Text::NotSupergroup => "No se puede vincular un chat que no es un supergrupo",
+        Text::FetchOnlyInSupergroup => "Solo se pueden obtener eventos en un supergrupo",
+        Text::PinOnlyInSupergroup => "Solo se pueden fijar eventos en un supergrupo",
+        Text::NextEvent => "Próximo evento:",
+        Text::TodaysEvents => "Eventos de hoy:",
+        Text::NoEventsToday => "No hay eventos hoy",
+        Text::EventsMatching => "Eventos que coinciden con {}:",
+        Text::NoEventsMatch => "Ningún próximo evento coincide con {}",
+        Text::SearchUsage => "Uso: /search [términos], por ejemplo /search pizza",
+        Text::SearchOnlyInGroups => "Solo se pueden buscar eventos en un supergrupo o en un chat privado",
+        Text::ChannelsOnly => "El comando {} solo se puede usar en canales",
+        Text::Initialized => "Inicializado",
+        Text::InitBeforeLinking => "Usa /init en el canal antes de vincularlo",
+        Text::Linked => "Canal '{}' vinculado a los chats ({})",
+        Text::Protected => "Los nuevos anuncios estarán protegidos contra el reenvío",
+        Text::Unprotected => "Los nuevos anuncios se pueden reenviar",
+        Text::ChannelLanguageSet => "Los eventos se anunciarán en {}",
+        Text::ChannelTimeFormatSet => "Los eventos se anunciarán con horas en formato de {} horas",
+        Text::Moderated => "Los nuevos eventos de anfitriones que no son administradores esperarán la aprobación de un administrador",
+        Text::Unmoderated => "Los nuevos eventos se anunciarán sin esperar aprobación",
+        Text::UnbanUsage => "Uso: /unban [@usuario|id de usuario]",
+        Text::HostUnbanned => "{} puede volver a crear eventos",
+        Text::NotBanned => "{} no estaba bloqueado",
+        Text::BanUsage => "Uso: /ban [@usuario|id de usuario]",
+        Text::BanUsernameNotFound => "No encontré a {} en los chats de este canal, o ya está bloqueado. Los usuarios que no han escrito en los chats se pueden bloquear por su id de usuario",
+        Text::AlreadyBanned => "{} ya está bloqueado",
+        Text::HostBanned => "{} ya no puede crear eventos en este canal",
+        Text::UserById => "Usuario {}",
+        Text::DigestDaySet => "El resumen semanal se publicará cada {}",
+        Text::DigestOff => "El resumen semanal está desactivado",
+        Text::DigestUsage => "Uso: /digest [día|off], por ejemplo /digest monday",
+        Text::DigestPinned => "El resumen semanal se fijará",
+        Text::DigestUnpinned => "El resumen semanal no se fijará",
+        Text::AnnouncementsPinned => "Los nuevos anuncios se fijarán hasta que termine su evento",
+        Text::AnnouncementsUnpinned => "Los nuevos anuncios no se fijarán",
+        Text::Listed => "Este canal y sus eventos públicos ahora aparecen en {}",
+        Text::Unlisted => "Este canal ya no aparece en el directorio",
+        Text::GreetingOff => "El saludo está desactivado",
+        Text::GreetingUsage => "Uso: /setgreeting [texto|off], por ejemplo /setgreeting ¡Bienvenidos a nuestros eventos!",
+        Text::Templates => "{}\n\nCambia una con /template [announcement|reminder|ended] [texto|default]. Las plantillas pueden usar {}",
+        Text::DefaultTemplate => "(predeterminada)",
+        Text::TemplateSet => "La plantilla {} está configurada",
+        Text::TemplateReset => "La plantilla {} volvió a la predeterminada",
+        Text::UnknownPlaceholder => "{{}} no es un marcador, prueba uno de {}",
+        Text::TemplateUsage => "Uso: /template [announcement|reminder|ended] [texto|default], por ejemplo /template reminder ¡{title} empieza pronto, nos vemos allí!",
+        Text::Features => "{}\n\nActiva o desactiva una con /features [rsvp|digest|public_page] [on|off]",
+        Text::FeaturesUsage => "Uso: /features [rsvp|digest|public_page] [on|off]",
+        Text::BoostSet => "Los eventos con menos de {} confirmaciones dos días antes de empezar se anunciarán de nuevo",
+        Text::BoostOff => "Los eventos no se anunciarán de nuevo",
+        Text::BoostUsage => "Uso: /boost [número|off], por ejemplo /boost 5",
+        Text::Stats => "Eventos creados este mes: {}\nAsistencia media: {}\nChats vinculados: {}\nUsuarios en esos chats: {}\n\nAnfitriones más activos:\n{}",
+        Text::People => "{} personas",
+        Text::NoEventsEnded => "Todavía no ha terminado ningún evento",
+        Text::NoHostsYet => "Nadie ha organizado un evento todavía",
+        Text::NoUsername => "Alguien sin nombre de usuario: {}",
+        Text::TokenInChannel => "Los tokens de API solo se envían en privado. Envíame /token en un chat privado para obtener uno para este canal",
+        Text::SecretInChannel => "Los secretos de webhooks solo se envían en privado. Envíame /webhook secret o /webhook rotate en un chat privado",
+        Text::CalendarLink => "El calendario de este canal está en {}/events/calendar/{}={}\n\nLos enlaces anteriores al calendario ya no funcionan.",
+        Text::WebhookUsage => "Uso: /webhook [add|remove] [url]",
+        Text::TokenIssued => "El nuevo token de API del canal es {}\n\nLos calendarios externos pueden añadir eventos al canal enviándolos a {}/api/v1/channels/{}/events con el token en un encabezado \"Authorization: Bearer\". Los sitios web que solo muestran los eventos del canal deberían usar un token de solo lectura de /token read.\n\nLos tokens anteriores ya no funcionan. Elimina este mensaje cuando hayas guardado el token en un lugar seguro.",
+        Text::ReadTokenIssued => "El nuevo token de API de solo lectura del canal es {}\n\nLos sitios web pueden listar los eventos del canal desde {}/api/v1/channels/{}/events y obtener eventos individuales desde {}/api/v1/events/{id}. Envía el token en un encabezado \"Authorization: Bearer\". No puede añadir eventos.\n\nLos tokens de solo lectura anteriores ya no funcionan.",
+        Text::WebhookScheme => "Las URL de los webhooks deben empezar con http:// o https://",
+        Text::WebhookPublic => "Los webhooks solo se pueden enviar a direcciones públicas",
+        Text::WebhookAdded => "{} recibirá avisos cuando cambien los eventos\n\nLas solicitudes se firman con el secreto de webhooks de este canal. Envíame /webhook secret en un chat privado para obtenerlo. {} explica cómo comprobar las firmas.",
+        Text::SecretRotated => "Los webhooks del canal ahora se firman con el secreto {}\n\nElimina este mensaje cuando hayas guardado el secreto en un lugar seguro.",
+        Text::SharedSecret => "Los webhooks del canal se firman con el secreto {}",
+        Text::WebhookSecrets => "Los webhooks del canal se firman con estos secretos:",
+        Text::SaveSecret => "{}\n\nElimina este mensaje cuando hayas guardado el secreto en un lugar seguro.",
+        Text::NoWebhooks => "Este canal no tiene webhooks",
+        Text::WebhookRemoved => "{} ya no recibirá avisos",
+        Text::NotAWebhook => "{} no es un webhook de este canal",
+        Text::WebhookList => "Webhooks de este canal:\n{}",
+        Text::NoFailedMessages => "No hay mensajes que no se hayan podido enviar",
+        Text::FailedAnnouncement => "Anuncio",
+        Text::FailedMessage => "Mensaje",
+        Text::FailedMessageLine => "{}. {} del evento {} al chat {}, {}\n{}\n{}",
+        Text::FailedMessages => "Mensajes que no se pudieron enviar, los más recientes primero:\n\n{}\n\nEnvía /failed replay [id] para volver a enviar uno",
+        Text::Replaying => "Enviando de nuevo el mensaje {}",
+        Text::PoolStatus => "Conexiones a la base de datos:\nLibres: {}\nEn uso: {}\nConectando: {}\nSolicitudes en espera: {}",
+        Text::NewEventLink => "Usa este enlace para crear tu evento: {}\nEste enlace caduca a las {}",
+        Text::EditEventLink => "Usa este enlace para actualizar tu evento: {}\nEste enlace caduca a las {}",
+        Text::NewEventLinkExpired => "Tu enlace para crear tu evento caducó",
+        Text::EditEventLinkExpired => "Tu enlace para actualizar tu evento caducó",
+        Text::NewLink => "Generar un enlace nuevo",
+        Text::Created => "¡{} creado!",
+        Text::CreatedPending => "¡{} creado! Se anunciará cuando los administradores del canal lo aprueben",
+        Text::EventDeletedReply => "¡Evento eliminado!",
+        Text::EventDeletedNotice => "Evento eliminado: {}",
+        Text::EventCancelledReply => "¡Evento cancelado!",
+        Text::EventCancelledNotice => "Evento cancelado: {}",
+        Text::EventUpdated => "¡Evento actualizado!",
+        Text::UpcomingEventsAsOf => "Próximos eventos, a las {} {}:\n\n{}",
+        Text::EditButton => "Editar {}",
+        Text::DeleteButton => "Eliminar {}",
+        Text::GoingButton => "Voy",
+        Text::CantGoButton => "No puedo ir",
+        Text::Going => "¡Vas a ir a {}!{}",
+        Text::Waitlisted => "{} está lleno, te añadimos a la lista de espera",
+        Text::AlreadyResponded => "Ya respondiste a {}",
+        Text::RsvpCancelled => "{} se canceló",
+        Text::RsvpsClosed => "Lo siento, las confirmaciones para {} ya cerraron",
+        Text::RsvpsOff => "Las confirmaciones están desactivadas en este canal",
+        Text::RsvpMembersOnly => "{} es solo para miembros, tienes que estar en uno de sus grupos vinculados para confirmar tu asistencia",
+        Text::NotGoing => "Ya no vas a ir a {}",
+        Text::Promoted => "¡Se liberó un lugar! Ahora vas a ir a {}{}",
+        Text::DeleteIt => "Eliminarlo",
+        Text::CancelInstead => "Mejor cancelarlo",
+        Text::ConfirmDelete => "¿Seguro que quieres eliminar {}?\n",
+        Text::NoRsvps => "\nNadie ha confirmado su asistencia todavía.",
+        Text::PersonIs => "persona va",
+        Text::PeopleAre => "personas van",
+        Text::RsvpsGoing => "\n{}: {}",
+        Text::RsvpsWaitlisted => "\n{} en la lista de espera: {}",
+        Text::RsvpsDeleted => "\nSus confirmaciones se eliminarán con el evento.",
+        Text::CancelInsteadHint => "\n\nSi lo cancelas, se conservan el evento y sus confirmaciones, y todos sabrán que no se hará.",
+        Text::ReallyDelete => "¿De verdad quieres eliminar '{}'? Esto no se puede deshacer.",
+        Text::Yes => "Sí",
+        Text::No => "No",
+        Text::ReallyForget => "¿De verdad quieres que el bot olvide todo lo que sabe de ti? Te quitaremos de los eventos a los que vas y que organizas. Esto no se puede deshacer.",
+        Text::ExportCaption => "Aquí está todo lo que el bot tiene guardado sobre ti",
+        Text::Forgotten => "Listo, el bot te olvidó. Eliminó:\n\n{} membresías de chats\n{} registros como anfitrión\n{} confirmaciones de asistencia\n{} enlaces para crear o editar eventos\n\nSi sigues escribiendo en chats que usan este bot, empezará a recordarte de nuevo.",
+        Text::NothingChanged => "De acuerdo, no se cambió nada",
+        Text::Nevermind => "No importa",
+        Text::AskBroadcast => "¿Qué quieres decirles a todos los que van a {}? Tu próximo mensaje se les enviará.",
+        Text::BroadcastTooSoon => "Solo se puede escribir a los asistentes una vez cada {} minutos, inténtalo de nuevo en {} minutos",
+        Text::BroadcastSending => "Enviando tu mensaje a las {} personas que van a {}",
+        Text::BroadcastNobody => "Ahora mismo no se puede escribir a nadie que vaya a {}",
+        Text::BroadcastMessage => "Un mensaje del anfitrión de {}:\n\n{}\n\nEnvía /mute broadcasts para dejar de recibir mensajes de los anfitriones",
+        Text::PickNewHost => "¿Quién debería organizar {}?",
+        Text::NoNewHosts => "Nadie más en los chats de {} puede organizarlo",
+        Text::MadeHost => "@{} te hizo anfitrión de {}",
+        Text::Transferred => "¡{} transferido!",
+        Text::StartingSoon => "Empieza pronto:\n\n{}",
+        Text::AttendeeReminder => "Recordatorio: {} empieza a las {}\n\nEnvía /mute reminders para dejar de recibir recordatorios",
+        Text::AwaitingApproval => "Esperando aprobación",
+        Text::Approve => "Aprobar",
+        Text::TurnDown => "Rechazar",
+        Text::Approved => "{} aprobado",
+        Text::TurnedDown => "{} rechazado",
+        Text::PendingApproval => "{} se anunciará cuando los administradores del canal lo aprueben",
+        Text::ApprovedNotice => "Los administradores del canal aprobaron {}",
+        Text::TurnedDownNotice => "Los administradores del canal rechazaron {}",
+        Text::DefaultReminder => "¡No lo olvides! ¡{title} empieza pronto!",
+        Text::DefaultStarted => "¡{title} ha empezado!",
+        Text::DefaultEnded => "¡{title} ha terminado!",
+        Text::HowItWorks => "\n\nCómo funciona este canal:\n- Aquí se anuncian los próximos eventos. Toca \"Voy\" en un anuncio para confirmar tu asistencia.\n- Los miembros de los grupos vinculados pueden crear eventos enviando /new al bot en un chat privado.\n- Envía /events en un grupo vinculado para ver lo que viene.",
+        Text::GreetingDigestPinned => "\n- Cada {} se publica y se fija un resumen de los eventos de la semana.",
+        Text::GreetingDigest => "\n- Cada {} se publica un resumen de los eventos de la semana.",
+        Text::GreetingPinned => "\n- Los nuevos anuncios se fijan hasta que termina su evento.",
+        Text::Help => "Event Bot es un bot de Telegram que ayuda a los grupos a organizar eventos.\n\nEn los grupos, están disponibles estos comandos:\n{}\n\nEn los chats privados, están disponibles estos comandos:\n{}\n\nSi eres administrador y quieres añadir este bot a un chat, te interesarán estos comandos:\n{}\n\nTen en cuenta que este bot solo funciona en supergrupos, no en grupos normales.\n\nSi tienes preguntas o necesitas ayuda para configurar o usar el bot, escribe a @asonix\n\nEste bot se publica bajo la Licencia Pública General de GNU, versión 3 o posterior. Si quieres una copia del código, búscala aquí:\nhttp://github.com/asonix/telegram-event-bot\n",
+        Text::HelpUsage => "uso",
+        Text::HelpEvents => "muestra los eventos del chat actual",
+        Text::HelpPinEvents => "fija una lista de los próximos eventos en el grupo actual",
+        Text::HelpNext => "muestra el próximo evento del chat actual",
+        Text::HelpToday => "muestra los eventos que empiezan hoy en el chat actual",
+        Text::HelpSearch => "busca entre los próximos eventos del chat actual",
+        Text::HelpNew => "Crea un evento nuevo",
+        Text::HelpQuick => "Crea un evento a partir de una descripción",
+        Text::HelpEdit => "Edita un evento que organizas",
+        Text::HelpCancel => "Cancela un evento que organizas, conservándolo junto con sus confirmaciones",
+        Text::HelpTransfer => "Pasa un evento que organizas a otra persona",
+        Text::HelpDelete => "Elimina un evento que organizas",
+        Text::HelpMyEvents => "Muestra los eventos que organizas",
+        Text::HelpAnnounce => "Envía un mensaje a todos los que van a un evento que organizas",
+        Text::HelpNotifications => "Elige qué mensajes privados recibes del bot",
+        Text::HelpMute => "Deja de recibir mensajes privados del bot",
+        Text::HelpUnmute => "Vuelve a recibir mensajes privados del bot",
+        Text::HelpLanguage => "Elige el idioma en el que se muestran tus eventos",
+        Text::HelpTimeFormat => "Elige si tus eventos se muestran con horas en formato de 12 o de 24 horas",
+        Text::HelpExportMe => "Obtén una copia de todo lo que el bot sabe de ti",
+        Text::HelpForgetMe => "Elimina todo lo que el bot sabe de ti, incluidas tus confirmaciones",
+        Text::HelpSearchPrivate => "Busca entre los próximos eventos de todos tus canales",
+        Text::HelpHelp => "Muestra este mensaje de ayuda",
+        Text::HelpInit => "Inicializa un canal de eventos",
+        Text::HelpLink => "en un canal de eventos, vincula un grupo",
+        Text::HelpProtect => "en un canal de eventos, activa o desactiva el reenvío de los anuncios",
+        Text::HelpModerate => "en un canal de eventos, activa o desactiva que los eventos de anfitriones que no son administradores necesiten la aprobación de un administrador antes de anunciarse",
+        Text::HelpChannelLanguage => "en un canal de eventos, elige el idioma en el que se anuncian los eventos",
+        Text::HelpChannelTimeFormat => "en un canal de eventos, elige si los eventos se anuncian con horas en formato de 12 o de 24 horas",
+        Text::HelpBan => "en un canal de eventos, impide que un usuario cree eventos en él",
+        Text::HelpUnban => "en un canal de eventos, permite que un usuario bloqueado vuelva a crear eventos",
+        Text::HelpDigest => "en un canal de eventos, elige el día en que se publica el resumen semanal",
+        Text::HelpPinDigest => "en un canal de eventos, activa o desactiva que se fije el resumen semanal",
+        Text::HelpPinAnnouncements => "en un canal de eventos, activa o desactiva que se fijen los anuncios de eventos nuevos",
+        Text::HelpDirectory => "en un canal de eventos, activa o desactiva que el canal y sus eventos públicos aparezcan en el directorio de la web",
+        Text::HelpSetGreeting => "en un canal de eventos, define una presentación que el bot fija y mantiene al día",
+        Text::HelpTemplate => "en un canal de eventos, muestra o cambia los mensajes de anuncio, recordatorio y fin",
+        Text::HelpFeatures => "en un canal de eventos, muestra, activa o desactiva las confirmaciones, el resumen semanal y las páginas públicas",
+        Text::HelpBoost => "en un canal de eventos, vuelve a anunciar los eventos dos días antes de empezar si han confirmado menos personas que esta cantidad",
+        Text::HelpStats => "en un canal de eventos, muestra cuántos eventos se crearon este mes, los anfitriones más activos, la asistencia media y cuántos chats y usuarios están vinculados",
+        Text::HelpToken => "en un chat privado, crea un token que permite a otros sitios web y bots añadir eventos a un canal que administras, o un token de solo lectura que les permite listar sus eventos",
+        Text::HelpCalendar => "en un canal de eventos, crea un enlace a un calendario con los eventos públicos del canal",
+        Text::HelpWebhook => "en un canal de eventos, muestra, añade o quita las URL que reciben avisos cuando cambian los eventos. En un chat privado, obtén o reemplaza el secreto con el que se firman",
+        Text::HelpId => "muestra el id de un grupo",
+        Text::HelpUpdates => "cambia cómo recibe el bot las actualizaciones de Telegram",
+        Text::HelpPool => "comprueba las conexiones del bot a la base de datos",
+        Text::HelpSnooze => "pospone las alertas por un rato",
+        Text::HelpFailed => "muestra los mensajes que no se enviaron, o vuelve a enviar uno",
+    }
+}
+
+fn german(text: Text) -> &'static str {
+    match text {
+        Text::Channel => "Kanal",
+        Text::When => "Wann",
+        Text::HappeningNow => "Läuft gerade, endet",
+        Text::StartsIn => "noch",
+        Text::StartingNow => "beginnt gleich",
+        Text::At => "um",
+        Text::Ends => "Endet",
+        Text::Duration => "Dauer",
+        Text::Where => "Wo",
+        Text::HiddenLocation => "den genauen Ort bekommst du, wenn du zusagst",
+        Text::Map => "Karte",
+        Text::Description => "Beschreibung",
+        Text::Hosts => "Gastgeber",
+        Text::Capacity => "Plätze",
+        Text::MembersOnly => "Nur für Mitglieder",
+        Text::MoreInfo => "Mehr Infos",
+        Text::Week => "Woche",
+        Text::Weeks => "Wochen",
+        Text::Day => "Tag",
+        Text::Days => "Tage",
+        Text::Hour => "Stunde",
+        Text::Hours => "Stunden",
+        Text::Minute => "Minute",
+        Text::Minutes => "Minuten",
+        Text::NoTime => "Keine Zeit",
+        Text::EventHeading => "----Event----",
+        Text::CancelledHeading => "----Abgesagt----",
+        Text::NewEvent => "Neues Event!",
+        Text::StillRoom => "Es gibt noch Plätze!",
+        Text::Cancelled => "ABGESAGT",
+        Text::ThisWeeksEvents => "Events dieser Woche:",
+        Text::UpcomingEvents => "Kommende Events:",
+        Text::NoUpcomingEvents => "Keine kommenden Events",
+        Text::YourEvents => "Deine Events:",
+        Text::NotHosting => "Du bist bei keinem Event Gastgeber",
+        Text::UnknownChannel => "Unbekannter Kanal",
+        Text::Start => "Beginn",
+        Text::End => "Ende",
+        Text::Location => "Ort",
+        Text::ViewMap => "Auf OpenStreetMap ansehen",
+        Text::RegistrationClosed => "Anmeldung geschlossen",
+        Text::RsvpsClose => "Anmeldeschluss",
+        Text::Busy => "Der Bot ist gerade beschäftigt, bitte versuch es gleich noch einmal",
+        Text::Banned => "Die Admins des Kanals haben dir verboten, Events dafür zu erstellen",
+        Text::Quota => "Du bist schon Gastgeber von so vielen kommenden Events in diesem Kanal, wie der Bot erlaubt",
+        Text::NotAdmin => "Dafür muss ich Admin des Kanals sein. Mach mich zum Admin und versuch es noch einmal",
+        Text::ChannelRights => "Ich muss Admin dieses Kanals sein und Nachrichten posten und bearbeiten dürfen. Gib mir diese Rechte in den Admin-Einstellungen des Kanals und versuch es noch einmal",
+        Text::ChatNotFound => "Ich konnte diesen Chat nicht finden. Prüf seine ID, stell sicher, dass ich hinzugefügt wurde, und versuch es noch einmal",
+        Text::Blocked => "Ich kann dir keine privaten Nachrichten schicken. Öffne einen privaten Chat mit mir, sende /start und versuch es noch einmal",
+        Text::TooLong => "Das ist zu lang für eine Telegram-Nachricht. Versuch es noch einmal mit einer kürzeren Beschreibung",
+        Text::ChannelsLookupFailed => "Deine Event-Kanäle konnten nicht geladen werden",
+        Text::EventsLookupFailed => "Deine Events konnten nicht geladen werden",
+        Text::UserNotFound => "Ich konnte dich nicht finden, hast du schon in einer deiner Gruppen geschrieben?",
+        Text::FetchEventsFailed => "Die Events konnten nicht geladen werden",
+        Text::SearchFailed => "Die Events konnten nicht durchsucht werden",
+        Text::AdminCheckFailed => "Es konnte nicht geprüft werden, ob du Admin dieser Chats bist",
+        Text::ChannelNotUpdated => "Der Kanal konnte nicht aktualisiert werden, wurde er schon initialisiert?",
+        Text::UnbanFailed => "Die Sperre konnte nicht aufgehoben werden",
+        Text::BanFailed => "Der Nutzer konnte nicht gesperrt werden, wurde der Kanal schon initialisiert?",
+        Text::GreetingFailed => "Die Begrüßung konnte nicht gepostet werden",
+        Text::ChannelNotFound => "Der Kanal wurde nicht gefunden, wurde er schon initialisiert?",
+        Text::InitFailed => "Der Chat konnte nicht initialisiert werden",
+        Text::NewLinkFailed => "Der Link für das Event konnte nicht erstellt werden",
+        Text::EditLinkFailed => "Der Link zum Bearbeiten konnte nicht erstellt werden",
+        Text::WebhookAddFailed => "Der Webhook konnte nicht hinzugefügt werden, wurde der Kanal schon initialisiert?",
+        Text::RotateAdminsOnly => "Nur die Admins des Kanals können sein Webhook-Geheimnis ersetzen",
+        Text::WebhookRemoveFailed => "Der Webhook konnte nicht entfernt werden",
+        Text::WebhooksLookupFailed => "Die Webhooks konnten nicht geladen werden",
+        Text::CreateEventFailed => "Das Event konnte nicht erstellt werden",
+        Text::RsvpFailed => "Deine Zusage konnte nicht gespeichert werden",
+        Text::CancelRsvpFailed => "Deine Zusage konnte nicht zurückgezogen werden",
+        Text::EventLookupFailed => "Das Event konnte nicht geladen werden",
+        Text::NotificationsLookupFailed => "Deine Benachrichtigungen konnten nicht geladen werden",
+        Text::NothingStored => "Der Bot hat nichts über dich gespeichert",
+        Text::BroadcastFailed => "Die Teilnehmer konnten nicht benachrichtigt werden",
+        Text::DeleteEventFailed => "Das Event konnte nicht gelöscht werden",
+        Text::CancelEventFailed => "Das Event konnte nicht abgesagt werden",
+        Text::TransferFailed => "Das Event konnte nicht übergeben werden",
+        Text::ReviewFailed => "Das Event konnte nicht geprüft werden, hat es schon ein anderer Admin getan?",
+        Text::AdminChannelsLookupFailed => "Deine Kanäle wurden nicht gefunden",
+        Text::TokenAdminsOnly => "Nur die Admins des Kanals können sein API-Token bekommen",
+        Text::SecretAdminsOnly => "Nur die Admins des Kanals können sein Webhook-Geheimnis bekommen",
+        Text::PoolStatusFailed => "Die Datenbankverbindungen konnten nicht geprüft werden",
+        Text::FailedMessagesLookupFailed => "Die Nachrichten, die nicht gesendet werden konnten, konnten nicht geladen werden",
+        Text::ReplayFailed => "Die Nachricht konnte nicht erneut gesendet werden, wurde das Event gelöscht?",
+        Text::CalendarFailed => "Der Kalender-Link konnte nicht erstellt werden",
+        Text::TokenFailed => "Das Token konnte nicht erstellt werden",
+        Text::SecretFailed => "Das Geheimnis konnte nicht erstellt werden",
+        Text::NotLinked => "Du kannst nur Events für Kanäle erstellen, die mit einem deiner Chats verknüpft sind. Wenn du glaubst, dass das ein Fehler ist, schreib zuerst etwas in den Chat und versuch es dann noch einmal",
+        Text::NotPrivate => "Bitte sende diesen Befehl als private Nachricht",
+        Text::NoChannels => "Du bist in keinem Chat mit einem zugehörigen Event-Kanal. Wenn du glaubst, dass das ein Fehler ist, schreib zuerst etwas in den zugehörigen Chat und versuch es dann noch einmal",
+        Text::NoAdminChannels => "Du bist kein Admin eines Kanals, der mit einem deiner Chats verknüpft ist",
+        Text::PickChannelForNew => "Für welchen Kanal möchtest du ein Event erstellen?",
+        Text::ConfirmNewEvent => "Möchtest du ein Event für diesen Kanal erstellen?",
+        Text::PickEventToEdit => "Welches Event möchtest du bearbeiten?",
+        Text::PickEventToDelete => "Welches Event möchtest du löschen?",
+        Text::PickEventToCancel => "Welches Event möchtest du absagen?",
+        Text::PickEventToTransfer => "Welches Event möchtest du übergeben?",
+        Text::PickEventToBroadcast => "Welchen Teilnehmern welches Events möchtest du schreiben?",
+        Text::PickChannelForToken => "Für welchen Kanal möchtest du ein neues API-Token?",
+        Text::PickChannelForReadToken => "Für welchen Kanal möchtest du ein neues API-Token nur zum Lesen?",
+        Text::PickChannelForSecret => "Das Webhook-Geheimnis welches Kanals möchtest du?",
+        Text::PickChannelForRotate => "Das Webhook-Geheimnis welches Kanals möchtest du ersetzen?",
+        Text::NoTitle => "Kein Titel",
+        Text::MuteUsage => "Verwendung: {} [reminders|broadcasts|hosting|waitlist]",
+        Text::Preferences => "{}\n\nSchalte eine mit /mute oder /unmute aus oder ein, oder tippe dich mit /notifications durch",
+        Text::On => "an",
+        Text::Off => "aus",
+        Text::NotificationsMenu => "Tippe auf eine Art von Nachricht, um sie ein- oder auszuschalten",
+        Text::RemindersLabel => "Erinnerungen",
+        Text::BroadcastsLabel => "Nachrichten von Gastgebern",
+        Text::HostNudgesLabel => "Neuigkeiten zu deinen Events als Gastgeber",
+        Text::WaitlistLabel => "Freie Plätze auf der Warteliste",
+        Text::LanguageUsage => "Verwendung: /language [Code]\n\n{}",
+        Text::UserLanguageSet => "Deine Events werden auf {} angezeigt",
+        Text::UserTimeFormatSet => "Deine Events werden mit Uhrzeiten im {}-Stunden-Format angezeigt",
+        Text::TimeFormatUsage => "Verwendung: /timeformat [12|24]",
+        Text::UpdatesAdminsOnly => "Nur die Admins des Bots können ändern, wie er Updates bekommt",
+        Text::ReceivingUpdates => "Updates kommen per {}",
+        Text::SwitchingUpdates => "Wechsle für Updates zu {}",
+        Text::UpdatesUsage => "Verwendung: /updates [polling|webhook]",
+        Text::SnoozeAdminsOnly => "Nur die Admins des Bots können Warnungen pausieren",
+        Text::Snoozed => "Warnungen sind für {} Minuten pausiert",
+        Text::Unsnoozed => "Warnungen sind nicht mehr pausiert",
+        Text::SnoozeUsage => "Verwendung: /snooze [Minuten|off]",
+        Text::FailedAdminsOnly => "Nur die Admins des Bots können Nachrichten sehen, die nicht gesendet werden konnten",
+        Text::FailedUsage => "Verwendung: /failed [replay ID]",
+        Text::PoolAdminsOnly => "Nur die Admins des Bots können seine Datenbankverbindungen prüfen",
+        Text::QuickInPast => "Dieses Event beginnt in der Vergangenheit, bitte wähle eine spätere Zeit",
+        Text::QuickPickChannel => "{}\n\nIn welchem Kanal soll dieses Event erstellt werden?",
+        Text::QuickUsage => "Ich habe dieses Event nicht verstanden, versuch etwas wie /quick Pizza night tomorrow 7pm-9pm",
+        Text::QuickExpired => "Dieses Event ist abgelaufen, bitte benutze /quick noch einmal",
+        Text::TokenUsage => "Verwendung: /token oder /token read",
+        Text::WebhookPrivateUsage => "Webhooks werden in Kanälen hinzugefügt. Sende /webhook secret, um das Geheimnis zu bekommen, mit dem die Webhooks eines Kanals signiert werden, oder /webhook rotate, um es zu ersetzen",
+        Text::UpgradeToSupergroup => "Bitte mach diese Gruppe vor dem Verknüpfen zu einer Supergruppe",
+        Text::NotSupergroup => "Nur Supergruppen können verknüpft werden",
+        Text::FetchOnlyInSupergroup => "Events können nur in einer Supergruppe abgerufen werden",
+        Text::PinOnlyInSupergroup => "Events können nur in einer Supergruppe angeheftet werden",
+        Text::NextEvent => "Nächstes Event:",
+        Text::TodaysEvents => "Heutige Events:",
+        Text::NoEventsToday => "Heute keine Events",
+        Text::EventsMatching => "Events zu {}:",
+        Text::NoEventsMatch => "Keine kommenden Events passen zu {}",
+        Text::SearchUsage => "Verwendung: /search [Begriffe], zum Beispiel /search pizza",
+        Text::SearchOnlyInGroups => "Events können nur in einer Supergruppe oder einem privaten Chat durchsucht werden",
+        Text::ChannelsOnly => "Der Befehl {} kann nur in Kanälen verwendet werden",
+        Text::Initialized => "Initialisiert",
+        Text::InitBeforeLinking => "Bitte initialisiere den Kanal mit /init, bevor du ihn verknüpfst",
+        Text::Linked => "Kanal '{}' mit den Chats ({}) verknüpft",
+        Text::Protected => "Neue Ankündigungen sind vor dem Weiterleiten geschützt",
+        Text::Unprotected => "Neue Ankündigungen können weitergeleitet werden",
+        Text::ChannelLanguageSet => "Events werden auf {} angekündigt",
+        Text::ChannelTimeFormatSet => "Events werden mit Uhrzeiten im {}-Stunden-Format angekündigt",
+        Text::Moderated => "Neue Events von Gastgebern, die keine Admins sind, warten auf die Freigabe durch einen Admin",
+        Text::Unmoderated => "Neue Events werden ohne Freigabe angekündigt",
+        Text::UnbanUsage => "Verwendung: /unban [@Nutzername|Nutzer-ID]",
+        Text::HostUnbanned => "{} kann wieder Events erstellen",
+        Text::NotBanned => "{} war nicht gesperrt",
+        Text::BanUsage => "Verwendung: /ban [@Nutzername|Nutzer-ID]",
+        Text::BanUsernameNotFound => "{} wurde in den Chats dieses Kanals nicht gefunden oder ist schon gesperrt. Nutzer, die in den Chats noch nichts geschrieben haben, können über ihre Nutzer-ID gesperrt werden",
+        Text::AlreadyBanned => "{} ist schon gesperrt",
+        Text::HostBanned => "{} kann keine Events mehr für diesen Kanal erstellen",
+        Text::UserById => "Nutzer {}",
+        Text::DigestDaySet => "Die wöchentliche Übersicht wird jeden {} gepostet",
+        Text::DigestOff => "Die wöchentliche Übersicht ist aus",
+        Text::DigestUsage => "Verwendung: /digest [Tag|off], zum Beispiel /digest monday",
+        Text::DigestPinned => "Die wöchentliche Übersicht wird angeheftet",
+        Text::DigestUnpinned => "Die wöchentliche Übersicht wird nicht angeheftet",
+        Text::AnnouncementsPinned => "Neue Ankündigungen werden angeheftet, bis ihr Event endet",
+        Text::AnnouncementsUnpinned => "Neue Ankündigungen werden nicht angeheftet",
+        Text::Listed => "Dieser Kanal und seine öffentlichen Events sind jetzt unter {} aufgeführt",
+        Text::Unlisted => "Dieser Kanal ist nicht mehr im Verzeichnis aufgeführt",
+        Text::GreetingOff => "Die Begrüßung ist aus",
+        Text::GreetingUsage => "Verwendung: /setgreeting [Text|off], zum Beispiel /setgreeting Willkommen bei unseren Events!",
+        Text::Templates => "{}\n\nÄndere eine mit /template [announcement|reminder|ended] [Text|default]. Vorlagen können {} verwenden",
+        Text::DefaultTemplate => "(Standard)",
+        Text::TemplateSet => "Die Vorlage {} ist gesetzt",
+        Text::TemplateReset => "Die Vorlage {} ist wieder die Standardvorlage",
+        Text::UnknownPlaceholder => "{{}} ist kein Platzhalter, versuch einen von {}",
+        Text::TemplateUsage => "Verwendung: /template [announcement|reminder|ended] [Text|default], zum Beispiel /template reminder {title} beginnt bald, bis gleich!",
+        Text::Features => "{}\n\nSchalte eine mit /features [rsvp|digest|public_page] [on|off] ein oder aus",
+        Text::FeaturesUsage => "Verwendung: /features [rsvp|digest|public_page] [on|off]",
+        Text::BoostSet => "Events mit weniger als {} Zusagen zwei Tage vor Beginn werden noch einmal angekündigt",
+        Text::BoostOff => "Events werden nicht noch einmal angekündigt",
+        Text::BoostUsage => "Verwendung: /boost [Zahl|off], zum Beispiel /boost 5",
+        Text::Stats => "Diesen Monat erstellte Events: {}\nDurchschnittliche Teilnahme: {}\nVerknüpfte Chats: {}\nNutzer in diesen Chats: {}\n\nAktivste Gastgeber:\n{}",
+        Text::People => "{} Personen",
+        Text::NoEventsEnded => "Es ist noch kein Event zu Ende gegangen",
+        Text::NoHostsYet => "Noch niemand war Gastgeber eines Events",
+        Text::NoUsername => "Jemand ohne Nutzernamen: {}",
+        Text::TokenInChannel => "API-Tokens werden nur privat verschickt. Sende mir /token in einem privaten Chat, um eines für diesen Kanal zu bekommen",
+        Text::SecretInChannel => "Webhook-Geheimnisse werden nur privat verschickt. Sende mir /webhook secret oder /webhook rotate in einem privaten Chat",
+        Text::CalendarLink => "Der Kalender dieses Kanals ist unter {}/events/calendar/{}={}\n\nFrühere Kalender-Links funktionieren nicht mehr.",
+        Text::WebhookUsage => "Verwendung: /webhook [add|remove] [URL]",
+        Text::TokenIssued => "Das neue API-Token des Kanals ist {}\n\nExterne Kalender können Events zum Kanal hinzufügen, indem sie sie mit dem Token in einem \"Authorization: Bearer\"-Header an {}/api/v1/channels/{}/events senden. Websites, die nur die Events des Kanals anzeigen, sollten stattdessen ein Token nur zum Lesen von /token read verwenden.\n\nFrühere Tokens funktionieren nicht mehr. Lösch diese Nachricht, sobald du das Token sicher gespeichert hast.",
+        Text::ReadTokenIssued => "Das neue API-Token nur zum Lesen des Kanals ist {}\n\nWebsites können die Events des Kanals von {}/api/v1/channels/{}/events abrufen und einzelne Events von {}/api/v1/events/{id}. Sende das Token in einem \"Authorization: Bearer\"-Header. Es kann keine Events hinzufügen.\n\nFrühere Tokens nur zum Lesen funktionieren nicht mehr.",
+        Text::WebhookScheme => "Webhook-URLs müssen mit http:// oder https:// beginnen",
+        Text::WebhookPublic => "Webhooks können nur an öffentliche Adressen gesendet werden",
+        Text::WebhookAdded => "{} wird benachrichtigt, wenn sich Events ändern\n\nAnfragen werden mit dem Webhook-Geheimnis dieses Kanals signiert. Sende mir /webhook secret in einem privaten Chat, um es zu bekommen. {} erklärt, wie man die Signaturen prüft.",
+        Text::SecretRotated => "Die Webhooks des Kanals werden jetzt mit dem Geheimnis {} signiert\n\nLösch diese Nachricht, sobald du das Geheimnis sicher gespeichert hast.",
+        Text::SharedSecret => "Die Webhooks des Kanals werden mit dem Geheimnis {} signiert",
+        Text::WebhookSecrets => "Die Webhooks des Kanals werden mit diesen Geheimnissen signiert:",
+        Text::SaveSecret => "{}\n\nLösch diese Nachricht, sobald du das Geheimnis sicher gespeichert hast.",
+        Text::NoWebhooks => "Dieser Kanal hat keine Webhooks",
+        Text::WebhookRemoved => "{} wird nicht mehr benachrichtigt",
+        Text::NotAWebhook => "{} ist kein Webhook dieses Kanals",
+        Text::WebhookList => "Webhooks dieses Kanals:\n{}",
+        Text::NoFailedMessages => "Es sind keine Nachrichten beim Senden fehlgeschlagen",
+        Text::FailedAnnouncement => "Ankündigung",
+        Text::FailedMessage => "Nachricht",
+        Text::FailedMessageLine => "{}. {} für Event {} an Chat {}, {}\n{}\n{}",
+        Text::FailedMessages => "Nachrichten, die nicht gesendet werden konnten, die neuesten zuerst:\n\n{}\n\nSende /failed replay [ID], um eine erneut zu senden",
+        Text::Replaying => "Sende Nachricht {} erneut",
+        Text::PoolStatus => "Datenbankverbindungen:\nFrei: {}\nIn Benutzung: {}\nVerbinden: {}\nWartende Anfragen: {}",
+        Text::NewEventLink => "Benutze diesen Link, um dein Event zu erstellen: {}\nDer Link läuft um {} ab",
+        Text::EditEventLink => "Benutze diesen Link, um dein Event zu aktualisieren: {}\nDer Link läuft um {} ab",
+        Text::NewEventLinkExpired => "Dein Link zum Erstellen deines Events ist abgelaufen",
+        Text::EditEventLinkExpired => "Dein Link zum Aktualisieren deines Events ist abgelaufen",
+        Text::NewLink => "Neuen Link erstellen",
+        Text::Created => "{} erstellt!",
+        Text::CreatedPending => "{} erstellt! Es wird angekündigt, sobald die Admins des Kanals es freigeben",
+        Text::EventDeletedReply => "Event gelöscht!",
+        Text::EventDeletedNotice => "Event gelöscht: {}",
+        Text::EventCancelledReply => "Event abgesagt!",
+        Text::EventCancelledNotice => "Event abgesagt: {}",
+        Text::EventUpdated => "Event aktualisiert!",
+        Text::UpcomingEventsAsOf => "Kommende Events, Stand {} {}:\n\n{}",
+        Text::EditButton => "{} bearbeiten",
+        Text::DeleteButton => "{} löschen",
+        Text::GoingButton => "Ich komme",
+        Text::CantGoButton => "Ich kann nicht",
+        Text::Going => "Du kommst zu {}!{}",
+        Text::Waitlisted => "{} ist voll, du stehst jetzt auf der Warteliste",
+        Text::AlreadyResponded => "Du hast schon auf {} geantwortet",
+        Text::RsvpCancelled => "{} wurde abgesagt",
+        Text::RsvpsClosed => "Tut mir leid, die Anmeldung für {} ist geschlossen",
+        Text::RsvpsOff => "Zusagen sind in diesem Kanal ausgeschaltet",
+        Text::RsvpMembersOnly => "{} ist nur für Mitglieder, du musst in einer der verknüpften Gruppen sein, um zuzusagen",
+        Text::NotGoing => "Du kommst nicht mehr zu {}",
+        Text::Promoted => "Ein Platz ist frei geworden! Du kommst jetzt zu {}{}",
+        Text::DeleteIt => "Löschen",
+        Text::CancelInstead => "Stattdessen absagen",
+        Text::ConfirmDelete => "Bist du sicher, dass du {} löschen möchtest?\n",
+        Text::NoRsvps => "\nNoch niemand hat zugesagt.",
+        Text::PersonIs => "Person kommt",
+        Text::PeopleAre => "Personen kommen",
+        Text::RsvpsGoing => "\n{}: {}",
+        Text::RsvpsWaitlisted => "\n{} auf der Warteliste: {}",
+        Text::RsvpsDeleted => "\nIhre Zusagen werden mit dem Event gelöscht.",
+        Text::CancelInsteadHint => "\n\nWenn du es stattdessen absagst, bleiben das Event und seine Zusagen erhalten, und alle erfahren, dass es ausfällt.",
+        Text::ReallyDelete => "'{}' wirklich löschen? Das kann nicht rückgängig gemacht werden.",
+        Text::Yes => "Ja",
+        Text::No => "Nein",
+        Text::ReallyForget => "Soll der Bot wirklich alles vergessen, was er über dich weiß? Du wirst von den Events entfernt, zu denen du kommst und bei denen du Gastgeber bist. Das kann nicht rückgängig gemacht werden.",
+        Text::ExportCaption => "Hier ist alles, was der Bot über dich gespeichert hat",
+        Text::Forgotten => "Erledigt, der Bot hat dich vergessen. Gelöscht wurden:\n\n{} Chat-Mitgliedschaften\n{} Gastgeber-Einträge\n{} Zusagen\n{} Links zum Erstellen oder Bearbeiten von Events\n\nWenn du weiter in Chats schreibst, die diesen Bot verwenden, merkt er sich dich wieder.",
+        Text::NothingChanged => "Okay, es wurde nichts geändert",
+        Text::Nevermind => "Doch nicht",
+        Text::AskBroadcast => "Was möchtest du allen sagen, die zu {} kommen? Deine nächste Nachricht wird an sie gesendet.",
+        Text::BroadcastTooSoon => "Teilnehmern kann nur alle {} Minuten geschrieben werden, versuch es in {} Minuten noch einmal",
+        Text::BroadcastSending => "Deine Nachricht wird an die {} Personen gesendet, die zu {} kommen",
+        Text::BroadcastNobody => "Gerade kann niemandem geschrieben werden, der zu {} kommt",
+        Text::BroadcastMessage => "Eine Nachricht vom Gastgeber von {}:\n\n{}\n\nSende /mute broadcasts, um keine Nachrichten von Gastgebern mehr zu bekommen",
+        Text::PickNewHost => "Wer soll Gastgeber von {} sein?",
+        Text::NoNewHosts => "Niemand sonst in den Chats von {} kann Gastgeber sein",
+        Text::MadeHost => "@{} hat dich zum Gastgeber von {} gemacht",
+        Text::Transferred => "{} übergeben!",
+        Text::StartingSoon => "Beginnt bald:\n\n{}",
+        Text::AttendeeReminder => "Erinnerung: {} beginnt um {}\n\nSende /mute reminders, um keine Erinnerungen mehr zu bekommen",
+        Text::AwaitingApproval => "Wartet auf Freigabe",
+        Text::Approve => "Freigeben",
+        Text::TurnDown => "Ablehnen",
+        Text::Approved => "{} freigegeben",
+        Text::TurnedDown => "{} abgelehnt",
+        Text::PendingApproval => "{} wird angekündigt, sobald die Admins des Kanals es freigeben",
+        Text::ApprovedNotice => "{} wurde von den Admins des Kanals freigegeben",
+        Text::TurnedDownNotice => "{} wurde von den Admins des Kanals abgelehnt",
+        Text::DefaultReminder => "Nicht vergessen! {title} beginnt bald!",
+        Text::DefaultStarted => "{title} hat begonnen!",
+        Text::DefaultEnded => "{title} ist zu Ende!",
+        Text::HowItWorks => "\n\nSo funktioniert dieser Kanal:\n- Kommende Events werden hier angekündigt. Tippe bei einer Ankündigung auf \"Ich komme\", um zuzusagen.\n- Mitglieder der verknüpften Gruppen können Events erstellen, indem sie dem Bot in einem privaten Chat /new senden.\n- Sende /events in einer verknüpften Gruppe, um zu sehen, was ansteht.",
+        Text::GreetingDigestPinned => "\n- Jeden {} wird eine Übersicht der Events der Woche gepostet und angeheftet.",
+        Text::GreetingDigest => "\n- Jeden {} wird eine Übersicht der Events der Woche gepostet.",
+        Text::GreetingPinned => "\n- Neue Ankündigungen werden angeheftet, bis ihr Event endet.",
+        Text::Help => "Event Bot ist ein Telegram-Bot, der Gruppen hilft, Events zu organisieren.\n\nIn Gruppen gibt es diese Befehle:\n{}\n\nIn privaten Chats gibt es diese Befehle:\n{}\n\nWenn du Admin bist und diesen Bot zu einem Chat hinzufügen möchtest, sind diese Befehle interessant für dich:\n{}\n\nDenk daran, dass dieser Bot nur in Supergruppen funktioniert, nicht in normalen Gruppen.\n\nWenn du Fragen hast oder Hilfe beim Einrichten oder Benutzen des Bots brauchst, schreib @asonix\n\nDieser Bot steht unter der GNU General Public License Version 3 oder neuer. Eine Kopie des Codes findest du hier:\nhttp://github.com/asonix/telegram-event-bot\n",
+        Text::HelpUsage => "Verwendung",
+        Text::HelpEvents => "zeigt die Events des aktuellen Chats",
+        Text::HelpPinEvents => "heftet eine Liste der kommenden Events in der aktuellen Gruppe an",
+        Text::HelpNext => "zeigt das nächste Event des aktuellen Chats",
+        Text::HelpToday => "zeigt die Events des aktuellen Chats, die heute beginnen",
+        Text::HelpSearch => "durchsucht die kommenden Events des aktuellen Chats",
+        Text::HelpNew => "Erstellt ein neues Event",
+        Text::HelpQuick => "Erstellt ein Event aus einer Beschreibung",
+        Text::HelpEdit => "Bearbeitet ein Event, bei dem du Gastgeber bist",
+        Text::HelpCancel => "Sagt ein Event ab, bei dem du Gastgeber bist, und behält es samt Zusagen",
+        Text::HelpTransfer => "Übergibt ein Event, bei dem du Gastgeber bist, an jemand anderen",
+        Text::HelpDelete => "Löscht ein Event, bei dem du Gastgeber bist",
+        Text::HelpMyEvents => "Zeigt die Events, bei denen du Gastgeber bist",
+        Text::HelpAnnounce => "Schickt allen, die zu einem deiner Events kommen, eine Nachricht",
+        Text::HelpNotifications => "Wählt, welche privaten Nachrichten du vom Bot bekommst",
+        Text::HelpMute => "Keine privaten Nachrichten mehr vom Bot bekommen",
+        Text::HelpUnmute => "Wieder private Nachrichten vom Bot bekommen",
+        Text::HelpLanguage => "Wählt die Sprache, in der deine Events angezeigt werden",
+        Text::HelpTimeFormat => "Wählt, ob deine Events mit Uhrzeiten im 12- oder 24-Stunden-Format angezeigt werden",
+        Text::HelpExportMe => "Holt eine Kopie von allem, was der Bot über dich weiß",
+        Text::HelpForgetMe => "Löscht alles, was der Bot über dich weiß, auch deine Zusagen",
+        Text::HelpSearchPrivate => "Durchsucht die kommenden Events in all deinen Kanälen",
+        Text::HelpHelp => "Zeigt diese Hilfe",
+        Text::HelpInit => "Initialisiert einen Event-Kanal",
+        Text::HelpLink => "verknüpft in einem Event-Kanal eine Gruppe",
+        Text::HelpProtect => "legt in einem Event-Kanal fest, ob Ankündigungen weitergeleitet werden können",
+        Text::HelpModerate => "legt in einem Event-Kanal fest, ob Events von Gastgebern, die keine Admins sind, vor der Ankündigung von einem Admin freigegeben werden müssen",
+        Text::HelpChannelLanguage => "wählt in einem Event-Kanal die Sprache, in der Events angekündigt werden",
+        Text::HelpChannelTimeFormat => "wählt in einem Event-Kanal, ob Events mit Uhrzeiten im 12- oder 24-Stunden-Format angekündigt werden",
+        Text::HelpBan => "verbietet in einem Event-Kanal einem Nutzer, Events dafür zu erstellen",
+        Text::HelpUnban => "erlaubt in einem Event-Kanal einem gesperrten Nutzer wieder, Events zu erstellen",
+        Text::HelpDigest => "legt in einem Event-Kanal den Tag fest, an dem die wöchentliche Übersicht gepostet wird",
+        Text::HelpPinDigest => "legt in einem Event-Kanal fest, ob die wöchentliche Übersicht angeheftet wird",
+        Text::HelpPinAnnouncements => "legt in einem Event-Kanal fest, ob Ankündigungen neuer Events angeheftet werden",
+        Text::HelpDirectory => "legt in einem Event-Kanal fest, ob der Kanal und seine öffentlichen Events im Verzeichnis der Weboberfläche aufgeführt werden",
+        Text::HelpSetGreeting => "legt in einem Event-Kanal eine Einführung fest, die der Bot anheftet und aktuell hält",
+        Text::HelpTemplate => "zeigt oder ändert in einem Event-Kanal die Nachrichten für Ankündigung, Erinnerung und Ende",
+        Text::HelpFeatures => "zeigt in einem Event-Kanal Zusagen, die wöchentliche Übersicht und öffentliche Seiten an oder schaltet sie ein und aus",
+        Text::HelpBoost => "kündigt in einem Event-Kanal Events zwei Tage vor Beginn noch einmal an, wenn weniger als so viele Personen zugesagt haben",
+        Text::HelpStats => "zeigt in einem Event-Kanal, wie viele Events diesen Monat erstellt wurden, die aktivsten Gastgeber, die durchschnittliche Teilnahme und wie viele Chats und Nutzer verknüpft sind",
+        Text::HelpToken => "erstellt in einem privaten Chat ein Token, mit dem andere Websites und Bots Events zu einem Kanal hinzufügen können, dessen Admin du bist, oder ein Token nur zum Lesen, mit dem sie seine Events abrufen können",
+        Text::HelpCalendar => "erstellt in einem Event-Kanal einen Link zu einem Kalender mit den öffentlichen Events des Kanals",
+        Text::HelpWebhook => "zeigt, ergänzt oder entfernt in einem Event-Kanal URLs, die bei Änderungen an Events benachrichtigt werden. Holt oder ersetzt in einem privaten Chat das Geheimnis, mit dem sie signiert werden",
+        Text::HelpId => "zeigt die ID einer Gruppe",
+        Text::HelpUpdates => "wechselt, wie der Bot Updates von Telegram bekommt",
+        Text::HelpPool => "prüft die Datenbankverbindungen des Bots",
+        Text::HelpSnooze => "hält Warnungen eine Weile zurück",
+        Text::HelpFailed => "zeigt Nachrichten, die nicht gesendet werden konnten, oder sendet eine erneut",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::{Language, Text, TimeFormat};
+
+    #[test]
+    fn reads_language_codes() {
+        assert_eq!(Language::from_code("es"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("DE-at"), Some(Language::German));
+        assert_eq!(Language::from_code("en_GB"), Some(Language::English));
+        assert_eq!(Language::from_code("fr"), None);
+    }
+
+    #[test]
+    fn picks_the_preferred_supported_language() {
+        assert_eq!(
+            Language::from_accept_language("fr-FR,fr;q=0.9,de;q=0.7,en;q=0.8"),
+            Language::English
+        );
+        assert_eq!(
+            Language::from_accept_language("es-MX, de"),
+            Language::Spanish
+        );
+        assert_eq!(
+            Language::from_accept_language("es;q=0, de;q=0.5"),
+            Language::German
+        );
+        assert_eq!(Language::from_accept_language(""), Language::English);
+    }
+
+    #[test]
+    fn names_dates() {
+        assert_eq!(Language::German.weekday(Weekday::Fri), "Freitag");
+        assert_eq!(Language::Spanish.month(6), "junio");
+        assert_eq!(Language::English.month(12), "December");
+        assert_eq!(Language::English.month(13), "");
+    }
+
+    #[test]
+    fn fills_in_text() {
+        assert_eq!(
+            Language::German.fill(Text::Going, &[&"Pizza", &""]),
+            "Du kommst zu Pizza!"
+        );
+        assert_eq!(
+            Language::English.fill(Text::UnknownPlaceholder, &[&"where", &"{title}"]),
+            "{where} isn't a placeholder, try one of {title}"
+        );
+        assert_eq!(
+            Language::English.fill(Text::DefaultEnded, &[&"Pizza"]),
+            "{title} has ended!"
+        );
+    }
+
+    #[test]
+    fn formats_times() {
+        let twelve = TimeFormat::TwelveHour;
+
+        assert_eq!(TimeFormat::TwentyFourHour.format_time(19, 5), "19:05");
+        assert_eq!(twelve.format_time(19, 5), "7:05 PM");
+        assert_eq!(twelve.format_time(0, 30), "12:30 AM");
+        assert_eq!(twelve.format_time(12, 0), "12:00 PM");
+        assert_eq!(TimeFormat::from_name("12h"), Some(twelve));
+    }
+}
@@ -20,7 +20,11 @@ extern crate event_web;
 extern crate futures;
 
 use actix::{Actor, Context, Handler, Message, System};
-use event_web::{EditEvent, FrontendErrorKind, LookupEvent, NewEvent, SendFutResponse};
+use event_web::{
+    EditEvent, FormSettings, FrontendErrorKind, ListApiEvents, ListDirectory, LookupApiEvent,
+    LookupCalendar, LookupEvent, LookupPublicEvent, NewEvent, PushEvent, SendFutResponse,
+    SuggestTimes, TelegramUpdate,
+};
 use futures::IntoFuture;
 
 #[derive(Copy, Clone, Debug)]
@@ -36,7 +40,7 @@ impl Handler<NewEvent> for MyHandler {
     fn handle(&mut self, msg: NewEvent, _: &mut Self::Context) -> Self::Result {
         println!("Event: {:?}", msg.0);
 
-        SendFutResponse::new(Box::new(Ok(()).into_future()) as <NewEvent as Message>::Result)
+        SendFutResponse::new(Box::new(Ok(msg.0).into_future()) as <NewEvent as Message>::Result)
     }
 }
 
@@ -50,6 +54,16 @@ impl Handler<EditEvent> for MyHandler {
     }
 }
 
+impl Handler<PushEvent> for MyHandler {
+    type Result = SendFutResponse<PushEvent>;
+
+    fn handle(&mut self, msg: PushEvent, _: &mut Self::Context) -> Self::Result {
+        println!("Event: {:?}", msg.0);
+
+        SendFutResponse::new(Box::new(Ok(()).into_future()) as <PushEvent as Message>::Result)
+    }
+}
+
 impl Handler<LookupEvent> for MyHandler {
     type Result = SendFutResponse<LookupEvent>;
 
@@ -61,10 +75,95 @@ impl Handler<LookupEvent> for MyHandler {
     }
 }
 
+impl Handler<LookupPublicEvent> for MyHandler {
+    type Result = SendFutResponse<LookupPublicEvent>;
+
+    fn handle(&mut self, _: LookupPublicEvent, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Err(FrontendErrorKind::NoEvent.into()).into_future())
+                as <LookupPublicEvent as Message>::Result,
+        )
+    }
+}
+
+impl Handler<ListApiEvents> for MyHandler {
+    type Result = SendFutResponse<ListApiEvents>;
+
+    fn handle(&mut self, _: ListApiEvents, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Ok(Vec::new()).into_future()) as <ListApiEvents as Message>::Result
+        )
+    }
+}
+
+impl Handler<LookupApiEvent> for MyHandler {
+    type Result = SendFutResponse<LookupApiEvent>;
+
+    fn handle(&mut self, _: LookupApiEvent, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Err(FrontendErrorKind::NoEvent.into()).into_future())
+                as <LookupApiEvent as Message>::Result,
+        )
+    }
+}
+
+impl Handler<ListDirectory> for MyHandler {
+    type Result = SendFutResponse<ListDirectory>;
+
+    fn handle(&mut self, _: ListDirectory, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Ok(Vec::new()).into_future()) as <ListDirectory as Message>::Result
+        )
+    }
+}
+
+impl Handler<TelegramUpdate> for MyHandler {
+    type Result = SendFutResponse<TelegramUpdate>;
+
+    fn handle(&mut self, _: TelegramUpdate, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Err(FrontendErrorKind::BadToken.into()).into_future())
+                as <TelegramUpdate as Message>::Result,
+        )
+    }
+}
+
+impl Handler<SuggestTimes> for MyHandler {
+    type Result = SendFutResponse<SuggestTimes>;
+
+    fn handle(&mut self, _: SuggestTimes, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Ok(Vec::new()).into_future()) as <SuggestTimes as Message>::Result
+        )
+    }
+}
+
+impl Handler<LookupCalendar> for MyHandler {
+    type Result = SendFutResponse<LookupCalendar>;
+
+    fn handle(&mut self, _: LookupCalendar, _: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(Err(FrontendErrorKind::BadToken.into()).into_future())
+                as <LookupCalendar as Message>::Result,
+        )
+    }
+}
+
 fn main() {
     let sys = System::new("womp");
 
-    event_web::start(MyHandler.start(), "0.0.0.0:8000", None);
+    let started = event_web::start(
+        MyHandler.start(),
+        "0.0.0.0:8000",
+        None,
+        FormSettings::default(),
+        None,
+    );
+
+    if let Err(e) = started {
+        eprintln!("{}", e);
+        return;
+    }
 
     sys.run();
 }
@@ -0,0 +1,119 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Running the Web UI behind a reverse proxy, like nginx
+
+use std::net::IpAddr;
+
+/// Find the address of the client that made a request
+///
+/// For requests from trusted proxies, this is the last address in `X-Forwarded-For` that isn't
+/// one of them. Anything before that was sent by the client, who could have put anything there.
+pub fn client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut client = match peer {
+        Some(peer) => peer,
+        None => return None,
+    };
+
+    if !trusted.contains(&client) {
+        return Some(client);
+    }
+
+    if let Some(forwarded_for) = forwarded_for {
+        for hop in forwarded_for.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+
+                    if !trusted.contains(&ip) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    Some(client)
+}
+
+/// The path of a URL, or the URL itself if it's already a path
+///
+/// Anything else, like a host without a scheme, is treated as the root.
+pub fn url_path(url: &str) -> &str {
+    match url.find("://") {
+        Some(scheme_end) => {
+            let rest = &url[scheme_end + 3..];
+
+            match rest.find('/') {
+                Some(path_start) => &rest[path_start..],
+                None => "/",
+            }
+        }
+        None if url.starts_with('/') => url,
+        None => "/",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{client_ip, url_path};
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn trusts_forwarded_addresses_from_proxies() {
+        let proxies = [ip("127.0.0.1"), ip("10.0.0.2")];
+
+        // The client made up the first address, and the two proxies added the rest
+        let forwarded = Some("1.2.3.4, 203.0.113.9, 10.0.0.2");
+
+        assert_eq!(
+            client_ip(Some(ip("127.0.0.1")), forwarded, &proxies),
+            Some(ip("203.0.113.9"))
+        );
+        assert_eq!(client_ip(Some(ip("127.0.0.1")), None, &proxies), Some(ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn ignores_forwarded_addresses_from_clients() {
+        assert_eq!(
+            client_ip(Some(ip("203.0.113.9")), Some("1.2.3.4"), &[ip("127.0.0.1")]),
+            Some(ip("203.0.113.9"))
+        );
+        assert_eq!(
+            client_ip(Some(ip("203.0.113.9")), Some("1.2.3.4"), &[]),
+            Some(ip("203.0.113.9"))
+        );
+    }
+
+    #[test]
+    fn finds_the_path_behind_a_prefix() {
+        assert_eq!(url_path("https://example.com/bot/events/new/abc"), "/bot/events/new/abc");
+        assert_eq!(url_path("https://example.com"), "/");
+        assert_eq!(url_path("/events/new/abc"), "/events/new/abc");
+        assert_eq!(url_path("localhost:8000/events/new/abc"), "/");
+    }
+}
@@ -0,0 +1,157 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Security headers for every response the Web UI sends
+
+use actix_web::middleware::{Middleware, Response};
+use actix_web::{HttpRequest, HttpResponse, Result};
+use http::header::{self, HeaderValue};
+
+/// The frame rules for pages that only the Web UI itself can frame
+pub const SELF_FRAMES: &str = "frame-ancestors 'self'";
+
+/// What pages can load
+///
+/// Theme colors are an inline `style` element, and the theme's logo can be on any site. Frame
+/// rules are left out, since each page sets its own.
+const CONTENT_POLICY: &str = "default-src 'self'; img-src 'self' https: http:; \
+                              style-src 'self' 'unsafe-inline'; object-src 'none'; \
+                              base-uri 'self'; form-action 'self'";
+
+/// `SecurityHeaders` adds a `Content-Security-Policy`, `X-Frame-Options`,
+/// `X-Content-Type-Options`, and `Referrer-Policy` to every response
+///
+/// A `Content-Security-Policy` already set by a handler is taken to be that page's frame rules,
+/// and is kept. Pages without one can only be framed by the Web UI.
+pub struct SecurityHeaders {
+    report_only: bool,
+}
+
+impl SecurityHeaders {
+    /// With `report_only`, browsers report what the content policy would block instead of
+    /// blocking it
+    ///
+    /// Frame rules are still enforced, since browsers ignore them in report-only policies.
+    pub fn new(report_only: bool) -> Self {
+        SecurityHeaders { report_only }
+    }
+}
+
+impl<S> Middleware<S> for SecurityHeaders {
+    fn response(&self, _: &mut HttpRequest<S>, mut resp: HttpResponse) -> Result<Response> {
+        let frames = resp.headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .and_then(|policy| policy.to_str().ok())
+            .unwrap_or(SELF_FRAMES)
+            .to_owned();
+
+        let (policy_header, policy) = if self.report_only {
+            (header::CONTENT_SECURITY_POLICY_REPORT_ONLY, CONTENT_POLICY.to_owned())
+        } else {
+            (header::CONTENT_SECURITY_POLICY, format!("{}; {}", CONTENT_POLICY, frames))
+        };
+
+        let headers = resp.headers_mut();
+
+        if self.report_only {
+            if let Ok(frames) = HeaderValue::from_str(&frames) {
+                headers.insert(header::CONTENT_SECURITY_POLICY, frames);
+            }
+        }
+
+        if let Ok(policy) = HeaderValue::from_str(&policy) {
+            headers.insert(policy_header, policy);
+        }
+
+        // Browsers that don't know frame-ancestors can't be told about other origins, so
+        // embeddable pages are left out
+        if frames == SELF_FRAMES {
+            headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("SAMEORIGIN"));
+        }
+
+        headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+
+        // Event links have their secret in the path, so it shouldn't be passed on to other sites
+        headers.insert(header::REFERRER_POLICY, HeaderValue::from_static("no-referrer"));
+
+        Ok(Response::Done(resp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::middleware::{Middleware, Response};
+    use actix_web::test::TestRequest;
+    use actix_web::HttpResponse;
+    use http::header;
+
+    use super::{SecurityHeaders, CONTENT_POLICY, SELF_FRAMES};
+
+    fn secured(report_only: bool, resp: HttpResponse) -> HttpResponse {
+        let mut req = TestRequest::default().finish();
+
+        match SecurityHeaders::new(report_only).response(&mut req, resp) {
+            Ok(Response::Done(resp)) => resp,
+            _ => panic!("Headers weren't added"),
+        }
+    }
+
+    fn value_of<'a>(resp: &'a HttpResponse, name: header::HeaderName) -> Option<&'a str> {
+        resp.headers().get(name).and_then(|value| value.to_str().ok())
+    }
+
+    #[test]
+    fn keeps_pages_to_the_web_ui_by_default() {
+        let resp = secured(false, HttpResponse::Ok().finish());
+
+        assert_eq!(
+            value_of(&resp, header::CONTENT_SECURITY_POLICY),
+            Some(format!("{}; {}", CONTENT_POLICY, SELF_FRAMES).as_str())
+        );
+        assert_eq!(value_of(&resp, header::X_FRAME_OPTIONS), Some("SAMEORIGIN"));
+        assert_eq!(value_of(&resp, header::X_CONTENT_TYPE_OPTIONS), Some("nosniff"));
+        assert_eq!(value_of(&resp, header::REFERRER_POLICY), Some("no-referrer"));
+    }
+
+    #[test]
+    fn keeps_embeddable_pages_embeddable() {
+        let frames = "frame-ancestors 'self' https://example.com";
+        let resp = secured(
+            false,
+            HttpResponse::Ok()
+                .header(header::CONTENT_SECURITY_POLICY, frames)
+                .finish(),
+        );
+
+        assert_eq!(
+            value_of(&resp, header::CONTENT_SECURITY_POLICY),
+            Some(format!("{}; {}", CONTENT_POLICY, frames).as_str())
+        );
+        assert_eq!(value_of(&resp, header::X_FRAME_OPTIONS), None);
+    }
+
+    #[test]
+    fn enforces_frames_when_only_reporting() {
+        let resp = secured(true, HttpResponse::Ok().finish());
+
+        assert_eq!(value_of(&resp, header::CONTENT_SECURITY_POLICY), Some(SELF_FRAMES));
+        assert_eq!(
+            value_of(&resp, header::CONTENT_SECURITY_POLICY_REPORT_ONLY),
+            Some(CONTENT_POLICY)
+        );
+    }
+}
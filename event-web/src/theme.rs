@@ -0,0 +1,129 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Branding for the Web UI's pages, so communities running their own instance can make the pages
+//! look like theirs
+
+/// `Theme` holds the colors, logo, and footer shown on the forms, the page shown after saving an
+/// event, and error pages
+///
+/// Anything left out keeps the look from `styles.css`
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    accent_color: Option<String>,
+    background_color: Option<String>,
+    logo_url: Option<String>,
+    footer: Option<String>,
+}
+
+impl Theme {
+    /// Color headings, links, and buttons with this color, like `#2a6496`
+    ///
+    /// Colors that aren't hex colors are ignored, so they can't break out of the page's styles
+    pub fn accent_color(mut self, color: &str) -> Self {
+        if Theme::is_color(color) {
+            self.accent_color = Some(color.to_owned());
+        }
+        self
+    }
+
+    /// Show the pages on this background color, like `#f4f4f4`
+    pub fn background_color(mut self, color: &str) -> Self {
+        if Theme::is_color(color) {
+            self.background_color = Some(color.to_owned());
+        }
+        self
+    }
+
+    /// Show this image above each page
+    pub fn logo_url(mut self, logo_url: &str) -> Self {
+        self.logo_url = Some(logo_url.to_owned());
+        self
+    }
+
+    /// Show this text at the bottom of each page
+    pub fn footer(mut self, footer: &str) -> Self {
+        self.footer = Some(footer.to_owned());
+        self
+    }
+
+    /// Check whether a color is a hex color, like `#fff` or `#2a6496`
+    pub fn is_color(color: &str) -> bool {
+        color.starts_with('#')
+            && (color.len() == 4 || color.len() == 7)
+            && color[1..].chars().all(|c| c.is_digit(16))
+    }
+
+    pub fn logo(&self) -> Option<&str> {
+        self.logo_url.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn footer_text(&self) -> Option<&str> {
+        self.footer.as_ref().map(|s| s.as_str())
+    }
+
+    /// The styles that override `styles.css`, if the theme has any colors
+    pub fn style(&self) -> Option<String> {
+        let mut style = String::new();
+
+        if let Some(ref color) = self.background_color {
+            style.push_str(&format!("body, section {{ background-color: {}; }}\n", color));
+        }
+
+        if let Some(ref color) = self.accent_color {
+            style.push_str(&format!(
+                "h1, h3, a {{ color: {color}; }}\n\
+                 input[type=submit] {{ background-color: {color}; border-color: {color}; \
+                 color: #fff; }}\n",
+                color = color
+            ));
+        }
+
+        if style.is_empty() {
+            None
+        } else {
+            Some(style)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Theme;
+
+    #[test]
+    fn only_accepts_hex_colors() {
+        assert!(Theme::is_color("#fff"));
+        assert!(Theme::is_color("#2A6496"));
+        assert!(!Theme::is_color("red"));
+        assert!(!Theme::is_color("#12345"));
+        assert!(!Theme::is_color("#fff; }"));
+    }
+
+    #[test]
+    fn styles_only_what_was_set() {
+        assert_eq!(Theme::default().style(), None);
+
+        let style = Theme::default()
+            .background_color("#f4f4f4")
+            .accent_color("red; } body { display: none")
+            .style()
+            .unwrap();
+
+        assert_eq!(style, "body, section { background-color: #f4f4f4; }\n");
+    }
+}
@@ -0,0 +1,79 @@
+/*
+ * This file is part of Event Web
+ *
+ * Event Web is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Event Web is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Serving the Web UI over HTTPS, so small deployments don't need a reverse proxy in front of it
+
+use failure::ResultExt;
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod};
+
+use error::{FrontendError, FrontendErrorKind};
+
+/// `TlsSettings` controls where the Web UI serves HTTPS, and which certificate it serves
+///
+/// The certificate chain and private key are PEM files, which are loaded when the server starts.
+#[derive(Clone, Debug)]
+pub struct TlsSettings {
+    address: String,
+    cert_file: String,
+    key_file: String,
+}
+
+impl TlsSettings {
+    /// Serve HTTPS on `address`, with the certificate chain in `cert_file` and its private key in
+    /// `key_file`
+    pub fn new(address: &str, cert_file: &str, key_file: &str) -> Self {
+        TlsSettings {
+            address: address.to_owned(),
+            cert_file: cert_file.to_owned(),
+            key_file: key_file.to_owned(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn cert_file(&self) -> &str {
+        &self.cert_file
+    }
+
+    pub fn key_file(&self) -> &str {
+        &self.key_file
+    }
+
+    /// Check that the certificate chain and private key can be loaded, and that they belong
+    /// together
+    pub fn check(&self) -> Result<(), FrontendError> {
+        acceptor(self).map(|_| ())
+    }
+}
+
+/// Build the acceptor for HTTPS connections from the certificate chain and private key
+pub fn acceptor(settings: &TlsSettings) -> Result<SslAcceptorBuilder, FrontendError> {
+    let mut builder =
+        SslAcceptor::mozilla_intermediate(SslMethod::tls()).context(FrontendErrorKind::Tls)?;
+
+    builder
+        .set_private_key_file(&settings.key_file, SslFiletype::PEM)
+        .context(FrontendErrorKind::Tls)?;
+    builder
+        .set_certificate_chain_file(&settings.cert_file)
+        .context(FrontendErrorKind::Tls)?;
+    builder.check_private_key().context(FrontendErrorKind::Tls)?;
+
+    Ok(builder)
+}
@@ -15,11 +15,20 @@
  * along with Event Web.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
+use chrono_tz::Tz;
 use failure::Fail;
-use maud::{html, Markup, DOCTYPE};
+use maud::{html, Markup, PreEscaped, DOCTYPE};
 
+use calendar::CalendarMonth;
 use error::FrontendError;
-use event::{CreateEvent, Event, OptionEvent};
+use event::{
+    CreateEvent, DirectoryChannel, Event, FieldErrors, OptionEvent, PublicEvent, StoredEvent,
+    SuggestedTime, WEEKDAY_NAMES,
+};
+use locale::{Language, Text, TimeFormat};
+use theme::Theme;
+use FormSettings;
 
 pub fn form(
     create_event: CreateEvent,
@@ -32,32 +41,50 @@ pub fn form(
     minutes: Vec<u32>,
     timezones: Vec<&'static str>,
     id: String,
+    csrf_token: String,
     heading_text: &str,
+    notice: Option<&str>,
+    errors: &FieldErrors,
+    stored_event: Option<&StoredEvent>,
+    suggestions: &[(SuggestedTime, DateTime<Tz>)],
+    embed: bool,
+    settings: &FormSettings,
 ) -> Markup {
+    let picked_hosts = option_event
+        .as_ref()
+        .and_then(|o| o.hosts().map(|hosts| hosts.to_owned()));
+
     html! {
         (DOCTYPE)
         html {
             head {
                 title (heading_text);
                 meta charset="utf-8";
-                link href="/assets/styles.css" rel="stylesheet" type="text/css";
+                (stylesheet(settings))
+                (theme_style(&settings.theme))
+                script src=(settings.external_url("/assets/dates.js")) {}
+                script src=(settings.external_url("/assets/hosts.js")) {}
+                script src=(settings.external_url("/assets/suggestions.js")) {}
             }
-            body {
+            body class=(body_class(embed)) {
                 section {
-                    @if let Some(o) = option_event {
+                    (theme_header(&settings.theme))
+                    @if let Some(notice) = notice {
                         article.missing-keys {
                             h1 {
-                                "Please provide the following keys"
+                                (notice)
                             }
-                            ul {
-                                @for key in &o.missing_keys() {
-                                    li {
-                                        (key)
-                                    }
-                                }
+                        }
+                    } @else if !errors.is_empty() {
+                        article.missing-keys {
+                            h1 {
+                                "Please fix the problems marked below"
                             }
                         }
                     }
+                    @if let Some(stored_event) = stored_event {
+                        (summary(stored_event))
+                    }
                     article {
                         form#event action=(submit_url) method="POST" {
                             fieldset {
@@ -67,100 +94,85 @@ pub fn form(
                                 div {
                                     label for="title" "Title:";
                                     input type="text" name="title" value=(create_event.title);
+                                    (field_errors(errors, "title"))
 
                                     label for="description" "Description:";
                                     textarea form="event" name="description" {
                                         (create_event.description)
                                     }
+                                    (field_errors(errors, "description"))
 
-                                    fieldset#first {
-                                        legend {
-                                            h3 { "Start Date" }
-                                        }
-                                        div {
-                                            label for="start_year" "Year:";
-                                            select name="start_year" {
-                                                @for year in &years {
-                                                    @if year == &create_event.start_year {
-                                                        option value=(year) selected="true" {
-                                                            (year)
-                                                        }
-                                                    } @else {
-                                                        option value=(year) {
-                                                            (year)
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                    label for="location" "Location (optional):";
+                                    input type="text" name="location" value=(create_event.location);
 
-                                            label for="start_month" "Month:";
-                                            select name="start_month" {
-                                                @for &(i, month) in &months {
-                                                    @if i == create_event.start_month {
-                                                        option value=(i) selected="true" {
-                                                            (month)
-                                                        }
-                                                    } @else {
-                                                        option value=(i) {
-                                                            (month)
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                    label for="location_hint" "Area (optional, hides the location until people RSVP):";
+                                    input type="text" name="location_hint" value=(create_event.location_hint);
 
-                                            label for="start_day" "Day:";
-                                            select name="start_day" {
-                                                @for day in &days {
-                                                    @if day == &create_event.start_day {
-                                                        option value=(day) selected="true" {
-                                                            (day)
-                                                        }
-                                                    } @else {
-                                                        option value=(day) {
-                                                            (day)
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                    label for="latitude" "Latitude (optional, adds a map link to the location):";
+                                    input type="text" name="latitude" value=(create_event.latitude);
 
-                                            label for="start_hour" "Hour:";
-                                            select name="start_hour" {
-                                                @for hour in &hours {
-                                                    @if hour == &create_event.start_hour {
-                                                        option value=(hour) selected="true" {
-                                                            (hour)
-                                                        }
-                                                    } @else {
-                                                        option value=(hour) {
-                                                            (hour)
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                    label for="longitude" "Longitude (optional):";
+                                    input type="text" name="longitude" value=(create_event.longitude);
+                                    (field_errors(errors, "coordinates"))
 
-                                            label for="start_minute" "Minute:";
-                                            select name="start_minute" {
-                                                @for minute in &minutes {
-                                                    @if minute == &create_event.start_minute {
-                                                        option value=(minute) selected="true" {
-                                                            @if *minute < 10 {
-                                                                (format!("0{}", minute))
-                                                            } @else {
-                                                                (minute)
-                                                            }
-                                                        }
-                                                    } @else {
-                                                        option value=(minute) {
-                                                            @if *minute < 10 {
-                                                                (format!("0{}", minute))
-                                                            } @else {
-                                                                (minute)
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                    label for="image_url" "Image URL (optional, posted with the announcement):";
+                                    input type="url" name="image_url" value=(create_event.image_url);
+                                    (field_errors(errors, "image_url"))
+
+                                    label for="capacity" "Max Attendees (optional):";
+                                    input type="number" name="capacity" min="1" value=(create_event.capacity);
+                                    (field_errors(errors, "capacity"))
+
+                                    label for="boost_threshold" "Announce again 2 days before if fewer than this many people RSVP (optional, 0 to never, empty to use the channel's setting):";
+                                    input type="number" name="boost_threshold" min="0" value=(create_event.boost_threshold);
+                                    (field_errors(errors, "boost_threshold"))
+
+                                    div {
+                                        label for="members_only" "Members only (only people in the linked groups can RSVP):";
+                                        @if create_event.members_only {
+                                            input type="checkbox" name="members_only" value="true" checked="true";
+                                        } @else {
+                                            input type="checkbox" name="members_only" value="true";
+                                        }
+                                    }
+
+                                    div {
+                                        label for="unlisted" "Unlisted (only announced in the channel, and left out of its public page, the directory, and search):";
+                                        @if create_event.unlisted {
+                                            input type="checkbox" name="unlisted" value="true" checked="true";
+                                        } @else {
+                                            input type="checkbox" name="unlisted" value="true";
+                                        }
+                                    }
+
+                                    @if let Some(stored_event) = stored_event {
+                                        (host_picker(stored_event, picked_hosts.as_ref().map(|hosts| hosts.as_str())))
+                                    }
+                                    (field_errors(errors, "hosts"))
+
+                                    fieldset#first {
+                                        legend {
+                                            h3 { "Start Date" }
+                                        }
+                                        @if !suggestions.is_empty() {
+                                            (suggested_times(suggestions))
                                         }
+                                        (date_fields(
+                                            "start",
+                                            (
+                                                create_event.start_year,
+                                                create_event.start_month,
+                                                create_event.start_day,
+                                                create_event.start_hour,
+                                                create_event.start_minute,
+                                            ),
+                                            &years,
+                                            &months,
+                                            &days,
+                                            &hours,
+                                            &minutes,
+                                        ))
+                                        (field_errors(errors, "start"))
                                     }
 
                                     fieldset#second {
@@ -168,89 +180,95 @@ pub fn form(
                                             h3 { "End Date" }
                                         }
                                         div {
-                                            label for="end_year" "Year:";
-                                            select name="end_year" {
-                                                @for year in &years {
-                                                    @if year == &create_event.end_year {
-                                                        option value=(year) selected="true" {
-                                                            (year)
-                                                        }
-                                                    } @else {
-                                                        option value=(year) {
-                                                            (year)
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-                                            label for="end_month" "Month:";
-                                            select name="end_month" {
-                                                @for &(i, month) in &months {
-                                                    @if i == create_event.end_month {
-                                                        option value=(i) selected="true" {
-                                                            (month)
-                                                        }
-                                                    } @else {
-                                                        option value=(i) {
-                                                            (month)
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-                                            label for="end_day" "Day:";
-                                            select name="end_day" {
-                                                @for day in &days {
-                                                    @if day == &create_event.end_day {
-                                                        option value=(day) selected="true" {
-                                                            (day)
-                                                        }
-                                                    } @else {
-                                                        option value=(day) {
-                                                            (day)
-                                                        }
-                                                    }
-                                                }
+                                            label for="use_duration" "Set how long it lasts instead:";
+                                            @if create_event.use_duration {
+                                                input type="checkbox" name="use_duration" value="true" checked="true";
+                                            } @else {
+                                                input type="checkbox" name="use_duration" value="true";
                                             }
+                                        }
+                                        (duration_fields(
+                                            (create_event.duration_hours, create_event.duration_minutes),
+                                            &hours,
+                                            &minutes,
+                                        ))
+                                        (field_errors(errors, "duration"))
+                                        (date_fields(
+                                            "end",
+                                            (
+                                                create_event.end_year,
+                                                create_event.end_month,
+                                                create_event.end_day,
+                                                create_event.end_hour,
+                                                create_event.end_minute,
+                                            ),
+                                            &years,
+                                            &months,
+                                            &days,
+                                            &hours,
+                                            &minutes,
+                                        ))
+                                        (field_errors(errors, "end"))
+                                    }
 
-                                            label for="end_hour" "Hour:";
-                                            select name="end_hour" {
-                                                @for hour in &hours {
-                                                    @if hour == &create_event.end_hour {
-                                                        option value=(hour) selected="true" {
-                                                            (hour)
-                                                        }
-                                                    } @else {
-                                                        option value=(hour) {
-                                                            (hour)
-                                                        }
-                                                    }
-                                                }
+                                    fieldset#third {
+                                        legend {
+                                            h3 { "Announcement" }
+                                        }
+                                        div {
+                                            label for="announce_later" "Announce later:";
+                                            @if create_event.announce_later {
+                                                input type="checkbox" name="announce_later" value="true" checked="true";
+                                            } @else {
+                                                input type="checkbox" name="announce_later" value="true";
                                             }
+                                        }
+                                        (date_fields(
+                                            "publish",
+                                            (
+                                                create_event.publish_year,
+                                                create_event.publish_month,
+                                                create_event.publish_day,
+                                                create_event.publish_hour,
+                                                create_event.publish_minute,
+                                            ),
+                                            &years,
+                                            &months,
+                                            &days,
+                                            &hours,
+                                            &minutes,
+                                        ))
+                                        (field_errors(errors, "publish"))
+                                    }
 
-                                            label for="end_minute" "Minute:";
-                                            select name="end_minute" {
-                                                @for minute in &minutes {
-                                                    @if minute == &create_event.end_minute {
-                                                        option value=(minute) selected="true" {
-                                                            @if *minute < 10 {
-                                                                (format!("0{}", minute))
-                                                            } @else {
-                                                                (minute)
-                                                            }
-                                                        }
-                                                    } @else {
-                                                        option value=(minute) {
-                                                            @if *minute < 10 {
-                                                                (format!("0{}", minute))
-                                                            } @else {
-                                                                (minute)
-                                                            }
-                                                        }
-                                                    }
-                                                }
+                                    fieldset#fourth {
+                                        legend {
+                                            h3 { "RSVP Deadline" }
+                                        }
+                                        div {
+                                            label for="close_rsvps" "Close RSVPs early:";
+                                            @if create_event.close_rsvps {
+                                                input type="checkbox" name="close_rsvps" value="true" checked="true";
+                                            } @else {
+                                                input type="checkbox" name="close_rsvps" value="true";
                                             }
                                         }
+                                        (date_fields(
+                                            "rsvp",
+                                            (
+                                                create_event.rsvp_year,
+                                                create_event.rsvp_month,
+                                                create_event.rsvp_day,
+                                                create_event.rsvp_hour,
+                                                create_event.rsvp_minute,
+                                            ),
+                                            &years,
+                                            &months,
+                                            &days,
+                                            &hours,
+                                            &minutes,
+                                        ))
+                                        (field_errors(errors, "rsvp"))
                                     }
 
                                     label for="timezone" "Timezone:";
@@ -267,30 +285,357 @@ pub fn form(
                                             }
                                         }
                                     }
+                                    (field_errors(errors, "timezone"))
                                 }
 
                                 input type="hidden" name="secret" value=(id);
+                                input type="hidden" name="csrf_token" value=(csrf_token);
                             }
                             input type="submit" value="Submit";
                         }
                     }
+                    (theme_footer(&settings.theme))
+                }
+            }
+        }
+    }
+}
+
+/// Render a checkbox for each user who can host the event
+///
+/// The checkboxes aren't submitted themselves. `hosts.js` copies the checked IDs into the `hosts`
+/// field, which starts out with the picked hosts so the form still works without scripts.
+fn host_picker(stored_event: &StoredEvent, picked: Option<&str>) -> Markup {
+    let picked_ids = match picked {
+        Some(picked) => picked
+            .split(',')
+            .filter_map(|id| id.trim().parse::<i32>().ok())
+            .collect::<Vec<_>>(),
+        None => stored_event.hosts().iter().map(|host| host.id()).collect(),
+    };
+
+    let value = picked_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    html! {
+        fieldset#hosts {
+            legend {
+                h3 { "Hosts" }
+            }
+            @for member in stored_event.members() {
+                div {
+                    label for=(format!("host_{}", member.id())) (format!("@{}", member.username()));
+                    @if picked_ids.contains(&member.id()) {
+                        input.host type="checkbox" id=(format!("host_{}", member.id())) value=(member.id()) checked="true";
+                    } @else {
+                        input.host type="checkbox" id=(format!("host_{}", member.id())) value=(member.id());
+                    }
+                }
+            }
+            input type="hidden" name="hosts" value=(value);
+        }
+    }
+}
+
+/// Render a button for each suggested time
+///
+/// The buttons don't do anything without scripts. `suggestions.js` fills in the start date from
+/// the picked button, and moves the end date so the event keeps the same length.
+fn suggested_times(suggestions: &[(SuggestedTime, DateTime<Tz>)]) -> Markup {
+    html! {
+        div.suggestions {
+            p { "Past events here drew the most people at these times:" }
+            @for &(ref suggestion, ref start) in suggestions {
+                @if let Some(weekday) = WEEKDAY_NAMES.get(suggestion.weekday() as usize) {
+                    button.suggestion type="button" data-year=(start.year()) data-month=(start.month0()) data-day=(start.day()) data-hour=(start.hour()) {
+                        (format!(
+                            "{}s at {}:00 ({:.1} going on average)",
+                            weekday,
+                            suggestion.hour(),
+                            suggestion.average_attendance()
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render the event as it's currently saved, so editors can see what they're changing
+fn summary(stored_event: &StoredEvent) -> Markup {
+    let event = stored_event.event();
+    let hosts = stored_event
+        .hosts()
+        .iter()
+        .map(|host| format!("@{}", host.username()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    html! {
+        article.summary {
+            h1 {
+                "Currently Saved"
+            }
+            @if stored_event.cancelled() {
+                p {
+                    strong { "This event has been cancelled" }
+                }
+            }
+            h3 {
+                (event.title())
+            }
+            @if let Some(channel) = stored_event.channel() {
+                p {
+                    "Channel: " (channel)
+                }
+            }
+            p {
+                (event.description())
+            }
+            p {
+                "Start: " (event.start_date().format(PUBLIC_DATE_FORMAT))
+            }
+            p {
+                "End: " (event.end_date().format(PUBLIC_DATE_FORMAT))
+            }
+            @if let Some(location) = event.location() {
+                p {
+                    "Location: " (location)
+                }
+            }
+            @if let Some(map_url) = event.map_url() {
+                p {
+                    a href=(map_url) { "View on OpenStreetMap" }
+                }
+            }
+            @if let Some(location_hint) = event.location_hint() {
+                p {
+                    "Area: " (location_hint)
+                }
+            }
+            @if let Some(capacity) = event.capacity() {
+                p {
+                    "Max Attendees: " (capacity)
+                }
+            }
+            @if event.members_only() {
+                p {
+                    "Members only"
+                }
+            }
+            @if event.unlisted() {
+                p {
+                    "Unlisted"
+                }
+            }
+            @if let Some(publish_at) = event.publish_at() {
+                p {
+                    "Announcement: " (publish_at.format(PUBLIC_DATE_FORMAT))
+                }
+            }
+            @if let Some(rsvp_deadline) = event.rsvp_deadline() {
+                p {
+                    "RSVPs close: " (rsvp_deadline.format(PUBLIC_DATE_FORMAT))
+                }
+            }
+            @if !hosts.is_empty() {
+                p {
+                    "Hosts: " (hosts)
+                }
+            }
+            p.last-edited {
+                @if let Some(updated_at) = stored_event.updated_at() {
+                    "Last edited " (updated_at.format(PUBLIC_DATE_FORMAT))
+                } @else {
+                    "Not edited since it was created"
+                }
+            }
+        }
+    }
+}
+
+/// Render the year, month, day, hour, and minute selects for a date, with the inputs named after
+/// the given prefix
+fn date_fields(
+    prefix: &str,
+    selected: (i32, u32, u32, u32, u32),
+    years: &[i32],
+    months: &[(u32, &&str)],
+    days: &[u32],
+    hours: &[u32],
+    minutes: &[u32],
+) -> Markup {
+    let (selected_year, selected_month, selected_day, selected_hour, selected_minute) = selected;
+
+    let year_name = format!("{}_year", prefix);
+    let month_name = format!("{}_month", prefix);
+    let day_name = format!("{}_day", prefix);
+    let hour_name = format!("{}_hour", prefix);
+    let minute_name = format!("{}_minute", prefix);
+
+    html! {
+        div {
+            label for=(year_name) "Year:";
+            select name=(year_name) {
+                @for year in years {
+                    @if *year == selected_year {
+                        option value=(year) selected="true" {
+                            (year)
+                        }
+                    } @else {
+                        option value=(year) {
+                            (year)
+                        }
+                    }
+                }
+            }
+
+            label for=(month_name) "Month:";
+            select name=(month_name) {
+                @for &(i, month) in months {
+                    @if i == selected_month {
+                        option value=(i) selected="true" {
+                            (month)
+                        }
+                    } @else {
+                        option value=(i) {
+                            (month)
+                        }
+                    }
+                }
+            }
+
+            label for=(day_name) "Day:";
+            select name=(day_name) {
+                @for day in days {
+                    @if *day == selected_day {
+                        option value=(day) selected="true" {
+                            (day)
+                        }
+                    } @else {
+                        option value=(day) {
+                            (day)
+                        }
+                    }
+                }
+            }
+
+            label for=(hour_name) "Hour:";
+            select name=(hour_name) {
+                @for hour in hours {
+                    @if *hour == selected_hour {
+                        option value=(hour) selected="true" {
+                            (hour)
+                        }
+                    } @else {
+                        option value=(hour) {
+                            (hour)
+                        }
+                    }
+                }
+            }
+
+            label for=(minute_name) "Minute:";
+            select name=(minute_name) {
+                @for minute in minutes {
+                    @if *minute == selected_minute {
+                        option value=(minute) selected="true" {
+                            (format!("{:02}", minute))
+                        }
+                    } @else {
+                        option value=(minute) {
+                            (format!("{:02}", minute))
+                        }
+                    }
                 }
             }
         }
     }
 }
 
-pub fn success(event: Event, title: &str) -> Markup {
+/// Show the problems with one part of the form, if there are any
+fn field_errors(errors: &FieldErrors, field: &str) -> Markup {
+    html! {
+        @for message in errors.get(field) {
+            p.field-error {
+                (message)
+            }
+        }
+    }
+}
+
+/// The hours and minutes an event lasts, for events that end on the day they start
+fn duration_fields(selected: (u32, u32), hours: &[u32], minutes: &[u32]) -> Markup {
+    let (selected_hours, selected_minutes) = selected;
+
+    html! {
+        div {
+            label for="duration_hours" "Hours:";
+            select name="duration_hours" {
+                @for hour in hours {
+                    @if *hour == selected_hours {
+                        option value=(hour) selected="true" {
+                            (hour)
+                        }
+                    } @else {
+                        option value=(hour) {
+                            (hour)
+                        }
+                    }
+                }
+            }
+
+            label for="duration_minutes" "Minutes:";
+            select name="duration_minutes" {
+                @for minute in minutes {
+                    @if *minute == selected_minutes {
+                        option value=(minute) selected="true" {
+                            (format!("{:02}", minute))
+                        }
+                    } @else {
+                        option value=(minute) {
+                            (format!("{:02}", minute))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Format a date for the page shown after saving an event, like
+/// `Friday, June 1, 2018 at 7:00 PM CDT`
+fn saved_date(date: &DateTime<Tz>, time_format: TimeFormat) -> String {
+    format!(
+        "{} at {} {}",
+        date.format("%A, %B %-d, %Y"),
+        time_format.format_time(date.hour(), date.minute()),
+        date.format("%Z")
+    )
+}
+
+pub fn success(
+    event: Event,
+    title: &str,
+    embed: bool,
+    time_format: TimeFormat,
+    settings: &FormSettings,
+) -> Markup {
     html! {
         (DOCTYPE)
         html {
             head {
                 meta charset="utf-8";
                 title (title);
-                link href="/assets/styles.css" rel="stylesheet" type="text/css";
+                (stylesheet(settings))
+                (theme_style(&settings.theme))
             }
-            body {
+            body class=(body_class(embed)) {
                 section {
+                    (theme_header(&settings.theme))
                     article {
                         h1 {
                             "Thanks for creating an event!"
@@ -302,10 +647,518 @@ pub fn success(event: Event, title: &str) -> Markup {
                             (event.description())
                         }
                         p {
-                            "Start: " (event.start_date().to_rfc2822())
+                            "Start: " (saved_date(&event.start_date(), time_format))
+                        }
+                        p {
+                            "End: " (saved_date(&event.end_date(), time_format))
+                        }
+                        @if let Some(location) = event.location() {
+                            p {
+                                "Location: " (location)
+                            }
+                        }
+                        @if let Some(map_url) = event.map_url() {
+                            p {
+                                a href=(map_url) { "View on OpenStreetMap" }
+                            }
+                        }
+                        @if let Some(location_hint) = event.location_hint() {
+                            p {
+                                "Area: " (location_hint)
+                            }
+                        }
+                        @if let Some(capacity) = event.capacity() {
+                            p {
+                                "Max Attendees: " (capacity)
+                            }
+                        }
+                        @if event.members_only() {
+                            p {
+                                "Members only"
+                            }
+                        }
+                        @if event.unlisted() {
+                            p {
+                                "Unlisted"
+                            }
+                        }
+                        @if let Some(publish_at) = event.publish_at() {
+                            p {
+                                "Announcement: " (saved_date(&publish_at, time_format))
+                            }
+                        }
+                        @if let Some(rsvp_deadline) = event.rsvp_deadline() {
+                            p {
+                                "RSVPs close: " (saved_date(&rsvp_deadline, time_format))
+                            }
+                        }
+                    }
+                    (theme_footer(&settings.theme))
+                }
+            }
+        }
+    }
+}
+
+/// The format dates are shown in on public pages, like `Friday, June 1, 2018 at 7:00 PM CDT`
+const PUBLIC_DATE_FORMAT: &str = "%A, %B %-d, %Y at %-I:%M %p %Z";
+
+/// Format a date for a public page in the given language, like `Freitag, 1. Juni 2018, 19:00 CDT`
+fn public_date(date: &DateTime<Tz>, language: Language) -> String {
+    let weekday = language.weekday(date.weekday());
+    let month = language.month(date.month());
+    let time = date.format("%H:%M %Z");
+
+    match language {
+        Language::English => date.format(PUBLIC_DATE_FORMAT).to_string(),
+        Language::Spanish => format!(
+            "{}, {} de {} de {}, {}",
+            weekday,
+            date.day(),
+            month,
+            date.year(),
+            time
+        ),
+        Language::German => format!(
+            "{}, {}. {} {}, {}",
+            weekday,
+            date.day(),
+            month,
+            date.year(),
+            time
+        ),
+    }
+}
+
+pub fn event_page(
+    public_id: &str,
+    public_event: PublicEvent,
+    embed: bool,
+    language: Language,
+    settings: &FormSettings,
+) -> Markup {
+    let event = public_event.event();
+    let hosts = public_event
+        .hosts()
+        .iter()
+        .map(|host| format!("@{}", host))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    html! {
+        (DOCTYPE)
+        html lang=(language.code()) {
+            head {
+                meta charset="utf-8";
+                title { "Event Bot | " (event.title()) }
+                (stylesheet(settings))
+                (link_preview(public_id, event, settings))
+            }
+            body class=(body_class(embed)) {
+                section {
+                    article {
+                        h1 {
+                            (event.title())
+                        }
+                        p {
+                            (language.text(Text::Start)) ": "
+                            (public_date(&event.start_date(), language))
+                        }
+                        p {
+                            (language.text(Text::End)) ": "
+                            (public_date(&event.end_date(), language))
+                        }
+                        @if let Some(location) = event.location() {
+                            p {
+                                (language.text(Text::Location)) ": " (location)
+                            }
+                        }
+                        @if let Some(map_url) = event.map_url() {
+                            p {
+                                a href=(map_url) { (language.text(Text::ViewMap)) }
+                            }
+                        }
+                        @if !hosts.is_empty() {
+                            p {
+                                (language.text(Text::Hosts)) ": " (hosts)
+                            }
+                        }
+                        @if event.rsvps_closed() {
+                            p {
+                                strong { (language.text(Text::RegistrationClosed)) }
+                            }
+                        } @else {
+                            @if let Some(rsvp_deadline) = event.rsvp_deadline() {
+                                p {
+                                    (language.text(Text::RsvpsClose)) ": "
+                                    (public_date(&rsvp_deadline, language))
+                                }
+                            }
+                        }
+                        p {
+                            (event.description())
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The directory of listed channels and their upcoming events, filtered by `terms` and `tag`
+pub fn directory_page(
+    channels: Vec<DirectoryChannel>,
+    terms: &str,
+    tag: &str,
+    embed: bool,
+    settings: &FormSettings,
+) -> Markup {
+    let filtered = terms.len() > 0 || tag.len() > 0;
+
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                title "Event Bot | Directory";
+                (stylesheet(settings))
+            }
+            body class=(body_class(embed)) {
+                section {
+                    article {
+                        h1 {
+                            "Directory"
+                        }
+                        form#search action=(settings.external_url("/directory")) method="GET" {
+                            input type="text" name="q" value=(terms);
+                            @if tag.len() > 0 {
+                                input type="hidden" name="tag" value=(tag);
+                            }
+                            @if embed {
+                                input type="hidden" name="embed" value="1";
+                            }
+                            input type="submit" value="Search";
+                        }
+                        @if tag.len() > 0 {
+                            p {
+                                "Showing events tagged #" (tag) " "
+                                a href=(directory_url(embed, settings)) { "Show all events" }
+                            }
+                        }
+                    }
+                    @if channels.is_empty() {
+                        article {
+                            @if filtered {
+                                p { "No upcoming events matched" }
+                            } @else {
+                                p { "No channels are listed yet" }
+                            }
+                        }
+                    }
+                    @for channel in &channels {
+                        article.channel {
+                            h2 {
+                                @if let Some(url) = channel.url() {
+                                    a href=(url) { (channel.title()) }
+                                } @else {
+                                    (channel.title())
+                                }
+                            }
+                            @if channel.events().is_empty() {
+                                p { "No upcoming events" }
+                            }
+                            @for directory_event in channel.events() {
+                                @let event = directory_event.event();
+                                @let tags = event.tags();
+                                div.event {
+                                    h3 {
+                                        @let url = event_url(
+                                            directory_event.public_id(),
+                                            embed,
+                                            settings,
+                                        );
+                                        a href=(url) {
+                                            (event.title())
+                                        }
+                                    }
+                                    p {
+                                        (event.start_date().format(PUBLIC_DATE_FORMAT))
+                                    }
+                                    @if !tags.is_empty() {
+                                        p.tags {
+                                            @for tag in &tags {
+                                                a href=(tag_url(tag, embed, settings)) { "#" (tag) }
+                                                " "
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A month of a channel's events, with a link to each event's public page
+///
+/// `today` is marked if it's in the month. The links to other months are relative, so they keep
+/// the calendar's token.
+pub fn calendar_page(
+    channel: &DirectoryChannel,
+    month: CalendarMonth,
+    today: NaiveDate,
+    embed: bool,
+    settings: &FormSettings,
+) -> Markup {
+    let previous = month.previous();
+    let next = month.next();
+
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                title { "Event Bot | " (channel.title()) " | " (month.name()) " " (month.year()) }
+                (stylesheet(settings))
+            }
+            body class=(body_class(embed)) {
+                section {
+                    article {
+                        h1 {
+                            @if let Some(url) = channel.url() {
+                                a href=(url) { (channel.title()) }
+                            } @else {
+                                (channel.title())
+                            }
+                        }
+                        nav.months {
+                            a href=(month_url(previous, embed)) {
+                                "‹ " (previous.name()) " " (previous.year())
+                            }
+                            strong { (month.name()) " " (month.year()) }
+                            a href=(month_url(next, embed)) {
+                                (next.name()) " " (next.year()) " ›"
+                            }
+                        }
+                        table.calendar {
+                            thead {
+                                tr {
+                                    @for weekday in WEEKDAY_NAMES.iter() {
+                                        th { (weekday) }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for week in month.weeks(channel.events()) {
+                                    tr {
+                                        @for day in week {
+                                            @if let Some(day) = day {
+                                                @let date = month.date(day.day());
+                                                td class=(day_class(date == today)) {
+                                                    span.day { (day.day()) }
+                                                    @for directory_event in day.events() {
+                                                        a href=(event_url(
+                                                            directory_event.public_id(),
+                                                            embed,
+                                                            settings,
+                                                        )) {
+                                                            (directory_event.event().title())
+                                                        }
+                                                    }
+                                                }
+                                            } @else {
+                                                td {}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn day_class(today: bool) -> &'static str {
+    if today {
+        "today"
+    } else {
+        ""
+    }
+}
+
+fn month_url(month: CalendarMonth, embed: bool) -> String {
+    if embed {
+        format!("?month={}&embed=1", month.query())
+    } else {
+        format!("?month={}", month.query())
+    }
+}
+
+/// Embedded pages link to other embedded pages, so people browsing them stay in the frame
+fn event_url(public_id: &str, embed: bool, settings: &FormSettings) -> String {
+    if embed {
+        settings.external_url(&format!("/events/view/{}?embed=1", public_id))
+    } else {
+        settings.external_url(&format!("/events/view/{}", public_id))
+    }
+}
+
+fn tag_url(tag: &str, embed: bool, settings: &FormSettings) -> String {
+    if embed {
+        settings.external_url(&format!("/directory?tag={}&embed=1", tag))
+    } else {
+        settings.external_url(&format!("/directory?tag={}", tag))
+    }
+}
+
+fn directory_url(embed: bool, settings: &FormSettings) -> String {
+    if embed {
+        settings.external_url("/directory?embed=1")
+    } else {
+        settings.external_url("/directory")
+    }
+}
+
+/// Open Graph tags, so chat apps show the event's title, description, and picture when its page
+/// is shared, falling back to the theme's logo when the event has no picture
+fn link_preview(public_id: &str, event: &Event, settings: &FormSettings) -> Markup {
+    html! {
+        meta property="og:type" content="website";
+        meta property="og:title" content=(event.title());
+        @if !event.description().is_empty() {
+            meta property="og:description" content=(event.description());
+        }
+        meta property="og:url" content=(event_url(public_id, false, settings));
+        @if let Some(image) = event.image_url().or_else(|| settings.theme.logo()) {
+            meta property="og:image" content=(image);
+        }
+    }
+}
+
+/// The Web UI's stylesheet, linked through the external URL so it loads behind a proxy's path
+fn stylesheet(settings: &FormSettings) -> Markup {
+    html! {
+        link href=(settings.external_url("/assets/styles.css")) rel="stylesheet" type="text/css";
+    }
+}
+
+/// The theme's colors, for the page's head
+fn theme_style(theme: &Theme) -> Markup {
+    html! {
+        @if let Some(style) = theme.style() {
+            style {
+                (PreEscaped(style))
+            }
+        }
+    }
+}
+
+/// The theme's logo, shown at the top of the page
+fn theme_header(theme: &Theme) -> Markup {
+    html! {
+        @if let Some(logo) = theme.logo() {
+            header.theme {
+                img src=(logo) alt="";
+            }
+        }
+    }
+}
+
+/// The theme's footer text, shown at the bottom of the page
+fn theme_footer(theme: &Theme) -> Markup {
+    html! {
+        @if let Some(footer) = theme.footer_text() {
+            footer.theme {
+                p {
+                    (footer)
+                }
+            }
+        }
+    }
+}
+
+/// Embedded pages leave out the page's own background and margins, so they fit into the site
+/// they're shown on
+fn body_class(embed: bool) -> &'static str {
+    if embed {
+        "embed"
+    } else {
+        ""
+    }
+}
+
+/// How to check that a webhook request came from the bot
+pub fn webhook_docs_page(settings: &FormSettings) -> Markup {
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                title "Event Bot | Webhooks";
+                (stylesheet(settings))
+            }
+            body {
+                section {
+                    article {
+                        h1 {
+                            "Checking webhook requests"
+                        }
+                        p {
+                            "Whenever an event is created, updated, cancelled, or deleted, the bot "
+                            "sends a POST with a JSON body to each of the channel's webhooks. The "
+                            "body's " code { "action" } " says what happened, "
+                            code { "event" } " describes the event, " code { "timestamp" }
+                            " is when the request was sent in seconds since the Unix epoch, and "
+                            code { "nonce" } " is different for each change."
+                        }
+                        p {
+                            "Only changes to public events are sent, so members only and "
+                            "unlisted events, and events that haven't been announced yet, are "
+                            "left out."
+                        }
+                        p {
+                            "All of a channel's webhooks are signed with the channel's webhook "
+                            "secret. The bot only sends it privately: send it "
+                            code { "/webhook secret" } " in a private chat to get it, or "
+                            code { "/webhook rotate" } " to replace it. Webhooks added before "
+                            "secrets were shared by the whole channel keep their own secrets "
+                            "until the channel's secret is replaced."
                         }
                         p {
-                            "End: " (event.end_date().to_rfc2822())
+                            "To check a request:"
+                        }
+                        ol {
+                            li {
+                                "Compute the HMAC-SHA256 of the raw request body with the secret, "
+                                "and hex-encode it."
+                            }
+                            li {
+                                "Compare it with the " code { "X-Event-Bot-Signature" } " header, "
+                                "which looks like " code { "sha256=<signature>" } ", using a "
+                                "constant-time comparison. Reject the request if they differ."
+                            }
+                            li {
+                                "Reject the request if its " code { "timestamp" } " is more than "
+                                "five minutes old."
+                            }
+                            li {
+                                "Remember each " code { "nonce" } " for five minutes, and don't "
+                                "act on a nonce you've already seen. Failed requests are sent "
+                                "again with the same body, so respond with a 2xx status to a repeated "
+                                "nonce."
+                            }
+                        }
+                        p {
+                            "Requests that fail or get a response other than 2xx are tried up to "
+                            "three times. A webhook that fails five deliveries in a row isn't sent "
+                            "changes for a minute, then twice as long each time it fails again, up "
+                            "to an hour."
                         }
                     }
                 }
@@ -314,17 +1167,33 @@ pub fn success(event: Event, title: &str) -> Markup {
     }
 }
 
-pub fn error(error: &FrontendError) -> Markup {
+/// A sitemap listing each of the given absolute URLs
+pub fn sitemap_page(urls: &[String]) -> Markup {
+    html! {
+        (PreEscaped(r#"<?xml version="1.0" encoding="UTF-8"?>"#))
+        urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" {
+            @for url in urls {
+                url {
+                    loc { (url) }
+                }
+            }
+        }
+    }
+}
+
+pub fn error(error: &FrontendError, settings: &FormSettings) -> Markup {
     html! {
         (DOCTYPE)
         html {
             head {
                 meta charset="utf-8";
                 title "Event Bot | Error";
-                link href="/assets/styles.css" rel="stylesheet" type="text/css";
+                (stylesheet(settings))
+                (theme_style(&settings.theme))
             }
             body {
                 section {
+                    (theme_header(&settings.theme))
                     article {
                         h1 {
                             "Oops, there was an error processing your request"
@@ -334,7 +1203,13 @@ pub fn error(error: &FrontendError) -> Markup {
                                 (cause)
                             }
                         }
+                        @if let Some(back) = error.back() {
+                            p {
+                                a href=(back) { "Back to the form" }
+                            }
+                        }
                     }
+                    (theme_footer(&settings.theme))
                 }
             }
         }
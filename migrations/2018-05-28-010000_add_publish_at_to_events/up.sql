@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN publish_at TIMESTAMP WITH TIME ZONE;
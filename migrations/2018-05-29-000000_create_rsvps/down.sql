@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE waitlist;
+DROP TABLE rsvps;
+
+ALTER TABLE events
+DROP COLUMN capacity;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN capacity INTEGER;
+
+CREATE TABLE rsvps (
+    id          SERIAL UNIQUE PRIMARY KEY,
+    events_id   INTEGER REFERENCES events ON DELETE CASCADE NOT NULL,
+    users_id    INTEGER REFERENCES users ON DELETE CASCADE NOT NULL,
+    UNIQUE (events_id, users_id)
+);
+
+CREATE TABLE waitlist (
+    id          SERIAL UNIQUE PRIMARY KEY,
+    events_id   INTEGER REFERENCES events ON DELETE CASCADE NOT NULL,
+    users_id    INTEGER REFERENCES users ON DELETE CASCADE NOT NULL,
+    UNIQUE (events_id, users_id)
+);
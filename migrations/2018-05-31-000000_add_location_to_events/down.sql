@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+DROP COLUMN location_hint;
+
+ALTER TABLE events
+DROP COLUMN location;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN location TEXT;
+
+ALTER TABLE events
+ADD COLUMN location_hint TEXT;
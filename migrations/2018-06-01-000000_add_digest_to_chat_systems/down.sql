@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+DROP COLUMN last_digest_at,
+DROP COLUMN pin_digest,
+DROP COLUMN digest_day;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN digest_day SMALLINT DEFAULT 0,
+ADD COLUMN pin_digest BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN last_digest_at TIMESTAMP WITH TIME ZONE;
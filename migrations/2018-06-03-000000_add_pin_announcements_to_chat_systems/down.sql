@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+DROP COLUMN pinned_event_id,
+DROP COLUMN pin_announcements;
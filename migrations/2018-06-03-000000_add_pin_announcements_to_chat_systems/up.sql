@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN pin_announcements BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN pinned_event_id INTEGER;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE edit_event_links
+DROP COLUMN expires_at;
+
+ALTER TABLE new_event_links
+DROP COLUMN expires_at;
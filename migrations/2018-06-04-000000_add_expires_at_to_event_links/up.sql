@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE new_event_links
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW() + INTERVAL '1 day';
+
+ALTER TABLE edit_event_links
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW() + INTERVAL '1 day';
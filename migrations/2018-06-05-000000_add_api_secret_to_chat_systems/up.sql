@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN api_secret TEXT;
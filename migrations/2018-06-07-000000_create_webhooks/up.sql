@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE webhooks (
+    id          SERIAL UNIQUE PRIMARY KEY,
+    system_id   INTEGER REFERENCES chat_systems ON DELETE CASCADE NOT NULL,
+    url         TEXT NOT NULL,
+    secret      TEXT NOT NULL,
+    UNIQUE (system_id, url)
+);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+-- The old secrets can't be restored, links and tokens have to be created again.
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Secrets were bcrypt hashes, which can't be checked against SHA-256 hashes of the new tokens.
+-- Unused links are dropped, and channels need to issue /token again.
+DELETE FROM new_event_links;
+DELETE FROM edit_event_links;
+UPDATE chat_systems SET api_secret = NULL;
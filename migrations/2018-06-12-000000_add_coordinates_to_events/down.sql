@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+DROP CONSTRAINT events_coordinates_check,
+DROP COLUMN longitude,
+DROP COLUMN latitude;
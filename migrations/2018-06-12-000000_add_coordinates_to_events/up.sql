@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN latitude DOUBLE PRECISION,
+ADD COLUMN longitude DOUBLE PRECISION,
+ADD CONSTRAINT events_coordinates_check CHECK ((latitude IS NULL) = (longitude IS NULL));
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN greeting TEXT,
+ADD COLUMN greeting_message_id BIGINT;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+DROP COLUMN ended_template,
+DROP COLUMN reminder_template,
+DROP COLUMN announcement_template;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN announcement_template TEXT,
+ADD COLUMN reminder_template TEXT,
+ADD COLUMN ended_template TEXT;
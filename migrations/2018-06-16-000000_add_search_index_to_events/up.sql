@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX events_search ON events USING GIN (to_tsvector('english', title || ' ' || description));
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN rsvp_deadline TIMESTAMP WITH TIME ZONE;
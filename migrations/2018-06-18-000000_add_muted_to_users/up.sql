@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE users
+ADD COLUMN muted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+DROP COLUMN channel_username,
+DROP COLUMN channel_title,
+DROP COLUMN listed;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN listed BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN channel_title TEXT,
+ADD COLUMN channel_username TEXT;
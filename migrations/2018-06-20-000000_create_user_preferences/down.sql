@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE users
+ADD COLUMN muted BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE users SET muted = TRUE
+FROM user_preferences AS up
+WHERE up.users_id = users.id AND up.mute_broadcasts = TRUE;
+
+DROP TABLE user_preferences;
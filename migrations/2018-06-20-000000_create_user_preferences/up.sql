@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE user_preferences (
+    id              SERIAL UNIQUE PRIMARY KEY,
+    users_id        INTEGER REFERENCES users ON DELETE CASCADE NOT NULL UNIQUE,
+    mute_reminders  BOOLEAN NOT NULL DEFAULT FALSE,
+    mute_broadcasts BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO user_preferences (users_id, mute_broadcasts)
+SELECT id, TRUE FROM users WHERE muted = TRUE;
+
+ALTER TABLE users
+DROP COLUMN muted;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE chat_system_features (
+    id                  SERIAL UNIQUE PRIMARY KEY,
+    chat_systems_id     INTEGER REFERENCES chat_systems ON DELETE CASCADE NOT NULL UNIQUE,
+    rsvp_enabled        BOOLEAN NOT NULL DEFAULT TRUE,
+    digest_enabled      BOOLEAN NOT NULL DEFAULT TRUE,
+    public_page_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    payments_enabled    BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_preferences
+DROP COLUMN mute_waitlist,
+DROP COLUMN mute_host_nudges,
+DROP COLUMN mute_digests;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE user_preferences
+ADD COLUMN mute_digests BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN mute_host_nudges BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN mute_waitlist BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN created_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE events
+ALTER COLUMN created_at SET DEFAULT NOW();
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+DROP COLUMN boosted,
+DROP COLUMN boost_threshold;
+
+ALTER TABLE chat_systems
+DROP COLUMN boost_threshold;
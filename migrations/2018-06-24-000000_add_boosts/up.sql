@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN boost_threshold INTEGER;
+
+ALTER TABLE events
+ADD COLUMN boost_threshold INTEGER,
+ADD COLUMN boosted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+DROP COLUMN pending;
+
+ALTER TABLE chat_systems
+DROP COLUMN moderated;
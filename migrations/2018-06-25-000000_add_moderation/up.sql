@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN moderated BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE events
+ADD COLUMN pending BOOLEAN NOT NULL DEFAULT FALSE;
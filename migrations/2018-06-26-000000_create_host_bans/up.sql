@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE host_bans (
+    id              SERIAL UNIQUE PRIMARY KEY,
+    chat_systems_id INTEGER REFERENCES chat_systems ON DELETE CASCADE NOT NULL,
+    users_id        INTEGER REFERENCES users ON DELETE CASCADE NOT NULL,
+    UNIQUE (chat_systems_id, users_id)
+);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN unlisted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER notify_event_change ON events;
+DROP FUNCTION notify_event_change();
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE OR REPLACE FUNCTION notify_event_change() RETURNS trigger AS $$
+BEGIN
+    IF (TG_OP = 'DELETE') THEN
+        PERFORM pg_notify('event_changes', 'deleted:' || OLD.id);
+    ELSE
+        PERFORM pg_notify('event_changes', 'saved:' || NEW.id);
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_event_change AFTER INSERT OR UPDATE OR DELETE ON events
+    FOR EACH ROW EXECUTE PROCEDURE notify_event_change();
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events DROP COLUMN new_event_links_id;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN new_event_links_id INTEGER UNIQUE REFERENCES new_event_links ON DELETE SET NULL;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+UPDATE webhooks AS wh
+SET secret = sys.webhook_secret
+FROM chat_systems AS sys
+WHERE sys.id = wh.system_id AND wh.secret IS NULL;
+
+ALTER TABLE webhooks ALTER COLUMN secret SET NOT NULL;
+
+ALTER TABLE chat_systems DROP COLUMN webhook_secret;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems ADD COLUMN webhook_secret TEXT;
+
+-- New webhooks are signed with the secret of each channel's first webhook
+UPDATE chat_systems AS sys
+SET webhook_secret = wh.secret
+FROM webhooks AS wh
+WHERE wh.id = (SELECT MIN(first.id) FROM webhooks AS first WHERE first.system_id = sys.id);
+
+-- The channel's other webhooks keep signing with their own secrets until the channel's secret is
+-- replaced, so their receivers don't start rejecting requests
+ALTER TABLE webhooks ALTER COLUMN secret DROP NOT NULL;
+
+UPDATE webhooks AS wh
+SET secret = NULL
+FROM chat_systems AS sys
+WHERE sys.id = wh.system_id AND sys.webhook_secret = wh.secret;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE failed_messages (
+    id              SERIAL UNIQUE PRIMARY KEY,
+    chat_id         BIGINT NOT NULL,
+    event_id        INTEGER NOT NULL,
+    announcement    BOOLEAN NOT NULL DEFAULT FALSE,
+    text            TEXT NOT NULL,
+    error           TEXT NOT NULL,
+    failed_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
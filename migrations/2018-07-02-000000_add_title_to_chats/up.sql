@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE chats
+ADD COLUMN title TEXT;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+DROP COLUMN language;
+
+ALTER TABLE user_preferences
+DROP COLUMN language;
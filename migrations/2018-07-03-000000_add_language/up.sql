@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE user_preferences
+ADD COLUMN language TEXT;
+
+ALTER TABLE chat_systems
+ADD COLUMN language TEXT;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_preferences
+DROP COLUMN twelve_hour;
+
+ALTER TABLE chat_systems
+DROP COLUMN twelve_hour;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE chat_systems
+ADD COLUMN twelve_hour BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE user_preferences
+ADD COLUMN twelve_hour BOOLEAN;
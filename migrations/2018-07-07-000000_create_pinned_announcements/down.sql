@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_systems
+ADD COLUMN pinned_event_id INTEGER;
+
+DROP TABLE pinned_announcements;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE pinned_announcements (
+    events_id       INTEGER UNIQUE PRIMARY KEY,
+    chat_systems_id INTEGER NOT NULL REFERENCES chat_systems ON DELETE CASCADE,
+    message_id      BIGINT NOT NULL
+);
+
+ALTER TABLE chat_systems
+DROP COLUMN pinned_event_id;
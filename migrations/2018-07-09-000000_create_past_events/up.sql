@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+-- Events are deleted once they end, so what the stats need from them is kept here
+CREATE TABLE past_events (
+    id          INTEGER UNIQUE PRIMARY KEY,
+    system_id   INTEGER REFERENCES chat_systems ON DELETE CASCADE NOT NULL,
+    start_date  TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_date    TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at  TIMESTAMP WITH TIME ZONE,
+    going       INTEGER NOT NULL,
+    cancelled   BOOLEAN NOT NULL,
+    host_ids    INTEGER[] NOT NULL
+);
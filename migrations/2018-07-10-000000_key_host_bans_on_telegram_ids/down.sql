@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE host_bans ADD COLUMN users_id INTEGER REFERENCES users ON DELETE CASCADE;
+
+UPDATE host_bans AS hb
+SET users_id = usr.id
+FROM users AS usr
+WHERE usr.user_id = hb.user_id;
+
+DELETE FROM host_bans WHERE users_id IS NULL;
+
+ALTER TABLE host_bans DROP COLUMN user_id;
+ALTER TABLE host_bans ALTER COLUMN users_id SET NOT NULL;
+ALTER TABLE host_bans ADD UNIQUE (chat_systems_id, users_id);
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- Bans outlive the bot forgetting a user, and users it hasn't seen yet can be banned too
+ALTER TABLE host_bans ADD COLUMN user_id BIGINT;
+
+UPDATE host_bans AS hb
+SET user_id = usr.user_id
+FROM users AS usr
+WHERE usr.id = hb.users_id;
+
+DELETE FROM host_bans WHERE user_id IS NULL;
+
+ALTER TABLE host_bans DROP COLUMN users_id;
+ALTER TABLE host_bans ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE host_bans ADD UNIQUE (chat_systems_id, user_id);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Each instance of the bot tracks every event, so a stage is only carried out by the instance that
+-- claims it here first
+CREATE TABLE timer_claims (
+    events_id   INTEGER REFERENCES events ON DELETE CASCADE NOT NULL,
+    stage       TEXT NOT NULL,
+    due         TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (events_id, stage, due)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_preferences
+ADD COLUMN mute_digests BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- The weekly digest is only posted in channels, so there is no private digest to mute
+ALTER TABLE user_preferences
+DROP COLUMN mute_digests;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE chat_system_features
+ADD COLUMN payments_enabled BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- The bot has no paid events, so there is nothing for the flag to turn on
+ALTER TABLE chat_system_features
+DROP COLUMN payments_enabled;
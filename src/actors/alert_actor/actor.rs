@@ -0,0 +1,53 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the actor-related behaviours for the AlertActor
+
+use actix::{Actor, Context, Handler};
+
+use super::messages::*;
+use super::AlertActor;
+
+impl Actor for AlertActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<Alert> for AlertActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Alert, _: &mut Self::Context) -> Self::Result {
+        self.alert(msg.key, msg.text);
+    }
+}
+
+impl Handler<Resolved> for AlertActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Resolved, _: &mut Self::Context) -> Self::Result {
+        self.resolved(msg.key, msg.text);
+    }
+}
+
+impl Handler<Snooze> for AlertActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Snooze, _: &mut Self::Context) -> Self::Result {
+        self.snooze(msg.minutes);
+    }
+}
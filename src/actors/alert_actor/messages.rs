@@ -0,0 +1,58 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the messages that the AlertActor can receive.
+
+use actix::Message;
+
+/// This tells the AlertActor about a problem the people running the bot should know about
+///
+/// `key` names the problem, so it isn't posted again each time it happens
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Alert {
+    pub key: String,
+    pub text: String,
+}
+
+impl Message for Alert {
+    type Result = ();
+}
+
+/// This tells the AlertActor that a problem it was told about is over
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Resolved {
+    pub key: String,
+    pub text: String,
+}
+
+impl Message for Resolved {
+    type Result = ();
+}
+
+/// This asks the AlertActor to hold back alerts for the given number of minutes
+///
+/// Zero minutes stops holding them back
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Snooze {
+    pub minutes: u64,
+}
+
+impl Message for Snooze {
+    type Result = ();
+}
@@ -0,0 +1,118 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the AlertActor, which tells the people running the bot about problems by
+//! posting them to the alert chat.
+//!
+//! Each alert has a key naming the problem, like `database`. While a problem keeps happening, it's
+//! only posted again every `REPEAT_SECONDS`, and once it's resolved a message says so. Bot admins
+//! can snooze alerts with `/snooze`, which holds back new alerts until the snooze runs out.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use actix::Arbiter;
+use futures::Future;
+use telebot::functions::FunctionMessage;
+use telebot::objects::Integer;
+use telebot::RcBot;
+
+use config::Config;
+use logging::LogContext;
+use util::telegram_error;
+
+mod actor;
+pub mod messages;
+
+/// How many seconds pass before an ongoing problem is posted again
+const REPEAT_SECONDS: u64 = 30 * 60;
+
+/// The AlertActor posts alerts to the alert chat, if one is set up
+pub struct AlertActor {
+    bot: RcBot,
+    chat_id: Option<Integer>,
+    // When each ongoing problem was last posted
+    active: HashMap<String, Instant>,
+    snoozed_until: Option<Instant>,
+}
+
+impl AlertActor {
+    pub fn new(config: &Config, bot: RcBot) -> Self {
+        AlertActor {
+            bot,
+            chat_id: config.alert_chat(),
+            active: HashMap::new(),
+            snoozed_until: None,
+        }
+    }
+
+    fn alert(&mut self, key: String, text: String) {
+        let now = Instant::now();
+
+        if self.snoozed(now) {
+            return;
+        }
+
+        let repeat = Duration::from_secs(REPEAT_SECONDS);
+
+        if let Some(posted) = self.active.get(&key) {
+            if now < *posted + repeat {
+                return;
+            }
+        }
+
+        self.active.insert(key, now);
+        self.post(format!("Alert: {}", text));
+    }
+
+    fn resolved(&mut self, key: String, text: String) {
+        if self.active.remove(&key).is_some() && !self.snoozed(Instant::now()) {
+            self.post(format!("Resolved: {}", text));
+        }
+    }
+
+    /// Hold back alerts for the given number of minutes, or stop holding them back if it's zero
+    fn snooze(&mut self, minutes: u64) {
+        self.snoozed_until = if minutes > 0 {
+            Some(Instant::now() + Duration::from_secs(minutes * 60))
+        } else {
+            None
+        };
+    }
+
+    fn snoozed(&self, now: Instant) -> bool {
+        self.snoozed_until.map(|until| now < until).unwrap_or(false)
+    }
+
+    fn post(&self, text: String) {
+        let chat_id = match self.chat_id {
+            Some(chat_id) => chat_id,
+            None => return,
+        };
+
+        Arbiter::handle().spawn(
+            self.bot
+                .message(chat_id, text)
+                .send()
+                .map(|_| ())
+                .map_err(telegram_error)
+                .map_err(log_err!(LogContext::new("alerts"), "Error posting alert")),
+        );
+    }
+}
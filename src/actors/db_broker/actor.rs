@@ -19,28 +19,46 @@
 
 //! This module defines all the Handler and Actor traits for the `DbBroker` type.
 
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
 use actix::fut::wrap_future;
 use actix::{Actor, Addr, Arbiter, AsyncContext, Context, Handler, ResponseActFuture, Unsync};
 use futures::Future;
 use telebot::objects::Integer;
 use tokio_postgres::Connection;
+use tokio_timer::Delay;
 
 use super::messages::*;
-use super::DbBroker;
+use super::{
+    reconnect_delay, DbBroker, PoolCounts, DATABASE_ALERT, POOL_ALERT, RELISTEN_SECONDS,
+};
+use actors::alert_actor::messages::{Alert, Resolved};
 use conn::connect_to_database;
-use error::EventError;
+use error::{EventError, EventErrorKind};
+use event_web::locale::Language;
+use logging::LogContext;
 use models::chat::Chat;
-use models::chat_system::ChatSystem;
+use models::chat_system::{ChatSystem, Listing};
 use models::edit_event_link::EditEventLink;
 use models::event::Event;
+use models::failed_message::FailedMessage;
+use models::features::Features;
 use models::new_event_link::NewEventLink;
-use models::user::User;
+use models::rsvp::RsvpStatus;
+use models::stats::{Stats, TimeSlot};
+use models::user::{Export, Forgotten, User};
+use models::user_preferences::UserPreferences;
+use models::webhook::Webhook;
 
 type FutureResponse<I> = ResponseActFuture<DbBroker, I, EventError>;
 
 impl DbBroker {
     /// Given a function that returns a future, create an ActorFuture that will run in the context
     /// of the Broker, providing a Connection to the future and taking it back afterwards
+    ///
+    /// If no Connection frees up within the busy timeout, the future fails with
+    /// `EventErrorKind::Busy` instead of waiting any longer
     fn wrap_fut<I, Fut, Func>(
         &self,
         f: Func,
@@ -52,10 +70,26 @@ impl DbBroker {
         I: 'static,
     {
         let addr: Addr<Unsync, _> = ctx.address();
+        let exhausted = Rc::clone(&self.exhausted);
+        let alerts = self.alerts.clone();
+        let pool_size = self.num_connections;
 
         Box::new(wrap_future(
             self.connections
-                .clone()
+                .checkout(self.busy_timeout)
+                .map_err(move |e| {
+                    if e.kind() == EventErrorKind::Busy {
+                        exhausted.set(true);
+                        alerts.do_send(Alert {
+                            key: POOL_ALERT.to_owned(),
+                            text: format!(
+                                "All {} database connections are busy, requests are being turned away",
+                                pool_size
+                            ),
+                        });
+                    }
+                    e
+                })
                 .map_err(Err)
                 .and_then(move |connection| f(connection).map_err(Ok))
                 .then(move |full_res| match full_res {
@@ -65,7 +99,9 @@ impl DbBroker {
                     }
                     Err(res) => match res {
                         Ok((err, connection)) => {
-                            addr.do_send(Ready { connection });
+                            // The connection may have been lost, so it's checked before it's
+                            // used again
+                            addr.do_send(Check { connection });
                             Err(err)
                         }
                         Err(err) => Err(err),
@@ -82,16 +118,103 @@ impl Actor for DbBroker {
         let db_broker: Addr<Unsync, _> = ctx.address();
 
         for _ in 0..self.num_connections {
-            let fut = connect_to_database(self.db_url.clone(), Arbiter::handle().clone())
-                .join(Ok(db_broker.clone()))
-                .and_then(move |(connection, db_broker)| {
-                    db_broker.do_send(Ready { connection });
-                    Ok(())
-                })
-                .map_err(|e| error!("Error: {:?}", e));
+            db_broker.do_send(Reconnect { attempt: 0 });
+        }
+    }
+}
+
+impl Handler<Reconnect> for DbBroker {
+    type Result = ();
+
+    fn handle(&mut self, msg: Reconnect, ctx: &mut Self::Context) -> Self::Result {
+        let db_broker: Addr<Unsync, _> = ctx.address();
+        let db_url = self.db_url.clone();
+        let alerts = self.alerts.clone();
+        let attempt = msg.attempt;
 
-            Arbiter::handle().spawn(fut);
+        // Retries are for a connection that's already counted
+        if attempt == 0 {
+            self.connecting += 1;
         }
+
+        let fut = Delay::new(Instant::now() + reconnect_delay(attempt))
+            .then(move |_| connect_to_database(db_url, Arbiter::handle().clone()))
+            .then(move |res| {
+                match res {
+                    Ok(connection) => db_broker.do_send(Connected { connection }),
+                    Err(e) => {
+                        ctx_error!(
+                            LogContext::new("db_broker"),
+                            "Error connecting to database, attempt {}: {:?}",
+                            attempt + 1,
+                            e
+                        );
+                        alerts.do_send(Alert {
+                            key: DATABASE_ALERT.to_owned(),
+                            text: format!("Can't connect to the database: {}", e),
+                        });
+                        db_broker.do_send(Reconnect {
+                            attempt: attempt + 1,
+                        });
+                    }
+                }
+
+                Ok(())
+            });
+
+        Arbiter::handle().spawn(fut);
+    }
+}
+
+impl Handler<Connected> for DbBroker {
+    type Result = ();
+
+    fn handle(&mut self, msg: Connected, _: &mut Self::Context) -> Self::Result {
+        self.connecting = self.connecting.saturating_sub(1);
+        self.connections.restore(msg.connection);
+        self.alerts.do_send(Resolved {
+            key: DATABASE_ALERT.to_owned(),
+            text: "Connected to the database".to_owned(),
+        });
+        ctx_debug!(
+            LogContext::new("db_broker"),
+            "Opened db connection, total available connections: {}",
+            self.connections.idle()
+        );
+    }
+}
+
+impl Handler<PoolStatus> for DbBroker {
+    type Result = Result<PoolCounts, EventError>;
+
+    fn handle(&mut self, _: PoolStatus, _: &mut Self::Context) -> Self::Result {
+        Ok(self.pool_counts())
+    }
+}
+
+impl Handler<Check> for DbBroker {
+    type Result = ();
+
+    fn handle(&mut self, msg: Check, ctx: &mut Self::Context) -> Self::Result {
+        let db_broker: Addr<Unsync, _> = ctx.address();
+
+        let fut = msg.connection.batch_execute("SELECT 1").then(move |res| {
+            match res {
+                Ok(connection) => db_broker.do_send(Ready { connection }),
+                Err((e, _)) => {
+                    ctx_error!(
+                        LogContext::new("db_broker"),
+                        "Lost db connection, replacing it: {:?}",
+                        e
+                    );
+                    db_broker.do_send(Reconnect { attempt: 0 });
+                }
+            }
+
+            Ok(())
+        });
+
+        Arbiter::handle().spawn(fut);
     }
 }
 
@@ -99,14 +222,49 @@ impl Handler<Ready> for DbBroker {
     type Result = ();
 
     fn handle(&mut self, msg: Ready, _: &mut Self::Context) -> Self::Result {
-        self.connections.0.borrow_mut().push_back(msg.connection);
-        debug!(
+        self.connections.restore(msg.connection);
+
+        if self.exhausted.get() && self.connections.waiting() == 0 {
+            self.exhausted.set(false);
+            self.alerts.do_send(Resolved {
+                key: POOL_ALERT.to_owned(),
+                text: "Database connections are free again".to_owned(),
+            });
+        }
+        ctx_debug!(
+            LogContext::new("db_broker"),
             "Restored db connection, total available connections: {}",
-            self.connections.0.borrow().len()
+            self.connections.idle()
         );
     }
 }
 
+impl Handler<ListenForChanges> for DbBroker {
+    type Result = ();
+
+    fn handle(&mut self, msg: ListenForChanges, ctx: &mut Self::Context) -> Self::Result {
+        let db_broker: Addr<Unsync, _> = ctx.address();
+        let recipient = msg.recipient.clone();
+
+        let fut = DbBroker::listen(self.db_url.clone(), msg.recipient)
+            .then(|res| {
+                if let Err(e) = res {
+                    ctx_error!(LogContext::new("db_broker"), "Stopped listening: {:?}", e);
+                }
+
+                Delay::new(Instant::now() + Duration::from_secs(RELISTEN_SECONDS))
+            })
+            .then(move |_| {
+                // Changes made while the connection was down are picked up by the Timers' next
+                // check for upcoming events
+                db_broker.do_send(ListenForChanges { recipient });
+                Ok(())
+            });
+
+        Arbiter::handle().spawn(fut);
+    }
+}
+
 impl Handler<NewChannel> for DbBroker {
     type Result = FutureResponse<ChatSystem>;
 
@@ -129,6 +287,30 @@ impl Handler<DeleteChannel> for DbBroker {
     }
 }
 
+impl Handler<SetChatTitle> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: SetChatTitle, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_chat_title(msg.chat_id, msg.title, msg.username, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<DeleteChat> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: DeleteChat, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::delete_chat(msg.chat_id, connection),
+            ctx,
+        )
+    }
+}
+
 impl Handler<NewChat> for DbBroker {
     type Result = FutureResponse<Chat>;
 
@@ -167,9 +349,11 @@ impl Handler<NewRelation> for DbBroker {
 }
 
 impl Handler<NewEvent> for DbBroker {
-    type Result = FutureResponse<Event>;
+    type Result = FutureResponse<(Event, bool)>;
 
     fn handle(&mut self, msg: NewEvent, ctx: &mut Self::Context) -> Self::Result {
+        let event_quota = self.event_quota;
+
         self.wrap_fut(
             move |connection| {
                 DbBroker::insert_event(
@@ -179,6 +363,19 @@ impl Handler<NewEvent> for DbBroker {
                     msg.start_date,
                     msg.end_date,
                     msg.hosts,
+                    msg.publish_at,
+                    msg.capacity,
+                    msg.boost_threshold,
+                    msg.location,
+                    msg.location_hint,
+                    msg.coordinates,
+                    msg.image_url,
+                    msg.rsvp_deadline,
+                    msg.members_only,
+                    msg.unlisted,
+                    msg.needs_approval,
+                    msg.link_id,
+                    event_quota,
                     connection,
                 )
             },
@@ -201,6 +398,17 @@ impl Handler<EditEvent> for DbBroker {
                     msg.start_date,
                     msg.end_date,
                     msg.hosts,
+                    msg.publish_at,
+                    msg.capacity,
+                    msg.boost_threshold,
+                    msg.location,
+                    msg.location_hint,
+                    msg.coordinates,
+                    msg.image_url,
+                    msg.rsvp_deadline,
+                    msg.members_only,
+                    msg.unlisted,
+                    msg.link_id,
                     connection,
                 )
             },
@@ -220,6 +428,74 @@ impl Handler<LookupEventsByChatId> for DbBroker {
     }
 }
 
+impl Handler<LookupNextEventByChatId> for DbBroker {
+    type Result = FutureResponse<Option<Event>>;
+
+    fn handle(&mut self, msg: LookupNextEventByChatId, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::get_next_event_by_chat_id(msg.chat_id, msg.after, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupEventsByChatIdInRange> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
+
+    fn handle(
+        &mut self,
+        msg: LookupEventsByChatIdInRange,
+        ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::get_events_by_chat_id_in_range(
+                    msg.chat_id,
+                    msg.start_date,
+                    msg.end_date,
+                    connection,
+                )
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SearchEvents> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
+
+    fn handle(&mut self, msg: SearchEvents, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::search_events(msg.scope, msg.terms, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetListings> for DbBroker {
+    type Result = FutureResponse<Vec<Listing>>;
+
+    fn handle(&mut self, _: GetListings, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_listings(connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SearchDirectory> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
+
+    fn handle(&mut self, msg: SearchDirectory, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::search_directory(msg.terms, msg.tag, connection),
+            ctx,
+        )
+    }
+}
+
 impl Handler<LookupEvent> for DbBroker {
     type Result = FutureResponse<Event>;
 
@@ -231,6 +507,17 @@ impl Handler<LookupEvent> for DbBroker {
     }
 }
 
+impl Handler<LookupEventByPublicId> for DbBroker {
+    type Result = FutureResponse<Event>;
+
+    fn handle(&mut self, msg: LookupEventByPublicId, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::lookup_event_by_public_id(msg.public_id, connection),
+            ctx,
+        )
+    }
+}
+
 impl Handler<LookupEventsByUserId> for DbBroker {
     type Result = FutureResponse<Vec<Event>>;
 
@@ -253,6 +540,74 @@ impl Handler<DeleteEvent> for DbBroker {
     }
 }
 
+impl Handler<SetAnnouncement> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: SetAnnouncement, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::set_announcement(msg.event_id, msg.message_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<CancelEvent> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: CancelEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::cancel_event(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<ApproveEvent> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: ApproveEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::approve_event(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<RejectEvent> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: RejectEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::reject_event(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<TransferEvent> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: TransferEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::transfer_event(msg.event_id, msg.from_host, msg.to_host, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupAnnouncement> for DbBroker {
+    type Result = FutureResponse<Option<Integer>>;
+
+    fn handle(&mut self, msg: LookupAnnouncement, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::lookup_announcement(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
 impl Handler<GetEventsInRange> for DbBroker {
     type Result = FutureResponse<Vec<Event>>;
 
@@ -266,176 +621,853 @@ impl Handler<GetEventsInRange> for DbBroker {
     }
 }
 
-impl Handler<LookupSystem> for DbBroker {
-    type Result = FutureResponse<ChatSystem>;
+impl Handler<GetEventsToPublish> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
 
-    fn handle(&mut self, msg: LookupSystem, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: GetEventsToPublish, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_system_by_id(msg.system_id, connection),
+            move |connection| DbBroker::get_events_to_publish(msg.before, connection),
             ctx,
         )
     }
 }
 
-impl Handler<LookupSystemWithChats> for DbBroker {
-    type Result = FutureResponse<(ChatSystem, Vec<Integer>)>;
+impl Handler<GetEventsClosingRsvps> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
 
-    fn handle(&mut self, msg: LookupSystemWithChats, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: GetEventsClosingRsvps, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_system_with_chats_by_id(msg.system_id, connection),
+            move |connection| {
+                DbBroker::get_events_closing_rsvps(msg.start_date, msg.end_date, connection)
+            },
             ctx,
         )
     }
 }
 
-impl Handler<LookupSystemByChannel> for DbBroker {
-    type Result = FutureResponse<ChatSystem>;
+impl Handler<ClaimBoosts> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
 
-    fn handle(&mut self, msg: LookupSystemByChannel, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: ClaimBoosts, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_system_by_channel(msg.0, connection),
+            move |connection| DbBroker::claim_boosts(msg.before, connection),
             ctx,
         )
     }
 }
 
-impl Handler<GetEventsForSystem> for DbBroker {
-    type Result = FutureResponse<Vec<Event>>;
+impl Handler<MarkPublished> for DbBroker {
+    type Result = FutureResponse<()>;
 
-    fn handle(&mut self, msg: GetEventsForSystem, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: MarkPublished, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_events_for_system(msg.system_id, connection),
+            move |connection| DbBroker::mark_published(msg.event_id, connection),
             ctx,
         )
     }
 }
 
-impl Handler<GetUsersWithChats> for DbBroker {
-    type Result = FutureResponse<Vec<(User, Chat)>>;
+impl Handler<ClaimStage> for DbBroker {
+    type Result = FutureResponse<bool>;
 
-    fn handle(&mut self, _: GetUsersWithChats, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: ClaimStage, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_users_with_chats(connection),
+            move |connection| DbBroker::claim_stage(msg.event_id, msg.stage, msg.due, connection),
             ctx,
         )
     }
 }
 
-impl Handler<StoreEditEventLink> for DbBroker {
-    type Result = FutureResponse<EditEventLink>;
+impl Handler<ToggleProtectContent> for DbBroker {
+    type Result = FutureResponse<bool>;
 
-    fn handle(&mut self, msg: StoreEditEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: ToggleProtectContent, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| {
-                DbBroker::store_edit_event_link(
-                    msg.user_id,
-                    msg.system_id,
-                    msg.event_id,
-                    msg.secret,
-                    connection,
-                )
-            },
+            move |connection| DbBroker::toggle_protect_content(msg.channel_id, connection),
             ctx,
         )
     }
 }
 
-impl Handler<LookupEditEventLink> for DbBroker {
-    type Result = FutureResponse<EditEventLink>;
+impl Handler<ToggleModerated> for DbBroker {
+    type Result = FutureResponse<bool>;
 
-    fn handle(&mut self, msg: LookupEditEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: ToggleModerated, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_edit_event_link(msg.0, connection),
+            move |connection| DbBroker::toggle_moderated(msg.channel_id, connection),
             ctx,
         )
     }
 }
 
-impl Handler<DeleteEditEventLink> for DbBroker {
-    type Result = FutureResponse<()>;
+impl Handler<BanHost> for DbBroker {
+    type Result = FutureResponse<bool>;
 
-    fn handle(&mut self, msg: DeleteEditEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: BanHost, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::delete_edit_event_link(msg.id, connection),
+            move |connection| DbBroker::ban_host(msg.channel_id, msg.user, connection),
             ctx,
         )
     }
 }
 
-impl Handler<StoreEventLink> for DbBroker {
-    type Result = FutureResponse<NewEventLink>;
+impl Handler<UnbanHost> for DbBroker {
+    type Result = FutureResponse<bool>;
 
-    fn handle(&mut self, msg: StoreEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: UnbanHost, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::unban_host(msg.channel_id, msg.user, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<CheckBanned> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: CheckBanned, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::check_banned(msg.system_id, msg.user_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<TogglePinAnnouncements> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: TogglePinAnnouncements, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::toggle_pin_announcements(msg.channel_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<ToggleListed> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: ToggleListed, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
             move |connection| {
-                DbBroker::store_event_link(msg.user_id, msg.system_id, msg.secret, connection)
+                DbBroker::toggle_listed(msg.channel_id, msg.title, msg.username, connection)
             },
             ctx,
         )
     }
 }
 
-impl Handler<LookupEventLink> for DbBroker {
-    type Result = FutureResponse<NewEventLink>;
+impl Handler<SetApiSecret> for DbBroker {
+    type Result = FutureResponse<i32>;
 
-    fn handle(&mut self, msg: LookupEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: SetApiSecret, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_event_link(msg.0, connection),
+            move |connection| DbBroker::set_api_secret(msg.channel_id, msg.secret, connection),
             ctx,
         )
     }
 }
 
-impl Handler<DeleteEventLink> for DbBroker {
-    type Result = FutureResponse<()>;
+impl Handler<LookupSystemWithApiSecret> for DbBroker {
+    type Result = FutureResponse<(ChatSystem, Option<String>)>;
 
-    fn handle(&mut self, msg: DeleteEventLink, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: LookupSystemWithApiSecret, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::delete_event_link(msg.id, connection),
+            move |connection| DbBroker::lookup_system_with_api_secret(msg.0, connection),
             ctx,
         )
     }
 }
 
-impl Handler<LookupUser> for DbBroker {
-    type Result = FutureResponse<User>;
+impl Handler<SetReadSecret> for DbBroker {
+    type Result = FutureResponse<i32>;
 
-    fn handle(&mut self, msg: LookupUser, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: SetReadSecret, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::lookup_user(msg.0, connection),
+            move |connection| DbBroker::set_read_secret(msg.channel_id, msg.secret, connection),
             ctx,
         )
     }
 }
 
-impl Handler<GetSystemsWithChats> for DbBroker {
-    type Result = FutureResponse<Vec<(ChatSystem, Chat)>>;
+impl Handler<LookupSystemWithReadSecret> for DbBroker {
+    type Result = FutureResponse<(ChatSystem, Option<String>)>;
 
-    fn handle(&mut self, _: GetSystemsWithChats, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: LookupSystemWithReadSecret, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::get_systems_with_chats(connection),
+            move |connection| DbBroker::lookup_system_with_read_secret(msg.0, connection),
             ctx,
         )
     }
 }
 
-impl Handler<RemoveUserChat> for DbBroker {
-    type Result = FutureResponse<()>;
+impl Handler<SetCalendarSecret> for DbBroker {
+    type Result = FutureResponse<i32>;
+
+    fn handle(&mut self, msg: SetCalendarSecret, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::set_calendar_secret(msg.channel_id, msg.secret, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupSystemWithCalendarSecret> for DbBroker {
+    type Result = FutureResponse<(ChatSystem, Option<String>)>;
 
-    fn handle(&mut self, msg: RemoveUserChat, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(
+        &mut self,
+        msg: LookupSystemWithCalendarSecret,
+        ctx: &mut Self::Context,
+    ) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::remove_user_chat(msg.0, msg.1, connection),
+            move |connection| DbBroker::lookup_system_with_calendar_secret(msg.0, connection),
             ctx,
         )
     }
 }
 
-impl Handler<DeleteUserByUserId> for DbBroker {
+impl Handler<SetPinnedEvent> for DbBroker {
     type Result = FutureResponse<()>;
 
-    fn handle(&mut self, msg: DeleteUserByUserId, ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: SetPinnedEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_pinned_event(msg.system_id, msg.event_id, msg.message_id, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<ClearPinnedEvent> for DbBroker {
+    type Result = FutureResponse<Option<Integer>>;
+
+    fn handle(&mut self, msg: ClearPinnedEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::clear_pinned_event(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetDigestDay> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: SetDigestDay, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_digest_day(msg.channel_id, msg.digest_day, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetBoostThreshold> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: SetBoostThreshold, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_boost_threshold(msg.channel_id, msg.boost_threshold, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetGreeting> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: SetGreeting, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::set_greeting(msg.channel_id, msg.greeting, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetChannelLanguage> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: SetChannelLanguage, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_channel_language(msg.channel_id, msg.language, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupLanguage> for DbBroker {
+    type Result = FutureResponse<Option<Language>>;
+
+    fn handle(&mut self, msg: LookupLanguage, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::lookup_language(msg.chat_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetChannelTimeFormat> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: SetChannelTimeFormat, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_channel_time_format(msg.channel_id, msg.time_format, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetTemplate> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: SetTemplate, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_template(msg.channel_id, msg.kind, msg.template, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetFeature> for DbBroker {
+    type Result = FutureResponse<Features>;
+
+    fn handle(&mut self, msg: SetFeature, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_feature(msg.channel_id, msg.feature, msg.enabled, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetGreetingMessage> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: SetGreetingMessage, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_greeting_message(msg.system_id, msg.message_id, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<TogglePinDigest> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: TogglePinDigest, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::toggle_pin_digest(msg.channel_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetPinnedDigest> for DbBroker {
+    type Result = FutureResponse<Option<Integer>>;
+
+    fn handle(&mut self, msg: SetPinnedDigest, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_pinned_digest(msg.system_id, msg.message_id, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<ReleaseDigest> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: ReleaseDigest, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::release_digest(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<ClaimDigests> for DbBroker {
+    type Result = FutureResponse<Vec<ChatSystem>>;
+
+    fn handle(&mut self, msg: ClaimDigests, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::claim_digests(msg.digest_day, msg.since, msg.now, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<Rsvp> for DbBroker {
+    type Result = FutureResponse<RsvpStatus>;
+
+    fn handle(&mut self, msg: Rsvp, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::rsvp(msg.event_id, msg.user_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<CancelRsvp> for DbBroker {
+    type Result = FutureResponse<Vec<User>>;
+
+    fn handle(&mut self, msg: CancelRsvp, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::cancel_rsvp(msg.event_id, msg.user_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<FillWaitlist> for DbBroker {
+    type Result = FutureResponse<Vec<User>>;
+
+    fn handle(&mut self, msg: FillWaitlist, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::fill_waitlist(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetSystemMembers> for DbBroker {
+    type Result = FutureResponse<Vec<User>>;
+
+    fn handle(&mut self, msg: GetSystemMembers, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_system_members(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetAttendees> for DbBroker {
+    type Result = FutureResponse<(Vec<User>, Vec<User>)>;
+
+    fn handle(&mut self, msg: GetAttendees, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_attendees(msg.event_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetRecipients> for DbBroker {
+    type Result = FutureResponse<Vec<Integer>>;
+
+    fn handle(&mut self, msg: GetRecipients, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::get_recipients(msg.event_id, msg.notification, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupSystem> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: LookupSystem, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_system_by_id(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupSystemWithChats> for DbBroker {
+    type Result = FutureResponse<(ChatSystem, Vec<Integer>)>;
+
+    fn handle(&mut self, msg: LookupSystemWithChats, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_system_with_chats_by_id(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupSystemByChannel> for DbBroker {
+    type Result = FutureResponse<ChatSystem>;
+
+    fn handle(&mut self, msg: LookupSystemByChannel, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_system_by_channel(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupSystemsByChannels> for DbBroker {
+    type Result = FutureResponse<Vec<ChatSystem>>;
+
+    fn handle(&mut self, msg: LookupSystemsByChannels, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_systems_by_channels(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetEventsForSystem> for DbBroker {
+    type Result = FutureResponse<Vec<Event>>;
+
+    fn handle(&mut self, msg: GetEventsForSystem, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_events_for_system(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetUsersWithChats> for DbBroker {
+    type Result = FutureResponse<Vec<(User, Chat)>>;
+
+    fn handle(&mut self, _: GetUsersWithChats, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_users_with_chats(connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<StoreEditEventLink> for DbBroker {
+    type Result = FutureResponse<EditEventLink>;
+
+    fn handle(&mut self, msg: StoreEditEventLink, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::store_edit_event_link(
+                    msg.user_id,
+                    msg.system_id,
+                    msg.event_id,
+                    msg.secret,
+                    connection,
+                )
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<EditEventLinkUnused> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: EditEventLinkUnused, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::edit_event_link_unused(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupEditEventLink> for DbBroker {
+    type Result = FutureResponse<EditEventLink>;
+
+    fn handle(&mut self, msg: LookupEditEventLink, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_edit_event_link(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<StoreEventLink> for DbBroker {
+    type Result = FutureResponse<NewEventLink>;
+
+    fn handle(&mut self, msg: StoreEventLink, ctx: &mut Self::Context) -> Self::Result {
+        let event_quota = self.event_quota;
+
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::store_event_link(
+                    msg.user_id,
+                    msg.system_id,
+                    msg.secret,
+                    event_quota,
+                    connection,
+                )
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<EventLinkUnused> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: EventLinkUnused, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::event_link_unused(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupEventLink> for DbBroker {
+    type Result = FutureResponse<NewEventLink>;
+
+    fn handle(&mut self, msg: LookupEventLink, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_event_link(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupUsedEventLink> for DbBroker {
+    type Result = FutureResponse<NewEventLink>;
+
+    fn handle(&mut self, msg: LookupUsedEventLink, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_used_event_link(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupUser> for DbBroker {
+    type Result = FutureResponse<User>;
+
+    fn handle(&mut self, msg: LookupUser, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::lookup_user(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetSystemsWithChats> for DbBroker {
+    type Result = FutureResponse<Vec<(ChatSystem, Chat)>>;
+
+    fn handle(&mut self, _: GetSystemsWithChats, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_systems_with_chats(connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<RemoveUserFromChat> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: RemoveUserFromChat, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::remove_user_from_chat(
+                    msg.user_id,
+                    msg.chat_id,
+                    msg.delete_user,
+                    connection,
+                )
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<ForgetUser> for DbBroker {
+    type Result = FutureResponse<Forgotten>;
+
+    fn handle(&mut self, msg: ForgetUser, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::forget_user(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<ExportUser> for DbBroker {
+    type Result = FutureResponse<Export>;
+
+    fn handle(&mut self, msg: ExportUser, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::export_user(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetStats> for DbBroker {
+    type Result = FutureResponse<Stats>;
+
+    fn handle(&mut self, msg: GetStats, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_stats(msg.channel_id, msg.since, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetSuggestedTimes> for DbBroker {
+    type Result = FutureResponse<Vec<TimeSlot>>;
+
+    fn handle(&mut self, msg: GetSuggestedTimes, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::get_suggested_times(msg.system_id, msg.timezone, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<LookupPreferences> for DbBroker {
+    type Result = FutureResponse<UserPreferences>;
+
+    fn handle(&mut self, msg: LookupPreferences, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::lookup_preferences(msg.0, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetMuted> for DbBroker {
+    type Result = FutureResponse<UserPreferences>;
+
+    fn handle(&mut self, msg: SetMuted, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_muted(msg.user_id, msg.notification, msg.muted, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetUserLanguage> for DbBroker {
+    type Result = FutureResponse<UserPreferences>;
+
+    fn handle(&mut self, msg: SetUserLanguage, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::set_user_language(msg.user_id, msg.language, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<SetUserTimeFormat> for DbBroker {
+    type Result = FutureResponse<UserPreferences>;
+
+    fn handle(&mut self, msg: SetUserTimeFormat, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::set_user_time_format(msg.user_id, msg.time_format, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<NewWebhook> for DbBroker {
+    type Result = FutureResponse<Webhook>;
+
+    fn handle(&mut self, msg: NewWebhook, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::new_webhook(msg.channel_id, msg.url, msg.secret, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<RotateWebhookSecret> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: RotateWebhookSecret, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::rotate_webhook_secret(msg.channel_id, msg.secret, connection)
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<DeleteWebhook> for DbBroker {
+    type Result = FutureResponse<bool>;
+
+    fn handle(&mut self, msg: DeleteWebhook, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::delete_webhook(msg.channel_id, msg.url, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetWebhooksForSystem> for DbBroker {
+    type Result = FutureResponse<Vec<Webhook>>;
+
+    fn handle(&mut self, msg: GetWebhooksForSystem, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_webhooks_for_system(msg.system_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetWebhooksForChannel> for DbBroker {
+    type Result = FutureResponse<Vec<Webhook>>;
+
+    fn handle(&mut self, msg: GetWebhooksForChannel, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| DbBroker::get_webhooks_for_channel(msg.channel_id, connection),
+            ctx,
+        )
+    }
+}
+
+impl Handler<StoreFailedMessage> for DbBroker {
+    type Result = FutureResponse<()>;
+
+    fn handle(&mut self, msg: StoreFailedMessage, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(
+            move |connection| {
+                DbBroker::store_failed_message(
+                    msg.chat_id,
+                    msg.event_id,
+                    msg.announcement,
+                    msg.text,
+                    msg.error,
+                    connection,
+                )
+            },
+            ctx,
+        )
+    }
+}
+
+impl Handler<GetFailedMessages> for DbBroker {
+    type Result = FutureResponse<Vec<FailedMessage>>;
+
+    fn handle(&mut self, _: GetFailedMessages, ctx: &mut Self::Context) -> Self::Result {
+        self.wrap_fut(DbBroker::get_failed_messages, ctx)
+    }
+}
+
+impl Handler<TakeFailedMessage> for DbBroker {
+    type Result = FutureResponse<FailedMessage>;
+
+    fn handle(&mut self, msg: TakeFailedMessage, ctx: &mut Self::Context) -> Self::Result {
         self.wrap_fut(
-            move |connection| DbBroker::delete_user_by_user_id(msg.0, connection),
+            move |connection| DbBroker::take_failed_message(msg.id, connection),
             ctx,
         )
     }
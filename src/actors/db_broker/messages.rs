@@ -19,19 +19,28 @@
 
 //! This module defines all the messages it is possible to send to the `DbBroker` actor
 
-use actix::Message;
-use chrono::DateTime;
+use actix::{Message, Recipient, Syn};
+use chrono::offset::Utc;
+use chrono::{DateTime, Weekday};
 use chrono_tz::Tz;
 use telebot::objects::Integer;
 use tokio_postgres::Connection;
 
+use super::PoolCounts;
 use error::EventError;
+use event_web::locale::{Language, TimeFormat};
 use models::chat::Chat;
-use models::chat_system::ChatSystem;
+use models::chat_system::{BannedUser, ChatSystem, Listing, TemplateKind};
 use models::edit_event_link::EditEventLink;
-use models::event::Event;
+use models::event::{Coordinates, Event, SearchScope};
+use models::failed_message::FailedMessage;
+use models::features::{Feature, Features};
 use models::new_event_link::NewEventLink;
-use models::user::User;
+use models::rsvp::RsvpStatus;
+use models::stats::{Stats, TimeSlot};
+use models::user::{Export, Forgotten, User};
+use models::user_preferences::{Notification, UserPreferences};
+use models::webhook::Webhook;
 
 /// This type notifies the DbBroker of a connection that has been created or returned
 pub struct Ready {
@@ -42,6 +51,46 @@ impl Message for Ready {
     type Result = ();
 }
 
+/// This type notifies the DbBroker of a connection that was returned after a query on it failed,
+/// which is checked before it goes back in the pool
+pub struct Check {
+    pub connection: Connection,
+}
+
+impl Message for Check {
+    type Result = ();
+}
+
+/// This type notifies the DbBroker of a connection it opened in place of one the pool lost
+pub struct Connected {
+    pub connection: Connection,
+}
+
+impl Message for Connected {
+    type Result = ();
+}
+
+/// This type asks the DbBroker how many of its connections are idle, in use, and being opened,
+/// and how many requests are waiting for one
+#[derive(Clone, Copy, Debug)]
+pub struct PoolStatus;
+
+impl Message for PoolStatus {
+    type Result = Result<PoolCounts, EventError>;
+}
+
+/// This type tells the DbBroker to open a connection in place of one the pool lost
+///
+/// `attempt` counts the tries that have failed so far, and sets how long to wait first
+#[derive(Clone, Copy, Debug)]
+pub struct Reconnect {
+    pub attempt: u32,
+}
+
+impl Message for Reconnect {
+    type Result = ();
+}
+
 /// This type notifies the DbBroker of a channel that should be initialized
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct NewChannel {
@@ -88,8 +137,6 @@ impl Message for NewRelation {
 
 /// This type notifies the DbBroker that a given Channel should be deleted. Deleting a channel
 /// deletes all associated chats and users as well
-///
-/// TODO: Make sure UsersActor has similar functionality
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct DeleteChannel {
     pub channel_id: Integer,
@@ -99,7 +146,37 @@ impl Message for DeleteChannel {
     type Result = Result<(), EventError>;
 }
 
+/// This type notifies the DbBroker of the current title of a chat or channel
+///
+/// `username` is only known for channels looked up on Telegram, and is left as it was otherwise
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetChatTitle {
+    pub chat_id: Integer,
+    pub title: Option<String>,
+    pub username: Option<String>,
+}
+
+impl Message for SetChatTitle {
+    type Result = Result<(), EventError>;
+}
+
+/// This type notifies the DbBroker that a chat should be unlinked from its channel and deleted
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DeleteChat {
+    pub chat_id: Integer,
+}
+
+impl Message for DeleteChat {
+    type Result = Result<(), EventError>;
+}
+
 /// This type notifies the DbBroker that an event should be created
+///
+/// `needs_approval` is whether the event should wait for an admin to approve it if its channel is
+/// moderated. `link_id` is the `NewEventLink` the event is being made from, if any.
+///
+/// The result is the event, and whether it was just created. Creating an event from a link that
+/// was already used yields the event the link made instead of a second one.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NewEvent {
     pub system_id: i32,
@@ -108,13 +185,29 @@ pub struct NewEvent {
     pub start_date: DateTime<Tz>,
     pub end_date: DateTime<Tz>,
     pub hosts: Vec<i32>,
+    pub publish_at: Option<DateTime<Tz>>,
+    pub capacity: Option<i32>,
+    pub boost_threshold: Option<i32>,
+    pub location: Option<String>,
+    pub location_hint: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    pub image_url: Option<String>,
+    pub rsvp_deadline: Option<DateTime<Tz>>,
+    pub members_only: bool,
+    pub unlisted: bool,
+    pub needs_approval: bool,
+    pub link_id: Option<i32>,
 }
 
 impl Message for NewEvent {
-    type Result = Result<Event, EventError>;
+    type Result = Result<(Event, bool), EventError>;
 }
 
 /// This type notifies the DbBroker that the given event should be updated
+///
+/// `hosts` holds the database IDs of the event's hosts, and leaves them as they are if it isn't
+/// set. `link_id` is the `EditEventLink` the event is being edited from, if any, which is used up
+/// along with the edit.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EditEvent {
     pub id: i32,
@@ -123,7 +216,18 @@ pub struct EditEvent {
     pub description: String,
     pub start_date: DateTime<Tz>,
     pub end_date: DateTime<Tz>,
-    pub hosts: Vec<i32>,
+    pub hosts: Option<Vec<i32>>,
+    pub publish_at: Option<DateTime<Tz>>,
+    pub capacity: Option<i32>,
+    pub boost_threshold: Option<i32>,
+    pub location: Option<String>,
+    pub location_hint: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    pub image_url: Option<String>,
+    pub rsvp_deadline: Option<DateTime<Tz>>,
+    pub members_only: bool,
+    pub unlisted: bool,
+    pub link_id: Option<i32>,
 }
 
 impl Message for EditEvent {
@@ -140,6 +244,63 @@ impl Message for LookupEventsByChatId {
     type Result = Result<Vec<Event>, EventError>;
 }
 
+/// This type requests the first event in the current chat's channel that starts after the given
+/// date
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LookupNextEventByChatId {
+    pub chat_id: Integer,
+    pub after: DateTime<Tz>,
+}
+
+impl Message for LookupNextEventByChatId {
+    type Result = Result<Option<Event>, EventError>;
+}
+
+/// This type requests the events in the current chat's channel that start within the given time
+/// range
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LookupEventsByChatIdInRange {
+    pub chat_id: Integer,
+    pub start_date: DateTime<Tz>,
+    pub end_date: DateTime<Tz>,
+}
+
+impl Message for LookupEventsByChatIdInRange {
+    type Result = Result<Vec<Event>, EventError>;
+}
+
+/// This type searches the upcoming events in a group chat's channel, or in every channel a user
+/// can see, by their titles and descriptions
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SearchEvents {
+    pub scope: SearchScope,
+    pub terms: String,
+}
+
+impl Message for SearchEvents {
+    type Result = Result<Vec<Event>, EventError>;
+}
+
+/// This type requests every channel listed in the Web UI's directory
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetListings;
+
+impl Message for GetListings {
+    type Result = Result<Vec<Listing>, EventError>;
+}
+
+/// This type searches the upcoming events of the channels listed in the directory by their titles
+/// and descriptions, and by their hashtags
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SearchDirectory {
+    pub terms: String,
+    pub tag: String,
+}
+
+impl Message for SearchDirectory {
+    type Result = Result<Vec<Event>, EventError>;
+}
+
 /// This type requests a single event by the event's ID
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct LookupEvent {
@@ -150,6 +311,16 @@ impl Message for LookupEvent {
     type Result = Result<Event, EventError>;
 }
 
+/// This type requests a single event by the ID used in its public web page
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LookupEventByPublicId {
+    pub public_id: String,
+}
+
+impl Message for LookupEventByPublicId {
+    type Result = Result<Event, EventError>;
+}
+
 /// This type requests events by the host's ID
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct LookupEventsByUserId {
@@ -170,6 +341,76 @@ impl Message for DeleteEvent {
     type Result = Result<(), EventError>;
 }
 
+/// This type notifies the DbBroker that an event has been cancelled
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CancelEvent {
+    pub event_id: i32,
+}
+
+impl Message for CancelEvent {
+    type Result = Result<(), EventError>;
+}
+
+/// This type notifies the DbBroker that an admin approved a pending event
+///
+/// This fails if the event isn't pending
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ApproveEvent {
+    pub event_id: i32,
+}
+
+impl Message for ApproveEvent {
+    type Result = Result<(), EventError>;
+}
+
+/// This type notifies the DbBroker that an admin turned down a pending event, deleting it
+///
+/// This fails if the event isn't pending
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RejectEvent {
+    pub event_id: i32,
+}
+
+impl Message for RejectEvent {
+    type Result = Result<(), EventError>;
+}
+
+/// This type requests that an event be handed over from one host to another. Both hosts are
+/// given by their database IDs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TransferEvent {
+    pub event_id: i32,
+    pub from_host: i32,
+    pub to_host: i32,
+}
+
+impl Message for TransferEvent {
+    type Result = Result<(), EventError>;
+}
+
+/// This type notifies the DbBroker of the message that announced an event in its channel
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetAnnouncement {
+    pub event_id: i32,
+    pub message_id: Integer,
+}
+
+impl Message for SetAnnouncement {
+    type Result = Result<(), EventError>;
+}
+
+/// This type requests the message ID of an event's announcement
+///
+/// The result is `None` if the event hasn't been announced yet
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupAnnouncement {
+    pub event_id: i32,
+}
+
+impl Message for LookupAnnouncement {
+    type Result = Result<Option<Integer>, EventError>;
+}
+
 /// This type requests Events that exist within the given time range
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct GetEventsInRange {
@@ -181,142 +422,832 @@ impl Message for GetEventsInRange {
     type Result = Result<Vec<Event>, EventError>;
 }
 
-/// This type requests the ChatSystem given the system's ID
+/// This type requests Events whose announcements are scheduled before the given date
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct LookupSystem {
-    pub system_id: i32,
+pub struct GetEventsToPublish {
+    pub before: DateTime<Tz>,
 }
 
-impl Message for LookupSystem {
-    type Result = Result<ChatSystem, EventError>;
+impl Message for GetEventsToPublish {
+    type Result = Result<Vec<Event>, EventError>;
 }
 
-pub struct LookupSystemWithChats {
-    pub system_id: i32,
+/// This type requests announced Events whose RSVP deadlines fall within the given range
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GetEventsClosingRsvps {
+    pub start_date: DateTime<Tz>,
+    pub end_date: DateTime<Tz>,
 }
 
-impl Message for LookupSystemWithChats {
-    type Result = Result<(ChatSystem, Vec<Integer>), EventError>;
+impl Message for GetEventsClosingRsvps {
+    type Result = Result<Vec<Event>, EventError>;
 }
 
-/// This type requests the ChatSystem given the channel's Telegram ID
+/// This type requests the announced Events starting before `before` that have too few RSVPs,
+/// marking them so they're only announced again once
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct LookupSystemByChannel(pub Integer);
+pub struct ClaimBoosts {
+    pub before: DateTime<Tz>,
+}
 
-impl Message for LookupSystemByChannel {
-    type Result = Result<ChatSystem, EventError>;
+impl Message for ClaimBoosts {
+    type Result = Result<Vec<Event>, EventError>;
 }
 
-/// This type requests events associated with a ChatSystem
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct GetEventsForSystem {
-    pub system_id: i32,
+/// This type notifies the DbBroker that an event's announcement has been sent
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MarkPublished {
+    pub event_id: i32,
 }
 
-impl Message for GetEventsForSystem {
-    type Result = Result<Vec<Event>, EventError>;
+impl Message for MarkPublished {
+    type Result = Result<(), EventError>;
 }
 
-/// This type requests a User given the User's Telegram ID
-#[derive(Clone, Copy, Debug)]
-pub struct LookupUser(pub Integer);
+/// This type asks the DbBroker to claim a stage of an event for this instance's Timer
+///
+/// `stage` is the stage's name and `due` is when it was due. The result is whether it was
+/// claimed, since only the instance that claims a stage first carries it out
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClaimStage {
+    pub event_id: i32,
+    pub stage: &'static str,
+    pub due: DateTime<Utc>,
+}
 
-impl Message for LookupUser {
-    type Result = Result<User, EventError>;
+impl Message for ClaimStage {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type requests all users with their associated chats
-#[derive(Clone, Copy, Debug)]
-pub struct GetUsersWithChats;
+/// This type asks the DbBroker to toggle whether a channel's announcements are protected from
+/// forwarding
+///
+/// The result is the new setting
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ToggleProtectContent {
+    pub channel_id: Integer,
+}
 
-impl Message for GetUsersWithChats {
-    type Result = Result<Vec<(User, Chat)>, EventError>;
+impl Message for ToggleProtectContent {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type notifies the `DbBroker` that it should insert the given information as an
-/// `EditEventLink`
-#[derive(Clone, Debug)]
-pub struct StoreEditEventLink {
-    pub user_id: i32,
-    pub event_id: i32,
-    pub system_id: i32,
-    pub secret: String,
+/// This type asks the DbBroker to toggle whether events from hosts who aren't a channel's admins
+/// need to be approved
+///
+/// The result is the new setting
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ToggleModerated {
+    pub channel_id: Integer,
 }
 
-impl Message for StoreEditEventLink {
-    type Result = Result<EditEventLink, EventError>;
+impl Message for ToggleModerated {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type requests an `EditEventLink` given it's ID
-#[derive(Clone, Copy, Debug)]
-pub struct LookupEditEventLink(pub i32);
+/// This type asks the DbBroker to stop a user from creating events for a channel
+///
+/// The result is whether the user was banned
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BanHost {
+    pub channel_id: Integer,
+    pub user: BannedUser,
+}
 
-impl Message for LookupEditEventLink {
-    type Result = Result<EditEventLink, EventError>;
+impl Message for BanHost {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type notifies the `DbBroker` that an `EditEventLink` should be marked as used
-#[derive(Clone, Copy, Debug)]
-pub struct DeleteEditEventLink {
-    pub id: i32,
+/// This type asks the DbBroker to let a banned user create events for a channel again
+///
+/// The result is whether the user had been banned
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct UnbanHost {
+    pub channel_id: Integer,
+    pub user: BannedUser,
 }
 
-impl Message for DeleteEditEventLink {
-    type Result = Result<(), EventError>;
+impl Message for UnbanHost {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type notifies the `DbBroker` that it should insert the given information as a
-/// `NewEventLink`
-#[derive(Clone, Debug)]
-pub struct StoreEventLink {
-    pub user_id: i32,
+/// This type asks the DbBroker whether a user has been banned from creating events for a Chat
+/// System. `user_id` is the user's Telegram ID
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CheckBanned {
     pub system_id: i32,
-    pub secret: String,
+    pub user_id: Integer,
 }
 
-impl Message for StoreEventLink {
-    type Result = Result<NewEventLink, EventError>;
+impl Message for CheckBanned {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type requests a `NewEventLink` by its ID
-#[derive(Clone, Copy, Debug)]
-pub struct LookupEventLink(pub i32);
+/// This type asks the DbBroker to toggle whether a channel's new event announcements are pinned
+///
+/// The result is the new setting
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TogglePinAnnouncements {
+    pub channel_id: Integer,
+}
 
-impl Message for LookupEventLink {
-    type Result = Result<NewEventLink, EventError>;
+impl Message for TogglePinAnnouncements {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type notifies the `DbBroker` that a `NewEventLink` should be marked as used
-#[derive(Clone, Copy, Debug)]
-pub struct DeleteEventLink {
-    pub id: i32,
+/// This type asks the DbBroker to toggle whether a channel is listed in the directory, recording
+/// the channel's current title and username
+///
+/// The result is the new setting
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ToggleListed {
+    pub channel_id: Integer,
+    pub title: Option<String>,
+    pub username: Option<String>,
 }
 
-impl Message for DeleteEventLink {
-    type Result = Result<(), EventError>;
+impl Message for ToggleListed {
+    type Result = Result<bool, EventError>;
 }
 
-/// This type requests every `ChatSystem` with it's associated chats
-#[derive(Clone, Copy, Debug)]
-pub struct GetSystemsWithChats;
+/// This type asks the DbBroker to set or remove a channel's greeting
+///
+/// The result is the updated Chat System
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetGreeting {
+    pub channel_id: Integer,
+    pub greeting: Option<String>,
+}
 
-impl Message for GetSystemsWithChats {
-    type Result = Result<Vec<(ChatSystem, Chat)>, EventError>;
+impl Message for SetGreeting {
+    type Result = Result<ChatSystem, EventError>;
 }
 
-/// This type notifies the `DbBroker` that it should remove the association between the User and
-/// Chat given their Telegram IDs
-#[derive(Clone, Copy, Debug)]
-pub struct RemoveUserChat(pub Integer, pub Integer);
+/// This type asks the DbBroker to set the language the bot speaks in a channel
+///
+/// The result is the updated Chat System
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetChannelLanguage {
+    pub channel_id: Integer,
+    pub language: Language,
+}
 
-impl Message for RemoveUserChat {
-    type Result = Result<(), EventError>;
+impl Message for SetChannelLanguage {
+    type Result = Result<ChatSystem, EventError>;
 }
 
-/// This type notifies the `DbBroker` that it should delete the user with the given Telegram ID
-#[derive(Clone, Copy, Debug)]
-pub struct DeleteUserByUserId(pub Integer);
+/// This type asks the DbBroker for the language the bot replies in for a user, channel, or group
+///
+/// The result is `None` if nobody has picked one
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupLanguage {
+    pub chat_id: Integer,
+}
 
-impl Message for DeleteUserByUserId {
-    type Result = Result<(), EventError>;
+impl Message for LookupLanguage {
+    type Result = Result<Option<Language>, EventError>;
+}
+
+/// This type asks the DbBroker to set whether times are shown with 12 or 24 hours in a channel
+///
+/// The result is the updated Chat System
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetChannelTimeFormat {
+    pub channel_id: Integer,
+    pub time_format: TimeFormat,
+}
+
+impl Message for SetChannelTimeFormat {
+    type Result = Result<ChatSystem, EventError>;
+}
+
+/// This type asks the DbBroker to set or remove one of a channel's message templates
+///
+/// The result is the updated Chat System
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetTemplate {
+    pub channel_id: Integer,
+    pub kind: TemplateKind,
+    pub template: Option<String>,
+}
+
+impl Message for SetTemplate {
+    type Result = Result<ChatSystem, EventError>;
+}
+
+/// This type asks the DbBroker to turn one of a channel's features on or off
+///
+/// The result is the channel's updated features
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetFeature {
+    pub channel_id: Integer,
+    pub feature: Feature,
+    pub enabled: bool,
+}
+
+impl Message for SetFeature {
+    type Result = Result<Features, EventError>;
+}
+
+/// This type notifies the DbBroker of the ID of a Chat System's greeting message
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetGreetingMessage {
+    pub system_id: i32,
+    pub message_id: Option<Integer>,
+}
+
+impl Message for SetGreetingMessage {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the DbBroker to store the hashed secret of a channel's new API token
+///
+/// The result is the ID of the channel's Chat System
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetApiSecret {
+    pub channel_id: Integer,
+    pub secret: String,
+}
+
+impl Message for SetApiSecret {
+    type Result = Result<i32, EventError>;
+}
+
+/// This type requests a Chat System and the hashed secret of its API token
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupSystemWithApiSecret(pub i32);
+
+impl Message for LookupSystemWithApiSecret {
+    type Result = Result<(ChatSystem, Option<String>), EventError>;
+}
+
+/// This type stores the hashed secret of a channel's new read-only API token
+///
+/// The result is the ID of the channel's Chat System
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetReadSecret {
+    pub channel_id: Integer,
+    pub secret: String,
+}
+
+impl Message for SetReadSecret {
+    type Result = Result<i32, EventError>;
+}
+
+/// This type requests a Chat System and the hashed secret of its read-only API token
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupSystemWithReadSecret(pub i32);
+
+impl Message for LookupSystemWithReadSecret {
+    type Result = Result<(ChatSystem, Option<String>), EventError>;
+}
+
+/// This type stores the hashed secret of a channel's new calendar token
+///
+/// The result is the ID of the channel's Chat System
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SetCalendarSecret {
+    pub channel_id: Integer,
+    pub secret: String,
+}
+
+impl Message for SetCalendarSecret {
+    type Result = Result<i32, EventError>;
+}
+
+/// This type requests a Chat System and the hashed secret of its calendar token
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupSystemWithCalendarSecret(pub i32);
+
+impl Message for LookupSystemWithCalendarSecret {
+    type Result = Result<(ChatSystem, Option<String>), EventError>;
+}
+
+/// This type notifies the DbBroker that an event's announcement was pinned in a Chat System's
+/// channel
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetPinnedEvent {
+    pub system_id: i32,
+    pub event_id: i32,
+    pub message_id: Integer,
+}
+
+impl Message for SetPinnedEvent {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the DbBroker to forget the pinned announcement for an event
+///
+/// The result is the ID of the announcement if it was pinned
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClearPinnedEvent {
+    pub event_id: i32,
+}
+
+impl Message for ClearPinnedEvent {
+    type Result = Result<Option<Integer>, EventError>;
+}
+
+/// This type asks the DbBroker to record which weekly digest is pinned in a channel
+///
+/// The result is the digest that was pinned before, if any
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetPinnedDigest {
+    pub system_id: i32,
+    pub message_id: Option<Integer>,
+}
+
+impl Message for SetPinnedDigest {
+    type Result = Result<Option<Integer>, EventError>;
+}
+
+/// This type asks the DbBroker to let a Chat System's weekly digest be claimed again, because it
+/// couldn't be posted
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ReleaseDigest {
+    pub system_id: i32,
+}
+
+impl Message for ReleaseDigest {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the DbBroker to change the day a channel's weekly digest is posted on
+///
+/// A `digest_day` of `None` turns the digest off
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetDigestDay {
+    pub channel_id: Integer,
+    pub digest_day: Option<Weekday>,
+}
+
+impl Message for SetDigestDay {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the DbBroker to set how few RSVPs a channel's events need two days before they
+/// start to be announced again. `None` turns boosts off.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetBoostThreshold {
+    pub channel_id: Integer,
+    pub boost_threshold: Option<i32>,
+}
+
+impl Message for SetBoostThreshold {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the DbBroker to toggle whether a channel's weekly digest is pinned
+///
+/// The result is the new setting
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TogglePinDigest {
+    pub channel_id: Integer,
+}
+
+impl Message for TogglePinDigest {
+    type Result = Result<bool, EventError>;
+}
+
+/// This type requests the Chat Systems whose weekly digest is due on `digest_day` and hasn't been
+/// posted since `since`. The returned Chat Systems are marked as having posted their digest.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ClaimDigests {
+    pub digest_day: Weekday,
+    pub since: DateTime<Tz>,
+    pub now: DateTime<Tz>,
+}
+
+impl Message for ClaimDigests {
+    type Result = Result<Vec<ChatSystem>, EventError>;
+}
+
+/// This type notifies the DbBroker that a user would like to attend an event
+///
+/// `user_id` is the database ID of the user
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rsvp {
+    pub event_id: i32,
+    pub user_id: i32,
+}
+
+impl Message for Rsvp {
+    type Result = Result<RsvpStatus, EventError>;
+}
+
+/// This type notifies the DbBroker that a user is no longer attending an event
+///
+/// The result is the users promoted from the waitlist
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CancelRsvp {
+    pub event_id: i32,
+    pub user_id: i32,
+}
+
+impl Message for CancelRsvp {
+    type Result = Result<Vec<User>, EventError>;
+}
+
+/// This type notifies the DbBroker that an event may have room for users on its waitlist, like
+/// after its capacity is raised
+///
+/// The result is the users promoted from the waitlist
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FillWaitlist {
+    pub event_id: i32,
+}
+
+impl Message for FillWaitlist {
+    type Result = Result<Vec<User>, EventError>;
+}
+
+/// This type requests the users in any of a Chat System's chats
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetSystemMembers {
+    pub system_id: i32,
+}
+
+impl Message for GetSystemMembers {
+    type Result = Result<Vec<User>, EventError>;
+}
+
+/// This type requests the users going to an event, and the users on its waitlist
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetAttendees {
+    pub event_id: i32,
+}
+
+impl Message for GetAttendees {
+    type Result = Result<(Vec<User>, Vec<User>), EventError>;
+}
+
+/// This type requests the Telegram IDs of the users going to an event who haven't muted the
+/// notification
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetRecipients {
+    pub event_id: i32,
+    pub notification: Notification,
+}
+
+impl Message for GetRecipients {
+    type Result = Result<Vec<Integer>, EventError>;
+}
+
+/// This type requests the ChatSystem given the system's ID
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LookupSystem {
+    pub system_id: i32,
+}
+
+impl Message for LookupSystem {
+    type Result = Result<ChatSystem, EventError>;
+}
+
+pub struct LookupSystemWithChats {
+    pub system_id: i32,
+}
+
+impl Message for LookupSystemWithChats {
+    type Result = Result<(ChatSystem, Vec<Integer>), EventError>;
+}
+
+/// This type requests the ChatSystem given the channel's Telegram ID
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LookupSystemByChannel(pub Integer);
+
+impl Message for LookupSystemByChannel {
+    type Result = Result<ChatSystem, EventError>;
+}
+
+/// This type requests the ChatSystems of each of the given channels, leaving out channels that
+/// haven't been initialized
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LookupSystemsByChannels(pub Vec<Integer>);
+
+impl Message for LookupSystemsByChannels {
+    type Result = Result<Vec<ChatSystem>, EventError>;
+}
+
+/// This type requests events associated with a ChatSystem
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GetEventsForSystem {
+    pub system_id: i32,
+}
+
+impl Message for GetEventsForSystem {
+    type Result = Result<Vec<Event>, EventError>;
+}
+
+/// This type requests a User given the User's Telegram ID
+#[derive(Clone, Copy, Debug)]
+pub struct LookupUser(pub Integer);
+
+impl Message for LookupUser {
+    type Result = Result<User, EventError>;
+}
+
+/// This type requests all users with their associated chats
+#[derive(Clone, Copy, Debug)]
+pub struct GetUsersWithChats;
+
+impl Message for GetUsersWithChats {
+    type Result = Result<Vec<(User, Chat)>, EventError>;
+}
+
+/// This type notifies the `DbBroker` that it should insert the given information as an
+/// `EditEventLink`
+#[derive(Clone, Debug)]
+pub struct StoreEditEventLink {
+    pub user_id: i32,
+    pub event_id: i32,
+    pub system_id: i32,
+    pub secret: String,
+}
+
+impl Message for StoreEditEventLink {
+    type Result = Result<EditEventLink, EventError>;
+}
+
+/// This type requests an `EditEventLink` given it's ID
+#[derive(Clone, Copy, Debug)]
+pub struct LookupEditEventLink(pub i32);
+
+impl Message for LookupEditEventLink {
+    type Result = Result<EditEventLink, EventError>;
+}
+
+/// This type asks whether an `EditEventLink` is still unused, even if it has expired
+#[derive(Clone, Copy, Debug)]
+pub struct EditEventLinkUnused(pub i32);
+
+impl Message for EditEventLinkUnused {
+    type Result = Result<bool, EventError>;
+}
+
+/// This type notifies the `DbBroker` that it should insert the given information as a
+/// `NewEventLink`
+#[derive(Clone, Debug)]
+pub struct StoreEventLink {
+    pub user_id: i32,
+    pub system_id: i32,
+    pub secret: String,
+}
+
+impl Message for StoreEventLink {
+    type Result = Result<NewEventLink, EventError>;
+}
+
+/// This type requests a `NewEventLink` by its ID
+#[derive(Clone, Copy, Debug)]
+pub struct LookupEventLink(pub i32);
+
+impl Message for LookupEventLink {
+    type Result = Result<NewEventLink, EventError>;
+}
+
+/// This type requests a `NewEventLink` that has already been used, by its ID
+#[derive(Clone, Copy, Debug)]
+pub struct LookupUsedEventLink(pub i32);
+
+impl Message for LookupUsedEventLink {
+    type Result = Result<NewEventLink, EventError>;
+}
+
+/// This type asks whether a `NewEventLink` is still unused, even if it has expired
+#[derive(Clone, Copy, Debug)]
+pub struct EventLinkUnused(pub i32);
+
+impl Message for EventLinkUnused {
+    type Result = Result<bool, EventError>;
+}
+
+/// This type requests every `ChatSystem` with it's associated chats
+#[derive(Clone, Copy, Debug)]
+pub struct GetSystemsWithChats;
+
+impl Message for GetSystemsWithChats {
+    type Result = Result<Vec<(ChatSystem, Chat)>, EventError>;
+}
+
+/// This type notifies the `DbBroker` that it should remove the association between the User and
+/// Chat given their Telegram IDs, and delete the User too if they aren't in any other chats
+#[derive(Clone, Copy, Debug)]
+pub struct RemoveUserFromChat {
+    pub user_id: Integer,
+    pub chat_id: Integer,
+    pub delete_user: bool,
+}
+
+impl Message for RemoveUserFromChat {
+    type Result = Result<(), EventError>;
+}
+
+/// This type asks the `DbBroker` to delete everything stored about the user with the given
+/// Telegram ID
+///
+/// The result counts what was deleted
+#[derive(Clone, Copy, Debug)]
+pub struct ForgetUser(pub Integer);
+
+impl Message for ForgetUser {
+    type Result = Result<Forgotten, EventError>;
+}
+
+/// This type asks the `DbBroker` to gather everything stored about the user with the given
+/// Telegram ID
+#[derive(Clone, Copy, Debug)]
+pub struct ExportUser(pub Integer);
+
+impl Message for ExportUser {
+    type Result = Result<Export, EventError>;
+}
+
+/// This type asks the DbBroker for a summary of the activity in the channel's chat system,
+/// counting the events created since `since`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GetStats {
+    pub channel_id: Integer,
+    pub since: DateTime<Tz>,
+}
+
+impl Message for GetStats {
+    type Result = Result<Stats, EventError>;
+}
+
+/// This type asks the DbBroker for the times when the chat system's past events drew the most
+/// people, in the given timezone
+#[derive(Clone, Copy, Debug)]
+pub struct GetSuggestedTimes {
+    pub system_id: i32,
+    pub timezone: Tz,
+}
+
+impl Message for GetSuggestedTimes {
+    type Result = Result<Vec<TimeSlot>, EventError>;
+}
+
+/// This type asks the DbBroker for the preferences of the user with the given Telegram ID
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupPreferences(pub Integer);
+
+impl Message for LookupPreferences {
+    type Result = Result<UserPreferences, EventError>;
+}
+
+/// This type asks the DbBroker to mute or unmute a notification for the user with the given
+/// Telegram ID. `None` mutes or unmutes every notification.
+///
+/// The result is the user's updated preferences
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetMuted {
+    pub user_id: Integer,
+    pub notification: Option<Notification>,
+    pub muted: bool,
+}
+
+impl Message for SetMuted {
+    type Result = Result<UserPreferences, EventError>;
+}
+
+/// This type asks the DbBroker to set the language the bot speaks to the user with the given
+/// Telegram ID in
+///
+/// The result is the user's updated preferences
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetUserLanguage {
+    pub user_id: Integer,
+    pub language: Language,
+}
+
+impl Message for SetUserLanguage {
+    type Result = Result<UserPreferences, EventError>;
+}
+
+/// This type asks the DbBroker to set whether times are shown with 12 or 24 hours for the user
+/// with the given Telegram ID
+///
+/// The result is the user's updated preferences
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SetUserTimeFormat {
+    pub user_id: Integer,
+    pub time_format: TimeFormat,
+}
+
+impl Message for SetUserTimeFormat {
+    type Result = Result<UserPreferences, EventError>;
+}
+
+/// This type asks the DbBroker to register a webhook for a channel
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct NewWebhook {
+    pub channel_id: Integer,
+    pub url: String,
+    pub secret: String,
+}
+
+impl Message for NewWebhook {
+    type Result = Result<Webhook, EventError>;
+}
+
+/// This type asks the DbBroker to replace the secret a channel's webhooks are signed with
+///
+/// The result is whether the channel has any webhooks
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RotateWebhookSecret {
+    pub channel_id: Integer,
+    pub secret: String,
+}
+
+impl Message for RotateWebhookSecret {
+    type Result = Result<bool, EventError>;
+}
+
+/// This type asks the DbBroker to remove a channel's webhook
+///
+/// The result is whether the webhook was registered
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DeleteWebhook {
+    pub channel_id: Integer,
+    pub url: String,
+}
+
+impl Message for DeleteWebhook {
+    type Result = Result<bool, EventError>;
+}
+
+/// This type requests the webhooks registered for a Chat System
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetWebhooksForSystem {
+    pub system_id: i32,
+}
+
+impl Message for GetWebhooksForSystem {
+    type Result = Result<Vec<Webhook>, EventError>;
+}
+
+/// This type requests the webhooks registered for a channel
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetWebhooksForChannel {
+    pub channel_id: Integer,
+}
+
+impl Message for GetWebhooksForChannel {
+    type Result = Result<Vec<Webhook>, EventError>;
+}
+
+/// This type asks the DbBroker to store a message the bot gave up sending
+///
+/// `announcement` is whether the message was the announcement of the event with `event_id`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct StoreFailedMessage {
+    pub chat_id: Integer,
+    pub event_id: i32,
+    pub announcement: bool,
+    pub text: String,
+    pub error: String,
+}
+
+impl Message for StoreFailedMessage {
+    type Result = Result<(), EventError>;
+}
+
+/// This type requests the most recent messages the bot gave up sending
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GetFailedMessages;
+
+impl Message for GetFailedMessages {
+    type Result = Result<Vec<FailedMessage>, EventError>;
+}
+
+/// This type asks the DbBroker to remove a failed message so it can be sent again
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TakeFailedMessage {
+    pub id: i32,
+}
+
+impl Message for TakeFailedMessage {
+    type Result = Result<FailedMessage, EventError>;
+}
+
+/// This type asks the DbBroker to listen for events being saved or deleted by any instance of the
+/// bot, and to pass each change on to the recipient
+pub struct ListenForChanges {
+    pub recipient: Recipient<Syn, EventChanged>,
+}
+
+impl Message for ListenForChanges {
+    type Result = ();
+}
+
+/// This type tells a listener that an event changed in the database
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EventChanged {
+    /// The event with this ID was created or edited
+    Saved(i32),
+    /// The event with this ID was deleted
+    Deleted(i32),
+}
+
+impl Message for EventChanged {
+    type Result = ();
 }
@@ -19,56 +19,164 @@
 
 //! This module defines the DbBroker, a struct that manages access to database conections
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use chrono::DateTime;
+use actix::{Addr, Arbiter, Recipient, Syn};
+use chrono::offset::Utc;
+use chrono::{DateTime, Weekday};
 use chrono_tz::Tz;
+use failure::Fail;
 use futures::task;
-use futures::{Async, Future, Poll};
+use futures::future::Either;
+use futures::{stream, Async, Future, IntoFuture, Poll, Stream};
 use telebot::objects::Integer;
 use tokio_postgres::Connection;
 
+use self::messages::EventChanged;
+use actors::alert_actor::AlertActor;
+use config::Config;
+use conn::connect_to_database;
 use error::{EventError, EventErrorKind};
+use event_web::locale::{Language, TimeFormat};
+use logging::LogContext;
 use models::chat::{Chat, CreateChat};
-use models::chat_system::ChatSystem;
+use models::chat_system::{BannedUser, ChatSystem, Listing, TemplateKind};
 use models::edit_event_link::EditEventLink;
-use models::event::{CreateEvent, Event, UpdateEvent};
+use models::event::{Coordinates, CreateEvent, Event, SearchScope, UpdateEvent};
+use models::failed_message::FailedMessage;
+use models::features::{Feature, Features};
 use models::new_event_link::NewEventLink;
-use models::user::{CreateUser, User};
+use models::rsvp::{Rsvp, RsvpStatus};
+use models::stats::{Stats, TimeSlot};
+use models::user::{CreateUser, Export, Forgotten, User};
+use models::user_preferences::{Notification, UserPreferences};
+use models::webhook::Webhook;
 
 mod actor;
 pub mod messages;
 
+/// The Postgres channel that changes to the `events` table are announced on
+const EVENT_CHANGES_CHANNEL: &str = "event_changes";
+
+/// How many seconds to wait before listening again after the listening connection is lost
+const RELISTEN_SECONDS: u64 = 5;
+
+/// The longest to wait, in seconds, between attempts to replace a lost connection
+const MAX_RECONNECT_SECONDS: u64 = 60;
+
+/// The key of the alert posted when the database can't be reached
+const DATABASE_ALERT: &str = "database";
+
+/// The key of the alert posted when requests give up waiting for a connection
+const POOL_ALERT: &str = "database_pool";
+
+/// How long to wait before trying to replace a lost connection, after `attempt` tries have failed
+///
+/// The first try happens right away, and the wait doubles after each failure up to a minute
+fn reconnect_delay(attempt: u32) -> Duration {
+    if attempt == 0 {
+        Duration::from_secs(0)
+    } else {
+        Duration::from_secs(MAX_RECONNECT_SECONDS.min(1 << (attempt - 1).min(6)))
+    }
+}
+
+/// How long, in milliseconds, a request can wait for a `Connection` before a warning is logged
+///
+/// Requests that wait this long are a sign the pool is too small for the bot's load
+const SLOW_CHECKOUT_MILLIS: u64 = 1000;
+
 /// Define the structure that contains the `Connection` collection
 ///
 /// This wraps an Rc<RefCell<>> to allow multiple future chains on the DbBroker to have access to
 /// the connections.
 ///
-/// Future is implemented for this type, and since it can be easily cloned, multiple futures can
-/// wait on the presence of a `Connection` in the pool.
-pub struct Connections(Rc<RefCell<VecDeque<Connection>>>);
+/// Since it can be easily cloned, multiple futures can wait on the presence of a `Connection` in
+/// the pool, with `checkout`.
+pub struct Connections {
+    idle: Rc<RefCell<VecDeque<Connection>>>,
+    waiting: Rc<Cell<usize>>,
+}
+
+impl Connections {
+    /// Wait for a `Connection`, failing with `EventErrorKind::Busy` if none is free before
+    /// `timeout` has passed
+    pub fn checkout(&self, timeout: Duration) -> Checkout {
+        self.waiting.set(self.waiting.get() + 1);
+        let now = Instant::now();
+
+        Checkout {
+            connections: self.clone(),
+            started: now,
+            deadline: now + timeout,
+        }
+    }
+
+    /// Put a `Connection` back in the pool
+    fn restore(&self, connection: Connection) {
+        self.idle.borrow_mut().push_back(connection);
+    }
+
+    /// The number of `Connection`s in the pool that aren't being used
+    fn idle(&self) -> usize {
+        self.idle.borrow().len()
+    }
+
+    /// The number of requests waiting for a `Connection`
+    fn waiting(&self) -> usize {
+        self.waiting.get()
+    }
+}
 
 impl Clone for Connections {
     fn clone(&self) -> Self {
-        Connections(Rc::clone(&self.0))
+        Connections {
+            idle: Rc::clone(&self.idle),
+            waiting: Rc::clone(&self.waiting),
+        }
     }
 }
 
 impl Default for Connections {
     fn default() -> Self {
-        Connections(Rc::new(RefCell::new(VecDeque::default())))
+        Connections {
+            idle: Rc::new(RefCell::new(VecDeque::default())),
+            waiting: Rc::new(Cell::new(0)),
+        }
     }
 }
 
-impl Future for Connections {
+/// A future that resolves to a `Connection` from the pool, or fails if the pool is busy for too
+/// long
+pub struct Checkout {
+    connections: Connections,
+    started: Instant,
+    deadline: Instant,
+}
+
+impl Future for Checkout {
     type Item = Connection;
     type Error = EventError;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        if let Some(item) = self.0.borrow_mut().pop_front() {
+        if let Some(item) = self.connections.idle.borrow_mut().pop_front() {
+            let waited = self.started.elapsed();
+
+            if waited >= Duration::from_millis(SLOW_CHECKOUT_MILLIS) {
+                ctx_warn!(
+                    LogContext::new("db_broker"),
+                    "Waited {}ms for a db connection, {} other requests are waiting",
+                    waited.as_secs() * 1000 + u64::from(waited.subsec_nanos() / 1_000_000),
+                    self.connections.waiting().saturating_sub(1)
+                );
+            }
+
             Ok(Async::Ready(item))
+        } else if Instant::now() >= self.deadline {
+            Err(EventErrorKind::Busy.into())
         } else {
             // busy wait until we have a connection to use
             task::current().notify();
@@ -77,23 +185,110 @@ impl Future for Connections {
     }
 }
 
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        let waiting = &self.connections.waiting;
+        waiting.set(waiting.get().saturating_sub(1));
+    }
+}
+
+/// `PoolCounts` describes the state of the DbBroker's pool of `Connection`s
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PoolCounts {
+    /// Connections waiting in the pool for a request
+    pub idle: usize,
+    /// Connections being used by a request
+    pub in_use: usize,
+    /// Connections that were lost, or haven't been opened yet
+    pub connecting: usize,
+    /// Requests waiting for a connection
+    pub waiting: usize,
+}
+
 /// Define the DbBroker. This struct manages access to the connections, and additionally contains
 /// the database url to ensure that new connections can be created.
 pub struct DbBroker {
     num_connections: usize,
+    // Connections being opened, either when the broker starts or in place of lost ones
+    connecting: usize,
+    busy_timeout: Duration,
     db_url: String,
+    event_quota: Option<i64>,
     connections: Connections,
+    // Whether a request has given up waiting for a connection since the pool was last free
+    exhausted: Rc<Cell<bool>>,
+    alerts: Addr<Syn, AlertActor>,
 }
 
 impl DbBroker {
-    pub fn new(db_url: String, num_connections: usize) -> Self {
+    pub fn new(config: &Config, alerts: Addr<Syn, AlertActor>) -> Self {
         DbBroker {
-            num_connections: num_connections,
-            db_url: db_url,
+            num_connections: config.pool_size(),
+            connecting: 0,
+            busy_timeout: config.db_busy_timeout(),
+            db_url: config.database_url().to_owned(),
+            event_quota: config.event_quota(),
             connections: Connections::default(),
+            exhausted: Rc::new(Cell::new(false)),
+            alerts,
         }
     }
 
+    /// Count the pool's connections and the requests waiting for them
+    fn pool_counts(&self) -> PoolCounts {
+        let idle = self.connections.idle();
+
+        PoolCounts {
+            idle,
+            in_use: self.num_connections
+                .saturating_sub(idle)
+                .saturating_sub(self.connecting),
+            connecting: self.connecting,
+            waiting: self.connections.waiting(),
+        }
+    }
+
+    /// Open a connection outside the pool and pass every change announced on it to the recipient,
+    /// until the connection is lost
+    fn listen(
+        db_url: String,
+        recipient: Recipient<Syn, EventChanged>,
+    ) -> impl Future<Item = (), Error = EventError> {
+        connect_to_database(db_url, Arbiter::handle().clone())
+            .and_then(|connection| {
+                connection
+                    .batch_execute(&format!("LISTEN {}", EVENT_CHANGES_CHANNEL))
+                    .map_err(|(e, _)| EventError::from(e.context(EventErrorKind::Listen)))
+            })
+            .and_then(move |connection| {
+                ctx_debug!(LogContext::new("db_broker"), "Listening for event changes");
+
+                connection
+                    .notifications()
+                    .map_err(|e| EventError::from(e.context(EventErrorKind::Listen)))
+                    .for_each(move |notification| {
+                        match parse_event_change(&notification.payload) {
+                            Some(change) => {
+                                if recipient.do_send(change).is_err() {
+                                    ctx_error!(
+                                        LogContext::new("db_broker"),
+                                        "Failed to pass on event change: {:?}",
+                                        change
+                                    );
+                                }
+                            }
+                            None => ctx_error!(
+                                LogContext::new("db_broker"),
+                                "Unknown event change: {}",
+                                notification.payload
+                            ),
+                        }
+
+                        Ok(())
+                    })
+            })
+    }
+
     fn insert_event(
         system_id: i32,
         title: String,
@@ -101,10 +296,89 @@ impl DbBroker {
         start_date: DateTime<Tz>,
         end_date: DateTime<Tz>,
         hosts: Vec<i32>,
+        publish_at: Option<DateTime<Tz>>,
+        capacity: Option<i32>,
+        boost_threshold: Option<i32>,
+        location: Option<String>,
+        location_hint: Option<String>,
+        coordinates: Option<Coordinates>,
+        image_url: Option<String>,
+        rsvp_deadline: Option<DateTime<Tz>>,
+        members_only: bool,
+        unlisted: bool,
+        needs_approval: bool,
+        link_id: Option<i32>,
+        event_quota: Option<i64>,
         connection: Connection,
-    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
-        User::by_ids(hosts, connection)
-            .map(|(hosts, connection)| (hosts, connection))
+    ) -> impl Future<Item = ((Event, bool), Connection), Error = (EventError, Connection)> {
+        // A form that was submitted twice gets the event from the first submission, without
+        // counting against the quota again
+        DbBroker::event_from_link(link_id, connection).and_then(move |(event, connection)| {
+            if let Some(event) = event {
+                Either::A(Ok(((event, false), connection)).into_future())
+            } else {
+                Either::B(DbBroker::create_event(
+                    system_id,
+                    title,
+                    description,
+                    start_date,
+                    end_date,
+                    hosts,
+                    publish_at,
+                    capacity,
+                    boost_threshold,
+                    location,
+                    location_hint,
+                    coordinates,
+                    image_url,
+                    rsvp_deadline,
+                    members_only,
+                    unlisted,
+                    needs_approval,
+                    link_id,
+                    event_quota,
+                    connection,
+                ))
+            }
+        })
+    }
+
+    /// Look up the event made from a `NewEventLink`, if there's a link and it's been used
+    fn event_from_link(
+        link_id: Option<i32>,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Event>, Connection), Error = (EventError, Connection)> {
+        if let Some(link_id) = link_id {
+            Either::A(Event::by_link_id(link_id, connection))
+        } else {
+            Either::B(Ok((None, connection)).into_future())
+        }
+    }
+
+    fn create_event(
+        system_id: i32,
+        title: String,
+        description: String,
+        start_date: DateTime<Tz>,
+        end_date: DateTime<Tz>,
+        hosts: Vec<i32>,
+        publish_at: Option<DateTime<Tz>>,
+        capacity: Option<i32>,
+        boost_threshold: Option<i32>,
+        location: Option<String>,
+        location_hint: Option<String>,
+        coordinates: Option<Coordinates>,
+        image_url: Option<String>,
+        rsvp_deadline: Option<DateTime<Tz>>,
+        members_only: bool,
+        unlisted: bool,
+        needs_approval: bool,
+        link_id: Option<i32>,
+        event_quota: Option<i64>,
+        connection: Connection,
+    ) -> impl Future<Item = ((Event, bool), Connection), Error = (EventError, Connection)> {
+        DbBroker::check_quota(system_id, hosts.clone(), event_quota, connection)
+            .and_then(move |connection| User::by_ids(hosts, connection))
             .and_then(move |(hosts, connection)| {
                 let new_event = CreateEvent {
                     system_id,
@@ -113,10 +387,32 @@ impl DbBroker {
                     title,
                     description,
                     hosts,
+                    publish_at,
+                    capacity,
+                    boost_threshold,
+                    location,
+                    location_hint,
+                    coordinates,
+                    image_url,
+                    rsvp_deadline,
+                    members_only,
+                    unlisted,
+                    needs_approval,
+                    link_id,
                 };
 
                 new_event.create(connection)
             })
+            .and_then(move |(event, connection)| match event {
+                Some(event) => Either::A(Ok(((event, true), connection)).into_future()),
+                // Another submission used the link while this one was checking the quota
+                None => Either::B(DbBroker::event_from_link(link_id, connection).and_then(
+                    |(event, connection)| match event {
+                        Some(event) => Ok(((event, false), connection)),
+                        None => Err((EventErrorKind::Lookup.into(), connection)),
+                    },
+                )),
+            })
     }
 
     fn edit_event(
@@ -126,7 +422,18 @@ impl DbBroker {
         description: String,
         start_date: DateTime<Tz>,
         end_date: DateTime<Tz>,
-        hosts: Vec<i32>,
+        hosts: Option<Vec<i32>>,
+        publish_at: Option<DateTime<Tz>>,
+        capacity: Option<i32>,
+        boost_threshold: Option<i32>,
+        location: Option<String>,
+        location_hint: Option<String>,
+        coordinates: Option<Coordinates>,
+        image_url: Option<String>,
+        rsvp_deadline: Option<DateTime<Tz>>,
+        members_only: bool,
+        unlisted: bool,
+        link_id: Option<i32>,
         connection: Connection,
     ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
         let updated_event = UpdateEvent {
@@ -137,6 +444,17 @@ impl DbBroker {
             title,
             description,
             hosts,
+            publish_at,
+            capacity,
+            boost_threshold,
+            location,
+            location_hint,
+            coordinates,
+            image_url,
+            rsvp_deadline,
+            members_only,
+            unlisted,
+            link_id,
         };
 
         updated_event.update(connection)
@@ -149,6 +467,13 @@ impl DbBroker {
         Event::by_id(event_id, connection)
     }
 
+    fn lookup_event_by_public_id(
+        public_id: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
+        Event::by_public_id(public_id, connection)
+    }
+
     fn lookup_events_by_user_id(
         user_id: Integer,
         connection: Connection,
@@ -169,6 +494,51 @@ impl DbBroker {
         })
     }
 
+    fn approve_event(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::approve_by_id(event_id, connection)
+    }
+
+    fn reject_event(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::reject_by_id(event_id, connection)
+    }
+
+    fn cancel_event(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::cancel_by_id(event_id, connection)
+    }
+
+    fn transfer_event(
+        event_id: i32,
+        from_host: i32,
+        to_host: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::transfer_by_id(event_id, from_host, to_host, connection)
+    }
+
+    fn set_announcement(
+        event_id: i32,
+        message_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::set_announcement_id(event_id, message_id, connection)
+    }
+
+    fn lookup_announcement(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        Event::announcement_id_by_id(event_id, connection)
+    }
+
     fn delete_chat_system(
         channel_id: Integer,
         connection: Connection,
@@ -185,6 +555,25 @@ impl DbBroker {
             })
     }
 
+    fn set_chat_title(
+        chat_id: Integer,
+        title: Option<String>,
+        username: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        // The ID is either a linked chat's or a channel's, so both are updated
+        Chat::set_title(chat_id, title.clone(), connection).and_then(move |(_, connection)| {
+            ChatSystem::set_channel_title(chat_id, title, username, connection)
+        })
+    }
+
+    fn delete_chat(
+        chat_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Chat::delete_by_chat_id(chat_id, connection)
+    }
+
     fn insert_channel(
         channel_id: Integer,
         connection: Connection,
@@ -234,6 +623,45 @@ impl DbBroker {
         Event::by_chat_id(chat_id, connection)
     }
 
+    fn get_next_event_by_chat_id(
+        chat_id: Integer,
+        after: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Event>, Connection), Error = (EventError, Connection)> {
+        Event::next_by_chat_id(chat_id, after, connection)
+    }
+
+    fn get_events_by_chat_id_in_range(
+        chat_id: Integer,
+        start_date: DateTime<Tz>,
+        end_date: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::by_chat_id_in_range(chat_id, start_date, end_date, connection)
+    }
+
+    fn search_events(
+        scope: SearchScope,
+        terms: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::search(scope, &terms, connection)
+    }
+
+    fn get_listings(
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Listing>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::listings(connection)
+    }
+
+    fn search_directory(
+        terms: String,
+        tag: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::directory(&terms, &tag, connection)
+    }
+
     fn get_events_in_range(
         start_date: DateTime<Tz>,
         end_date: DateTime<Tz>,
@@ -242,6 +670,312 @@ impl DbBroker {
         Event::in_range(start_date, end_date, connection)
     }
 
+    fn get_events_to_publish(
+        before: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::to_publish(before, connection)
+    }
+
+    fn get_events_closing_rsvps(
+        start_date: DateTime<Tz>,
+        end_date: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::closing_rsvps(start_date, end_date, connection)
+    }
+
+    fn claim_boosts(
+        before: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        Event::claim_boosts(before, connection)
+    }
+
+    fn mark_published(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        Event::mark_published_by_id(event_id, connection)
+    }
+
+    fn claim_stage(
+        event_id: i32,
+        stage: &'static str,
+        due: DateTime<Utc>,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        Event::claim_stage(event_id, stage, due, connection)
+    }
+
+    fn toggle_protect_content(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_protect_content(channel_id, connection)
+    }
+
+    fn toggle_moderated(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_moderated(channel_id, connection)
+    }
+
+    fn ban_host(
+        channel_id: Integer,
+        user: BannedUser,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::ban_host(channel_id, user, connection)
+    }
+
+    fn unban_host(
+        channel_id: Integer,
+        user: BannedUser,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::unban_host(channel_id, user, connection)
+    }
+
+    fn check_banned(
+        system_id: i32,
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::is_banned(system_id, user_id, connection)
+    }
+
+    fn toggle_pin_announcements(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_pin_announcements(channel_id, connection)
+    }
+
+    fn toggle_listed(
+        channel_id: Integer,
+        title: Option<String>,
+        username: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_listed(channel_id, title, username, connection)
+    }
+
+    fn set_api_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_api_secret(channel_id, secret, connection)
+    }
+
+    fn lookup_system_with_api_secret(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        ChatSystem::by_id_with_api_secret(system_id, connection)
+    }
+
+    fn set_read_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_read_secret(channel_id, secret, connection)
+    }
+
+    fn lookup_system_with_read_secret(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        ChatSystem::by_id_with_read_secret(system_id, connection)
+    }
+
+    fn set_calendar_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_calendar_secret(channel_id, secret, connection)
+    }
+
+    fn lookup_system_with_calendar_secret(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        ChatSystem::by_id_with_calendar_secret(system_id, connection)
+    }
+
+    fn set_pinned_event(
+        system_id: i32,
+        event_id: i32,
+        message_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_pinned_event(system_id, event_id, message_id, connection)
+    }
+
+    fn clear_pinned_event(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::clear_pinned_event(event_id, connection)
+    }
+
+    fn set_digest_day(
+        channel_id: Integer,
+        digest_day: Option<Weekday>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_digest_day(channel_id, digest_day, connection)
+    }
+
+    fn set_boost_threshold(
+        channel_id: Integer,
+        boost_threshold: Option<i32>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_boost_threshold(channel_id, boost_threshold, connection)
+    }
+
+    fn set_greeting(
+        channel_id: Integer,
+        greeting: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_greeting(channel_id, greeting, connection)
+    }
+
+    fn set_channel_language(
+        channel_id: Integer,
+        language: Language,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_language(channel_id, language, connection)
+    }
+
+    fn lookup_language(
+        chat_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Language>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::language_for_chat(chat_id, connection)
+    }
+
+    fn set_channel_time_format(
+        channel_id: Integer,
+        time_format: TimeFormat,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_time_format(channel_id, time_format, connection)
+    }
+
+    fn set_template(
+        channel_id: Integer,
+        kind: TemplateKind,
+        template: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_template(channel_id, kind, template, connection)
+    }
+
+    fn set_feature(
+        channel_id: Integer,
+        feature: Feature,
+        enabled: bool,
+        connection: Connection,
+    ) -> impl Future<Item = (Features, Connection), Error = (EventError, Connection)> {
+        Features::set_enabled(channel_id, feature, enabled, connection)
+    }
+
+    fn set_greeting_message(
+        system_id: i32,
+        message_id: Option<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_greeting_message(system_id, message_id, connection)
+    }
+
+    fn toggle_pin_digest(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_pin_digest(channel_id, connection)
+    }
+
+    fn set_pinned_digest(
+        system_id: i32,
+        message_id: Option<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::set_pinned_digest(system_id, message_id, connection)
+    }
+
+    fn release_digest(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        ChatSystem::release_digest(system_id, connection)
+    }
+
+    fn claim_digests(
+        digest_day: Weekday,
+        since: DateTime<Tz>,
+        now: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<ChatSystem>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::claim_digests(digest_day, since, now, connection)
+    }
+
+    fn rsvp(
+        event_id: i32,
+        user_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (RsvpStatus, Connection), Error = (EventError, Connection)> {
+        Rsvp::going(event_id, user_id, connection)
+    }
+
+    fn cancel_rsvp(
+        event_id: i32,
+        user_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        Rsvp::cancel(event_id, user_id, connection)
+    }
+
+    fn fill_waitlist(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        Rsvp::fill(event_id, connection)
+    }
+
+    fn get_system_members(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        User::by_system_id(system_id, connection)
+    }
+
+    fn get_attendees(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((Vec<User>, Vec<User>), Connection), Error = (EventError, Connection)>
+    {
+        Rsvp::attendees(event_id, connection)
+    }
+
+    fn get_recipients(
+        event_id: i32,
+        notification: Notification,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Integer>, Connection), Error = (EventError, Connection)> {
+        UserPreferences::recipients(event_id, notification, connection)
+    }
+
     fn get_events_for_system(
         system_id: i32,
         connection: Connection,
@@ -271,6 +1005,13 @@ impl DbBroker {
         ChatSystem::by_channel_id(channel_id, connection)
     }
 
+    fn get_systems_by_channels(
+        channel_ids: Vec<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<ChatSystem>, Connection), Error = (EventError, Connection)> {
+        ChatSystem::by_channel_ids(channel_ids, connection)
+    }
+
     fn get_users_with_chats(
         connection: Connection,
     ) -> impl Future<Item = (Vec<(User, Chat)>, Connection), Error = (EventError, Connection)> {
@@ -294,20 +1035,50 @@ impl DbBroker {
         EditEventLink::by_id(id, connection)
     }
 
-    fn delete_edit_event_link(
+    fn edit_event_link_unused(
         id: i32,
         connection: Connection,
-    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        EditEventLink::delete(id, connection).map(|c| ((), c))
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        EditEventLink::is_unused(id, connection)
     }
 
     fn store_event_link(
         user_id: i32,
         system_id: i32,
         secret: String,
+        event_quota: Option<i64>,
         connection: Connection,
     ) -> impl Future<Item = (NewEventLink, Connection), Error = (EventError, Connection)> {
-        NewEventLink::create(user_id, system_id, secret, connection)
+        DbBroker::check_quota(system_id, vec![user_id], event_quota, connection).and_then(
+            move |connection| NewEventLink::create(user_id, system_id, secret, connection),
+        )
+    }
+
+    /// Fail with a Quota error if any of the hosts is already hosting as many upcoming events in
+    /// the chat system as the quota allows
+    fn check_quota(
+        system_id: i32,
+        hosts: Vec<i32>,
+        event_quota: Option<i64>,
+        connection: Connection,
+    ) -> impl Future<Item = Connection, Error = (EventError, Connection)> {
+        let hosts = if event_quota.is_some() {
+            hosts
+        } else {
+            Vec::new()
+        };
+
+        stream::iter_ok(hosts).fold(connection, move |connection, user_id| {
+            Event::count_upcoming_for_host(system_id, user_id, connection).and_then(
+                move |(upcoming, connection)| {
+                    if event_quota.map(|quota| upcoming >= quota).unwrap_or(false) {
+                        Err((EventErrorKind::Quota.into(), connection))
+                    } else {
+                        Ok(connection)
+                    }
+                },
+            )
+        })
     }
 
     fn get_event_link(
@@ -317,11 +1088,18 @@ impl DbBroker {
         NewEventLink::by_id(id, connection)
     }
 
-    fn delete_event_link(
+    fn get_used_event_link(
         id: i32,
         connection: Connection,
-    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        NewEventLink::delete(id, connection).map(|c| ((), c))
+    ) -> impl Future<Item = (NewEventLink, Connection), Error = (EventError, Connection)> {
+        NewEventLink::used_by_id(id, connection)
+    }
+
+    fn event_link_unused(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        NewEventLink::is_unused(id, connection)
     }
 
     fn lookup_user(
@@ -344,22 +1122,155 @@ impl DbBroker {
         ChatSystem::all_with_chats(connection)
     }
 
-    fn remove_user_chat(
+    fn remove_user_from_chat(
         user_id: Integer,
         chat_id: Integer,
+        delete_user: bool,
         connection: Connection,
     ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        debug!(
-            "Deleting relation between chat {} and user {}",
-            chat_id, user_id
+        ctx_debug!(
+            LogContext::new("db_broker").chat_id(chat_id),
+            "Deleting relation with user {}",
+            user_id
         );
-        User::delete_relation_by_ids(user_id, chat_id, connection)
+        User::remove_from_chat(user_id, chat_id, delete_user, connection)
+    }
+
+    fn forget_user(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Forgotten, Connection), Error = (EventError, Connection)> {
+        User::forget(user_id, connection)
+    }
+
+    fn export_user(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Export, Connection), Error = (EventError, Connection)> {
+        User::export(user_id, connection)
+    }
+
+    fn get_stats(
+        channel_id: Integer,
+        since: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Stats, Connection), Error = (EventError, Connection)> {
+        Stats::for_channel(channel_id, since, connection)
+    }
+
+    fn get_suggested_times(
+        system_id: i32,
+        timezone: Tz,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<TimeSlot>, Connection), Error = (EventError, Connection)> {
+        TimeSlot::best_for_system(system_id, timezone, connection)
     }
 
-    fn delete_user_by_user_id(
+    fn lookup_preferences(
         user_id: Integer,
         connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        UserPreferences::by_user_id(user_id, connection)
+    }
+
+    fn set_muted(
+        user_id: Integer,
+        notification: Option<Notification>,
+        muted: bool,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        UserPreferences::set_muted(user_id, notification, muted, connection)
+    }
+
+    fn set_user_language(
+        user_id: Integer,
+        language: Language,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        UserPreferences::set_language(user_id, language, connection)
+    }
+
+    fn set_user_time_format(
+        user_id: Integer,
+        time_format: TimeFormat,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        UserPreferences::set_time_format(user_id, time_format, connection)
+    }
+
+    fn new_webhook(
+        channel_id: Integer,
+        url: String,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Webhook, Connection), Error = (EventError, Connection)> {
+        Webhook::create(channel_id, url, secret, connection)
+    }
+
+    fn rotate_webhook_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        Webhook::rotate_secret(channel_id, secret, connection)
+    }
+
+    fn delete_webhook(
+        channel_id: Integer,
+        url: String,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        Webhook::delete(channel_id, url, connection)
+    }
+
+    fn get_webhooks_for_system(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Webhook>, Connection), Error = (EventError, Connection)> {
+        Webhook::by_system_id(system_id, connection)
+    }
+
+    fn get_webhooks_for_channel(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Webhook>, Connection), Error = (EventError, Connection)> {
+        Webhook::by_channel_id(channel_id, connection)
+    }
+
+    fn store_failed_message(
+        chat_id: Integer,
+        event_id: i32,
+        announcement: bool,
+        text: String,
+        error: String,
+        connection: Connection,
     ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        User::delete_by_user_id(user_id, connection)
+        FailedMessage::create(chat_id, event_id, announcement, text, error, connection)
+    }
+
+    fn get_failed_messages(
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<FailedMessage>, Connection), Error = (EventError, Connection)> {
+        FailedMessage::recent(connection)
+    }
+
+    fn take_failed_message(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (FailedMessage, Connection), Error = (EventError, Connection)> {
+        FailedMessage::take(id, connection)
+    }
+}
+
+/// Read a change announced by the `notify_event_change` trigger, like `saved:12`
+fn parse_event_change(payload: &str) -> Option<EventChanged> {
+    let mut parts = payload.splitn(2, ':');
+    let kind = parts.next()?;
+    let event_id = parts.next()?.parse::<i32>().ok()?;
+
+    match kind {
+        "saved" => Some(EventChanged::Saved(event_id)),
+        "deleted" => Some(EventChanged::Deleted(event_id)),
+        _ => None,
     }
 }
@@ -20,7 +20,9 @@
 use actix::fut::wrap_future;
 use actix::{Actor, AsyncContext, Context, Handler, Message};
 use event_web::{
-    EditEvent, FrontendError, FrontendErrorKind, LookupEvent, NewEvent, SendFutResponse,
+    EditEvent, FrontendError, FrontendErrorKind, ListApiEvents, ListDirectory, LookupApiEvent,
+    LookupCalendar, LookupEvent, LookupPublicEvent, NewEvent, PushEvent, SendFutResponse,
+    SuggestTimes, TelegramUpdate,
 };
 use failure::Fail;
 use futures::sync::oneshot;
@@ -83,8 +85,98 @@ impl Handler<EditEvent> for EventActor {
 
     fn handle(&mut self, msg: EditEvent, ctx: &mut Self::Context) -> Self::Result {
         SendFutResponse::new(
-            Box::new(split(self.edit_event(msg.0, msg.1), ctx).then(flatten))
+            Box::new(split(self.edit_event(msg.0, msg.1, msg.2), ctx).then(flatten))
                 as <EditEvent as Message>::Result,
         )
     }
 }
+
+impl Handler<PushEvent> for EventActor {
+    type Result = SendFutResponse<PushEvent>;
+
+    fn handle(&mut self, msg: PushEvent, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(
+                split(self.push_event(msg.channel_id, msg.event, msg.token), ctx).then(flatten),
+            )
+                as <PushEvent as Message>::Result,
+        )
+    }
+}
+
+impl Handler<LookupPublicEvent> for EventActor {
+    type Result = SendFutResponse<LookupPublicEvent>;
+
+    fn handle(&mut self, msg: LookupPublicEvent, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.lookup_public_event(msg.0), ctx).then(flatten))
+                as <LookupPublicEvent as Message>::Result,
+        )
+    }
+}
+
+impl Handler<ListApiEvents> for EventActor {
+    type Result = SendFutResponse<ListApiEvents>;
+
+    fn handle(&mut self, msg: ListApiEvents, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.list_api_events(msg.channel_id, msg.token), ctx).then(flatten))
+                as <ListApiEvents as Message>::Result,
+        )
+    }
+}
+
+impl Handler<LookupApiEvent> for EventActor {
+    type Result = SendFutResponse<LookupApiEvent>;
+
+    fn handle(&mut self, msg: LookupApiEvent, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.lookup_api_event(msg.event_id, msg.token), ctx).then(flatten))
+                as <LookupApiEvent as Message>::Result,
+        )
+    }
+}
+
+impl Handler<ListDirectory> for EventActor {
+    type Result = SendFutResponse<ListDirectory>;
+
+    fn handle(&mut self, msg: ListDirectory, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.list_directory(msg.terms, msg.tag), ctx).then(flatten))
+                as <ListDirectory as Message>::Result,
+        )
+    }
+}
+
+impl Handler<TelegramUpdate> for EventActor {
+    type Result = SendFutResponse<TelegramUpdate>;
+
+    fn handle(&mut self, msg: TelegramUpdate, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.telegram_update(msg.token, msg.body), ctx).then(flatten))
+                as <TelegramUpdate as Message>::Result,
+        )
+    }
+}
+
+impl Handler<SuggestTimes> for EventActor {
+    type Result = SendFutResponse<SuggestTimes>;
+
+    fn handle(&mut self, msg: SuggestTimes, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.suggest_times(msg.id, msg.timezone), ctx).then(flatten))
+                as <SuggestTimes as Message>::Result,
+        )
+    }
+}
+
+impl Handler<LookupCalendar> for EventActor {
+    type Result = SendFutResponse<LookupCalendar>;
+
+    fn handle(&mut self, msg: LookupCalendar, ctx: &mut Self::Context) -> Self::Result {
+        SendFutResponse::new(
+            Box::new(split(self.lookup_calendar(msg.0), ctx).then(flatten))
+                as <LookupCalendar as Message>::Result,
+        )
+    }
+}
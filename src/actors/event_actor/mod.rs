@@ -17,23 +17,40 @@
  * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-//! This module defines the EventActor. This actor handles callbacks from the web UI
+//! This module defines the EventActor. This actor handles callbacks from the web UI and the API
 use actix::{Addr, Syn, Unsync};
+use chrono_tz::Tz;
+use event_web::locale::TimeFormat;
 use event_web::verify_secret;
-use event_web::{Event as FrontendEvent, FrontendError, FrontendErrorKind};
+use event_web::{
+    ApiEvent, DirectoryChannel, DirectoryEvent, Event as FrontendEvent, FrontendError,
+    FrontendErrorKind, Host, PublicEvent, StoredEvent, SuggestedTime,
+};
 use failure::Fail;
 use futures::{Future, IntoFuture};
 
 use actors::db_broker::messages::{
-    DeleteEditEventLink, DeleteEventLink, EditEvent, LookupEditEventLink, LookupEvent,
-    LookupEventLink, NewEvent,
+    EditEvent, GetEventsForSystem, GetListings, GetSuggestedTimes, GetSystemMembers,
+    LookupEditEventLink, LookupEvent, LookupEventByPublicId, LookupEventLink, LookupSystem,
+    LookupSystemWithApiSecret, LookupSystemWithCalendarSecret, LookupSystemWithReadSecret,
+    LookupUsedEventLink, NewEvent, SearchDirectory,
 };
 use actors::db_broker::DbBroker;
-use actors::telegram_actor::messages::{NewEvent as TgNewEvent, UpdateEvent as TgUpdateEvent};
+use actors::telegram_actor::messages::{
+    CapacityChanged, NewEvent as TgNewEvent, ReviewEvent as TgReviewEvent,
+    UpdateEvent as TgUpdateEvent, WebhookUpdate,
+};
 use actors::telegram_actor::TelegramActor;
 use actors::timer::messages::{Events, UpdateEvent};
-use actors::timer::Timer;
+use actors::timer_coordinator::TimerCoordinator;
+use actors::webhook_actor::messages::EventChanged;
+use actors::webhook_actor::{EventAction, WebhookActor};
 use error::{EventError, EventErrorKind};
+use logging::LogContext;
+use models::chat_system::ChatSystem;
+use models::event::{Coordinates, Event};
+use models::features::Feature;
+use models::user::User;
 use util::flatten;
 
 mod actor;
@@ -45,86 +62,237 @@ mod actor;
 pub struct EventActor {
     tg: Addr<Syn, TelegramActor>,
     db: Addr<Unsync, DbBroker>,
-    timer: Addr<Syn, Timer>,
+    timer: Addr<Syn, TimerCoordinator>,
+    webhooks: Addr<Syn, WebhookActor>,
 }
 
 impl EventActor {
     pub fn new(
         tg: Addr<Syn, TelegramActor>,
         db: Addr<Unsync, DbBroker>,
-        timer: Addr<Syn, Timer>,
+        timer: Addr<Syn, TimerCoordinator>,
+        webhooks: Addr<Syn, WebhookActor>,
     ) -> Self {
-        EventActor { tg, db, timer }
+        EventActor {
+            tg,
+            db,
+            timer,
+            webhooks,
+        }
     }
 
     /// This handles new events from the web UI
+    ///
+    /// The event's link is used up in the same transaction that saves the event, so a form that's
+    /// submitted twice only makes one event. The second submission gets the first one's event,
+    /// and nothing is announced for it again.
     fn new_event(
         &mut self,
         event: FrontendEvent,
         id: String,
-    ) -> impl Future<Item = (), Error = FrontendError> {
-        debug!("Got event: {:?}", event);
+    ) -> impl Future<Item = (FrontendEvent, TimeFormat), Error = FrontendError> {
+        ctx_debug!(LogContext::new("events"), "Got event: {:?}", event);
 
         let database = self.db.clone();
         let db = self.db.clone();
+        let used_db = self.db.clone();
+        let system_db = self.db.clone();
         let tg = self.tg.clone();
         let timer = self.timer.clone();
+        let webhooks = self.webhooks.clone();
 
-        // The ID is defined as a series of random characters, followed by an =, followed by the
-        // ID of the `NewEventLink` used to create the event. This is used to validate that
-        // someone actually used the generated link instead of guessing.
-        id.rfind('=')
-            .ok_or(EventError::from(EventErrorKind::Secret))
-            .and_then(move |index| {
-                let (base64d, nel_id) = id.split_at(index);
-                let base64d = base64d.to_owned();
-                let nel_id = nel_id.trim_left_matches('=');
-
-                nel_id
-                    .parse::<i32>()
-                    .map_err(|_| EventError::from(EventErrorKind::Secret))
-                    .map(|nel_id| (nel_id, base64d))
-            })
+        split_event_link(&id)
             .into_future()
             .and_then(move |(nel_id, base64d)| {
                 db.send(LookupEventLink(nel_id))
                     .then(flatten)
-                    .and_then(move |nel| match verify_secret(&base64d, nel.secret()) {
-                        Ok(b) => if b {
+                    // The link may have been used by an earlier submission of the same form
+                    .or_else(move |_| used_db.send(LookupUsedEventLink(nel_id)).then(flatten))
+                    .and_then(move |nel| {
+                        if verify_secret(&base64d, nel.secret()) {
                             // If the secret was verified, continue
                             Ok(nel)
                         } else {
                             // Error if the secret was not valid
                             Err(EventError::from(EventErrorKind::Frontend))
-                        },
-                        Err(e) => Err(EventError::from(e.context(EventErrorKind::Frontend))),
+                        }
                     })
                     .and_then(move |nel| {
                         database
-                            .send(DeleteEventLink { id: nel.id() })
+                            .send(NewEvent {
+                                system_id: nel.system_id(),
+                                title: event.title().to_owned(),
+                                description: event.description().to_owned(),
+                                start_date: event.start_date(),
+                                end_date: event.end_date(),
+                                hosts: vec![nel.user_id()],
+                                publish_at: event.publish_at(),
+                                capacity: event.capacity(),
+                                boost_threshold: event.boost_threshold(),
+                                location: event.location().map(|s| s.to_owned()),
+                                location_hint: event.location_hint().map(|s| s.to_owned()),
+                                coordinates: backend_coordinates(&event),
+                                image_url: event.image_url().map(|s| s.to_owned()),
+                                rsvp_deadline: event.rsvp_deadline(),
+                                members_only: event.members_only(),
+                                unlisted: event.unlisted(),
+                                needs_approval: true,
+                                link_id: Some(nel.id()),
+                            })
                             .then(flatten)
-                            .join(
-                                database
-                                    .send(NewEvent {
-                                        system_id: nel.system_id(),
-                                        title: event.title().to_owned(),
-                                        description: event.description().to_owned(),
-                                        start_date: event.start_date(),
-                                        end_date: event.end_date(),
-                                        hosts: vec![nel.user_id()],
-                                    })
-                                    .then(flatten)
-                                    .map(move |event| {
-                                        tg.do_send(TgNewEvent(event.clone()));
-                                        timer.do_send(Events {
-                                            events: vec![event],
-                                        });
-                                    }),
-                            )
                     })
-                    .map(|_| ())
+                    .map(move |(event, created)| {
+                        if created {
+                            // Scheduled announcements are sent by the Timer, and pending events
+                            // are announced once they're approved
+                            if event.pending() {
+                                tg.do_send(TgReviewEvent(event.clone()));
+                            } else {
+                                if event.publish_at().is_none() {
+                                    tg.do_send(TgNewEvent(event.clone()));
+                                }
+                                webhooks.do_send(EventChanged {
+                                    action: EventAction::Created,
+                                    event: event.clone(),
+                                });
+                            }
+                            timer.do_send(Events {
+                                events: vec![event.clone()],
+                            });
+                        } else {
+                            ctx_debug!(
+                                LogContext::new("events").event_id(event.id()),
+                                "Form was submitted again"
+                            );
+                        }
+
+                        event
+                    })
+                    .and_then(move |event| {
+                        system_db
+                            .send(LookupSystem {
+                                system_id: event.system_id(),
+                            })
+                            .then(flatten)
+                            .map(move |chat_system| {
+                                (frontend_event(&event), chat_system.time_format())
+                            })
+                    })
+            })
+            .map_err(|e| frontend_error(e, FrontendErrorKind::Verification))
+    }
+
+    /// This handles requests for the times to suggest on the new event form
+    ///
+    /// The times come from the past events of the chat system the link was made for. The link
+    /// isn't used up by this.
+    fn suggest_times(
+        &mut self,
+        id: String,
+        timezone: Tz,
+    ) -> impl Future<Item = Vec<SuggestedTime>, Error = FrontendError> {
+        let db = self.db.clone();
+        let database = self.db.clone();
+
+        split_event_link(&id)
+            .into_future()
+            .and_then(move |(nel_id, base64d)| {
+                db.send(LookupEventLink(nel_id))
+                    .then(flatten)
+                    .and_then(move |nel| {
+                        if verify_secret(&base64d, nel.secret()) {
+                            Ok(nel)
+                        } else {
+                            Err(EventError::from(EventErrorKind::Frontend))
+                        }
+                    })
+            })
+            .and_then(move |nel| {
+                database
+                    .send(GetSuggestedTimes {
+                        system_id: nel.system_id(),
+                        timezone,
+                    })
+                    .then(flatten)
+            })
+            .map(|slots| {
+                slots
+                    .iter()
+                    .map(|slot| {
+                        SuggestedTime::new(slot.weekday(), slot.hour(), slot.average_attendance())
+                    })
+                    .collect()
+            })
+            .map_err(|e| frontend_error(e, FrontendErrorKind::Verification))
+    }
+
+    /// This handles events pushed to the API by external systems
+    ///
+    /// Pushed events have no hosts, but are otherwise created and announced just like events from
+    /// the web UI. They never wait for a moderated channel's admins to approve them.
+    fn push_event(
+        &mut self,
+        channel_id: i64,
+        event: FrontendEvent,
+        token: String,
+    ) -> impl Future<Item = (), Error = FrontendError> {
+        ctx_debug!(LogContext::new("events"), "Got pushed event: {:?}", event);
+
+        let db = self.db.clone();
+        let tg = self.tg.clone();
+        let timer = self.timer.clone();
+        let webhooks = self.webhooks.clone();
+
+        verify_api_token(self.db.clone(), token)
+            .and_then(move |chat_system| {
+                // A token only grants access to its own channel
+                if chat_system.events_channel() == channel_id {
+                    Ok(chat_system)
+                } else {
+                    Err(FrontendErrorKind::BadToken.into())
+                }
+            })
+            .and_then(move |chat_system| {
+                let log_ctx = LogContext::new("events").chat_id(chat_system.events_channel());
+
+                db.send(NewEvent {
+                    system_id: chat_system.id(),
+                    title: event.title().to_owned(),
+                    description: event.description().to_owned(),
+                    start_date: event.start_date(),
+                    end_date: event.end_date(),
+                    hosts: Vec::new(),
+                    publish_at: event.publish_at(),
+                    capacity: event.capacity(),
+                    boost_threshold: event.boost_threshold(),
+                    location: event.location().map(|s| s.to_owned()),
+                    location_hint: event.location_hint().map(|s| s.to_owned()),
+                    coordinates: backend_coordinates(&event),
+                    image_url: event.image_url().map(|s| s.to_owned()),
+                    rsvp_deadline: event.rsvp_deadline(),
+                    members_only: event.members_only(),
+                    unlisted: event.unlisted(),
+                    // The channel's admins handed out the API token themselves
+                    needs_approval: false,
+                    link_id: None,
+                }).then(flatten)
+                    .map(move |(event, _)| {
+                        ctx_debug!(log_ctx.event_id(event.id()), "Created pushed event");
+
+                        // Scheduled announcements are sent by the Timer
+                        if event.publish_at().is_none() {
+                            tg.do_send(TgNewEvent(event.clone()));
+                        }
+                        webhooks.do_send(EventChanged {
+                            action: EventAction::Created,
+                            event: event.clone(),
+                        });
+                        timer.do_send(Events {
+                            events: vec![event],
+                        });
+                    })
+                    .map_err(|e| frontend_error(e, FrontendErrorKind::Creation))
             })
-            .map_err(|e| FrontendError::from(e.context(FrontendErrorKind::Verification)))
     }
 
     /// When editing an event, the frontend requests the event's current contents. This handles
@@ -132,7 +300,7 @@ impl EventActor {
     fn lookup_event(
         &mut self,
         id: String,
-    ) -> impl Future<Item = FrontendEvent, Error = FrontendError> {
+    ) -> impl Future<Item = StoredEvent, Error = FrontendError> {
         let eel_id = if let Some(index) = id.rfind('=') {
             let (base64d, eel_id) = id.split_at(index);
             let base64d = base64d.to_owned();
@@ -154,31 +322,223 @@ impl EventActor {
                 database
                     .send(LookupEditEventLink(eel_id))
                     .then(flatten)
-                    .and_then(move |eel| match verify_secret(&base64d, eel.secret()) {
-                        Ok(b) => if b {
+                    .and_then(move |eel| {
+                        if verify_secret(&base64d, eel.secret()) {
                             Ok(eel)
                         } else {
                             Err(EventError::from(EventErrorKind::Frontend))
-                        },
-                        Err(e) => Err(EventError::from(e.context(EventErrorKind::Frontend))),
+                        }
                     })
                     .and_then(move |eel| {
+                        // Anyone in the event's chats can be picked as a host
                         database
                             .send(LookupEvent {
                                 event_id: eel.event_id(),
                             })
                             .then(flatten)
+                            .join3(
+                                database
+                                    .send(GetSystemMembers {
+                                        system_id: eel.system_id(),
+                                    })
+                                    .then(flatten),
+                                database
+                                    .send(LookupSystem {
+                                        system_id: eel.system_id(),
+                                    })
+                                    .then(flatten),
+                            )
                     })
             })
-            .map(|event| {
-                FrontendEvent::from_parts(
-                    event.title().to_owned(),
-                    event.description().to_owned(),
-                    event.start_date().to_owned(),
-                    event.end_date().to_owned(),
+            .map(|(event, members, chat_system)| {
+                let hosts = event.hosts().iter().map(frontend_host).collect();
+                let members = members.iter().map(frontend_host).collect();
+
+                StoredEvent::new(
+                    frontend_event(&event),
+                    hosts,
+                    members,
+                    chat_system.title().map(|title| title.to_owned()),
+                    event.updated_at().cloned(),
+                    event.cancelled(),
                 )
             })
-            .map_err(|e| FrontendError::from(e.context(FrontendErrorKind::Verification)))
+            .map_err(|e| frontend_error(e, FrontendErrorKind::Verification))
+    }
+
+    /// This handles requests for an event's public page
+    ///
+    /// Events that aren't public are treated as though they don't exist
+    fn lookup_public_event(
+        &mut self,
+        public_id: String,
+    ) -> impl Future<Item = PublicEvent, Error = FrontendError> {
+        let db = self.db.clone();
+
+        self.db
+            .send(LookupEventByPublicId { public_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Channels can turn their public pages off
+                db.send(LookupSystem {
+                    system_id: event.system_id(),
+                }).then(flatten)
+                    .map(move |chat_system| (event, chat_system))
+            })
+            .map_err(|e| frontend_error(e, FrontendErrorKind::NoEvent))
+            .and_then(|(event, chat_system)| {
+                if is_public(&event) && chat_system.features().enabled(Feature::PublicPage) {
+                    Ok(public_event(&event))
+                } else {
+                    Err(FrontendErrorKind::NoEvent.into())
+                }
+            })
+    }
+
+    /// This handles API requests for the events in a channel
+    fn list_api_events(
+        &mut self,
+        channel_id: i64,
+        token: String,
+    ) -> impl Future<Item = Vec<ApiEvent>, Error = FrontendError> {
+        let db = self.db.clone();
+
+        verify_read_token(self.db.clone(), token)
+            .and_then(move |chat_system| {
+                // A token only grants access to its own channel
+                if chat_system.events_channel() == channel_id {
+                    Ok(chat_system)
+                } else {
+                    Err(FrontendErrorKind::BadToken.into())
+                }
+            })
+            .and_then(move |chat_system| {
+                db.send(GetEventsForSystem {
+                    system_id: chat_system.id(),
+                }).then(flatten)
+                    .map_err(|e| frontend_error(e, FrontendErrorKind::NoEvent))
+            })
+            .map(|events| {
+                events
+                    .into_iter()
+                    .filter(is_public)
+                    .map(|event| api_event(&event))
+                    .collect()
+            })
+    }
+
+    /// This handles API requests for a single event
+    fn lookup_api_event(
+        &mut self,
+        event_id: i32,
+        token: String,
+    ) -> impl Future<Item = ApiEvent, Error = FrontendError> {
+        let db = self.db.clone();
+
+        verify_read_token(self.db.clone(), token).and_then(move |chat_system| {
+            db.send(LookupEvent { event_id })
+                .then(flatten)
+                .map_err(|e| frontend_error(e, FrontendErrorKind::NoEvent))
+                .and_then(move |event| {
+                    // Events in other channels are treated as though they don't exist
+                    if event.system_id() == chat_system.id() && is_public(&event) {
+                        Ok(api_event(&event))
+                    } else {
+                        Err(FrontendErrorKind::NoEvent.into())
+                    }
+                })
+        })
+    }
+
+    /// This handles requests for the directory of listed channels
+    ///
+    /// Channels without matching events are left out when the directory is being searched or
+    /// filtered by a tag, but shown otherwise so people can still find them
+    fn list_directory(
+        &mut self,
+        terms: String,
+        tag: String,
+    ) -> impl Future<Item = Vec<DirectoryChannel>, Error = FrontendError> {
+        let db = self.db.clone();
+        let filtered = terms.len() > 0 || tag.len() > 0;
+
+        self.db
+            .send(GetListings)
+            .then(flatten)
+            .and_then(move |listings| {
+                db.send(SearchDirectory { terms, tag })
+                    .then(flatten)
+                    .map(move |events| (listings, events))
+            })
+            .map_err(|e| frontend_error(e, FrontendErrorKind::NoEvent))
+            .map(move |(listings, events)| {
+                listings
+                    .into_iter()
+                    .map(|listing| {
+                        let events = events
+                            .iter()
+                            .filter(|event| event.system_id() == listing.system_id())
+                            .map(|event| {
+                                DirectoryEvent::new(
+                                    event.public_id().to_owned(),
+                                    public_event(event),
+                                )
+                            })
+                            .collect::<Vec<_>>();
+
+                        DirectoryChannel::new(
+                            listing.title().unwrap_or("Untitled channel").to_owned(),
+                            listing.username().map(|s| s.to_owned()),
+                            events,
+                        )
+                    })
+                    .filter(|channel| !filtered || !channel.events().is_empty())
+                    .collect()
+            })
+    }
+
+    /// This handles requests for a channel's calendar, which shows the same events the directory
+    /// would
+    fn lookup_calendar(
+        &mut self,
+        token: String,
+    ) -> impl Future<Item = DirectoryChannel, Error = FrontendError> {
+        let db = self.db.clone();
+
+        verify_calendar_token(self.db.clone(), token).and_then(move |chat_system| {
+            db.send(GetEventsForSystem {
+                system_id: chat_system.id(),
+            }).then(flatten)
+                .map_err(|e| frontend_error(e, FrontendErrorKind::NoEvent))
+                .map(move |events| {
+                    let events = events
+                        .into_iter()
+                        .filter(is_public)
+                        .map(|event| {
+                            DirectoryEvent::new(event.public_id().to_owned(), public_event(&event))
+                        })
+                        .collect();
+
+                    DirectoryChannel::new(
+                        chat_system.title().unwrap_or("Untitled channel").to_owned(),
+                        None,
+                        events,
+                    )
+                })
+        })
+    }
+
+    /// This passes updates sent to the webhook on to the `TelegramActor`, which checks the
+    /// webhook's token
+    fn telegram_update(
+        &mut self,
+        token: String,
+        body: String,
+    ) -> impl Future<Item = (), Error = FrontendError> {
+        self.tg
+            .send(WebhookUpdate { token, body })
+            .then(flatten)
+            .map_err(|e| frontend_error(e, FrontendErrorKind::BadToken))
     }
 
     /// When the edited event comes in from the Web UI, this handles the update logic
@@ -186,13 +546,17 @@ impl EventActor {
         &mut self,
         event: FrontendEvent,
         id: String,
-    ) -> impl Future<Item = (), Error = FrontendError> {
-        debug!("Got event: {:?}", event);
+        hosts: Option<Vec<i32>>,
+    ) -> impl Future<Item = TimeFormat, Error = FrontendError> {
+        ctx_debug!(LogContext::new("events"), "Got event: {:?}", event);
 
         let database = self.db.clone();
         let db = self.db.clone();
+        let lookup_db = self.db.clone();
+        let system_db = self.db.clone();
         let tg = self.tg.clone();
         let timer = self.timer.clone();
+        let webhooks = self.webhooks.clone();
 
         // Split the ID into the secret and ID parts
         id.rfind('=')
@@ -211,39 +575,259 @@ impl EventActor {
             .and_then(move |(eel_id, base64d)| {
                 db.send(LookupEditEventLink(eel_id))
                     .then(flatten)
-                    .and_then(move |eel| match verify_secret(&base64d, eel.secret()) {
+                    .and_then(move |eel| {
                         // Verify the secret is valid
-                        Ok(b) => if b {
+                        if verify_secret(&base64d, eel.secret()) {
                             Ok(eel)
                         } else {
                             Err(EventError::from(EventErrorKind::Frontend))
-                        },
-                        Err(e) => Err(EventError::from(e.context(EventErrorKind::Frontend))),
+                        }
                     })
                     .and_then(move |eel| {
+                        lookup_db
+                            .send(LookupEvent {
+                                event_id: eel.event_id(),
+                            })
+                            .then(flatten)
+                            .map(move |previous| (eel, previous.publish_at().is_some()))
+                    })
+                    .and_then(move |(eel, was_scheduled)| {
                         database
-                            .send(DeleteEditEventLink { id: eel.id() })
+                            .send(EditEvent {
+                                id: eel.event_id(),
+                                system_id: eel.system_id(),
+                                title: event.title().to_owned(),
+                                description: event.description().to_owned(),
+                                start_date: event.start_date(),
+                                end_date: event.end_date(),
+                                hosts,
+                                publish_at: event.publish_at(),
+                                capacity: event.capacity(),
+                                boost_threshold: event.boost_threshold(),
+                                location: event.location().map(|s| s.to_owned()),
+                                location_hint: event.location_hint().map(|s| s.to_owned()),
+                                coordinates: backend_coordinates(&event),
+                                image_url: event.image_url().map(|s| s.to_owned()),
+                                rsvp_deadline: event.rsvp_deadline(),
+                                members_only: event.members_only(),
+                                unlisted: event.unlisted(),
+                                link_id: Some(eel.id()),
+                            })
                             .then(flatten)
-                            .join(
-                                database
-                                    .send(EditEvent {
-                                        id: eel.event_id(),
+                            .map(move |event| {
+                                if !event.pending() {
+                                    // An event that was waiting to be announced hasn't been
+                                    // posted yet, so there's no announcement to update. When
+                                    // the host announces it right away, the Timer posts it.
+                                    if event.publish_at().is_none() && !was_scheduled {
+                                        tg.do_send(TgUpdateEvent(event.clone()));
+                                    }
+                                    tg.do_send(CapacityChanged(event.clone()));
+                                    webhooks.do_send(EventChanged {
+                                        action: EventAction::Updated,
+                                        event: event.clone(),
+                                    });
+                                }
+                                timer.do_send(UpdateEvent { event });
+                            })
+                            .and_then(move |_| {
+                                system_db
+                                    .send(LookupSystem {
                                         system_id: eel.system_id(),
-                                        title: event.title().to_owned(),
-                                        description: event.description().to_owned(),
-                                        start_date: event.start_date(),
-                                        end_date: event.end_date(),
-                                        hosts: vec![eel.user_id()],
                                     })
                                     .then(flatten)
-                                    .map(move |event| {
-                                        tg.do_send(TgUpdateEvent(event.clone()));
-                                        timer.do_send(UpdateEvent { event });
-                                    }),
-                            )
+                            })
+                            .map(|chat_system| chat_system.time_format())
                     })
-                    .map(|_| ())
             })
-            .map_err(|e| FrontendError::from(e.context(FrontendErrorKind::Verification)))
+            .map_err(|e| frontend_error(e, FrontendErrorKind::Verification))
+    }
+}
+
+/// Split the ID from a new event link into the ID of its `NewEventLink` and the secret
+///
+/// The ID is defined as a series of random characters, followed by an =, followed by the ID of the
+/// `NewEventLink` used to create the event. This is used to validate that someone actually used
+/// the generated link instead of guessing.
+fn split_event_link(id: &str) -> Result<(i32, String), EventError> {
+    let index = id.rfind('=').ok_or(EventError::from(EventErrorKind::Secret))?;
+
+    let (base64d, nel_id) = id.split_at(index);
+
+    nel_id
+        .trim_left_matches('=')
+        .parse::<i32>()
+        .map_err(|_| EventError::from(EventErrorKind::Secret))
+        .map(|nel_id| (nel_id, base64d.to_owned()))
+}
+
+/// Check a channel's API token, returning the channel's `ChatSystem`
+///
+/// The token has the same shape as the secrets in event links: a series of random characters,
+/// followed by an =, followed by the ID of the Chat System. Only a hash of the random characters is
+/// stored.
+fn verify_api_token(
+    db: Addr<Unsync, DbBroker>,
+    token: String,
+) -> impl Future<Item = ChatSystem, Error = FrontendError> {
+    split_token(&token)
+        .into_future()
+        .and_then(move |(system_id, base64d)| {
+            db.send(LookupSystemWithApiSecret(system_id))
+                .then(flatten)
+                .map_err(|e| frontend_error(e, FrontendErrorKind::BadToken))
+                .and_then(move |(chat_system, secret)| match secret {
+                    Some(ref secret) if verify_secret(&base64d, secret) => Ok(chat_system),
+                    Some(_) => Err(FrontendErrorKind::BadToken.into()),
+                    // Channels that haven't created a token don't have API access
+                    None => Err(FrontendErrorKind::BadToken.into()),
+                })
+        })
+}
+
+/// Check a channel's read-only API token, returning the channel's `ChatSystem`
+///
+/// Read-only tokens are kept apart from the token that adds events, so a website showing a
+/// channel's schedule can't add events to it.
+fn verify_read_token(
+    db: Addr<Unsync, DbBroker>,
+    token: String,
+) -> impl Future<Item = ChatSystem, Error = FrontendError> {
+    split_token(&token)
+        .into_future()
+        .and_then(move |(system_id, base64d)| {
+            db.send(LookupSystemWithReadSecret(system_id))
+                .then(flatten)
+                .map_err(|e| frontend_error(e, FrontendErrorKind::BadToken))
+                .and_then(move |(chat_system, secret)| match secret {
+                    Some(ref secret) if verify_secret(&base64d, secret) => Ok(chat_system),
+                    Some(_) => Err(FrontendErrorKind::BadToken.into()),
+                    // Channels that haven't created a read-only token can't be read from the API
+                    None => Err(FrontendErrorKind::BadToken.into()),
+                })
+        })
+}
+
+/// Check a channel's calendar token, returning the channel's `ChatSystem`
+///
+/// Calendar tokens are shaped like API tokens, but are stored separately so a calendar link can
+/// be shared without giving away API access.
+fn verify_calendar_token(
+    db: Addr<Unsync, DbBroker>,
+    token: String,
+) -> impl Future<Item = ChatSystem, Error = FrontendError> {
+    split_token(&token)
+        .into_future()
+        .and_then(move |(system_id, base64d)| {
+            db.send(LookupSystemWithCalendarSecret(system_id))
+                .then(flatten)
+                .map_err(|e| frontend_error(e, FrontendErrorKind::BadToken))
+                .and_then(move |(chat_system, secret)| match secret {
+                    Some(ref secret) if verify_secret(&base64d, secret) => Ok(chat_system),
+                    Some(_) => Err(FrontendErrorKind::BadToken.into()),
+                    // Channels that haven't created a calendar link don't have a calendar
+                    None => Err(FrontendErrorKind::BadToken.into()),
+                })
+        })
+}
+
+/// Split a token into the ID of its Chat System and its random characters
+fn split_token(token: &str) -> Result<(i32, String), FrontendError> {
+    token
+        .rfind('=')
+        .ok_or(FrontendError::from(FrontendErrorKind::BadToken))
+        .and_then(|index| {
+            let (base64d, system_id) = token.split_at(index);
+            let base64d = base64d.to_owned();
+            let system_id = system_id.trim_left_matches('=');
+
+            system_id
+                .parse::<i32>()
+                .map_err(|_| FrontendError::from(FrontendErrorKind::BadToken))
+                .map(|system_id| (system_id, base64d))
+        })
+}
+
+/// Describe a failed request to the web UI as `kind`, unless it failed because the database was
+/// too busy
+fn frontend_error(e: EventError, kind: FrontendErrorKind) -> FrontendError {
+    if e.kind() == EventErrorKind::Busy {
+        FrontendErrorKind::Busy.into()
+    } else {
+        e.context(kind).into()
     }
 }
+
+fn frontend_coordinates(coordinates: &Coordinates) -> (f64, f64) {
+    (coordinates.latitude(), coordinates.longitude())
+}
+
+fn backend_coordinates(event: &FrontendEvent) -> Option<Coordinates> {
+    event
+        .coordinates()
+        .and_then(|(latitude, longitude)| Coordinates::new(latitude, longitude))
+}
+
+fn frontend_host(user: &User) -> Host {
+    Host::new(user.id(), user.username().to_owned())
+}
+
+/// Build the Web UI's view of an event, with everything its hosts can see
+fn frontend_event(event: &Event) -> FrontendEvent {
+    FrontendEvent::from_parts(
+        event.title().to_owned(),
+        event.description().to_owned(),
+        event.start_date().to_owned(),
+        event.end_date().to_owned(),
+        event.publish_at().cloned(),
+        event.capacity(),
+        event.boost_threshold(),
+        event.location().map(|s| s.to_owned()),
+        event.location_hint().map(|s| s.to_owned()),
+        event.coordinates().map(frontend_coordinates),
+        event.image_url().map(|s| s.to_owned()),
+        event.rsvp_deadline().cloned(),
+        event.members_only(),
+        event.unlisted(),
+    )
+}
+
+/// Check whether an event can be shown outside of its channel's chats
+///
+/// Members-only events, unlisted events, and events that haven't been announced yet aren't public
+pub fn is_public(event: &Event) -> bool {
+    !event.members_only() && !event.unlisted() && event.publish_at().is_none() && !event.pending()
+}
+
+/// Build the public view of an event, which only includes the location that can be shown to anyone
+fn public_event(event: &Event) -> PublicEvent {
+    let hosts = event
+        .hosts()
+        .iter()
+        .map(|host| host.username().to_owned())
+        .collect();
+
+    PublicEvent::new(
+        FrontendEvent::from_parts(
+            event.title().to_owned(),
+            event.description().to_owned(),
+            event.start_date().to_owned(),
+            event.end_date().to_owned(),
+            None,
+            event.capacity(),
+            None,
+            event.public_location().map(|s| s.to_owned()),
+            None,
+            event.public_coordinates().map(frontend_coordinates),
+            event.image_url().map(|s| s.to_owned()),
+            event.rsvp_deadline().cloned(),
+            event.members_only(),
+            event.unlisted(),
+        ),
+        hosts,
+    )
+}
+
+fn api_event(event: &Event) -> ApiEvent {
+    ApiEvent::new(event.id(), event.public_id().to_owned(), public_event(event))
+}
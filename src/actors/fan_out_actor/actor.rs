@@ -0,0 +1,82 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the actor-related behaviours for the FanOutActor
+
+use std::time::{Duration, Instant};
+
+use actix::{Actor, Addr, AsyncContext, Context, Handler, Running, StreamHandler, Unsync};
+use futures::Stream;
+use tokio_timer::Interval;
+
+use super::messages::*;
+use super::{FanOutActor, CHUNK_INTERVAL};
+use logging::LogContext;
+
+impl Actor for FanOutActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        debug!("Started FanOut Actor");
+        ctx.add_stream(
+            Interval::new(Instant::now(), Duration::from_secs(CHUNK_INTERVAL))
+                .map(|_| Flush)
+                .map_err(|_| FlushError),
+        );
+    }
+}
+
+impl Handler<FanOut> for FanOutActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: FanOut, _: &mut Self::Context) -> Self::Result {
+        let log_ctx = LogContext::new("fan_out").event_id(msg.event_id);
+
+        self.fan_out(log_ctx, msg.event_id, msg.chat_ids, msg.text, msg.keep_failed);
+    }
+}
+
+impl Handler<Retry> for FanOutActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Retry, _: &mut Self::Context) -> Self::Result {
+        self.retry(msg.0);
+    }
+}
+
+impl StreamHandler<Flush, FlushError> for FanOutActor {
+    fn handle(&mut self, _: Flush, ctx: &mut Self::Context) {
+        let address: Addr<Unsync, _> = ctx.address();
+
+        self.flush(address);
+    }
+
+    fn error(&mut self, _: FlushError, _: &mut Self::Context) -> Running {
+        ctx_error!(LogContext::new("fan_out"), "Interval for Flush errored");
+        Running::Continue
+    }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        ctx.add_stream(
+            Interval::new(Instant::now(), Duration::from_secs(CHUNK_INTERVAL))
+                .map(|_| Flush)
+                .map_err(|_| FlushError),
+        );
+    }
+}
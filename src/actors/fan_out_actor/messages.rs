@@ -0,0 +1,61 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the messages that the FanOutActor can receive.
+
+use actix::Message;
+use telebot::objects::Integer;
+
+use super::Delivery;
+
+/// This asks the FanOutActor to send the same text to each of the given chats
+///
+/// If `keep_failed` is set, messages the FanOutActor gives up on are stored as failed messages,
+/// so they can be sent again later
+pub struct FanOut {
+    pub event_id: i32,
+    pub chat_ids: Vec<Integer>,
+    pub text: String,
+    pub keep_failed: bool,
+}
+
+impl Message for FanOut {
+    type Result = ();
+}
+
+/// This tells the FanOutActor to send the next chunk of queued messages
+pub struct Flush;
+
+impl Message for Flush {
+    type Result = ();
+}
+
+/// This notifies the FanOutActor that the Flush stream has errored.
+pub struct FlushError;
+
+impl Message for FlushError {
+    type Result = ();
+}
+
+/// This puts a message that failed to send back in the queue
+pub struct Retry(pub Delivery);
+
+impl Message for Retry {
+    type Result = ();
+}
@@ -0,0 +1,195 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the FanOutActor, which sends the same message to many chats at once.
+//!
+//! Systems can have a lot of linked chats, so messages are queued and sent in chunks, waiting
+//! between chunks to stay under Telegram's rate limits. Each chat keeps its own retry state, so a
+//! chat that fails to get a message is retried later without holding up the rest. Important
+//! messages that still can't be sent are stored in the `failed_messages` table, where the bot's
+//! admins can look at them and send them again.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use actix::{Addr, Unsync};
+use futures::future::Either;
+use futures::{Future, IntoFuture};
+use telebot::functions::FunctionMessage;
+use telebot::objects::Integer;
+use telebot::RcBot;
+
+use actors::db_broker::messages::StoreFailedMessage;
+use actors::db_broker::DbBroker;
+use error::EventErrorKind;
+use logging::LogContext;
+use util::{describe_error, flatten, telegram_error};
+
+mod actor;
+pub mod messages;
+
+use self::messages::Retry;
+
+/// The number of messages sent in each chunk
+const CHUNK_SIZE: usize = 20;
+
+/// The number of seconds to wait between chunks
+const CHUNK_INTERVAL: u64 = 1;
+
+/// The number of times a message is sent to a chat before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// `Delivery` is a message waiting to be sent to a single chat
+pub struct Delivery {
+    event_id: i32,
+    chat_id: Integer,
+    text: String,
+    keep_failed: bool,
+    attempt: u32,
+    send_at: Instant,
+    log_ctx: LogContext,
+}
+
+/// The FanOutActor keeps a queue of messages to send, and works through it a chunk at a time
+pub struct FanOutActor {
+    bot: RcBot,
+    db: Addr<Unsync, DbBroker>,
+    queue: VecDeque<Delivery>,
+}
+
+impl FanOutActor {
+    pub fn new(bot: RcBot, db: Addr<Unsync, DbBroker>) -> Self {
+        FanOutActor {
+            bot,
+            db,
+            queue: VecDeque::new(),
+        }
+    }
+
+    fn fan_out(
+        &mut self,
+        log_ctx: LogContext,
+        event_id: i32,
+        chat_ids: Vec<Integer>,
+        text: String,
+        keep_failed: bool,
+    ) {
+        let now = Instant::now();
+
+        for chat_id in chat_ids {
+            self.queue.push_back(Delivery {
+                event_id,
+                chat_id,
+                text: text.clone(),
+                keep_failed,
+                attempt: 1,
+                send_at: now,
+                log_ctx: log_ctx.clone().chat_id(chat_id),
+            });
+        }
+    }
+
+    fn retry(&mut self, delivery: Delivery) {
+        self.queue.push_back(delivery);
+    }
+
+    /// Send the next chunk of messages that are ready to go
+    fn flush(&mut self, address: Addr<Unsync, FanOutActor>) {
+        let now = Instant::now();
+        let mut waiting = Vec::new();
+        let mut sent = 0;
+
+        while sent < CHUNK_SIZE {
+            let delivery = match self.queue.pop_front() {
+                Some(delivery) => delivery,
+                None => break,
+            };
+
+            if delivery.send_at > now {
+                waiting.push(delivery);
+            } else {
+                self.send(delivery, address.clone());
+                sent += 1;
+            }
+        }
+
+        // Messages waiting to be retried keep their place at the front of the queue
+        for delivery in waiting.into_iter().rev() {
+            self.queue.push_front(delivery);
+        }
+    }
+
+    fn send(&self, delivery: Delivery, address: Addr<Unsync, FanOutActor>) {
+        let db = self.db.clone();
+
+        let fut = self.bot
+            .message(delivery.chat_id, delivery.text.clone())
+            .send()
+            .then(move |res| {
+                if let Err(e) = res {
+                    let e = telegram_error(e);
+
+                    // Only failures Telegram might recover from are worth retrying. A user who
+                    // blocked the bot will still have it blocked 5 seconds later
+                    if e.kind() == EventErrorKind::Telegram && delivery.attempt < MAX_ATTEMPTS {
+                        ctx_debug!(
+                            delivery.log_ctx,
+                            "Message {} failed, retrying: {:?}",
+                            delivery.attempt,
+                            e
+                        );
+
+                        // Wait 5 seconds after the first attempt, then 25 seconds after the second
+                        let wait = Duration::from_secs(5u64.pow(delivery.attempt));
+
+                        address.do_send(Retry(Delivery {
+                            attempt: delivery.attempt + 1,
+                            send_at: Instant::now() + wait,
+                            ..delivery
+                        }));
+                    } else {
+                        ctx_error!(
+                            delivery.log_ctx,
+                            "Error sending message to Telegram: {:?}",
+                            e
+                        );
+
+                        if delivery.keep_failed {
+                            let log_ctx = delivery.log_ctx.clone();
+
+                            return Either::A(
+                                db.send(StoreFailedMessage {
+                                    chat_id: delivery.chat_id,
+                                    event_id: delivery.event_id,
+                                    announcement: false,
+                                    text: delivery.text,
+                                    error: describe_error(&e),
+                                }).then(flatten)
+                                    .map_err(log_err!(log_ctx, "Error storing failed message")),
+                            );
+                        }
+                    }
+                }
+
+                Either::B(Ok(()).into_future())
+            });
+
+        self.bot.inner.handle.spawn(fut);
+    }
+}
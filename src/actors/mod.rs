@@ -17,8 +17,12 @@
  * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod alert_actor;
 pub mod db_broker;
 pub mod event_actor;
+pub mod fan_out_actor;
 pub mod telegram_actor;
 pub mod timer;
+pub mod timer_coordinator;
 pub mod users_actor;
+pub mod webhook_actor;
@@ -24,38 +24,68 @@
 //! handle incoming events like Telegram Updates, or a failed Telegram Update Stream. Other actors
 //! send this actor messages as a proxy to talk to Telegram.
 
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
 use actix::{
     Actor, Addr, Arbiter, AsyncContext, Context, Handler, Message, Running, StreamHandler,
     Supervised, Unsync,
 };
 use futures::future::Either;
 use futures::stream::{iter_ok, repeat};
-use futures::{Future, IntoFuture, Stream};
+use futures::{Future, Stream};
 use telebot::functions::*;
 use telebot::objects::Update;
 use telebot::RcBot;
+use tokio_timer::Delay;
 
 use super::messages::*;
-use super::TelegramActor;
-use error::{EventError, EventErrorKind};
+use super::{
+    register_commands, set_webhook, TelegramActor, MAX_UPDATE_RETRY_SECONDS, RESTART_ALERT,
+    STALLED_FETCHES, UPDATES_ALERT,
+};
+use actors::alert_actor::messages::{Alert, Resolved};
+use config::UpdateMode;
+use error::EventError;
+use logging::LogContext;
+use util::telegram_error;
 
 impl Actor for TelegramActor {
     type Context = Context<Self>;
 
-    fn started(&mut self, _: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         debug!("Started telegram message actor");
+        self.address = Some(ctx.address());
+
+        Arbiter::handle().spawn(
+            register_commands(&self.bot, &self.bot_admins)
+                .map_err(log_err!(LogContext::new("telegram"), "Error registering commands")),
+        );
     }
 }
 
 impl Supervised for TelegramActor {
     fn restarting(&mut self, ctx: &mut <Self as Actor>::Context) {
         debug!("Restarting telegram message actor!");
+        self.alerts.do_send(Alert {
+            key: RESTART_ALERT.to_owned(),
+            text: "The Telegram actor stopped unexpectedly and was restarted".to_owned(),
+        });
         self.bot = RcBot::new(Arbiter::handle().clone(), &self.bot.inner.key);
 
         ctx.address::<Addr<Unsync, _>>().do_send(StartStreaming);
     }
 }
 
+impl Handler<SetTimer> for TelegramActor {
+    type Result = <SetTimer as Message>::Result;
+
+    fn handle(&mut self, msg: SetTimer, _: &mut Self::Context) -> Self::Result {
+        self.timer = Some(msg.0);
+    }
+}
+
 impl Handler<NewEvent> for TelegramActor {
     type Result = <NewEvent as Message>::Result;
 
@@ -64,6 +94,14 @@ impl Handler<NewEvent> for TelegramActor {
     }
 }
 
+impl Handler<ReviewEvent> for TelegramActor {
+    type Result = <ReviewEvent as Message>::Result;
+
+    fn handle(&mut self, msg: ReviewEvent, _: &mut Self::Context) -> Self::Result {
+        self.review_event(msg.0);
+    }
+}
+
 impl Handler<UpdateEvent> for TelegramActor {
     type Result = <UpdateEvent as Message>::Result;
 
@@ -72,6 +110,22 @@ impl Handler<UpdateEvent> for TelegramActor {
     }
 }
 
+impl Handler<CapacityChanged> for TelegramActor {
+    type Result = <CapacityChanged as Message>::Result;
+
+    fn handle(&mut self, msg: CapacityChanged, _: &mut Self::Context) -> Self::Result {
+        self.fill_waitlist(msg.0);
+    }
+}
+
+impl Handler<Digest> for TelegramActor {
+    type Result = <Digest as Message>::Result;
+
+    fn handle(&mut self, msg: Digest, _: &mut Self::Context) -> Self::Result {
+        self.digest(msg.chat_system, msg.events);
+    }
+}
+
 impl Handler<EventSoon> for TelegramActor {
     type Result = <EventSoon as Message>::Result;
 
@@ -80,6 +134,38 @@ impl Handler<EventSoon> for TelegramActor {
     }
 }
 
+impl Handler<EventsSoon> for TelegramActor {
+    type Result = <EventsSoon as Message>::Result;
+
+    fn handle(&mut self, msg: EventsSoon, _: &mut Self::Context) -> Self::Result {
+        self.events_soon(msg.0);
+    }
+}
+
+impl Handler<Boost> for TelegramActor {
+    type Result = <Boost as Message>::Result;
+
+    fn handle(&mut self, msg: Boost, _: &mut Self::Context) -> Self::Result {
+        self.boost(msg.0);
+    }
+}
+
+impl Handler<RemindAttendees> for TelegramActor {
+    type Result = <RemindAttendees as Message>::Result;
+
+    fn handle(&mut self, msg: RemindAttendees, _: &mut Self::Context) -> Self::Result {
+        self.remind_attendees(msg.event, msg.user_ids);
+    }
+}
+
+impl Handler<RsvpsClosed> for TelegramActor {
+    type Result = <RsvpsClosed as Message>::Result;
+
+    fn handle(&mut self, msg: RsvpsClosed, _: &mut Self::Context) -> Self::Result {
+        self.rsvps_closed(msg.0);
+    }
+}
+
 impl Handler<EventStarted> for TelegramActor {
     type Result = <EventStarted as Message>::Result;
 
@@ -105,6 +191,23 @@ impl Handler<TgUpdate> for TelegramActor {
     }
 }
 
+impl Handler<Localized> for TelegramActor {
+    type Result = <Localized as Message>::Result;
+
+    fn handle(&mut self, msg: Localized, _: &mut Self::Context) -> Self::Result {
+        self.localized_update(msg.update, msg.language);
+    }
+}
+
+impl Handler<WebhookUpdate> for TelegramActor {
+    type Result = <WebhookUpdate as Message>::Result;
+
+    fn handle(&mut self, msg: WebhookUpdate, _: &mut Self::Context) -> Self::Result {
+        debug!("Handling webhook update");
+        self.webhook_update(&msg.token, &msg.body)
+    }
+}
+
 impl StreamHandler<TgUpdate, EventError> for TelegramActor {
     fn handle(&mut self, msg: TgUpdate, _: &mut Self::Context) {
         debug!("Handling update");
@@ -112,7 +215,7 @@ impl StreamHandler<TgUpdate, EventError> for TelegramActor {
     }
 
     fn error(&mut self, err: EventError, _: &mut Self::Context) -> Running {
-        error!("Error {:?}", err);
+        ctx_error!(LogContext::new("telegram"), "Error in update stream: {:?}", err);
         // don't stop on stream error
         Running::Continue
     }
@@ -127,34 +230,106 @@ impl Handler<StartStreaming> for TelegramActor {
     type Result = <StartStreaming as Message>::Result;
 
     fn handle(&mut self, _: StartStreaming, ctx: &mut Self::Context) -> Self::Result {
+        // Any polling stream that's already running stops before it asks for more updates
+        let generation = self.update_generation.get() + 1;
+        self.update_generation.set(generation);
+
+        if self.update_mode == UpdateMode::Webhook {
+            self.register_webhook();
+            return;
+        }
+
+        self.update_secret = None;
+
         let addr: Addr<Unsync, _> = ctx.address();
+        let log_ctx = LogContext::new("telegram");
+        let update_ctx = log_ctx.clone();
+        let bot = self.bot.clone();
+        let current = self.update_generation.clone();
+        let restart_current = self.update_generation.clone();
+        let restart_addr = addr.clone();
+        let alerts = self.alerts.clone();
+        // Requests for updates that failed in a row
+        let mut failures = 0;
 
-        Arbiter::handle().spawn(
-            bot_stream(self.bot.clone())
+        // Telegram won't give out updates while a webhook is set
+        Arbiter::handle().spawn(set_webhook(&self.bot, String::new()).then(move |_| {
+            bot_stream(bot, current, generation)
                 .then(move |res| match res {
-                    Ok((bot, update)) => Either::A(addr.send(TgUpdate { bot, update }).map(|_| ())),
+                    Ok((bot, update)) => {
+                        if failures >= STALLED_FETCHES {
+                            alerts.do_send(Resolved {
+                                key: UPDATES_ALERT.to_owned(),
+                                text: "Getting updates from Telegram".to_owned(),
+                            });
+                        }
+                        failures = 0;
+
+                        Either::A(addr.send(TgUpdate { bot, update }).map(|_| ()))
+                    }
                     Err(e) => {
-                        error!("Error: {:?}", e);
-                        Either::B(Ok(()).into_future())
+                        ctx_error!(update_ctx, "Error fetching updates: {:?}", e);
+                        failures += 1;
+
+                        if failures >= STALLED_FETCHES {
+                            alerts.do_send(Alert {
+                                key: UPDATES_ALERT.to_owned(),
+                                text: format!(
+                                    "Couldn't get updates from Telegram {} times in a row: {}",
+                                    failures, e
+                                ),
+                            });
+                        }
+
+                        // Wait before asking again, so an outage isn't met with a flood of
+                        // requests
+                        Either::B(
+                            Delay::new(Instant::now() + update_retry_delay(failures))
+                                .then(|_| Ok(())),
+                        )
                     }
                 })
-                .map_err(|e| error!("Error: {:?}", e))
-                .for_each(|_| Ok(())),
-        )
+                .map_err(log_err!(log_ctx, "Error delivering update"))
+                .for_each(|_| Ok(()))
+                .then(move |_| {
+                    // The stream stops if an update can't be handed to the actor, so it's started
+                    // again unless it's been replaced
+                    if restart_current.get() == generation {
+                        restart_addr.do_send(StartStreaming);
+                    }
+
+                    Ok(())
+                })
+        }))
     }
 }
 
+/// How long to wait before asking for updates again, after `failures` requests failed in a row
+///
+/// The wait starts at a second and doubles after each failure, up to a minute
+fn update_retry_delay(failures: u32) -> Duration {
+    Duration::from_secs(MAX_UPDATE_RETRY_SECONDS.min(1 << failures.saturating_sub(1).min(6)))
+}
+
 /// define a static stream for an `RcBot`, in order to use this as a future spawned in the actor's
 /// context.
-fn bot_stream(bot: RcBot) -> impl Stream<Item = (RcBot, Update), Error = EventError> {
+///
+/// The stream ends once `current` no longer matches `generation`, which happens when updates are
+/// restarted or switched to a webhook.
+fn bot_stream(
+    bot: RcBot,
+    current: Rc<Cell<usize>>,
+    generation: usize,
+) -> impl Stream<Item = (RcBot, Update), Error = EventError> {
     repeat::<RcBot, EventError>(bot)
+        .take_while(move |_| Ok(current.get() == generation))
         .and_then(move |bot| {
             debug!("Querying for updates");
             bot.get_updates()
                 .offset(bot.inner.last_id.get())
                 .timeout(bot.inner.timeout.get() as i64)
                 .send()
-                .map_err(|e| e.context(EventErrorKind::Telegram).into())
+                .map_err(telegram_error)
         })
         .map(|(bot, updates)| iter_ok(updates.0).map(move |update| (bot.clone(), update)))
         .flatten()
@@ -166,7 +341,7 @@ fn bot_stream(bot: RcBot) -> impl Stream<Item = (RcBot, Update), Error = EventEr
             Ok((bot, update))
         })
         .map_err(|e| {
-            error!("Error in bot stream: {:?}", e);
+            ctx_error!(LogContext::new("telegram"), "Error in bot stream: {:?}", e);
             e
         })
 }
@@ -0,0 +1,132 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module caches which channel each chat system announces events in.
+//!
+//! Reminders and updates only need a system's events channel, which never changes once the
+//! system exists, so it's kept here instead of being looked up for every message.
+
+use std::collections::{HashMap, VecDeque};
+
+use telebot::objects::Integer;
+
+/// How many systems' channels are kept before the least recently used one is dropped
+pub const CHANNEL_CACHE_SIZE: usize = 256;
+
+/// A least-recently-used cache from system ids to events channels
+#[derive(Clone, Debug)]
+pub struct ChannelCache {
+    capacity: usize,
+    channels: HashMap<i32, Integer>,
+    // System ids, from least to most recently used
+    recent: VecDeque<i32>,
+}
+
+impl ChannelCache {
+    pub fn new(capacity: usize) -> Self {
+        ChannelCache {
+            capacity,
+            channels: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Get the system's events channel, marking it as recently used
+    pub fn get(&mut self, system_id: i32) -> Option<Integer> {
+        let channel_id = self.channels.get(&system_id).cloned();
+
+        if channel_id.is_some() {
+            self.touch(system_id);
+        }
+
+        channel_id
+    }
+
+    /// Remember the system's events channel, dropping the least recently used system if the
+    /// cache is full
+    pub fn insert(&mut self, system_id: i32, channel_id: Integer) {
+        if self.channels.insert(system_id, channel_id).is_some() {
+            self.touch(system_id);
+            return;
+        }
+
+        self.recent.push_back(system_id);
+
+        if self.recent.len() > self.capacity {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.channels.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forget any system announcing in the given channel
+    pub fn remove_channel(&mut self, channel_id: Integer) {
+        let channels = &mut self.channels;
+
+        self.recent.retain(|system_id| match channels.get(system_id) {
+            Some(id) if *id == channel_id => {
+                channels.remove(system_id);
+                false
+            }
+            _ => true,
+        });
+    }
+
+    fn touch(&mut self, system_id: i32) {
+        self.recent.retain(|id| *id != system_id);
+        self.recent.push_back(system_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChannelCache;
+
+    #[test]
+    fn drops_least_recently_used() {
+        let mut cache = ChannelCache::new(2);
+
+        cache.insert(1, -100);
+        cache.insert(2, -200);
+        assert_eq!(cache.get(1), Some(-100));
+
+        cache.insert(3, -300);
+
+        assert_eq!(cache.get(1), Some(-100));
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(3), Some(-300));
+    }
+
+    #[test]
+    fn removes_by_channel() {
+        let mut cache = ChannelCache::new(2);
+
+        cache.insert(1, -100);
+        cache.insert(2, -200);
+        cache.remove_channel(-100);
+
+        assert_eq!(cache.get(1), None);
+        assert_eq!(cache.get(2), Some(-200));
+
+        cache.insert(3, -300);
+
+        assert_eq!(cache.get(2), Some(-200));
+        assert_eq!(cache.get(3), Some(-300));
+    }
+}
@@ -0,0 +1,432 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module lists the bot's commands.
+//!
+//! The `/help` message and the command suggestions registered with Telegram are both built from
+//! `COMMANDS`, so they can't drift apart. Descriptions come from the locale catalog, so both are
+//! offered in every language the bot speaks.
+
+use event_web::locale::{Language, Text};
+
+/// Where a command can be used
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scope {
+    /// Group chats linked to an event channel
+    Group,
+    /// Private chats with the bot
+    Private,
+    /// Event channels, or group chats for `/id`, used by the chat's admins
+    Admin,
+    /// Private chats with the people running the bot, listed in `BOT_ADMINS`
+    ///
+    /// These commands aren't listed in `/help`
+    BotAdmin,
+}
+
+/// A command the bot understands
+#[derive(Clone, Copy, Debug)]
+pub struct Command {
+    pub name: &'static str,
+    pub description: Text,
+    pub usage: Option<&'static str>,
+    pub scope: Scope,
+}
+
+impl Command {
+    /// The command's line in the `/help` message
+    fn help_line(&self, language: Language) -> String {
+        let description = language.text(self.description);
+
+        match self.usage {
+            Some(usage) => format!(
+                "/{} - {} ({}: {})",
+                self.name,
+                description,
+                language.text(Text::HelpUsage),
+                usage
+            ),
+            None => format!("/{} - {}", self.name, description),
+        }
+    }
+}
+
+/// Every command the bot understands, in the order they're listed in `/help`
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "events",
+        description: Text::HelpEvents,
+        usage: None,
+        scope: Scope::Group,
+    },
+    Command {
+        name: "pinevents",
+        description: Text::HelpPinEvents,
+        usage: None,
+        scope: Scope::Group,
+    },
+    Command {
+        name: "next",
+        description: Text::HelpNext,
+        usage: None,
+        scope: Scope::Group,
+    },
+    Command {
+        name: "today",
+        description: Text::HelpToday,
+        usage: None,
+        scope: Scope::Group,
+    },
+    Command {
+        name: "search",
+        description: Text::HelpSearch,
+        usage: Some("/search [terms]"),
+        scope: Scope::Group,
+    },
+    Command {
+        name: "new",
+        description: Text::HelpNew,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "quick",
+        description: Text::HelpQuick,
+        usage: Some("/quick Pizza night tomorrow 7pm-9pm"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "edit",
+        description: Text::HelpEdit,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "cancel",
+        description: Text::HelpCancel,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "transfer",
+        description: Text::HelpTransfer,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "delete",
+        description: Text::HelpDelete,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "myevents",
+        description: Text::HelpMyEvents,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "announce",
+        description: Text::HelpAnnounce,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "notifications",
+        description: Text::HelpNotifications,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "mute",
+        description: Text::HelpMute,
+        usage: Some("/mute [reminders|broadcasts|hosting|waitlist]"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "unmute",
+        description: Text::HelpUnmute,
+        usage: Some("/unmute [reminders|broadcasts|hosting|waitlist]"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "language",
+        description: Text::HelpLanguage,
+        usage: Some("/language [en|es|de]"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "timeformat",
+        description: Text::HelpTimeFormat,
+        usage: Some("/timeformat [12|24]"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "exportme",
+        description: Text::HelpExportMe,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "forgetme",
+        description: Text::HelpForgetMe,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "search",
+        description: Text::HelpSearchPrivate,
+        usage: Some("/search [terms]"),
+        scope: Scope::Private,
+    },
+    Command {
+        name: "help",
+        description: Text::HelpHelp,
+        usage: None,
+        scope: Scope::Private,
+    },
+    Command {
+        name: "init",
+        description: Text::HelpInit,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "link",
+        description: Text::HelpLink,
+        usage: Some("/link [chat_id]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "protect",
+        description: Text::HelpProtect,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "moderate",
+        description: Text::HelpModerate,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "language",
+        description: Text::HelpChannelLanguage,
+        usage: Some("/language [en|es|de]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "timeformat",
+        description: Text::HelpChannelTimeFormat,
+        usage: Some("/timeformat [12|24]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "ban",
+        description: Text::HelpBan,
+        usage: Some("/ban [@username|user id]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "unban",
+        description: Text::HelpUnban,
+        usage: Some("/unban [@username|user id]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "digest",
+        description: Text::HelpDigest,
+        usage: Some("/digest [day|off]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "pindigest",
+        description: Text::HelpPinDigest,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "pinannouncements",
+        description: Text::HelpPinAnnouncements,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "directory",
+        description: Text::HelpDirectory,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "setgreeting",
+        description: Text::HelpSetGreeting,
+        usage: Some("/setgreeting [text|off]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "template",
+        description: Text::HelpTemplate,
+        usage: Some("/template [announcement|reminder|ended] [text|default]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "features",
+        description: Text::HelpFeatures,
+        usage: Some("/features [rsvp|digest|public_page] [on|off]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "boost",
+        description: Text::HelpBoost,
+        usage: Some("/boost [number|off]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "stats",
+        description: Text::HelpStats,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "token",
+        description: Text::HelpToken,
+        usage: Some("/token [read]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "calendar",
+        description: Text::HelpCalendar,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "webhook",
+        description: Text::HelpWebhook,
+        usage: Some("/webhook [add|remove] [url] or /webhook [secret|rotate]"),
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "id",
+        description: Text::HelpId,
+        usage: None,
+        scope: Scope::Admin,
+    },
+    Command {
+        name: "updates",
+        description: Text::HelpUpdates,
+        usage: Some("/updates [polling|webhook]"),
+        scope: Scope::BotAdmin,
+    },
+    Command {
+        name: "pool",
+        description: Text::HelpPool,
+        usage: None,
+        scope: Scope::BotAdmin,
+    },
+    Command {
+        name: "snooze",
+        description: Text::HelpSnooze,
+        usage: Some("/snooze [minutes|off]"),
+        scope: Scope::BotAdmin,
+    },
+    Command {
+        name: "failed",
+        description: Text::HelpFailed,
+        usage: Some("/failed [replay id]"),
+        scope: Scope::BotAdmin,
+    },
+];
+
+/// Get the commands that can be used in the given scope
+pub fn in_scope(scope: Scope) -> impl Iterator<Item = &'static Command> {
+    COMMANDS
+        .iter()
+        .filter(move |command| command.scope == scope)
+}
+
+/// Build the `/help` message in the given language
+pub fn help_text(language: Language) -> String {
+    let lines = |scope| {
+        in_scope(scope)
+            .map(|command| command.help_line(language))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    language.fill(
+        Text::Help,
+        &[
+            &lines(Scope::Group),
+            &lines(Scope::Private),
+            &lines(Scope::Admin),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use event_web::locale::LANGUAGES;
+
+    use super::{help_text, in_scope, Scope, COMMANDS};
+
+    #[test]
+    fn help_lists_every_public_command() {
+        for language in LANGUAGES {
+            let help = help_text(*language);
+
+            for command in COMMANDS {
+                let line = format!("/{} - {}", command.name, language.text(command.description));
+
+                assert_eq!(help.contains(&line), command.scope != Scope::BotAdmin);
+            }
+        }
+    }
+
+    #[test]
+    fn commands_fit_telegram_limits() {
+        for command in COMMANDS {
+            assert!(command.name.len() <= 32);
+            assert!(command
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'));
+
+            for language in LANGUAGES {
+                let description = language.text(command.description);
+
+                assert!(description.chars().count() >= 3 && description.chars().count() <= 256);
+            }
+        }
+    }
+
+    #[test]
+    fn scopes_have_no_duplicates() {
+        for scope in &[Scope::Group, Scope::Private, Scope::Admin, Scope::BotAdmin] {
+            let mut names = in_scope(*scope)
+                .map(|command| command.name)
+                .collect::<Vec<_>>();
+            let count = names.len();
+
+            names.sort();
+            names.dedup();
+
+            assert_eq!(names.len(), count);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module builds the JSON document users get from the `/exportme` command.
+//!
+//! Dates are RFC 3339 timestamps in the bot's timezone.
+
+use chrono_tz::Tz;
+use serde_json;
+use telebot::objects::Integer;
+
+use models::user::Export;
+
+/// The name the document is sent with
+pub const FILE_NAME: &str = "telegram-event-bot-export.json";
+
+#[derive(Serialize)]
+struct Document<'a> {
+    user: ExportedUser<'a>,
+    chats: Vec<Integer>,
+    hosted_events: Vec<HostedEvent<'a>>,
+    rsvps: Vec<ExportedRsvp<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportedUser<'a> {
+    telegram_id: Integer,
+    username: &'a str,
+}
+
+/// An event the user hosts. Hosts can see everything about their events, so the full location is
+/// included.
+#[derive(Serialize)]
+struct HostedEvent<'a> {
+    id: i32,
+    title: &'a str,
+    description: &'a str,
+    start_date: String,
+    end_date: String,
+    location: Option<&'a str>,
+    cancelled: bool,
+}
+
+#[derive(Serialize)]
+struct ExportedRsvp<'a> {
+    event_id: i32,
+    title: &'a str,
+    start_date: String,
+    waitlisted: bool,
+}
+
+/// Render everything stored about a user as pretty-printed JSON
+pub fn render(export: &Export, timezone: Tz) -> String {
+    let document = Document {
+        user: ExportedUser {
+            telegram_id: export.user().user_id(),
+            username: export.user().username(),
+        },
+        chats: export.chats().iter().map(|chat| chat.chat_id()).collect(),
+        hosted_events: export
+            .hosted()
+            .iter()
+            .map(|event| HostedEvent {
+                id: event.id(),
+                title: event.title(),
+                description: event.description(),
+                start_date: event.start_date().with_timezone(&timezone).to_rfc3339(),
+                end_date: event.end_date().with_timezone(&timezone).to_rfc3339(),
+                location: event.location(),
+                cancelled: event.cancelled(),
+            })
+            .collect(),
+        rsvps: export
+            .rsvps()
+            .iter()
+            .map(|rsvp| ExportedRsvp {
+                event_id: rsvp.event_id(),
+                title: rsvp.title(),
+                start_date: rsvp.start_date().with_timezone(&timezone).to_rfc3339(),
+                waitlisted: rsvp.waitlisted(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&document).unwrap()
+}
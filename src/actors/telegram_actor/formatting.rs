@@ -0,0 +1,1014 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module formats events and dates for messages sent to Telegram.
+//!
+//! Every message that describes an event is built from an `EventCard`, so announcements, updates,
+//! and event listings all look the same. Cards can also be formatted as HTML, for messages sent
+//! with Telegram's HTML parse mode, and in any of the languages in `event_web::locale`.
+
+use std::fmt::{self, Debug};
+
+use chrono::offset::Utc;
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use chrono_tz::Tz;
+use event_web::locale::{Language, Text, TimeFormat};
+
+use models::chat_system::ChatSystem;
+use models::event::{Coordinates, Event};
+use models::user::User;
+
+/// The number of units event cards show durations with, like `1 Day 23 Hours`
+///
+/// This is fixed rather than configurable, since two units are enough to keep an hour from being
+/// hidden without turning durations into a sentence
+pub const DURATION_PRECISION: usize = 2;
+
+/// `EventCard` builds the text describing a single event
+///
+/// Only the title, start date, and duration are always included. The other lines are added with
+/// the builder methods, and are left out of the card when they're not set. Dates are shown in the
+/// bot's configured timezone, and the end date is shown too when the event doesn't end on the day
+/// it starts. Cards are in English with 24-hour times unless they're changed with `language` and
+/// `time_format`.
+#[derive(Clone, Debug)]
+pub struct EventCard<'a> {
+    heading: Option<&'a str>,
+    title: &'a str,
+    channel: Option<&'a str>,
+    start_date: &'a DateTime<Tz>,
+    end_date: &'a DateTime<Tz>,
+    timezone: Tz,
+    location: Option<(&'a str, bool)>,
+    coordinates: Option<&'a Coordinates>,
+    description: Option<&'a str>,
+    hosts: Option<&'a [User]>,
+    capacity: Option<i32>,
+    members_only: bool,
+    link: Option<&'a str>,
+    now: Option<DateTime<Tz>>,
+    html: bool,
+    language: Language,
+    time_format: TimeFormat,
+}
+
+impl<'a> EventCard<'a> {
+    /// Start a card with only the title and dates
+    pub fn new(
+        title: &'a str,
+        start_date: &'a DateTime<Tz>,
+        end_date: &'a DateTime<Tz>,
+        timezone: Tz,
+    ) -> Self {
+        EventCard {
+            heading: None,
+            title,
+            channel: None,
+            start_date,
+            end_date,
+            timezone,
+            location: None,
+            coordinates: None,
+            description: None,
+            hosts: None,
+            capacity: None,
+            members_only: false,
+            link: None,
+            now: None,
+            html: false,
+            language: Language::default(),
+            time_format: TimeFormat::default(),
+        }
+    }
+
+    /// Start a card with an event's title, dates, public location, and description
+    pub fn from_event(event: &'a Event, timezone: Tz) -> Self {
+        let card = EventCard::new(event.title(), event.start_date(), event.end_date(), timezone)
+            .description(event.description());
+
+        let card = match event.public_location() {
+            Some(location) => card.location(location, event.location_hidden()),
+            None => card,
+        };
+
+        match event.public_coordinates() {
+            Some(coordinates) => card.coordinates(coordinates),
+            None => card,
+        }
+    }
+
+    /// Add a line above the title, like `New Event!`
+    pub fn heading(mut self, heading: &'a str) -> Self {
+        self.heading = Some(heading);
+        self
+    }
+
+    /// Add the name of the channel the event belongs to
+    pub fn channel(mut self, channel: &'a str) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Add the event's location. A `hidden` location is only a hint, and the card says the exact
+    /// location comes with an RSVP.
+    pub fn location(mut self, location: &'a str, hidden: bool) -> Self {
+        self.location = Some((location, hidden));
+        self
+    }
+
+    /// Add the coordinates of the event's location, which are shown as a map link
+    pub fn coordinates(mut self, coordinates: &'a Coordinates) -> Self {
+        self.coordinates = Some(coordinates);
+        self
+    }
+
+    /// Add the event's description
+    pub fn description(mut self, description: &'a str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Add the event's hosts
+    pub fn hosts(mut self, hosts: &'a [User]) -> Self {
+        self.hosts = Some(hosts);
+        self
+    }
+
+    /// Add the event's maximum number of attendees, if it has one
+    pub fn capacity(mut self, capacity: Option<i32>) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Mark the event as members only
+    pub fn members_only(mut self, members_only: bool) -> Self {
+        self.members_only = members_only;
+        self
+    }
+
+    /// Add a link to the event's public page
+    pub fn link(mut self, link: &'a str) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// Describe the event as of the given time. If it's already started, the card says it's
+    /// happening now and when it ends, instead of when it started. Otherwise, how long it is until
+    /// the event starts follows the start date.
+    pub fn as_of(mut self, now: DateTime<Tz>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Format the card as HTML. The title is bold, everything the hosts wrote is escaped, and
+    /// Markdown-style links in the description become real links.
+    pub fn html(mut self) -> Self {
+        self.html = true;
+        self
+    }
+
+    /// Set the language of the card's labels and dates
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Set whether the card's times have 12 or 24 hours
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Get a label in the card's language
+    fn label(&self, text: Text) -> &'static str {
+        self.language.text(text)
+    }
+
+    /// Escape text the hosts wrote, if the card is HTML
+    fn text(&self, text: &str) -> String {
+        if self.html {
+            escape_html(text)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+impl<'a> fmt::Display for EventCard<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(heading) = self.heading {
+            writeln!(f, "{}", self.text(heading))?;
+        }
+
+        if self.html {
+            write!(f, "<b>{}</b>", escape_html(self.title))?;
+        } else {
+            write!(f, "{}", self.title)?;
+        }
+
+        if let Some(channel) = self.channel {
+            write!(f, "\n{}: {}", self.label(Text::Channel), self.text(channel))?;
+        }
+
+        let start_date = self.start_date.with_timezone(&self.timezone);
+        let end_date = self.end_date.with_timezone(&self.timezone);
+
+        match self.now.as_ref().map(|now| now.with_timezone(&self.timezone)) {
+            Some(now) if start_date <= now && now < end_date => write!(
+                f,
+                "\n{}: {} {}",
+                self.label(Text::When),
+                self.label(Text::HappeningNow),
+                format_end(&now, &end_date, self.language, self.time_format)
+            )?,
+            _ => {
+                write!(
+                    f,
+                    "\n{}: {}",
+                    self.label(Text::When),
+                    format_localized_date(start_date.clone(), self.language, self.time_format)
+                )?;
+
+                if let Some(ref now) = self.now {
+                    if *now < start_date {
+                        write!(f, " ({})", format_starts_in(now, &start_date, self.language))?;
+                    }
+                }
+
+                if start_date.date() != end_date.date() {
+                    write!(
+                        f,
+                        "\n{}: {}",
+                        self.label(Text::Ends),
+                        format_localized_date(end_date, self.language, self.time_format)
+                    )?;
+                }
+            }
+        }
+
+        write!(
+            f,
+            "\n{}: {}",
+            self.label(Text::Duration),
+            format_duration_between(
+                self.start_date,
+                self.end_date,
+                DURATION_PRECISION,
+                self.language,
+            )
+        )?;
+
+        match self.location {
+            Some((location, true)) => write!(
+                f,
+                "\n{}: {} ({})",
+                self.label(Text::Where),
+                self.text(location),
+                self.label(Text::HiddenLocation)
+            )?,
+            Some((location, false)) => {
+                write!(f, "\n{}: {}", self.label(Text::Where), self.text(location))?
+            }
+            None => (),
+        }
+
+        if let Some(coordinates) = self.coordinates {
+            write!(f, "\n{}: {}", self.label(Text::Map), self.text(&map_url(coordinates)))?;
+        }
+
+        if let Some(description) = self.description {
+            if self.html {
+                write!(
+                    f,
+                    "\n{}: {}",
+                    self.label(Text::Description),
+                    description_html(description)
+                )?;
+            } else {
+                write!(f, "\n{}: {}", self.label(Text::Description), description)?;
+            }
+        }
+
+        // Events pushed through the API have no hosts
+        if let Some(hosts) = self.hosts.filter(|hosts| !hosts.is_empty()) {
+            let hosts = hosts
+                .iter()
+                .map(|host| format!("@{}", host.username()))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            write!(f, "\n{}: {}", self.label(Text::Hosts), self.text(&hosts))?;
+        }
+
+        if let Some(capacity) = self.capacity {
+            write!(f, "\n{}: {}", self.label(Text::Capacity), capacity)?;
+        }
+
+        if self.members_only {
+            write!(f, "\n{}", self.label(Text::MembersOnly))?;
+        }
+
+        if let Some(link) = self.link {
+            write!(f, "\n{}: {}", self.label(Text::MoreInfo), self.text(link))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a list of events, one after another, as HTML
+///
+/// Events that have already started are marked as happening now.
+pub fn format_events(
+    events: &[Event],
+    timezone: Tz,
+    language: Language,
+    time_format: TimeFormat,
+) -> String {
+    let now = Utc::now().with_timezone(&timezone);
+
+    events
+        .iter()
+        .map(|event| {
+            EventCard::from_event(event, timezone)
+                .heading(list_heading(event, language))
+                .hosts(event.hosts())
+                .as_of(now.clone())
+                .language(language)
+                .time_format(time_format)
+                .html()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The heading for an event in a list of events, which shows whether it's been cancelled
+pub fn list_heading(event: &Event, language: Language) -> &'static str {
+    if event.cancelled() {
+        language.text(Text::CancelledHeading)
+    } else {
+        language.text(Text::EventHeading)
+    }
+}
+
+/// Format the greeting pinned in a Chat System's channel
+///
+/// The admins' greeting comes first, followed by how to use the bot and the channel's settings, so
+/// the greeting has to be refreshed when those settings change. Everything after the admins'
+/// greeting is in the channel's language.
+pub fn format_greeting(chat_system: &ChatSystem) -> String {
+    let language = chat_system.language();
+    let mut text = chat_system.greeting().unwrap_or_default().to_owned();
+
+    text.push_str(language.text(Text::HowItWorks));
+
+    match chat_system.digest_day() {
+        Some(day) if chat_system.pin_digest() => text.push_str(&language.fill(
+            Text::GreetingDigestPinned,
+            &[&language.weekday(day)],
+        )),
+        Some(day) => text.push_str(&language.fill(Text::GreetingDigest, &[&language.weekday(day)])),
+        None => (),
+    }
+
+    if chat_system.pin_announcements() {
+        text.push_str(language.text(Text::GreetingPinned));
+    }
+
+    text
+}
+
+/// Format an event's exact location for users who are going to it
+///
+/// This is empty unless the location is hidden from everyone else
+pub fn format_private_location(event: &Event, language: Language) -> String {
+    if event.location_hidden() {
+        let location = event
+            .location()
+            .map(|location| format!("\n{}: {}", language.text(Text::Where), location))
+            .unwrap_or_default();
+
+        let map = event
+            .coordinates()
+            .map(|coordinates| {
+                format!("\n{}: {}", language.text(Text::Map), map_url(coordinates))
+            })
+            .unwrap_or_default();
+
+        location + &map
+    } else {
+        "".to_owned()
+    }
+}
+
+/// Escape text for messages sent with Telegram's HTML parse mode
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape a description for the HTML parse mode, turning Markdown-style links, like
+/// `[the menu](https://example.com/menu)`, into real links
+///
+/// Anything that isn't a link to a web page is left as it was written.
+fn description_html(description: &str) -> String {
+    let mut html = String::new();
+    let mut rest = description;
+
+    while let Some(start) = rest.find('[') {
+        let link = rest[start + 1..].find("](").and_then(|text_len| {
+            let text = &rest[start + 1..start + 1 + text_len];
+            let url_start = start + 1 + text_len + 2;
+
+            rest[url_start..].find(')').map(|url_len| {
+                let url = &rest[url_start..url_start + url_len];
+
+                (text, url, url_start + url_len + 1)
+            })
+        });
+
+        match link {
+            Some((text, url, end)) if !text.contains('[') && is_web_url(url) => {
+                html.push_str(&escape_html(&rest[..start]));
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    escape_html(text)
+                ));
+                rest = &rest[end..];
+            }
+            _ => {
+                html.push_str(&escape_html(&rest[..start + 1]));
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+
+    html.push_str(&escape_html(rest));
+    html
+}
+
+fn is_web_url(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.contains(char::is_whitespace)
+}
+
+/// Build a link to the coordinates on OpenStreetMap
+pub fn map_url(coordinates: &Coordinates) -> String {
+    format!(
+        "https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=17/{lat}/{lon}",
+        lat = coordinates.latitude(),
+        lon = coordinates.longitude()
+    )
+}
+
+/// Ask a host to confirm deleting an event, listing the RSVPs that would be lost
+///
+/// Hosts are pointed at cancelling instead unless the event's already been cancelled
+pub fn format_delete_confirmation(
+    title: &str,
+    cancelled: bool,
+    going: &[User],
+    waitlisted: &[User],
+    language: Language,
+) -> String {
+    let mut text = language.fill(Text::ConfirmDelete, &[&title]);
+
+    if going.is_empty() && waitlisted.is_empty() {
+        text.push_str(language.text(Text::NoRsvps));
+    } else {
+        if !going.is_empty() {
+            let count = pluralize(
+                going.len() as i64,
+                language.text(Text::PersonIs),
+                language.text(Text::PeopleAre),
+            );
+
+            text.push_str(&language.fill(Text::RsvpsGoing, &[&count, &format_usernames(going)]));
+        }
+
+        if !waitlisted.is_empty() {
+            text.push_str(&language.fill(
+                Text::RsvpsWaitlisted,
+                &[&waitlisted.len(), &format_usernames(waitlisted)],
+            ));
+        }
+
+        text.push_str(language.text(Text::RsvpsDeleted));
+    }
+
+    if !cancelled {
+        text.push_str(language.text(Text::CancelInsteadHint));
+    }
+
+    text
+}
+
+/// List users by their usernames, like `@asonix, @someone`
+pub fn format_usernames(users: &[User]) -> String {
+    users
+        .iter()
+        .map(|user| format!("@{}", user.username()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describe when an ongoing event ends, like `at 21:00` if it ends today, `Sunday` if it ends in
+/// the next week, or the full date otherwise
+fn format_end(
+    now: &DateTime<Tz>,
+    end_date: &DateTime<Tz>,
+    language: Language,
+    time_format: TimeFormat,
+) -> String {
+    let days = end_date
+        .date()
+        .signed_duration_since(now.date())
+        .num_days();
+
+    if days == 0 {
+        format!(
+            "{} {}",
+            language.text(Text::At),
+            time_format.format_time(end_date.hour(), end_date.minute())
+        )
+    } else if days < 7 {
+        language.weekday(end_date.weekday()).to_owned()
+    } else {
+        format_localized_date(end_date.clone(), language, time_format)
+    }
+}
+
+/// Describe how long it is until an event starts, like `starts in 3 Hours`
+///
+/// Only the largest unit is shown, since the time is out of date as soon as it's sent anyway
+fn format_starts_in(now: &DateTime<Tz>, start_date: &DateTime<Tz>, language: Language) -> String {
+    if start_date.signed_duration_since(now.clone()).num_minutes() < 1 {
+        return language.text(Text::StartingNow).to_owned();
+    }
+
+    format!(
+        "{} {}",
+        language.text(Text::StartsIn),
+        format_duration_between(now, start_date, 1, language)
+    )
+}
+
+/// Format the time between two dates in the given language, like `1 Day 23 Hours`
+///
+/// `precision` is the number of units to show, starting from the largest one that isn't zero. A
+/// precision of 2 shows weeks and days, or days and hours, and so on. Units that are zero are
+/// left out, so `1 Week 4 Hours` is shown as `1 Week`.
+pub fn format_duration_between(
+    start_date: &DateTime<Tz>,
+    end_date: &DateTime<Tz>,
+    precision: usize,
+    language: Language,
+) -> String {
+    let minutes = end_date.signed_duration_since(start_date.clone()).num_minutes();
+
+    let units = [
+        (60 * 24 * 7, Text::Week, Text::Weeks),
+        (60 * 24, Text::Day, Text::Days),
+        (60, Text::Hour, Text::Hours),
+        (1, Text::Minute, Text::Minutes),
+    ];
+
+    let (parts, _) = units
+        .iter()
+        .skip_while(|&&(size, _, _)| minutes < size)
+        .take(precision)
+        .fold(
+            (Vec::new(), minutes),
+            |(mut parts, remaining), &(size, singular, plural)| {
+                if remaining >= size {
+                    parts.push(pluralize(
+                        remaining / size,
+                        language.text(singular),
+                        language.text(plural),
+                    ));
+                }
+
+                (parts, remaining % size)
+            },
+        );
+
+    if parts.is_empty() {
+        language.text(Text::NoTime).to_owned()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Format a count along with the singular or plural form of what's being counted, like `1 Day` or
+/// `2 Days`
+///
+/// Every count shown to users should go through this, so there's one place to change when other
+/// languages are supported.
+pub fn pluralize(count: i64, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+/// Get the English ordinal suffix for a number, like the `st` in `1st` or the `th` in `11th`
+pub fn ordinal_suffix(n: u32) -> &'static str {
+    match (n % 10, n % 100) {
+        (_, 11) | (_, 12) | (_, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Format a date in the given language and time format, like `19:05 CDT, Friday, June 1st` or
+/// `7:05 PM CDT, viernes, 1 de junio`
+pub fn format_localized_date<T>(
+    localtime: DateTime<T>,
+    language: Language,
+    time_format: TimeFormat,
+) -> String
+where
+    T: TimeZone + Debug,
+{
+    let weekday = language.weekday(localtime.weekday());
+    let month = language.month(localtime.month());
+    let day = localtime.day();
+
+    let date = match language {
+        Language::English => format!("{} {}{}", month, day, ordinal_suffix(day)),
+        Language::Spanish => format!("{} de {}", day, month),
+        Language::German => format!("{}. {}", day, month),
+    };
+
+    format!(
+        "{} {:?}, {}, {}",
+        time_format.format_time(localtime.hour(), localtime.minute()),
+        localtime.timezone(),
+        weekday,
+        date
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::US::Central;
+
+    use event_web::locale::{Language, TimeFormat};
+
+    use super::{
+        description_html, format_delete_confirmation, format_duration_between,
+        format_localized_date, ordinal_suffix, pluralize, EventCard,
+    };
+    use models::event::Coordinates;
+    use models::user::User;
+
+    fn user(id: i32, username: &str) -> User {
+        User::maybe_from_parts(Some(id), Some(id as i64), Some(username.to_owned())).unwrap()
+    }
+
+    #[test]
+    fn formats_dates() {
+        let localtime = Central.ymd(2018, 6, 1).and_hms(19, 5, 0);
+
+        let date = format_localized_date(localtime, Language::English, TimeFormat::default());
+
+        assert!(date.starts_with("19:05 "));
+        assert!(date.ends_with("Friday, June 1st"));
+    }
+
+    #[test]
+    fn formats_dates_in_other_languages() {
+        let localtime = Central.ymd(2018, 6, 1).and_hms(19, 5, 0);
+        let time_format = TimeFormat::TwentyFourHour;
+
+        let spanish = format_localized_date(localtime.clone(), Language::Spanish, time_format);
+        let german = format_localized_date(localtime, Language::German, time_format);
+
+        assert!(spanish.ends_with("viernes, 1 de junio"));
+        assert!(german.ends_with("Freitag, 1. Juni"));
+    }
+
+    #[test]
+    fn formats_twelve_hour_times() {
+        let localtime = Central.ymd(2018, 6, 1).and_hms(19, 5, 0);
+
+        let date = format_localized_date(localtime, Language::English, TimeFormat::TwelveHour);
+
+        assert!(date.starts_with("7:05 PM "));
+        assert!(date.ends_with("Friday, June 1st"));
+    }
+
+    #[test]
+    fn formats_durations_in_the_largest_units() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+
+        let two_hours = Central.ymd(2018, 6, 1).and_hms(21, 30, 0);
+        let three_days = Central.ymd(2018, 6, 4).and_hms(19, 0, 0);
+        let almost_two_days = Central.ymd(2018, 6, 3).and_hms(18, 0, 0);
+        let seventeen_days = Central.ymd(2018, 6, 18).and_hms(19, 0, 0);
+
+        assert_eq!(
+            format_duration_between(&start, &two_hours, 2, Language::English),
+            "2 Hours 30 Minutes"
+        );
+        assert_eq!(format_duration_between(&start, &three_days, 2, Language::English), "3 Days");
+        assert_eq!(
+            format_duration_between(&start, &almost_two_days, 2, Language::English),
+            "1 Day 23 Hours"
+        );
+        assert_eq!(
+            format_duration_between(&start, &seventeen_days, 2, Language::English),
+            "2 Weeks 3 Days"
+        );
+        assert_eq!(format_duration_between(&start, &start, 2, Language::English), "No time");
+    }
+
+    #[test]
+    fn limits_durations_to_the_precision() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+
+        let almost_two_days = Central.ymd(2018, 6, 3).and_hms(18, 30, 0);
+        let week_and_hours = Central.ymd(2018, 6, 8).and_hms(23, 0, 0);
+
+        assert_eq!(
+            format_duration_between(&start, &almost_two_days, 1, Language::English),
+            "1 Day"
+        );
+        assert_eq!(
+            format_duration_between(&start, &almost_two_days, 3, Language::English),
+            "1 Day 23 Hours 30 Minutes"
+        );
+        assert_eq!(
+            format_duration_between(&start, &week_and_hours, 2, Language::English),
+            "1 Week"
+        );
+    }
+
+    #[test]
+    fn uses_singular_for_one() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let one_hour = Central.ymd(2018, 6, 1).and_hms(20, 0, 0);
+
+        assert_eq!(format_duration_between(&start, &one_hour, 2, Language::English), "1 Hour");
+        assert_eq!(pluralize(1, "Day", "Days"), "1 Day");
+        assert_eq!(pluralize(0, "Day", "Days"), "0 Days");
+    }
+
+    #[test]
+    fn picks_ordinal_suffixes() {
+        let suffixes = [1, 2, 3, 4, 11, 12, 13, 21, 22, 23, 31, 111]
+            .iter()
+            .map(|n| ordinal_suffix(*n))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            suffixes,
+            ["st", "nd", "rd", "th", "th", "th", "th", "st", "nd", "rd", "st", "th"]
+        );
+    }
+
+    #[test]
+    fn leaves_out_unset_lines() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(21, 0, 0);
+
+        let card = EventCard::new("Pizza night", &start, &end, Central).to_string();
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Pizza night");
+        assert!(lines[1].starts_with("When: "));
+        assert_eq!(lines[2], "Duration: 2 Hours");
+    }
+
+    #[test]
+    fn labels_cards_in_their_language() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(21, 0, 0);
+
+        let card = EventCard::new("Pizzaabend", &start, &end, Central)
+            .members_only(true)
+            .language(Language::German)
+            .to_string();
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert!(lines[1].starts_with("Wann: "));
+        assert!(lines[1].ends_with("Freitag, 1. Juni"));
+        assert_eq!(lines[2], "Dauer: 2 Stunden");
+        assert_eq!(lines[3], "Nur für Mitglieder");
+    }
+
+    #[test]
+    fn links_coordinates_to_a_map() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(21, 0, 0);
+        let coordinates = Coordinates::new(44.98, -93.27).unwrap();
+
+        let card = EventCard::new("Pizza night", &start, &end, Central)
+            .location("Pizza Place", false)
+            .coordinates(&coordinates)
+            .to_string();
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[3], "Where: Pizza Place");
+        assert_eq!(
+            lines[4],
+            "Map: https://www.openstreetmap.org/?mlat=44.98&mlon=-93.27#map=17/44.98/-93.27"
+        );
+    }
+
+    #[test]
+    fn escapes_html_cards() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(21, 0, 0);
+
+        let card = EventCard::new("Pizza & <friends>", &start, &end, Central)
+            .description("Order from [the menu](https://example.com/?a=1&b=2) <b>early</b>")
+            .html()
+            .to_string();
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "<b>Pizza &amp; &lt;friends&gt;</b>");
+        assert_eq!(
+            lines[3],
+            "Description: Order from <a href=\"https://example.com/?a=1&amp;b=2\">the menu</a> \
+             &lt;b&gt;early&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn only_links_web_pages_in_descriptions() {
+        assert_eq!(
+            description_html("[a [b](https://example.com)"),
+            "[a <a href=\"https://example.com\">b</a>"
+        );
+        assert_eq!(
+            description_html("[click](javascript:alert(1))"),
+            "[click](javascript:alert(1))"
+        );
+        assert_eq!(description_html("[unfinished](https://"), "[unfinished](https://");
+    }
+
+    #[test]
+    fn shows_end_date_for_multi_day_events() {
+        let start = Central.ymd(2018, 6, 1).and_hms(22, 0, 0);
+        let end = Central.ymd(2018, 6, 2).and_hms(2, 0, 0);
+
+        let card = EventCard::new("Late night", &start, &end, Central).to_string();
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert!(lines[1].ends_with("Friday, June 1st"));
+        assert!(lines[2].starts_with("Ends: 2:00 "));
+        assert!(lines[2].ends_with("Saturday, June 2nd"));
+        assert_eq!(lines[3], "Duration: 4 Hours");
+    }
+
+    #[test]
+    fn marks_ongoing_events() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 3).and_hms(17, 0, 0);
+
+        let during = Central.ymd(2018, 6, 2).and_hms(12, 0, 0);
+        let last_day = Central.ymd(2018, 6, 3).and_hms(9, 0, 0);
+        let before = Central.ymd(2018, 6, 1).and_hms(12, 0, 0);
+
+        let card = |now| {
+            EventCard::new("Convention", &start, &end, Central)
+                .as_of(now)
+                .to_string()
+        };
+
+        assert_eq!(
+            card(during).lines().nth(1),
+            Some("When: Happening now, ends Sunday")
+        );
+        assert_eq!(
+            card(last_day).lines().nth(1),
+            Some("When: Happening now, ends at 17:00")
+        );
+        assert!(card(before).lines().nth(1).unwrap().starts_with("When: 19:00 "));
+    }
+
+    #[test]
+    fn says_when_upcoming_events_start() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(22, 0, 0);
+
+        let card = |now, language| {
+            EventCard::new("Pizza night", &start, &end, Central)
+                .as_of(now)
+                .language(language)
+                .to_string()
+        };
+
+        let afternoon = Central.ymd(2018, 6, 1).and_hms(15, 30, 0);
+        let last_week = Central.ymd(2018, 5, 22).and_hms(19, 0, 0);
+        let just_before = Central.ymd(2018, 6, 1).and_hms(18, 59, 30);
+
+        assert!(card(afternoon, Language::English)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("June 1st (starts in 3 Hours)"));
+        assert!(card(last_week, Language::English)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("(starts in 1 Week)"));
+        assert!(card(just_before, Language::English)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("(starting now)"));
+        assert!(card(afternoon, Language::German)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("(noch 3 Stunden)"));
+    }
+
+    #[test]
+    fn renders_optional_lines_in_order() {
+        let start = Central.ymd(2018, 6, 1).and_hms(19, 0, 0);
+        let end = Central.ymd(2018, 6, 1).and_hms(21, 0, 0);
+
+        let card = EventCard::new("Pizza night", &start, &end, Central)
+            .heading("New Event!")
+            .location("Downtown", true)
+            .description("Bring a friend")
+            .capacity(Some(10))
+            .members_only(true)
+            .to_string();
+
+        let lines = card.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "New Event!");
+        assert_eq!(lines[1], "Pizza night");
+        assert_eq!(
+            lines[4],
+            "Where: Downtown (the exact location is sent to you when you RSVP)"
+        );
+        assert_eq!(lines[5], "Description: Bring a friend");
+        assert_eq!(lines[6], "Capacity: 10");
+        assert_eq!(lines[7], "Members only");
+    }
+
+    #[test]
+    fn lists_rsvps_lost_by_deleting() {
+        let going = [user(1, "alice"), user(2, "bob")];
+        let waitlisted = [user(3, "carol")];
+
+        let text = format_delete_confirmation(
+            "Pizza night",
+            false,
+            &going,
+            &waitlisted,
+            Language::English,
+        );
+        let lines = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "Are you sure you want to delete Pizza night?");
+        assert_eq!(lines[2], "2 people are going: @alice, @bob");
+        assert_eq!(lines[3], "1 on the waitlist: @carol");
+        assert!(text.contains("Cancelling it instead"));
+    }
+
+    #[test]
+    fn only_offers_cancelling_events_that_arent_cancelled() {
+        let going = [user(1, "alice")];
+
+        let text = format_delete_confirmation("Pizza night", true, &going, &[], Language::English);
+
+        assert!(text.contains("1 person is going: @alice"));
+        assert!(!text.contains("waitlist"));
+        assert!(!text.contains("Cancelling it instead"));
+        assert!(
+            format_delete_confirmation("Pizza night", false, &[], &[], Language::English)
+                .contains("Nobody has RSVP'd yet.")
+        );
+    }
+}
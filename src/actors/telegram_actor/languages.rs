@@ -0,0 +1,141 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module caches which language the bot replies in for each chat.
+//!
+//! Every update is answered in the language of the user or channel it came from, so the language
+//! is kept here instead of being looked up for every message. Languages can be changed through
+//! other instances of the bot, so they're looked up again once they've been kept for a while.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use event_web::locale::Language;
+use telebot::objects::Integer;
+
+/// How many chats' languages are kept before the least recently used one is dropped
+pub const LANGUAGE_CACHE_SIZE: usize = 1024;
+
+/// How many seconds a chat's language is kept before it's looked up again
+pub const LANGUAGE_TTL: u64 = 10 * 60;
+
+/// A least-recently-used cache from chat ids to the language the bot replies in
+#[derive(Clone, Debug)]
+pub struct LanguageCache {
+    capacity: usize,
+    ttl: Duration,
+    languages: HashMap<Integer, (Language, Instant)>,
+    // Chat ids, from least to most recently used
+    recent: VecDeque<Integer>,
+}
+
+impl LanguageCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        LanguageCache {
+            capacity,
+            ttl,
+            languages: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Get the chat's language, marking it as recently used, unless it's been kept too long
+    pub fn get(&mut self, chat_id: Integer) -> Option<Language> {
+        match self.languages.get(&chat_id).cloned() {
+            Some((language, stored_at)) if stored_at.elapsed() < self.ttl => {
+                self.touch(chat_id);
+                Some(language)
+            }
+            Some(_) => {
+                self.remove(chat_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remember the chat's language, dropping the least recently used chat if the cache is full
+    pub fn insert(&mut self, chat_id: Integer, language: Language) {
+        if self.languages
+            .insert(chat_id, (language, Instant::now()))
+            .is_some()
+        {
+            self.touch(chat_id);
+            return;
+        }
+
+        self.recent.push_back(chat_id);
+
+        if self.recent.len() > self.capacity {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.languages.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forget every chat's language, like when a channel's language changes and the groups linked
+    /// to it should follow
+    pub fn clear(&mut self) {
+        self.languages.clear();
+        self.recent.clear();
+    }
+
+    fn remove(&mut self, chat_id: Integer) {
+        self.languages.remove(&chat_id);
+        self.recent.retain(|id| *id != chat_id);
+    }
+
+    fn touch(&mut self, chat_id: Integer) {
+        self.recent.retain(|id| *id != chat_id);
+        self.recent.push_back(chat_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use event_web::locale::Language;
+
+    use super::LanguageCache;
+
+    #[test]
+    fn drops_least_recently_used() {
+        let mut cache = LanguageCache::new(2, Duration::from_secs(60));
+
+        cache.insert(1, Language::Spanish);
+        cache.insert(-100, Language::German);
+        assert_eq!(cache.get(1), Some(Language::Spanish));
+
+        cache.insert(2, Language::English);
+
+        assert_eq!(cache.get(1), Some(Language::Spanish));
+        assert_eq!(cache.get(-100), None);
+        assert_eq!(cache.get(2), Some(Language::English));
+    }
+
+    #[test]
+    fn forgets_expired_languages() {
+        let mut cache = LanguageCache::new(2, Duration::from_secs(0));
+
+        cache.insert(1, Language::Spanish);
+
+        assert_eq!(cache.get(1), None);
+    }
+}
@@ -20,10 +20,14 @@
 //! This module defines the types that the `TelegramActor` accepts as messages. They come in two
 //! classes: Those that the `TelegramActor` sends itself, and those that other actors send.
 
-use actix::Message;
-use telebot::objects::Update;
+use actix::{Addr, Message, Syn};
+use telebot::objects::{Integer, Update};
 use telebot::RcBot;
 
+use actors::timer_coordinator::TimerCoordinator;
+use error::EventError;
+use event_web::locale::Language;
+use models::chat_system::ChatSystem;
 use models::event::Event;
 
 /// This message comes when the bot receives an Update or a series of Updates from telegram
@@ -38,6 +42,17 @@ impl Message for TgUpdate {
     type Result = ();
 }
 
+/// This message carries an Update once the language to reply to it in has been looked up. The
+/// `TelegramActor` sends this to itself
+pub struct Localized {
+    pub update: Update,
+    pub language: Language,
+}
+
+impl Message for Localized {
+    type Result = ();
+}
+
 /// This message instructs the actor to start the Telegram Update stream. It is sent when the actor
 /// crashes and restarts, or when the stream errors and needs to restart.
 pub struct StartStreaming;
@@ -46,6 +61,25 @@ impl Message for StartStreaming {
     type Result = ();
 }
 
+/// This message gives the actor the TimerCoordinator's address, so events it creates are scheduled.
+/// It is sent once the TimerCoordinator has started, since the TimerCoordinator needs this actor
+pub struct SetTimer(pub Addr<Syn, TimerCoordinator>);
+
+impl Message for SetTimer {
+    type Result = ();
+}
+
+/// This message carries an update Telegram sent to the webhook, along with the token from the
+/// webhook's URL. The Web UI produces this message
+pub struct WebhookUpdate {
+    pub token: String,
+    pub body: String,
+}
+
+impl Message for WebhookUpdate {
+    type Result = Result<(), EventError>;
+}
+
 /// This message is to alert the required channel that an event is starting soon. The Timer actor
 /// produces this message
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -55,6 +89,36 @@ impl Message for EventSoon {
     type Result = ();
 }
 
+/// This message is to alert a channel that several of its events are starting soon, so they can
+/// be sent as one message. The Timer actor produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventsSoon(pub Vec<Event>);
+
+impl Message for EventsSoon {
+    type Result = ();
+}
+
+/// This message is to announce an event again because too few people have RSVP'd two days before
+/// it starts. The TimerCoordinator produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Boost(pub Event);
+
+impl Message for Boost {
+    type Result = ();
+}
+
+/// This message is to remind an event's attendees privately that it's starting soon. The Timer
+/// actor produces this message, leaving out the attendees who muted reminders
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemindAttendees {
+    pub event: Event,
+    pub user_ids: Vec<Integer>,
+}
+
+impl Message for RemindAttendees {
+    type Result = ();
+}
+
 /// This message is to alert the required channel that an event has started. The Timer actor
 /// produces this message
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -73,6 +137,15 @@ impl Message for EventOver {
     type Result = ();
 }
 
+/// This message is to remove the RSVP buttons from an event's announcement once its RSVP deadline
+/// has passed. The Timer actor produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RsvpsClosed(pub Event);
+
+impl Message for RsvpsClosed {
+    type Result = ();
+}
+
 /// This message is to alert the require channel that an event has been created.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NewEvent(pub Event);
@@ -81,6 +154,15 @@ impl Message for NewEvent {
     type Result = ();
 }
 
+/// This message is to ask a moderated channel's admins to approve an event that's waiting for
+/// them. The EventActor produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReviewEvent(pub Event);
+
+impl Message for ReviewEvent {
+    type Result = ();
+}
+
 /// This message is to alert the required channel that an event has been updated.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UpdateEvent(pub Event);
@@ -88,3 +170,24 @@ pub struct UpdateEvent(pub Event);
 impl Message for UpdateEvent {
     type Result = ();
 }
+
+/// This message is to promote users from an event's waitlist once its capacity might have grown.
+/// The EventActor produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapacityChanged(pub Event);
+
+impl Message for CapacityChanged {
+    type Result = ();
+}
+
+/// This message is to post the weekly digest of upcoming events in a Chat System's channel. The
+/// Timer actor produces this message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Digest {
+    pub chat_system: ChatSystem,
+    pub events: Vec<Event>,
+}
+
+impl Message for Digest {
+    type Result = ();
+}
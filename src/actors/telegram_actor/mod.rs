@@ -20,44 +20,125 @@
 //! This module defines the `TelegramActor` struct and related functions. It handles talking to
 //! Telegram.
 
-use std::collections::HashSet;
-use std::fmt::Debug;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use actix::{Addr, Arbiter, Syn, Unsync};
-use base_x::encode;
-use chrono::{DateTime, Datelike, TimeZone, Timelike, Weekday};
-use chrono_tz::US::Central;
-use event_web::generate_secret;
+use chrono::offset::Utc;
+use chrono::{DateTime, Datelike, Timelike};
+use chrono::Duration as OldDuration;
+use chrono_tz::Tz;
+use event_web::locale::{Language, Text, TimeFormat, LANGUAGES};
+use event_web::{generate_secret, verify_secret};
+use futures::future::Either;
 use futures::stream::{futures_unordered, iter_ok};
-use futures::{Future, Stream};
-use rand::os::OsRng;
-use rand::Rng;
+use futures::{Future, IntoFuture, Stream};
 use serde_json;
 use telebot::functions::{
-    FunctionEditMessageText, FunctionGetChat, FunctionGetChatAdministrators, FunctionMessage,
-    FunctionPinChatMessage,
+    FunctionDocument, FunctionEditMessageText, FunctionGetChat, FunctionGetChatAdministrators,
+    FunctionMessage, FunctionPinChatMessage, FunctionUnpinChatMessage,
 };
 use telebot::objects::{
-    CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, Integer, Message, Update,
+    CallbackQuery, ChatMember, InlineKeyboardButton, InlineKeyboardMarkup, InlineQuery, Integer,
+    Message, Update,
 };
 use telebot::RcBot;
+use tokio_timer::Delay;
 
+use actors::alert_actor::messages::Snooze;
+use actors::alert_actor::AlertActor;
 use actors::db_broker::messages::{
-    DeleteEvent, DeleteUserByUserId, GetEventsForSystem, LookupEvent, LookupEventsByChatId,
-    LookupEventsByUserId, LookupSystem, LookupSystemByChannel, LookupSystemWithChats, LookupUser,
-    NewChannel, NewChat, NewRelation, NewUser, RemoveUserChat, StoreEditEventLink, StoreEventLink,
+    ApproveEvent, BanHost, CancelEvent, CancelRsvp, CheckBanned, ClearPinnedEvent, DeleteChannel,
+    DeleteChat, DeleteEvent, DeleteWebhook, EditEventLinkUnused, EventLinkUnused, ExportUser,
+    FillWaitlist, ForgetUser, GetAttendees, GetEventsForSystem, GetFailedMessages, GetRecipients,
+    GetStats, GetSystemMembers, GetWebhooksForChannel, LookupAnnouncement, LookupEvent,
+    LookupEventsByChatId, LookupEventsByChatIdInRange, LookupEventsByUserId, LookupLanguage,
+    LookupNextEventByChatId, LookupPreferences, LookupSystem, LookupSystemByChannel,
+    LookupSystemWithChats, LookupSystemsByChannels, LookupUser, NewChannel, NewChat,
+    NewEvent as DbNewEvent, NewRelation, NewUser, NewWebhook, PoolStatus, RejectEvent,
+    ReleaseDigest, RemoveUserFromChat, RotateWebhookSecret, Rsvp, SearchEvents, SetAnnouncement,
+    SetApiSecret, SetBoostThreshold, SetCalendarSecret, SetChannelLanguage, SetChannelTimeFormat,
+    SetChatTitle, SetDigestDay, SetFeature, SetGreeting, SetGreetingMessage, SetMuted,
+    SetPinnedDigest, SetPinnedEvent, SetReadSecret, SetTemplate, SetUserLanguage,
+    SetUserTimeFormat, StoreEditEventLink, StoreEventLink, StoreFailedMessage, TakeFailedMessage,
+    ToggleListed, ToggleModerated, TogglePinAnnouncements, TogglePinDigest, ToggleProtectContent,
+    TransferEvent, UnbanHost,
 };
 use actors::db_broker::DbBroker;
-use actors::users_actor::messages::{LookupChannels, RemoveRelation, TouchChannel, TouchUser};
+use actors::fan_out_actor::messages::FanOut;
+use actors::fan_out_actor::FanOutActor;
+use actors::users_actor::messages::{
+    LookupChannels, LookupLinkedChats, RemoveChannel, RemoveChat, RemoveRelation, RemoveUser,
+    TouchChannel, TouchUser,
+};
+use actors::timer::messages::Events;
+use actors::timer_coordinator::TimerCoordinator;
 use actors::users_actor::{DeleteState, UserState, UsersActor};
+use actors::webhook_actor::messages::EventChanged;
+use actors::webhook_actor::{public_url, EventAction, WebhookActor};
+use config::{Config, UpdateMode};
 use error::{EventError, EventErrorKind};
-use models::chat_system::ChatSystem;
-use models::event::Event;
-use util::flatten;
-use ENCODING_ALPHABET;
+use logging::LogContext;
+use models::chat_system::{BannedUser, ChatSystem, TemplateKind};
+use models::event::{Event, SearchScope};
+use models::features::{Feature, Features, FEATURES};
+use models::rsvp::RsvpStatus;
+use models::stats::Stats;
+use models::user::User;
+use models::user_preferences::{Notification, UserPreferences, NOTIFICATIONS};
+use models::webhook::Webhook;
+use util::{describe_error, flatten, telegram_error, telegram_lookup_error};
 
 mod actor;
+mod channels;
+mod commands;
+mod export;
+mod formatting;
+mod languages;
 pub mod messages;
+mod quick;
+mod template;
+
+use self::channels::{ChannelCache, CHANNEL_CACHE_SIZE};
+use self::commands::Scope;
+use self::languages::{LanguageCache, LANGUAGE_CACHE_SIZE, LANGUAGE_TTL};
+use self::formatting::{
+    escape_html, format_delete_confirmation, format_events, format_greeting,
+    format_localized_date, format_private_location, list_heading, EventCard,
+};
+use self::messages::{Localized, ReviewEvent, StartStreaming};
+use self::quick::QuickEvent;
+use self::template::Values;
+
+/// The longest caption Telegram allows on a photo
+const MAX_CAPTION_LENGTH: usize = 1024;
+
+/// How many seconds Telegram may cache the answer to an inline query
+const INLINE_CACHE_TIME: u32 = 60;
+
+/// How many seconds hosts wait between messages to the same event's attendees
+const BROADCAST_INTERVAL: u64 = 10 * 60;
+
+/// How many seconds an event from `/quick` waits for the user to pick a channel
+const QUICK_EVENT_TTL: u64 = 10 * 60;
+
+/// How many requests for updates can fail in a row before the bot's admins are alerted
+const STALLED_FETCHES: u32 = 3;
+
+/// The longest to wait, in seconds, before asking for updates again after a failed request
+const MAX_UPDATE_RETRY_SECONDS: u64 = 60;
+
+/// The key of the alert posted when updates can't be fetched
+const UPDATES_ALERT: &str = "updates";
+
+/// The key of the alert posted when the TelegramActor stops unexpectedly and is restarted
+const RESTART_ALERT: &str = "telegram_restart";
+
+/// The parse mode for messages formatted with `EventCard::html`
+const HTML: &str = "HTML";
 
 /// This type defines all the possible shapes of data coming from a Telegram Callback Query
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -65,55 +146,227 @@ pub enum CallbackQueryMessage {
     NewEvent { channel_id: Integer },
     EditEvent { event_id: i32 },
     DeleteEvent { event_id: i32, system_id: i32 },
+    ConfirmDelete { event_id: i32, system_id: i32 },
+    Really(Destructive),
+    Nevermind,
+    CancelEvent { event_id: i32 },
+    TransferEvent { event_id: i32 },
+    TransferTo { event_id: i32, user_id: i32 },
+    Broadcast { event_id: i32 },
+    Rsvp { event_id: i32 },
+    CancelRsvp { event_id: i32 },
+    QuickEvent { channel_id: Integer },
+    Mute { name: String, muted: bool },
+    Review { event_id: i32, approved: bool },
+    ApiToken { channel_id: Integer, read_only: bool },
+    WebhookSecret { channel_id: Integer },
+    RotateWebhookSecret { channel_id: Integer },
 }
 
-/// Define the Telegram Actor. It knows the base URL of the Web UI, and can talk to the database,
-/// the users actor, and Telegram itself.
+/// Destructive actions, which are only carried out once the user has said they're sure
+///
+/// Telegram only allows 64 bytes of callback data, so these should only hold the IDs they need
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Destructive {
+    DeleteEvent { event_id: i32 },
+    ForgetMe,
+}
+
+/// Define the Telegram Actor. It knows the base URL of the Web UI and the timezone to show dates
+/// in, and can talk to the database, the users actor, and Telegram itself.
+///
+/// Events created with `/quick` are kept by the user's ID, along with when they were made, until
+/// the user picks a channel for them.
+/// Hosts who picked an event with `/announce` are kept by their user ID until they send the message
+/// for its attendees, and the time each event's attendees were last messaged is kept by its ID.
 pub struct TelegramActor {
     url: String,
+    timezone: Tz,
     bot: RcBot,
     db: Addr<Unsync, DbBroker>,
     users: Addr<Syn, UsersActor>,
+    webhooks: Addr<Syn, WebhookActor>,
+    fan_out: Addr<Unsync, FanOutActor>,
+    alerts: Addr<Syn, AlertActor>,
+    // Set once the TimerCoordinator starts, since it needs this actor's address first
+    timer: Option<Addr<Syn, TimerCoordinator>>,
+    quick_events: HashMap<Integer, (Instant, QuickEvent)>,
+    // Only filled in once the event has been looked up and the user is known to host it
+    broadcasts: Rc<RefCell<HashMap<Integer, i32>>>,
+    last_broadcasts: Rc<RefCell<HashMap<i32, Instant>>>,
+    update_mode: UpdateMode,
+    // The hash of the token in the webhook's URL, while the bot is using a webhook
+    update_secret: Option<String>,
+    // Bumped each time updates are restarted, so a polling stream can tell it's been replaced
+    update_generation: Rc<Cell<usize>>,
+    bot_admins: Vec<Integer>,
+    // Each system's events channel, so reminders and updates don't look the system up every time
+    channels: Rc<RefCell<ChannelCache>>,
+    // The language replies go out in for each user, channel, and group
+    languages: Rc<RefCell<LanguageCache>>,
+    address: Option<Addr<Unsync, TelegramActor>>,
 }
 
 impl TelegramActor {
     pub fn new(
-        url: String,
+        config: &Config,
         bot: RcBot,
         db: Addr<Unsync, DbBroker>,
         users: Addr<Syn, UsersActor>,
+        webhooks: Addr<Syn, WebhookActor>,
+        fan_out: Addr<Unsync, FanOutActor>,
+        alerts: Addr<Syn, AlertActor>,
     ) -> Self {
         TelegramActor {
-            url,
+            url: config.event_url().to_owned(),
+            timezone: config.timezone(),
             bot,
             db,
             users,
+            webhooks,
+            fan_out,
+            alerts,
+            timer: None,
+            quick_events: HashMap::new(),
+            broadcasts: Rc::new(RefCell::new(HashMap::new())),
+            last_broadcasts: Rc::new(RefCell::new(HashMap::new())),
+            update_mode: config.update_mode(),
+            update_secret: None,
+            update_generation: Rc::new(Cell::new(0)),
+            bot_admins: config.bot_admins().to_vec(),
+            channels: Rc::new(RefCell::new(ChannelCache::new(CHANNEL_CACHE_SIZE))),
+            languages: Rc::new(RefCell::new(LanguageCache::new(
+                LANGUAGE_CACHE_SIZE,
+                Duration::from_secs(LANGUAGE_TTL),
+            ))),
+            address: None,
         }
     }
 
-    fn handle_update(&self, update: Update) {
-        debug!("handle update: {}", update.update_id);
-        if let Some(msg) = update.message {
-            self.handle_message(msg);
-        } else if let Some(channel_post) = update.channel_post {
-            self.handle_channel_post(channel_post);
-        } else if let Some(callback_query) = update.callback_query {
-            self.handle_callback_query(callback_query);
-        } else {
-            debug!("Update: {:?}", update);
+    /// Handle an update in the language of the user or chat it came from, looking the language
+    /// up first if it isn't known yet
+    fn handle_update(&mut self, update: Update) {
+        let chat_id = match reply_chat_id(&update) {
+            Some(chat_id) => chat_id,
+            None => return self.localized_update(update, Language::default()),
+        };
+
+        let cached = self.languages.borrow_mut().get(chat_id);
+
+        if let Some(language) = cached {
+            return self.localized_update(update, language);
+        }
+
+        let address = match self.address {
+            Some(ref address) => address.clone(),
+            None => return self.localized_update(update, Language::default()),
+        };
+
+        let log_ctx = update_log_context(&update);
+
+        // An update is still handled if its language can't be looked up, just in the default one
+        Arbiter::handle().spawn(
+            self.db
+                .send(LookupLanguage { chat_id })
+                .then(flatten)
+                .then(move |res| {
+                    let language = match res {
+                        Ok(language) => language.unwrap_or_default(),
+                        Err(e) => {
+                            ctx_warn!(log_ctx, "Error looking up language: {:?}", e);
+                            Language::default()
+                        }
+                    };
+
+                    address.do_send(Localized { update, language });
+                    Ok(())
+                }),
+        );
+    }
+
+    fn localized_update(&mut self, update: Update, language: Language) {
+        let log_ctx = update_log_context(&update);
+
+        if let Some(chat_id) = reply_chat_id(&update) {
+            self.languages.borrow_mut().insert(chat_id, language);
+        }
+
+        log_ctx.clone().scope(|| {
+            debug!("handle update");
+            if let Some(msg) = update.message {
+                self.handle_message(log_ctx, language, msg);
+            } else if let Some(channel_post) = update.channel_post {
+                self.handle_channel_post(log_ctx, language, channel_post);
+            } else if let Some(callback_query) = update.callback_query {
+                self.handle_callback_query(log_ctx, language, callback_query);
+            } else if let Some(inline_query) = update.inline_query {
+                self.handle_inline_query(log_ctx, language, inline_query);
+            } else {
+                debug!("Update: {:?}", update);
+            }
+        })
+    }
+
+    /// Have Telegram send updates to the Web UI
+    ///
+    /// Each registration puts a new token in the webhook's URL, so only Telegram knows where to
+    /// send updates. Only the token's hash is kept.
+    fn register_webhook(&mut self) {
+        let log_ctx = LogContext::new("telegram");
+
+        let token = match generate_secret() {
+            Ok((token, secret)) => {
+                self.update_secret = Some(secret);
+                token
+            }
+            Err(e) => {
+                ctx_error!(log_ctx, "Error creating webhook token: {:?}", e);
+                return;
+            }
+        };
+
+        let url = format!("{}/telegram/{}", self.url, token);
+
+        self.bot.inner.handle.spawn(
+            set_webhook(&self.bot, url).map_err(log_err!(log_ctx, "Error registering webhook")),
+        );
+    }
+
+    /// Handle an update Telegram sent to the webhook
+    ///
+    /// Updates that can't be read are logged and dropped, since Telegram would keep resending them
+    fn webhook_update(&mut self, token: &str, body: &str) -> Result<(), EventError> {
+        match self.update_secret {
+            Some(ref secret) if verify_secret(token, secret) => (),
+            _ => return Err(EventErrorKind::Secret.into()),
+        }
+
+        match serde_json::from_str::<Update>(body) {
+            Ok(update) => self.handle_update(update),
+            Err(e) => ctx_error!(
+                LogContext::new("telegram"),
+                "Error reading webhook update: {:?}",
+                e
+            ),
         }
+
+        Ok(())
     }
 
-    fn handle_message(&self, message: Message) {
+    fn handle_message(&mut self, log_ctx: LogContext, language: Language, message: Message) {
         debug!("handle message");
         if let Some(user) = message.left_chat_member {
             debug!("left chat member");
-            if message.chat.kind == "supergroup" {
+            if user.id == bot_user_id(&self.bot) {
+                debug!("bot removed");
+                self.chat_removed(message.chat.id);
+            } else if message.chat.kind == "supergroup" {
                 debug!("supergroup");
                 let chat_id = message.chat.id;
                 let user_id = user.id;
 
                 let db = self.db.clone();
+                let db_ctx = log_ctx.clone();
 
                 // Spawn a future that handles removing a user from a chat
                 Arbiter::handle().spawn(
@@ -121,22 +374,21 @@ impl TelegramActor {
                         .send(RemoveRelation(user_id, chat_id))
                         .then(flatten)
                         .map(move |delete_state| {
-                            match delete_state {
-                                DeleteState::UserEmpty => Arbiter::handle().spawn(
-                                    db.send(DeleteUserByUserId(user_id))
-                                        .then(flatten)
-                                        .map_err(|e| error!("Error deleting User: {:?}", e)),
-                                ),
-                                _ => (),
-                            }
+                            let delete_user = match delete_state {
+                                DeleteState::UserEmpty => true,
+                                _ => false,
+                            };
 
                             Arbiter::handle().spawn(
-                                db.send(RemoveUserChat(user_id, chat_id))
-                                    .then(flatten)
-                                    .map_err(|e| error!("Error removing UserChat: {:?}", e)),
+                                db.send(RemoveUserFromChat {
+                                    user_id,
+                                    chat_id,
+                                    delete_user,
+                                }).then(flatten)
+                                    .map_err(log_err!(db_ctx, "Error removing UserChat")),
                             );
                         })
-                        .map_err(|e| error!("Error removing User/Chat relation: {:?}", e)),
+                        .map_err(log_err!(log_ctx, "Error removing User/Chat relation")),
                 );
             }
         } else if let Some(user) = message.new_chat_member {
@@ -144,6 +396,7 @@ impl TelegramActor {
             if message.chat.kind == "supergroup" {
                 debug!("supergroup");
                 let db = self.db.clone();
+                let db_ctx = log_ctx.clone();
 
                 let user_id = user.id;
                 let username = user.username.unwrap_or(user.first_name);
@@ -156,11 +409,11 @@ impl TelegramActor {
                         .then(flatten)
                         .map(move |user_state| match user_state {
                             UserState::NewRelation => {
-                                debug!("Sending NewRelation");
+                                ctx_debug!(db_ctx, "Sending NewRelation");
                                 db.do_send(NewRelation { chat_id, user_id });
                             }
                             UserState::NewUser => {
-                                debug!("Sending NewUser");
+                                ctx_debug!(db_ctx, "Sending NewUser");
                                 db.do_send(NewUser {
                                     chat_id,
                                     user_id,
@@ -169,9 +422,12 @@ impl TelegramActor {
                             }
                             _ => (),
                         })
-                        .map_err(|e| error!("Error touching user/chat relation: {:?}", e)),
+                        .map_err(log_err!(log_ctx, "Error touching user/chat relation")),
                 );
             }
+        } else if let Some(title) = message.new_chat_title {
+            debug!("new chat title");
+            self.title_changed(log_ctx, message.chat.id, Some(title), None);
         } else if let Some(user) = message.from {
             debug!("user");
             if let Some(text) = message.text {
@@ -181,30 +437,57 @@ impl TelegramActor {
                     if message.chat.kind == "private" {
                         debug!("private");
                         let bot = self.bot.clone();
+                        let bot2 = self.bot.clone();
+                        let db = self.db.clone();
+                        let db2 = self.db.clone();
+                        let users = self.users.clone();
                         let chat_id = message.chat.id;
+                        let user_id = user.id;
+                        let username = user.username.unwrap_or(user.first_name);
 
                         // spawn a future that handles asking the User which chat they want to
                         // create an event for
                         Arbiter::handle().spawn(
                             self.users
-                                .send(LookupChannels(user.id))
+                                .send(LookupChannels(user_id))
                                 .then(flatten)
+                                .and_then(move |chats| {
+                                    // The bot only learns about users as they post, so users it
+                                    // doesn't know yet might still be in one of the linked chats
+                                    if chats.is_empty() {
+                                        Either::A(TelegramActor::sync_membership(
+                                            bot2, db, users, user_id, username,
+                                        ))
+                                    } else {
+                                        Either::B(Ok(chats).into_future())
+                                    }
+                                })
                                 .then(move |chats| match chats {
-                                    Ok(chats) => Ok(TelegramActor::ask_chats(bot, chats, chat_id)),
+                                    Ok(chats) => Ok(TelegramActor::ask_chats(
+                                        bot,
+                                        db2,
+                                        chats,
+                                        chat_id,
+                                        language,
+                                        language.text(Text::PickChannelForNew).to_owned(),
+                                        |channel_id| CallbackQueryMessage::NewEvent { channel_id },
+                                    )),
                                     Err(e) => {
-                                        TelegramActor::send_error(
+                                        TelegramActor::send_failure(
                                             &bot,
                                             chat_id,
-                                            "Failed to get event channnels for user",
+                                            &e,
+                                            language,
+                                            Text::ChannelsLookupFailed,
                                         );
                                         Err(e)
                                     }
                                 })
-                                .map_err(|e| error!("Error looking up channel: {:?}", e)),
+                                .map_err(log_err!(log_ctx, "Error looking up channel")),
                         );
                     } else {
                         debug!("not private");
-                        self.notify_private(message.chat.id);
+                        self.notify_private(language, message.chat.id);
                     }
                 } else if text.starts_with("/edit") {
                     debug!("edit");
@@ -222,23 +505,28 @@ impl TelegramActor {
                                 .send(LookupEventsByUserId { user_id: user.id })
                                 .then(flatten)
                                 .then(move |events| match events {
-                                    Ok(events) => {
-                                        Ok(TelegramActor::ask_events(bot, events, chat_id))
-                                    }
+                                    Ok(events) => Ok(TelegramActor::ask_events(
+                                        bot,
+                                        events,
+                                        chat_id,
+                                        language,
+                                    )),
                                     Err(e) => {
-                                        TelegramActor::send_error(
+                                        TelegramActor::send_failure(
                                             &bot,
                                             chat_id,
-                                            "Failed to get events for user",
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
                                         );
                                         Err(e)
                                     }
                                 })
-                                .map_err(|e| error!("Error looking up events: {:?}", e)),
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
                         );
                     } else {
                         debug!("not private");
-                        self.notify_private(message.chat.id);
+                        self.notify_private(language, message.chat.id);
                     }
                 } else if text.starts_with("/delete") {
                     debug!("delete");
@@ -256,1106 +544,7132 @@ impl TelegramActor {
                                 .send(LookupEventsByUserId { user_id: user.id })
                                 .then(flatten)
                                 .then(move |events| match events {
-                                    Ok(events) => {
-                                        Ok(TelegramActor::ask_delete_events(bot, events, chat_id))
-                                    }
+                                    Ok(events) => Ok(TelegramActor::ask_hosted_events(
+                                        bot,
+                                        events,
+                                        chat_id,
+                                        language,
+                                        Text::PickEventToDelete,
+                                        |event| CallbackQueryMessage::DeleteEvent {
+                                            event_id: event.id(),
+                                            system_id: event.system_id(),
+                                        },
+                                    )),
                                     Err(e) => {
-                                        TelegramActor::send_error(
+                                        TelegramActor::send_failure(
                                             &bot,
                                             chat_id,
-                                            "Failed to get events for user",
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
                                         );
                                         Err(e)
                                     }
                                 })
-                                .map_err(|e| error!("Error looking up events: {:?}", e)),
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
                         );
                     } else {
                         debug!("not private");
-                        self.notify_private(message.chat.id);
+                        self.notify_private(language, message.chat.id);
                     }
-                } else if text.starts_with("/id") {
-                    debug!("id");
-                    let chat_id = message.chat.id;
-
-                    if message.chat.kind == "supergroup" {
-                        debug!("supergroup");
-
-                        // Print the ID of the given chat
-                        TelegramActor::print_id(&self.bot, chat_id);
-                    } else if message.chat.kind == "group" {
-                        TelegramActor::send_error(
-                            &self.bot,
-                            chat_id,
-                            "Please upgrade this group to a supergroup before linking",
-                        );
-                    } else {
-                        TelegramActor::send_error(
-                            &self.bot,
-                            chat_id,
-                            "Cannot link non-supergroup chat",
-                        );
-                    }
-                } else if text.starts_with("/events") {
-                    debug!("events");
-                    let chat_id = message.chat.id;
-
-                    if message.chat.kind == "supergroup" {
-                        debug!("supergroup");
+                } else if text.starts_with("/cancel") {
+                    debug!("cancel");
+                    if message.chat.kind == "private" {
+                        debug!("private");
                         let bot = self.bot.clone();
+                        let chat_id = message.chat.id;
 
-                        // Spawn a future that handles printing the events for a given chat
+                        // Spawn a future that handles asking the user which event they would like
+                        // to cancel.
+                        //
+                        // Users can only cancel events they host, and only ones that haven't
+                        // already been cancelled.
                         Arbiter::handle().spawn(
                             self.db
-                                .send(LookupEventsByChatId { chat_id })
+                                .send(LookupEventsByUserId { user_id: user.id })
                                 .then(flatten)
                                 .then(move |events| match events {
-                                    Ok(events) => {
-                                        Ok(TelegramActor::send_events(&bot, chat_id, events))
-                                    }
+                                    Ok(events) => Ok(TelegramActor::ask_hosted_events(
+                                        bot,
+                                        events
+                                            .into_iter()
+                                            .filter(|event| !event.cancelled())
+                                            .collect(),
+                                        chat_id,
+                                        language,
+                                        Text::PickEventToCancel,
+                                        |event| CallbackQueryMessage::CancelEvent {
+                                            event_id: event.id(),
+                                        },
+                                    )),
                                     Err(e) => {
-                                        TelegramActor::send_error(
+                                        TelegramActor::send_failure(
                                             &bot,
                                             chat_id,
-                                            "Failed to fetch events",
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
                                         );
                                         Err(e)
                                     }
                                 })
-                                .map_err(|e| error!("Error looking up events: {:?}", e)),
-                        )
-                    } else {
-                        TelegramActor::send_error(
-                            &self.bot,
-                            chat_id,
-                            "Can only fetch events in a supergroup",
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
                         );
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
                     }
-                } else if text.starts_with("/pinevents") {
-                    debug!("pinevents");
-                    let chat_id = message.chat.id;
-
-                    if message.chat.kind == "supergroup" {
-                        debug!("supergroup");
+                } else if text.starts_with("/transfer") {
+                    debug!("transfer");
+                    if message.chat.kind == "private" {
+                        debug!("private");
                         let bot = self.bot.clone();
+                        let chat_id = message.chat.id;
 
-                        // Spawn a future that handles printing the events for a given chat
+                        // Spawn a future that handles asking the user which event they would like
+                        // to hand over to someone else.
                         Arbiter::handle().spawn(
                             self.db
-                                .send(LookupEventsByChatId { chat_id })
+                                .send(LookupEventsByUserId { user_id: user.id })
                                 .then(flatten)
                                 .then(move |events| match events {
-                                    Ok(events) => Ok(TelegramActor::send_and_pin_events(
-                                        &bot, chat_id, events,
+                                    Ok(events) => Ok(TelegramActor::ask_hosted_events(
+                                        bot,
+                                        events,
+                                        chat_id,
+                                        language,
+                                        Text::PickEventToTransfer,
+                                        |event| CallbackQueryMessage::TransferEvent {
+                                            event_id: event.id(),
+                                        },
                                     )),
                                     Err(e) => {
-                                        TelegramActor::send_error(
+                                        TelegramActor::send_failure(
                                             &bot,
                                             chat_id,
-                                            "Failed to fetch events",
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
                                         );
                                         Err(e)
                                     }
                                 })
-                                .map_err(|e| error!("Error looking up events: {:?}", e)),
-                        )
-                    } else {
-                        TelegramActor::send_error(
-                            &self.bot,
-                            chat_id,
-                            "Can only pin events in a supergroup",
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
                         );
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
                     }
-                } else if text.starts_with("/help")
-                    || (text.starts_with("/start") && message.chat.kind == "private")
-                {
-                    debug!("help | start + private");
-                    self.send_help(message.chat.id);
-                } else {
-                    debug!("else");
-                    if message.chat.kind == "supergroup" {
-                        debug!("supergroup");
-                        let db = self.db.clone();
-
-                        let user_id = user.id;
-                        let username = user.username.unwrap_or(user.first_name);
+                } else if text.starts_with("/announce") {
+                    debug!("announce");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let bot = self.bot.clone();
                         let chat_id = message.chat.id;
 
-                        // Spawn a future that handles updating a user/chat relation
+                        // Spawn a future that handles asking the user which event's attendees
+                        // they would like to message
                         Arbiter::handle().spawn(
-                            self.users
-                                .send(TouchUser(user_id, chat_id))
+                            self.db
+                                .send(LookupEventsByUserId { user_id: user.id })
                                 .then(flatten)
-                                .and_then(move |user_state| {
-                                    Ok(match user_state {
-                                        UserState::NewRelation => {
-                                            debug!("Sending NewRelation");
-                                            db.do_send(NewRelation { chat_id, user_id });
-                                        }
-                                        UserState::NewUser => {
-                                            debug!("Sending NewUser");
-                                            db.do_send(NewUser {
-                                                chat_id,
-                                                user_id,
-                                                username,
-                                            });
-                                        }
-                                        _ => (),
-                                    })
+                                .then(move |events| match events {
+                                    Ok(events) => Ok(TelegramActor::ask_hosted_events(
+                                        bot,
+                                        events
+                                            .into_iter()
+                                            .filter(|event| !event.cancelled())
+                                            .collect(),
+                                        chat_id,
+                                        language,
+                                        Text::PickEventToBroadcast,
+                                        |event| CallbackQueryMessage::Broadcast {
+                                            event_id: event.id(),
+                                        },
+                                    )),
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
+                                        );
+                                        Err(e)
+                                    }
                                 })
-                                .map_err(|e| error!("Error Updating user/chat relations: {:?}", e)),
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
                         );
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
                     }
-                }
-            }
-        }
-    }
-
-    fn handle_channel_post(&self, message: Message) {
-        debug!("handle channel post");
-        if let Some(text) = message.text {
-            debug!("text");
-            if text.starts_with("/link") {
-                debug!("link");
-                let channel_id = message.chat.id;
-
-                if message.chat.kind == "channel" {
-                    debug!("channel");
-                    let db = self.db.clone();
-                    let bot = self.bot.clone();
-                    let bot2 = bot.clone();
+                } else if text.starts_with("/mute") || text.starts_with("/unmute") {
+                    debug!("mute");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let muted = text.starts_with("/mute");
+                        let command = if muted { "/mute" } else { "/unmute" };
+                        let chat_id = message.chat.id;
 
-                    let users = self.users.clone();
+                        match notification_from_name(text.trim_left_matches(command).trim()) {
+                            Some(notification) => {
+                                let bot = self.bot.clone();
 
-                    Arbiter::handle().spawn(
-                        self.db
-                            .send(LookupSystemByChannel(channel_id))
-                            .then(flatten)
-                            .or_else(move |_| {
-                                TelegramActor::send_error(
-                                    &bot,
-                                    channel_id,
-                                    "Please /init the channel before linking",
+                                // Spawn a future that stores the user's new preferences
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(SetMuted {
+                                            user_id: user.id,
+                                            notification,
+                                            muted,
+                                        })
+                                        .then(flatten)
+                                        .then(move |res| match res {
+                                            Ok(preferences) => {
+                                                send_message(
+                                                    &bot,
+                                                    chat_id,
+                                                    describe_preferences(&preferences, language),
+                                                );
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::UserNotFound,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error setting muted")),
                                 );
-                                Err(())
-                            })
-                            .and_then(move |_: ChatSystem| {
-                                // Get the valid IDs provided in the link message, update the UserActor with
-                                // the valid links
-                                let chat_ids = text.trim_left_matches("/link")
-                                    .split(' ')
-                                    .into_iter()
-                                    .filter_map(|chat_id| chat_id.parse::<Integer>().ok())
-                                    .map(|chat_id| {
-                                        users.do_send(TouchChannel(channel_id, chat_id));
+                            }
+                            None => TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                &language.fill(Text::MuteUsage, &[&command]),
+                            ),
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/language") {
+                    debug!("language");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
 
-                                        chat_id
-                                    })
-                                    .collect();
+                        match Language::from_code(text.trim_left_matches("/language")) {
+                            Some(new_language) => {
+                                let bot = self.bot.clone();
+                                let languages = self.languages.clone();
 
-                                // Spawn a future updating the links between the channel and the given chats in
-                                // the database
-                                TelegramActor::is_admin(bot2.clone(), channel_id, chat_ids)
-                                    .then(move |res| match res {
-                                        Ok(item) => Ok((item, bot2)),
-                                        Err(err) => Err((err, bot2)),
-                                    })
-                                    .and_then(move |(chat_ids, bot)| {
-                                        for chat_id in chat_ids.iter() {
-                                            db.do_send(NewChat {
-                                                channel_id: channel_id,
-                                                chat_id: *chat_id,
-                                            });
-                                        }
+                                // Spawn a future that stores the user's language, and replies in
+                                // it from then on
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(SetUserLanguage {
+                                            user_id: user.id,
+                                            language: new_language,
+                                        })
+                                        .then(flatten)
+                                        .then(move |res| match res {
+                                            Ok(preferences) => {
+                                                let language = preferences.language();
+                                                languages.borrow_mut().insert(chat_id, language);
 
-                                        TelegramActor::linked(&bot, channel_id, chat_ids);
-                                        Ok(())
-                                    })
-                                    .map_err(move |(e, bot)| {
-                                        TelegramActor::send_error(
-                                    &bot,
-                                    channel_id,
-                                    "Could not determine if you are an admin of provided chats",
+                                                send_message(
+                                                    &bot,
+                                                    chat_id,
+                                                    language.fill(
+                                                        Text::UserLanguageSet,
+                                                        &[&language.name()],
+                                                    ),
+                                                );
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::UserNotFound,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error setting language")),
                                 );
-                                        e
-                                    })
-                                    .map_err(|e| error!("Error checking admin: {:?}", e))
-                            }),
+                            }
+                            None => TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                &language_usage(language),
+                            ),
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/timeformat") {
+                    debug!("timeformat");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
+
+                        match TimeFormat::from_name(text.trim_left_matches("/timeformat")) {
+                            Some(time_format) => {
+                                let bot = self.bot.clone();
+
+                                // Spawn a future that stores the user's time format
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(SetUserTimeFormat {
+                                            user_id: user.id,
+                                            time_format,
+                                        })
+                                        .then(flatten)
+                                        .then(move |res| match res {
+                                            Ok(_) => {
+                                                send_message(
+                                                    &bot,
+                                                    chat_id,
+                                                    language.fill(
+                                                        Text::UserTimeFormatSet,
+                                                        &[&time_format.name()],
+                                                    ),
+                                                );
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::UserNotFound,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error setting time format")),
+                                );
+                            }
+                            None => TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::TimeFormatUsage),
+                            ),
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/notifications") {
+                    debug!("notifications");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        self.notifications_menu(log_ctx, language, user.id, message.chat.id);
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/updates") {
+                    debug!("updates");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
+                        let name = text.trim_left_matches("/updates").trim();
+
+                        if !self.bot_admins.contains(&user.id) {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::UpdatesAdminsOnly),
+                            );
+                        } else if name.is_empty() {
+                            send_message(
+                                &self.bot,
+                                chat_id,
+                                language.fill(Text::ReceivingUpdates, &[&self.update_mode.name()]),
+                            );
+                        } else if let Some(update_mode) = UpdateMode::from_name(name) {
+                            self.update_mode = update_mode;
+
+                            if let Some(ref address) = self.address {
+                                address.do_send(StartStreaming);
+                            }
+
+                            send_message(
+                                &self.bot,
+                                chat_id,
+                                language.fill(Text::SwitchingUpdates, &[&update_mode.name()]),
+                            );
+                        } else {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::UpdatesUsage),
+                            );
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/snooze") {
+                    debug!("snooze");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
+                        let arg = text.trim_left_matches("/snooze").trim();
+
+                        let minutes = match arg.parse::<u64>() {
+                            Ok(minutes) if minutes > 0 => Some(minutes),
+                            _ if arg == "off" => Some(0),
+                            _ => None,
+                        };
+
+                        if !self.bot_admins.contains(&user.id) {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::SnoozeAdminsOnly),
+                            );
+                        } else if let Some(minutes) = minutes {
+                            self.alerts.do_send(Snooze { minutes });
+
+                            let msg = if minutes > 0 {
+                                language.fill(Text::Snoozed, &[&minutes])
+                            } else {
+                                language.text(Text::Unsnoozed).to_owned()
+                            };
+
+                            send_message(&self.bot, chat_id, msg);
+                        } else {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::SnoozeUsage),
+                            );
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/failed") {
+                    debug!("failed");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
+                        let args = text.trim_left_matches("/failed").trim();
+
+                        if !self.bot_admins.contains(&user.id) {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::FailedAdminsOnly),
+                            );
+                        } else if args.is_empty() {
+                            self.list_failed_messages(log_ctx, language, chat_id);
+                        } else if let Ok(id) = args.trim_left_matches("replay").trim().parse() {
+                            self.replay_failed_message(log_ctx, language, chat_id, id);
+                        } else {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::FailedUsage),
+                            );
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/pool") {
+                    debug!("pool");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        if self.bot_admins.contains(&user.id) {
+                            self.pool_status(log_ctx, language, message.chat.id);
+                        } else {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                message.chat.id,
+                                language.text(Text::PoolAdminsOnly),
+                            );
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/forgetme") {
+                    debug!("forgetme");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        self.ask_forget(log_ctx, language, message.chat.id);
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/exportme") {
+                    debug!("exportme");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        self.export_user(log_ctx, language, user.id, message.chat.id);
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/quick") {
+                    debug!("quick");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let chat_id = message.chat.id;
+                        let now = Utc::now().with_timezone(&self.timezone);
+
+                        match quick::parse(text.trim_left_matches("/quick"), now) {
+                            Some(ref quick_event) if quick_event.start_date < now => {
+                                TelegramActor::send_error(
+                                    &self.bot,
+                                    chat_id,
+                                    language.text(Text::QuickInPast),
+                                );
+                            }
+                            Some(quick_event) => {
+                                let card = EventCard::new(
+                                    &quick_event.title,
+                                    &quick_event.start_date,
+                                    &quick_event.end_date,
+                                    self.timezone,
+                                ).language(language);
+                                let prompt = language.fill(Text::QuickPickChannel, &[&card]);
+
+                                // Hold on to the event until the user confirms it by picking a
+                                // channel, dropping any that were never confirmed
+                                let ttl = Duration::from_secs(QUICK_EVENT_TTL);
+                                self.quick_events
+                                    .retain(|_, &mut (created, _)| created.elapsed() < ttl);
+                                self.quick_events
+                                    .insert(user.id, (Instant::now(), quick_event));
+
+                                let bot = self.bot.clone();
+                                let db = self.db.clone();
+
+                                Arbiter::handle().spawn(
+                                    self.users
+                                        .send(LookupChannels(user.id))
+                                        .then(flatten)
+                                        .then(move |chats| match chats {
+                                            Ok(chats) => Ok(TelegramActor::ask_chats(
+                                                bot,
+                                                db,
+                                                chats,
+                                                chat_id,
+                                                language,
+                                                prompt,
+                                                |channel_id| CallbackQueryMessage::QuickEvent {
+                                                    channel_id,
+                                                },
+                                            )),
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::ChannelsLookupFailed,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error looking up channel")),
+                                );
+                            }
+                            None => TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::QuickUsage),
+                            ),
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/token") {
+                    debug!("token");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let args = text.trim_left_matches("/token").trim();
+
+                        if args.is_empty() {
+                            self.ask_admin_channels(
+                                log_ctx,
+                                language,
+                                user.id,
+                                message.chat.id,
+                                Text::PickChannelForToken,
+                                |channel_id| CallbackQueryMessage::ApiToken {
+                                    channel_id,
+                                    read_only: false,
+                                },
+                            );
+                        } else if args == "read" {
+                            self.ask_admin_channels(
+                                log_ctx,
+                                language,
+                                user.id,
+                                message.chat.id,
+                                Text::PickChannelForReadToken,
+                                |channel_id| CallbackQueryMessage::ApiToken {
+                                    channel_id,
+                                    read_only: true,
+                                },
+                            );
+                        } else {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                message.chat.id,
+                                language.text(Text::TokenUsage),
+                            );
+                        }
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/webhook") && message.chat.kind == "private" {
+                    debug!("webhook");
+                    let args = text.trim_left_matches("/webhook").trim();
+
+                    if args == "secret" {
+                        self.ask_admin_channels(
+                            log_ctx,
+                            language,
+                            user.id,
+                            message.chat.id,
+                            Text::PickChannelForSecret,
+                            |channel_id| CallbackQueryMessage::WebhookSecret { channel_id },
+                        );
+                    } else if args == "rotate" {
+                        self.ask_admin_channels(
+                            log_ctx,
+                            language,
+                            user.id,
+                            message.chat.id,
+                            Text::PickChannelForRotate,
+                            |channel_id| CallbackQueryMessage::RotateWebhookSecret { channel_id },
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            message.chat.id,
+                            language.text(Text::WebhookPrivateUsage),
+                        );
+                    }
+                } else if text.starts_with("/myevents") {
+                    debug!("myevents");
+                    if message.chat.kind == "private" {
+                        debug!("private");
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+                        let chat_id = message.chat.id;
+                        let timezone = self.timezone;
+
+                        // Spawn a future that lists every event the user hosts, across all
+                        // channels, with buttons to edit or delete each one
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupEventsByUserId { user_id: user.id })
+                                .then(flatten)
+                                .join(self.db.send(LookupPreferences(user.id)).then(flatten))
+                                .then(move |res| match res {
+                                    Ok((events, preferences)) => {
+                                        Ok(TelegramActor::send_my_events(
+                                            bot,
+                                            db,
+                                            events,
+                                            chat_id,
+                                            timezone,
+                                            preferences.language(),
+                                            preferences.time_format().unwrap_or_default(),
+                                        ))
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::EventsLookupFailed,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
+                        );
+                    } else {
+                        debug!("not private");
+                        self.notify_private(language, message.chat.id);
+                    }
+                } else if text.starts_with("/id") {
+                    debug!("id");
+                    let chat_id = message.chat.id;
+
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+
+                        // Print the ID of the given chat
+                        TelegramActor::print_id(&self.bot, chat_id);
+                    } else if message.chat.kind == "group" {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::UpgradeToSupergroup),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::NotSupergroup),
+                        );
+                    }
+                } else if text.starts_with("/events") {
+                    debug!("events");
+                    let chat_id = message.chat.id;
+
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+                        let timezone = self.timezone;
+
+                        // Spawn a future that handles printing the events for a given chat
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupEventsByChatId { chat_id })
+                                .then(flatten)
+                                .then(move |events| match events {
+                                    Ok(events) => Ok(TelegramActor::send_events(
+                                        &bot, &db, chat_id, events, timezone, language,
+                                    )),
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::FetchEventsFailed,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
+                        )
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::FetchOnlyInSupergroup),
+                        );
+                    }
+                } else if text.starts_with("/pinevents") {
+                    debug!("pinevents");
+                    let chat_id = message.chat.id;
+
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+                        let timezone = self.timezone;
+
+                        // Spawn a future that handles printing the events for a given chat
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupEventsByChatId { chat_id })
+                                .then(flatten)
+                                .then(move |events| match events {
+                                    Ok(events) => Ok(TelegramActor::send_and_pin_events(
+                                        &bot, &db, chat_id, events, timezone, language,
+                                    )),
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::FetchEventsFailed,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error looking up events")),
+                        )
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::PinOnlyInSupergroup),
+                        );
+                    }
+                } else if text.starts_with("/next") {
+                    debug!("next");
+                    let chat_id = message.chat.id;
+
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+                        let timezone = self.timezone;
+
+                        // Spawn a future that handles printing the next event for a given chat
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupNextEventByChatId {
+                                    chat_id,
+                                    after: Utc::now().with_timezone(&timezone),
+                                })
+                                .then(flatten)
+                                .then(move |event| match event {
+                                    Ok(event) => Ok(TelegramActor::send_listing(
+                                        &bot,
+                                        &db,
+                                        chat_id,
+                                        language.text(Text::NextEvent).to_owned(),
+                                        language.text(Text::NoUpcomingEvents).to_owned(),
+                                        event.into_iter().collect(),
+                                        timezone,
+                                        language,
+                                    )),
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::FetchEventsFailed,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error looking up next event")),
+                        )
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::FetchOnlyInSupergroup),
+                        );
+                    }
+                } else if text.starts_with("/today") {
+                    debug!("today");
+                    let chat_id = message.chat.id;
+
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+                        let timezone = self.timezone;
+
+                        // Today runs from midnight to midnight in the bot's timezone. Midnight
+                        // can be skipped by a daylight saving change, so fall back to now.
+                        let now = Utc::now().with_timezone(&timezone);
+                        let start_date = now.date().and_hms_opt(0, 0, 0).unwrap_or(now.clone());
+                        let end_date = now.date()
+                            .succ()
+                            .and_hms_opt(0, 0, 0)
+                            .unwrap_or(now.clone() + OldDuration::days(1));
+
+                        // Spawn a future that handles printing today's events for a given chat
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupEventsByChatIdInRange {
+                                    chat_id,
+                                    start_date,
+                                    end_date,
+                                })
+                                .then(flatten)
+                                .then(move |events| match events {
+                                    Ok(events) => Ok(TelegramActor::send_listing(
+                                        &bot,
+                                        &db,
+                                        chat_id,
+                                        language.text(Text::TodaysEvents).to_owned(),
+                                        language.text(Text::NoEventsToday).to_owned(),
+                                        events,
+                                        timezone,
+                                        language,
+                                    )),
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            chat_id,
+                                            &e,
+                                            language,
+                                            Text::FetchEventsFailed,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error looking up today's events")),
+                        )
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            chat_id,
+                            language.text(Text::FetchOnlyInSupergroup),
+                        );
+                    }
+                } else if text.starts_with("/search") {
+                    debug!("search");
+                    let chat_id = message.chat.id;
+                    let terms = text.trim_left_matches("/search").trim().to_owned();
+
+                    // Groups search their own channel, and private chats search every channel
+                    // the user can see
+                    let scope = match message.chat.kind.as_str() {
+                        "supergroup" => Some(SearchScope::Chat(chat_id)),
+                        "private" => Some(SearchScope::User(user.id)),
+                        _ => None,
+                    };
+
+                    match scope {
+                        Some(scope) if terms.len() > 0 => {
+                            let bot = self.bot.clone();
+                            let db = self.db.clone();
+                            let timezone = self.timezone;
+
+                            // Spawn a future that searches for the events
+                            Arbiter::handle().spawn(
+                                self.db
+                                    .send(SearchEvents {
+                                        scope,
+                                        terms: terms.clone(),
+                                    })
+                                    .then(flatten)
+                                    .then(move |events| match events {
+                                        Ok(events) => Ok(TelegramActor::send_listing(
+                                            &bot,
+                                            &db,
+                                            chat_id,
+                                            language.fill(
+                                                Text::EventsMatching,
+                                                &[&escape_html(&terms)],
+                                            ),
+                                            language.fill(
+                                                Text::NoEventsMatch,
+                                                &[&escape_html(&terms)],
+                                            ),
+                                            events,
+                                            timezone,
+                                            language,
+                                        )),
+                                        Err(e) => {
+                                            TelegramActor::send_failure(
+                                                &bot,
+                                                chat_id,
+                                                &e,
+                                                language,
+                                                Text::SearchFailed,
+                                            );
+                                            Err(e)
+                                        }
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error searching events")),
+                            )
+                        }
+                        Some(_) => {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::SearchUsage),
+                            );
+                        }
+                        None => {
+                            TelegramActor::send_error(
+                                &self.bot,
+                                chat_id,
+                                language.text(Text::SearchOnlyInGroups),
+                            );
+                        }
+                    }
+                } else if text.starts_with("/start new_") && message.chat.kind == "private" {
+                    debug!("start new + private");
+                    let chat_id = message.chat.id;
+
+                    // Channels link to t.me/{bot}?start=new_{channel_id} to start /new for
+                    // themselves
+                    match text.trim_left_matches("/start new_").trim().parse() {
+                        Ok(channel_id) => {
+                            let user_id = user.id;
+                            let username = user.username.unwrap_or(user.first_name);
+
+                            self.start_new_event(
+                                log_ctx, language, user_id, username, chat_id, channel_id,
+                            );
+                        }
+                        Err(_) => self.send_help(language, chat_id),
+                    }
+                } else if text.starts_with("/help")
+                    || (text.starts_with("/start") && message.chat.kind == "private")
+                {
+                    debug!("help | start + private");
+                    self.send_help(language, message.chat.id);
+                } else {
+                    debug!("else");
+                    if message.chat.kind == "supergroup" {
+                        debug!("supergroup");
+                        let db = self.db.clone();
+                        let db_ctx = log_ctx.clone();
+
+                        let user_id = user.id;
+                        let username = user.username.unwrap_or(user.first_name);
+                        let chat_id = message.chat.id;
+
+                        // Spawn a future that handles updating a user/chat relation
+                        Arbiter::handle().spawn(
+                            self.users
+                                .send(TouchUser(user_id, chat_id))
+                                .then(flatten)
+                                .and_then(move |user_state| {
+                                    Ok(match user_state {
+                                        UserState::NewRelation => {
+                                            ctx_debug!(db_ctx, "Sending NewRelation");
+                                            db.do_send(NewRelation { chat_id, user_id });
+                                        }
+                                        UserState::NewUser => {
+                                            ctx_debug!(db_ctx, "Sending NewUser");
+                                            db.do_send(NewUser {
+                                                chat_id,
+                                                user_id,
+                                                username,
+                                            });
+                                        }
+                                        _ => (),
+                                    })
+                                })
+                                .map_err(log_err!(log_ctx, "Error Updating user/chat relations")),
+                        );
+                    } else if message.chat.kind == "private" {
+                        debug!("private");
+                        // Hosts who picked an event with /announce send the message for its
+                        // attendees next
+                        let broadcast = self.broadcasts.borrow_mut().remove(&user.id);
+
+                        if let Some(event_id) = broadcast {
+                            self.broadcast(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user.id,
+                                message.chat.id,
+                                text,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_channel_post(&self, log_ctx: LogContext, language: Language, message: Message) {
+        debug!("handle channel post");
+        if let Some(title) = message.new_chat_title {
+            debug!("new chat title");
+            self.title_changed(log_ctx, message.chat.id, Some(title), message.chat.username);
+        } else if let Some(text) = message.text {
+            debug!("text");
+            if text.starts_with("/link") {
+                debug!("link");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let db = self.db.clone();
+                    let bot = self.bot.clone();
+                    let bot2 = bot.clone();
+
+                    let users = self.users.clone();
+                    let users2 = self.users.clone();
+
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(LookupSystemByChannel(channel_id))
+                            .then(flatten)
+                            .or_else(move |_| {
+                                TelegramActor::send_error(
+                                    &bot,
+                                    channel_id,
+                                    language.text(Text::InitBeforeLinking),
+                                );
+                                Err(())
+                            })
+                            .and_then(move |_: ChatSystem| {
+                                // Get the valid IDs provided in the link message, update the UserActor with
+                                // the valid links
+                                let chat_ids = text.trim_left_matches("/link")
+                                    .split(' ')
+                                    .into_iter()
+                                    .filter_map(|chat_id| chat_id.parse::<Integer>().ok())
+                                    .map(|chat_id| {
+                                        users.do_send(TouchChannel(channel_id, chat_id));
+
+                                        chat_id
+                                    })
+                                    .collect();
+
+                                let bot3 = bot2.clone();
+
+                                // Spawn a future updating the links between the channel and the given chats in
+                                // the database, once it's clear announcements can be posted
+                                check_channel_rights(&bot2, channel_id)
+                                    .and_then(move |_| {
+                                        TelegramActor::is_admin(bot3, channel_id, chat_ids)
+                                    })
+                                    .then(move |res| match res {
+                                        Ok(item) => Ok((item, bot2)),
+                                        Err(err) => Err((err, bot2)),
+                                    })
+                                    .and_then(move |(chat_ids, bot)| {
+                                        for chat_id in chat_ids.iter() {
+                                            let chat_id = *chat_id;
+                                            let link_ctx = LogContext::new("telegram")
+                                                .chat_id(chat_id);
+
+                                            // The chat's admins can host events right away,
+                                            // before they've posted anything in it
+                                            let import = TelegramActor::import_admins(
+                                                bot.clone(),
+                                                db.clone(),
+                                                users2.clone(),
+                                                chat_id,
+                                            );
+
+                                            bot.inner.handle.spawn(
+                                                db.send(NewChat {
+                                                    channel_id: channel_id,
+                                                    chat_id: chat_id,
+                                                }).then(flatten)
+                                                    .and_then(move |_| import)
+                                                    .map_err(log_err!(
+                                                        link_ctx,
+                                                        "Error importing chat admins"
+                                                    )),
+                                            );
+                                        }
+
+                                        TelegramActor::linked(&bot, channel_id, language, chat_ids);
+                                        Ok(())
+                                    })
+                                    .map_err(move |(e, bot)| {
+                                        TelegramActor::send_failure(
+                                    &bot,
+                                    channel_id,
+                                    &e,
+                                    language,
+                                    Text::AdminCheckFailed,
+                                );
+                                        e
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error checking admin"))
+                            }),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/link"]),
+                    );
+                }
+            } else if text.starts_with("/protect") {
+                debug!("protect");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+
+                    // Spawn a future that toggles whether announcements can be forwarded
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(ToggleProtectContent { channel_id })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(protected) => {
+                                    let msg = if protected {
+                                        Text::Protected
+                                    } else {
+                                        Text::Unprotected
+                                    };
+
+                                    send_message(&bot, channel_id, language.text(msg).to_owned());
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotUpdated,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error toggling protect_content")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/protect"]),
+                    );
+                }
+            } else if text.starts_with("/language") {
+                debug!("language");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+
+                    match Language::from_code(text.trim_left_matches("/language")) {
+                        Some(new_language) => {
+                            let bot = self.bot.clone();
+                            let languages = self.languages.clone();
+
+                            // Spawn a future that stores the channel's language. Its linked chats
+                            // follow it, so every cached language is looked up again.
+                            Arbiter::handle().spawn(
+                                self.db
+                                    .send(SetChannelLanguage {
+                                        channel_id,
+                                        language: new_language,
+                                    })
+                                    .then(flatten)
+                                    .then(move |res| match res {
+                                        Ok(chat_system) => {
+                                            let language = chat_system.language();
+                                            languages.borrow_mut().clear();
+
+                                            send_message(
+                                                &bot,
+                                                channel_id,
+                                                language.fill(
+                                                    Text::ChannelLanguageSet,
+                                                    &[&language.name()],
+                                                ),
+                                            );
+                                            Ok(())
+                                        }
+                                        Err(e) => {
+                                            TelegramActor::send_failure(
+                                                &bot,
+                                                channel_id,
+                                                &e,
+                                                language,
+                                                Text::ChannelNotUpdated,
+                                            );
+                                            Err(e)
+                                        }
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error setting language")),
+                            );
+                        }
+                        None => TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            &language_usage(language),
+                        ),
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/language"]),
+                    );
+                }
+            } else if text.starts_with("/timeformat") {
+                debug!("timeformat");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+
+                    match TimeFormat::from_name(text.trim_left_matches("/timeformat")) {
+                        Some(time_format) => {
+                            let bot = self.bot.clone();
+
+                            // Spawn a future that stores the channel's time format
+                            Arbiter::handle().spawn(
+                                self.db
+                                    .send(SetChannelTimeFormat {
+                                        channel_id,
+                                        time_format,
+                                    })
+                                    .then(flatten)
+                                    .then(move |res| match res {
+                                        Ok(chat_system) => {
+                                            send_message(
+                                                &bot,
+                                                channel_id,
+                                                language.fill(
+                                                    Text::ChannelTimeFormatSet,
+                                                    &[&chat_system.time_format().name()],
+                                                ),
+                                            );
+                                            Ok(())
+                                        }
+                                        Err(e) => {
+                                            TelegramActor::send_failure(
+                                                &bot,
+                                                channel_id,
+                                                &e,
+                                                language,
+                                                Text::ChannelNotUpdated,
+                                            );
+                                            Err(e)
+                                        }
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error setting time format")),
+                            );
+                        }
+                        None => TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::TimeFormatUsage),
+                        ),
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/timeformat"]),
+                    );
+                }
+            } else if text.starts_with("/moderate") {
+                debug!("moderate");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+
+                    // Spawn a future that toggles whether new events need an admin's approval
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(ToggleModerated { channel_id })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(moderated) => {
+                                    let msg = if moderated {
+                                        Text::Moderated
+                                    } else {
+                                        Text::Unmoderated
+                                    };
+
+                                    send_message(&bot, channel_id, language.text(msg).to_owned());
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotUpdated,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error toggling moderated")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/moderate"]),
+                    );
+                }
+            } else if text.starts_with("/unban") {
+                debug!("unban");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    match BannedUser::parse(text.trim_left_matches("/unban")) {
+                        None => TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::UnbanUsage),
+                        ),
+                        Some(user) => {
+                            let bot = self.bot.clone();
+                            let name = banned_name(&user, language);
+
+                            // Spawn a future that lets the user create events again
+                            Arbiter::handle().spawn(
+                                self.db
+                                    .send(UnbanHost { channel_id, user })
+                                    .then(flatten)
+                                    .then(move |res| match res {
+                                        Ok(unbanned) => {
+                                            let msg = if unbanned {
+                                                language.fill(Text::HostUnbanned, &[&name])
+                                            } else {
+                                                language.fill(Text::NotBanned, &[&name])
+                                            };
+
+                                            send_message(&bot, channel_id, msg);
+                                            Ok(())
+                                        }
+                                        Err(e) => {
+                                            TelegramActor::send_failure(
+                                                &bot,
+                                                channel_id,
+                                                &e,
+                                                language,
+                                                Text::UnbanFailed,
+                                            );
+                                            Err(e)
+                                        }
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error unbanning host")),
+                            );
+                        }
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/unban"]),
+                    );
+                }
+            } else if text.starts_with("/ban") {
+                debug!("ban");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    match BannedUser::parse(text.trim_left_matches("/ban")) {
+                        None => TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::BanUsage),
+                        ),
+                        Some(user) => {
+                            let bot = self.bot.clone();
+                            let name = banned_name(&user, language);
+                            let not_banned = match user {
+                                BannedUser::Username(_) => {
+                                    language.fill(Text::BanUsernameNotFound, &[&name])
+                                }
+                                BannedUser::UserId(_) => {
+                                    language.fill(Text::AlreadyBanned, &[&name])
+                                }
+                            };
+
+                            // Spawn a future that stops the user from creating events
+                            Arbiter::handle().spawn(
+                                self.db
+                                    .send(BanHost { channel_id, user })
+                                    .then(flatten)
+                                    .then(move |res| match res {
+                                        Ok(banned) => {
+                                            let msg = if banned {
+                                                language.fill(Text::HostBanned, &[&name])
+                                            } else {
+                                                not_banned
+                                            };
+
+                                            send_message(&bot, channel_id, msg);
+                                            Ok(())
+                                        }
+                                        Err(e) => {
+                                            TelegramActor::send_failure(
+                                                &bot,
+                                                channel_id,
+                                                &e,
+                                                language,
+                                                Text::BanFailed,
+                                            );
+                                            Err(e)
+                                        }
+                                    })
+                                    .map_err(log_err!(log_ctx, "Error banning host")),
+                            );
+                        }
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/ban"]),
+                    );
+                }
+            } else if text.starts_with("/digest") {
+                debug!("digest");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let arg = text.trim_left_matches("/digest").trim().to_lowercase();
+
+                    let digest_day = if arg == "off" {
+                        Some(None)
+                    } else {
+                        quick::parse_weekday(&arg).map(Some)
+                    };
+
+                    if let Some(digest_day) = digest_day {
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+
+                        // Spawn a future that updates the day the digest is posted on
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(SetDigestDay {
+                                    channel_id,
+                                    digest_day,
+                                })
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(_) => {
+                                        let msg = match digest_day {
+                                            Some(day) => language.fill(
+                                                Text::DigestDaySet,
+                                                &[&language.weekday(day)],
+                                            ),
+                                            None => language.text(Text::DigestOff).to_owned(),
+                                        };
+
+                                        send_message(&bot, channel_id, msg);
+                                        refresh_greeting(&bot, &db, channel_id);
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotUpdated,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error setting digest day")),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::DigestUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/digest"]),
+                    );
+                }
+            } else if text.starts_with("/pindigest") {
+                debug!("pindigest");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+                    let db = self.db.clone();
+
+                    // Spawn a future that toggles whether the digest is pinned
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(TogglePinDigest { channel_id })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(pinned) => {
+                                    let msg = if pinned {
+                                        Text::DigestPinned
+                                    } else {
+                                        Text::DigestUnpinned
+                                    };
+
+                                    send_message(&bot, channel_id, language.text(msg).to_owned());
+                                    refresh_greeting(&bot, &db, channel_id);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotUpdated,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error toggling pin_digest")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/pindigest"]),
+                    );
+                }
+            } else if text.starts_with("/pinannouncements") {
+                debug!("pinannouncements");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+                    let db = self.db.clone();
+
+                    // Spawn a future that toggles whether announcements are pinned
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(TogglePinAnnouncements { channel_id })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(pinned) => {
+                                    let msg = if pinned {
+                                        Text::AnnouncementsPinned
+                                    } else {
+                                        Text::AnnouncementsUnpinned
+                                    };
+
+                                    send_message(&bot, channel_id, language.text(msg).to_owned());
+                                    refresh_greeting(&bot, &db, channel_id);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotUpdated,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error toggling pin_announcements")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/pinannouncements"]),
+                    );
+                }
+            } else if text.starts_with("/directory") {
+                debug!("directory");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+                    let url = format!("{}/directory", self.url);
+
+                    // Spawn a future that toggles whether the channel is listed in the directory
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(ToggleListed {
+                                channel_id,
+                                title: message.chat.title.clone(),
+                                username: message.chat.username.clone(),
+                            })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(listed) => {
+                                    let msg = if listed {
+                                        language.fill(Text::Listed, &[&url])
+                                    } else {
+                                        language.text(Text::Unlisted).to_owned()
+                                    };
+
+                                    send_message(&bot, channel_id, msg);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotUpdated,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error toggling listed")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/directory"]),
+                    );
+                }
+            } else if text.starts_with("/setgreeting") {
+                debug!("setgreeting");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let arg = text.trim_left_matches("/setgreeting").trim();
+
+                    if arg.len() > 0 {
+                        let greeting = if arg.to_lowercase() == "off" {
+                            None
+                        } else {
+                            Some(arg.to_owned())
+                        };
+
+                        let turned_off = greeting.is_none();
+                        let bot = self.bot.clone();
+                        let db = self.db.clone();
+
+                        // Spawn a future that stores the greeting, then posts the new greeting
+                        // message in place of the old one
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupSystemByChannel(channel_id))
+                                .then(flatten)
+                                .and_then(move |old_system| {
+                                    let old_message_id = old_system.greeting_message_id();
+
+                                    db.send(SetGreeting {
+                                        channel_id,
+                                        greeting,
+                                    }).then(flatten)
+                                        .and_then(move |chat_system| {
+                                            replace_greeting(
+                                                &bot,
+                                                &db,
+                                                chat_system,
+                                                old_message_id,
+                                            ).then(move |res| match res {
+                                                Ok(()) => {
+                                                    if turned_off {
+                                                        let msg = language
+                                                            .text(Text::GreetingOff)
+                                                            .to_owned();
+
+                                                        send_message(&bot, channel_id, msg);
+                                                    }
+                                                    Ok(())
+                                                }
+                                                Err(e) => {
+                                                    TelegramActor::send_failure(
+                                                        &bot,
+                                                        channel_id,
+                                                        &e,
+                                                        language,
+                                                        Text::GreetingFailed,
+                                                    );
+                                                    Err(e)
+                                                }
+                                            })
+                                        })
+                                })
+                                .map_err(log_err!(log_ctx, "Error setting greeting")),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::GreetingUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/setgreeting"]),
+                    );
+                }
+            } else if text.starts_with("/template") {
+                debug!("template");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let args = text.trim_left_matches("/template").trim();
+                    let mut split = args.splitn(2, char::is_whitespace);
+                    let name = split.next().unwrap_or("");
+                    let body = split.next().unwrap_or("").trim();
+
+                    let kind = if body.len() > 0 {
+                        template::kind_from_name(name)
+                    } else {
+                        None
+                    };
+
+                    if args.len() == 0 {
+                        let bot = self.bot.clone();
+
+                        // Spawn a future that lists the channel's templates
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupSystemByChannel(channel_id))
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(chat_system) => {
+                                        send_message(
+                                            &bot,
+                                            channel_id,
+                                            template::describe(&chat_system, language),
+                                        );
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotFound,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error listing templates")),
+                        );
+                    } else if let Some(kind) = kind {
+                        let new_template = if body.to_lowercase() == "default" {
+                            Ok(None)
+                        } else {
+                            template::validate(body).map(|_| Some(body.to_owned()))
+                        };
+
+                        match new_template {
+                            Ok(new_template) => {
+                                let bot = self.bot.clone();
+
+                                // Spawn a future that stores the template
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(SetTemplate {
+                                            channel_id,
+                                            kind,
+                                            template: new_template,
+                                        })
+                                        .then(flatten)
+                                        .then(move |res| match res {
+                                            Ok(chat_system) => {
+                                                let msg = match chat_system.template(kind) {
+                                                    Some(_) => Text::TemplateSet,
+                                                    None => Text::TemplateReset,
+                                                };
+                                                let msg = language
+                                                    .fill(msg, &[&template::kind_name(kind)]);
+
+                                                send_message(&bot, channel_id, msg);
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    channel_id,
+                                                    &e,
+                                                    language,
+                                                    Text::ChannelNotUpdated,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error setting template")),
+                                );
+                            }
+                            Err(placeholder) => {
+                                TelegramActor::send_error(
+                                    &self.bot,
+                                    channel_id,
+                                    &language.fill(
+                                        Text::UnknownPlaceholder,
+                                        &[&placeholder, &template::placeholder_list()],
+                                    ),
+                                );
+                            }
+                        }
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::TemplateUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/template"]),
+                    );
+                }
+            } else if text.starts_with("/features") {
+                debug!("features");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let args = text.trim_left_matches("/features").trim();
+                    let mut split = args.split_whitespace();
+                    let feature = split.next().and_then(Feature::from_name);
+
+                    let enabled = match split.next().map(|state| state.to_lowercase()) {
+                        Some(ref state) if state == "on" => Some(true),
+                        Some(ref state) if state == "off" => Some(false),
+                        _ => None,
+                    };
+
+                    if args.len() == 0 {
+                        let bot = self.bot.clone();
+
+                        // Spawn a future that lists the channel's features
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(LookupSystemByChannel(channel_id))
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(chat_system) => {
+                                        send_message(
+                                            &bot,
+                                            channel_id,
+                                            describe_features(&chat_system.features(), language),
+                                        );
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotFound,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error listing features")),
+                        );
+                    } else if let (Some(feature), Some(enabled)) = (feature, enabled) {
+                        let bot = self.bot.clone();
+
+                        // Spawn a future that turns the feature on or off
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(SetFeature {
+                                    channel_id,
+                                    feature,
+                                    enabled,
+                                })
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(features) => {
+                                        send_message(
+                                            &bot,
+                                            channel_id,
+                                            describe_features(&features, language),
+                                        );
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotUpdated,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error setting feature")),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::FeaturesUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/features"]),
+                    );
+                }
+            } else if text.starts_with("/boost") {
+                debug!("boost");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let arg = text.trim_left_matches("/boost").trim().to_lowercase();
+
+                    let boost_threshold = if arg == "off" {
+                        Some(None)
+                    } else {
+                        match arg.parse::<i32>() {
+                            Ok(threshold) if threshold > 0 => Some(Some(threshold)),
+                            _ => None,
+                        }
+                    };
+
+                    if let Some(boost_threshold) = boost_threshold {
+                        let bot = self.bot.clone();
+
+                        // Spawn a future that updates how few RSVPs get an event boosted
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(SetBoostThreshold {
+                                    channel_id,
+                                    boost_threshold,
+                                })
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(()) => {
+                                        let msg = match boost_threshold {
+                                            Some(threshold) => {
+                                                language.fill(Text::BoostSet, &[&threshold])
+                                            }
+                                            None => language.text(Text::BoostOff).to_owned(),
+                                        };
+
+                                        send_message(&bot, channel_id, msg);
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotUpdated,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error setting boost threshold")),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::BoostUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/boost"]),
+                    );
+                }
+            } else if text.starts_with("/stats") {
+                debug!("stats");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+
+                    // This month starts at midnight on the first in the bot's timezone. Midnight
+                    // can be skipped by a daylight saving change, so fall back to now.
+                    let now = Utc::now().with_timezone(&self.timezone);
+                    let since = now.date()
+                        .with_day(1)
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                        .unwrap_or(now.clone());
+
+                    // Spawn a future that sends the channel's stats
+                    Arbiter::handle().spawn(
+                        self.db
+                            .send(GetStats { channel_id, since })
+                            .then(flatten)
+                            .then(move |res| match res {
+                                Ok(stats) => {
+                                    let msg = describe_stats(&stats, language);
+
+                                    send_message(&bot, channel_id, msg);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::ChannelNotFound,
+                                    );
+                                    Err(e)
+                                }
+                            })
+                            .map_err(log_err!(log_ctx, "Error getting stats")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/stats"]),
+                    );
+                }
+            } else if text.starts_with("/token") {
+                debug!("token");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    // Anyone who can read the channel would see a token posted here
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        language.text(Text::TokenInChannel),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/token"]),
+                    );
+                }
+            } else if text.starts_with("/calendar") {
+                debug!("calendar");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    if let Ok((base64d, secret)) = generate_secret() {
+                        let bot = self.bot.clone();
+                        let url = self.url.clone();
+
+                        // Spawn a future that replaces the channel's calendar link. The link only
+                        // shows public events, so it can be shared, but it has its own token so
+                        // sharing it doesn't give away API access.
+                        Arbiter::handle().spawn(
+                            self.db
+                                .send(SetCalendarSecret { channel_id, secret })
+                                .then(flatten)
+                                .then(move |res| match res {
+                                    Ok(system_id) => {
+                                        send_message(
+                                            &bot,
+                                            channel_id,
+                                            language.fill(
+                                                Text::CalendarLink,
+                                                &[&url, &base64d, &system_id],
+                                            ),
+                                        );
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        TelegramActor::send_failure(
+                                            &bot,
+                                            channel_id,
+                                            &e,
+                                            language,
+                                            Text::ChannelNotUpdated,
+                                        );
+                                        Err(e)
+                                    }
+                                })
+                                .map_err(log_err!(log_ctx, "Error setting calendar token")),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::CalendarFailed),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/calendar"]),
+                    );
+                }
+            } else if text.starts_with("/webhook") {
+                debug!("webhook");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let args = text.trim_left_matches("/webhook").trim().to_owned();
+
+                    if args.is_empty() {
+                        self.list_webhooks(log_ctx, language, channel_id);
+                    } else if args.starts_with("add ") {
+                        let url = args.trim_left_matches("add").trim().to_owned();
+
+                        self.add_webhook(log_ctx, language, channel_id, url);
+                    } else if args.starts_with("remove ") {
+                        let url = args.trim_left_matches("remove").trim().to_owned();
+
+                        self.remove_webhook(log_ctx, language, channel_id, url);
+                    } else if args == "secret" || args == "rotate" {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::SecretInChannel),
+                        );
+                    } else {
+                        TelegramActor::send_error(
+                            &self.bot,
+                            channel_id,
+                            language.text(Text::WebhookUsage),
+                        );
+                    }
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/webhook"]),
+                    );
+                }
+            } else if text.starts_with("/init") {
+                debug!("init");
+                let channel_id = message.chat.id;
+
+                if message.chat.kind == "channel" {
+                    debug!("channel");
+                    let bot = self.bot.clone();
+                    let db = self.db.clone();
+                    let db2 = self.db.clone();
+                    let channels = self.channels.clone();
+                    let title = message.chat.title.clone();
+                    let username = message.chat.username.clone();
+
+                    // Spawn a future that adds the given channel to the database, once it's clear
+                    // announcements can be posted
+                    Arbiter::handle().spawn(
+                        check_channel_rights(&bot, channel_id)
+                            .and_then(move |_| db.send(NewChannel { channel_id }).then(flatten))
+                            .map(move |chat_system| {
+                                channels.borrow_mut().remove_channel(channel_id);
+                                db2.do_send(SetChatTitle {
+                                    chat_id: channel_id,
+                                    title,
+                                    username,
+                                });
+                                chat_system
+                            })
+                            .then(move |res| match res {
+                                Ok(item) => Ok((item, bot)),
+                                Err(err) => Err((err, bot)),
+                            })
+                            .map(move |(_chat_system, bot)| {
+                                TelegramActor::created_channel(&bot, channel_id, language)
+                            })
+                            .map_err(move |(e, bot)| {
+                                TelegramActor::send_failure(
+                                    &bot,
+                                    channel_id,
+                                    &e,
+                                    language,
+                                    Text::InitFailed,
+                                );
+                                e
+                            })
+                            .map_err(log_err!(log_ctx, "Error creating channel")),
+                    );
+                } else {
+                    TelegramActor::send_error(
+                        &self.bot,
+                        channel_id,
+                        &language.fill(Text::ChannelsOnly, &[&"/init"]),
+                    );
+                }
+            }
+        }
+    }
+
+    fn handle_callback_query(
+        &mut self,
+        log_ctx: LogContext,
+        language: Language,
+        callback_query: CallbackQuery,
+    ) {
+        debug!("handle callback query");
+
+        let user_id = callback_query.from.id;
+        let query_id = callback_query.id;
+
+        if let Some(msg) = callback_query.message {
+            let chat_id = msg.chat.id;
+            let message_id = msg.message_id;
+
+            if let Some(data) = callback_query.data {
+                if let Ok(query_data) = serde_json::from_str::<CallbackQueryMessage>(&data) {
+                    // RSVPs, cancellations, and confirmations don't need a secret, so handle them
+                    // before generating one
+                    match query_data {
+                        CallbackQueryMessage::Rsvp { event_id } => {
+                            let from = callback_query.from;
+                            let username = from.username.unwrap_or(from.first_name);
+
+                            self.rsvp(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user_id,
+                                username,
+                                query_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::CancelRsvp { event_id } => {
+                            self.cancel_rsvp(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::QuickEvent { channel_id } => {
+                            self.create_quick_event(
+                                log_ctx, language, channel_id, user_id, chat_id, message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::DeleteEvent {
+                            event_id,
+                            system_id,
+                        } => {
+                            self.confirm_delete(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                system_id,
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::ConfirmDelete { event_id, .. } => {
+                            self.ask_really(
+                                log_ctx.event_id(event_id),
+                                language,
+                                Destructive::DeleteEvent { event_id },
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::Really(action) => {
+                            self.carry_out(log_ctx, language, action, user_id, chat_id);
+                            return;
+                        }
+                        CallbackQueryMessage::Nevermind => {
+                            // The user may be backing out of messaging an event's attendees
+                            self.broadcasts.borrow_mut().remove(&user_id);
+                            self.nevermind(log_ctx, language, chat_id, message_id);
+                            return;
+                        }
+                        CallbackQueryMessage::CancelEvent { event_id } => {
+                            self.cancel_event(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user_id,
+                                chat_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::TransferEvent { event_id } => {
+                            self.ask_new_host(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user_id,
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::TransferTo {
+                            event_id,
+                            user_id: new_host_id,
+                        } => {
+                            self.transfer_event(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                new_host_id,
+                                user_id,
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::Broadcast { event_id } => {
+                            self.ask_broadcast(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                user_id,
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::Mute { ref name, muted } => {
+                            if let Some(notification) = Notification::from_name(name) {
+                                self.toggle_notification(
+                                    log_ctx,
+                                    language,
+                                    notification,
+                                    muted,
+                                    user_id,
+                                    chat_id,
+                                    message_id,
+                                );
+                            }
+                            return;
+                        }
+                        CallbackQueryMessage::Review { event_id, approved } => {
+                            self.review(
+                                log_ctx.event_id(event_id),
+                                language,
+                                event_id,
+                                approved,
+                                user_id,
+                                chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::ApiToken {
+                            channel_id,
+                            read_only,
+                        } => {
+                            self.issue_api_token(
+                                log_ctx, language, channel_id, read_only, user_id, chat_id,
+                                message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::WebhookSecret { channel_id } => {
+                            self.send_webhook_secret(
+                                log_ctx, language, channel_id, user_id, chat_id, message_id,
+                            );
+                            return;
+                        }
+                        CallbackQueryMessage::RotateWebhookSecret { channel_id } => {
+                            self.rotate_webhook_secret(
+                                log_ctx, language, channel_id, user_id, chat_id, message_id,
+                            );
+                            return;
+                        }
+                        _ => (),
+                    }
+
+                    if let Ok((base64d, secret)) = generate_secret() {
+                        let db = self.db.clone();
+                        let db2 = self.db.clone();
+                        let db3 = self.db.clone();
+                        let bot = self.bot.clone();
+                        let users = self.users.clone();
+
+                        let url = self.url.clone();
+                        let timezone = self.timezone;
+                        match query_data {
+                            CallbackQueryMessage::NewEvent { channel_id } => {
+                                // Spawn a future that creates a new event
+                                debug!("channel_id: {}", channel_id);
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(LookupUser(user_id))
+                                        .then(flatten)
+                                        .and_then(move |user| {
+                                            db.send(LookupSystemByChannel(channel_id))
+                                                .then(flatten)
+                                                .map(|chat_system| (chat_system, user))
+                                        })
+                                        .and_then(move |(chat_system, user)| {
+                                            let events_channel = chat_system.events_channel();
+                                            users
+                                                .send(LookupChannels(user.user_id()))
+                                                .then(flatten)
+                                                .and_then(move |channel_ids| {
+                                                    if channel_ids.contains(&events_channel) {
+                                                        Ok(())
+                                                    } else {
+                                                        Err(EventErrorKind::Permissions.into())
+                                                    }
+                                                })
+                                                .and_then(move |_| {
+                                                    // Channel admins can ban users from
+                                                    // creating events
+                                                    db2.send(CheckBanned {
+                                                        system_id: chat_system.id(),
+                                                        user_id: user.user_id(),
+                                                    }).then(flatten)
+                                                        .and_then(|banned| {
+                                                            if banned {
+                                                                Err(EventErrorKind::Banned.into())
+                                                            } else {
+                                                                Ok(())
+                                                            }
+                                                        })
+                                                        .and_then(move |_| {
+                                                            db2.send(StoreEventLink {
+                                                                user_id: user.id(),
+                                                                system_id: chat_system.id(),
+                                                                secret,
+                                                            }).then(flatten)
+                                                        })
+                                                })
+                                        })
+                                        .then(move |nel| match nel {
+                                            Ok(nel) => {
+                                                let nel_id = nel.id();
+
+                                                TelegramActor::edit_with_url(
+                                                    &bot,
+                                                    chat_id,
+                                                    message_id,
+                                                    language,
+                                                    Text::NewEventLink,
+                                                    format!(
+                                                        "{}/events/new/{}={}",
+                                                        url, base64d, nel_id
+                                                    ),
+                                                    nel.expires_at(),
+                                                    timezone,
+                                                );
+
+                                                TelegramActor::offer_new_link(
+                                                    &bot,
+                                                    chat_id,
+                                                    message_id,
+                                                    language,
+                                                    Text::NewEventLinkExpired,
+                                                    nel.expires_at(),
+                                                    move || {
+                                                        db3.send(EventLinkUnused(nel_id))
+                                                            .then(flatten)
+                                                    },
+                                                    CallbackQueryMessage::NewEvent {
+                                                        channel_id,
+                                                    },
+                                                );
+
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::NewLinkFailed,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(
+                                            log_ctx,
+                                            "Error creating event link"
+                                        )),
+                                );
+                            }
+                            CallbackQueryMessage::EditEvent { event_id } => {
+                                let log_ctx = log_ctx.event_id(event_id);
+
+                                // Spawn a future that updates a given event
+                                Arbiter::handle().spawn(
+                                    self.db
+                                        .send(LookupEvent { event_id })
+                                        .then(flatten)
+                                        .and_then(move |event| {
+                                            if event
+                                                .hosts()
+                                                .iter()
+                                                .any(|host| host.user_id() == user_id)
+                                            {
+                                                Ok(event)
+                                            } else {
+                                                Err(EventErrorKind::Lookup.into())
+                                            }
+                                        })
+                                        .and_then(move |event| {
+                                            let e2 = event.clone();
+                                            let host = e2.hosts()
+                                                .iter()
+                                                .find(|host| host.user_id() == user_id)
+                                                .unwrap();
+
+                                            db2.send(StoreEditEventLink {
+                                                user_id: host.id(),
+                                                system_id: event.system_id(),
+                                                event_id: event.id(),
+                                                secret,
+                                            }).then(flatten)
+                                        })
+                                        .then(move |eel| match eel {
+                                            Ok(eel) => {
+                                                let eel_id = eel.id();
+
+                                                TelegramActor::edit_with_url(
+                                                    &bot,
+                                                    chat_id,
+                                                    message_id,
+                                                    language,
+                                                    Text::EditEventLink,
+                                                    format!(
+                                                        "{}/events/edit/{}={}",
+                                                        url, base64d, eel_id
+                                                    ),
+                                                    eel.expires_at(),
+                                                    timezone,
+                                                );
+
+                                                TelegramActor::offer_new_link(
+                                                    &bot,
+                                                    chat_id,
+                                                    message_id,
+                                                    language,
+                                                    Text::EditEventLinkExpired,
+                                                    eel.expires_at(),
+                                                    move || {
+                                                        db3.send(EditEventLinkUnused(eel_id))
+                                                            .then(flatten)
+                                                    },
+                                                    CallbackQueryMessage::EditEvent { event_id },
+                                                );
+
+                                                Ok(())
+                                            }
+                                            Err(e) => {
+                                                TelegramActor::send_failure(
+                                                    &bot,
+                                                    chat_id,
+                                                    &e,
+                                                    language,
+                                                    Text::EditLinkFailed,
+                                                );
+                                                Err(e)
+                                            }
+                                        })
+                                        .map_err(log_err!(log_ctx, "Error creating edit link")),
+                                );
+                            }
+                            CallbackQueryMessage::Rsvp { .. }
+                            | CallbackQueryMessage::CancelRsvp { .. }
+                            | CallbackQueryMessage::DeleteEvent { .. }
+                            | CallbackQueryMessage::ConfirmDelete { .. }
+                            | CallbackQueryMessage::Really(_)
+                            | CallbackQueryMessage::Nevermind
+                            | CallbackQueryMessage::CancelEvent { .. }
+                            | CallbackQueryMessage::TransferEvent { .. }
+                            | CallbackQueryMessage::TransferTo { .. }
+                            | CallbackQueryMessage::Broadcast { .. }
+                            | CallbackQueryMessage::QuickEvent { .. }
+                            | CallbackQueryMessage::Mute { .. }
+                            | CallbackQueryMessage::Review { .. } => (),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Answer an inline query, like `@eventbot pizza`, with the matching upcoming events
+    ///
+    /// Only events from channels linked to the user's chats are offered. Picking one posts its
+    /// card to the chat the user is typing in.
+    fn handle_inline_query(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        inline_query: InlineQuery,
+    ) {
+        debug!("handle inline query");
+        let bot = self.bot.clone();
+        let url = self.url.clone();
+        let timezone = self.timezone;
+        let inline_query_id = inline_query.id;
+
+        let fut = self.db
+            .send(SearchEvents {
+                scope: SearchScope::User(inline_query.from.id),
+                terms: inline_query.query,
+            })
+            .then(flatten)
+            .and_then(move |events| {
+                let results = events
+                    .iter()
+                    .map(|event| InlineArticle::from_event(event, &url, timezone, language))
+                    .collect();
+
+                let body = serde_json::to_string(&InlineQueryAnswer {
+                    inline_query_id,
+                    results,
+                    cache_time: INLINE_CACHE_TIME,
+                    is_personal: true,
+                }).unwrap();
+
+                bot.inner
+                    .fetch_json("answerInlineQuery", &body)
+                    .map(|_| ())
+                    .map_err(telegram_error)
+            })
+            .map_err(log_err!(log_ctx, "Error answering inline query"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Register a URL to be notified when the channel's events change
+    ///
+    /// All of a channel's webhooks are signed with the same secret, which is only sent privately
+    /// to the channel's admins. Unlike API tokens, the secret is stored as-is, since it's needed to
+    /// sign every request.
+    fn add_webhook(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        url: String,
+    ) {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            TelegramActor::send_error(&self.bot, channel_id, language.text(Text::WebhookScheme));
+            return;
+        }
+
+        let secret = match generate_secret() {
+            Ok((token, _)) => token,
+            Err(_) => {
+                TelegramActor::send_error(&self.bot, channel_id, language.text(Text::SecretFailed));
+                return;
+            }
+        };
+
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let docs_url = format!("{}/docs/webhooks", self.url);
+
+        // The URL's name is looked up without holding up the actor
+        Arbiter::handle().spawn(
+            public_url(&url)
+                .then(move |public| match public {
+                    Ok(true) => Either::A(
+                        db.send(NewWebhook {
+                            channel_id,
+                            url,
+                            secret,
+                        }).then(flatten)
+                            .then(move |res| match res {
+                                Ok(webhook) => {
+                                    let msg = language
+                                        .fill(Text::WebhookAdded, &[&webhook.url(), &docs_url]);
+
+                                    send_message(&bot, channel_id, msg);
+                                    Ok(())
+                                }
+                                Err(e) => {
+                                    TelegramActor::send_failure(
+                                        &bot,
+                                        channel_id,
+                                        &e,
+                                        language,
+                                        Text::WebhookAddFailed,
+                                    );
+                                    Err(e)
+                                }
+                            }),
+                    ),
+                    _ => {
+                        let text = language.text(Text::WebhookPublic);
+
+                        TelegramActor::send_error(&bot, channel_id, text);
+                        Either::B(Ok(()).into_future())
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error adding webhook")),
+        );
+    }
+
+    /// Replace the secret the channel's webhooks are signed with, like when the old one was
+    /// leaked, and send the new one privately to the admin who asked
+    fn rotate_webhook_secret(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let secret = match generate_secret() {
+            Ok((token, _)) => token,
+            Err(_) => {
+                TelegramActor::send_error(&self.bot, chat_id, language.text(Text::SecretFailed));
+                return;
+            }
+        };
+
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+
+        Arbiter::handle().spawn(
+            channel_admin(&self.bot, channel_id, user_id)
+                .and_then(|admin| {
+                    if admin {
+                        Ok(())
+                    } else {
+                        Err(EventErrorKind::Permissions.into())
+                    }
+                })
+                .and_then(move |_| {
+                    db.send(RotateWebhookSecret {
+                        channel_id,
+                        secret: secret.clone(),
+                    }).then(flatten)
+                        .map(move |rotated| (rotated, secret))
+                })
+                .then(move |res| match res {
+                    Ok((rotated, secret)) => {
+                        let text = if rotated {
+                            language.fill(Text::SecretRotated, &[&secret])
+                        } else {
+                            language.text(Text::NoWebhooks).to_owned()
+                        };
+
+                        Either::A(
+                            bot.edit_message_text(text)
+                                .chat_id(chat_id)
+                                .message_id(message_id)
+                                .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                                .send()
+                                .map(|_| ())
+                                .map_err(telegram_error),
+                        )
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            chat_id,
+                            &e,
+                            language,
+                            Text::RotateAdminsOnly,
+                        );
+                        Either::B(Err(e).into_future())
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error replacing webhook secret")),
+        );
+    }
+
+    fn remove_webhook(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        url: String,
+    ) {
+        let bot = self.bot.clone();
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(DeleteWebhook {
+                    channel_id,
+                    url: url.clone(),
+                })
+                .then(flatten)
+                .then(move |res| match res {
+                    Ok(deleted) => {
+                        let msg = if deleted {
+                            language.fill(Text::WebhookRemoved, &[&url])
+                        } else {
+                            language.fill(Text::NotAWebhook, &[&url])
+                        };
+
+                        send_message(&bot, channel_id, msg);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            channel_id,
+                            &e,
+                            language,
+                            Text::WebhookRemoveFailed,
+                        );
+                        Err(e)
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error removing webhook")),
+        );
+    }
+
+    fn list_webhooks(&self, log_ctx: LogContext, language: Language, channel_id: Integer) {
+        let bot = self.bot.clone();
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(GetWebhooksForChannel { channel_id })
+                .then(flatten)
+                .then(move |res| match res {
+                    Ok(webhooks) => {
+                        let msg = if webhooks.is_empty() {
+                            language.text(Text::NoWebhooks).to_owned()
+                        } else {
+                            let urls = webhooks
+                                .iter()
+                                .map(|webhook| webhook.url())
+                                .collect::<Vec<_>>()
+                                .join("\n");
+
+                            language.fill(Text::WebhookList, &[&urls])
+                        };
+
+                        send_message(&bot, channel_id, msg);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            channel_id,
+                            &e,
+                            language,
+                            Text::WebhooksLookupFailed,
+                        );
+                        Err(e)
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error looking up webhooks")),
+        );
+    }
+
+    /// Create the event the user described with `/quick` in the channel they picked
+    ///
+    /// The Timer picks the new event up the next time it checks for upcoming events
+    fn create_quick_event(
+        &mut self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let ttl = Duration::from_secs(QUICK_EVENT_TTL);
+
+        let quick_event = match self.quick_events.remove(&user_id) {
+            Some((created, quick_event)) if created.elapsed() < ttl => quick_event,
+            _ => {
+                TelegramActor::send_error(
+                    &self.bot,
+                    chat_id,
+                    language.text(Text::QuickExpired),
+                );
+                return;
+            }
+        };
+
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let db3 = self.db.clone();
+        let db4 = self.db.clone();
+        let users = self.users.clone();
+        let webhooks = self.webhooks.clone();
+        let timer = self.timer.clone();
+        let address = self.address.clone();
+        let send_ctx = log_ctx.clone();
+        let url = self.url.clone();
+        let timezone = self.timezone;
+
+        let fut = self.db
+            .send(LookupUser(user_id))
+            .then(flatten)
+            .and_then(move |user| {
+                db.send(LookupSystemByChannel(channel_id))
+                    .then(flatten)
+                    .map(|chat_system| (chat_system, user))
+            })
+            .and_then(move |(chat_system, user)| {
+                let events_channel = chat_system.events_channel();
+                let system_id = chat_system.id();
+
+                users
+                    .send(LookupChannels(user.user_id()))
+                    .then(flatten)
+                    .and_then(move |channel_ids| {
+                        if channel_ids.contains(&events_channel) {
+                            Ok(())
+                        } else {
+                            Err(EventErrorKind::Permissions.into())
+                        }
+                    })
+                    .and_then(move |_| {
+                        // Channel admins can ban users from creating events
+                        db4.send(CheckBanned {
+                            system_id,
+                            user_id,
+                        }).then(flatten)
+                            .and_then(|banned| {
+                                if banned {
+                                    Err(EventErrorKind::Banned.into())
+                                } else {
+                                    Ok(())
+                                }
+                            })
+                    })
+                    .and_then(move |_| {
+                        db2.send(DbNewEvent {
+                            system_id: chat_system.id(),
+                            title: quick_event.title,
+                            description: quick_event.description,
+                            start_date: quick_event.start_date,
+                            end_date: quick_event.end_date,
+                            hosts: vec![user.id()],
+                            publish_at: None,
+                            capacity: None,
+                            boost_threshold: None,
+                            location: None,
+                            location_hint: None,
+                            coordinates: None,
+                            image_url: None,
+                            rsvp_deadline: None,
+                            members_only: false,
+                            unlisted: false,
+                            needs_approval: true,
+                            link_id: None,
+                        }).then(flatten)
+                            .map(|(event, _)| event)
+                    })
+            })
+            .then(move |event| match event {
+                Ok(event) => {
+                    let text = if event.pending() {
+                        language.fill(Text::CreatedPending, &[&event.title()])
+                    } else {
+                        language.fill(Text::Created, &[&event.title()])
+                    };
+
+                    bot.inner.handle.spawn(
+                        bot.edit_message_text(text)
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                            .send()
+                            .map(|_| ())
+                            .map_err(log_err!(send_ctx, "Error sending message to Telegram")),
+                    );
+
+                    if event.pending() {
+                        if let Some(address) = address {
+                            address.do_send(ReviewEvent(event));
+                        }
+                        Ok(())
+                    } else {
+                        if let Some(timer) = timer {
+                            timer.do_send(Events {
+                                events: vec![event.clone()],
+                            });
+                        }
+
+                        webhooks.do_send(EventChanged {
+                            action: EventAction::Created,
+                            event: event.clone(),
+                        });
+
+                        Ok(TelegramActor::announce_event(bot, db3, event, &url, timezone))
+                    }
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::CreateEventFailed,
+                    );
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error creating quick event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// RSVP a user to an event
+    ///
+    /// The user is told how it went in a private message, except when the RSVP deadline has
+    /// passed, which is shown on the button they pressed
+    ///
+    /// Like `/new`, users the bot hasn't seen post yet are looked for in the linked chats before
+    /// they're turned away from members only events
+    fn rsvp(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+        username: String,
+        query_id: String,
+    ) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let db = self.db.clone();
+        let db3 = self.db.clone();
+        let db2 = self.db.clone();
+        let users = self.users.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                if event.cancelled() || event.rsvps_closed() {
+                    Either::B(Ok((event, true, false)).into_future())
+                } else {
+                    // Channels can turn RSVPs off
+                    Either::A(
+                        db.send(LookupSystem {
+                            system_id: event.system_id(),
+                        }).then(flatten)
+                            .and_then(move |chat_system| {
+                                let enabled = chat_system.features().enabled(Feature::Rsvp);
+
+                                if enabled && event.members_only() {
+                                    // Only users in a chat linked to the event's channel are
+                                    // members
+                                    let channel_id = chat_system.events_channel();
+
+                                    Either::A(
+                                        users
+                                            .send(LookupChannels(user_id))
+                                            .then(flatten)
+                                            .and_then(move |channels| {
+                                                if channels.contains(&channel_id) {
+                                                    Either::A(Ok(channels).into_future())
+                                                } else {
+                                                    Either::B(TelegramActor::sync_membership(
+                                                        bot2, db3, users, user_id, username,
+                                                    ))
+                                                }
+                                            })
+                                            .map(move |channels| {
+                                                (event, true, channels.contains(&channel_id))
+                                            }),
+                                    )
+                                } else {
+                                    Either::B(Ok((event, enabled, true)).into_future())
+                                }
+                            }),
+                    )
+                }
+            })
+            .and_then(move |(event, enabled, is_member)| {
+                let can_rsvp = enabled && is_member && !event.cancelled() && !event.rsvps_closed();
+
+                if can_rsvp {
+                    Either::A(
+                        db2.send(LookupUser(user_id))
+                            .then(flatten)
+                            .and_then(move |user| {
+                                db2.send(Rsvp {
+                                    event_id,
+                                    user_id: user.id(),
+                                }).then(flatten)
+                            })
+                            .map(move |status| (Some(status), event, enabled)),
+                    )
+                } else {
+                    Either::B(Ok((None, event, enabled)).into_future())
+                }
+            })
+            .then(move |res| match res {
+                Ok((Some(status), event, _)) => {
+                    let msg = match status {
+                        RsvpStatus::Going => language.fill(
+                            Text::Going,
+                            &[&event.title(), &format_private_location(&event, language)],
+                        ),
+                        RsvpStatus::Waitlisted => {
+                            language.fill(Text::Waitlisted, &[&event.title()])
+                        }
+                        RsvpStatus::Unchanged => {
+                            language.fill(Text::AlreadyResponded, &[&event.title()])
+                        }
+                    };
+
+                    send_message(&bot, user_id, msg);
+                    Ok(())
+                }
+                Ok((None, event, enabled)) => {
+                    if event.cancelled() {
+                        let msg = language.fill(Text::RsvpCancelled, &[&event.title()]);
+
+                        send_message(&bot, user_id, msg);
+                    } else if event.rsvps_closed() {
+                        // The buttons stay up until the Timer takes them down, so late presses
+                        // are expected
+                        answer_callback_query(
+                            &bot,
+                            query_id,
+                            language.fill(Text::RsvpsClosed, &[&event.title()]),
+                        );
+                    } else if !enabled {
+                        answer_callback_query(
+                            &bot,
+                            query_id,
+                            language.text(Text::RsvpsOff).to_owned(),
+                        );
+                    } else {
+                        let msg = language.fill(Text::RsvpMembersOnly, &[&event.title()]);
+
+                        send_message(&bot, user_id, msg);
+                    }
+
+                    Ok(())
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(&bot, user_id, &e, language, Text::RsvpFailed);
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error handling RSVP"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    fn cancel_rsvp(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+
+        let fut = self.db
+            .send(LookupUser(user_id))
+            .then(flatten)
+            .and_then(move |user| {
+                db.send(CancelRsvp {
+                    event_id,
+                    user_id: user.id(),
+                }).then(flatten)
+                    .join(db.send(LookupEvent { event_id }).then(flatten))
+            })
+            .then(move |res| match res {
+                Ok((promoted, event)) => {
+                    send_message(
+                        &bot,
+                        user_id,
+                        language.fill(Text::NotGoing, &[&event.title()]),
+                    );
+
+                    TelegramActor::notify_promoted(&bot, &db2, &event, promoted);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        user_id,
+                        &e,
+                        language,
+                        Text::CancelRsvpFailed,
+                    );
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error cancelling RSVP"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Promote users from an event's waitlist if there's room for them now, like after the event's
+    /// capacity was raised
+    fn fill_waitlist(&self, event: Event) {
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+
+        let fut = self.db
+            .send(FillWaitlist {
+                event_id: event.id(),
+            })
+            .then(flatten)
+            .map(move |promoted| TelegramActor::notify_promoted(&bot, &db, &event, promoted))
+            .map_err(log_err!(log_ctx, "Error filling waitlist"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Tell users promoted from an event's waitlist that they're going
+    fn notify_promoted(
+        bot: &RcBot,
+        db: &Addr<Unsync, DbBroker>,
+        event: &Event,
+        promoted: Vec<User>,
+    ) {
+        for user in promoted {
+            let event = event.clone();
+
+            send_notification(
+                bot,
+                db,
+                user.user_id(),
+                Notification::WaitlistPromotions,
+                move |language| {
+                    language.fill(
+                        Text::Promoted,
+                        &[&event.title(), &format_private_location(&event, language)],
+                    )
+                },
+            );
+        }
+    }
+
+    /// Ask the host to confirm deleting an event, replacing the list of events they picked it from
+    ///
+    /// The confirmation lists the RSVPs that would be deleted along with the event, and offers to
+    /// cancel the event instead
+    fn confirm_delete(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        system_id: i32,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .join(self.db.send(GetAttendees { event_id }).then(flatten))
+            .then(move |res| match res {
+                Ok((event, (going, waitlisted))) => {
+                    let mut buttons =
+                        vec![
+                            InlineKeyboardButton::new(language.text(Text::DeleteIt).to_owned())
+                                .callback_data(
+                                    serde_json::to_string(&CallbackQueryMessage::ConfirmDelete {
+                                        event_id,
+                                        system_id,
+                                    }).unwrap(),
+                                ),
+                        ];
+
+                    if !event.cancelled() {
+                        let cancel_instead = language.text(Text::CancelInstead).to_owned();
+
+                        buttons.push(
+                            InlineKeyboardButton::new(cancel_instead)
+                                .callback_data(
+                                    serde_json::to_string(&CallbackQueryMessage::CancelEvent {
+                                        event_id,
+                                    }).unwrap(),
+                                ),
+                        );
+                    }
+
+                    let text = format_delete_confirmation(
+                        event.title(),
+                        event.cancelled(),
+                        &going,
+                        &waitlisted,
+                        language,
+                    );
+
+                    Either::A(
+                        bot.edit_message_text(text)
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .reply_markup(InlineKeyboardMarkup::new(vec![buttons]))
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::EventLookupFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error confirming deletion"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Ask the user whether they're sure about a destructive action, replacing the message they
+    /// picked it from
+    ///
+    /// The action is only carried out if they answer yes
+    fn ask_really(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        action: Destructive,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+
+        let question = match action {
+            Destructive::DeleteEvent { event_id } => self.db
+                .send(LookupEvent { event_id })
+                .then(flatten)
+                .map(move |event| language.fill(Text::ReallyDelete, &[&event.title()])),
+        };
+
+        let fut = question
+            .then(move |res| match res {
+                Ok(question) => {
+                    let buttons = vec![
+                        InlineKeyboardButton::new(language.text(Text::Yes).to_owned())
+                            .callback_data(
+                                serde_json::to_string(&CallbackQueryMessage::Really(action))
+                                    .unwrap(),
+                            ),
+                        InlineKeyboardButton::new(language.text(Text::No).to_owned()).callback_data(
+                            serde_json::to_string(&CallbackQueryMessage::Nevermind).unwrap(),
+                        ),
+                    ];
+
+                    Either::A(
+                        bot.edit_message_text(question)
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .reply_markup(InlineKeyboardMarkup::new(vec![buttons]))
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::EventLookupFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error asking for confirmation"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Carry out a destructive action the user said they're sure about
+    fn carry_out(
+        &mut self,
+        log_ctx: LogContext,
+        language: Language,
+        action: Destructive,
+        user_id: Integer,
+        chat_id: Integer,
+    ) {
+        match action {
+            Destructive::DeleteEvent { event_id } => {
+                self.delete_event(log_ctx.event_id(event_id), language, event_id, chat_id)
+            }
+            Destructive::ForgetMe => self.forget_user(log_ctx, language, user_id, chat_id),
+        }
+    }
+
+    /// Ask the user whether they're sure they want the bot to forget them
+    fn ask_forget(&self, log_ctx: LogContext, language: Language, chat_id: Integer) {
+        let buttons = vec![
+            InlineKeyboardButton::new(language.text(Text::Yes).to_owned()).callback_data(
+                serde_json::to_string(&CallbackQueryMessage::Really(Destructive::ForgetMe))
+                    .unwrap(),
+            ),
+            InlineKeyboardButton::new(language.text(Text::No).to_owned()).callback_data(
+                serde_json::to_string(&CallbackQueryMessage::Nevermind).unwrap(),
+            ),
+        ];
+
+        let fut = self.bot
+            .message(chat_id, language.text(Text::ReallyForget).to_owned())
+            .reply_markup(InlineKeyboardMarkup::new(vec![buttons]))
+            .send()
+            .map(|_| ())
+            .map_err(log_err!(log_ctx, "Error asking to forget user"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Send the user a menu with a button for each kind of private message, which turns it on or
+    /// off
+    fn notifications_menu(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        user_id: Integer,
+        chat_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+
+        let fut = self.db
+            .send(LookupPreferences(user_id))
+            .then(flatten)
+            .then(move |res| match res {
+                Ok(preferences) => Either::A(
+                    bot.message(chat_id, language.text(Text::NotificationsMenu).to_owned())
+                        .reply_markup(notifications_keyboard(&preferences, language))
+                        .send()
+                        .map(|_| ())
+                        .map_err(telegram_error),
+                ),
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::NotificationsLookupFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error sending notifications menu"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Mute or unmute a notification from the `/notifications` menu, updating its buttons
+    fn toggle_notification(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        notification: Notification,
+        muted: bool,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+
+        let fut = self.db
+            .send(SetMuted {
+                user_id,
+                notification: Some(notification),
+                muted,
+            })
+            .then(flatten)
+            .then(move |res| match res {
+                Ok(preferences) => Either::A(
+                    bot.edit_message_text(language.text(Text::NotificationsMenu).to_owned())
+                        .chat_id(chat_id)
+                        .message_id(message_id)
+                        .reply_markup(notifications_keyboard(&preferences, language))
+                        .send()
+                        .map(|_| ())
+                        .map_err(telegram_error),
+                ),
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::UserNotFound,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error toggling notification"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Send the user a JSON document with everything stored about them
+    fn export_user(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        user_id: Integer,
+        chat_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let timezone = self.timezone;
+
+        let fut = self.db
+            .send(ExportUser(user_id))
+            .then(flatten)
+            .then(move |res| match res {
+                Ok(data) => {
+                    let document = export::render(&data, timezone);
+
+                    Either::A(
+                        bot.document(chat_id)
+                            .file((export::FILE_NAME, Cursor::new(document.into_bytes())))
+                            .caption(language.text(Text::ExportCaption))
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::NothingStored,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error exporting user"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Delete everything stored about the user, and tell them what was deleted
+    ///
+    /// Anything the bot was holding on to while the user finished a command is dropped too
+    fn forget_user(
+        &mut self,
+        log_ctx: LogContext,
+        language: Language,
+        user_id: Integer,
+        chat_id: Integer,
+    ) {
+        self.quick_events.remove(&user_id);
+        self.broadcasts.borrow_mut().remove(&user_id);
+        self.users.do_send(RemoveUser(user_id));
+
+        let bot = self.bot.clone();
+
+        let fut = self.db
+            .send(ForgetUser(user_id))
+            .then(flatten)
+            .then(move |res| match res {
+                Ok(forgotten) => {
+                    let msg = language.fill(
+                        Text::Forgotten,
+                        &[
+                            &forgotten.chats(),
+                            &forgotten.hosted(),
+                            &forgotten.rsvps(),
+                            &forgotten.links(),
+                        ],
+                    );
+
+                    send_message(&bot, chat_id, msg);
+                    Ok(())
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::NothingStored,
+                    );
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error forgetting user"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Let the user know nothing happened when they back out of an action
+    fn nevermind(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let fut = self.bot
+            .edit_message_text(language.text(Text::NothingChanged).to_owned())
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+            .send()
+            .map(|_| ())
+            .map_err(log_err!(log_ctx, "Error backing out of action"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Ask a host for the message to send to everyone going to their event
+    ///
+    /// The host's next message in the private chat is the one that gets sent
+    fn ask_broadcast(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let broadcasts = self.broadcasts.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Users can only message the attendees of events they host
+                if event.hosts().iter().any(|host| host.user_id() == user_id) {
+                    broadcasts.borrow_mut().insert(user_id, event_id);
+                    Ok(event)
+                } else {
+                    Err(EventErrorKind::Permissions.into())
+                }
+            })
+            .then(move |res| match res {
+                Ok(event) => {
+                    let buttons = vec![vec![
+                        InlineKeyboardButton::new(language.text(Text::Nevermind).to_owned())
+                            .callback_data(
+                                serde_json::to_string(&CallbackQueryMessage::Nevermind).unwrap(),
+                            ),
+                    ]];
+
+                    let text = language.fill(Text::AskBroadcast, &[&event.title()]);
+
+                    Either::A(
+                        bot.edit_message_text(text)
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .reply_markup(InlineKeyboardMarkup::new(buttons))
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::EventLookupFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error asking for a message to attendees"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Send a host's message to everyone going to their event
+    ///
+    /// Attendees who muted broadcasts with `/mute` are skipped. The messages are handed to the
+    /// `FanOutActor` to stay under Telegram's rate limits, and each event's attendees can only be
+    /// messaged once every `BROADCAST_INTERVAL` seconds
+    fn broadcast(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+        chat_id: Integer,
+        text: String,
+    ) {
+        let now = Instant::now();
+        let interval = Duration::from_secs(BROADCAST_INTERVAL);
+
+        let ready_at = self.last_broadcasts
+            .borrow()
+            .get(&event_id)
+            .map(|last_broadcast| *last_broadcast + interval);
+
+        if let Some(ready_at) = ready_at {
+            if ready_at > now {
+                let minutes = (ready_at - now).as_secs() / 60 + 1;
+
+                TelegramActor::send_error(
+                    &self.bot,
+                    chat_id,
+                    &language.fill(Text::BroadcastTooSoon, &[&(BROADCAST_INTERVAL / 60), &minutes]),
+                );
+                return;
+            }
+        }
+
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let fan_out = self.fan_out.clone();
+        let last_broadcasts = self.last_broadcasts.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Users can only message the attendees of events they host
+                if event.hosts().iter().any(|host| host.user_id() == user_id) {
+                    last_broadcasts.borrow_mut().insert(event_id, now);
+                    Ok(event)
+                } else {
+                    Err(EventErrorKind::Permissions.into())
+                }
+            })
+            .and_then(move |event| {
+                let system_id = event.system_id();
+
+                db.send(GetRecipients {
+                    event_id,
+                    notification: Notification::Broadcasts,
+                })
+                    .then(flatten)
+                    .join(db.send(LookupSystem { system_id }).then(flatten))
+                    .map(move |(recipients, chat_system)| (event, recipients, chat_system))
+            })
+            .then(move |res| match res {
+                Ok((event, recipients, chat_system)) => {
+                    // Hosts who are going themselves don't need a copy
+                    let chat_ids = recipients
+                        .into_iter()
+                        .filter(|recipient| *recipient != user_id)
+                        .collect::<Vec<_>>();
+
+                    let reply = if chat_ids.len() > 0 {
+                        language.fill(Text::BroadcastSending, &[&chat_ids.len(), &event.title()])
+                    } else {
+                        language.fill(Text::BroadcastNobody, &[&event.title()])
+                    };
+
+                    if chat_ids.len() > 0 {
+                        fan_out.do_send(FanOut {
+                            event_id,
+                            chat_ids,
+                            // Attendees are messaged in the language of the event's channel
+                            text: chat_system
+                                .language()
+                                .fill(Text::BroadcastMessage, &[&event.title(), &text]),
+                            keep_failed: false,
+                        });
+                    }
+
+                    send_message(&bot, chat_id, reply);
+                    Ok(())
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(&bot, chat_id, &e, language, Text::BroadcastFailed);
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error messaging attendees"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Delete an event and take down its announcement
+    fn delete_event(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        chat_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let fan_out = self.fan_out.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let db3 = self.db.clone();
+        let webhooks = self.webhooks.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                let title = event.title().to_owned();
+                let system_id = event.system_id();
+
+                // Look up the announcement before the event is gone
+                db.send(LookupAnnouncement { event_id })
+                    .then(flatten)
+                    .and_then(move |announcement_id| {
+                        db2.send(DeleteEvent { event_id })
+                            .then(flatten)
+                            .map(move |_| announcement_id)
+                    })
+                    .and_then(move |announcement_id| {
+                        webhooks.do_send(EventChanged {
+                            action: EventAction::Deleted,
+                            event,
+                        });
+
+                        db3.send(LookupSystem { system_id })
+                            .then(flatten)
+                            .map(move |chat_system| (chat_system, title, announcement_id))
+                    })
+            })
+            .then(move |res| match res {
+                Ok((chat_system, title, announcement_id)) => Ok(TelegramActor::event_deleted(
+                    &bot,
+                    &fan_out,
+                    event_id,
+                    chat_id,
+                    language,
+                    &chat_system,
+                    title,
+                    announcement_id,
+                )),
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::DeleteEventFailed,
+                    );
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error deleting event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Mark an event as cancelled, keeping it and its RSVPs around
+    ///
+    /// The event's announcement is edited to show that it's been cancelled, and the `Timer` stops
+    /// sending reminders for it
+    fn cancel_event(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+        chat_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let webhooks = self.webhooks.clone();
+        let timezone = self.timezone;
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Users can only cancel events they host
+                if event.hosts().iter().any(|host| host.user_id() == user_id) {
+                    Ok(event)
+                } else {
+                    Err(EventErrorKind::Permissions.into())
+                }
+            })
+            .and_then(move |event| {
+                let system_id = event.system_id();
+
+                db.send(CancelEvent { event_id })
+                    .then(flatten)
+                    .and_then(move |_| {
+                        db2.send(LookupAnnouncement { event_id })
+                            .then(flatten)
+                            .join(db2.send(LookupSystem { system_id }).then(flatten))
+                    })
+                    .map(move |(announcement_id, chat_system)| {
+                        (event, announcement_id, chat_system)
+                    })
+            })
+            .then(move |res| match res {
+                Ok((event, announcement_id, chat_system)) => {
+                    let channel_id = chat_system.events_channel();
+
+                    let channel_language = chat_system.language();
+
+                    send_message(
+                        &bot,
+                        chat_id,
+                        language.text(Text::EventCancelledReply).to_owned(),
+                    );
+                    send_message(
+                        &bot,
+                        channel_id,
+                        channel_language.fill(Text::EventCancelledNotice, &[&event.title()]),
+                    );
+
+                    if let Some(message_id) = announcement_id {
+                        let text = EventCard::from_event(&event, timezone)
+                            .heading(channel_language.text(Text::Cancelled))
+                            .hosts(event.hosts())
+                            .language(channel_language)
+                            .time_format(chat_system.time_format())
+                            .to_string();
+                        let log_ctx = LogContext::new("telegram").chat_id(channel_id);
+
+                        bot.inner.handle.spawn(
+                            mark_cancelled(&bot, channel_id, message_id, text)
+                                .map_err(log_err!(log_ctx, "Error editing announcement")),
+                        );
+                    }
+
+                    webhooks.do_send(EventChanged {
+                        action: EventAction::Cancelled,
+                        event,
+                    });
+
+                    Ok(())
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::CancelEventFailed,
+                    );
+                    Err(e)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error cancelling event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Ask a host who they'd like to hand their event over to
+    ///
+    /// Anyone in one of the chats linked to the event's channel who isn't already hosting it can
+    /// be picked
+    fn ask_new_host(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Users can only transfer events they host
+                if event.hosts().iter().any(|host| host.user_id() == user_id) {
+                    Ok(event)
+                } else {
+                    Err(EventErrorKind::Permissions.into())
+                }
+            })
+            .and_then(move |event| {
+                db.send(GetSystemMembers {
+                    system_id: event.system_id(),
+                }).then(flatten)
+                    .map(move |members| (event, members))
+            })
+            .then(move |res| match res {
+                Ok((event, members)) => {
+                    let buttons = members
+                        .into_iter()
+                        .filter(|member| {
+                            !event.hosts().iter().any(|host| host.id() == member.id())
+                        })
+                        .map(|member| {
+                            vec![
+                                InlineKeyboardButton::new(format!("@{}", member.username()))
+                                    .callback_data(
+                                        serde_json::to_string(&CallbackQueryMessage::TransferTo {
+                                            event_id,
+                                            user_id: member.id(),
+                                        }).unwrap(),
+                                    ),
+                            ]
+                        })
+                        .collect::<Vec<_>>();
+
+                    let text = if buttons.len() > 0 {
+                        language.fill(Text::PickNewHost, &[&event.title()])
+                    } else {
+                        language.fill(Text::NoNewHosts, &[&event.title()])
+                    };
+
+                    let msg = bot.edit_message_text(text)
+                        .chat_id(chat_id)
+                        .message_id(message_id);
+
+                    let msg = if buttons.len() > 0 {
+                        msg.reply_markup(InlineKeyboardMarkup::new(buttons))
+                    } else {
+                        msg
+                    };
+
+                    Either::A(
+                        msg.send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::EventLookupFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error asking for a new host"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Hand an event over from the user who asked to the host they picked
+    ///
+    /// The new host is told about it in a private message, which only arrives if they've started a
+    /// chat with the bot and haven't muted hosting updates
+    fn transfer_event(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        new_host_id: i32,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let db3 = self.db.clone();
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                // Users can only transfer events they host
+                let host = event
+                    .hosts()
+                    .iter()
+                    .find(|host| host.user_id() == user_id)
+                    .cloned();
+
+                match host {
+                    Some(host) => Ok((host, event)),
+                    None => Err(EventErrorKind::Permissions.into()),
+                }
+            })
+            .and_then(move |(host, event)| {
+                db.send(TransferEvent {
+                    event_id,
+                    from_host: host.id(),
+                    to_host: new_host_id,
+                }).then(flatten)
+                    .and_then(move |_| db2.send(LookupEvent { event_id }).then(flatten))
+                    .map(move |event| (host, event))
+            })
+            .then(move |res| match res {
+                Ok((host, event)) => {
+                    if let Some(new_host) = event.hosts().iter().find(|h| h.id() == new_host_id) {
+                        let username = host.username().to_owned();
+                        let title = event.title().to_owned();
+
+                        send_notification(
+                            &bot,
+                            &db3,
+                            new_host.user_id(),
+                            Notification::HostNudges,
+                            move |language| language.fill(Text::MadeHost, &[&username, &title]),
+                        );
+                    }
+
+                    Either::A(
+                        bot.edit_message_text(language.fill(Text::Transferred, &[&event.title()]))
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(&bot, chat_id, &e, language, Text::TransferFailed);
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error transferring event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    fn event_soon(&self, event: Event) {
+        self.notify_chats(&event, Some(TemplateKind::Reminder), Text::DefaultReminder);
+    }
+
+    /// Remind a channel and its linked chats that several of its events are starting soon
+    ///
+    /// Each event is rendered from the channel's reminder template, and they're all sent as one
+    /// message
+    fn events_soon(&self, events: Vec<Event>) {
+        let event_id = match events.first() {
+            Some(event) => event.id(),
+            None => return,
+        };
+        let system_id = events[0].system_id();
+
+        let fan_out = self.fan_out.clone();
+        let log_ctx = LogContext::new("telegram").event_id(event_id);
+        let timezone = self.timezone;
+
+        let fut = self.db
+            .send(LookupSystemWithChats { system_id })
+            .then(flatten)
+            .map(move |(chat_system, mut chat_ids)| {
+                chat_ids.insert(0, chat_system.events_channel());
+
+                let language = chat_system.language();
+                let time_format = chat_system.time_format();
+                let template = chat_system
+                    .template(TemplateKind::Reminder)
+                    .unwrap_or(language.text(Text::DefaultReminder));
+                let reminders = events
+                    .iter()
+                    .map(|event| Values::from_event(event, timezone, language, time_format))
+                    .map(|values| template::render(template, &values, false))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+
+                fan_out.do_send(FanOut {
+                    event_id,
+                    chat_ids,
+                    text: language.fill(Text::StartingSoon, &[&reminders]),
+                    keep_failed: false,
+                });
+            })
+            .map_err(log_err!(log_ctx, "Error notifying chats"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Remind attendees privately that the event is starting soon
+    ///
+    /// Like broadcasts, the reminders are handed to the `FanOutActor` so events with a lot of
+    /// attendees don't run into Telegram's rate limits
+    fn remind_attendees(&self, event: Event, user_ids: Vec<Integer>) {
+        let fan_out = self.fan_out.clone();
+        let event_id = event.id();
+        let log_ctx = LogContext::new("telegram").event_id(event_id);
+        let timezone = self.timezone;
+
+        // Attendees are reminded in the language of the event's channel
+        let fut = self.db
+            .send(LookupSystem {
+                system_id: event.system_id(),
+            })
+            .then(flatten)
+            .map(move |chat_system| {
+                let language = chat_system.language();
+                let values =
+                    Values::from_event(&event, timezone, language, chat_system.time_format());
+                let text = language.fill(Text::AttendeeReminder, &[&values.title, &values.when]);
+
+                fan_out.do_send(FanOut {
+                    event_id,
+                    chat_ids: user_ids,
+                    text,
+                    keep_failed: false,
+                });
+            })
+            .map_err(log_err!(log_ctx, "Error reminding attendees"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    fn event_over(&self, event: Event) {
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+
+        let id = event.id();
+        let system_id = event.system_id();
+
+        let unpin_bot = self.bot.clone();
+
+        // Take down the event's announcement if it's still pinned, leaving other events' pinned
+        let unpin = self.events_channel(system_id)
+            .join(self.db.send(ClearPinnedEvent { event_id: id }).then(flatten))
+            .and_then(move |(channel_id, pinned)| match pinned {
+                Some(message_id) => Either::A(unpin_message(&unpin_bot, channel_id, message_id)),
+                None => Either::B(Ok(()).into_future()),
+            })
+            .map_err(log_err!(log_ctx, "Error unpinning announcement"));
+
+        self.bot.inner.handle.spawn(unpin);
+
+        self.notify_chats(&event, Some(TemplateKind::Ended), Text::DefaultEnded);
+
+        self.query_events(id, system_id);
+    }
+
+    fn event_started(&self, event: Event) {
+        self.notify_chats(&event, None, Text::DefaultStarted);
+    }
+
+    /// Take the RSVP buttons off an event's announcement once its RSVP deadline has passed
+    fn rsvps_closed(&self, event: Event) {
+        let bot = self.bot.clone();
+        let event_id = event.id();
+        let log_ctx = LogContext::new("telegram").event_id(event_id);
+
+        let fut = self.db
+            .send(LookupAnnouncement { event_id })
+            .then(flatten)
+            .join(self.events_channel(event.system_id()))
+            .and_then(move |(announcement_id, channel_id)| match announcement_id {
+                Some(message_id) => Either::A(remove_rsvp_buttons(&bot, channel_id, message_id)),
+                None => Either::B(Ok(()).into_future()),
+            })
+            .map_err(log_err!(log_ctx, "Error closing RSVPs"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Send the same message to the event's channel and each of its linked chats
+    ///
+    /// The message is rendered from the channel's template of the given kind, or from the
+    /// `default_template` in the channel's language if it hasn't set one. The messages are handed
+    /// to the `FanOutActor`, which spreads them out so systems with a lot of chats don't run into
+    /// Telegram's rate limits
+    fn notify_chats(
+        &self,
+        event: &Event,
+        kind: Option<TemplateKind>,
+        default_template: Text,
+    ) {
+        let fan_out = self.fan_out.clone();
+        let event_id = event.id();
+        let log_ctx = LogContext::new("telegram").event_id(event_id);
+        let timezone = self.timezone;
+        let system_id = event.system_id();
+        let event = event.clone();
+
+        let fut = self.db
+            .send(LookupSystemWithChats { system_id })
+            .then(flatten)
+            .map(move |(chat_system, mut chat_ids)| {
+                chat_ids.insert(0, chat_system.events_channel());
+
+                let language = chat_system.language();
+                let values =
+                    Values::from_event(&event, timezone, language, chat_system.time_format());
+                let template = kind
+                    .and_then(|kind| chat_system.template(kind))
+                    .unwrap_or(language.text(default_template));
+                let text = template::render(template, &values, false);
+
+                fan_out.do_send(FanOut {
+                    event_id,
+                    chat_ids,
+                    text,
+                    keep_failed: false,
+                });
+            })
+            .map_err(log_err!(log_ctx, "Error notifying chats"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    fn new_event(&self, event: Event) {
+        TelegramActor::announce_event(
+            self.bot.clone(),
+            self.db.clone(),
+            event,
+            &self.url,
+            self.timezone,
+        );
+    }
+
+    /// Ask the admins of a moderated channel to approve a pending event
+    ///
+    /// Events hosted by one of the channel's admins are approved right away. Otherwise each admin
+    /// is sent the event in a private message, which only arrives if they've started a chat with
+    /// the bot.
+    fn review_event(&self, event: Event) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let webhooks = self.webhooks.clone();
+        let url = self.url.clone();
+        let timezone = self.timezone;
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+
+        let fut = self.db
+            .send(LookupSystem {
+                system_id: event.system_id(),
+            })
+            .then(flatten)
+            .and_then(move |chat_system| {
+                let language = chat_system.language();
+
+                bot.unban_chat_administrators(chat_system.events_channel())
+                    .send()
+                    .map_err(telegram_lookup_error)
+                    .map(move |(bot, admins)| (bot, admins, language))
+            })
+            .and_then(move |(bot, admins, language)| {
+                let admins = admins
+                    .into_iter()
+                    .map(|admin| admin.user.id)
+                    .collect::<HashSet<_>>();
+
+                // Admins don't need anyone's approval
+                if event
+                    .hosts()
+                    .iter()
+                    .any(|host| admins.contains(&host.user_id()))
+                {
+                    Either::A(TelegramActor::approve_event(
+                        bot,
+                        db,
+                        webhooks,
+                        event.id(),
+                        url,
+                        timezone,
+                    ))
+                } else {
+                    let text = EventCard::from_event(&event, timezone)
+                        .heading(language.text(Text::AwaitingApproval))
+                        .hosts(event.hosts())
+                        .capacity(event.capacity())
+                        .members_only(event.members_only())
+                        .language(language)
+                        .to_string();
+
+                    let buttons = vec![vec![
+                        InlineKeyboardButton::new(language.text(Text::Approve).to_owned())
+                            .callback_data(
+                                serde_json::to_string(&CallbackQueryMessage::Review {
+                                    event_id: event.id(),
+                                    approved: true,
+                                }).unwrap(),
+                            ),
+                        InlineKeyboardButton::new(language.text(Text::TurnDown).to_owned())
+                            .callback_data(
+                                serde_json::to_string(&CallbackQueryMessage::Review {
+                                    event_id: event.id(),
+                                    approved: false,
+                                }).unwrap(),
+                            ),
+                    ]];
+
+                    for admin_id in admins {
+                        let log_ctx = LogContext::new("telegram").chat_id(admin_id);
+
+                        bot.inner.handle.spawn(
+                            bot.message(admin_id, text.clone())
+                                .reply_markup(InlineKeyboardMarkup::new(buttons.clone()))
+                                .send()
+                                .map(|_| ())
+                                .map_err(log_err!(log_ctx, "Error asking admin for approval")),
+                        );
+                    }
+
+                    for host in event.hosts() {
+                        let title = event.title().to_owned();
+
+                        send_notification(
+                            &bot,
+                            &db,
+                            host.user_id(),
+                            Notification::HostNudges,
+                            move |language| language.fill(Text::PendingApproval, &[&title]),
+                        );
+                    }
+
+                    Either::B(Ok(()).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error asking for approval"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Approve or turn down a pending event for one of its channel's admins
+    ///
+    /// The event's hosts are told how it went in a private message. Turned down events are deleted.
+    fn review(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        event_id: i32,
+        approved: bool,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let db3 = self.db.clone();
+        let webhooks = self.webhooks.clone();
+        let url = self.url.clone();
+        let timezone = self.timezone;
+
+        let fut = self.db
+            .send(LookupEvent { event_id })
+            .then(flatten)
+            .and_then(move |event| {
+                db.send(LookupSystem {
+                    system_id: event.system_id(),
+                }).then(flatten)
+                    .map(move |chat_system| (event, chat_system))
+            })
+            .and_then(move |(event, chat_system)| {
+                bot.unban_chat_administrators(chat_system.events_channel())
+                    .send()
+                    .map_err(telegram_lookup_error)
+                    .and_then(move |(bot, admins)| {
+                        // Only the channel's admins can review its events
+                        if admins.iter().any(|admin| admin.user.id == user_id) {
+                            Ok((bot, event))
+                        } else {
+                            Err(EventErrorKind::Permissions.into())
+                        }
+                    })
+            })
+            .and_then(move |(bot, event)| {
+                let reviewed = if approved {
+                    Either::A(TelegramActor::approve_event(
+                        bot.clone(),
+                        db2,
+                        webhooks,
+                        event_id,
+                        url,
+                        timezone,
+                    ))
+                } else {
+                    Either::B(db2.send(RejectEvent { event_id }).then(flatten))
+                };
+
+                reviewed.map(move |_| (bot, event))
+            })
+            .then(move |res| match res {
+                Ok((bot, event)) => {
+                    let (text, notice) = if approved {
+                        (Text::Approved, Text::ApprovedNotice)
+                    } else {
+                        (Text::TurnedDown, Text::TurnedDownNotice)
+                    };
+                    let text = language.fill(text, &[&event.title()]);
+
+                    for host in event.hosts() {
+                        let title = event.title().to_owned();
+
+                        send_notification(
+                            &bot,
+                            &db3,
+                            host.user_id(),
+                            Notification::HostNudges,
+                            move |language| language.fill(notice, &[&title]),
+                        );
+                    }
+
+                    Either::A(
+                        bot.edit_message_text(text)
+                            .chat_id(chat_id)
+                            .message_id(message_id)
+                            .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                            .send()
+                            .map(|_| ())
+                            .map_err(telegram_error),
+                    )
+                }
+                Err(e) => {
+                    TelegramActor::send_failure(
+                        &bot2,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::ReviewFailed,
+                    );
+                    Either::B(Err(e).into_future())
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error reviewing event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Mark a pending event as approved, and announce it like any other new event
+    fn approve_event(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        webhooks: Addr<Syn, WebhookActor>,
+        event_id: i32,
+        url: String,
+        timezone: Tz,
+    ) -> impl Future<Item = (), Error = EventError> {
+        let db2 = db.clone();
+
+        db.send(ApproveEvent { event_id })
+            .then(flatten)
+            .and_then(move |_| db2.send(LookupEvent { event_id }).then(flatten))
+            .map(move |event| {
+                webhooks.do_send(EventChanged {
+                    action: EventAction::Created,
+                    event: event.clone(),
+                });
+
+                // Scheduled announcements are sent by the Timer
+                if event.publish_at().is_none() {
+                    TelegramActor::announce_event(bot, db, event, &url, timezone);
+                }
+            })
+    }
+
+    /// Send the announcement for a new event to its events channel
+    ///
+    /// Members-only events don't have a public page, so only other announcements link to one, as
+    /// long as the channel hasn't turned public pages off. Events with an image are announced with
+    /// a photo, unless the announcement is too long to be its caption. Channels with their own
+    /// announcement template are sent that instead of the usual card, and channels that turned
+    /// RSVPs off don't get the RSVP buttons.
+    fn announce_event(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        event: Event,
+        url: &str,
+        timezone: Tz,
+    ) {
+        let link = format!("{}/events/view/{}", url, event.public_id());
+
+        let handle = bot.inner.handle.clone();
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+
+        let fut = db
+            .send(LookupSystem {
+                system_id: event.system_id(),
+            })
+            .then(flatten)
+            .and_then(move |chat_system| {
+                let event_id = event.id();
+                let features = chat_system.features();
+                let language = chat_system.language();
+
+                let reply_markup = if features.enabled(Feature::Rsvp) {
+                    Some(InlineKeyboardMarkup::new(rsvp_buttons(event_id, language)))
+                } else {
+                    None
+                };
+
+                let text = match chat_system.template(TemplateKind::Announcement) {
+                    Some(announcement) => {
+                        let time_format = chat_system.time_format();
+                        let values = Values::from_event(&event, timezone, language, time_format);
+
+                        template::render(announcement, &values, true)
+                    }
+                    None => {
+                        let card = EventCard::from_event(&event, timezone)
+                            .heading(language.text(Text::NewEvent))
+                            .hosts(event.hosts())
+                            .capacity(event.capacity())
+                            .members_only(event.members_only())
+                            .as_of(Utc::now().with_timezone(&timezone))
+                            .language(language)
+                            .time_format(chat_system.time_format())
+                            .html();
+
+                        if !has_public_page(&event, features) {
+                            card.to_string()
+                        } else {
+                            card.link(&link).to_string()
+                        }
+                    }
+                };
+
+                let photo = if text.chars().count() <= MAX_CAPTION_LENGTH {
+                    event.image_url().map(|s| s.to_owned())
+                } else {
+                    None
+                };
+
+                let channel_id = chat_system.events_channel();
+                let failed_text = text.clone();
+                let failed_db = db.clone();
+
+                let sent = match photo {
+                    Some(photo) => Either::A(send_photo_announcement(
+                        &bot,
+                        chat_system.events_channel(),
+                        photo,
+                        text,
+                        reply_markup,
+                        chat_system.protect_content(),
+                    )),
+                    None => Either::B(send_announcement(
+                        &bot,
+                        chat_system.events_channel(),
+                        text,
+                        reply_markup,
+                        chat_system.protect_content(),
+                    )),
+                };
+
+                // Announcements that can't be posted are kept, so they can be posted again once
+                // the problem is fixed
+                let sent = sent.or_else(move |e| {
+                    failed_db
+                        .send(StoreFailedMessage {
+                            chat_id: channel_id,
+                            event_id,
+                            announcement: true,
+                            text: failed_text,
+                            error: describe_error(&e),
+                        })
+                        .then(move |_| Err(e))
+                });
+
+                sent.and_then(move |message_id| {
+                    // The announcement is taken down if the event is deleted
+                    let stored = db.send(SetAnnouncement {
+                        event_id,
+                        message_id,
+                    }).then(flatten);
+
+                    if chat_system.pin_announcements() {
+                        Either::A(
+                            stored
+                                .join(pin_announcement(
+                                    &bot,
+                                    &db,
+                                    &chat_system,
+                                    message_id,
+                                    event_id,
+                                ))
+                                .map(|_| ()),
+                        )
+                    } else {
+                        Either::B(stored)
+                    }
+                })
+            })
+            .map_err(log_err!(log_ctx, "Error announcing event"));
+
+        handle.spawn(fut);
+    }
+
+    /// Announce an event again in its channel because too few people have RSVP'd to it
+    ///
+    /// The new post isn't tracked like the original announcement, so it's left up if the event is
+    /// cancelled or deleted
+    fn boost(&self, event: Event) {
+        let bot = self.bot.clone();
+        let link = format!("{}/events/view/{}", self.url, event.public_id());
+        let timezone = self.timezone;
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+
+        let fut = self.db
+            .send(LookupSystem {
+                system_id: event.system_id(),
+            })
+            .then(flatten)
+            .and_then(move |chat_system| {
+                let language = chat_system.language();
+                let card = EventCard::from_event(&event, timezone)
+                    .heading(language.text(Text::StillRoom))
+                    .hosts(event.hosts())
+                    .capacity(event.capacity())
+                    .members_only(event.members_only())
+                    .as_of(Utc::now().with_timezone(&timezone))
+                    .language(language)
+                    .time_format(chat_system.time_format())
+                    .html();
+
+                let text = if !has_public_page(&event, chat_system.features()) {
+                    card.to_string()
+                } else {
+                    card.link(&link).to_string()
+                };
+
+                send_announcement(
+                    &bot,
+                    chat_system.events_channel(),
+                    text,
+                    Some(InlineKeyboardMarkup::new(rsvp_buttons(event.id(), language))),
+                    chat_system.protect_content(),
+                )
+            })
+            .map(|_| ())
+            .map_err(log_err!(log_ctx, "Error boosting event"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Post the weekly digest of upcoming events to a Chat System's channel, pinning it if the
+    /// channel asks for it
+    ///
+    /// Last week's digest is unpinned, and if the digest can't be posted it's left for the next
+    /// hour's attempt
+    fn digest(&self, chat_system: ChatSystem, events: Vec<Event>) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let log_ctx = LogContext::new("telegram").chat_id(chat_system.events_channel());
+        let pin_ctx = log_ctx.clone();
+        let language = chat_system.language();
+        let system_id = chat_system.id();
+        let channel_id = chat_system.events_channel();
+
+        let fut = send_announcement(
+            &self.bot,
+            chat_system.events_channel(),
+            format!(
+                "{}\n\n{}",
+                language.text(Text::ThisWeeksEvents),
+                format_events(&events, self.timezone, language, chat_system.time_format())
+            ),
+            None,
+            chat_system.protect_content(),
+        ).then(move |res| match res {
+            Ok(message_id) => {
+                let pinned = if chat_system.pin_digest() {
+                    Some(message_id)
+                } else {
+                    None
+                };
+
+                let fut = db.send(SetPinnedDigest {
+                    system_id,
+                    message_id: pinned,
+                }).then(flatten)
+                    .and_then(move |previous| {
+                        let unpin = match previous {
+                            Some(previous) => {
+                                Either::A(unpin_message(&bot, channel_id, previous))
+                            }
+                            None => Either::B(Ok(()).into_future()),
+                        };
+
+                        let pin = match pinned {
+                            Some(message_id) => Either::A(
+                                bot.pin_chat_message(channel_id, message_id)
+                                    .send()
+                                    .map(|_| ())
+                                    .map_err(telegram_error),
+                            ),
+                            None => Either::B(Ok(()).into_future()),
+                        };
+
+                        unpin.join(pin).map(|_| ())
+                    })
+                    .map_err(log_err!(pin_ctx, "Error pinning digest"));
+
+                Either::A(fut)
+            }
+            Err(e) => {
+                ctx_error!(log_ctx, "Error posting digest: {:?}", e);
+
+                Either::B(
+                    db2.send(ReleaseDigest { system_id })
+                        .then(flatten)
+                        .map_err(log_err!(log_ctx, "Error releasing digest")),
+                )
+            }
+        });
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    /// Get the channel a system announces events in, looking the system up only if it isn't
+    /// cached
+    fn events_channel(&self, system_id: i32) -> impl Future<Item = Integer, Error = EventError> {
+        if let Some(channel_id) = self.channels.borrow_mut().get(system_id) {
+            return Either::A(Ok(channel_id).into_future());
+        }
+
+        let channels = self.channels.clone();
+
+        Either::B(
+            self.db
+                .send(LookupSystem { system_id })
+                .then(flatten)
+                .map(move |chat_system: ChatSystem| {
+                    let channel_id = chat_system.events_channel();
+                    channels.borrow_mut().insert(system_id, channel_id);
+                    channel_id
+                }),
+        )
+    }
+
+    fn update_event(&self, event: Event) {
+        let bot = self.bot.clone();
+        let timezone = self.timezone;
+        let log_ctx = LogContext::new("telegram").event_id(event.id());
+
+        let fut = self.db
+            .send(LookupSystem {
+                system_id: event.system_id(),
+            })
+            .then(flatten)
+            .and_then(move |chat_system| {
+                let language = chat_system.language();
+                let text = EventCard::from_event(&event, timezone)
+                    .heading(language.text(Text::EventUpdated))
+                    .language(language)
+                    .time_format(chat_system.time_format())
+                    .html()
+                    .to_string();
+
+                bot.message(chat_system.events_channel(), text)
+                    .parse_mode(HTML)
+                    .send()
+                    .map_err(telegram_error)
+            })
+            .map(|_| ())
+            .map_err(log_err!(log_ctx, "Error announcing event update"));
+
+        self.bot.inner.handle.spawn(fut);
+    }
+
+    fn query_events(&self, event_id: i32, system_id: i32) {
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let bot = self.bot.clone();
+        let timezone = self.timezone;
+        let log_ctx = LogContext::new("telegram").event_id(event_id);
+
+        let fut = self.db
+            .send(LookupSystem { system_id })
+            .then(flatten)
+            .and_then(move |chat_system| {
+                let channel_id = chat_system.events_channel();
+                let language = chat_system.language();
+
+                db.send(GetEventsForSystem { system_id })
+                    .then(flatten)
+                    .and_then(move |events: Vec<Event>| {
+                        let events = events
+                            .into_iter()
+                            .filter(|event| event.id() != event_id)
+                            .collect();
+
+                        print_events(&bot, &db2, channel_id, events, timezone, language)
+                            .map(|_| ())
+                    })
+            });
+
+        self.bot
+            .inner
+            .handle
+            .spawn(fut.map(|_| ()).map_err(log_err!(log_ctx, "Error listing remaining events")));
+    }
+
+    /// Ask the user to pick one of their channels, building each button's callback with
+    /// `callback`
+    fn ask_chats(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        channels: HashSet<Integer>,
+        chat_id: Integer,
+        language: Language,
+        prompt: String,
+        callback: fn(Integer) -> CallbackQueryMessage,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        let bot2 = bot.clone();
+        let bot3 = bot.clone();
+
+        // The channels are looked up together, and only channels the bot hasn't stored a title for
+        // are looked up on Telegram
+        let fut = db
+            .send(LookupSystemsByChannels(channels.into_iter().collect()))
+            .then(flatten)
+            .and_then(move |chat_systems| {
+                let fut_iter = chat_systems.into_iter().map(move |chat_system| {
+                    let channel_id = chat_system.events_channel();
+
+                    TelegramActor::channel_title(&bot, db.clone(), &chat_system, language).map(
+                        move |title| {
+                            debug!("Asking about channel_id: {}", channel_id);
+                            InlineKeyboardButton::new(title).callback_data(
+                                serde_json::to_string(&callback(channel_id)).unwrap(),
+                            )
+                        },
+                    )
+                });
+
+                futures_unordered(fut_iter).collect()
+            })
+            .and_then(move |buttons| {
+                let msg = if buttons.len() > 0 {
+                    let buttons = buttons.into_iter().fold(
+                        Vec::new(),
+                        |mut acc: Vec<Vec<_>>, button| {
+                            let len = acc.len();
+
+                            if len > 0 {
+                                if acc[len - 1].len() < 2 {
+                                    acc[len - 1].push(button);
+                                } else {
+                                    acc.push(vec![button]);
+                                }
+                            } else {
+                                acc.push(vec![button]);
+                            }
+
+                            acc
+                        },
+                    );
+
+                    bot2.message(chat_id, prompt)
+                        .reply_markup(InlineKeyboardMarkup::new(buttons))
+                } else {
+                    bot2.message(chat_id, language.text(Text::NoChannels).to_owned())
+                };
+
+                msg.send()
+                    .map_err(telegram_error)
+            });
+
+        bot3.inner
+            .handle
+            .spawn(fut.map(|_| ()).map_err(log_err!(log_ctx, "Error asking for a channel")));
+    }
+
+    /// Ask a user privately which channel a command is for, offering only the channels they're an
+    /// admin of
+    ///
+    /// Channels are found through the chats the user is in, and channels whose admins can't be
+    /// looked up are left out
+    fn ask_admin_channels(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        user_id: Integer,
+        chat_id: Integer,
+        prompt: Text,
+        callback: fn(Integer) -> CallbackQueryMessage,
+    ) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let db = self.db.clone();
+
+        Arbiter::handle().spawn(
+            self.users
+                .send(LookupChannels(user_id))
+                .then(flatten)
+                .and_then(move |channels| {
+                    iter_ok(channels)
+                        .and_then(move |channel_id| {
+                            channel_admin(&bot, channel_id, user_id).then(move |res| {
+                                Ok(match res {
+                                    Ok(true) => Some(channel_id),
+                                    _ => None,
+                                })
+                            })
+                        })
+                        .filter_map(|channel_id| channel_id)
+                        .collect()
+                })
+                .then(move |res| match res {
+                    Ok(channels) => {
+                        if channels.is_empty() {
+                            TelegramActor::send_error(
+                                &bot2,
+                                chat_id,
+                                language.text(Text::NoAdminChannels),
+                            );
+                        } else {
+                            TelegramActor::ask_chats(
+                                bot2,
+                                db,
+                                channels.into_iter().collect(),
+                                chat_id,
+                                language,
+                                language.text(prompt).to_owned(),
+                                callback,
+                            );
+                        }
+                        Ok(())
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot2,
+                            chat_id,
+                            &e,
+                            language,
+                            Text::AdminChannelsLookupFailed,
+                        );
+                        Err(e)
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error looking up admin channels")),
+        );
+    }
+
+    /// Replace a channel's API token, or its read-only API token, sending the new one privately
+    /// to the admin who asked for it
+    ///
+    /// Only the hash of the token is stored, so a lost token can't be recovered, only replaced.
+    fn issue_api_token(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        read_only: bool,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let (base64d, secret) = match generate_secret() {
+            Ok(pair) => pair,
+            Err(_) => {
+                TelegramActor::send_error(&self.bot, chat_id, language.text(Text::TokenFailed));
+                return;
+            }
+        };
+
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+        let url = self.url.clone();
+
+        Arbiter::handle().spawn(
+            channel_admin(&self.bot, channel_id, user_id)
+                .and_then(|admin| {
+                    // The buttons only offered the user's channels, but callback data comes from
+                    // the user's client
+                    if admin {
+                        Ok(())
+                    } else {
+                        Err(EventErrorKind::Permissions.into())
+                    }
+                })
+                .and_then(move |_| {
+                    if read_only {
+                        Either::A(db.send(SetReadSecret { channel_id, secret }).then(flatten))
+                    } else {
+                        Either::B(db.send(SetApiSecret { channel_id, secret }).then(flatten))
+                    }
+                })
+                .then(move |res| match res {
+                    Ok(system_id) => {
+                        let token = format!("{}={}", base64d, system_id);
+
+                        let text = if read_only {
+                            language.fill(
+                                Text::ReadTokenIssued,
+                                &[&token, &url, &channel_id, &url],
+                            )
+                        } else {
+                            language.fill(Text::TokenIssued, &[&token, &url, &channel_id])
+                        };
+
+                        Either::A(
+                            bot.edit_message_text(text)
+                                .chat_id(chat_id)
+                                .message_id(message_id)
+                                .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                                .send()
+                                .map(|_| ())
+                                .map_err(telegram_error),
+                        )
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            chat_id,
+                            &e,
+                            language,
+                            Text::TokenAdminsOnly,
+                        );
+                        Either::B(Err(e).into_future())
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error setting API token")),
+        );
+    }
+
+    /// Send a channel's webhook secret privately to the admin who asked for it
+    fn send_webhook_secret(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        channel_id: Integer,
+        user_id: Integer,
+        chat_id: Integer,
+        message_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let db = self.db.clone();
+
+        Arbiter::handle().spawn(
+            channel_admin(&self.bot, channel_id, user_id)
+                .and_then(|admin| {
+                    if admin {
+                        Ok(())
+                    } else {
+                        Err(EventErrorKind::Permissions.into())
+                    }
+                })
+                .and_then(move |_| db.send(GetWebhooksForChannel { channel_id }).then(flatten))
+                .then(move |res| match res {
+                    Ok(webhooks) => {
+                        let text = webhook_secrets_text(&webhooks, language);
+
+                        Either::A(
+                            bot.edit_message_text(text)
+                                .chat_id(chat_id)
+                                .message_id(message_id)
+                                .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                                .send()
+                                .map(|_| ())
+                                .map_err(telegram_error),
+                        )
+                    }
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            chat_id,
+                            &e,
+                            language,
+                            Text::SecretAdminsOnly,
+                        );
+                        Either::B(Err(e).into_future())
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error sending webhook secret")),
+        );
+    }
+
+    /// Ask the user to pick one of the events they're hosting, building each button's callback
+    /// with `callback`
+    fn ask_hosted_events(
+        bot: RcBot,
+        events: Vec<Event>,
+        chat_id: Integer,
+        language: Language,
+        prompt: Text,
+        callback: fn(&Event) -> CallbackQueryMessage,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        let bot2 = bot.clone();
+
+        let fut = iter_ok(events)
+            .map(move |event| {
+                InlineKeyboardButton::new(event.title().to_owned())
+                    .callback_data(serde_json::to_string(&callback(&event)).unwrap())
+            })
+            .collect()
+            .and_then(move |buttons| {
+                let msg = if buttons.len() > 0 {
+                    let buttons = buttons.into_iter().fold(
+                        Vec::new(),
+                        |mut acc: Vec<Vec<_>>, button| {
+                            let len = acc.len();
+
+                            if len > 0 {
+                                if acc[len - 1].len() < 2 {
+                                    acc[len - 1].push(button);
+                                } else {
+                                    acc.push(vec![button]);
+                                }
+                            } else {
+                                acc.push(vec![button]);
+                            }
+
+                            acc
+                        },
                     );
+
+                    bot2.message(chat_id, language.text(prompt).to_owned())
+                        .reply_markup(InlineKeyboardMarkup::new(buttons))
                 } else {
-                    TelegramActor::send_error(
-                        &self.bot,
-                        channel_id,
-                        "The /link command can only be used in channels",
+                    bot2.message(chat_id, language.text(Text::NotHosting).to_owned())
+                };
+                msg.send()
+                    .map_err(telegram_error)
+            });
+
+        bot.inner
+            .handle
+            .spawn(fut.map(|_| ()).map_err(log_err!(log_ctx, "Error asking for an event")));
+    }
+
+    fn ask_events(bot: RcBot, events: Vec<Event>, chat_id: Integer, language: Language) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        let bot2 = bot.clone();
+
+        let fut = iter_ok(events)
+            .map(|event| {
+                InlineKeyboardButton::new(event.title().to_owned()).callback_data(
+                    serde_json::to_string(&CallbackQueryMessage::EditEvent {
+                        event_id: event.id(),
+                    }).unwrap(),
+                )
+            })
+            .collect()
+            .and_then(move |buttons| {
+                let msg = if buttons.len() > 0 {
+                    let buttons = buttons.into_iter().fold(
+                        Vec::new(),
+                        |mut acc: Vec<Vec<_>>, button| {
+                            let len = acc.len();
+
+                            if len > 0 {
+                                if acc[len - 1].len() < 2 {
+                                    acc[len - 1].push(button);
+                                } else {
+                                    acc.push(vec![button]);
+                                }
+                            } else {
+                                acc.push(vec![button]);
+                            }
+
+                            acc
+                        },
                     );
-                }
-            } else if text.starts_with("/init") {
-                debug!("init");
-                let channel_id = message.chat.id;
 
-                if message.chat.kind == "channel" {
-                    debug!("channel");
-                    let bot = self.bot.clone();
+                    bot2.message(chat_id, language.text(Text::PickEventToEdit).to_owned())
+                        .reply_markup(InlineKeyboardMarkup::new(buttons))
+                } else {
+                    bot2.message(chat_id, language.text(Text::NotHosting).to_owned())
+                };
+                msg.send()
+                    .map_err(telegram_error)
+            });
+
+        bot.inner
+            .handle
+            .spawn(fut.map(|_| ()).map_err(log_err!(log_ctx, "Error listing events to edit")));
+    }
+
+    fn send_my_events(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        mut events: Vec<Event>,
+        chat_id: Integer,
+        timezone: Tz,
+        language: Language,
+        time_format: TimeFormat,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+        let log_ctx2 = log_ctx.clone();
+
+        let bot2 = bot.clone();
+        let bot3 = bot.clone();
+
+        // Events that have already ended can't be edited or deleted in any useful way
+        let now = Utc::now().with_timezone(&timezone);
+        events.retain(|event| *event.end_date() > now);
+        events.sort_by_key(|event| event.start_date().clone());
+
+        let system_ids = events
+            .iter()
+            .map(|event| event.system_id())
+            .collect::<HashSet<_>>();
+
+        // Look up the title of the channel for every system the user hosts events in. A channel
+        // that can't be looked up is shown as unknown, so the rest of the events are still listed.
+        let fut_iter = system_ids.into_iter().map(move |system_id| {
+            let bot = bot.clone();
+            let db2 = db.clone();
+            let log_ctx = log_ctx2.clone();
+
+            db.send(LookupSystem { system_id })
+                .then(flatten)
+                .and_then(move |chat_system| {
+                    TelegramActor::channel_title(&bot, db2, &chat_system, language)
+                })
+                .then(move |res| {
+                    let title = res.map_err(log_err!(log_ctx, "Error looking up channel"))
+                        .ok();
+
+                    Ok::<_, EventError>((system_id, title))
+                })
+        });
+
+        let fut = futures_unordered(fut_iter)
+            .collect()
+            .and_then(move |titles: Vec<(i32, Option<String>)>| {
+                let titles = titles
+                    .into_iter()
+                    .filter_map(|(system_id, title)| title.map(|title| (system_id, title)))
+                    .collect::<HashMap<_, _>>();
+
+                let msg = if events.len() > 0 {
+                    let text = events
+                        .iter()
+                        .map(|event| {
+                            let channel = titles
+                                .get(&event.system_id())
+                                .map(|title| title.as_str())
+                                .unwrap_or(language.text(Text::UnknownChannel));
+
+                            EventCard::new(
+                                event.title(),
+                                event.start_date(),
+                                event.end_date(),
+                                timezone,
+                            )
+                                .heading(list_heading(event, language))
+                                .channel(channel)
+                                .language(language)
+                                .time_format(time_format)
+                                .to_string()
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+
+                    let buttons = events
+                        .iter()
+                        .map(|event| {
+                            vec![
+                                InlineKeyboardButton::new(
+                                    language.fill(Text::EditButton, &[&event.title()]),
+                                )
+                                    .callback_data(
+                                        serde_json::to_string(&CallbackQueryMessage::EditEvent {
+                                            event_id: event.id(),
+                                        }).unwrap(),
+                                    ),
+                                InlineKeyboardButton::new(
+                                    language.fill(Text::DeleteButton, &[&event.title()]),
+                                )
+                                    .callback_data(
+                                        serde_json::to_string(
+                                            &CallbackQueryMessage::DeleteEvent {
+                                                event_id: event.id(),
+                                                system_id: event.system_id(),
+                                            },
+                                        ).unwrap(),
+                                    ),
+                            ]
+                        })
+                        .collect();
+
+                    bot2.message(
+                        chat_id,
+                        format!("{}\n\n{}", language.text(Text::YourEvents), text),
+                    )
+                        .reply_markup(InlineKeyboardMarkup::new(buttons))
+                } else {
+                    bot2.message(chat_id, language.text(Text::NotHosting).to_owned())
+                };
+
+                msg.send()
+                    .map_err(telegram_error)
+            });
+
+        bot3.inner
+            .handle
+            .spawn(fut.map(|_| ()).map_err(log_err!(log_ctx, "Error listing hosted events")));
+    }
+
+    /// Tell the host and the event's channel that the event was deleted
+    ///
+    /// The channel's notice goes through the `FanOutActor`, so it's retried if it fails, and kept
+    /// as a failed message if it still can't be sent
+    fn event_deleted(
+        bot: &RcBot,
+        fan_out: &Addr<Unsync, FanOutActor>,
+        event_id: i32,
+        chat_id: Integer,
+        language: Language,
+        chat_system: &ChatSystem,
+        title: String,
+        announcement_id: Option<Integer>,
+    ) {
+        let channel_id = chat_system.events_channel();
+        let channel_language = chat_system.language();
+
+        send_message(bot, chat_id, language.text(Text::EventDeletedReply).to_owned());
+
+        fan_out.do_send(FanOut {
+            event_id,
+            chat_ids: vec![channel_id],
+            text: channel_language.fill(Text::EventDeletedNotice, &[&title]),
+            keep_failed: true,
+        });
+
+        if let Some(message_id) = announcement_id {
+            let log_ctx = LogContext::new("telegram").chat_id(channel_id);
+
+            bot.inner.handle.spawn(
+                remove_announcement(bot, channel_id, message_id, channel_language, title)
+                    .map_err(log_err!(log_ctx, "Error removing announcement")),
+            );
+        }
+    }
+
+    /// Tell one of the bot's admins how many database connections are idle, in use, and being
+    /// opened, and how many requests are waiting for one
+    fn pool_status(&self, log_ctx: LogContext, language: Language, chat_id: Integer) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(PoolStatus)
+                .then(flatten)
+                .map(move |counts| {
+                    send_message(
+                        &bot,
+                        chat_id,
+                        language.fill(
+                            Text::PoolStatus,
+                            &[
+                                &counts.idle,
+                                &counts.in_use,
+                                &counts.connecting,
+                                &counts.waiting,
+                            ],
+                        ),
+                    )
+                })
+                .map_err(move |e| {
+                    TelegramActor::send_failure(
+                        &bot2,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::PoolStatusFailed,
+                    );
+                    e
+                })
+                .map_err(log_err!(log_ctx, "Error checking database connections")),
+        );
+    }
+
+    /// Show one of the bot's admins the most recent messages that couldn't be sent
+    fn list_failed_messages(&self, log_ctx: LogContext, language: Language, chat_id: Integer) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let timezone = self.timezone;
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(GetFailedMessages)
+                .then(flatten)
+                .map(move |failed| {
+                    let msg = if failed.is_empty() {
+                        language.text(Text::NoFailedMessages).to_owned()
+                    } else {
+                        let lines = failed
+                            .iter()
+                            .map(|message| {
+                                let kind = if message.announcement() {
+                                    language.text(Text::FailedAnnouncement)
+                                } else {
+                                    language.text(Text::FailedMessage)
+                                };
+                                let preview = message.text().chars().take(100).collect::<String>();
+
+                                language.fill(
+                                    Text::FailedMessageLine,
+                                    &[
+                                        &message.id(),
+                                        &kind,
+                                        &message.event_id(),
+                                        &message.chat_id(),
+                                        &format_localized_date(
+                                            message.failed_at().with_timezone(&timezone),
+                                            language,
+                                            TimeFormat::default(),
+                                        ),
+                                        &message.error(),
+                                        &preview,
+                                    ],
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n\n");
+
+                        language.fill(Text::FailedMessages, &[&lines])
+                    };
+
+                    send_message(&bot, chat_id, msg);
+                })
+                .map_err(move |e| {
+                    TelegramActor::send_failure(
+                        &bot2,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::FailedMessagesLookupFailed,
+                    );
+                    e
+                })
+                .map_err(log_err!(log_ctx, "Error looking up failed messages")),
+        );
+    }
+
+    /// Send a message that couldn't be sent again
+    ///
+    /// Announcements are replayed by announcing their event again, so the new post gets RSVP
+    /// buttons and is taken down with the event. Messages that fail again are kept again.
+    fn replay_failed_message(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        chat_id: Integer,
+        id: i32,
+    ) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let db = self.db.clone();
+        let fan_out = self.fan_out.clone();
+        let url = self.url.clone();
+        let timezone = self.timezone;
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(TakeFailedMessage { id })
+                .then(flatten)
+                .and_then(move |failed| {
+                    if failed.announcement() {
+                        Either::A(
+                            db.send(LookupEvent {
+                                event_id: failed.event_id(),
+                            }).then(flatten)
+                                .map(move |event| {
+                                    TelegramActor::announce_event(
+                                        bot.clone(),
+                                        db,
+                                        event,
+                                        &url,
+                                        timezone,
+                                    );
+                                    bot
+                                }),
+                        )
+                    } else {
+                        fan_out.do_send(FanOut {
+                            event_id: failed.event_id(),
+                            chat_ids: vec![failed.chat_id()],
+                            text: failed.text().to_owned(),
+                            keep_failed: true,
+                        });
+
+                        Either::B(Ok(bot).into_future())
+                    }
+                })
+                .map(move |bot| {
+                    send_message(&bot, chat_id, language.fill(Text::Replaying, &[&id]));
+                })
+                .map_err(move |e| {
+                    TelegramActor::send_failure(
+                        &bot2,
+                        chat_id,
+                        &e,
+                        language,
+                        Text::ReplayFailed,
+                    );
+                    e
+                })
+                .map_err(log_err!(log_ctx, "Error replaying failed message")),
+        );
+    }
+
+    /// Start creating an event for one channel, for users who followed the channel's
+    /// `t.me/{bot}?start=new_{channel_id}` link
+    ///
+    /// Like `/new`, users the bot hasn't seen post yet are looked for in the channel's linked
+    /// chats, and the user picks the channel with a button, which makes their link
+    fn start_new_event(
+        &self,
+        log_ctx: LogContext,
+        language: Language,
+        user_id: Integer,
+        username: String,
+        chat_id: Integer,
+        channel_id: Integer,
+    ) {
+        let bot = self.bot.clone();
+        let bot2 = self.bot.clone();
+        let db = self.db.clone();
+        let db2 = self.db.clone();
+        let users = self.users.clone();
 
-                    // Spawn a future that adds the given channel to the database
-                    Arbiter::handle().spawn(
-                        self.db
-                            .send(NewChannel { channel_id })
-                            .then(flatten)
-                            .then(move |res| match res {
-                                Ok(item) => Ok((item, bot)),
-                                Err(err) => Err((err, bot)),
-                            })
-                            .map(move |(_chat_system, bot)| {
-                                TelegramActor::created_channel(&bot, channel_id)
-                            })
-                            .map_err(move |(e, bot)| {
-                                TelegramActor::send_error(
-                                    &bot,
-                                    channel_id,
-                                    "Could not initialize the chat",
-                                );
-                                e
+        Arbiter::handle().spawn(
+            self.users
+                .send(LookupChannels(user_id))
+                .then(flatten)
+                .and_then(move |channels| {
+                    if channels.contains(&channel_id) {
+                        Either::A(Ok(channels).into_future())
+                    } else {
+                        Either::B(TelegramActor::sync_membership(
+                            bot2, db, users, user_id, username,
+                        ))
+                    }
+                })
+                .and_then(move |channels| {
+                    if channels.contains(&channel_id) {
+                        Ok(())
+                    } else {
+                        Err(EventErrorKind::Permissions.into())
+                    }
+                })
+                .then(move |res| match res {
+                    Ok(()) => Ok(TelegramActor::ask_chats(
+                        bot,
+                        db2,
+                        vec![channel_id].into_iter().collect(),
+                        chat_id,
+                        language,
+                        language.text(Text::ConfirmNewEvent).to_owned(),
+                        |channel_id| CallbackQueryMessage::NewEvent { channel_id },
+                    )),
+                    Err(e) => {
+                        TelegramActor::send_failure(
+                            &bot,
+                            chat_id,
+                            &e,
+                            language,
+                            Text::NotLinked,
+                        );
+                        Err(e)
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error starting an event from a link")),
+        );
+    }
+
+    /// Record the new title of a chat or channel
+    fn title_changed(
+        &self,
+        log_ctx: LogContext,
+        chat_id: Integer,
+        title: Option<String>,
+        username: Option<String>,
+    ) {
+        Arbiter::handle().spawn(
+            self.db
+                .send(SetChatTitle {
+                    chat_id,
+                    title,
+                    username,
+                })
+                .then(flatten)
+                .map_err(log_err!(log_ctx, "Error storing chat title")),
+        );
+    }
+
+    /// Get a channel's title, looking it up on Telegram only if the bot hasn't stored it yet
+    ///
+    /// Titles that are looked up are stored, so they don't have to be looked up again
+    fn channel_title(
+        bot: &RcBot,
+        db: Addr<Unsync, DbBroker>,
+        chat_system: &ChatSystem,
+        language: Language,
+    ) -> impl Future<Item = String, Error = EventError> {
+        if let Some(title) = chat_system.title() {
+            return Either::A(Ok(title.to_owned()).into_future());
+        }
+
+        let channel_id = chat_system.events_channel();
+
+        Either::B(
+            bot.get_chat(channel_id)
+                .send()
+                .map_err(telegram_lookup_error)
+                .map(move |(_, channel)| {
+                    db.do_send(SetChatTitle {
+                        chat_id: channel_id,
+                        title: channel.title.clone(),
+                        username: channel.username.clone(),
+                    });
+
+                    channel
+                        .title
+                        .unwrap_or(
+                            channel
+                                .username
+                                .unwrap_or(language.text(Text::NoTitle).to_owned()),
+                        )
+                }),
+        )
+    }
+
+    /// Forget a group or channel Telegram says the bot has been removed from
+    ///
+    /// A channel's chat system is deleted along with its events, which takes them off the Timers,
+    /// while a group is only unlinked from its channel. This is only done for removals Telegram
+    /// sends as updates, since a failed message doesn't mean the bot is gone for good
+    fn chat_removed(&self, chat_id: Integer) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+        let db = self.db.clone();
+        let users = self.users.clone();
+
+        self.channels.borrow_mut().remove_channel(chat_id);
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(LookupSystemByChannel(chat_id))
+                .then(flatten)
+                .then(|res| match res {
+                    Ok(_) => Ok(true),
+                    Err(ref e) if e.kind() == EventErrorKind::Lookup => Ok(false),
+                    Err(e) => Err(e),
+                })
+                .and_then(move |is_channel| {
+                    if is_channel {
+                        users.do_send(RemoveChannel(chat_id));
+                        Either::A(
+                            db.send(DeleteChannel {
+                                channel_id: chat_id,
+                            }).then(flatten),
+                        )
+                    } else {
+                        users.do_send(RemoveChat(chat_id));
+                        Either::B(db.send(DeleteChat { chat_id }).then(flatten))
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error forgetting removed chat")),
+        );
+    }
+
+    fn notify_private(&self, language: Language, chat_id: Integer) {
+        send_message(&self.bot, chat_id, language.text(Text::NotPrivate).to_owned());
+    }
+
+    fn is_admin(
+        bot: RcBot,
+        channel_id: Integer,
+        chat_ids: Vec<Integer>,
+    ) -> impl Future<Item = Vec<Integer>, Error = EventError> {
+        bot.unban_chat_administrators(channel_id)
+            .send()
+            .map_err(telegram_lookup_error)
+            .and_then(move |(bot, admins)| {
+                let channel_admins = admins
+                    .into_iter()
+                    .map(|admin| admin.user.id)
+                    .collect::<HashSet<_>>();
+
+                iter_ok(chat_ids)
+                    .and_then(move |chat_id| {
+                        bot.unban_chat_administrators(chat_id)
+                            .send()
+                            .map_err(telegram_lookup_error)
+                            .map(move |(bot, admins)| (bot, admins, chat_id))
+                    })
+                    .filter_map(move |(_, admins, chat_id)| {
+                        if admins
+                            .into_iter()
+                            .any(|admin| channel_admins.contains(&admin.user.id))
+                        {
+                            Some(chat_id)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            })
+    }
+
+    /// Record a linked chat's admins as members of it, as though they'd posted in it
+    ///
+    /// The bot otherwise only learns about users when they post, and only users it knows about can
+    /// host events in the chat's channel.
+    fn import_admins(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        users: Addr<Syn, UsersActor>,
+        chat_id: Integer,
+    ) -> impl Future<Item = (), Error = EventError> {
+        bot.unban_chat_administrators(chat_id)
+            .send()
+            .map_err(telegram_lookup_error)
+            .and_then(move |(_, admins)| {
+                iter_ok(admins).for_each(move |admin| {
+                    let username = admin.user.username.unwrap_or(admin.user.first_name);
+
+                    TelegramActor::touch_member(
+                        db.clone(),
+                        &users,
+                        chat_id,
+                        admin.user.id,
+                        username,
+                    )
+                })
+            })
+    }
+
+    /// Check each linked chat for a user the bot hasn't seen post yet, recording the chats they're
+    /// in, and return the channels they can create events for
+    fn sync_membership(
+        bot: RcBot,
+        db: Addr<Unsync, DbBroker>,
+        users: Addr<Syn, UsersActor>,
+        user_id: Integer,
+        username: String,
+    ) -> impl Future<Item = HashSet<Integer>, Error = EventError> {
+        let users2 = users.clone();
+        let users3 = users.clone();
+
+        users
+            .send(LookupLinkedChats)
+            .then(flatten)
+            .and_then(move |chat_ids| {
+                iter_ok(chat_ids)
+                    .and_then(move |chat_id| {
+                        let body = serde_json::to_string(&ChatMemberQuery { chat_id, user_id })
+                            .unwrap();
+
+                        // Chats the bot can't check are skipped
+                        bot.inner.fetch_json("getChatMember", &body).then(move |res| {
+                            let member = res.ok()
+                                .and_then(|json| serde_json::from_str::<ChatMember>(&json).ok());
+
+                            Ok(match member {
+                                Some(ref member) if is_member(member) => Some(chat_id),
+                                _ => None,
                             })
-                            .map_err(|e| error!("Error creating channel: {:?}", e)),
-                    );
-                } else {
-                    TelegramActor::send_error(
-                        &self.bot,
-                        channel_id,
-                        "The /init command can only be used in channels",
-                    );
+                        })
+                    })
+                    .filter_map(|chat_id| chat_id)
+                    .for_each(move |chat_id| {
+                        TelegramActor::touch_member(
+                            db.clone(),
+                            &users2,
+                            chat_id,
+                            user_id,
+                            username.clone(),
+                        )
+                    })
+            })
+            .and_then(move |_| users3.send(LookupChannels(user_id)).then(flatten))
+    }
+
+    /// Record that a user is in a chat, storing the relation if the bot didn't know about it
+    fn touch_member(
+        db: Addr<Unsync, DbBroker>,
+        users: &Addr<Syn, UsersActor>,
+        chat_id: Integer,
+        user_id: Integer,
+        username: String,
+    ) -> impl Future<Item = (), Error = EventError> {
+        users
+            .send(TouchUser(user_id, chat_id))
+            .then(flatten)
+            .map(move |user_state| match user_state {
+                UserState::NewRelation => {
+                    db.do_send(NewRelation { chat_id, user_id });
                 }
-            }
-        }
+                UserState::NewUser => {
+                    db.do_send(NewUser {
+                        chat_id,
+                        user_id,
+                        username,
+                    });
+                }
+                _ => (),
+            })
+    }
+
+    fn send_help(&self, language: Language, chat_id: Integer) {
+        send_message(&self.bot, chat_id, commands::help_text(language));
+    }
+
+    fn send_error(bot: &RcBot, chat_id: Integer, error: &str) {
+        send_message(bot, chat_id, error.to_owned());
+    }
+
+    /// Tell the user their request failed with `error`, unless it failed for a reason they can do
+    /// something about, like the database being too busy, being banned from creating events, or
+    /// the bot not being an admin of the channel, in which case they're told what to do instead
+    fn send_failure(
+        bot: &RcBot,
+        chat_id: Integer,
+        e: &EventError,
+        language: Language,
+        error: Text,
+    ) {
+        let message = match e.kind() {
+            EventErrorKind::Busy => Text::Busy,
+            EventErrorKind::Banned => Text::Banned,
+            EventErrorKind::Quota => Text::Quota,
+            EventErrorKind::NotAdmin => Text::NotAdmin,
+            EventErrorKind::ChannelRights => Text::ChannelRights,
+            EventErrorKind::ChatNotFound => Text::ChatNotFound,
+            EventErrorKind::Blocked => Text::Blocked,
+            EventErrorKind::TooLong => Text::TooLong,
+            _ => error,
+        };
+
+        TelegramActor::send_error(bot, chat_id, language.text(message));
+    }
+
+    fn edit_with_url(
+        bot: &RcBot,
+        chat_id: Integer,
+        message_id: Integer,
+        language: Language,
+        text: Text,
+        url: String,
+        expires_at: &DateTime<Utc>,
+        timezone: Tz,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        let expires_at = format_localized_date(
+            expires_at.with_timezone(&timezone),
+            language,
+            TimeFormat::default(),
+        );
+
+        bot.inner.handle.spawn(
+            bot.edit_message_text(language.fill(text, &[&url, &expires_at]))
+                .chat_id(chat_id)
+                .message_id(message_id)
+                .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+                .send()
+                .map(|_| ())
+                .map_err(log_err!(log_ctx, "Error sending message to Telegram")),
+        );
+    }
+
+    /// Wait for a link to expire, and if it was never used, replace it with a button to generate a
+    /// new one
+    ///
+    /// `is_unused` is called once the link has expired, and `retry` is the callback that generates
+    /// the new link
+    fn offer_new_link<F, Fut>(
+        bot: &RcBot,
+        chat_id: Integer,
+        message_id: Integer,
+        language: Language,
+        expired: Text,
+        expires_at: &DateTime<Utc>,
+        is_unused: F,
+        retry: CallbackQueryMessage,
+    ) where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Item = bool, Error = EventError> + 'static,
+    {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        let bot2 = bot.clone();
+        let wait = expires_at
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or(Duration::from_secs(0));
+
+        bot.inner.handle.spawn(
+            Delay::new(Instant::now() + wait)
+                .map_err(|e| EventError::from(e.context(EventErrorKind::Timer)))
+                .and_then(move |_| is_unused())
+                .and_then(move |unused| {
+                    if unused {
+                        let buttons = vec![vec![
+                            InlineKeyboardButton::new(language.text(Text::NewLink).to_owned())
+                                .callback_data(serde_json::to_string(&retry).unwrap()),
+                        ]];
+
+                        Either::A(
+                            bot2.edit_message_text(language.text(expired).to_owned())
+                                .chat_id(chat_id)
+                                .message_id(message_id)
+                                .reply_markup(InlineKeyboardMarkup::new(buttons))
+                                .send()
+                                .map(|_| ())
+                                .map_err(telegram_error),
+                        )
+                    } else {
+                        Either::B(Ok(()).into_future())
+                    }
+                })
+                .map_err(log_err!(log_ctx, "Error offering new link")),
+        );
     }
 
-    fn handle_callback_query(&self, callback_query: CallbackQuery) {
-        debug!("handle callback query");
+    fn send_events(
+        bot: &RcBot,
+        db: &Addr<Unsync, DbBroker>,
+        chat_id: Integer,
+        events: Vec<Event>,
+        timezone: Tz,
+        language: Language,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        bot.inner.handle.spawn(
+            print_events(bot, db, chat_id, events, timezone, language)
+                .map(|_| ())
+                .map_err(log_err!(log_ctx, "Error sending events to Telegram")),
+        );
+    }
+
+    /// Send a list of events under `heading`, or `empty` if there aren't any
+    ///
+    /// Both are sent as HTML, so anything users wrote in them needs to be escaped
+    fn send_listing(
+        bot: &RcBot,
+        db: &Addr<Unsync, DbBroker>,
+        chat_id: Integer,
+        heading: String,
+        empty: String,
+        events: Vec<Event>,
+        timezone: Tz,
+        language: Language,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+        let bot = bot.clone();
+
+        let fut = listing_time_format(db, &events).and_then(move |time_format| {
+            let msg = if events.len() > 0 {
+                format!(
+                    "{}\n\n{}",
+                    heading,
+                    format_events(&events, timezone, language, time_format)
+                )
+            } else {
+                empty
+            };
+
+            bot.message(chat_id, msg)
+                .parse_mode(HTML)
+                .send()
+                .map_err(telegram_error)
+        });
+
+        Arbiter::handle().spawn(
+            fut.map(|_| ())
+                .map_err(log_err!(log_ctx, "Error sending events to Telegram")),
+        );
+    }
+
+    fn send_and_pin_events(
+        bot: &RcBot,
+        db: &Addr<Unsync, DbBroker>,
+        chat_id: Integer,
+        events: Vec<Event>,
+        timezone: Tz,
+        language: Language,
+    ) {
+        let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
+        bot.inner.handle.spawn(
+            print_events(bot, db, chat_id, events, timezone, language)
+                .map_err(log_err!(log_ctx, "Error sending events to Telegram"))
+                .and_then(move |(bot, message)| {
+                    let message_id = message.message_id;
+                    let chat_id = message.chat.id;
+
+                    bot.pin_chat_message(chat_id, message_id)
+                        .send()
+                        .map(|_| ())
+                        .map_err(log_err!(log_ctx, "Error pinning message"))
+                }),
+        );
+    }
+
+    fn print_id(bot: &RcBot, chat_id: Integer) {
+        send_message(bot, chat_id, format!("{}", chat_id));
+    }
+
+    fn linked(bot: &RcBot, channel_id: Integer, language: Language, chat_ids: Vec<Integer>) {
+        let chat_ids = chat_ids
+            .into_iter()
+            .map(|id| format!("{}", id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let msg = language.fill(Text::Linked, &[&channel_id, &chat_ids]);
+
+        send_message(bot, channel_id, msg);
+    }
+
+    fn created_channel(bot: &RcBot, channel_id: Integer, language: Language) {
+        send_message(bot, channel_id, language.text(Text::Initialized).to_owned());
+    }
+}
+
+/// The body of a sendMessage request for announcements that can't be forwarded or saved
+///
+/// The bot library doesn't know about `protect_content`, so these are sent as raw requests
+#[derive(Serialize)]
+struct ProtectedMessage {
+    chat_id: Integer,
+    text: String,
+    parse_mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<InlineKeyboardMarkup>,
+    protect_content: bool,
+}
+
+/// The body of a deleteMessage request
+///
+/// The bot library doesn't know about deleting messages, so these are sent as raw requests
+#[derive(Serialize)]
+struct DeletedMessage {
+    chat_id: Integer,
+    message_id: Integer,
+}
+
+/// The body of an unpinChatMessage request
+///
+/// The bot library can only unpin the most recently pinned message, so these are sent as raw
+/// requests
+#[derive(Serialize)]
+struct UnpinnedMessage {
+    chat_id: Integer,
+    message_id: Integer,
+}
+
+/// The body of a sendPhoto request for announcements with a picture
+///
+/// The bot library can only upload photos from files, so these are sent as raw requests, which
+/// lets Telegram fetch the photo from its URL
+#[derive(Serialize)]
+struct PhotoMessage {
+    chat_id: Integer,
+    photo: String,
+    caption: String,
+    parse_mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<InlineKeyboardMarkup>,
+    protect_content: bool,
+}
+
+/// The body of an editMessageCaption request
+///
+/// The bot library doesn't know about editing captions, so these are sent as raw requests
+#[derive(Serialize)]
+struct EditedCaption {
+    chat_id: Integer,
+    message_id: Integer,
+    caption: String,
+    reply_markup: InlineKeyboardMarkup,
+}
+
+/// The body of an editMessageReplyMarkup request
+///
+/// The bot library doesn't know about editing only a message's buttons, so these are sent as raw
+/// requests
+#[derive(Serialize)]
+struct EditedReplyMarkup {
+    chat_id: Integer,
+    message_id: Integer,
+    reply_markup: InlineKeyboardMarkup,
+}
 
-        let user_id = callback_query.from.id;
+/// The body of a setWebhook request
+///
+/// The bot library doesn't know about webhooks, so these are sent as raw requests
+#[derive(Serialize)]
+struct WebhookSettings {
+    url: String,
+}
 
-        if let Some(msg) = callback_query.message {
-            let chat_id = msg.chat.id;
-            let message_id = msg.message_id;
+/// The body of a setMyCommands request
+///
+/// The bot library doesn't know about registering commands, so these are sent as raw requests
+#[derive(Serialize)]
+struct MyCommands {
+    commands: Vec<BotCommand>,
+    scope: CommandScope,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_code: Option<&'static str>,
+}
 
-            if let Some(data) = callback_query.data {
-                if let Ok(query_data) = serde_json::from_str::<CallbackQueryMessage>(&data) {
-                    if let Ok(mut rng) = OsRng::new() {
-                        let mut bytes = [0; 8];
+/// A command suggested to users as they type, in a setMyCommands request
+#[derive(Serialize)]
+struct BotCommand {
+    command: &'static str,
+    description: &'static str,
+}
 
-                        rng.fill_bytes(&mut bytes);
-                        let base64d = encode(ENCODING_ALPHABET, &bytes);
+/// The chats a setMyCommands request's commands are suggested in
+#[derive(Clone, Serialize)]
+struct CommandScope {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chat_id: Option<Integer>,
+}
 
-                        if let Ok(secret) = generate_secret(&base64d) {
-                            let db = self.db.clone();
-                            let db2 = self.db.clone();
-                            let bot = self.bot.clone();
-                            let users = self.users.clone();
-
-                            let url = self.url.clone();
-                            match query_data {
-                                CallbackQueryMessage::NewEvent { channel_id } => {
-                                    // Spawn a future that creates a new event
-                                    debug!("channel_id: {}", channel_id);
-                                    Arbiter::handle().spawn(
-                                        self.db
-                                            .send(LookupUser(user_id))
-                                            .then(flatten)
-                                            .and_then(move |user| {
-                                                db.send(LookupSystemByChannel(channel_id))
-                                                    .then(flatten)
-                                                    .map(|chat_system| (chat_system, user))
-                                            })
-                                            .and_then(move |(chat_system, user)| {
-                                                let events_channel = chat_system.events_channel();
-                                                users
-                                                    .send(LookupChannels(user.user_id()))
-                                                    .then(flatten)
-                                                    .and_then(move |channel_ids| {
-                                                        if channel_ids.contains(&events_channel) {
-                                                            Ok(())
-                                                        } else {
-                                                            Err(EventErrorKind::Permissions.into())
-                                                        }
-                                                    })
-                                                    .and_then(move |_| {
-                                                        db2.send(StoreEventLink {
-                                                            user_id: user.id(),
-                                                            system_id: chat_system.id(),
-                                                            secret,
-                                                        }).then(flatten)
-                                                    })
-                                            })
-                                            .then(move |nel| match nel {
-                                                Ok(nel) => Ok(TelegramActor::edit_with_url(
-                                                    &bot,
-                                                    chat_id,
-                                                    message_id,
-                                                    "create".to_owned(),
-                                                    format!(
-                                                        "{}/events/new/{}={}",
-                                                        url,
-                                                        base64d,
-                                                        nel.id()
-                                                    ),
-                                                )),
-                                                Err(e) => {
-                                                    TelegramActor::send_error(
-                                                        &bot,
-                                                        chat_id,
-                                                        "Failed to generate new event link",
-                                                    );
-                                                    Err(e)
-                                                }
-                                            })
-                                            .map_err(|e| error!("Error: {:?}", e)),
-                                    );
-                                }
-                                CallbackQueryMessage::EditEvent { event_id } => {
-                                    // Spawn a future that updates a given event
-                                    Arbiter::handle().spawn(
-                                        self.db
-                                            .send(LookupEvent { event_id })
-                                            .then(flatten)
-                                            .and_then(move |event| {
-                                                if event
-                                                    .hosts()
-                                                    .iter()
-                                                    .any(|host| host.user_id() == user_id)
-                                                {
-                                                    Ok(event)
-                                                } else {
-                                                    Err(EventErrorKind::Lookup.into())
-                                                }
-                                            })
-                                            .and_then(move |event| {
-                                                let e2 = event.clone();
-                                                let host = e2.hosts()
-                                                    .iter()
-                                                    .find(|host| host.user_id() == user_id)
-                                                    .unwrap();
-
-                                                db2.send(StoreEditEventLink {
-                                                    user_id: host.id(),
-                                                    system_id: event.system_id(),
-                                                    event_id: event.id(),
-                                                    secret,
-                                                }).then(flatten)
-                                            })
-                                            .then(move |eel| match eel {
-                                                Ok(eel) => Ok(TelegramActor::edit_with_url(
-                                                    &bot,
-                                                    chat_id,
-                                                    message_id,
-                                                    "update".to_owned(),
-                                                    format!(
-                                                        "{}/events/edit/{}={}",
-                                                        url,
-                                                        base64d,
-                                                        eel.id()
-                                                    ),
-                                                )),
-                                                Err(e) => {
-                                                    TelegramActor::send_error(
-                                                        &bot,
-                                                        chat_id,
-                                                        "Unable to generate edit link",
-                                                    );
-                                                    Err(e)
-                                                }
-                                            })
-                                            .map_err(|e| error!("Error: {:?}", e)),
-                                    );
-                                }
-                                CallbackQueryMessage::DeleteEvent {
-                                    event_id,
-                                    system_id,
-                                } => {
-                                    let db = self.db.clone();
-                                    let bot2 = self.bot.clone();
-
-                                    Arbiter::handle().spawn(
-                                        // Spawn a future taht deletes the given event
-                                        self.db
-                                            .send(LookupEvent { event_id })
-                                            .then(flatten)
-                                            .or_else(move |e| {
-                                                TelegramActor::send_error(
-                                                    &bot2,
-                                                    chat_id,
-                                                    "Failed to delete event",
-                                                );
-                                                Err(e)
-                                            })
-                                            .map_err(|e| {
-                                                error!("Error finding event to delete: {:?}", e)
-                                            })
-                                            .and_then(move |event| {
-                                                let title = event.title().to_owned();
-                                                db.send(DeleteEvent { event_id })
-                                                    .then(flatten)
-                                                    .and_then(move |_| {
-                                                        db.send(LookupSystem { system_id })
-                                                            .then(flatten)
-                                                    })
-                                                    .then(move |chat_system| match chat_system {
-                                                        Ok(chat_system) => {
-                                                            Ok(TelegramActor::event_deleted(
-                                                                &bot,
-                                                                chat_id,
-                                                                chat_system.events_channel(),
-                                                                title,
-                                                            ))
-                                                        }
-                                                        Err(e) => {
-                                                            TelegramActor::send_error(
-                                                                &bot,
-                                                                chat_id,
-                                                                "Failed to delete event",
-                                                            );
-                                                            Err(e)
-                                                        }
-                                                    })
-                                                    .map_err(|e| error!("Error: {:?}", e))
-                                            }),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+impl CommandScope {
+    /// Every chat of the given kind, like `all_private_chats`
+    fn all(kind: &'static str) -> Self {
+        CommandScope {
+            kind,
+            chat_id: None,
         }
     }
 
-    fn event_soon(&self, event: Event) {
-        let bot = self.bot.clone();
+    /// Only the chat with the given ID
+    fn chat(chat_id: Integer) -> Self {
+        CommandScope {
+            kind: "chat",
+            chat_id: Some(chat_id),
+        }
+    }
+}
 
-        let fut = self.db
-            .send(LookupSystemWithChats {
-                system_id: event.system_id(),
-            })
-            .then(flatten)
-            .and_then(move |(chat_system, chats)| {
-                for chat in chats {
-                    bot.inner.handle.spawn(
-                        bot.message(
-                            chat,
-                            format!("Don't forget! {} is starting soon!", event.title()),
-                        ).send()
-                            .map(|_| ())
-                            .map_err(|e| error!("Error: {:?}", e)),
-                    );
-                }
+/// The body of a getChatMember request
+///
+/// The bot library's version of this request is misnamed, so these are sent as raw requests
+#[derive(Serialize)]
+struct ChatMemberQuery {
+    chat_id: Integer,
+    user_id: Integer,
+}
 
-                bot.message(
-                    chat_system.events_channel(),
-                    format!("Don't forget! {} is starting soon!", event.title()),
-                ).send()
-                    .map_err(|e| e.context(EventErrorKind::Telegram).into())
-            })
-            .map(|_| ())
-            .map_err(|e| error!("Error: {:?}", e));
+/// The parts of a getChatMember response that say what an admin can do in a channel
+///
+/// The bot library's `ChatMember` doesn't include an admin's rights
+#[derive(Deserialize)]
+struct ChannelRights {
+    status: String,
+    #[serde(default)]
+    can_post_messages: bool,
+    #[serde(default)]
+    can_edit_messages: bool,
+}
 
-        self.bot.inner.handle.spawn(fut);
-    }
+/// The body of an answerCallbackQuery request
+///
+/// The bot library doesn't know about answering callback queries, so these are sent as raw
+/// requests
+#[derive(Serialize)]
+struct CallbackAnswer {
+    callback_query_id: String,
+    text: String,
+}
 
-    fn event_over(&self, event: Event) {
-        let bot = self.bot.clone();
+/// The body of an answerInlineQuery request
+///
+/// The bot library doesn't know about inline queries, so these are sent as raw requests
+#[derive(Serialize)]
+struct InlineQueryAnswer {
+    inline_query_id: String,
+    results: Vec<InlineArticle>,
+    cache_time: u32,
+    is_personal: bool,
+}
 
-        let id = event.id();
-        let system_id = event.system_id();
+/// An event offered as the answer to an inline query
+#[derive(Serialize)]
+struct InlineArticle {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: String,
+    title: String,
+    description: String,
+    input_message_content: InlineMessageContent,
+}
 
-        let fut = self.db
-            .send(LookupSystemWithChats { system_id })
-            .then(flatten)
-            .and_then(move |(chat_system, chats)| {
-                for chat in chats {
-                    bot.inner.handle.spawn(
-                        bot.message(chat, format!("{} has ended!", event.title()))
-                            .send()
-                            .map(|_| ())
-                            .map_err(|e| error!("Error: {:?}", e)),
-                    );
-                }
+/// The message posted when an `InlineArticle` is picked
+#[derive(Serialize)]
+struct InlineMessageContent {
+    message_text: String,
+    parse_mode: &'static str,
+}
 
-                bot.message(
-                    chat_system.events_channel(),
-                    format!("{} has ended!", event.title()),
-                ).send()
-                    .map_err(|e| e.context(EventErrorKind::Telegram).into())
-            })
-            .map(|_| ())
-            .map_err(|e| error!("Error: {:?}", e));
+impl InlineArticle {
+    /// Offer an event's card, linking to its public page if it has one
+    fn from_event(event: &Event, url: &str, timezone: Tz, language: Language) -> Self {
+        let link = format!("{}/events/view/{}", url, event.public_id());
 
-        self.bot.inner.handle.spawn(fut);
+        let card = EventCard::from_event(event, timezone)
+            .hosts(event.hosts())
+            .capacity(event.capacity())
+            .members_only(event.members_only())
+            .language(language)
+            .html();
 
-        self.query_events(id, system_id);
+        let message_text = if event.members_only() || event.unlisted() {
+            card.to_string()
+        } else {
+            card.link(&link).to_string()
+        };
+
+        InlineArticle {
+            kind: "article",
+            id: event.public_id().to_owned(),
+            title: event.title().to_owned(),
+            description: format_localized_date(
+                event.start_date().with_timezone(&timezone),
+                language,
+                TimeFormat::default(),
+            ),
+            input_message_content: InlineMessageContent {
+                message_text,
+                parse_mode: HTML,
+            },
+        }
     }
+}
 
-    fn event_started(&self, event: Event) {
-        let bot = self.bot.clone();
+/// Take down an event's announcement after the event is deleted
+///
+/// Bots can only delete messages for 48 hours after sending them, so if the announcement can't be
+/// deleted, it's edited to say the event was cancelled instead
+fn remove_announcement(
+    bot: &RcBot,
+    channel_id: Integer,
+    message_id: Integer,
+    language: Language,
+    title: String,
+) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&DeletedMessage {
+        chat_id: channel_id,
+        message_id,
+    }).unwrap();
 
-        let fut = self.db
-            .send(LookupSystemWithChats {
-                system_id: event.system_id(),
-            })
-            .then(flatten)
-            .and_then(move |(chat_system, chats)| {
-                for chat in chats {
-                    bot.inner.handle.spawn(
-                        bot.message(chat, format!("{} has started!", event.title()))
-                            .send()
-                            .map(|_| ())
-                            .map_err(|e| error!("Error: {:?}", e)),
-                    );
-                }
+    let bot2 = bot.clone();
 
-                bot.message(
-                    chat_system.events_channel(),
-                    format!("{} has started!", event.title()),
-                ).send()
-                    .map_err(|e| e.context(EventErrorKind::Telegram).into())
-            })
-            .map(|_| ())
-            .map_err(|e| error!("Error: {:?}", e));
+    bot.inner
+        .fetch_json("deleteMessage", &body)
+        .map(|_| ())
+        .or_else(move |_| {
+            let text = format!("{}\n\n{}", language.text(Text::Cancelled), title);
 
-        self.bot.inner.handle.spawn(fut);
-    }
+            mark_cancelled(&bot2, channel_id, message_id, text)
+        })
+}
 
-    fn new_event(&self, event: Event) {
-        let localtime = event.start_date().with_timezone(&Central);
-        let when = format_date(localtime);
-        let hosts = event
-            .hosts()
-            .iter()
-            .map(|host| format!("@{}", host.username()))
-            .collect::<Vec<_>>()
-            .join(", ");
+/// Replace an announcement's text with `text`, removing its RSVP buttons
+///
+/// Announcements posted with a picture have a caption instead of text, so the caption is edited if
+/// the text can't be
+fn mark_cancelled(
+    bot: &RcBot,
+    channel_id: Integer,
+    message_id: Integer,
+    text: String,
+) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&EditedCaption {
+        chat_id: channel_id,
+        message_id,
+        caption: text.clone(),
+        reply_markup: InlineKeyboardMarkup::new(vec![vec![]]),
+    }).unwrap();
 
-        let length = format_duration(&event);
+    let bot2 = bot.clone();
 
-        let bot = self.bot.clone();
+    bot.edit_message_text(text)
+        .chat_id(channel_id)
+        .message_id(message_id)
+        .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
+        .send()
+        .map(|_| ())
+        .or_else(move |_| {
+            bot2.inner
+                .fetch_json("editMessageCaption", &body)
+                .map(|_| ())
+        })
+        .map_err(telegram_error)
+}
 
-        let fut = self.db
-            .send(LookupSystem {
-                system_id: event.system_id(),
-            })
-            .then(flatten)
-            .and_then(move |chat_system| {
-                bot.message(
-                    chat_system.events_channel(),
-                    format!(
-                        "New Event!\n{}\nWhen: {}\nDuration: {}\nDescription: {}\nHosts: {}",
-                        event.title(),
-                        when,
-                        length,
-                        event.description(),
-                        hosts
-                    ),
-                ).send()
-                    .map_err(|e| e.context(EventErrorKind::Telegram).into())
-            })
-            .map(|_| ())
-            .map_err(|e| error!("Error: {:?}", e));
+/// Remove an announcement's RSVP buttons, leaving its text or caption as it is
+fn remove_rsvp_buttons(
+    bot: &RcBot,
+    channel_id: Integer,
+    message_id: Integer,
+) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&EditedReplyMarkup {
+        chat_id: channel_id,
+        message_id,
+        reply_markup: InlineKeyboardMarkup::new(vec![vec![]]),
+    }).unwrap();
 
-        self.bot.inner.handle.spawn(fut);
-    }
+    bot.inner
+        .fetch_json("editMessageReplyMarkup", &body)
+        .map(|_| ())
+        .map_err(telegram_error)
+}
 
-    fn update_event(&self, event: Event) {
-        let localtime = event.start_date().with_timezone(&Central);
-        let when = format_date(localtime);
+/// Post a Chat System's greeting to its channel and pin it, taking down the previous greeting
+///
+/// If the greeting was turned off, the previous greeting is only taken down. Bots can only delete
+/// messages for 48 hours after sending them, so an old greeting that can't be deleted is left in
+/// place.
+fn replace_greeting(
+    bot: &RcBot,
+    db: &Addr<Unsync, DbBroker>,
+    chat_system: ChatSystem,
+    old_message_id: Option<Integer>,
+) -> impl Future<Item = (), Error = EventError> {
+    let bot2 = bot.clone();
+    let channel_id = chat_system.events_channel();
+    let system_id = chat_system.id();
 
-        let length = format_duration(&event);
+    let posted = if chat_system.greeting().is_some() {
+        let bot = bot.clone();
+        let db = db.clone();
 
-        let bot = self.bot.clone();
+        Either::A(
+            bot.message(channel_id, format_greeting(&chat_system))
+                .send()
+                .map(|(_, message)| message.message_id)
+                .map_err(telegram_error)
+                .and_then(move |message_id| {
+                    db.send(SetGreetingMessage {
+                        system_id,
+                        message_id: Some(message_id),
+                    }).then(flatten)
+                        .and_then(move |_| {
+                            bot.pin_chat_message(channel_id, message_id)
+                                .send()
+                                .map(|_| ())
+                                .map_err(telegram_error)
+                        })
+                }),
+        )
+    } else {
+        Either::B(
+            db.send(SetGreetingMessage {
+                system_id,
+                message_id: None,
+            }).then(flatten),
+        )
+    };
 
-        let fut = self.db
-            .send(LookupSystem {
-                system_id: event.system_id(),
-            })
-            .then(flatten)
-            .and_then(move |chat_system| {
-                bot.message(
-                    chat_system.events_channel(),
-                    format!(
-                        "Event Updated!\n{}\nWhen: {}\nDuration: {}\nDescription: {}",
-                        event.title(),
-                        when,
-                        length,
-                        event.description(),
-                    ),
-                ).send()
-                    .map_err(|e| e.context(EventErrorKind::Telegram).into())
-            })
-            .map(|_| ())
-            .map_err(|e| error!("Error: {:?}", e));
+    posted.and_then(move |_| match old_message_id {
+        Some(message_id) => {
+            let body = serde_json::to_string(&DeletedMessage {
+                chat_id: channel_id,
+                message_id,
+            }).unwrap();
 
-        self.bot.inner.handle.spawn(fut);
-    }
+            Either::A(
+                bot2.inner
+                    .fetch_json("deleteMessage", &body)
+                    .then(|_| Ok(())),
+            )
+        }
+        None => Either::B(Ok(()).into_future()),
+    })
+}
 
-    fn query_events(&self, event_id: i32, system_id: i32) {
-        let db = self.db.clone();
-        let bot = self.bot.clone();
+/// Bring a channel's pinned greeting up to date after its settings change
+///
+/// Channels without a greeting are left alone
+fn refresh_greeting(bot: &RcBot, db: &Addr<Unsync, DbBroker>, channel_id: Integer) {
+    let bot2 = bot.clone();
+    let log_ctx = LogContext::new("telegram").chat_id(channel_id);
 
-        let fut = self.db
-            .send(LookupSystem { system_id })
-            .then(flatten)
-            .map_err(|e| {
-                error!("LookupSystem");
-                e
-            })
-            .and_then(move |chat_system: ChatSystem| {
-                db.send(GetEventsForSystem { system_id })
-                    .then(flatten)
-                    .map_err(|e| {
-                        error!("GetEventsForSystem");
-                        e
-                    })
-                    .and_then(move |events: Vec<Event>| {
-                        let events = events
-                            .into_iter()
-                            .filter(|event| event.id() != event_id)
-                            .collect();
+    let fut = db
+        .send(LookupSystemByChannel(channel_id))
+        .then(flatten)
+        .and_then(move |chat_system| {
+            let message_id = match (chat_system.greeting(), chat_system.greeting_message_id()) {
+                (Some(_), Some(message_id)) => message_id,
+                _ => return Either::B(Ok(()).into_future()),
+            };
 
-                        print_events(&bot, chat_system.events_channel(), events).map(|_| ())
-                    })
-            });
+            Either::A(
+                bot2.edit_message_text(format_greeting(&chat_system))
+                    .chat_id(channel_id)
+                    .message_id(message_id)
+                    .send()
+                    .map(|_| ())
+                    .map_err(telegram_error),
+            )
+        })
+        .map_err(log_err!(log_ctx, "Error refreshing greeting"));
 
-        self.bot
-            .inner
-            .handle
-            .spawn(fut.map(|_| ()).map_err(|e| error!("Error: {:?}", e)));
+    bot.inner.handle.spawn(fut);
+}
+
+/// Pin an event's announcement in a Chat System's channel, remembering it so the announcement can
+/// be unpinned when the event ends
+fn pin_announcement(
+    bot: &RcBot,
+    db: &Addr<Unsync, DbBroker>,
+    chat_system: &ChatSystem,
+    message_id: Integer,
+    event_id: i32,
+) -> impl Future<Item = (), Error = EventError> {
+    let db = db.clone();
+    let system_id = chat_system.id();
+
+    bot.pin_chat_message(chat_system.events_channel(), message_id)
+        .send()
+        .map_err(telegram_error)
+        .and_then(move |_| {
+            db.send(SetPinnedEvent {
+                system_id,
+                event_id,
+                message_id,
+            }).then(flatten)
+        })
+}
+
+/// Unpin one message in a chat, leaving any others pinned
+fn unpin_message(
+    bot: &RcBot,
+    chat_id: Integer,
+    message_id: Integer,
+) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&UnpinnedMessage {
+        chat_id,
+        message_id,
+    }).unwrap();
+
+    bot.inner
+        .fetch_json("unpinChatMessage", &body)
+        .map(|_| ())
+        .map_err(telegram_error)
+}
+
+/// How a banned user is named in the bot's replies
+fn banned_name(user: &BannedUser, language: Language) -> String {
+    match *user {
+        BannedUser::Username(ref username) => format!("@{}", username),
+        BannedUser::UserId(user_id) => language.fill(Text::UserById, &[&user_id]),
     }
+}
 
-    fn ask_chats(bot: RcBot, channels: HashSet<Integer>, chat_id: Integer) {
-        let bot2 = bot.clone();
-        let bot3 = bot.clone();
+/// Describe the secrets a channel's webhooks are signed with
+///
+/// Webhooks added before secrets were shared by the whole channel keep their own until the
+/// channel's secret is replaced, so those are listed by URL
+fn webhook_secrets_text(webhooks: &[Webhook], language: Language) -> String {
+    let shared = webhooks
+        .first()
+        .map(|first| webhooks.iter().all(|webhook| webhook.secret() == first.secret()));
+
+    let secrets = match shared {
+        Some(true) => language.fill(Text::SharedSecret, &[&webhooks[0].secret()]),
+        Some(false) => webhooks.iter().fold(
+            language.text(Text::WebhookSecrets).to_owned(),
+            |acc, webhook| format!("{}\n{}: {}", acc, webhook.url(), webhook.secret()),
+        ),
+        None => return language.text(Text::NoWebhooks).to_owned(),
+    };
+
+    language.fill(Text::SaveSecret, &[&secrets])
+}
+
+/// Check whether a user is one of a channel's admins
+fn channel_admin(
+    bot: &RcBot,
+    channel_id: Integer,
+    user_id: Integer,
+) -> impl Future<Item = bool, Error = EventError> {
+    bot.unban_chat_administrators(channel_id)
+        .send()
+        .map_err(telegram_lookup_error)
+        .map(move |(_, admins)| admins.into_iter().any(|admin| admin.user.id == user_id))
+}
+
+/// Check whether an event's announcements should link to its public page
+///
+/// Members only and unlisted events don't have one, and neither do events in channels that turned
+/// public pages off
+fn has_public_page(event: &Event, features: Features) -> bool {
+    !event.members_only() && !event.unlisted() && features.enabled(Feature::PublicPage)
+}
+
+/// Send an announcement to an events channel, protecting it from forwarding if the channel asks
+/// for it
+///
+/// Announcements are formatted as HTML
+///
+/// The future resolves to the ID of the sent message
+fn send_announcement(
+    bot: &RcBot,
+    chat_id: Integer,
+    text: String,
+    reply_markup: Option<InlineKeyboardMarkup>,
+    protect_content: bool,
+) -> impl Future<Item = Integer, Error = EventError> {
+    if protect_content {
+        let body = serde_json::to_string(&ProtectedMessage {
+            chat_id,
+            text,
+            parse_mode: HTML,
+            reply_markup,
+            protect_content,
+        }).unwrap();
+
+        Either::A(
+            bot.inner
+                .fetch_json("sendMessage", &body)
+                .map_err(telegram_error)
+                .and_then(|json| {
+                    serde_json::from_str::<Message>(&json)
+                        .map(|message| message.message_id)
+                        .map_err(telegram_error)
+                }),
+        )
+    } else {
+        let message = bot.message(chat_id, text).parse_mode(HTML);
 
-        let fut_iter = channels.into_iter().map(move |channel_id| {
-            bot.clone()
-                .get_chat(channel_id)
+        let message = match reply_markup {
+            Some(reply_markup) => message.reply_markup(reply_markup),
+            None => message,
+        };
+
+        Either::B(
+            message
                 .send()
-                .map_err(|e| e.context(EventErrorKind::TelegramLookup).into())
-                .map(move |(_, channel)| {
-                    debug!("Asking about channel_id: {}", channel.id);
-                    InlineKeyboardButton::new(
-                        channel
-                            .title
-                            .unwrap_or(channel.username.unwrap_or("No title".to_owned())),
-                    ).callback_data(
-                        serde_json::to_string(&CallbackQueryMessage::NewEvent {
-                            channel_id: channel.id,
-                        }).unwrap(),
-                    )
-                })
-        });
+                .map(|(_, message)| message.message_id)
+                .map_err(telegram_error),
+        )
+    }
+}
 
-        let fut = futures_unordered(fut_iter)
-            .collect()
-            .and_then(move |buttons| {
-                let msg = if buttons.len() > 0 {
-                    let buttons = buttons.into_iter().fold(
-                        Vec::new(),
-                        |mut acc: Vec<Vec<_>>, button| {
-                            let len = acc.len();
+/// Send an announcement with a picture to an events channel, using the announcement's HTML as the
+/// caption
+///
+/// The future resolves to the ID of the sent message
+fn send_photo_announcement(
+    bot: &RcBot,
+    chat_id: Integer,
+    photo: String,
+    caption: String,
+    reply_markup: Option<InlineKeyboardMarkup>,
+    protect_content: bool,
+) -> impl Future<Item = Integer, Error = EventError> {
+    let body = serde_json::to_string(&PhotoMessage {
+        chat_id,
+        photo,
+        caption,
+        parse_mode: HTML,
+        reply_markup,
+        protect_content,
+    }).unwrap();
 
-                            if len > 0 {
-                                if acc[len - 1].len() < 2 {
-                                    acc[len - 1].push(button);
-                                } else {
-                                    acc.push(vec![button]);
-                                }
-                            } else {
-                                acc.push(vec![button]);
-                            }
+    bot.inner
+        .fetch_json("sendPhoto", &body)
+        .map_err(telegram_error)
+        .and_then(|json| {
+            serde_json::from_str::<Message>(&json)
+                .map(|message| message.message_id)
+                .map_err(telegram_error)
+        })
+}
 
-                            acc
-                        },
-                    );
+/// Set the URL Telegram sends updates to. An empty URL removes the webhook, so the bot can poll for
+/// updates again.
+fn set_webhook(bot: &RcBot, url: String) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&WebhookSettings { url }).unwrap();
 
-                    bot2.message(
-                        chat_id,
-                        "Which channel would you like to create an event for?".to_owned(),
-                    ).reply_markup(InlineKeyboardMarkup::new(buttons))
-                } else {
-                    bot2.message(chat_id, "You aren't in any chats with an associated events channel. If you believe this a mistake, please send a message in the associated chat first, then try again".to_owned())
-                };
+    bot.inner
+        .fetch_json("setWebhook", &body)
+        .map(|_| ())
+        .map_err(telegram_error)
+}
 
-                msg.send()
-                    .map_err(|e| EventError::from(e.context(EventErrorKind::Telegram)))
-            });
+/// Tag log lines for an update with its ID and the chat it came from
+/// Get the user or chat whose language replies to an update go out in
+///
+/// Button presses and inline queries are answered in the language of the user who sent them, and
+/// messages in the language of the chat they were sent in
+fn reply_chat_id(update: &Update) -> Option<Integer> {
+    update
+        .message
+        .as_ref()
+        .or(update.channel_post.as_ref())
+        .map(|message| message.chat.id)
+        .or(update
+            .callback_query
+            .as_ref()
+            .map(|callback_query| callback_query.from.id))
+        .or(update
+            .inline_query
+            .as_ref()
+            .map(|inline_query| inline_query.from.id))
+}
 
-        bot3.inner
-            .handle
-            .spawn(fut.map(|_| ()).map_err(|e| error!("Error: {:?}", e)));
-    }
+fn update_log_context(update: &Update) -> LogContext {
+    let log_ctx = LogContext::new("telegram").update_id(update.update_id);
 
-    fn ask_delete_events(bot: RcBot, events: Vec<Event>, chat_id: Integer) {
-        let bot2 = bot.clone();
+    let chat_id = update
+        .message
+        .as_ref()
+        .or(update.channel_post.as_ref())
+        .or(update
+            .callback_query
+            .as_ref()
+            .and_then(|callback_query| callback_query.message.as_ref()))
+        .map(|message| message.chat.id);
 
-        let fut = iter_ok(events)
-            .map(|event| {
-                InlineKeyboardButton::new(event.title().to_owned()).callback_data(
-                    serde_json::to_string(&CallbackQueryMessage::DeleteEvent {
-                        event_id: event.id(),
-                        system_id: event.system_id(),
-                    }).unwrap(),
-                )
-            })
-            .collect()
-            .and_then(move |buttons| {
-                let msg = if buttons.len() > 0 {
-                    let buttons = buttons.into_iter().fold(
-                        Vec::new(),
-                        |mut acc: Vec<Vec<_>>, button| {
-                            let len = acc.len();
+    match chat_id {
+        Some(chat_id) => log_ctx.chat_id(chat_id),
+        None => log_ctx,
+    }
+}
 
-                            if len > 0 {
-                                if acc[len - 1].len() < 2 {
-                                    acc[len - 1].push(button);
-                                } else {
-                                    acc.push(vec![button]);
-                                }
-                            } else {
-                                acc.push(vec![button]);
-                            }
+/// Get the notification named in a `/mute` or `/unmute` command
+///
+/// An empty name means every notification. `None` means the name isn't one the bot knows.
+fn notification_from_name(name: &str) -> Option<Option<Notification>> {
+    if name.is_empty() {
+        Some(None)
+    } else {
+        Notification::from_name(name).map(Some)
+    }
+}
 
-                            acc
-                        },
-                    );
+/// The label a notification is shown with in the `/notifications` menu
+fn notification_label(notification: Notification, language: Language) -> &'static str {
+    let label = match notification {
+        Notification::Reminders => Text::RemindersLabel,
+        Notification::Broadcasts => Text::BroadcastsLabel,
+        Notification::HostNudges => Text::HostNudgesLabel,
+        Notification::WaitlistPromotions => Text::WaitlistLabel,
+    };
 
-                    bot2.message(chat_id, "Which event would you like to delete?".to_owned())
-                        .reply_markup(InlineKeyboardMarkup::new(buttons))
-                } else {
-                    bot2.message(chat_id, "You aren't hosting any events".to_owned())
-                };
-                msg.send()
-                    .map_err(|e| EventError::from(e.context(EventErrorKind::Telegram)))
-            });
+    language.text(label)
+}
 
-        bot.inner
-            .handle
-            .spawn(fut.map(|_| ()).map_err(|e| error!("Error: {:?}", e)));
+/// The buttons for RSVPing to an event from its announcement
+fn rsvp_buttons(event_id: i32, language: Language) -> Vec<Vec<InlineKeyboardButton>> {
+    vec![vec![
+        InlineKeyboardButton::new(language.text(Text::GoingButton).to_owned()).callback_data(
+            serde_json::to_string(&CallbackQueryMessage::Rsvp { event_id }).unwrap(),
+        ),
+        InlineKeyboardButton::new(language.text(Text::CantGoButton).to_owned()).callback_data(
+            serde_json::to_string(&CallbackQueryMessage::CancelRsvp { event_id }).unwrap(),
+        ),
+    ]]
+}
+
+/// Whether something is on or off, in the given language
+fn on_off(on: bool, language: Language) -> &'static str {
+    if on {
+        language.text(Text::On)
+    } else {
+        language.text(Text::Off)
     }
+}
 
-    fn ask_events(bot: RcBot, events: Vec<Event>, chat_id: Integer) {
-        let bot2 = bot.clone();
+/// Build the `/notifications` menu, with one button per notification that flips whether it's muted
+fn notifications_keyboard(
+    preferences: &UserPreferences,
+    language: Language,
+) -> InlineKeyboardMarkup {
+    let buttons = NOTIFICATIONS
+        .iter()
+        .map(|notification| {
+            let muted = preferences.muted(*notification);
 
-        let fut = iter_ok(events)
-            .map(|event| {
-                InlineKeyboardButton::new(event.title().to_owned()).callback_data(
-                    serde_json::to_string(&CallbackQueryMessage::EditEvent {
-                        event_id: event.id(),
+            vec![
+                InlineKeyboardButton::new(format!(
+                    "{}: {}",
+                    notification_label(*notification, language),
+                    on_off(!muted, language)
+                )).callback_data(
+                    serde_json::to_string(&CallbackQueryMessage::Mute {
+                        name: notification.name().to_owned(),
+                        muted: !muted,
                     }).unwrap(),
-                )
-            })
-            .collect()
-            .and_then(move |buttons| {
-                let msg = if buttons.len() > 0 {
-                    let buttons = buttons.into_iter().fold(
-                        Vec::new(),
-                        |mut acc: Vec<Vec<_>>, button| {
-                            let len = acc.len();
+                ),
+            ]
+        })
+        .collect();
 
-                            if len > 0 {
-                                if acc[len - 1].len() < 2 {
-                                    acc[len - 1].push(button);
-                                } else {
-                                    acc.push(vec![button]);
-                                }
-                            } else {
-                                acc.push(vec![button]);
-                            }
+    InlineKeyboardMarkup::new(buttons)
+}
 
-                            acc
-                        },
-                    );
+/// Describe which private messages a user gets, after they've changed their preferences
+fn describe_preferences(preferences: &UserPreferences, language: Language) -> String {
+    let list = NOTIFICATIONS
+        .iter()
+        .map(|notification| {
+            let state = on_off(!preferences.muted(*notification), language);
 
-                    bot2.message(chat_id, "Which event would you like to edit?".to_owned())
-                        .reply_markup(InlineKeyboardMarkup::new(buttons))
-                } else {
-                    bot2.message(chat_id, "You aren't hosting any events".to_owned())
-                };
-                msg.send()
-                    .map_err(|e| EventError::from(e.context(EventErrorKind::Telegram)))
-            });
+            format!("{}: {}", notification.name(), state)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
 
-        bot.inner
-            .handle
-            .spawn(fut.map(|_| ()).map_err(|e| error!("Error: {:?}", e)));
-    }
+    language.fill(Text::Preferences, &[&list])
+}
 
-    fn event_deleted(bot: &RcBot, chat_id: Integer, channel_id: Integer, title: String) {
-        send_message(bot, chat_id, "Deleted event!".to_owned());
+/// List the languages the bot speaks, and how to pick one
+fn language_usage(language: Language) -> String {
+    let list = LANGUAGES
+        .iter()
+        .map(|option| format!("{}: {}", option.code(), option.name()))
+        .collect::<Vec<_>>()
+        .join("\n");
 
-        send_message(bot, channel_id, format!("Event deleted: {}", title));
-    }
+    language.fill(Text::LanguageUsage, &[&list])
+}
 
-    fn notify_private(&self, chat_id: Integer) {
-        send_message(
-            &self.bot,
-            chat_id,
-            "Please send this command as a private message".to_owned(),
-        );
-    }
+/// List which features a channel has turned on, and how to change them
+fn describe_features(features: &Features, language: Language) -> String {
+    let list = FEATURES
+        .iter()
+        .map(|feature| {
+            let state = on_off(features.enabled(*feature), language);
 
-    fn is_admin(
-        bot: RcBot,
-        channel_id: Integer,
-        chat_ids: Vec<Integer>,
-    ) -> impl Future<Item = Vec<Integer>, Error = EventError> {
-        bot.unban_chat_administrators(channel_id)
-            .send()
-            .map_err(|e| EventError::from(e.context(EventErrorKind::TelegramLookup)))
-            .and_then(move |(bot, admins)| {
-                let channel_admins = admins
-                    .into_iter()
-                    .map(|admin| admin.user.id)
-                    .collect::<HashSet<_>>();
+            format!("{}: {}", feature.name(), state)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
 
-                iter_ok(chat_ids)
-                    .and_then(move |chat_id| {
-                        bot.unban_chat_administrators(chat_id)
-                            .send()
-                            .map_err(|e| e.context(EventErrorKind::TelegramLookup).into())
-                            .map(move |(bot, admins)| (bot, admins, chat_id))
-                    })
-                    .filter_map(move |(_, admins, chat_id)| {
-                        if admins
-                            .into_iter()
-                            .any(|admin| channel_admins.contains(&admin.user.id))
-                        {
-                            Some(chat_id)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
+    language.fill(Text::Features, &[&list])
+}
+
+/// Describe a channel's activity for its admins
+fn describe_stats(stats: &Stats, language: Language) -> String {
+    let hosts = if stats.top_hosts().is_empty() {
+        language.text(Text::NoHostsYet).to_owned()
+    } else {
+        stats
+            .top_hosts()
+            .iter()
+            .map(|&(ref username, hosted)| match *username {
+                Some(ref username) => format!("@{}: {}", username, hosted),
+                None => language.fill(Text::NoUsername, &[&hosted]),
             })
-    }
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
 
-    fn send_help(&self, chat_id: Integer) {
-        send_message(
-            &self.bot,
-            chat_id,
-            "Event Bot is a telegram bot to help groups manage events.
-
-In group chats, the following commands are available:
-/events - get a list of events for the current chat
-/pinevents - pin a list of upcomming events in the current group
-
-In private chats, the following commands are available:
-/new - Create a new event
-/edit - Edit an event you're hosting
-/delete - Delete an event you're hosting
-/help - Print this help message
-            
-If you're an admin wanting to add this bot to a chat, the following commands will be interesting to you:
-/init - Initialize an event channel
-/link - in an event channel, link a group chat (usage: /link [chat_id])
-/id - get the id of a group chat
-
-Keep in mind that this bot only works in supergroups, not regular groups.
-
-If you have any questions or need help setting up or using the bot, contact @asonix
-
-This bot is released under the GNU General Public License version 3 or later. If you would like a copy of the code, check here:
-http://github.com/asonix/telegram-event-bot
-"
-                .to_owned(),
-        );
-    }
+    let attendance = match stats.average_attendance() {
+        Some(average) => language.fill(Text::People, &[&format!("{:.1}", average)]),
+        None => language.text(Text::NoEventsEnded).to_owned(),
+    };
 
-    fn send_error(bot: &RcBot, chat_id: Integer, error: &str) {
-        send_message(bot, chat_id, error.to_owned());
-    }
+    language.fill(
+        Text::Stats,
+        &[
+            &stats.events_created(),
+            &attendance,
+            &stats.chats(),
+            &stats.users(),
+            &hosts,
+        ],
+    )
+}
 
-    fn edit_with_url(
-        bot: &RcBot,
-        chat_id: Integer,
-        message_id: Integer,
-        action: String,
-        url: String,
-    ) {
-        bot.inner.handle.spawn(
-            bot.edit_message_text(format!("Use this link to {} your event: {}", action, url))
-                .chat_id(chat_id)
-                .message_id(message_id)
-                .reply_markup(InlineKeyboardMarkup::new(vec![vec![]]))
-                .send()
-                .map(|_| ())
-                .map_err(|e| error!("Error sending message to Telegram: {:?}", e)),
-        );
-    }
+/// Register the bot's commands with Telegram, so they're suggested to users as they type
+///
+/// The commands come from the same list as the `/help` message. Group admins are offered the admin
+/// commands along with the group commands, and the bot's own admins are offered the commands for
+/// running the bot in their private chats with it.
+///
+/// Each language's descriptions are offered to users whose Telegram app is set to it, and the
+/// English ones to everyone else.
+fn register_commands(
+    bot: &RcBot,
+    bot_admins: &[Integer],
+) -> impl Future<Item = (), Error = EventError> {
+    let bot = bot.clone();
+    let mut scopes = vec![
+        (CommandScope::all("all_private_chats"), vec![Scope::Private]),
+        (CommandScope::all("all_group_chats"), vec![Scope::Group]),
+        (
+            CommandScope::all("all_chat_administrators"),
+            vec![Scope::Group, Scope::Admin],
+        ),
+    ];
 
-    fn send_events(bot: &RcBot, chat_id: Integer, events: Vec<Event>) {
-        bot.inner.handle.spawn(
-            print_events(bot, chat_id, events)
+    // A chat's own commands replace the ones for all private chats, so they're listed again
+    scopes.extend(bot_admins.iter().map(|admin_id| {
+        (
+            CommandScope::chat(*admin_id),
+            vec![Scope::Private, Scope::BotAdmin],
+        )
+    }));
+
+    iter_ok(scopes).for_each(move |(scope, scopes)| {
+        let bot = bot.clone();
+
+        iter_ok(LANGUAGES).for_each(move |language| {
+            let suggestions = scopes
+                .iter()
+                .flat_map(|scope| commands::in_scope(*scope))
+                .map(|command| BotCommand {
+                    command: command.name,
+                    description: language.text(command.description),
+                })
+                .collect();
+
+            let language_code = match *language {
+                Language::English => None,
+                _ => Some(language.code()),
+            };
+
+            let body = serde_json::to_string(&MyCommands {
+                commands: suggestions,
+                scope: scope.clone(),
+                language_code,
+            }).unwrap();
+
+            bot.inner
+                .fetch_json("setMyCommands", &body)
                 .map(|_| ())
-                .map_err(|e| error!("Error sending events to Telegram: {:?}", e)),
-        );
-    }
+                .map_err(telegram_error)
+        })
+    })
+}
 
-    fn send_and_pin_events(bot: &RcBot, chat_id: Integer, events: Vec<Event>) {
-        bot.inner.handle.spawn(
-            print_events(bot, chat_id, events)
-                .map_err(|e| error!("Error sending events to Telegram: {:?}", e))
-                .and_then(move |(bot, message)| {
-                    let message_id = message.message_id;
-                    let chat_id = message.chat.id;
+/// Check that the bot can post announcements in a channel and edit them later
+///
+/// This fails with `EventErrorKind::ChannelRights` if it can't, so the channel's admins find out
+/// while setting the channel up instead of when the first announcement fails. If the bot can't
+/// post at all, the reply saying so can't be posted either, and the failure is only logged.
+fn check_channel_rights(
+    bot: &RcBot,
+    channel_id: Integer,
+) -> impl Future<Item = (), Error = EventError> {
+    let body = serde_json::to_string(&ChatMemberQuery {
+        chat_id: channel_id,
+        user_id: bot_user_id(bot),
+    }).unwrap();
 
-                    bot.pin_chat_message(chat_id, message_id)
-                        .send()
-                        .map(|_| ())
-                        .map_err(|e| error!("Error pinning message: {:?}", e))
-                }),
-        );
-    }
+    bot.inner
+        .fetch_json("getChatMember", &body)
+        .map_err(telegram_lookup_error)
+        .and_then(|json| {
+            serde_json::from_str::<ChannelRights>(&json).map_err(telegram_lookup_error)
+        })
+        .and_then(|rights| {
+            let can_announce = rights.status == "creator"
+                || (rights.status == "administrator" && rights.can_post_messages
+                    && rights.can_edit_messages);
 
-    fn print_id(bot: &RcBot, chat_id: Integer) {
-        send_message(bot, chat_id, format!("{}", chat_id));
-    }
+            if can_announce {
+                Ok(())
+            } else {
+                Err(EventErrorKind::ChannelRights.into())
+            }
+        })
+}
 
-    fn linked(bot: &RcBot, channel_id: Integer, chat_ids: Vec<Integer>) {
-        let msg = format!(
-            "Linked channel '{}' to chats ({})",
-            channel_id,
-            chat_ids
-                .into_iter()
-                .map(|id| format!("{}", id))
-                .collect::<Vec<_>>()
-                .join(", ")
-        );
+/// Get the bot's own Telegram ID, which is the part of its token before the colon
+fn bot_user_id(bot: &RcBot) -> Integer {
+    bot.inner
+        .key
+        .split(':')
+        .next()
+        .and_then(|id| id.parse().ok())
+        .unwrap_or(0)
+}
 
-        send_message(bot, channel_id, msg);
+/// Check whether a chat member is still in the chat
+///
+/// Restricted users can still be members, but the bot library doesn't say whether they are, so
+/// they're counted
+fn is_member(member: &ChatMember) -> bool {
+    match member.status.as_str() {
+        "creator" | "administrator" | "member" | "restricted" => true,
+        _ => false,
     }
+}
 
-    fn created_channel(bot: &RcBot, channel_id: Integer) {
-        send_message(bot, channel_id, "Initialized".to_owned());
-    }
+/// Send a private message the user might have muted, if they haven't
+fn send_notification<F>(
+    bot: &RcBot,
+    db: &Addr<Unsync, DbBroker>,
+    user_id: Integer,
+    notification: Notification,
+    message: F,
+) where
+    F: FnOnce(Language) -> String + 'static,
+{
+    let log_ctx = LogContext::new("telegram").chat_id(user_id);
+    let bot2 = bot.clone();
+
+    bot.inner.handle.spawn(
+        db.send(LookupPreferences(user_id))
+            .then(flatten)
+            .map(move |preferences| {
+                if !preferences.muted(notification) {
+                    send_message(&bot2, user_id, message(preferences.language()));
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error looking up preferences")),
+    );
 }
 
 fn send_message(bot: &RcBot, chat_id: Integer, message: String) {
+    let log_ctx = LogContext::new("telegram").chat_id(chat_id);
+
     bot.inner.handle.spawn(
         bot.message(chat_id, message)
             .send()
             .map(|_| ())
-            .map_err(|e| error!("Error sending message to Telegram: {:?}", e)),
+            .map_err(log_err!(log_ctx, "Error sending message to Telegram")),
     );
 }
 
-fn format_duration(event: &Event) -> String {
-    let duration = event
-        .end_date()
-        .signed_duration_since(event.start_date().clone());
-
-    if duration.num_weeks() > 0 {
-        format!("{} Weeks", duration.num_weeks())
-    } else if duration.num_days() > 0 {
-        format!("{} Days", duration.num_days())
-    } else if duration.num_hours() > 0 {
-        format!("{} Hours", duration.num_hours())
-    } else if duration.num_minutes() > 0 {
-        format!("{} Minutes", duration.num_minutes())
-    } else {
-        "No time".to_owned()
-    }
+/// Show a notification to the user who pressed a button
+fn answer_callback_query(bot: &RcBot, callback_query_id: String, text: String) {
+    let body = serde_json::to_string(&CallbackAnswer {
+        callback_query_id,
+        text,
+    }).unwrap();
+
+    bot.inner.handle.spawn(
+        bot.inner
+            .fetch_json("answerCallbackQuery", &body)
+            .map(|_| ())
+            .map_err(log_err!(LogContext::new("telegram"), "Error answering callback query")),
+    );
 }
 
 fn print_events(
     bot: &RcBot,
+    db: &Addr<Unsync, DbBroker>,
     chat_id: Integer,
     events: Vec<Event>,
+    timezone: Tz,
+    language: Language,
 ) -> impl Future<Item = (RcBot, Message), Error = EventError> {
-    let events = events
-        .into_iter()
-        .map(|event| {
-            let localtime = event.start_date().with_timezone(&Central);
-            let when = format_date(localtime);
-            let duration = format_duration(&event);
-            let hosts = event
-                .hosts()
-                .iter()
-                .map(|host| format!("@{}", host.username()))
-                .collect::<Vec<_>>()
-                .join(", ");
+    let bot = bot.clone();
 
-            format!(
-                "----Event----\n{}\nWhen: {}\nDuration: {}\nDescription: {}\nHosts: {}",
-                event.title(),
-                when,
-                duration,
-                event.description(),
-                hosts
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n\n");
+    listing_time_format(db, &events).and_then(move |time_format| {
+        let msg = if events.len() > 0 {
+            let now = Utc::now().with_timezone(&timezone);
 
-    let msg = if events.len() > 0 {
-        format!("Upcoming Events:\n\n{}", events)
-    } else {
-        "No upcoming events".to_owned()
-    };
+            language.fill(
+                Text::UpcomingEventsAsOf,
+                &[
+                    &time_format.format_time(now.hour(), now.minute()),
+                    &now.format("%Z"),
+                    &format_events(&events, timezone, language, time_format),
+                ],
+            )
+        } else {
+            language.text(Text::NoUpcomingEvents).to_owned()
+        };
 
-    bot.message(chat_id, msg)
-        .send()
-        .map_err(|e| e.context(EventErrorKind::Telegram).into())
+        bot.message(chat_id, msg)
+            .parse_mode(HTML)
+            .send()
+            .map_err(telegram_error)
+    })
 }
 
-fn format_date<T>(localtime: DateTime<T>) -> String
-where
-    T: TimeZone + Debug,
-{
-    let weekday = match localtime.weekday() {
-        Weekday::Mon => "Monday",
-        Weekday::Tue => "Tuesday",
-        Weekday::Wed => "Wednesday",
-        Weekday::Thu => "Thursday",
-        Weekday::Fri => "Friday",
-        Weekday::Sat => "Saturday",
-        Weekday::Sun => "Sunday",
-    };
-
-    let month = match localtime.month() {
-        1 => "January",
-        2 => "February",
-        3 => "March",
-        4 => "April",
-        5 => "May",
-        6 => "June",
-        7 => "July",
-        8 => "August",
-        9 => "September",
-        10 => "October",
-        11 => "November",
-        12 => "December",
-        _ => "Unknown Month",
-    };
-
-    let day = match localtime.day() {
-        1 | 21 | 31 => "st",
-        2 | 22 => "nd",
-        3 | 23 => "rd",
-        _ => "th",
-    };
-
-    let minute = if localtime.minute() > 9 {
-        format!("{}", localtime.minute())
-    } else {
-        format!("0{}", localtime.minute())
-    };
-
-    format!(
-        "{}:{} {:?}, {}, {} {}{}",
-        localtime.hour(),
-        minute,
-        localtime.timezone(),
-        weekday,
-        month,
-        localtime.day(),
-        day
-    )
+/// Get the time format for a list of events from the channel of the first one
+///
+/// A group's listings only have its own channel's events. Empty listings don't show any times.
+fn listing_time_format(
+    db: &Addr<Unsync, DbBroker>,
+    events: &[Event],
+) -> impl Future<Item = TimeFormat, Error = EventError> {
+    match events.first() {
+        Some(event) => Either::A(
+            db.send(LookupSystem {
+                system_id: event.system_id(),
+            }).then(flatten)
+                .map(|chat_system| chat_system.time_format()),
+        ),
+        None => Either::B(Ok(TimeFormat::default()).into_future()),
+    }
 }
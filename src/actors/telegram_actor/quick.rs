@@ -0,0 +1,286 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module parses the text of the `/quick` command into an event.
+//!
+//! The first line of the command is expected to look like `Pizza night tomorrow 7pm-9pm`, where
+//! the title comes first, followed by the day and the time range. Any following lines are used as
+//! the event's description.
+
+use chrono::{Date, DateTime, Datelike, Duration as OldDuration, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+
+/// Words that can sit between the title and the date or time without being part of either
+const FILLER: &[&str] = &["at", "on", "from", "to", "until", "-"];
+
+/// An event parsed from the `/quick` command, waiting to be confirmed by the user
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuickEvent {
+    pub title: String,
+    pub description: String,
+    pub start_date: DateTime<Tz>,
+    pub end_date: DateTime<Tz>,
+}
+
+/// Parse the text following `/quick`, interpreting days and times relative to `now`
+pub fn parse(text: &str, now: DateTime<Tz>) -> Option<QuickEvent> {
+    let mut lines = text.trim().lines();
+    let first_line = lines.next()?;
+    let description = lines.collect::<Vec<_>>().join("\n").trim().to_owned();
+
+    let tokens = first_line.split_whitespace().collect::<Vec<_>>();
+
+    let mut title = Vec::new();
+    let mut date = None;
+    let mut times = None;
+    let mut next = false;
+
+    for token in tokens {
+        let lower = token.to_lowercase();
+
+        if lower == "next" {
+            next = true;
+        } else if let Some(d) = parse_date(&lower, now.date(), next) {
+            date = Some(d);
+            next = false;
+        } else if let Some(t) = parse_time_range(&lower) {
+            times = Some(t);
+        } else if date.is_some() || times.is_some() {
+            // Everything after the date or time has to be understood
+            if !FILLER.contains(&lower.as_str()) {
+                return None;
+            }
+        } else {
+            if next {
+                title.push("next");
+                next = false;
+            }
+            title.push(token);
+        }
+    }
+
+    while title
+        .last()
+        .map(|word| FILLER.contains(&word.to_lowercase().as_str()))
+        .unwrap_or(false)
+    {
+        title.pop();
+    }
+
+    if title.is_empty() {
+        return None;
+    }
+
+    let date = date.unwrap_or(now.date());
+    let (start, end) = times?;
+
+    let start_date = date.and_time(start)?;
+    let mut end_date = date.and_time(end)?;
+
+    // Ranges like 10pm-1am end on the following day
+    if end_date <= start_date {
+        end_date = end_date + OldDuration::days(1);
+    }
+
+    Some(QuickEvent {
+        title: title.join(" "),
+        description,
+        start_date,
+        end_date,
+    })
+}
+
+/// Parse a day like `today`, `tomorrow`, `friday`, or `6/1`
+fn parse_date(token: &str, today: Date<Tz>, next: bool) -> Option<Date<Tz>> {
+    match token {
+        "today" | "tonight" => return Some(today),
+        "tomorrow" => return Some(today.succ()),
+        _ => (),
+    }
+
+    if let Some(weekday) = parse_weekday(token) {
+        let current = today.weekday().num_days_from_monday() as i64;
+        let target = weekday.num_days_from_monday() as i64;
+
+        let mut days = (target - current + 7) % 7;
+
+        if next && days == 0 {
+            days = 7;
+        }
+
+        return Some(today + OldDuration::days(days));
+    }
+
+    let mut parts = token.split('/');
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let timezone = today.timezone();
+    let this_year = timezone.ymd_opt(today.year(), month, day).single()?;
+
+    // Dates that have already passed refer to next year
+    if this_year < today {
+        timezone.ymd_opt(today.year() + 1, month, day).single()
+    } else {
+        Some(this_year)
+    }
+}
+
+/// Parse the full name of a day of the week, like `friday`
+pub fn parse_weekday(token: &str) -> Option<Weekday> {
+    let weekday = match token {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+
+    Some(weekday)
+}
+
+/// Parse a time range like `7pm-9pm`, `7-9pm`, or `19:00-21:30`
+fn parse_time_range(token: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let mut parts = token.split('-');
+    let start = parts.next()?;
+    let end = parts.next()?;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let (end_hour, end_minute, end_meridiem) = parse_time(end)?;
+    let (start_hour, start_minute, start_meridiem) = parse_time(start)?;
+
+    let end = to_naive_time(end_hour, end_minute, end_meridiem)?;
+
+    let start = match start_meridiem {
+        Some(meridiem) => to_naive_time(start_hour, start_minute, Some(meridiem))?,
+        None => {
+            // Borrow the meridiem from the end of the range, unless that would put the start
+            // after the end, as in 11-1pm
+            let start = to_naive_time(start_hour, start_minute, end_meridiem)?;
+
+            if start > end && end_meridiem == Some(Meridiem::Pm) {
+                to_naive_time(start_hour, start_minute, Some(Meridiem::Am))?
+            } else {
+                start
+            }
+        }
+    };
+
+    Some((start, end))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Meridiem {
+    Am,
+    Pm,
+}
+
+/// Parse a single time like `7`, `7pm`, or `7:30pm`
+fn parse_time(token: &str) -> Option<(u32, u32, Option<Meridiem>)> {
+    let (time, meridiem) = if token.ends_with("am") {
+        (token.trim_right_matches("am"), Some(Meridiem::Am))
+    } else if token.ends_with("pm") {
+        (token.trim_right_matches("pm"), Some(Meridiem::Pm))
+    } else {
+        (token, None)
+    };
+
+    let mut parts = time.split(':');
+    let hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = match parts.next() {
+        Some(minute) => minute.parse::<u32>().ok()?,
+        None => 0,
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((hour, minute, meridiem))
+}
+
+fn to_naive_time(hour: u32, minute: u32, meridiem: Option<Meridiem>) -> Option<NaiveTime> {
+    let hour = match meridiem {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some(Meridiem::Am) => hour % 12,
+        Some(Meridiem::Pm) => hour % 12 + 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Timelike};
+    use chrono_tz::US::Central;
+
+    use super::parse;
+
+    #[test]
+    fn parses_title_day_and_range() {
+        // Monday, May 28th 2018
+        let now = Central.ymd(2018, 5, 28).and_hms(12, 0, 0);
+
+        let event = parse("Pizza night tomorrow 7pm-9pm", now).unwrap();
+
+        assert_eq!(event.title, "Pizza night");
+        assert_eq!(event.start_date, Central.ymd(2018, 5, 29).and_hms(19, 0, 0));
+        assert_eq!(event.end_date, Central.ymd(2018, 5, 29).and_hms(21, 0, 0));
+    }
+
+    #[test]
+    fn parses_weekdays_and_shared_meridiem() {
+        let now = Central.ymd(2018, 5, 28).and_hms(12, 0, 0);
+
+        let event = parse("Board games at friday 11-1pm\nBring snacks", now).unwrap();
+
+        assert_eq!(event.title, "Board games");
+        assert_eq!(event.description, "Bring snacks");
+        assert_eq!(event.start_date, Central.ymd(2018, 6, 1).and_hms(11, 0, 0));
+        assert_eq!(event.end_date.hour(), 13);
+    }
+
+    #[test]
+    fn ranges_can_end_the_next_day() {
+        let now = Central.ymd(2018, 5, 28).and_hms(12, 0, 0);
+
+        let event = parse("Late show 6/1 10pm-1am", now).unwrap();
+
+        assert_eq!(event.end_date, Central.ymd(2018, 6, 2).and_hms(1, 0, 0));
+    }
+
+    #[test]
+    fn rejects_missing_times() {
+        let now = Central.ymd(2018, 5, 28).and_hms(12, 0, 0);
+
+        assert!(parse("Pizza night tomorrow", now).is_none());
+        assert!(parse("tomorrow 7pm-9pm", now).is_none());
+    }
+}
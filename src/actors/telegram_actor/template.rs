@@ -0,0 +1,248 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module renders the message templates channel admins set with the `/template` command.
+//!
+//! A template is plain text with placeholders like `{title}`, which are filled in with the
+//! event's details when the message is sent.
+
+use chrono_tz::Tz;
+use event_web::locale::{Language, Text, TimeFormat};
+
+use super::formatting::{escape_html, format_localized_date, format_usernames};
+use models::chat_system::{ChatSystem, TemplateKind};
+use models::event::Event;
+
+/// The placeholders a template can use
+///
+/// The default templates, `Text::DefaultReminder`, `Text::DefaultStarted` and
+/// `Text::DefaultEnded`, come from the locale catalog so they're sent in the channel's language.
+/// Channels can't replace the one sent when an event starts.
+pub const PLACEHOLDERS: &[&str] = &["title", "when", "hosts", "location"];
+
+/// The details of an event that placeholders are filled in with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Values {
+    pub title: String,
+    pub when: String,
+    pub hosts: String,
+    pub location: String,
+}
+
+impl Values {
+    /// Collect an event's details, showing its start date in the given timezone, in the channel's
+    /// language and time format
+    ///
+    /// Hidden locations are left out, just like they are in announcements
+    pub fn from_event(
+        event: &Event,
+        timezone: Tz,
+        language: Language,
+        time_format: TimeFormat,
+    ) -> Self {
+        Values {
+            title: event.title().to_owned(),
+            when: format_localized_date(
+                event.start_date().with_timezone(&timezone),
+                language,
+                time_format,
+            ),
+            hosts: format_usernames(event.hosts()),
+            location: event.public_location().unwrap_or("").to_owned(),
+        }
+    }
+
+    fn get(&self, placeholder: &str) -> Option<&str> {
+        match placeholder {
+            "title" => Some(&self.title),
+            "when" => Some(&self.when),
+            "hosts" => Some(&self.hosts),
+            "location" => Some(&self.location),
+            _ => None,
+        }
+    }
+}
+
+/// Get the kind of template from the name used in the `/template` command
+pub fn kind_from_name(name: &str) -> Option<TemplateKind> {
+    match name.to_lowercase().as_str() {
+        "announcement" => Some(TemplateKind::Announcement),
+        "reminder" => Some(TemplateKind::Reminder),
+        "ended" => Some(TemplateKind::Ended),
+        _ => None,
+    }
+}
+
+/// Get the name used for a kind of template in the `/template` command
+pub fn kind_name(kind: TemplateKind) -> &'static str {
+    match kind {
+        TemplateKind::Announcement => "announcement",
+        TemplateKind::Reminder => "reminder",
+        TemplateKind::Ended => "ended",
+    }
+}
+
+/// List the placeholders, like `{title}, {when}`
+pub fn placeholder_list() -> String {
+    PLACEHOLDERS
+        .iter()
+        .map(|placeholder| format!("{{{}}}", placeholder))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describe the templates a channel has set, and how to change them
+pub fn describe(chat_system: &ChatSystem, language: Language) -> String {
+    let kinds = [
+        TemplateKind::Announcement,
+        TemplateKind::Reminder,
+        TemplateKind::Ended,
+    ];
+
+    let templates = kinds
+        .iter()
+        .map(|kind| {
+            let template = match chat_system.template(*kind) {
+                Some(template) => template.to_owned(),
+                None => language.text(Text::DefaultTemplate).to_owned(),
+            };
+
+            format!("{}: {}", kind_name(*kind), template)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    language.fill(Text::Templates, &[&templates, &placeholder_list()])
+}
+
+/// Check that every placeholder in the template is one the bot knows how to fill in
+///
+/// The error names the first unknown placeholder
+pub fn validate(template: &str) -> Result<(), String> {
+    let mut rest = template;
+
+    while let Some((_, placeholder, after)) = next_placeholder(rest) {
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(placeholder.to_owned());
+        }
+
+        rest = after;
+    }
+
+    Ok(())
+}
+
+/// Fill in the template's placeholders
+///
+/// If `html` is set, the whole message is escaped so it can be sent as HTML. Unknown placeholders
+/// are left as they are.
+pub fn render(template: &str, values: &Values, html: bool) -> String {
+    let text = |text: &str| {
+        if html {
+            escape_html(text)
+        } else {
+            text.to_owned()
+        }
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some((before, placeholder, after)) = next_placeholder(rest) {
+        rendered.push_str(&text(before));
+
+        match values.get(placeholder) {
+            Some(value) => rendered.push_str(&text(value)),
+            None => rendered.push_str(&text(&format!("{{{}}}", placeholder))),
+        }
+
+        rest = after;
+    }
+
+    rendered.push_str(&text(rest));
+    rendered
+}
+
+/// Split the text around its first placeholder, returning the text before it, the placeholder's
+/// name, and the text after it
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let start = text.find('{')?;
+    let len = text[start + 1..].find('}')?;
+    let placeholder = &text[start + 1..start + 1 + len];
+
+    // A brace that doesn't open a placeholder is only text
+    if placeholder.contains('{') || placeholder.contains(char::is_whitespace) {
+        let skip = start + 1;
+        let (before, placeholder, after) = next_placeholder(&text[skip..])?;
+
+        return Some((&text[..skip + before.len()], placeholder, after));
+    }
+
+    Some((&text[..start], placeholder, &text[start + len + 2..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use event_web::locale::{Language, Text};
+
+    use super::{render, validate, Values};
+
+    fn values() -> Values {
+        Values {
+            title: "Pizza & Games".to_owned(),
+            when: "19:00 CDT, Friday, June 1st".to_owned(),
+            hosts: "@asonix".to_owned(),
+            location: "".to_owned(),
+        }
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        let text = render("{title} starts at {when}, ask {hosts}", &values(), false);
+
+        assert_eq!(
+            text,
+            "Pizza & Games starts at 19:00 CDT, Friday, June 1st, ask @asonix"
+        );
+        assert_eq!(
+            render(Language::English.text(Text::DefaultEnded), &values(), false),
+            "Pizza & Games has ended!"
+        );
+    }
+
+    #[test]
+    fn escapes_html_templates() {
+        let text = render("<b>{title}</b>", &values(), true);
+
+        assert_eq!(text, "&lt;b&gt;Pizza &amp; Games&lt;/b&gt;");
+    }
+
+    #[test]
+    fn leaves_stray_braces_alone() {
+        assert_eq!(render("{ {title} }", &values(), false), "{ Pizza & Games }");
+        assert_eq!(render("{title", &values(), false), "{title");
+        assert_eq!(validate("{ oops {title}"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        assert_eq!(validate("{title} at {where}"), Err("where".to_owned()));
+        assert_eq!(render("{where}", &values(), false), "{where}");
+    }
+}
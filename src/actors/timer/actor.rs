@@ -19,121 +19,80 @@
 
 use std::time::{Duration, Instant};
 
-use actix::{
-    Actor, Addr, Arbiter, AsyncContext, Context, Handler, Message, Running, StreamHandler, Syn,
-};
-use futures::{Future, Stream};
-use tokio_timer::Interval;
+use actix::fut::wrap_future;
+use actix::{Actor, ActorFuture, AsyncContext, Context, Handler, Message};
+use chrono::offset::Utc;
+use tokio_timer::Delay;
 
 use super::messages::*;
 use super::Timer;
+use logging::LogContext;
 
 impl Actor for Timer {
     type Context = Context<Self>;
 
-    fn started(&mut self, ctx: &mut Self::Context) {
+    fn started(&mut self, _: &mut Self::Context) {
         debug!("Started Timer Actor");
-        // Every 30 minutes, check for events happening in the next hour
-        ctx.add_stream(
-            Interval::new(Instant::now(), Duration::from_secs(30 * 60))
-                .map(|_| NextHour)
-                .map_err(|_| Shutdown),
-        );
-
-        // Every 30 seconds, check if any events have any pending actions
-        ctx.add_stream(
-            Interval::new(Instant::now(), Duration::from_secs(30))
-                .map(|_| Migrate)
-                .map_err(|_| MigrateError),
-        );
-
-        ctx.notify(NextHour);
-        ctx.notify(Migrate);
     }
 }
 
-impl Handler<NextHour> for Timer {
-    type Result = <Migrate as Message>::Result;
-
-    fn handle(&mut self, _: NextHour, ctx: &mut Self::Context) -> Self::Result {
-        let address: Addr<Syn, _> = ctx.address();
-
-        let fut = self.get_next_hour()
-            .map(move |events| {
-                address.do_send(Events { events });
-            })
-            .map_err(|e| println!("Error: {:?}", e));
-
-        Arbiter::handle().spawn(fut);
-    }
-}
-
-impl StreamHandler<NextHour, Shutdown> for Timer {
-    fn handle(&mut self, _: NextHour, ctx: &mut Self::Context) {
-        let address: Addr<Syn, _> = ctx.address();
-
-        let fut = self.get_next_hour()
-            .map(move |events| {
-                address.do_send(Events { events });
-            })
-            .map_err(|e| println!("Error: {:?}", e));
-
-        Arbiter::handle().spawn(fut);
-    }
-
-    fn error(&mut self, _: Shutdown, _: &mut Self::Context) -> Running {
-        error!("Interval for NextHour errored");
-        Running::Continue
-    }
-
-    fn finished(&mut self, ctx: &mut Self::Context) {
-        ctx.add_stream(
-            Interval::new(Instant::now(), Duration::from_secs(60 * 60))
-                .map(|_| NextHour)
-                .map_err(|_| Shutdown),
-        );
-    }
-}
-
-impl Handler<Migrate> for Timer {
-    type Result = <Migrate as Message>::Result;
-
-    fn handle(&mut self, _: Migrate, _: &mut Self::Context) -> Self::Result {
-        self.migrate_events();
-    }
-}
-
-impl StreamHandler<Migrate, MigrateError> for Timer {
-    fn handle(&mut self, _: Migrate, _: &mut Self::Context) {
-        self.migrate_events();
-    }
-
-    fn error(&mut self, _: MigrateError, _: &mut Self::Context) -> Running {
-        error!("Interval for Migrate errored");
-        Running::Continue
-    }
-
-    fn finished(&mut self, ctx: &mut Self::Context) {
-        ctx.add_stream(
-            Interval::new(Instant::now(), Duration::from_secs(30))
-                .map(|_| Migrate)
-                .map_err(|_| MigrateError),
-        );
+impl Timer {
+    /// Sleep until the next queued stage is due, replacing the current wakeup if that's changed
+    fn schedule_wakeup(&mut self, ctx: &mut Context<Self>) {
+        let next_due = self.schedule.next_due();
+
+        if self.wakeup.as_ref().map(|&(due, _)| due) == next_due {
+            return;
+        }
+
+        if let Some((_, handle)) = self.wakeup.take() {
+            ctx.cancel_future(handle);
+        }
+
+        if let Some(due) = next_due {
+            // Stages that are already due run right away
+            let wait = (due - Utc::now()).to_std().unwrap_or(Duration::from_secs(0));
+
+            let handle = ctx.spawn(
+                wrap_future::<_, Self>(Delay::new(Instant::now() + wait))
+                    .map_err(|e, _, _| {
+                        ctx_error!(LogContext::new("timer"), "Error waiting for event: {:?}", e)
+                    })
+                    .map(|_, timer, ctx| {
+                        timer.wakeup = None;
+                        timer.run_due();
+                        timer.schedule_wakeup(ctx);
+                    }),
+            );
+
+            self.wakeup = Some((due, handle));
+        }
     }
 }
 
 impl Handler<Events> for Timer {
     type Result = <Events as Message>::Result;
 
-    fn handle(&mut self, msg: Events, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: Events, ctx: &mut Self::Context) -> Self::Result {
         self.handle_events(msg.events);
+        self.schedule_wakeup(ctx);
     }
 }
 
 impl Handler<UpdateEvent> for Timer {
     type Result = <UpdateEvent as Message>::Result;
 
-    fn handle(&mut self, msg: UpdateEvent, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: UpdateEvent, ctx: &mut Self::Context) -> Self::Result {
         self.update_event(msg.event);
+        self.schedule_wakeup(ctx);
+    }
+}
+
+impl Handler<ForgetEvent> for Timer {
+    type Result = <ForgetEvent as Message>::Result;
+
+    fn handle(&mut self, msg: ForgetEvent, ctx: &mut Self::Context) -> Self::Result {
+        self.forget_event(msg.event_id);
+        self.schedule_wakeup(ctx);
     }
 }
@@ -17,7 +17,7 @@
  * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-//! This module defines the messages that the Timer can receive.
+//! This module defines the messages that the Timer and the TimerCoordinator can receive.
 
 use actix::Message;
 
@@ -25,12 +25,15 @@ use models::event::Event;
 
 pub struct NextHour;
 
-/// This asks the Timer to check the next hour for events that need to be scheduled for action
+/// This asks the TimerCoordinator to check the next hour for events that need to be scheduled for
+/// action
 impl Message for NextHour {
     type Result = ();
 }
 
 /// This provides the Timer with events that need to be scheduled for action
+///
+/// Events sent to the TimerCoordinator are passed on to the Timer for their chat system
 pub struct Events {
     pub events: Vec<Event>,
 }
@@ -39,32 +42,27 @@ impl Message for Events {
     type Result = ();
 }
 
-/// This notifies the Timer that the stream providing events has errored.
+/// This notifies the TimerCoordinator that the stream providing events has errored.
 pub struct Shutdown;
 
 impl Message for Shutdown {
     type Result = ();
 }
 
-/// This notifies the Timer that it should check it's stored events for pending actions
-pub struct Migrate;
-
-impl Message for Migrate {
-    type Result = ();
+/// This notifies the Timer that an event has updated.
+pub struct UpdateEvent {
+    pub event: Event,
 }
 
-/// This notifies the Timer that the Migrate stream has errored.
-pub struct MigrateError;
-
-impl Message for MigrateError {
+impl Message for UpdateEvent {
     type Result = ();
 }
 
-/// This notifies the Timer that an event has updated.
-pub struct UpdateEvent {
-    pub event: Event,
+/// This notifies the Timer that an event was deleted, so it should stop tracking it
+pub struct ForgetEvent {
+    pub event_id: i32,
 }
 
-impl Message for UpdateEvent {
+impl Message for ForgetEvent {
     type Result = ();
 }
@@ -19,39 +19,55 @@
 
 //! This module defines the Timer functionality.
 //!
-//! It handles notifying telegram when events are soon, starting, and ending.
+//! It handles notifying telegram when events are soon, starting, and ending, and when their RSVPs
+//! close. Cancelled events aren't announced as soon or starting, but are still cleaned up once
+//! they end.
+//!
+//! Events whose reminders come up at the same time are grouped by chat system, so a channel with
+//! several events starting at once gets one "Starting soon" message instead of one per event.
+//!
+//! Each stage of each event is queued in a `Schedule` for the moment it's due, and the Timer sleeps
+//! until the first one comes up, so actions happen on time rather than on the next check.
+//!
+//! Each Timer only tracks the events of some chat systems. The `TimerCoordinator` looks up
+//! upcoming events and hands each one to the Timer for its system.
+//!
+//! Every instance of the bot sharing a database tracks the same events, so each stage is claimed
+//! in the database before it's carried out, and only the instance that claims it first goes ahead.
 
 use std::collections::HashMap;
 
-use actix::{Addr, Arbiter, Syn, Unsync};
+use actix::{Addr, Arbiter, SpawnHandle, Syn, Unsync};
 use chrono::offset::Utc;
-use chrono::{DateTime, Duration as OldDuration, Timelike};
-use chrono_tz::Tz;
-use futures::Future;
+use chrono::DateTime;
+use futures::future::{join_all, Either};
+use futures::{Future, IntoFuture};
 
-use actors::db_broker::messages::{DeleteEvent, GetEventsInRange};
+use actors::db_broker::messages::{
+    ClaimStage, DeleteEvent, GetRecipients, LookupEvent, MarkPublished,
+};
 use actors::db_broker::DbBroker;
-use actors::telegram_actor::messages::{EventOver, EventSoon, EventStarted};
+use actors::telegram_actor::messages::{
+    EventOver, EventSoon, EventStarted, EventsSoon, NewEvent, RemindAttendees, RsvpsClosed,
+};
 use actors::telegram_actor::TelegramActor;
-use error::EventError;
+use logging::LogContext;
 use models::event::Event;
+use models::user_preferences::Notification;
 use util::flatten;
 
+use self::schedule::{plan, Schedule, Stage};
+
 mod actor;
 pub mod messages;
-
-#[derive(Clone, Debug, Hash)]
-enum TimerState {
-    WaitingNotify,
-    WaitingStart,
-    WaitingEnd,
-    Future,
-}
+mod schedule;
 
 pub struct Timer {
     db: Addr<Unsync, DbBroker>,
     tg: Addr<Syn, TelegramActor>,
-    times: Vec<HashMap<i32, (TimerState, Event)>>,
+    schedule: Schedule,
+    events: HashMap<i32, Event>,
+    wakeup: Option<(DateTime<Utc>, SpawnHandle)>,
 }
 
 impl Timer {
@@ -59,86 +75,115 @@ impl Timer {
         Timer {
             db,
             tg,
-            times: (0..60).map(|_| HashMap::new()).collect(),
+            schedule: Schedule::new(),
+            events: HashMap::new(),
+            wakeup: None,
         }
     }
 
-    /// Notify telegram of any events starting in the next 45 minutes, if a notification has not
-    /// already been sent
-    fn migrate_notify(&mut self, index: usize, event: Event) {
-        debug!("Moving event {} to waiting_start", event.id());
-
-        self.notify_soon(event.clone());
-        self.times[index].insert(event.id(), (TimerState::WaitingStart, event));
-    }
+    /// Perform every queued stage that's due
+    fn run_due(&mut self) {
+        let now = Utc::now();
+        let mut soon = Vec::new();
 
-    /// Notify telegram of any events that have started, if a notification has not already been sent
-    fn migrate_start(&mut self, next_hour: DateTime<Utc>, index: usize, event: Event) {
-        let end_index = event.end_date().minute() as usize;
-        self.times[index].remove(&event.id());
+        while let Some((event_id, stage, due)) = self.schedule.pop_due(now) {
+            let event = if self.schedule.contains_event(event_id) {
+                self.events.get(&event_id).cloned()
+            } else {
+                self.events.remove(&event_id)
+            };
 
-        if next_hour > event.end_date().with_timezone(&Utc) {
-            debug!("Moving event {} to waiting_end", event.id());
-            self.times[end_index].insert(event.id(), (TimerState::WaitingEnd, event.clone()));
-        } else {
-            debug!("Moving event {} to futures", event.id());
-            self.times[end_index].insert(event.id(), (TimerState::Future, event.clone()));
+            if let Some(event) = event {
+                self.run_stage(stage, event, due, now, &mut soon);
+            }
         }
 
-        self.notify_now(event);
-    }
-
-    /// Store events that are happening now, but aren't ending for a while.
-    fn migrate_future(&mut self, next_hour: DateTime<Utc>, index: usize, event: Event) {
-        if next_hour > event.end_date().with_timezone(&Utc) {
-            debug!("Moving event {} to waiting_end", event.id());
-            self.times[index].insert(event.id(), (TimerState::WaitingEnd, event));
+        if !soon.is_empty() {
+            self.notify_soon(soon);
         }
     }
 
-    /// Notify telegram when an event has ended, if it has not already done so
-    fn migrate_end(&mut self, index: usize, event: Event) {
-        debug!("Removing completed event {}", event.id());
-        self.times[index].remove(&event.id());
-        self.delete_event(event);
-    }
-
-    fn migrate_events(&mut self) {
-        debug!("Migrating events");
-        let now = Utc::now();
-        let next_hour = now + OldDuration::hours(1);
-
-        let index = now.minute() as usize;
-
-        for (event_id, (state, event)) in self.times[index].clone() {
-            debug!("Checking event {}", event_id);
-
-            match state {
-                TimerState::WaitingNotify => {
-                    self.migrate_notify(index, event);
-                }
-                TimerState::WaitingStart => {
-                    self.migrate_start(next_hour, index, event);
-                }
-                TimerState::WaitingEnd => {
-                    self.migrate_end(index, event);
-                }
-                TimerState::Future => {
-                    self.migrate_future(next_hour, index, event);
-                }
+    /// Perform a stage of an event, except for reminders, which are added to `soon` so they can
+    /// be sent together
+    fn run_stage(
+        &mut self,
+        stage: Stage,
+        mut event: Event,
+        due: DateTime<Utc>,
+        now: DateTime<Utc>,
+        soon: &mut Vec<(Event, DateTime<Utc>)>,
+    ) {
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+
+        match stage {
+            Stage::Publish => {
+                ctx_debug!(log_ctx, "Publishing event");
+                event.mark_published();
+
+                self.publish_event(event.clone(), due);
+                self.new_event(event, now);
+            }
+            Stage::CloseRsvps => {
+                ctx_debug!(log_ctx, "Closing RSVPs");
+                self.close_rsvps(event, due);
+            }
+            Stage::Soon => {
+                ctx_debug!(log_ctx, "Starting soon");
+                soon.push((event, due));
+            }
+            Stage::Start => {
+                ctx_debug!(log_ctx, "Starting");
+                self.notify_now(event, due);
+            }
+            Stage::End => {
+                ctx_debug!(log_ctx, "Removing completed event");
+                self.delete_event(event, due);
             }
         }
     }
 
-    fn get_next_hour(&self) -> impl Future<Item = Vec<Event>, Error = EventError> {
-        let now = Utc::now();
-
-        self.db
-            .send(GetEventsInRange {
-                start_date: (now - OldDuration::hours(1)).with_timezone(&Tz::UTC),
-                end_date: (now + OldDuration::hours(1)).with_timezone(&Tz::UTC),
+    /// Claim a stage of an event for this instance, resolving to whether it was claimed
+    ///
+    /// A stage that's moved to a new time can be claimed again, and stages of events that have
+    /// been deleted can't be claimed at all.
+    fn claim(
+        db: &Addr<Unsync, DbBroker>,
+        event_id: i32,
+        stage: Stage,
+        due: DateTime<Utc>,
+    ) -> impl Future<Item = bool, Error = ()> {
+        let log_ctx = LogContext::new("timer").event_id(event_id);
+        let claim_ctx = log_ctx.clone();
+
+        db.send(ClaimStage {
+            event_id,
+            stage: stage.name(),
+            due,
+        }).then(flatten)
+            .map(move |claimed| {
+                if !claimed {
+                    ctx_debug!(claim_ctx, "{:?} was claimed by another instance", stage);
+                }
+                claimed
             })
-            .then(flatten)
+            .map_err(log_err!(log_ctx, "Error claiming stage"))
+    }
+
+    /// Carry out a stage of an event once this instance has claimed it
+    fn when_claimed<F, R>(&self, event_id: i32, stage: Stage, due: DateTime<Utc>, f: F)
+    where
+        F: FnOnce() -> R + 'static,
+        R: Future<Item = (), Error = ()> + 'static,
+    {
+        Arbiter::handle().spawn(Timer::claim(&self.db, event_id, stage, due).and_then(
+            move |claimed| {
+                if claimed {
+                    Either::A(f())
+                } else {
+                    Either::B(Ok(()).into_future())
+                }
+            },
+        ));
     }
 
     fn handle_events(&mut self, events: Vec<Event>) {
@@ -149,96 +194,251 @@ impl Timer {
         }
     }
 
-    /// Search all stored events for event with ID `event_id`
-    fn remove_event(&mut self, event_id: i32) -> Option<(TimerState, Event)> {
-        self.times
-            .iter_mut()
-            .find(|map| map.contains_key(&event_id))
-            .and_then(|map| map.remove(&event_id))
-    }
-
     /// Check if we're tracking the event with ID `event_id`
     fn tracking_event(&self, event_id: i32) -> bool {
-        self.times.iter().any(|map| map.contains_key(&event_id))
+        self.events.contains_key(&event_id)
+    }
+
+    /// Stop tracking an event that's been deleted
+    fn forget_event(&mut self, event_id: i32) {
+        ctx_debug!(LogContext::new("timer").event_id(event_id), "Forgetting event");
+        self.schedule.remove_event(event_id);
+        self.events.remove(&event_id);
     }
 
     /// Properly place and notify telegram of an updated event
     fn update_event(&mut self, event: Event) {
-        self.remove_event(event.id());
+        // Changes that don't move any stages, like the event being boosted, only need the stored
+        // copy replaced
+        if self.events
+            .get(&event.id())
+            .map(|tracked| same_stages(tracked, &event))
+            .unwrap_or(false)
+        {
+            self.events.insert(event.id(), event);
+            return;
+        }
+
+        // Stages carried out early are claimed for the times they were queued for, which every
+        // instance tracking the event agrees on
+        let publish_due = self.schedule.due(event.id(), Stage::Publish);
+        let close_due = self.schedule.due(event.id(), Stage::CloseRsvps);
+
+        self.schedule.remove_event(event.id());
+        self.events.remove(&event.id());
+
+        // The host may have decided to announce the event right away
+        if let Some(due) = publish_due {
+            if event.publish_at().is_none() {
+                self.publish_event(event.clone(), due);
+            }
+        }
+
+        // The host may have moved the RSVP deadline into the past
+        if let Some(due) = close_due {
+            if event.rsvps_closed() {
+                self.close_rsvps(event.clone(), due);
+            }
+        }
 
         self.new_event(event, Utc::now());
     }
 
-    /// Properly place and notify telegram of a new event
+    /// Queue every stage a new event has left
     fn new_event(&mut self, event: Event, now: DateTime<Utc>) {
-        debug!("Handling event");
-
-        if !self.tracking_event(event.id()) {
-            debug!("New event!");
-            let start = event.start_date().with_timezone(&Utc);
-            let end = event.end_date().with_timezone(&Utc);
-
-            let should_have_ended = now > end;
-            let ending_soon = now + OldDuration::hours(1) > end;
-            let should_have_started = now > start;
-            let starting_soon = now + OldDuration::minutes(45) > start;
-            let should_drop = now + OldDuration::hours(1) < start;
-
-            if should_have_ended {
-                debug!("Should have ended");
-                // delete event
-                self.delete_event(event);
-            } else {
-                if should_have_started {
-                    debug!("Should have started");
-                    // notify start
-                    self.notify_now(event.clone());
-
-                    let end_index = event.end_date().minute() as usize;
-
-                    if ending_soon {
-                        debug!("Ending soon");
-                        self.times[end_index].insert(event.id(), (TimerState::WaitingEnd, event));
-                    } else {
-                        debug!("Not ending soon");
-                        self.times[end_index].insert(event.id(), (TimerState::Future, event));
-                    }
-                } else if starting_soon {
-                    debug!("Starting soon");
-                    self.notify_soon(event.clone());
-
-                    self.times[event.start_date().minute() as usize]
-                        .insert(event.id(), (TimerState::WaitingStart, event));
-                } else if !should_drop {
-                    debug!("Waiting");
-                    self.times[event.start_date().minute() as usize]
-                        .insert(event.id(), (TimerState::WaitingNotify, event));
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+        ctx_debug!(log_ctx, "Handling event");
+
+        // Pending events are picked up once they're approved
+        if event.pending() {
+            ctx_debug!(log_ctx, "Waiting for event to be approved");
+            return;
+        }
+
+        if self.tracking_event(event.id()) {
+            ctx_debug!(log_ctx, "Already tracking event");
+            return;
+        }
+
+        let stages = plan(
+            now,
+            event.start_date().with_timezone(&Utc),
+            event.end_date().with_timezone(&Utc),
+            event.publish_at().map(|publish_at| publish_at.with_timezone(&Utc)),
+            event
+                .rsvp_deadline()
+                .map(|rsvp_deadline| rsvp_deadline.with_timezone(&Utc)),
+        );
+
+        for (stage, due) in stages {
+            ctx_debug!(log_ctx, "Queueing {:?} for {}", stage, due);
+            self.schedule.insert(event.id(), stage, due);
+        }
+
+        self.events.insert(event.id(), event);
+    }
+
+    fn publish_event(&self, event: Event, due: DateTime<Utc>) {
+        let db = self.db.clone();
+        let tg = self.tg.clone();
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+
+        self.when_claimed(event.id(), Stage::Publish, due, move || {
+            db.send(MarkPublished {
+                event_id: event.id(),
+            }).then(flatten)
+                .map(move |_| {
+                    tg.do_send(NewEvent(event));
+                })
+                .map_err(log_err!(log_ctx, "Error publishing event"))
+        });
+    }
+
+    /// Tell telegram about the events starting soon, with one message for each system
+    ///
+    /// Events another instance claimed are left out
+    fn notify_soon(&self, events: Vec<(Event, DateTime<Utc>)>) {
+        let tg = self.tg.clone();
+        let db = self.db.clone();
+
+        // An event that can't be looked up is left out, rather than holding up the rest
+        let lookups = events
+            .into_iter()
+            .map(|(event, due)| {
+                let db = self.db.clone();
+
+                Timer::claim(&self.db, event.id(), Stage::Soon, due)
+                    .and_then(move |claimed| {
+                        if claimed {
+                            Either::A(Timer::unless_cancelled(db, event))
+                        } else {
+                            Either::B(Ok(None).into_future())
+                        }
+                    })
+                    .then(|res| Ok::<_, ()>(res.unwrap_or(None)))
+            })
+            .collect::<Vec<_>>();
+
+        Arbiter::handle().spawn(join_all(lookups).and_then(move |events| {
+            let events = events.into_iter().filter_map(|event| event).collect::<Vec<_>>();
+            let mut systems: HashMap<i32, Vec<Event>> = HashMap::new();
+
+            for event in &events {
+                systems
+                    .entry(event.system_id())
+                    .or_insert_with(Vec::new)
+                    .push(event.clone());
+            }
+
+            for (_, mut system_events) in systems {
+                if system_events.len() == 1 {
+                    tg.do_send(EventSoon(system_events.remove(0)));
+                } else {
+                    tg.do_send(EventsSoon(system_events));
                 }
             }
-        }
+
+            join_all(
+                events
+                    .into_iter()
+                    .map(move |event| Timer::remind_attendees(db.clone(), tg.clone(), event))
+                    .collect::<Vec<_>>(),
+            ).map(|_| ())
+        }));
     }
 
-    fn notify_soon(&self, event: Event) {
-        self.tg.do_send(EventSoon(event));
+    /// Remind the event's attendees privately, leaving out the ones who muted reminders
+    fn remind_attendees(
+        db: Addr<Unsync, DbBroker>,
+        tg: Addr<Syn, TelegramActor>,
+        event: Event,
+    ) -> impl Future<Item = (), Error = ()> {
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+
+        db.send(GetRecipients {
+            event_id: event.id(),
+            notification: Notification::Reminders,
+        }).then(flatten)
+            .map(move |user_ids| {
+                if !user_ids.is_empty() {
+                    tg.do_send(RemindAttendees { event, user_ids });
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error looking up attendees to remind"))
     }
 
-    fn notify_now(&self, event: Event) {
-        self.tg.do_send(EventStarted(event));
+    fn notify_now(&self, event: Event, due: DateTime<Utc>) {
+        let db = self.db.clone();
+        let tg = self.tg.clone();
+
+        self.when_claimed(event.id(), Stage::Start, due, move || {
+            Timer::unless_cancelled(db, event).map(move |event| {
+                if let Some(event) = event {
+                    tg.do_send(EventStarted(event));
+                }
+            })
+        });
     }
 
-    fn delete_event(&self, event: Event) {
+    fn close_rsvps(&self, event: Event, due: DateTime<Utc>) {
+        let db = self.db.clone();
         let tg = self.tg.clone();
 
-        Arbiter::handle().spawn(
-            self.db
-                .send(DeleteEvent {
-                    event_id: event.id(),
-                })
-                .then(flatten)
+        self.when_claimed(event.id(), Stage::CloseRsvps, due, move || {
+            Timer::unless_cancelled(db, event).map(move |event| {
+                if let Some(event) = event {
+                    // The host may have moved the deadline since the Timer heard about it
+                    if event.rsvps_closed() {
+                        tg.do_send(RsvpsClosed(event));
+                    }
+                }
+            })
+        });
+    }
+
+    /// Get the current state of an event, or `None` if it's been cancelled
+    ///
+    /// Events are cancelled from Telegram without the Timer hearing about it, so the event it's
+    /// tracking may be out of date
+    fn unless_cancelled(
+        db: Addr<Unsync, DbBroker>,
+        event: Event,
+    ) -> impl Future<Item = Option<Event>, Error = ()> {
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+
+        db.send(LookupEvent {
+            event_id: event.id(),
+        }).then(flatten)
+            .map(|event| {
+                if event.cancelled() {
+                    None
+                } else {
+                    Some(event)
+                }
+            })
+            .map_err(log_err!(log_ctx, "Error looking up event"))
+    }
+
+    fn delete_event(&self, event: Event, due: DateTime<Utc>) {
+        let db = self.db.clone();
+        let tg = self.tg.clone();
+        let log_ctx = LogContext::new("timer").event_id(event.id());
+
+        self.when_claimed(event.id(), Stage::End, due, move || {
+            db.send(DeleteEvent {
+                event_id: event.id(),
+            }).then(flatten)
                 .map(move |_| {
                     tg.do_send(EventOver(event));
                 })
-                .map_err(|e| error!("Error: {:?}", e)),
-        );
+                .map_err(log_err!(log_ctx, "Error deleting event"))
+        });
     }
 }
+
+/// Check whether two versions of an event would have their stages queued at the same times
+fn same_stages(tracked: &Event, event: &Event) -> bool {
+    tracked.pending() == event.pending() && tracked.start_date() == event.start_date()
+        && tracked.end_date() == event.end_date() && tracked.publish_at() == event.publish_at()
+        && tracked.rsvp_deadline() == event.rsvp_deadline()
+}
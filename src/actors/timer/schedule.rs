@@ -0,0 +1,290 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the Timer's queue of upcoming actions.
+//!
+//! Each event gets an entry for every stage it has left, due at the moment that stage should
+//! happen. Entries are kept in a binary heap ordered by when they're due, so the Timer only ever
+//! needs to wait for the first one.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use chrono::offset::Utc;
+use chrono::{DateTime, Duration as OldDuration};
+
+/// How long before an event starts its attendees are told it's coming up
+const SOON_MINUTES: i64 = 45;
+
+/// The things the Timer does to an event, in the order it does them when several are due at once
+/// for the same event
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Stage {
+    /// Announce an event that was held back until its publish date
+    Publish,
+    /// Tell the channel an event's RSVPs have closed
+    CloseRsvps,
+    /// Tell the channel and the attendees an event starts soon
+    Soon,
+    /// Tell the channel an event has started
+    Start,
+    /// Delete an event that has ended
+    End,
+}
+
+impl Stage {
+    /// The name the stage is claimed under in the database
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Stage::Publish => "publish",
+            Stage::CloseRsvps => "close_rsvps",
+            Stage::Soon => "soon",
+            Stage::Start => "start",
+            Stage::End => "end",
+        }
+    }
+}
+
+/// Work out which stages an event still needs, and when each is due
+///
+/// Stages that should already have happened are due immediately. An event waiting to be published
+/// only gets its `Publish` stage, and is planned again once it's been announced.
+pub fn plan(
+    now: DateTime<Utc>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    publish_at: Option<DateTime<Utc>>,
+    rsvp_deadline: Option<DateTime<Utc>>,
+) -> Vec<(Stage, DateTime<Utc>)> {
+    if let Some(publish_at) = publish_at {
+        return vec![(Stage::Publish, publish_at)];
+    }
+
+    let mut stages = Vec::new();
+
+    if let Some(rsvp_deadline) = rsvp_deadline {
+        if now < rsvp_deadline {
+            stages.push((Stage::CloseRsvps, rsvp_deadline));
+        }
+    }
+
+    if now < start {
+        stages.push((Stage::Soon, start - OldDuration::minutes(SOON_MINUTES)));
+    }
+
+    if now < end {
+        stages.push((Stage::Start, start));
+    }
+
+    stages.push((Stage::End, end));
+    stages
+}
+
+/// A delay queue of `(due, event_id, stage)` entries
+///
+/// Replacing or removing an entry leaves the old one in the heap, and it's skipped when it comes
+/// up, since it no longer matches the time recorded for its event and stage.
+pub struct Schedule {
+    queue: BinaryHeap<Reverse<(DateTime<Utc>, i32, Stage)>>,
+    pending: HashMap<(i32, Stage), DateTime<Utc>>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Schedule {
+            queue: BinaryHeap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Queue a stage for an event, replacing the time it was due if it was already queued
+    pub fn insert(&mut self, event_id: i32, stage: Stage, due: DateTime<Utc>) {
+        self.pending.insert((event_id, stage), due);
+        self.queue.push(Reverse((due, event_id, stage)));
+    }
+
+    /// Get when a stage of the event is due, if it's queued
+    pub fn due(&self, event_id: i32, stage: Stage) -> Option<DateTime<Utc>> {
+        self.pending.get(&(event_id, stage)).cloned()
+    }
+
+    /// Check whether any stages are queued for the event
+    pub fn contains_event(&self, event_id: i32) -> bool {
+        self.pending.keys().any(|&(id, _)| id == event_id)
+    }
+
+    /// Forget every stage queued for the event, returning the ones that were queued
+    pub fn remove_event(&mut self, event_id: i32) -> Vec<Stage> {
+        let stages = self.pending
+            .keys()
+            .filter(|&&(id, _)| id == event_id)
+            .map(|&(_, stage)| stage)
+            .collect::<Vec<_>>();
+
+        for stage in stages.iter() {
+            self.pending.remove(&(event_id, *stage));
+        }
+
+        stages
+    }
+
+    /// Get when the next queued stage is due, dropping stale entries on the way
+    pub fn next_due(&mut self) -> Option<DateTime<Utc>> {
+        loop {
+            let (due, event_id, stage) = match self.queue.peek() {
+                Some(&Reverse(entry)) => entry,
+                None => return None,
+            };
+
+            if self.pending.get(&(event_id, stage)) == Some(&due) {
+                return Some(due);
+            }
+
+            self.queue.pop();
+        }
+    }
+
+    /// Take the next stage that's due by `now`, along with when it was due, if there is one
+    pub fn pop_due(&mut self, now: DateTime<Utc>) -> Option<(i32, Stage, DateTime<Utc>)> {
+        match self.next_due() {
+            Some(due) if due <= now => {
+                if let Some(Reverse((_, event_id, stage))) = self.queue.pop() {
+                    self.pending.remove(&(event_id, stage));
+
+                    Some((event_id, stage, due))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::offset::Utc;
+    use chrono::{DateTime, Duration as OldDuration, TimeZone};
+
+    use super::{plan, Schedule, Stage};
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.ymd(2018, 6, 28).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn plans_every_stage_for_a_future_event() {
+        let stages = plan(at(12, 0), at(19, 0), at(21, 0), None, Some(at(18, 0)));
+
+        assert_eq!(
+            stages,
+            vec![
+                (Stage::CloseRsvps, at(18, 0)),
+                (Stage::Soon, at(18, 15)),
+                (Stage::Start, at(19, 0)),
+                (Stage::End, at(21, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_soon_once_an_event_has_started() {
+        let stages = plan(at(19, 0) + OldDuration::seconds(1), at(19, 0), at(21, 0), None, None);
+
+        assert_eq!(stages, vec![(Stage::Start, at(19, 0)), (Stage::End, at(21, 0))]);
+    }
+
+    #[test]
+    fn only_ends_an_event_that_has_ended() {
+        let stages = plan(at(21, 0), at(19, 0), at(21, 0), None, Some(at(18, 0)));
+
+        assert_eq!(stages, vec![(Stage::End, at(21, 0))]);
+    }
+
+    #[test]
+    fn waits_to_publish_before_anything_else() {
+        let stages = plan(at(12, 0), at(19, 0), at(21, 0), Some(at(13, 0)), Some(at(18, 0)));
+
+        assert_eq!(stages, vec![(Stage::Publish, at(13, 0))]);
+    }
+
+    #[test]
+    fn pops_stages_only_once_theyre_due() {
+        let mut schedule = Schedule::new();
+        schedule.insert(1, Stage::Start, at(19, 0));
+        schedule.insert(2, Stage::Soon, at(18, 15));
+
+        assert_eq!(schedule.next_due(), Some(at(18, 15)));
+        assert_eq!(schedule.pop_due(at(18, 14)), None);
+        assert_eq!(schedule.pop_due(at(18, 15)), Some((2, Stage::Soon, at(18, 15))));
+        assert_eq!(schedule.pop_due(at(18, 59)), None);
+        assert_eq!(schedule.pop_due(at(19, 30)), Some((1, Stage::Start, at(19, 0))));
+        assert_eq!(schedule.next_due(), None);
+    }
+
+    #[test]
+    fn pops_stages_due_together_in_order() {
+        let mut schedule = Schedule::new();
+        schedule.insert(1, Stage::End, at(19, 0));
+        schedule.insert(1, Stage::Start, at(19, 0));
+        schedule.insert(1, Stage::Soon, at(19, 0));
+
+        assert_eq!(schedule.pop_due(at(19, 0)), Some((1, Stage::Soon, at(19, 0))));
+        assert_eq!(schedule.pop_due(at(19, 0)), Some((1, Stage::Start, at(19, 0))));
+        assert_eq!(schedule.pop_due(at(19, 0)), Some((1, Stage::End, at(19, 0))));
+        assert!(!schedule.contains_event(1));
+    }
+
+    #[test]
+    fn skips_replaced_and_removed_entries() {
+        let mut schedule = Schedule::new();
+        schedule.insert(1, Stage::Start, at(19, 0));
+        schedule.insert(1, Stage::Start, at(20, 0));
+        schedule.insert(2, Stage::End, at(19, 30));
+
+        assert_eq!(schedule.remove_event(2), vec![Stage::End]);
+        assert_eq!(schedule.next_due(), Some(at(20, 0)));
+        assert_eq!(schedule.pop_due(at(19, 45)), None);
+        assert_eq!(schedule.pop_due(at(20, 0)), Some((1, Stage::Start, at(20, 0))));
+        assert_eq!(schedule.pop_due(at(23, 0)), None);
+    }
+
+    #[test]
+    fn reports_when_queued_stages_are_due() {
+        let mut schedule = Schedule::new();
+        schedule.insert(1, Stage::Publish, at(13, 0));
+        schedule.insert(1, Stage::Publish, at(14, 0));
+
+        assert_eq!(schedule.due(1, Stage::Publish), Some(at(14, 0)));
+        assert_eq!(schedule.due(1, Stage::CloseRsvps), None);
+
+        schedule.remove_event(1);
+        assert_eq!(schedule.due(1, Stage::Publish), None);
+    }
+
+    #[test]
+    fn pops_a_duplicate_entry_once() {
+        let mut schedule = Schedule::new();
+        schedule.insert(1, Stage::End, at(21, 0));
+        schedule.insert(1, Stage::End, at(21, 0));
+
+        assert_eq!(schedule.pop_due(at(21, 0)), Some((1, Stage::End, at(21, 0))));
+        assert_eq!(schedule.pop_due(at(21, 0)), None);
+    }
+}
@@ -0,0 +1,121 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::time::{Duration, Instant};
+
+use actix::{
+    Actor, Addr, Arbiter, AsyncContext, Context, Handler, Message, Running, StreamHandler, Syn,
+};
+use futures::{Future, Stream};
+use tokio_timer::Interval;
+
+use super::TimerCoordinator;
+use actors::db_broker::messages::EventChanged;
+use actors::timer::messages::{Events, NextHour, Shutdown, UpdateEvent};
+use logging::LogContext;
+
+impl Actor for TimerCoordinator {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        debug!("Started TimerCoordinator Actor");
+        // Every 30 minutes, check for events happening in the next hour
+        ctx.add_stream(
+            Interval::new(Instant::now(), Duration::from_secs(30 * 60))
+                .map(|_| NextHour)
+                .map_err(|_| Shutdown),
+        );
+
+        ctx.notify(NextHour);
+    }
+}
+
+impl Handler<NextHour> for TimerCoordinator {
+    type Result = <NextHour as Message>::Result;
+
+    fn handle(&mut self, _: NextHour, ctx: &mut Self::Context) -> Self::Result {
+        let address: Addr<Syn, _> = ctx.address();
+
+        let fut = self.get_next_hour()
+            .map(move |events| {
+                address.do_send(Events { events });
+            })
+            .map_err(log_err!(LogContext::new("timer"), "Error fetching upcoming events"));
+
+        Arbiter::handle().spawn(fut);
+        self.send_digests();
+        self.send_boosts();
+    }
+}
+
+impl StreamHandler<NextHour, Shutdown> for TimerCoordinator {
+    fn handle(&mut self, _: NextHour, ctx: &mut Self::Context) {
+        let address: Addr<Syn, _> = ctx.address();
+
+        let fut = self.get_next_hour()
+            .map(move |events| {
+                address.do_send(Events { events });
+            })
+            .map_err(log_err!(LogContext::new("timer"), "Error fetching upcoming events"));
+
+        Arbiter::handle().spawn(fut);
+        self.send_digests();
+        self.send_boosts();
+    }
+
+    fn error(&mut self, _: Shutdown, _: &mut Self::Context) -> Running {
+        ctx_error!(LogContext::new("timer"), "Interval for NextHour errored");
+        Running::Continue
+    }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        ctx.add_stream(
+            Interval::new(Instant::now(), Duration::from_secs(60 * 60))
+                .map(|_| NextHour)
+                .map_err(|_| Shutdown),
+        );
+    }
+}
+
+impl Handler<Events> for TimerCoordinator {
+    type Result = <Events as Message>::Result;
+
+    fn handle(&mut self, msg: Events, _: &mut Self::Context) -> Self::Result {
+        self.distribute(msg.events);
+    }
+}
+
+impl Handler<UpdateEvent> for TimerCoordinator {
+    type Result = <UpdateEvent as Message>::Result;
+
+    fn handle(&mut self, msg: UpdateEvent, _: &mut Self::Context) -> Self::Result {
+        self.update_event(msg.event);
+    }
+}
+
+impl Handler<EventChanged> for TimerCoordinator {
+    type Result = <EventChanged as Message>::Result;
+
+    fn handle(&mut self, msg: EventChanged, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            EventChanged::Saved(event_id) => self.refresh_event(event_id, ctx.address()),
+            EventChanged::Deleted(event_id) => self.forget_event(event_id),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the TimerCoordinator, which splits the work of the Timer between several
+//! Timers.
+//!
+//! Every chat system belongs to one Timer, picked from its ID, so events from a busy system can't
+//! hold up reminders for the others. The coordinator looks up upcoming events and passes each one
+//! to its system's Timer. It also posts each channel's weekly digest of upcoming events, and
+//! announces undersubscribed events again, since those only need to happen once.
+
+use std::collections::HashMap;
+
+use actix::{Addr, Arbiter, Syn, Unsync};
+use chrono::offset::Utc;
+use chrono::{Datelike, Duration as OldDuration, Timelike};
+use chrono_tz::Tz;
+use futures::Future;
+
+use actors::db_broker::messages::{
+    ClaimBoosts, ClaimDigests, GetEventsClosingRsvps, GetEventsForSystem, GetEventsInRange,
+    GetEventsToPublish, LookupEvent, ReleaseDigest,
+};
+use actors::db_broker::DbBroker;
+use actors::telegram_actor::messages::{Boost, Digest};
+use actors::telegram_actor::TelegramActor;
+use actors::timer::messages::{Events, ForgetEvent, UpdateEvent};
+use actors::timer::Timer;
+use error::EventError;
+use logging::LogContext;
+use models::event::Event;
+use util::flatten;
+
+mod actor;
+
+/// The hour of the day, in the configured timezone, after which weekly digests are posted
+const DIGEST_HOUR: u32 = 9;
+
+/// How many hours before an event starts it's announced again if too few people have RSVP'd
+const BOOST_HOURS: i64 = 48;
+
+pub struct TimerCoordinator {
+    db: Addr<Unsync, DbBroker>,
+    tg: Addr<Syn, TelegramActor>,
+    timers: Vec<Addr<Syn, Timer>>,
+    timezone: Tz,
+}
+
+impl TimerCoordinator {
+    /// Create a coordinator for the given Timers
+    ///
+    /// At least one Timer must be given
+    pub fn new(
+        db: Addr<Unsync, DbBroker>,
+        tg: Addr<Syn, TelegramActor>,
+        timers: Vec<Addr<Syn, Timer>>,
+        timezone: Tz,
+    ) -> Self {
+        assert!(timers.len() > 0, "A TimerCoordinator needs at least one Timer");
+
+        TimerCoordinator {
+            db,
+            tg,
+            timers,
+            timezone,
+        }
+    }
+
+    /// Get the index of the Timer that tracks events for the given chat system
+    fn shard(&self, system_id: i32) -> usize {
+        (system_id as u32 as usize) % self.timers.len()
+    }
+
+    /// Pass each event to the Timer for its chat system
+    fn distribute(&self, events: Vec<Event>) {
+        let mut shards: HashMap<usize, Vec<Event>> = HashMap::new();
+
+        for event in events {
+            shards
+                .entry(self.shard(event.system_id()))
+                .or_insert_with(Vec::new)
+                .push(event);
+        }
+
+        for (shard, events) in shards {
+            self.timers[shard].do_send(Events { events });
+        }
+    }
+
+    /// Pass an updated event to the Timer for its chat system
+    fn update_event(&self, event: Event) {
+        self.timers[self.shard(event.system_id())].do_send(UpdateEvent { event });
+    }
+
+    /// Catch the Timers up on an event another instance of the bot saved, by looking up its new
+    /// state
+    fn refresh_event(&self, event_id: i32, address: Addr<Syn, TimerCoordinator>) {
+        Arbiter::handle().spawn(
+            self.db
+                .send(LookupEvent { event_id })
+                .then(flatten)
+                .map(move |event| address.do_send(UpdateEvent { event }))
+                .map_err(log_err!(
+                    LogContext::new("timer").event_id(event_id),
+                    "Error looking up changed event"
+                )),
+        );
+    }
+
+    /// Tell every Timer to stop tracking a deleted event, since its chat system can't be looked up
+    /// anymore
+    fn forget_event(&self, event_id: i32) {
+        for timer in self.timers.iter() {
+            timer.do_send(ForgetEvent { event_id });
+        }
+    }
+
+    fn get_next_hour(&self) -> impl Future<Item = Vec<Event>, Error = EventError> {
+        let now = Utc::now();
+
+        self.db
+            .send(GetEventsInRange {
+                start_date: (now - OldDuration::hours(1)).with_timezone(&Tz::UTC),
+                end_date: (now + OldDuration::hours(1)).with_timezone(&Tz::UTC),
+            })
+            .then(flatten)
+            .join(
+                self.db
+                    .send(GetEventsToPublish {
+                        before: (now + OldDuration::hours(1)).with_timezone(&Tz::UTC),
+                    })
+                    .then(flatten),
+            )
+            .join(
+                self.db
+                    .send(GetEventsClosingRsvps {
+                        start_date: now.with_timezone(&Tz::UTC),
+                        end_date: (now + OldDuration::hours(1)).with_timezone(&Tz::UTC),
+                    })
+                    .then(flatten),
+            )
+            .map(|((mut events, to_publish), closing_rsvps)| {
+                events.extend(to_publish);
+                events.extend(closing_rsvps);
+                events
+            })
+    }
+
+    /// Announce events starting in the next two days again if too few people have RSVP'd
+    fn send_boosts(&self) {
+        let tg = self.tg.clone();
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(ClaimBoosts {
+                    before: (Utc::now() + OldDuration::hours(BOOST_HOURS)).with_timezone(&Tz::UTC),
+                })
+                .then(flatten)
+                .map(move |events| {
+                    for event in events {
+                        ctx_debug!(LogContext::new("timer").event_id(event.id()), "Boosting event");
+                        tg.do_send(Boost(event));
+                    }
+                })
+                .map_err(log_err!(LogContext::new("timer"), "Error claiming boosts")),
+        );
+    }
+
+    /// Post the weekly digest for every channel whose digest is due today
+    fn send_digests(&self) {
+        let now = Utc::now().with_timezone(&self.timezone);
+
+        if now.hour() < DIGEST_HOUR {
+            return;
+        }
+
+        // Midnight doesn't exist on days the clocks skip it
+        let since = now.date().and_hms_opt(0, 0, 0).unwrap_or_else(|| {
+            now - OldDuration::seconds(i64::from(now.num_seconds_from_midnight()))
+        });
+
+        let week_end = now + OldDuration::weeks(1);
+        let db = self.db.clone();
+        let tg = self.tg.clone();
+
+        Arbiter::handle().spawn(
+            self.db
+                .send(ClaimDigests {
+                    digest_day: now.weekday(),
+                    since,
+                    now,
+                })
+                .then(flatten)
+                .map(move |chat_systems| {
+                    for chat_system in chat_systems {
+                        let log_ctx =
+                            LogContext::new("timer").chat_id(chat_system.events_channel());
+                        ctx_debug!(log_ctx, "Posting digest for system {}", chat_system.id());
+                        let system_id = chat_system.id();
+                        let tg = tg.clone();
+                        let db2 = db.clone();
+                        let release_ctx = log_ctx.clone();
+
+                        Arbiter::handle().spawn(
+                            db.send(GetEventsForSystem { system_id })
+                                .then(flatten)
+                                .map(move |events| {
+                                    let events = events
+                                        .into_iter()
+                                        .filter(|event| *event.start_date() < week_end)
+                                        .collect::<Vec<_>>();
+
+                                    // Don't post an empty digest
+                                    if events.len() > 0 {
+                                        tg.do_send(Digest {
+                                            chat_system,
+                                            events,
+                                        });
+                                    }
+                                })
+                                .or_else(move |e| {
+                                    ctx_error!(log_ctx, "Error fetching digest events: {:?}", e);
+
+                                    // Try again next hour
+                                    db2.send(ReleaseDigest { system_id })
+                                        .then(flatten)
+                                        .map_err(log_err!(release_ctx, "Error releasing digest"))
+                                }),
+                        );
+                    }
+                })
+                .map_err(log_err!(LogContext::new("timer"), "Error claiming digests")),
+        );
+    }
+}
@@ -30,6 +30,7 @@ use super::messages::*;
 use super::{DeleteState, UsersActor};
 use actors::db_broker::messages::{GetSystemsWithChats, GetUsersWithChats};
 use error::EventError;
+use logging::LogContext;
 use models::chat::Chat;
 use models::chat_system::ChatSystem;
 use models::user::User;
@@ -62,12 +63,12 @@ impl StreamHandler<TouchUser, EventError> for UsersActor {
     }
 
     fn error(&mut self, err: EventError, _: &mut Self::Context) -> Running {
-        error!("Error in TouchUser: {:?}", err);
+        ctx_error!(LogContext::new("users"), "Error in TouchUser: {:?}", err);
         Running::Continue
     }
 
     fn finished(&mut self, _: &mut Self::Context) {
-        debug!("Done importing Users");
+        ctx_debug!(LogContext::new("users"), "Done importing Users");
     }
 }
 
@@ -77,12 +78,12 @@ impl StreamHandler<TouchChannel, EventError> for UsersActor {
     }
 
     fn error(&mut self, err: EventError, _: &mut Self::Context) -> Running {
-        error!("Error in TouchChannel: {:?}", err);
+        ctx_error!(LogContext::new("users"), "Error in TouchChannel: {:?}", err);
         Running::Continue
     }
 
     fn finished(&mut self, ctx: &mut Self::Context) {
-        debug!("Done importing Channels");
+        ctx_debug!(LogContext::new("users"), "Done importing Channels");
         let db = self.db.clone();
 
         // add a stream that adds users from the database to the UsersActor's store
@@ -134,6 +135,14 @@ impl Handler<LookupChannels> for UsersActor {
     }
 }
 
+impl Handler<LookupLinkedChats> for UsersActor {
+    type Result = Result<HashSet<Integer>, EventError>;
+
+    fn handle(&mut self, _: LookupLinkedChats, _: &mut Self::Context) -> Self::Result {
+        Ok(self.chats.clone())
+    }
+}
+
 impl Handler<RemoveRelation> for UsersActor {
     type Result = Result<DeleteState, EventError>;
 
@@ -141,3 +150,27 @@ impl Handler<RemoveRelation> for UsersActor {
         Ok(self.remove_relation(msg.0, msg.1))
     }
 }
+
+impl Handler<RemoveChat> for UsersActor {
+    type Result = <RemoveChat as Message>::Result;
+
+    fn handle(&mut self, msg: RemoveChat, _: &mut Self::Context) -> Self::Result {
+        self.remove_chat(msg.0)
+    }
+}
+
+impl Handler<RemoveChannel> for UsersActor {
+    type Result = <RemoveChannel as Message>::Result;
+
+    fn handle(&mut self, msg: RemoveChannel, _: &mut Self::Context) -> Self::Result {
+        self.remove_channel(msg.0)
+    }
+}
+
+impl Handler<RemoveUser> for UsersActor {
+    type Result = <RemoveUser as Message>::Result;
+
+    fn handle(&mut self, msg: RemoveUser, _: &mut Self::Context) -> Self::Result {
+        self.remove_user(msg.0)
+    }
+}
@@ -51,6 +51,14 @@ impl Message for LookupChannels {
     type Result = Result<HashSet<Integer>, EventError>;
 }
 
+/// This type is for looking up every chat linked to a channel
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LookupLinkedChats;
+
+impl Message for LookupLinkedChats {
+    type Result = Result<HashSet<Integer>, EventError>;
+}
+
 /// This type is for ensuring a relationship between a channel and a chat
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct TouchChannel(pub Integer, pub Integer);
@@ -66,3 +74,27 @@ pub struct RemoveRelation(pub Integer, pub Integer);
 impl Message for RemoveRelation {
     type Result = Result<DeleteState, EventError>;
 }
+
+/// This type is for forgetting a chat the bot isn't in anymore
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RemoveChat(pub Integer);
+
+impl Message for RemoveChat {
+    type Result = ();
+}
+
+/// This type is for forgetting a channel the bot isn't in anymore, along with its chats
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RemoveChannel(pub Integer);
+
+impl Message for RemoveChannel {
+    type Result = ();
+}
+
+/// This type is for forgetting a user's chats entirely
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RemoveUser(pub Integer);
+
+impl Message for RemoveUser {
+    type Result = ();
+}
@@ -25,6 +25,7 @@ use actix::{Addr, Unsync};
 use telebot::objects::Integer;
 
 use actors::db_broker::DbBroker;
+use logging::LogContext;
 
 mod actor;
 pub mod messages;
@@ -68,7 +69,10 @@ impl UsersActor {
 
     fn touch_user(&mut self, user_id: Integer, chat_id: Integer) -> UserState {
         if !self.chats.contains(&chat_id) {
-            debug!("Chat isn't known");
+            ctx_debug!(
+                LogContext::new("users").chat_id(chat_id),
+                "Chat isn't known"
+            );
             return UserState::InvalidQuery;
         }
 
@@ -122,7 +126,11 @@ impl UsersActor {
     }
 
     fn remove_relation(&mut self, user_id: Integer, chat_id: Integer) -> DeleteState {
-        debug!("Removing chat {} from user {}", chat_id, user_id);
+        ctx_debug!(
+            LogContext::new("users").chat_id(chat_id),
+            "Removing chat from user {}",
+            user_id
+        );
         let mut hs = match self.users.remove(&user_id) {
             Some(hs) => hs,
             None => return DeleteState::UserEmpty,
@@ -137,4 +145,31 @@ impl UsersActor {
             DeleteState::UserEmpty
         }
     }
+
+    fn remove_chat(&mut self, chat_id: Integer) {
+        ctx_debug!(LogContext::new("users").chat_id(chat_id), "Removing chat");
+        self.chats.remove(&chat_id);
+
+        for chats in self.users.values_mut().chain(self.channels.values_mut()) {
+            chats.remove(&chat_id);
+        }
+
+        // Users who were only known from the removed chat are forgotten along with it
+        self.users.retain(|_, chats| !chats.is_empty());
+    }
+
+    fn remove_channel(&mut self, channel_id: Integer) {
+        ctx_debug!(LogContext::new("users"), "Removing channel {}", channel_id);
+
+        if let Some(chats) = self.channels.remove(&channel_id) {
+            for chat_id in chats {
+                self.remove_chat(chat_id);
+            }
+        }
+    }
+
+    fn remove_user(&mut self, user_id: Integer) {
+        ctx_debug!(LogContext::new("users"), "Removing user {}", user_id);
+        self.users.remove(&user_id);
+    }
 }
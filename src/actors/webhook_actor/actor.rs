@@ -0,0 +1,45 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use actix::{Actor, Addr, AsyncContext, Context, Handler, Unsync};
+
+use super::messages::*;
+use super::WebhookActor;
+
+impl Actor for WebhookActor {
+    type Context = Context<Self>;
+}
+
+impl Handler<EventChanged> for WebhookActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: EventChanged, ctx: &mut Self::Context) -> Self::Result {
+        let address: Addr<Unsync, _> = ctx.address();
+
+        self.event_changed(msg.action, msg.event, address);
+    }
+}
+
+impl Handler<Delivered> for WebhookActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Delivered, _: &mut Self::Context) -> Self::Result {
+        self.delivered(msg.webhook_id, msg.success);
+    }
+}
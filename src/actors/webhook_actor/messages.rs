@@ -0,0 +1,46 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the messages that the WebhookActor can receive.
+
+use actix::Message;
+
+use super::EventAction;
+use models::event::Event;
+
+/// This notifies the WebhookActor that an event was created, updated, or deleted
+pub struct EventChanged {
+    pub action: EventAction,
+    pub event: Event,
+}
+
+impl Message for EventChanged {
+    type Result = ();
+}
+
+/// This tells the WebhookActor whether a change was delivered to a webhook
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Delivered {
+    pub webhook_id: i32,
+    pub success: bool,
+}
+
+impl Message for Delivered {
+    type Result = ();
+}
@@ -0,0 +1,600 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module loads the bot's configuration.
+//!
+//! Settings are read from an optional TOML file, `config.toml` or the path in `CONFIG_FILE`, and
+//! from environment variables. Each setting's key in the file is the lowercase name of its
+//! environment variable, and environment variables take precedence over the file.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use dotenv::dotenv;
+use event_web::{Theme, TlsSettings};
+use failure::Fail;
+use toml;
+use toml::value::{Table, Value};
+
+use error::{ConfigError, EventError, EventErrorKind};
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_POOL_SIZE: usize = 5;
+const DEFAULT_DB_BUSY_TIMEOUT: u64 = 5000;
+const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:8000";
+const DEFAULT_TLS_BIND_ADDRESS: &str = "0.0.0.0:8443";
+const DEFAULT_TIMEZONE: &str = "US/Central";
+const DEFAULT_FORM_YEARS: u32 = 4;
+const DEFAULT_TIMER_SHARDS: usize = 1;
+
+/// How the bot receives updates from Telegram
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateMode {
+    /// Ask Telegram for updates
+    Polling,
+    /// Have Telegram send updates to the Web UI
+    Webhook,
+}
+
+impl UpdateMode {
+    /// Get the mode from its name, like `polling`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "polling" => Some(UpdateMode::Polling),
+            "webhook" => Some(UpdateMode::Webhook),
+            _ => None,
+        }
+    }
+
+    /// Get the mode's name
+    pub fn name(&self) -> &'static str {
+        match *self {
+            UpdateMode::Polling => "polling",
+            UpdateMode::Webhook => "webhook",
+        }
+    }
+}
+
+/// `Config` holds the validated settings the bot needs to start
+#[derive(Clone, Debug)]
+pub struct Config {
+    bot_token: String,
+    event_url: String,
+    database_url: String,
+    pool_size: usize,
+    db_busy_timeout: u64,
+    bind_address: String,
+    timezone: Tz,
+    form_years: u32,
+    timer_shards: usize,
+    update_mode: UpdateMode,
+    bot_admins: Vec<i64>,
+    alert_chat: Option<i64>,
+    event_quota: Option<i64>,
+    embed_origins: Vec<String>,
+    robots: Option<String>,
+    theme: Theme,
+    csp_report_only: bool,
+    tls: Option<TlsSettings>,
+    trusted_proxies: Vec<IpAddr>,
+}
+
+impl Config {
+    /// Load and validate the configuration
+    ///
+    /// This fails with a `ConfigError` describing the first missing or invalid setting
+    pub fn load() -> Result<Self, EventError> {
+        dotenv().ok();
+
+        Settings::load()
+            .and_then(|settings| Config::from_settings(&settings))
+            .map_err(|e| e.context(EventErrorKind::Config).into())
+    }
+
+    fn from_settings(settings: &Settings) -> Result<Self, ConfigError> {
+        let username = settings.require("db_user")?;
+        let password = settings.require("db_pass")?;
+        let host = settings.require("db_host")?;
+        let port = settings.require("db_port")?;
+        #[cfg(not(test))]
+        let name = settings.require("db_name")?;
+        #[cfg(test)]
+        let name = settings.require("test_db_name")?;
+
+        port.parse::<u16>()
+            .map_err(|_| ConfigError::invalid("db_port", &port, "must be a port number"))?;
+
+        // Links in messages and the forms' actions are built from this, so it needs a scheme for
+        // browsers to treat it as a web address
+        let event_url = settings.require("event_url")?;
+
+        if !(event_url.starts_with("https://") || event_url.starts_with("http://")) {
+            return Err(ConfigError::invalid(
+                "event_url",
+                &event_url,
+                "must be a web address, like https://events.example.com",
+            ));
+        }
+
+        let pool_size = match settings.get("db_pool_size") {
+            Some(pool_size) => match pool_size.parse::<usize>() {
+                Ok(size) if size > 0 => size,
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "db_pool_size",
+                        &pool_size,
+                        "must be a number greater than zero",
+                    ))
+                }
+            },
+            None => DEFAULT_POOL_SIZE,
+        };
+
+        let db_busy_timeout = match settings.get("db_busy_timeout") {
+            Some(db_busy_timeout) => match db_busy_timeout.parse::<u64>() {
+                Ok(timeout) if timeout > 0 => timeout,
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "db_busy_timeout",
+                        &db_busy_timeout,
+                        "must be a number of milliseconds greater than zero",
+                    ))
+                }
+            },
+            None => DEFAULT_DB_BUSY_TIMEOUT,
+        };
+
+        let bind_address = settings
+            .get("bind_address")
+            .unwrap_or(DEFAULT_BIND_ADDRESS.to_owned());
+
+        bind_address.parse::<SocketAddr>().map_err(|_| {
+            ConfigError::invalid(
+                "bind_address",
+                &bind_address,
+                "must be an IP address and port, like 0.0.0.0:8000",
+            )
+        })?;
+
+        let timezone = settings
+            .get("timezone")
+            .unwrap_or(DEFAULT_TIMEZONE.to_owned());
+
+        let timezone = timezone.parse::<Tz>().map_err(|_| {
+            ConfigError::invalid(
+                "timezone",
+                &timezone,
+                "must be a timezone name, like US/Central",
+            )
+        })?;
+
+        let form_years = match settings.get("form_years") {
+            Some(form_years) => match form_years.parse::<u32>() {
+                Ok(years) if years > 0 => years,
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "form_years",
+                        &form_years,
+                        "must be a number greater than zero",
+                    ))
+                }
+            },
+            None => DEFAULT_FORM_YEARS,
+        };
+
+        let timer_shards = match settings.get("timer_shards") {
+            Some(timer_shards) => match timer_shards.parse::<usize>() {
+                Ok(shards) if shards > 0 => shards,
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "timer_shards",
+                        &timer_shards,
+                        "must be a number greater than zero",
+                    ))
+                }
+            },
+            None => DEFAULT_TIMER_SHARDS,
+        };
+
+        let update_mode = match settings.get("update_mode") {
+            Some(update_mode) => UpdateMode::from_name(&update_mode).ok_or_else(|| {
+                ConfigError::invalid("update_mode", &update_mode, "must be polling or webhook")
+            })?,
+            None => UpdateMode::Polling,
+        };
+
+        let bot_admins = match settings.get("bot_admins") {
+            Some(bot_admins) => bot_admins
+                .split(',')
+                .map(|user_id| user_id.trim())
+                .filter(|user_id| user_id.len() > 0)
+                .map(|user_id| user_id.parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    ConfigError::invalid(
+                        "bot_admins",
+                        &bot_admins,
+                        "must be a comma-separated list of Telegram user IDs",
+                    )
+                })?,
+            None => Vec::new(),
+        };
+
+        // ADMIN_CHAT_ID is accepted as another name for ALERT_CHAT
+        let alert_chat = settings
+            .get("alert_chat")
+            .map(|alert_chat| ("alert_chat", alert_chat))
+            .or_else(|| {
+                settings
+                    .get("admin_chat_id")
+                    .map(|alert_chat| ("admin_chat_id", alert_chat))
+            });
+
+        let alert_chat = match alert_chat {
+            Some((key, alert_chat)) => Some(alert_chat.parse::<i64>().map_err(|_| {
+                ConfigError::invalid(key, &alert_chat, "must be a Telegram chat ID")
+            })?),
+            None => None,
+        };
+
+        let event_quota = match settings.get("event_quota") {
+            Some(event_quota) => match event_quota.parse::<i64>() {
+                Ok(quota) if quota > 0 => Some(quota),
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "event_quota",
+                        &event_quota,
+                        "must be a number greater than zero",
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        let embed_origins = match settings.get("embed_origins") {
+            Some(embed_origins) => {
+                let origins = embed_origins
+                    .split(',')
+                    .map(|origin| origin.trim().trim_right_matches('/').to_owned())
+                    .filter(|origin| origin.len() > 0)
+                    .collect::<Vec<_>>();
+
+                let valid = origins.iter().all(|origin| {
+                    (origin.starts_with("https://") || origin.starts_with("http://"))
+                        && !origin.contains(|c: char| c.is_whitespace() || c == ';' || c == '\'')
+                });
+
+                if !valid {
+                    return Err(ConfigError::invalid(
+                        "embed_origins",
+                        &embed_origins,
+                        "must be a comma-separated list of origins, like https://example.com",
+                    ));
+                }
+
+                origins
+            }
+            None => Vec::new(),
+        };
+
+        let robots = match settings.get("robots_file") {
+            Some(robots_file) => Some(read_file(&robots_file).map_err(|_| {
+                ConfigError::invalid("robots_file", &robots_file, "must be a file that can be read")
+            })?),
+            None => None,
+        };
+
+        let mut theme = Theme::default();
+
+        if let Some(color) = theme_color(settings, "theme_accent_color")? {
+            theme = theme.accent_color(&color);
+        }
+
+        if let Some(color) = theme_color(settings, "theme_background_color")? {
+            theme = theme.background_color(&color);
+        }
+
+        if let Some(logo_url) = settings.get("theme_logo_url") {
+            if !(logo_url.starts_with("https://") || logo_url.starts_with("http://")) {
+                return Err(ConfigError::invalid(
+                    "theme_logo_url",
+                    &logo_url,
+                    "must be a web address, like https://example.com/logo.png",
+                ));
+            }
+
+            theme = theme.logo_url(&logo_url);
+        }
+
+        if let Some(footer) = settings.get("theme_footer") {
+            theme = theme.footer(&footer);
+        }
+
+        let csp_report_only = match settings.get("csp_report_only") {
+            Some(csp_report_only) => match csp_report_only.as_str() {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(ConfigError::invalid(
+                        "csp_report_only",
+                        &csp_report_only,
+                        "must be true or false",
+                    ))
+                }
+            },
+            None => false,
+        };
+
+        let tls = tls_settings(settings)?;
+
+        let trusted_proxies = match settings.get("trusted_proxies") {
+            Some(trusted_proxies) => trusted_proxies
+                .split(',')
+                .map(|proxy| proxy.trim())
+                .filter(|proxy| proxy.len() > 0)
+                .map(|proxy| proxy.parse::<IpAddr>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    ConfigError::invalid(
+                        "trusted_proxies",
+                        &trusted_proxies,
+                        "must be a comma-separated list of IP addresses",
+                    )
+                })?,
+            None => Vec::new(),
+        };
+
+        Ok(Config {
+            bot_token: settings.require("telegram_bot_token")?,
+            event_url,
+            database_url: format!(
+                "postgres://{}:{}@{}:{}/{}",
+                username, password, host, port, name
+            ),
+            pool_size,
+            db_busy_timeout,
+            bind_address,
+            timezone,
+            form_years,
+            timer_shards,
+            update_mode,
+            bot_admins,
+            alert_chat,
+            event_quota,
+            embed_origins,
+            robots,
+            theme,
+            csp_report_only,
+            tls,
+            trusted_proxies,
+        })
+    }
+
+    pub fn bot_token(&self) -> &str {
+        &self.bot_token
+    }
+
+    /// The base URL of the Web UI, used to build links to create and edit events
+    pub fn event_url(&self) -> &str {
+        &self.event_url
+    }
+
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    /// The number of database connections each `DbBroker` keeps open
+    pub fn pool_size(&self) -> usize {
+        self.pool_size
+    }
+
+    /// How long a request waits for a free database connection before failing as busy
+    pub fn db_busy_timeout(&self) -> Duration {
+        Duration::from_millis(self.db_busy_timeout)
+    }
+
+    /// The address the Web UI listens on
+    pub fn bind_address(&self) -> &str {
+        &self.bind_address
+    }
+
+    /// The timezone dates are shown in
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// The number of years offered in the Web UI's date pickers
+    pub fn form_years(&self) -> u32 {
+        self.form_years
+    }
+
+    /// The number of Timers that event reminders are split between
+    pub fn timer_shards(&self) -> usize {
+        self.timer_shards
+    }
+
+    /// How the bot receives updates from Telegram when it starts
+    pub fn update_mode(&self) -> UpdateMode {
+        self.update_mode
+    }
+
+    /// The Telegram IDs of the users who run the bot, who can switch how it receives updates
+    pub fn bot_admins(&self) -> &[i64] {
+        &self.bot_admins
+    }
+
+    /// The chat that problems with the bot are posted to, if there is one
+    pub fn alert_chat(&self) -> Option<i64> {
+        self.alert_chat
+    }
+
+    /// The most upcoming events each user can host for a channel, if there's a limit
+    pub fn event_quota(&self) -> Option<i64> {
+        self.event_quota
+    }
+
+    /// The sites that can show the Web UI's embeddable pages in a frame
+    pub fn embed_origins(&self) -> &[String] {
+        &self.embed_origins
+    }
+
+    /// The robots.txt the Web UI serves instead of its default, if one was given
+    pub fn robots(&self) -> Option<&str> {
+        self.robots.as_ref().map(|robots| robots.as_str())
+    }
+
+    /// The colors, logo, and footer the Web UI's pages are branded with
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Whether the Web UI's `Content-Security-Policy` only reports what it would block
+    pub fn csp_report_only(&self) -> bool {
+        self.csp_report_only
+    }
+
+    /// Where the Web UI serves HTTPS and where its certificate comes from, if HTTPS is on
+    pub fn tls(&self) -> Option<&TlsSettings> {
+        self.tls.as_ref()
+    }
+
+    /// The reverse proxies the Web UI trusts to say which address a request came from
+    pub fn trusted_proxies(&self) -> &[IpAddr] {
+        &self.trusted_proxies
+    }
+}
+
+/// `Settings` looks up raw setting values from the environment and the config file
+struct Settings {
+    file: Table,
+}
+
+impl Settings {
+    fn load() -> Result<Self, ConfigError> {
+        let (path, required) = match env::var("CONFIG_FILE") {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_FILE.to_owned(), false),
+        };
+
+        let contents = match read_file(&path) {
+            Ok(contents) => contents,
+            // The default config file is optional, but one that was asked for by name isn't
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Settings { file: Table::new() });
+            }
+            Err(_) => return Err(ConfigError::ReadFile(path)),
+        };
+
+        toml::from_str(&contents)
+            .map(|file| Settings { file })
+            .map_err(|e| ConfigError::ParseFile {
+                path,
+                reason: e.to_string(),
+            })
+    }
+
+    /// Get the value of a setting, preferring the environment over the config file
+    fn get(&self, key: &'static str) -> Option<String> {
+        env::var(key.to_uppercase())
+            .ok()
+            .or_else(|| match self.file.get(key) {
+                Some(&Value::String(ref value)) => Some(value.to_owned()),
+                Some(value) => Some(value.to_string()),
+                None => None,
+            })
+    }
+
+    fn require(&self, key: &'static str) -> Result<String, ConfigError> {
+        self.get(key).ok_or_else(|| ConfigError::missing(key))
+    }
+}
+
+/// Get where HTTPS is served and which certificate it serves
+///
+/// HTTPS is on when the certificate and key files are set. They're loaded now, so a certificate
+/// that doesn't go with its key is caught before the Web UI starts.
+fn tls_settings(settings: &Settings) -> Result<Option<TlsSettings>, ConfigError> {
+    let cert_file = settings.get("tls_cert_file");
+    let key_file = settings.get("tls_key_file");
+
+    let (cert_file, key_file) = match (cert_file, key_file) {
+        (Some(cert_file), Some(key_file)) => (cert_file, key_file),
+        (Some(_), None) => return Err(ConfigError::missing("tls_key_file")),
+        (None, Some(_)) => return Err(ConfigError::missing("tls_cert_file")),
+        (None, None) => return Ok(None),
+    };
+
+    let address = settings
+        .get("tls_bind_address")
+        .unwrap_or(DEFAULT_TLS_BIND_ADDRESS.to_owned());
+
+    address.parse::<SocketAddr>().map_err(|_| {
+        ConfigError::invalid(
+            "tls_bind_address",
+            &address,
+            "must be an IP address and port, like 0.0.0.0:8443",
+        )
+    })?;
+
+    readable("tls_cert_file", &cert_file)?;
+    readable("tls_key_file", &key_file)?;
+
+    let tls = TlsSettings::new(&address, &cert_file, &key_file);
+
+    tls.check().map_err(|_| {
+        ConfigError::invalid(
+            "tls_cert_file",
+            &cert_file,
+            "must be a PEM certificate chain that goes with the private key in TLS_KEY_FILE",
+        )
+    })?;
+
+    Ok(Some(tls))
+}
+
+/// Check that a file named in a setting can be read
+fn readable(key: &'static str, path: &str) -> Result<(), ConfigError> {
+    read_file(path)
+        .map(|_| ())
+        .map_err(|_| ConfigError::invalid(key, path, "must be a file that can be read"))
+}
+
+/// Get one of the theme's colors, which have to be hex colors
+fn theme_color(settings: &Settings, key: &'static str) -> Result<Option<String>, ConfigError> {
+    match settings.get(key) {
+        Some(ref color) if !Theme::is_color(color) => Err(ConfigError::invalid(
+            key,
+            color,
+            "must be a hex color, like #2a6496",
+        )),
+        color => Ok(color),
+    }
+}
+
+fn read_file(path: &str) -> io::Result<String> {
+    let mut contents = String::new();
+
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    Ok(contents)
+}
@@ -45,6 +45,12 @@ impl fmt::Display for EventError {
     }
 }
 
+impl EventError {
+    pub fn kind(&self) -> EventErrorKind {
+        *self.context.get_context()
+    }
+}
+
 impl From<EventErrorKind> for EventError {
     fn from(e: EventErrorKind) -> Self {
         EventError {
@@ -64,8 +70,12 @@ impl From<Context<EventErrorKind>> for EventError {
 pub enum EventErrorKind {
     #[fail(display = "Failed to create a connection to the database")]
     CreateConnection,
-    #[fail(display = "Failed to get environment variable")]
-    MissingEnv,
+    #[fail(display = "Timed out waiting for a database connection")]
+    Busy,
+    #[fail(display = "Failed to listen for changes to the database")]
+    Listen,
+    #[fail(display = "Failed to load configuration")]
+    Config,
     #[fail(display = "Failed to lookup data from db")]
     Lookup,
     #[fail(display = "Failed to prepare db query")]
@@ -88,25 +98,60 @@ pub enum EventErrorKind {
     Telegram,
     #[fail(display = "Failed to lookup telegram item")]
     TelegramLookup,
+    #[fail(display = "The bot is not an admin of the chat")]
+    NotAdmin,
+    #[fail(display = "The bot cannot post and edit messages in the channel")]
+    ChannelRights,
+    #[fail(display = "The chat could not be found")]
+    ChatNotFound,
+    #[fail(display = "The user has not started a private chat with the bot, or has blocked it")]
+    Blocked,
+    #[fail(display = "The message is too long for telegram")]
+    TooLong,
     #[fail(display = "Error on frontend")]
     Frontend,
     #[fail(display = "User is not allowed to perform that action")]
     Permissions,
+    #[fail(display = "User has been banned from creating events")]
+    Banned,
+    #[fail(display = "User is hosting too many upcoming events")]
+    Quota,
     #[fail(display = "Bad client secret")]
     Secret,
+    #[fail(display = "Timer failed")]
+    Timer,
+    #[fail(display = "Failed to deliver webhook")]
+    Webhook,
 }
 
-/// Provide an error type for missing keys when constructing the database URL
-#[derive(Clone, Copy, Debug, Eq, Fail, PartialEq)]
-pub enum DbConnError {
-    #[fail(display = "Database username not supplied")]
-    User,
-    #[fail(display = "Database password not supplied")]
-    Pass,
-    #[fail(display = "Database host not supplied")]
-    Host,
-    #[fail(display = "Database port not supplied")]
-    Port,
-    #[fail(display = "Database name not supplied")]
-    Name,
+/// Describe what's wrong with the bot's configuration
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+pub enum ConfigError {
+    #[fail(display = "{} is not set, add {} to the environment or {} to the config file",
+           _0, _1, _0)]
+    Missing(&'static str, String),
+    #[fail(display = "{} is set to '{}', but it {}", key, value, reason)]
+    Invalid {
+        key: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    #[fail(display = "Could not read the config file at {}", _0)]
+    ReadFile(String),
+    #[fail(display = "Could not parse the config file at {}: {}", path, reason)]
+    ParseFile { path: String, reason: String },
+}
+
+impl ConfigError {
+    pub fn missing(key: &'static str) -> Self {
+        ConfigError::Missing(key, key.to_uppercase())
+    }
+
+    pub fn invalid(key: &'static str, value: &str, reason: &'static str) -> Self {
+        ConfigError::Invalid {
+            key,
+            value: value.to_owned(),
+            reason,
+        }
+    }
 }
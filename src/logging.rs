@@ -0,0 +1,214 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module sets up logging for the bot.
+//!
+//! Log lines can be tagged with a `LogContext`, which records which actor wrote the line, and which
+//! update, chat, and event it was handling. Setting `LOG_FORMAT=json` prints every line as a JSON
+//! object so the logs can be collected by a log aggregator.
+
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::io::Write;
+
+use chrono::Utc;
+use dotenv::dotenv;
+use env_logger::Builder;
+use log::Record;
+use serde_json;
+use telebot::objects::Integer;
+
+thread_local! {
+    static CURRENT: RefCell<Option<LogContext>> = RefCell::new(None);
+}
+
+/// Log an error tagged with the given `LogContext`
+macro_rules! ctx_error {
+    ($ctx:expr, $($arg:tt)+) => {
+        $ctx.scope(|| error!($($arg)+))
+    };
+}
+
+/// Log a warning tagged with the given `LogContext`
+macro_rules! ctx_warn {
+    ($ctx:expr, $($arg:tt)+) => {
+        $ctx.scope(|| warn!($($arg)+))
+    };
+}
+
+/// Log a debug message tagged with the given `LogContext`
+macro_rules! ctx_debug {
+    ($ctx:expr, $($arg:tt)+) => {
+        $ctx.scope(|| debug!($($arg)+))
+    };
+}
+
+/// Build a `map_err` callback that logs the error, tagged with the given `LogContext`
+macro_rules! log_err {
+    ($ctx:expr, $msg:expr) => {{
+        let ctx = $ctx.clone();
+        move |e| ctx_error!(ctx, concat!($msg, ": {:?}"), e)
+    }};
+}
+
+/// `LogContext` describes what an actor was doing when it wrote a log line
+#[derive(Clone, Debug, Serialize)]
+pub struct LogContext {
+    actor: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    update_id: Option<Integer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chat_id: Option<Integer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<i32>,
+}
+
+impl LogContext {
+    pub fn new(actor: &'static str) -> Self {
+        LogContext {
+            actor,
+            update_id: None,
+            chat_id: None,
+            event_id: None,
+        }
+    }
+
+    pub fn update_id(mut self, update_id: Integer) -> Self {
+        self.update_id = Some(update_id);
+        self
+    }
+
+    pub fn chat_id(mut self, chat_id: Integer) -> Self {
+        self.chat_id = Some(chat_id);
+        self
+    }
+
+    pub fn event_id(mut self, event_id: i32) -> Self {
+        self.event_id = Some(event_id);
+        self
+    }
+
+    /// Run `f` with this context attached to any log lines it writes
+    ///
+    /// Loggers write synchronously, so the context only needs to live for the duration of the
+    /// call. The previous context is restored afterwards so scopes can nest.
+    pub fn scope<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let res = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        res
+    }
+}
+
+impl fmt::Display for LogContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "actor={}", self.actor)?;
+
+        if let Some(update_id) = self.update_id {
+            write!(f, " update_id={}", update_id)?;
+        }
+
+        if let Some(chat_id) = self.chat_id {
+            write!(f, " chat_id={}", chat_id)?;
+        }
+
+        if let Some(event_id) = self.event_id {
+            write!(f, " event_id={}", event_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    timestamp: String,
+    level: String,
+    target: &'a str,
+    message: String,
+    #[serde(flatten)]
+    context: Option<LogContext>,
+}
+
+fn current_context() -> Option<LogContext> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Set up the global logger
+///
+/// Filtering is configured with `RUST_LOG` as usual, defaulting to debug logs for the bot. Lines
+/// are printed as plain text unless `LOG_FORMAT` is set to `json`.
+pub fn init() {
+    dotenv().ok();
+
+    let mut builder = Builder::new();
+
+    match env::var("RUST_LOG") {
+        Ok(filters) => builder.parse(&filters),
+        Err(_) => builder.parse("event_bot=debug"),
+    };
+
+    let json = env::var("LOG_FORMAT")
+        .map(|format| format == "json")
+        .unwrap_or(false);
+
+    if json {
+        builder.format(|buf, record: &Record| {
+            let line = JsonLine {
+                timestamp: Utc::now().to_rfc3339(),
+                level: record.level().to_string(),
+                target: record.target(),
+                message: record.args().to_string(),
+                context: current_context(),
+            };
+
+            let line = serde_json::to_string(&line).map_err(|e| {
+                ::std::io::Error::new(::std::io::ErrorKind::Other, e.to_string())
+            })?;
+
+            writeln!(buf, "{}", line)
+        });
+    } else {
+        builder.format(|buf, record: &Record| match current_context() {
+            Some(context) => writeln!(
+                buf,
+                "{} {:<5} {}: {} [{}]",
+                Utc::now().to_rfc3339(),
+                record.level(),
+                record.target(),
+                record.args(),
+                context
+            ),
+            None => writeln!(
+                buf,
+                "{} {:<5} {}: {}",
+                Utc::now().to_rfc3339(),
+                record.level(),
+                record.target(),
+                record.args()
+            ),
+        });
+    }
+
+    builder.init();
+}
@@ -18,7 +18,7 @@
  */
 
 extern crate actix;
-extern crate base_x;
+extern crate actix_web;
 extern crate chrono;
 extern crate chrono_tz;
 extern crate dotenv;
@@ -29,85 +29,156 @@ extern crate failure;
 extern crate failure_derive;
 extern crate futures;
 extern crate futures_state_stream;
+extern crate hmac;
 #[macro_use]
 extern crate log;
-extern crate rand;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+extern crate sha2;
 extern crate telebot;
 extern crate time;
 extern crate tokio_core;
 extern crate tokio_postgres;
 extern crate tokio_reactor;
 extern crate tokio_timer;
+extern crate toml;
+
+#[macro_use]
+mod logging;
 
 mod actors;
+mod config;
 mod conn;
 mod error;
 mod models;
 mod util;
 
 use actix::{Actor, Addr, Arbiter, Supervisor, Syn, System, Unsync};
+use actors::alert_actor::AlertActor;
+use actors::db_broker::messages::ListenForChanges;
 use actors::db_broker::DbBroker;
 use actors::event_actor::EventActor;
-use actors::telegram_actor::messages::StartStreaming;
+use actors::fan_out_actor::FanOutActor;
+use actors::telegram_actor::messages::{SetTimer, StartStreaming};
 use actors::telegram_actor::TelegramActor;
 use actors::timer::Timer;
+use actors::timer_coordinator::TimerCoordinator;
 use actors::users_actor::UsersActor;
-use conn::prepare_database_connection;
-use dotenv::dotenv;
+use actors::webhook_actor::WebhookActor;
+use config::Config;
+use event_web::FormSettings;
+use failure::Fail;
 use telebot::RcBot;
 
-use std::env;
-
-const ENCODING_ALPHABET: &str = "abcdefghizklmnopqrstuvwxyz1234567890";
-
-fn bot_token() -> String {
-    dotenv().ok();
-
-    env::var("TELEGRAM_BOT_TOKEN").unwrap()
-}
-
-fn url() -> String {
-    dotenv().ok();
-
-    env::var("EVENT_URL").unwrap()
-}
+use std::process;
 
 fn main() {
-    env::set_var("RUST_LOG", "event_bot=debug");
-    env_logger::init();
+    logging::init();
 
     debug!("Running!");
 
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            match e.cause() {
+                Some(cause) => error!("{}: {}", e, cause),
+                None => error!("{}", e),
+            }
+            process::exit(1);
+        }
+    };
+
     let sys = System::new("tg-event-system");
     let _ = Arbiter::new("one");
 
-    let db_url = prepare_database_connection().unwrap();
+    let bot = RcBot::new(Arbiter::handle().clone(), config.bot_token()).timeout(30);
+
+    let alerts: Addr<Syn, _> = AlertActor::new(&config, bot.clone()).start();
 
-    let db_broker: Addr<Unsync, _> = DbBroker::new(db_url.clone(), 5).start();
+    let db_broker: Addr<Unsync, _> = DbBroker::new(&config, alerts.clone()).start();
 
-    let bot = RcBot::new(Arbiter::handle().clone(), &bot_token()).timeout(30);
+    let webhooks: Addr<Syn, _> = WebhookActor::new(db_broker.clone(), alerts.clone()).start();
 
+    let tg_config = config.clone();
+    let tg_webhooks = webhooks.clone();
+    let tg_alerts = alerts.clone();
     let telegram_actor: Addr<Syn, _> = Supervisor::start(move |_| {
-        let db_broker: Addr<Unsync, _> = DbBroker::new(db_url, 5).start();
+        let db_broker: Addr<Unsync, _> = DbBroker::new(&tg_config, tg_alerts.clone()).start();
+        let fan_out: Addr<Unsync, _> = FanOutActor::new(bot.clone(), db_broker.clone()).start();
 
         TelegramActor::new(
-            url(),
+            &tg_config,
             bot,
             db_broker.clone(),
             UsersActor::new(db_broker).start(),
+            tg_webhooks.clone(),
+            fan_out,
+            tg_alerts.clone(),
         )
     });
 
     telegram_actor.do_send(StartStreaming);
 
-    let timer: Addr<Syn, _> = Timer::new(db_broker.clone(), telegram_actor.clone()).start();
+    // Each Timer runs in its own arbiter, with its own database connections
+    let timers = (0..config.timer_shards())
+        .map(|_| {
+            let timer_config = config.clone();
+            let timer_tg = telegram_actor.clone();
+            let timer_alerts = alerts.clone();
+
+            Arbiter::start(move |_| {
+                let db_broker: Addr<Unsync, _> =
+                    DbBroker::new(&timer_config, timer_alerts).start();
+
+                Timer::new(db_broker, timer_tg)
+            })
+        })
+        .collect();
+
+    let timer: Addr<Syn, _> = TimerCoordinator::new(
+        db_broker.clone(),
+        telegram_actor.clone(),
+        timers,
+        config.timezone(),
+    ).start();
+
+    telegram_actor.do_send(SetTimer(timer.clone()));
+
+    // Hear about events changed by other instances of the bot
+    db_broker.do_send(ListenForChanges {
+        recipient: timer.clone().recipient(),
+    });
 
-    let sync_event_actor: Addr<Syn, _> = EventActor::new(telegram_actor, db_broker, timer).start();
-    event_web::start(sync_event_actor, "0.0.0.0:8000", None);
+    let sync_event_actor: Addr<Syn, _> =
+        EventActor::new(telegram_actor, db_broker, timer, webhooks).start();
+    let mut form_settings = FormSettings::new(config.timezone(), config.form_years())
+        .embeddable_by(config.embed_origins().to_vec())
+        .served_at(config.event_url())
+        .theme(config.theme().clone())
+        .report_only(config.csp_report_only())
+        .trust_proxies(config.trusted_proxies().to_vec());
+
+    if let Some(robots) = config.robots() {
+        form_settings = form_settings.robots(robots.to_owned());
+    }
+
+    let started = event_web::start(
+        sync_event_actor,
+        config.bind_address(),
+        None,
+        form_settings,
+        config.tls().cloned(),
+    );
+
+    if let Err(e) = started {
+        match e.cause() {
+            Some(cause) => error!("{}: {}", e, cause),
+            None => error!("{}", e),
+        }
+        process::exit(1);
+    }
 
     sys.run();
 }
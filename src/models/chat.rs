@@ -39,6 +39,7 @@ use util::*;
 /// - id SERIAL
 /// - chat_id BIGINT
 /// - system_id INTEGER REFERENCES chat_systems
+/// - title TEXT
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Chat {
     id: i32,
@@ -90,6 +91,81 @@ impl Chat {
                 }
             })
     }
+
+    /// Record the title of the chat with the given Telegram ID
+    ///
+    /// Nothing is changed if the chat isn't linked to a channel
+    pub fn set_title(
+        chat_id: Integer,
+        title: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chats SET title = $2 WHERE chat_id = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&chat_id, &title])
+                    .map_err(update_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Delete the chat with the given Telegram ID, along with who's known to be in it
+    pub fn delete_by_chat_id(
+        chat_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "DELETE FROM chats AS ch WHERE ch.chat_id = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&chat_id])
+                    .map_err(delete_error)
+            })
+            .and_then(|(count, connection)| {
+                if count > 0 {
+                    Ok(((), connection))
+                } else {
+                    Err((EventErrorKind::Delete.into(), connection))
+                }
+            })
+    }
+
+    /// Get the chats the user with the given Telegram ID is known to be in
+    pub fn by_user_id(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Chat>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT ch.id, ch.chat_id
+                    FROM chats AS ch
+                    INNER JOIN user_chats AS uc ON uc.chats_id = ch.id
+                    INNER JOIN users AS usr ON usr.id = uc.users_id
+                    WHERE usr.user_id = $1
+                    ORDER BY ch.id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&user_id])
+                    .map(|row| Chat {
+                        id: row.get(0),
+                        chat_id: row.get(1),
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
 }
 
 /// This struct is used when inserting chats into the database
@@ -22,32 +22,180 @@
 //! Chat Systems are used to group a series of chats together with an events channel, to allow
 //! members of those chats to create events for the channel
 
-use futures::Future;
+use chrono::{DateTime, Weekday};
+use chrono_tz::Tz;
+use futures::future::Either;
+use futures::{Future, IntoFuture};
 use futures_state_stream::StateStream;
 use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
+use tokio_postgres::types::ToSql;
 use tokio_postgres::Connection;
 
 use super::chat::Chat;
+use super::features::Features;
 use error::{EventError, EventErrorKind};
+use event_web::locale::{Language, TimeFormat};
 use util::*;
 
+/// The columns that make up a `ChatSystem`, in the order `ChatSystem::from_row` expects them
+///
+/// Features come from their own table, falling back to the defaults for chat systems that haven't
+/// changed them
+const SYSTEM_COLUMNS: &str = concat!(
+    "sys.id, sys.events_channel, sys.protect_content, sys.digest_day, sys.pin_digest, sys.pin_announcements, sys.greeting, sys.greeting_message_id, sys.announcement_template, sys.reminder_template, sys.ended_template, sys.moderated, COALESCE(sys.channel_title, sys.channel_username), sys.language, sys.twelve_hour, ",
+    "COALESCE((SELECT f.rsvp_enabled FROM chat_system_features AS f WHERE f.chat_systems_id = sys.id), TRUE), ",
+    "COALESCE((SELECT f.digest_enabled FROM chat_system_features AS f WHERE f.chat_systems_id = sys.id), TRUE), ",
+    "COALESCE((SELECT f.public_page_enabled FROM chat_system_features AS f WHERE f.chat_systems_id = sys.id), TRUE)"
+);
+
+/// The number of columns in `SYSTEM_COLUMNS`, used to find the columns that follow them
+const SYSTEM_COLUMN_COUNT: usize = 18;
+
 /// ChatSystem represents a series of linked chats
 ///
 /// `events_channel` is the ID of the channel where full announcements are made
 /// `announce_chats` is as set of IDs where the bot should notify of announcements.
+/// `protect_content` is whether announcements should be protected from forwarding and saving
+/// `digest_day` is the day of the week the weekly digest is posted on, if it's enabled
+/// `pin_digest` is whether the weekly digest should be pinned in the events channel
+/// `pin_announcements` is whether new event announcements should be pinned in the events channel
+/// `greeting` is the admins' introduction to the channel, shown at the top of the pinned greeting
+/// `greeting_message_id` is the ID of the greeting message the bot keeps up to date
+/// `announcement_template`, `reminder_template`, and `ended_template` replace the bot's own
+/// messages for the channel, if the admins have set them
+/// `moderated` is whether events from hosts who aren't admins of the channel wait for an admin to
+/// approve them before they're announced
+/// `title` is the channel's title, or its username if it has no title, as of the last time the
+/// bot saw it change
+/// `language` is the language the bot speaks in the channel
+/// `time_format` is whether times are shown in the channel with 12 or 24 hours
+/// `features` are the parts of the bot the channel has turned on
 ///
 /// This is represented in the database as
 ///
 /// ### Relations:
 /// - chat_systems has_many chats (foreign_key on chats)
+/// - chat_systems has_one chat_system_features (foreign_key on chat_system_features)
+/// - chat_systems has_many host_bans (foreign_key on host_bans)
+/// - chat_systems has_many pinned_announcements (foreign_key on pinned_announcements)
 ///
 /// ### Columns:
 /// - id SERIAL
 /// - events_channel BIGINT
+/// - protect_content BOOLEAN
+/// - digest_day SMALLINT (days from monday)
+/// - pin_digest BOOLEAN
+/// - last_digest_at TIMESTAMP WITH TIME ZONE
+/// - pin_announcements BOOLEAN
+/// - api_secret TEXT (SHA-256 hash of the channel's API token)
+/// - greeting TEXT
+/// - greeting_message_id BIGINT
+/// - announcement_template TEXT
+/// - reminder_template TEXT
+/// - ended_template TEXT
+/// - listed BOOLEAN
+/// - channel_title TEXT
+/// - channel_username TEXT
+/// - boost_threshold INTEGER
+/// - moderated BOOLEAN
+/// - language TEXT (a code like `es`)
+/// - twelve_hour BOOLEAN
+/// - calendar_secret TEXT (SHA-256 hash of the channel's calendar token)
+/// - pinned_digest_id BIGINT (the message ID of the pinned weekly digest)
+/// - read_secret TEXT (SHA-256 hash of the channel's read-only API token)
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ChatSystem {
     id: i32,
     events_channel: Integer,
+    protect_content: bool,
+    digest_day: Option<Weekday>,
+    pin_digest: bool,
+    pin_announcements: bool,
+    greeting: Option<String>,
+    greeting_message_id: Option<Integer>,
+    announcement_template: Option<String>,
+    reminder_template: Option<String>,
+    ended_template: Option<String>,
+    moderated: bool,
+    title: Option<String>,
+    language: Language,
+    time_format: TimeFormat,
+    features: Features,
+}
+
+/// The messages a channel's admins can replace with their own templates
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TemplateKind {
+    /// The announcement posted to the channel when an event is created
+    Announcement,
+    /// The reminder sent shortly before an event starts
+    Reminder,
+    /// The message sent when an event ends
+    Ended,
+}
+
+/// `BannedUser` is who a channel's admins picked to ban or unban
+///
+/// Bans are kept by Telegram user ID. Usernames are only known for users the bot has seen in the
+/// Chat System's chats, so anyone else has to be picked by their ID.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BannedUser {
+    Username(String),
+    UserId(Integer),
+}
+
+impl BannedUser {
+    /// Read a Telegram user ID, or a username with or without its `@`, from a command
+    pub fn parse(arg: &str) -> Option<Self> {
+        let arg = arg.trim();
+
+        if arg.is_empty() {
+            None
+        } else if let Ok(user_id) = arg.parse::<Integer>() {
+            Some(BannedUser::UserId(user_id))
+        } else {
+            Some(BannedUser::Username(arg.trim_left_matches('@').to_owned()))
+        }
+    }
+}
+
+/// `Listing` is how a channel appears in the Web UI's directory of events
+///
+/// `title` and `username` are the channel's, as they were when the bot last saw them change
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Listing {
+    system_id: i32,
+    title: Option<String>,
+    username: Option<String>,
+}
+
+impl Listing {
+    /// Get the ID of the listed Chat System
+    pub fn system_id(&self) -> i32 {
+        self.system_id
+    }
+
+    /// Get the channel's title
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the channel's public username, if it has one
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_ref().map(|s| s.as_str())
+    }
+}
+
+impl TemplateKind {
+    /// The column the template is stored in
+    fn column(&self) -> &'static str {
+        match *self {
+            TemplateKind::Announcement => "announcement_template",
+            TemplateKind::Reminder => "reminder_template",
+            TemplateKind::Ended => "ended_template",
+        }
+    }
 }
 
 impl ChatSystem {
@@ -61,6 +209,97 @@ impl ChatSystem {
         self.events_channel
     }
 
+    /// Check whether announcements for the Chat System should be protected from forwarding
+    pub fn protect_content(&self) -> bool {
+        self.protect_content
+    }
+
+    /// Get the day of the week the weekly digest is posted on, if it's enabled
+    pub fn digest_day(&self) -> Option<Weekday> {
+        self.digest_day
+    }
+
+    /// Check whether the weekly digest should be pinned in the Chat System's channel
+    pub fn pin_digest(&self) -> bool {
+        self.pin_digest
+    }
+
+    /// Check whether new event announcements should be pinned in the Chat System's channel
+    pub fn pin_announcements(&self) -> bool {
+        self.pin_announcements
+    }
+
+    /// Get the admins' introduction to the Chat System's channel, if they've set one
+    pub fn greeting(&self) -> Option<&str> {
+        self.greeting.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the ID of the greeting message in the Chat System's channel, if one has been posted
+    pub fn greeting_message_id(&self) -> Option<Integer> {
+        self.greeting_message_id
+    }
+
+    /// Get the admins' template for the given message, if they've set one
+    pub fn template(&self, kind: TemplateKind) -> Option<&str> {
+        let template = match kind {
+            TemplateKind::Announcement => &self.announcement_template,
+            TemplateKind::Reminder => &self.reminder_template,
+            TemplateKind::Ended => &self.ended_template,
+        };
+
+        template.as_ref().map(|s| s.as_str())
+    }
+
+    /// Check whether events from hosts who aren't admins of the Chat System's channel need to be
+    /// approved before they're announced
+    pub fn moderated(&self) -> bool {
+        self.moderated
+    }
+
+    /// Get the channel's title, if the bot knows it
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the language the bot speaks in the channel
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Get how times are shown in the channel
+    pub fn time_format(&self) -> TimeFormat {
+        self.time_format
+    }
+
+    /// Get the features the Chat System has turned on
+    pub fn features(&self) -> Features {
+        self.features
+    }
+
+    /// Build a `ChatSystem` from a row selected with `SYSTEM_COLUMNS`
+    fn from_row(row: &Row) -> Self {
+        ChatSystem {
+            id: row.get(0),
+            events_channel: row.get(1),
+            protect_content: row.get(2),
+            digest_day: row.get::<_, Option<i16>>(3).and_then(weekday_from_i16),
+            pin_digest: row.get(4),
+            pin_announcements: row.get(5),
+            greeting: row.get(6),
+            greeting_message_id: row.get(7),
+            announcement_template: row.get(8),
+            reminder_template: row.get(9),
+            ended_template: row.get(10),
+            moderated: row.get(11),
+            title: row.get(12),
+            language: row.get::<_, Option<String>>(13)
+                .and_then(|code| Language::from_code(&code))
+                .unwrap_or_default(),
+            time_format: TimeFormat::from_twelve_hour(row.get(14)),
+            features: Features::from_row(row, 15),
+        }
+    }
+
     /// Create a `ChatSystem` given a Telegram Chat ID
     pub fn create(
         events_channel: Integer,
@@ -78,6 +317,20 @@ impl ChatSystem {
                     .map(move |row| ChatSystem {
                         id: row.get(0),
                         events_channel: events_channel,
+                        protect_content: false,
+                        digest_day: Some(Weekday::Mon),
+                        pin_digest: false,
+                        pin_announcements: false,
+                        greeting: None,
+                        greeting_message_id: None,
+                        announcement_template: None,
+                        reminder_template: None,
+                        ended_template: None,
+                        moderated: false,
+                        title: None,
+                        language: Language::default(),
+                        time_format: TimeFormat::default(),
+                        features: Features::default(),
                     })
                     .collect()
                     .map_err(insert_error)
@@ -96,21 +349,21 @@ impl ChatSystem {
         id: i32,
         connection: Connection,
     ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT sys.id, sys.events_channel
-                    FROM chat_systems AS sys
-                    WHERE sys.id = $1";
+        let sql = format!(
+            "SELECT {}
+                FROM chat_systems AS sys
+                WHERE sys.id = $1",
+            SYSTEM_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&id])
-                    .map(|row| ChatSystem {
-                        id: row.get(0),
-                        events_channel: row.get(1),
-                    })
+                    .map(|row| ChatSystem::from_row(&row))
                     .collect()
                     .map_err(lookup_error)
                     .and_then(|(mut chat_systems, connection)| {
@@ -128,25 +381,25 @@ impl ChatSystem {
         connection: Connection,
     ) -> impl Future<Item = ((ChatSystem, Vec<Integer>), Connection), Error = (EventError, Connection)>
     {
-        let sql = "SELECT sys.id, sys.events_channel, ch.chat_id
-                    FROM chat_systems AS sys
-                    INNER JOIN chats AS ch ON ch.system_id = sys.id
-                    WHERE sys.id = $1";
+        let sql = format!(
+            "SELECT {}, ch.chat_id
+                FROM chat_systems AS sys
+                INNER JOIN chats AS ch ON ch.system_id = sys.id
+                WHERE sys.id = $1",
+            SYSTEM_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&id])
                     .map(|row| {
-                        let sys = ChatSystem {
-                            id: row.get(0),
-                            events_channel: row.get(1),
-                        };
+                        let sys = ChatSystem::from_row(&row);
 
-                        let chat_id = row.get(2);
+                        let chat_id = row.get(SYSTEM_COLUMN_COUNT);
 
                         (sys, chat_id)
                     })
@@ -197,21 +450,21 @@ impl ChatSystem {
         channel_id: Integer,
         connection: Connection,
     ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT sys.id
-                    FROM chat_systems AS sys
-                    WHERE sys.events_channel = $1";
+        let sql = format!(
+            "SELECT {}
+                FROM chat_systems AS sys
+                WHERE sys.events_channel = $1",
+            SYSTEM_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&channel_id])
-                    .map(move |row| ChatSystem {
-                        id: row.get(0),
-                        events_channel: channel_id,
-                    })
+                    .map(|row| ChatSystem::from_row(&row))
                     .collect()
                     .map_err(lookup_error)
             })
@@ -224,33 +477,957 @@ impl ChatSystem {
             })
     }
 
+    /// Select the chat systems of each of the given channels that's been initialized
+    pub fn by_channel_ids(
+        channel_ids: Vec<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<ChatSystem>, Connection), Error = (EventError, Connection)> {
+        if channel_ids.is_empty() {
+            return Either::B(Ok((Vec::new(), connection)).into_future());
+        }
+
+        let values = channel_ids
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("${}", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let sql = format!(
+            "SELECT {}
+                FROM chat_systems AS sys
+                WHERE sys.events_channel IN ({})",
+            SYSTEM_COLUMNS, values
+        );
+        debug!("{}", sql);
+
+        Either::A(
+            connection
+                .prepare(&sql)
+                .map_err(prepare_error)
+                .and_then(move |(s, connection)| {
+                    let sql_args: Vec<_> = channel_ids.iter().map(|id| id as &ToSql).collect();
+
+                    connection
+                        .query(&s, sql_args.as_slice())
+                        .map(|row| ChatSystem::from_row(&row))
+                        .collect()
+                        .map_err(lookup_error)
+                }),
+        )
+    }
+
     /// Get a collection of every `ChatSystem` with its associated `Chats` from the database
     pub fn all_with_chats(
         connection: Connection,
     ) -> impl Future<Item = (Vec<(ChatSystem, Chat)>, Connection), Error = (EventError, Connection)>
     {
-        let sql = "SELECT sys.id, sys.events_channel, ch.id, ch.chat_id
-            FROM chats AS ch
-            INNER JOIN chat_systems AS sys ON ch.system_id = sys.id";
+        let sql = format!(
+            "SELECT {}, ch.id, ch.chat_id
+                FROM chats AS ch
+                INNER JOIN chat_systems AS sys ON ch.system_id = sys.id",
+            SYSTEM_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[])
                     .map(|row| {
                         (
-                            ChatSystem {
-                                id: row.get(0),
-                                events_channel: row.get(1),
-                            },
-                            Chat::from_parts(row.get(2), row.get(3)),
+                            ChatSystem::from_row(&row),
+                            Chat::from_parts(
+                                row.get(SYSTEM_COLUMN_COUNT),
+                                row.get(SYSTEM_COLUMN_COUNT + 1),
+                            ),
                         )
                     })
                     .collect()
                     .map_err(lookup_error)
             })
     }
+
+    /// Toggle whether announcements are protected for the `ChatSystem` with the given channel,
+    /// returning the new setting
+    pub fn toggle_protect_content(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_setting("protect_content", channel_id, connection)
+    }
+
+    /// Toggle whether announcements are pinned for the `ChatSystem` with the given channel,
+    /// returning the new setting
+    pub fn toggle_pin_announcements(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_setting("pin_announcements", channel_id, connection)
+    }
+
+    /// Toggle whether the `ChatSystem` with the given channel is listed in the directory,
+    /// returning the new setting
+    ///
+    /// The channel's title and username are recorded too, so the directory shows them as they
+    /// are now.
+    pub fn toggle_listed(
+        channel_id: Integer,
+        title: Option<String>,
+        username: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems
+                    SET listed = NOT listed, channel_title = $2, channel_username = $3
+                    WHERE events_channel = $1
+                    RETURNING listed";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id, &title, &username])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut settings, connection): (Vec<bool>, _)| {
+                if settings.len() > 0 {
+                    Ok((settings.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Record the title of the channel with the given Telegram ID, and its username if it's known
+    ///
+    /// Nothing is changed if the channel hasn't been initialized
+    pub fn set_channel_title(
+        channel_id: Integer,
+        title: Option<String>,
+        username: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems
+                    SET channel_title = $2, channel_username = COALESCE($3, channel_username)
+                    WHERE events_channel = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&channel_id, &title, &username])
+                    .map_err(update_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Get every channel listed in the directory, ordered by title
+    ///
+    /// Channels that turned off public pages are left out, even if they're listed.
+    pub fn listings(
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Listing>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT sys.id, sys.channel_title, sys.channel_username
+                    FROM chat_systems AS sys
+                    WHERE sys.listed = TRUE AND NOT EXISTS (
+                        SELECT 1 FROM chat_system_features AS f
+                        WHERE f.chat_systems_id = sys.id AND f.public_page_enabled = FALSE
+                    )
+                    ORDER BY LOWER(sys.channel_title), sys.id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[])
+                    .map(|row| Listing {
+                        system_id: row.get(0),
+                        title: row.get(1),
+                        username: row.get(2),
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+
+    /// Set the day of the week the weekly digest is posted on for the `ChatSystem` with the given
+    /// channel. `None` disables the digest.
+    pub fn set_digest_day(
+        channel_id: Integer,
+        digest_day: Option<Weekday>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET digest_day = $1 WHERE events_channel = $2";
+        debug!("{}", sql);
+
+        let digest_day = digest_day.map(|day| day.num_days_from_monday() as i16);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&digest_day, &channel_id])
+                    .map_err(update_error)
+            })
+            .and_then(|(count, connection)| {
+                if count > 0 {
+                    Ok(((), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Stop the given user from creating events for the `ChatSystem` with the given channel,
+    /// returning whether they were banned
+    ///
+    /// Users picked by username have to be in one of the Chat System's chats, and anyone can be
+    /// picked by their Telegram ID. Banning a user who's already banned does nothing.
+    pub fn ban_host(
+        channel_id: Integer,
+        user: BannedUser,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = match user {
+            BannedUser::UserId(_) => {
+                "INSERT INTO host_bans (chat_systems_id, user_id)
+                    SELECT sys.id, $2
+                    FROM chat_systems AS sys
+                    WHERE sys.events_channel = $1
+                    ON CONFLICT (chat_systems_id, user_id) DO NOTHING"
+            }
+            BannedUser::Username(_) => {
+                "INSERT INTO host_bans (chat_systems_id, user_id)
+                    SELECT DISTINCT sys.id, usr.user_id
+                    FROM chat_systems AS sys
+                    INNER JOIN chats AS ch ON ch.system_id = sys.id
+                    INNER JOIN user_chats AS uc ON uc.chats_id = ch.id
+                    INNER JOIN users AS usr ON usr.id = uc.users_id
+                    WHERE sys.events_channel = $1 AND LOWER(usr.username) = LOWER($2)
+                    ON CONFLICT (chat_systems_id, user_id) DO NOTHING"
+            }
+        };
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                let user_arg = match user {
+                    BannedUser::UserId(ref user_id) => user_id as &ToSql,
+                    BannedUser::Username(ref username) => username as &ToSql,
+                };
+
+                connection
+                    .execute(&s, &[&channel_id, user_arg])
+                    .map_err(insert_error)
+            })
+            .map(|(count, connection)| (count > 0, connection))
+    }
+
+    /// Let the given user create events for the `ChatSystem` with the given channel again,
+    /// returning whether they had been banned
+    pub fn unban_host(
+        channel_id: Integer,
+        user: BannedUser,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = match user {
+            BannedUser::UserId(_) => {
+                "DELETE FROM host_bans AS hb
+                    USING chat_systems AS sys
+                    WHERE hb.chat_systems_id = sys.id
+                        AND sys.events_channel = $1 AND hb.user_id = $2"
+            }
+            BannedUser::Username(_) => {
+                "DELETE FROM host_bans AS hb
+                    USING chat_systems AS sys, users AS usr
+                    WHERE hb.chat_systems_id = sys.id AND hb.user_id = usr.user_id
+                        AND sys.events_channel = $1 AND LOWER(usr.username) = LOWER($2)"
+            }
+        };
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                let user_arg = match user {
+                    BannedUser::UserId(ref user_id) => user_id as &ToSql,
+                    BannedUser::Username(ref username) => username as &ToSql,
+                };
+
+                connection
+                    .execute(&s, &[&channel_id, user_arg])
+                    .map_err(delete_error)
+            })
+            .map(|(count, connection)| (count > 0, connection))
+    }
+
+    /// Check whether the user with the given Telegram ID has been banned from creating events for
+    /// the `ChatSystem` with the given ID
+    pub fn is_banned(
+        system_id: i32,
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT id FROM host_bans WHERE chat_systems_id = $1 AND user_id = $2";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id, &user_id])
+                    .map(|row| row.get::<_, i32>(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(bans, connection)| (bans.len() > 0, connection))
+    }
+
+    /// Set how few RSVPs an event in the `ChatSystem` with the given channel needs two days before
+    /// it starts to be announced again. `None` turns boosts off for events that don't set their
+    /// own threshold.
+    pub fn set_boost_threshold(
+        channel_id: Integer,
+        boost_threshold: Option<i32>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET boost_threshold = $1 WHERE events_channel = $2";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&boost_threshold, &channel_id])
+                    .map_err(update_error)
+            })
+            .and_then(|(count, connection)| {
+                if count > 0 {
+                    Ok(((), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Toggle whether events from hosts who aren't admins need to be approved for the
+    /// `ChatSystem` with the given channel, returning the new setting
+    pub fn toggle_moderated(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_setting("moderated", channel_id, connection)
+    }
+
+    /// Toggle whether the weekly digest is pinned for the `ChatSystem` with the given channel,
+    /// returning the new setting
+    pub fn toggle_pin_digest(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        ChatSystem::toggle_setting("pin_digest", channel_id, connection)
+    }
+
+    /// Flip the boolean `column` for the `ChatSystem` with the given channel, returning the new
+    /// value
+    ///
+    /// `column` is formatted into the query, so it must never come from user input
+    fn toggle_setting(
+        column: &'static str,
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems
+                SET {0} = NOT {0}
+                WHERE events_channel = $1
+                RETURNING {0}",
+            column
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut settings, connection): (Vec<bool>, _)| {
+                if settings.len() > 0 {
+                    Ok((settings.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Set the greeting for the `ChatSystem` with the given channel, returning the updated
+    /// `ChatSystem`. `None` removes the greeting.
+    pub fn set_greeting(
+        channel_id: Integer,
+        greeting: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems AS sys
+                SET greeting = $1
+                WHERE sys.events_channel = $2
+                RETURNING {}",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&greeting, &channel_id])
+                    .map(|row| ChatSystem::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut systems, connection)| {
+                if systems.len() > 0 {
+                    Ok((systems.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Set the language for the `ChatSystem` with the given channel, returning the updated
+    /// `ChatSystem`
+    pub fn set_language(
+        channel_id: Integer,
+        language: Language,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems AS sys
+                SET language = $1
+                WHERE sys.events_channel = $2
+                RETURNING {}",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let code = language.code();
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&code, &channel_id])
+                    .map(|row| ChatSystem::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut systems, connection)| {
+                if systems.len() > 0 {
+                    Ok((systems.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Get the language the bot replies in for the chat with the given ID, if one has been picked
+    ///
+    /// Users get the language they picked for themselves, while channels and the groups linked to
+    /// them get the channel's language. Telegram gives users positive IDs and groups and channels
+    /// negative ones, so the ID only matches one of them.
+    pub fn language_for_chat(
+        chat_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Language>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT pref.language
+                    FROM user_preferences AS pref
+                    INNER JOIN users AS usr ON usr.id = pref.users_id
+                    WHERE usr.user_id = $1 AND pref.language IS NOT NULL
+                    UNION ALL
+                    SELECT sys.language
+                    FROM chat_systems AS sys
+                    LEFT OUTER JOIN chats AS ch ON ch.system_id = sys.id
+                    WHERE (sys.events_channel = $1 OR ch.chat_id = $1)
+                        AND sys.language IS NOT NULL
+                    LIMIT 1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&chat_id])
+                    .map(|row| row.get::<_, String>(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(codes, connection)| {
+                let language = codes.first().and_then(|code| Language::from_code(code));
+
+                (language, connection)
+            })
+    }
+
+    /// Set the time format for the `ChatSystem` with the given channel, returning the updated
+    /// `ChatSystem`
+    pub fn set_time_format(
+        channel_id: Integer,
+        time_format: TimeFormat,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems AS sys
+                SET twelve_hour = $1
+                WHERE sys.events_channel = $2
+                RETURNING {}",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let twelve_hour = time_format.is_twelve_hour();
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&twelve_hour, &channel_id])
+                    .map(|row| ChatSystem::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut systems, connection)| {
+                if systems.len() > 0 {
+                    Ok((systems.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Set one of the message templates for the `ChatSystem` with the given channel, returning the
+    /// updated `ChatSystem`. `None` goes back to the bot's own message.
+    pub fn set_template(
+        channel_id: Integer,
+        kind: TemplateKind,
+        template: Option<String>,
+        connection: Connection,
+    ) -> impl Future<Item = (ChatSystem, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems AS sys
+                SET {} = $1
+                WHERE sys.events_channel = $2
+                RETURNING {}",
+            kind.column(),
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&template, &channel_id])
+                    .map(|row| ChatSystem::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut systems, connection)| {
+                if systems.len() > 0 {
+                    Ok((systems.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Record the ID of the greeting message in the `ChatSystem`'s channel
+    pub fn set_greeting_message(
+        id: i32,
+        message_id: Option<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET greeting_message_id = $1 WHERE id = $2";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&message_id, &id])
+                    .map_err(update_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Store the hashed secret of a new API token for the `ChatSystem` with the given channel,
+    /// replacing any previous token, and return the `ChatSystem`'s ID
+    pub fn set_api_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET api_secret = $1 WHERE events_channel = $2 RETURNING id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&secret, &channel_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut ids, connection): (Vec<i32>, _)| {
+                if ids.len() > 0 {
+                    Ok((ids.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Get the `ChatSystem` with the given ID along with the hashed secret of its API token, if
+    /// one has been generated
+    pub fn by_id_with_api_secret(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        let sql = format!(
+            "SELECT {}, sys.api_secret
+                FROM chat_systems AS sys
+                WHERE sys.id = $1",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id])
+                    .map(|row| (ChatSystem::from_row(&row), row.get(SYSTEM_COLUMN_COUNT)))
+                    .collect()
+                    .map_err(lookup_error)
+                    .and_then(|(mut results, connection)| {
+                        if results.len() == 1 {
+                            Ok((results.remove(0), connection))
+                        } else {
+                            Err((EventErrorKind::Lookup.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Store the hashed secret of a new read-only API token for the `ChatSystem` with the given
+    /// channel, replacing any previous token, and return the `ChatSystem`'s ID
+    pub fn set_read_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET read_secret = $1 WHERE events_channel = $2 RETURNING id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&secret, &channel_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut ids, connection): (Vec<i32>, _)| {
+                if ids.len() > 0 {
+                    Ok((ids.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Get the `ChatSystem` with the given ID along with the hashed secret of its read-only API
+    /// token, if one has been generated
+    pub fn by_id_with_read_secret(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        let sql = format!(
+            "SELECT {}, sys.read_secret
+                FROM chat_systems AS sys
+                WHERE sys.id = $1",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id])
+                    .map(|row| (ChatSystem::from_row(&row), row.get(SYSTEM_COLUMN_COUNT)))
+                    .collect()
+                    .map_err(lookup_error)
+                    .and_then(|(mut results, connection)| {
+                        if results.len() == 1 {
+                            Ok((results.remove(0), connection))
+                        } else {
+                            Err((EventErrorKind::Lookup.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Store the hashed secret of a new calendar token for the `ChatSystem` with the given
+    /// channel, replacing any previous token, and return the `ChatSystem`'s ID
+    pub fn set_calendar_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (i32, Connection), Error = (EventError, Connection)> {
+        let sql =
+            "UPDATE chat_systems SET calendar_secret = $1 WHERE events_channel = $2 RETURNING id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&secret, &channel_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut ids, connection): (Vec<i32>, _)| {
+                if ids.len() > 0 {
+                    Ok((ids.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Get the `ChatSystem` with the given ID along with the hashed secret of its calendar token,
+    /// if one has been generated
+    pub fn by_id_with_calendar_secret(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((ChatSystem, Option<String>), Connection), Error = (EventError, Connection)>
+    {
+        let sql = format!(
+            "SELECT {}, sys.calendar_secret
+                FROM chat_systems AS sys
+                WHERE sys.id = $1",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id])
+                    .map(|row| (ChatSystem::from_row(&row), row.get(SYSTEM_COLUMN_COUNT)))
+                    .collect()
+                    .map_err(lookup_error)
+                    .and_then(|(mut results, connection)| {
+                        if results.len() == 1 {
+                            Ok((results.remove(0), connection))
+                        } else {
+                            Err((EventErrorKind::Lookup.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Record that the announcement with ID `message_id` for the event with ID `event_id` is
+    /// pinned in the `ChatSystem`'s channel
+    ///
+    /// Channels can have several announcements pinned at once, so each one is kept until its
+    /// event ends. The event isn't a foreign key, since the Timer deletes events before their
+    /// announcements are unpinned.
+    pub fn set_pinned_event(
+        id: i32,
+        event_id: i32,
+        message_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO pinned_announcements (events_id, chat_systems_id, message_id)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (events_id) DO UPDATE SET message_id = EXCLUDED.message_id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&event_id, &id, &message_id])
+                    .map_err(insert_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Forget the pinned announcement for the event with ID `event_id`, returning the
+    /// announcement's ID if it was pinned
+    pub fn clear_pinned_event(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        let sql = "DELETE FROM pinned_announcements WHERE events_id = $1 RETURNING message_id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&event_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(delete_error)
+            })
+            .map(|(message_ids, connection): (Vec<Integer>, _)| {
+                (message_ids.into_iter().next(), connection)
+            })
+    }
+
+    /// Record which weekly digest is pinned in the `ChatSystem`'s channel, returning the one that
+    /// was pinned before so it can be unpinned. `None` means no digest is pinned.
+    pub fn set_pinned_digest(
+        id: i32,
+        message_id: Option<Integer>,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        // The joined row still holds the value from before the update
+        let sql = "UPDATE chat_systems AS sys
+                    SET pinned_digest_id = $2
+                    FROM chat_systems AS old
+                    WHERE sys.id = $1 AND old.id = sys.id
+                    RETURNING old.pinned_digest_id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id, &message_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut previous, connection): (Vec<Option<Integer>>, _)| {
+                if previous.len() > 0 {
+                    Ok((previous.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Let the `ChatSystem`'s digest be claimed again, after it couldn't be posted
+    pub fn release_digest(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE chat_systems SET last_digest_at = NULL WHERE id = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| connection.execute(&s, &[&id]).map_err(update_error))
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Get every `ChatSystem` whose digest is posted on `digest_day` and hasn't been posted since
+    /// `since`, marking their digests as posted at `now`
+    ///
+    /// Marking the digests in the same query keeps a digest from being posted twice in one day.
+    /// Chat systems that turned the digest off are skipped.
+    pub fn claim_digests(
+        digest_day: Weekday,
+        since: DateTime<Tz>,
+        now: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<ChatSystem>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "UPDATE chat_systems AS sys
+                SET last_digest_at = $3
+                WHERE sys.digest_day = $1 AND (
+                    sys.last_digest_at IS NULL OR sys.last_digest_at < $2
+                ) AND NOT EXISTS (
+                    SELECT 1 FROM chat_system_features AS f
+                    WHERE f.chat_systems_id = sys.id AND f.digest_enabled = FALSE
+                )
+                RETURNING {}",
+            SYSTEM_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let digest_day = digest_day.num_days_from_monday() as i16;
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&digest_day, &since, &now])
+                    .map(|row| ChatSystem::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+    }
+}
+
+fn weekday_from_i16(day: i16) -> Option<Weekday> {
+    let weekday = match day {
+        0 => Weekday::Mon,
+        1 => Weekday::Tue,
+        2 => Weekday::Wed,
+        3 => Weekday::Thu,
+        4 => Weekday::Fri,
+        5 => Weekday::Sat,
+        6 => Weekday::Sun,
+        _ => return None,
+    };
+
+    Some(weekday)
 }
@@ -19,6 +19,8 @@
 
 //! This module defines the `EditEventLink` type, and associated types and functions
 
+use chrono::offset::Utc;
+use chrono::DateTime;
 use futures::Future;
 use futures_state_stream::StateStream;
 use tokio_postgres::Connection;
@@ -32,7 +34,8 @@ use util::*;
 /// `user_id` is the database ID of the user who asked for this link
 /// `system_id` is the database ID of the system the event is associated with
 /// `event_id` is the database ID of the event this link is associated with
-/// `secret` is a bcrypted secret used to verify that an edited event is valid
+/// `secret` is a hashed secret used to verify that an edited event is valid
+/// `expires_at` is the time after which the link can no longer be used
 ///
 /// ### Relations:
 /// - edit_event_links belongs_to users (foreign_key on edit_event_links)
@@ -45,6 +48,7 @@ use util::*;
 ///  - system_id INTEGER REFERENCES chat_systems
 ///  - event_id INTEGER REFERENCES events
 ///  - secret - TEXT
+///  - expires_at - TIMESTAMP WITH TIME ZONE
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EditEventLink {
     id: i32,
@@ -52,6 +56,7 @@ pub struct EditEventLink {
     system_id: i32,
     event_id: i32,
     secret: String,
+    expires_at: DateTime<Utc>,
 }
 
 impl EditEventLink {
@@ -82,6 +87,11 @@ impl EditEventLink {
         &self.secret
     }
 
+    /// Get the time the `EditEventLink` expires
+    pub fn expires_at(&self) -> &DateTime<Utc> {
+        &self.expires_at
+    }
+
     /// Insert an `EditEventLink` into the database given the associated IDs and the secret
     pub fn create(
         user_id: i32,
@@ -90,7 +100,7 @@ impl EditEventLink {
         secret: String,
         connection: Connection,
     ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
-        let sql = "INSERT INTO edit_event_links (users_id, system_id, events_id, secret) VALUES ($1, $2, $3, $4) RETURNING id";
+        let sql = "INSERT INTO edit_event_links (users_id, system_id, events_id, secret) VALUES ($1, $2, $3, $4) RETURNING id, expires_at";
         debug!("{}", sql);
 
         connection
@@ -105,6 +115,7 @@ impl EditEventLink {
                         system_id,
                         event_id,
                         secret: secret.clone(),
+                        expires_at: row.get(1),
                     })
                     .collect()
                     .map_err(insert_error)
@@ -123,9 +134,9 @@ impl EditEventLink {
         id: i32,
         connection: Connection,
     ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT eel.id, eel.users_id, eel.system_id, eel.events_id, eel.secret
+        let sql = "SELECT eel.id, eel.users_id, eel.system_id, eel.events_id, eel.secret, eel.expires_at
                     FROM edit_event_links AS eel
-                    WHERE eel.id = $1 AND eel.used = FALSE";
+                    WHERE eel.id = $1 AND eel.used = FALSE AND eel.expires_at > NOW()";
         debug!("{}", sql);
 
         connection
@@ -140,6 +151,7 @@ impl EditEventLink {
                         system_id: row.get(2),
                         event_id: row.get(3),
                         secret: row.get(4),
+                        expires_at: row.get(5),
                     })
                     .collect()
                     .map_err(lookup_error)
@@ -153,12 +165,12 @@ impl EditEventLink {
             })
     }
 
-    /// Mark an `EditEventLink` as used
-    pub fn delete(
+    /// Check whether the `EditEventLink` with the given ID is still unused, even if it has expired
+    pub fn is_unused(
         id: i32,
         connection: Connection,
-    ) -> impl Future<Item = Connection, Error = (EventError, Connection)> {
-        let sql = "UPDATE edit_event_links SET used = TRUE WHERE id = $1";
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT eel.used FROM edit_event_links AS eel WHERE eel.id = $1";
         debug!("{}", sql);
 
         connection
@@ -166,15 +178,15 @@ impl EditEventLink {
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .execute(&s, &[&id])
-                    .map_err(delete_error)
-                    .and_then(|(count, connection)| {
-                        if count > 0 {
-                            Ok(connection)
-                        } else {
-                            Err((EventErrorKind::Delete.into(), connection))
-                        }
-                    })
+                    .query(&s, &[&id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(used, connection): (Vec<Option<bool>>, _)| {
+                let unused = used.into_iter().any(|used| !used.unwrap_or(false));
+
+                (unused, connection)
             })
     }
 }
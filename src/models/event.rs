@@ -23,9 +23,11 @@ use chrono::offset::Utc;
 use chrono::DateTime;
 use chrono_tz::Tz;
 use failure::ResultExt;
+use futures::future::Either;
 use futures::{Future, IntoFuture};
 use futures_state_stream::StateStream;
 use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
 use tokio_postgres::stmt::Statement;
 use tokio_postgres::transaction::Transaction;
 use tokio_postgres::types::ToSql;
@@ -35,6 +37,24 @@ use super::user::User;
 use error::{EventError, EventErrorKind};
 use util::*;
 
+/// The columns that make up an `Event`, in the order `Event::from_row` expects them
+const EVENT_COLUMNS: &str = "evt.id, evt.system_id, evt.start_date, evt.end_date, evt.title, evt.description, evt.timezone, evt.publish_at, evt.capacity, evt.location, evt.location_hint, evt.members_only, evt.public_id, evt.cancelled, evt.updated_at, evt.latitude, evt.longitude, evt.image_url, evt.rsvp_deadline, evt.boost_threshold, evt.pending, evt.unlisted";
+
+/// The number of columns in `EVENT_COLUMNS`
+const EVENT_COLUMN_COUNT: usize = 22;
+
+/// The columns that make up a host, selected after `EVENT_COLUMNS` when hosts are joined
+const HOST_COLUMNS: &str = "usr.id, usr.user_id, usr.username";
+
+/// The most events `Event::search` returns
+const MAX_SEARCH: usize = 20;
+
+/// The most events `Event::directory` returns
+const MAX_DIRECTORY: usize = 100;
+
+/// The text `Event::search` looks through, which matches the `events_search` index
+const SEARCH_DOCUMENT: &str = "to_tsvector('english', ee.title || ' ' || ee.description)";
+
 /// Event represents a scheduled Event
 ///
 /// `start_date` is the date of the event
@@ -42,10 +62,31 @@ use util::*;
 /// `hosts` represents the user_ids of the users who are hosting the event
 /// `title` is the name of the event
 /// `description` is the description of the event
+/// `publish_at` is the time the event should be announced, if it hasn't been announced yet
+/// `capacity` is the maximum number of attendees, if the event has a limit
+/// `boost_threshold` overrides the channel's setting for announcing the event again when fewer
+/// people than this have RSVP'd two days before it starts. Zero means never.
+/// `location` is where the event takes place
+/// `location_hint` is a general area shown in place of `location` to people who haven't RSVP'd
+/// `coordinates` pinpoint `location` on a map, and are hidden along with it
+/// `image_url` is a picture posted with the event's announcement
+/// `rsvp_deadline` is when RSVPs close, if they close before the event starts
+/// `members_only` is whether only members of the linked chats can RSVP to the event
+/// `public_id` identifies the event on its public web page. It isn't a secret, it just can't be
+/// guessed from the IDs of other events
+/// `cancelled` is whether the hosts have called the event off. Cancelled events are kept, along
+/// with their RSVPs, but no reminders are sent for them
+/// `updated_at` is when the event was last edited, if it's been edited since it was created
+/// `pending` is whether the event is waiting for an admin of its moderated channel to approve it.
+/// Pending events aren't announced, reminded, or listed anywhere but their hosts' events
+/// `unlisted` is whether the event is left off its public page, the directory, and search. Unlisted
+/// events are still announced in their channel
 ///
 /// ### Relations:
 /// - events belongs_to chat_systems (foreign_key on events)
 /// - events has_many hosts (foreign_key on hosts)
+/// - events has_many rsvps (foreign_key on rsvps)
+/// - events has_many waitlist (foreign_key on waitlist)
 ///
 /// ### Columns:
 /// - id SERIAL
@@ -54,6 +95,26 @@ use util::*;
 /// - title TEXT
 /// - description TEXT
 /// - system_id INTEGER REFERENCES chat_systems
+/// - timezone VARCHAR(20)
+/// - publish_at TIMESTAMP WITH TIME ZONE
+/// - capacity INTEGER
+/// - location TEXT
+/// - location_hint TEXT
+/// - members_only BOOLEAN
+/// - public_id TEXT (generated by the database)
+/// - announcement_id BIGINT (the message ID of the event's announcement in its channel)
+/// - cancelled BOOLEAN
+/// - updated_at TIMESTAMP WITH TIME ZONE
+/// - latitude DOUBLE PRECISION
+/// - longitude DOUBLE PRECISION
+/// - image_url TEXT
+/// - rsvp_deadline TIMESTAMP WITH TIME ZONE
+/// - created_at TIMESTAMP WITH TIME ZONE (set by the database, missing for older events)
+/// - boost_threshold INTEGER
+/// - boosted BOOLEAN (whether the event has been announced again for having too few RSVPs)
+/// - pending BOOLEAN
+/// - unlisted BOOLEAN
+/// - new_event_links_id INTEGER REFERENCES new_event_links
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Event {
     id: i32,
@@ -63,6 +124,20 @@ pub struct Event {
     description: String,
     hosts: Vec<User>,
     system_id: i32,
+    publish_at: Option<DateTime<Tz>>,
+    capacity: Option<i32>,
+    location: Option<String>,
+    location_hint: Option<String>,
+    members_only: bool,
+    public_id: String,
+    cancelled: bool,
+    updated_at: Option<DateTime<Tz>>,
+    coordinates: Option<Coordinates>,
+    image_url: Option<String>,
+    rsvp_deadline: Option<DateTime<Tz>>,
+    boost_threshold: Option<i32>,
+    pending: bool,
+    unlisted: bool,
 }
 
 impl Hash for Event {
@@ -107,6 +182,175 @@ impl Event {
         self.system_id
     }
 
+    /// Get the time the `Event` is scheduled to be announced
+    ///
+    /// This is `None` once the event has been announced
+    pub fn publish_at(&self) -> Option<&DateTime<Tz>> {
+        self.publish_at.as_ref()
+    }
+
+    /// Mark the `Event` as announced
+    pub fn mark_published(&mut self) {
+        self.publish_at = None;
+    }
+
+    /// Get the maximum number of attendees for the `Event`, if there is one
+    pub fn capacity(&self) -> Option<i32> {
+        self.capacity
+    }
+
+    /// Get the exact location of the `Event`
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the general area of the `Event`, shown to people who haven't RSVP'd
+    pub fn location_hint(&self) -> Option<&str> {
+        self.location_hint.as_ref().map(|s| s.as_str())
+    }
+
+    /// Check whether the exact location should only be revealed to people who have RSVP'd
+    pub fn location_hidden(&self) -> bool {
+        self.location.is_some() && self.location_hint.is_some()
+    }
+
+    /// Get the location that can be shown to anyone
+    pub fn public_location(&self) -> Option<&str> {
+        if self.location_hidden() {
+            self.location_hint()
+        } else {
+            self.location()
+        }
+    }
+
+    /// Get the exact coordinates of the `Event`
+    pub fn coordinates(&self) -> Option<&Coordinates> {
+        self.coordinates.as_ref()
+    }
+
+    /// Get the coordinates that can be shown to anyone
+    ///
+    /// Coordinates would give away a hidden location, so they're left out when the location is
+    /// hidden
+    pub fn public_coordinates(&self) -> Option<&Coordinates> {
+        if self.location_hidden() {
+            None
+        } else {
+            self.coordinates()
+        }
+    }
+
+    /// Get the URL of the picture posted with the `Event`'s announcement
+    pub fn image_url(&self) -> Option<&str> {
+        self.image_url.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the number of RSVPs below which the `Event` is announced again, if it overrides its
+    /// channel's setting
+    pub fn boost_threshold(&self) -> Option<i32> {
+        self.boost_threshold
+    }
+
+    /// Get the time RSVPs to the `Event` close, if they close before it starts
+    pub fn rsvp_deadline(&self) -> Option<&DateTime<Tz>> {
+        self.rsvp_deadline.as_ref()
+    }
+
+    /// Check whether the `Event`'s RSVP deadline has passed
+    pub fn rsvps_closed(&self) -> bool {
+        self.rsvp_deadline
+            .map(|deadline| deadline.with_timezone(&Utc) <= Utc::now())
+            .unwrap_or(false)
+    }
+
+    /// Check whether only members of the chats linked to the `Event`'s channel can RSVP
+    ///
+    /// Members-only events should be left out of anything visible outside of those chats
+    pub fn members_only(&self) -> bool {
+        self.members_only
+    }
+
+    /// Get the identifier used in the `Event`'s public web page
+    pub fn public_id(&self) -> &str {
+        &self.public_id
+    }
+
+    /// Check whether the `Event` has been cancelled
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Check whether the `Event` is waiting to be approved by an admin of its channel
+    pub fn pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Check whether the event is left off its public page, the directory, and search
+    pub fn unlisted(&self) -> bool {
+        self.unlisted
+    }
+
+    /// Get the time the `Event` was last edited
+    ///
+    /// This is `None` if the event hasn't been edited since it was created
+    pub fn updated_at(&self) -> Option<&DateTime<Tz>> {
+        self.updated_at.as_ref()
+    }
+
+    /// Build an `Event` without hosts from a row selected with `EVENT_COLUMNS`
+    fn from_row(row: &Row) -> Result<Self, String> {
+        let tz: String = row.get(6);
+
+        let sd: DateTime<Utc> = row.get(2);
+        let ed: DateTime<Utc> = row.get(3);
+        let pa: Option<DateTime<Utc>> = row.get(7);
+        let ua: Option<DateTime<Utc>> = row.get(14);
+        let latitude: Option<f64> = row.get(15);
+        let longitude: Option<f64> = row.get(16);
+        let rd: Option<DateTime<Utc>> = row.get(18);
+
+        tz.parse::<Tz>().map(|timezone| Event {
+            id: row.get(0),
+            start_date: sd.with_timezone(&timezone),
+            end_date: ed.with_timezone(&timezone),
+            title: row.get(4),
+            description: row.get(5),
+            hosts: Vec::new(),
+            system_id: row.get(1),
+            publish_at: pa.map(|pa| pa.with_timezone(&timezone)),
+            capacity: row.get(8),
+            location: row.get(9),
+            location_hint: row.get(10),
+            members_only: row.get(11),
+            public_id: row.get(12),
+            cancelled: row.get(13),
+            updated_at: ua.map(|ua| ua.with_timezone(&timezone)),
+            coordinates: match (latitude, longitude) {
+                (Some(latitude), Some(longitude)) => Coordinates::new(latitude, longitude),
+                _ => None,
+            },
+            image_url: row.get(17),
+            rsvp_deadline: rd.map(|rd| rd.with_timezone(&timezone)),
+            boost_threshold: row.get(19),
+            pending: row.get(20),
+            unlisted: row.get(21),
+        })
+    }
+
+    /// Build an `Event` from a row selected with `EVENT_COLUMNS` followed by `HOST_COLUMNS`
+    fn from_row_with_host(row: &Row) -> Result<Self, String> {
+        Event::from_row(row).map(|mut event| {
+            event
+                .hosts
+                .extend(User::maybe_from_parts(
+                    row.get(EVENT_COLUMN_COUNT),
+                    row.get(EVENT_COLUMN_COUNT + 1),
+                    row.get(EVENT_COLUMN_COUNT + 2),
+                ));
+            event
+        })
+    }
+
     /// Merge two events that are the same, appending hosts but overwriting other fields, puttign
     /// the result on the end of a vector
     pub fn condense(events: &mut Vec<Self>, mut event_1: Self, event_2: Self) {
@@ -138,41 +382,36 @@ impl Event {
     }
 
     /// Lookup event by the host's id
+    ///
+    /// This includes events that have not been announced yet, since hosts need to be able to
+    /// manage them.
     pub fn by_user_id(
         user_id: Integer,
         connection: Connection,
     ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT evt.id, evt.system_id, evt.start_date, evt.end_date, evt.title, evt.description, evt.timezone, usr.id, usr.user_id, usr.username
-                    FROM events AS evt
-                    LEFT JOIN hosts AS h ON h.events_id = evt.id
-                    INNER JOIN users AS usr ON usr.id = h.users_id
-                    WHERE usr.user_id = $1";
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                INNER JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.id IN (
+                    SELECT hh.events_id
+                    FROM hosts AS hh
+                    INNER JOIN users AS uu ON uu.id = hh.users_id
+                    WHERE uu.user_id = $1
+                )
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&user_id])
-                    .map(move |row| {
-                        let tz: String = row.get(6);
-
-                        let sd: DateTime<Utc> = row.get(2);
-                        let ed: DateTime<Utc> = row.get(3);
-
-                        tz.parse::<Tz>().map(|timezone| Event {
-                            id: row.get(0),
-                            start_date: sd.with_timezone(&timezone),
-                            end_date: ed.with_timezone(&timezone),
-                            title: row.get(4),
-                            description: row.get(5),
-                            hosts: User::maybe_from_parts(row.get(7), row.get(8), row.get(9))
-                                .into_iter()
-                                .collect(),
-                            system_id: row.get(1),
-                        })
-                    })
+                    .map(|row| Event::from_row_with_host(&row))
                     .collect()
                     .map_err(lookup_error)
             })
@@ -184,52 +423,140 @@ impl Event {
             })
     }
 
+    /// Count the events the user with the given database ID is hosting in the chat system that
+    /// haven't ended or been cancelled
+    ///
+    /// Events waiting for approval are counted, so they can't be used to get around the quota.
+    pub fn count_upcoming_for_host(
+        system_id: i32,
+        user_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (i64, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT COUNT(*)
+                    FROM events AS evt
+                    INNER JOIN hosts AS h ON h.events_id = evt.id
+                    WHERE evt.system_id = $1 AND h.users_id = $2
+                        AND evt.end_date > NOW() AND evt.cancelled = FALSE";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id, &user_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(counts, connection)| (counts.into_iter().next().unwrap_or(0), connection))
+    }
+
     /// Lookup event by the event id
     pub fn by_id(
         id: i32,
         connection: Connection,
     ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT evt.system_id, evt.start_date, evt.end_date, evt.title, evt.description, evt.timezone, usr.id, usr.user_id, usr.username
-                    FROM events AS evt
-                    LEFT JOIN hosts AS h ON h.events_id = evt.id
-                    INNER JOIN users AS usr ON usr.id = h.users_id
-                    WHERE evt.id = $1";
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.id = $1",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
         debug!("{}", sql);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&id])
-                    .map(move |row| {
-                        let tz: String = row.get(5);
-
-                        let sd: DateTime<Utc> = row.get(1);
-                        let ed: DateTime<Utc> = row.get(2);
-
-                        tz.parse::<Tz>().map(|timezone| Event {
-                            id,
-                            start_date: sd.with_timezone(&timezone),
-                            end_date: ed.with_timezone(&timezone),
-                            title: row.get(3),
-                            description: row.get(4),
-                            hosts: User::maybe_from_parts(row.get(6), row.get(7), row.get(8))
-                                .into_iter()
-                                .collect(),
-                            system_id: row.get(0),
-                        })
-                    })
+                    .map(|row| Event::from_row_with_host(&row))
                     .collect()
                     .map_err(lookup_error)
             })
-            .and_then(|(mut events, connection)| {
+            .and_then(|(events, connection)| {
+                let mut events =
+                    Event::condense_events(events.into_iter().filter_map(Result::ok).collect());
+
                 if events.len() > 0 {
-                    if let Ok(event) = events.remove(0) {
-                        Ok((event, connection))
-                    } else {
-                        Err((EventErrorKind::Lookup.into(), connection))
-                    }
+                    Ok((events.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Lookup.into(), connection))
+                }
+            })
+    }
+
+    /// Lookup the event made from the `NewEventLink` with the given ID, if there is one
+    pub fn by_link_id(
+        link_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Event>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.new_event_links_id = $1",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&link_id])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(events, connection)| {
+                let mut events =
+                    Event::condense_events(events.into_iter().filter_map(Result::ok).collect());
+
+                if events.len() > 0 {
+                    (Some(events.remove(0)), connection)
+                } else {
+                    (None, connection)
+                }
+            })
+    }
+
+    /// Lookup event by the ID used in its public web page
+    pub fn by_public_id(
+        public_id: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.public_id = $1",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&public_id])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .and_then(|(events, connection)| {
+                let mut events =
+                    Event::condense_events(events.into_iter().filter_map(Result::ok).collect());
+
+                if events.len() > 0 {
+                    Ok((events.remove(0), connection))
                 } else {
                     Err((EventErrorKind::Lookup.into(), connection))
                 }
@@ -237,11 +564,26 @@ impl Event {
     }
 
     /// Delete and `Event` and all associated `hosts` given an ID
+    ///
+    /// Events that have ended are kept in `past_events`, with how many people went and who hosted
+    /// them, so the channel's stats still count them.
     pub fn delete_by_id(
         id: i32,
         connection: Connection,
     ) -> impl Future<Item = (u64, Connection), Error = (EventError, Connection)> {
-        let sql = "DELETE FROM events AS ev WHERE ev.id = $1";
+        let sql = "WITH archived AS (
+                        INSERT INTO past_events
+                            (id, system_id, start_date, end_date, created_at, going, cancelled,
+                                host_ids)
+                        SELECT ev.id, ev.system_id, ev.start_date, ev.end_date, ev.created_at,
+                            (SELECT COUNT(*) FROM rsvps AS r WHERE r.events_id = ev.id)::INTEGER,
+                            ev.cancelled,
+                            ARRAY(SELECT h.users_id FROM hosts AS h WHERE h.events_id = ev.id)
+                        FROM events AS ev
+                        WHERE ev.id = $1 AND ev.end_date <= NOW() AND ev.pending = FALSE
+                        ON CONFLICT (id) DO NOTHING
+                    )
+                    DELETE FROM events AS ev WHERE ev.id = $1";
         debug!("{}", sql);
 
         connection
@@ -251,41 +593,32 @@ impl Event {
     }
 
     /// Get a `Vec<Event>` with events happening within the next `start_date` to `end_date`
+    ///
+    /// Events that have not been announced yet are not included.
     pub fn in_range(
         start_date: DateTime<Tz>,
         end_date: DateTime<Tz>,
         connection: Connection,
     ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT DISTINCT ev.id, ev.start_date, ev.end_date, ev.title, ev.description, ev.system_id, ev.timezone
-                    FROM events AS ev
-                    WHERE ev.start_date > $1 AND ev.start_date < $2";
+        let sql = format!(
+            "SELECT DISTINCT {}
+                FROM events AS evt
+                WHERE evt.start_date > $1 AND evt.start_date < $2 AND evt.publish_at IS NULL
+                    AND evt.pending = FALSE",
+            EVENT_COLUMNS
+        );
         debug!("{}", sql);
 
         let sd = start_date.with_timezone(&Utc);
         let ed = end_date.with_timezone(&Utc);
 
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
                     .query(&s, &[&sd, &ed])
-                    .map(|row| {
-                        let sd: DateTime<Utc> = row.get(1);
-                        let ed: DateTime<Utc> = row.get(2);
-
-                        let tz: String = row.get(6);
-
-                        tz.parse::<Tz>().map(|timezone| Event {
-                            id: row.get(0),
-                            start_date: sd.with_timezone(&timezone),
-                            end_date: ed.with_timezone(&timezone),
-                            title: row.get(3),
-                            description: row.get(4),
-                            hosts: Vec::new(),
-                            system_id: row.get(5),
-                        })
-                    })
+                    .map(|row| Event::from_row(&row))
                     .collect()
                     .map(|(events, connection)| {
                         (
@@ -297,48 +630,34 @@ impl Event {
             })
     }
 
-    /// Given the system id, lookup all associated events
+    /// Get a `Vec<Event>` with announcements scheduled before the given date
     ///
-    /// This creates a future whose item contains the database connection and an ordered vector of
-    /// event structs. The events are ordered date.
-    pub fn by_system_id(
-        system_id: i32,
+    /// Events waiting to be approved are left out until they're approved.
+    pub fn to_publish(
+        before: DateTime<Tz>,
         connection: Connection,
-    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
-        let sql =
-            "SELECT evt.id, evt.start_date, evt.end_date, evt.title, evt.description, evt.timezone, usr.id, usr.user_id, usr.username
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
                 FROM events AS evt
                 LEFT JOIN hosts AS h ON h.events_id = evt.id
-                INNER JOIN users AS usr ON usr.id = h.users_id
-                WHERE evt.system_id = $1";
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.publish_at IS NOT NULL AND evt.publish_at < $1 AND evt.pending = FALSE
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
         debug!("{}", sql);
 
+        let before = before.with_timezone(&Utc);
+
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .query(&s, &[&system_id])
-                    .map(move |row| {
-                        let tz: String = row.get(5);
-
-                        let sd: DateTime<Utc> = row.get(1);
-                        let ed: DateTime<Utc> = row.get(2);
-
-                        tz.parse::<Tz>().map(|timezone| Event {
-                            id: row.get(0),
-                            start_date: sd.with_timezone(&timezone),
-                            end_date: ed.with_timezone(&timezone),
-                            title: row.get(3),
-                            description: row.get(4),
-                            hosts: User::maybe_from_parts(row.get(6), row.get(7), row.get(8))
-                                .into_iter()
-                                .collect(),
-                            system_id: system_id,
-                        })
-                    })
+                    .query(&s, &[&before])
+                    .map(|row| Event::from_row_with_host(&row))
                     .collect()
-                    .map_err(lookup_error)
                     .map(|(events, connection)| {
                         (
                             Event::condense_events(
@@ -347,55 +666,94 @@ impl Event {
                             connection,
                         )
                     })
+                    .map_err(lookup_error)
             })
     }
 
-    /// Given a chat id, lookup all associated events
+    /// Get a `Vec<Event>` whose RSVPs close between `start_date` and `end_date`
     ///
-    /// This creates a future whose item contains the database connection and an ordered vector of
-    /// event structs. The events are ordered date.
-    pub fn by_chat_id(
-        chat_id: Integer,
+    /// Events that have not been announced yet, or have been cancelled, are not included.
+    pub fn closing_rsvps(
+        start_date: DateTime<Tz>,
+        end_date: DateTime<Tz>,
         connection: Connection,
-    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
-        let sql =
-            "SELECT evt.id, evt.start_date, evt.end_date, evt.title, evt.description, evt.timezone, usr.id, usr.user_id, usr.username, sys.id
-               FROM events as evt
-               INNER JOIN chat_systems AS sys ON evt.system_id = sys.id
-               INNER JOIN chats AS ch ON ch.system_id = sys.id
-               LEFT JOIN hosts AS h ON h.events_id = evt.id
-               LEFT JOIN users AS usr ON h.users_id = usr.id
-               WHERE ch.chat_id = $1
-               ORDER BY evt.start_date, evt.id";
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT DISTINCT {}
+                FROM events AS evt
+                WHERE evt.rsvp_deadline > $1 AND evt.rsvp_deadline <= $2
+                    AND evt.publish_at IS NULL AND evt.pending = FALSE AND evt.cancelled = FALSE",
+            EVENT_COLUMNS
+        );
         debug!("{}", sql);
 
+        let sd = start_date.with_timezone(&Utc);
+        let ed = end_date.with_timezone(&Utc);
+
         connection
-            .prepare(sql)
+            .prepare(&sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .query(&s, &[&chat_id])
-                    .map(|row| {
-                        // StateStream::map()
-                        let host = User::maybe_from_parts(row.get(6), row.get(7), row.get(8));
-                        let tz: String = row.get(5);
-
-                        let sd: DateTime<Utc> = row.get(1);
-                        let ed: DateTime<Utc> = row.get(2);
-
-                        tz.parse::<Tz>().map(|timezone| Event {
-                            id: row.get(0),
-                            start_date: sd.with_timezone(&timezone),
-                            end_date: ed.with_timezone(&timezone),
-                            title: row.get(3),
-                            description: row.get(4),
-                            hosts: host.into_iter().collect(),
-                            system_id: row.get(9),
-                        })
+                    .query(&s, &[&sd, &ed])
+                    .map(|row| Event::from_row(&row))
+                    .collect()
+                    .map(|(events, connection)| {
+                        (
+                            events.into_iter().filter_map(Result::ok).collect(),
+                            connection,
+                        )
                     })
+                    .map_err(lookup_error)
+            })
+    }
+
+    /// Get the events starting before `before` that fewer people have RSVP'd to than their boost
+    /// threshold, marking them as boosted
+    ///
+    /// An event's own threshold takes the place of its channel's. Marking the events in the same
+    /// query keeps each one from being boosted more than once. Events that haven't been announced
+    /// yet, have been cancelled, or are in channels that turned RSVPs off are skipped.
+    pub fn claim_boosts(
+        before: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Event>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "WITH evt AS (
+                UPDATE events AS ee
+                    SET boosted = TRUE
+                    FROM chat_systems AS sys
+                    WHERE sys.id = ee.system_id AND ee.boosted = FALSE AND ee.cancelled = FALSE
+                        AND ee.publish_at IS NULL AND ee.pending = FALSE AND ee.start_date > NOW()
+                        AND ee.start_date <= $1
+                        AND (SELECT COUNT(*) FROM rsvps AS r WHERE r.events_id = ee.id)
+                            < COALESCE(ee.boost_threshold, sys.boost_threshold)
+                        AND NOT EXISTS (
+                            SELECT 1 FROM chat_system_features AS f
+                            WHERE f.chat_systems_id = sys.id AND f.rsvp_enabled = FALSE
+                        )
+                    RETURNING ee.*
+            )
+            SELECT {}, {}
+                FROM evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let before = before.with_timezone(&Utc);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&before])
+                    .map(|row| Event::from_row_with_host(&row))
                     .collect()
                     .map(|(events, connection)| {
-                        // Future::map()
                         (
                             Event::condense_events(
                                 events.into_iter().filter_map(Result::ok).collect(),
@@ -403,131 +761,1030 @@ impl Event {
                             connection,
                         )
                     })
-                    .map_err(lookup_error)
+                    .map_err(update_error)
             })
     }
-}
 
-/// This type exists as a way to safely update events in the database.
-///
-/// If all fields are provided and an UpdateEvent is successfully created, the event can be safely
-/// updated in the database.
-#[derive(Clone, Debug)]
-pub struct UpdateEvent {
-    pub id: i32,
-    pub system_id: i32,
-    pub start_date: DateTime<Tz>,
-    pub end_date: DateTime<Tz>,
-    pub title: String,
-    pub description: String,
-    pub hosts: Vec<i32>,
-}
+    /// Mark an `Event` as announced given its ID
+    /// Remember which message in the event's channel announces the event
+    pub fn set_announcement_id(
+        id: i32,
+        message_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE events SET announcement_id = $1 WHERE id = $2";
+        debug!("{}", sql);
 
-impl UpdateEvent {
-    /// Perform the database interaction to update the event
-    pub fn update(
-        self,
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&message_id, &id])
+                    .map_err(update_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Get the message ID of the event's announcement, if it's been announced
+    pub fn announcement_id_by_id(
+        id: i32,
         connection: Connection,
-    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
-        let sql = "UPDATE events
-                    SET start_date = $1, end_date = $2, title = $3, description = $4, timezone = $5
-                    WHERE id = $6";
+    ) -> impl Future<Item = (Option<Integer>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT evt.announcement_id FROM events AS evt WHERE evt.id = $1";
         debug!("{}", sql);
 
-        let UpdateEvent {
-            id,
-            system_id,
-            start_date,
-            end_date,
-            title,
-            description,
-            hosts: _hosts,
-        } = self;
-
-        let timezone = start_date.timezone().name();
-        let sd = start_date.with_timezone(&Utc);
-        let ed = end_date.with_timezone(&Utc);
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(lookup_error)
+                    .and_then(|(mut results, connection)| {
+                        if results.len() == 1 {
+                            Ok((results.remove(0), connection))
+                        } else {
+                            Err((EventErrorKind::Lookup.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Mark an `Event` as cancelled, keeping it and its RSVPs around
+    pub fn cancel_by_id(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE events SET cancelled = TRUE WHERE id = $1";
+        debug!("{}", sql);
 
         connection
-            .prepare(&sql)
+            .prepare(sql)
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .execute(&s, &[&sd, &ed, &title, &description, &timezone, &id])
+                    .execute(&s, &[&id])
                     .map_err(update_error)
-                    .and_then(move |(count, connection)| {
+                    .and_then(|(count, connection)| {
                         if count > 0 {
-                            Ok((
-                                Event {
-                                    id,
-                                    system_id,
-                                    start_date,
-                                    end_date,
-                                    title,
-                                    description,
-                                    hosts: Vec::new(),
-                                },
-                                connection,
-                            ))
+                            Ok(((), connection))
                         } else {
                             Err((EventErrorKind::Update.into(), connection))
                         }
                     })
             })
     }
-}
 
-/// This type provides a safe way to create events in the database
-#[derive(Clone, Debug)]
-pub struct CreateEvent {
-    pub system_id: i32,
-    pub start_date: DateTime<Tz>,
+    /// Hand an `Event` over from one host to another, given their database IDs
+    ///
+    /// The new host must belong to one of the chats linked to the event's channel. Nothing is
+    /// changed if they don't, or if the old host isn't hosting the event.
+    pub fn transfer_by_id(
+        id: i32,
+        from_host: i32,
+        to_host: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let add_sql = "INSERT INTO hosts (users_id, events_id)
+                        SELECT usr.id, evt.id
+                        FROM users AS usr, events AS evt
+                        WHERE evt.id = $1 AND usr.id = $2 AND NOT EXISTS (
+                            SELECT 1 FROM hosts AS h WHERE h.events_id = evt.id AND h.users_id = usr.id
+                        ) AND EXISTS (
+                            SELECT 1
+                            FROM user_chats AS uc
+                            INNER JOIN chats AS ch ON ch.id = uc.chats_id
+                            WHERE uc.users_id = usr.id AND ch.system_id = evt.system_id
+                        )";
+        // The old host is only removed once the new host is in place
+        let remove_sql = "DELETE FROM hosts
+                            WHERE events_id = $1 AND users_id = $2 AND EXISTS (
+                                SELECT 1 FROM hosts AS h WHERE h.events_id = $1 AND h.users_id = $3
+                            )";
+
+        connection
+            .transaction()
+            .map_err(transaction_error)
+            .and_then(move |transaction| {
+                debug!("{}", add_sql);
+
+                transaction
+                    .prepare(add_sql)
+                    .map_err(transaction_prepare_error)
+                    .and_then(move |(s, transaction)| {
+                        transaction
+                            .execute(&s, &[&id, &to_host])
+                            .map_err(transaction_insert_error)
+                    })
+                    .and_then(move |(_, transaction)| {
+                        debug!("{}", remove_sql);
+
+                        transaction
+                            .prepare(remove_sql)
+                            .map_err(transaction_prepare_error)
+                    })
+                    .and_then(move |(s, transaction)| {
+                        transaction
+                            .execute(&s, &[&id, &from_host, &to_host])
+                            .map_err(transaction_delete_error)
+                    })
+                    .and_then(|(count, transaction)| {
+                        if count > 0 {
+                            Ok(transaction)
+                        } else {
+                            Err((EventErrorKind::Hosts.into(), transaction))
+                        }
+                    })
+                    .or_else(|(e, transaction)| {
+                        transaction
+                            .rollback()
+                            .or_else(|(_, connection)| Err(connection))
+                            .then(move |res| match res {
+                                Ok(connection) => Err((e, connection)),
+                                Err(connection) => Err((e, connection)),
+                            })
+                    })
+                    .and_then(|transaction| {
+                        transaction
+                            .commit()
+                            .map_err(commit_error)
+                            .map(|connection| ((), connection))
+                    })
+            })
+    }
+
+    pub fn mark_published_by_id(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE events SET publish_at = NULL WHERE id = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&id])
+                    .map_err(update_error)
+                    .and_then(|(count, connection)| {
+                        if count > 0 {
+                            Ok(((), connection))
+                        } else {
+                            Err((EventErrorKind::Update.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Claim a stage of the `Event` that was due at `due`, returning whether it was claimed
+    ///
+    /// Each instance of the bot sharing the database tracks the same events, and only the one
+    /// that claims a stage first carries it out. Claims are forgotten once the event is deleted,
+    /// and stages of events that are already gone can't be claimed.
+    pub fn claim_stage(
+        id: i32,
+        stage: &'static str,
+        due: DateTime<Utc>,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO timer_claims (events_id, stage, due)
+                    SELECT ev.id, $2, $3 FROM events AS ev WHERE ev.id = $1
+                    ON CONFLICT DO NOTHING";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&id, &stage, &due])
+                    .map_err(insert_error)
+            })
+            .map(|(count, connection)| (count > 0, connection))
+    }
+
+    /// Approve a pending `Event`, so it's announced like any other
+    ///
+    /// This fails if the event isn't pending, so an event is only approved once even if several
+    /// admins are asked about it
+    pub fn approve_by_id(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "UPDATE events SET pending = FALSE WHERE id = $1 AND pending = TRUE";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&id])
+                    .map_err(update_error)
+                    .and_then(|(count, connection)| {
+                        if count > 0 {
+                            Ok(((), connection))
+                        } else {
+                            Err((EventErrorKind::Update.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Delete a pending `Event` an admin turned down
+    ///
+    /// This fails if the event isn't pending, so events that have already been approved are kept
+    pub fn reject_by_id(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "DELETE FROM events WHERE id = $1 AND pending = TRUE";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&id])
+                    .map_err(delete_error)
+                    .and_then(|(count, connection)| {
+                        if count > 0 {
+                            Ok(((), connection))
+                        } else {
+                            Err((EventErrorKind::Delete.into(), connection))
+                        }
+                    })
+            })
+    }
+
+    /// Given the system id, lookup all associated events
+    ///
+    /// This creates a future whose item contains the database connection and an ordered vector of
+    /// event structs. The events are ordered date. Events that have not been announced yet are
+    /// not included.
+    pub fn by_system_id(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.system_id = $1 AND evt.publish_at IS NULL AND evt.pending = FALSE
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+                    .map(|(events, connection)| {
+                        (
+                            Event::condense_events(
+                                events.into_iter().filter_map(Result::ok).collect(),
+                            ),
+                            connection,
+                        )
+                    })
+            })
+    }
+
+    /// Get the first event in the given chat's channel that starts after `after`, if there is one
+    ///
+    /// Cancelled events, and events that have not been announced yet, are skipped.
+    pub fn next_by_chat_id(
+        chat_id: Integer,
+        after: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Option<Self>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.id = (
+                    SELECT ee.id
+                    FROM events AS ee
+                    INNER JOIN chats AS ch ON ch.system_id = ee.system_id
+                    WHERE ch.chat_id = $1
+                    AND ee.start_date > $2
+                    AND ee.publish_at IS NULL
+                    AND ee.pending = FALSE
+                    AND ee.cancelled = FALSE
+                    ORDER BY ee.start_date, ee.id
+                    LIMIT 1
+                )",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let after = after.with_timezone(&Utc);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&chat_id, &after])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+                    .map(|(events, connection)| {
+                        let mut events = Event::condense_events(
+                            events.into_iter().filter_map(Result::ok).collect(),
+                        );
+
+                        (events.pop(), connection)
+                    })
+            })
+    }
+
+    /// Get the events in the given chat's channel that start from `start_date` up to `end_date`
+    ///
+    /// Cancelled events, and events that have not been announced yet, are not included.
+    pub fn by_chat_id_in_range(
+        chat_id: Integer,
+        start_date: DateTime<Tz>,
+        end_date: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                INNER JOIN chats AS ch ON ch.system_id = evt.system_id
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE ch.chat_id = $1
+                AND evt.start_date >= $2
+                AND evt.start_date < $3
+                AND evt.publish_at IS NULL
+                AND evt.pending = FALSE
+                AND evt.cancelled = FALSE
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        let sd = start_date.with_timezone(&Utc);
+        let ed = end_date.with_timezone(&Utc);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&chat_id, &sd, &ed])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+                    .map(|(events, connection)| {
+                        (
+                            Event::condense_events(
+                                events.into_iter().filter_map(Result::ok).collect(),
+                            ),
+                            connection,
+                        )
+                    })
+            })
+    }
+
+    /// Search the upcoming events in the given scope for ones matching `terms`
+    ///
+    /// This uses Postgres' full-text search over the title and description, and each term also
+    /// matches words it's the start of, so partly typed words still find events. Events that have
+    /// ended, been cancelled, not been announced yet, or are unlisted are left out, and at most
+    /// `MAX_SEARCH` events are returned, soonest first. Empty terms match every upcoming event.
+    pub fn search(
+        scope: SearchScope,
+        terms: &str,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
+        let systems = match scope {
+            SearchScope::Chat(_) => {
+                "SELECT ch.system_id
+                    FROM chats AS ch
+                    WHERE ch.chat_id = $1"
+            }
+            SearchScope::User(_) => {
+                "SELECT ch.system_id
+                    FROM chats AS ch
+                    INNER JOIN user_chats AS uc ON uc.chats_id = ch.id
+                    INNER JOIN users AS uu ON uu.id = uc.users_id
+                    WHERE uu.user_id = $1"
+            }
+        };
+
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.id IN (
+                    SELECT ee.id
+                    FROM events AS ee
+                    WHERE ee.system_id IN ({})
+                    AND ee.end_date > NOW()
+                    AND ee.publish_at IS NULL
+                    AND ee.pending = FALSE
+                    AND ee.cancelled = FALSE
+                    AND ee.unlisted = FALSE
+                    AND ($2 = '' OR {} @@ to_tsquery('english', $2))
+                    ORDER BY ee.start_date, ee.id
+                    LIMIT {}
+                )
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS, systems, SEARCH_DOCUMENT, MAX_SEARCH
+        );
+        debug!("{}", sql);
+
+        let id = match scope {
+            SearchScope::Chat(chat_id) => chat_id,
+            SearchScope::User(user_id) => user_id,
+        };
+        let query = search_query(terms);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id, &query])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+                    .map(|(events, connection)| {
+                        (
+                            Event::condense_events(
+                                events.into_iter().filter_map(Result::ok).collect(),
+                            ),
+                            connection,
+                        )
+                    })
+            })
+    }
+
+    /// Search the upcoming events of every channel listed in the directory for ones matching
+    /// `terms` and tagged with `tag`
+    ///
+    /// `terms` work like they do in `Event::search`. Tags are the hashtags in an event's title or
+    /// description, and an empty tag matches every event. Like the public event pages, the
+    /// directory leaves out members only and unlisted events, as well as events that have ended,
+    /// been cancelled, or not been announced yet, and channels that turned off public pages. At
+    /// most `MAX_DIRECTORY` events are returned, soonest first.
+    pub fn directory(
+        terms: &str,
+        tag: &str,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events AS evt
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON usr.id = h.users_id
+                WHERE evt.id IN (
+                    SELECT ee.id
+                    FROM events AS ee
+                    INNER JOIN chat_systems AS sys ON sys.id = ee.system_id
+                    WHERE sys.listed = TRUE
+                    AND NOT EXISTS (
+                        SELECT 1 FROM chat_system_features AS f
+                        WHERE f.chat_systems_id = sys.id AND f.public_page_enabled = FALSE
+                    )
+                    AND ee.end_date > NOW()
+                    AND ee.publish_at IS NULL
+                    AND ee.pending = FALSE
+                    AND ee.cancelled = FALSE
+                    AND ee.members_only = FALSE
+                    AND ee.unlisted = FALSE
+                    AND ($1 = '' OR {} @@ to_tsquery('english', $1))
+                    AND ($2 = '' OR (ee.title || ' ' || ee.description)
+                        ~* ('#' || $2 || '([^[:alnum:]_]|$)'))
+                    ORDER BY ee.start_date, ee.id
+                    LIMIT {}
+                )
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS, SEARCH_DOCUMENT, MAX_DIRECTORY
+        );
+        debug!("{}", sql);
+
+        let query = search_query(terms);
+        let tag = tag.chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect::<String>();
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&query, &tag])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map_err(lookup_error)
+                    .map(|(events, connection)| {
+                        (
+                            Event::condense_events(
+                                events.into_iter().filter_map(Result::ok).collect(),
+                            ),
+                            connection,
+                        )
+                    })
+            })
+    }
+
+    /// Given a chat id, lookup all associated events
+    ///
+    /// This creates a future whose item contains the database connection and an ordered vector of
+    /// event structs. The events are ordered date. Events that have not been announced yet are
+    /// not included.
+    pub fn by_chat_id(
+        chat_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Self>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT {}, {}
+                FROM events as evt
+                INNER JOIN chat_systems AS sys ON evt.system_id = sys.id
+                INNER JOIN chats AS ch ON ch.system_id = sys.id
+                LEFT JOIN hosts AS h ON h.events_id = evt.id
+                LEFT JOIN users AS usr ON h.users_id = usr.id
+                WHERE ch.chat_id = $1 AND evt.publish_at IS NULL AND evt.pending = FALSE
+                ORDER BY evt.start_date, evt.id",
+            EVENT_COLUMNS, HOST_COLUMNS
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&chat_id])
+                    .map(|row| Event::from_row_with_host(&row))
+                    .collect()
+                    .map(|(events, connection)| {
+                        (
+                            Event::condense_events(
+                                events.into_iter().filter_map(Result::ok).collect(),
+                            ),
+                            connection,
+                        )
+                    })
+                    .map_err(lookup_error)
+            })
+    }
+}
+
+/// `SearchScope` picks whose events `Event::search` looks through
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SearchScope {
+    /// The events of the channel linked to the group chat with this ID
+    Chat(Integer),
+    /// The events of every channel linked to a chat the user with this ID is in
+    User(Integer),
+}
+
+/// Turn the words someone searched for into a `tsquery` that matches events containing all of
+/// them, including words they're the start of
+///
+/// Anything but letters and numbers is dropped, so the query is always valid
+fn search_query(terms: &str) -> String {
+    terms
+        .split_whitespace()
+        .map(|term| term.chars().filter(|c| c.is_alphanumeric()).collect::<String>())
+        .filter(|term| term.len() > 0)
+        .map(|term| format!("{}:*", term))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// `Coordinates` pinpoint where an `Event` takes place
+///
+/// They're checked to be in range when they're built, so they're never NaN and can be compared
+/// exactly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl Eq for Coordinates {}
+
+impl Coordinates {
+    /// Build `Coordinates`, if the latitude and longitude are in range
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        if latitude.abs() <= 90.0 && longitude.abs() <= 180.0 {
+            Some(Coordinates {
+                latitude,
+                longitude,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+/// This type exists as a way to safely update events in the database.
+///
+/// If all fields are provided and an UpdateEvent is successfully created, the event can be safely
+/// updated in the database.
+///
+/// `publish_at` only applies to events that have not been announced yet. `hosts` holds the
+/// database IDs of the event's hosts, and leaves them as they are if it isn't set.
+#[derive(Clone, Debug)]
+pub struct UpdateEvent {
+    pub id: i32,
+    pub system_id: i32,
+    pub start_date: DateTime<Tz>,
+    pub end_date: DateTime<Tz>,
+    pub title: String,
+    pub description: String,
+    pub hosts: Option<Vec<i32>>,
+    pub publish_at: Option<DateTime<Tz>>,
+    pub capacity: Option<i32>,
+    pub boost_threshold: Option<i32>,
+    pub location: Option<String>,
+    pub location_hint: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    pub image_url: Option<String>,
+    pub rsvp_deadline: Option<DateTime<Tz>>,
+    pub members_only: bool,
+    pub unlisted: bool,
+    pub link_id: Option<i32>,
+}
+
+impl UpdateEvent {
+    /// Perform the database interaction to update the event
+    ///
+    /// If `hosts` is set, hosts who aren't in it are removed from the event, and users in it who
+    /// belong to one of the event's chats are added. Nothing is changed if that would leave the
+    /// event without any hosts.
+    ///
+    /// If `link_id` is set, the `EditEventLink` is used up along with the update, and nothing is
+    /// changed if it was already used.
+    pub fn update(
+        self,
+        connection: Connection,
+    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
+        connection
+            .transaction()
+            .map_err(transaction_error)
+            .and_then(move |transaction| {
+                let hosts = self.hosts.clone();
+                let hosts_changed = hosts.is_some();
+
+                claim_link(CLAIM_EDIT_EVENT_LINK, self.link_id, transaction)
+                    .and_then(|(claimed, transaction)| {
+                        if claimed {
+                            Ok(transaction)
+                        } else {
+                            Err((EventErrorKind::Lookup.into(), transaction))
+                        }
+                    })
+                    .and_then(move |transaction| update_event(self, transaction))
+                    .and_then(move |(event, transaction)| match hosts {
+                        Some(hosts) => Either::A(
+                            update_hosts(event.id, hosts, transaction)
+                                .map(move |transaction| (event, transaction)),
+                        ),
+                        None => Either::B(Ok((event, transaction)).into_future()),
+                    })
+                    .and_then(|(event, transaction)| lookup_hosts(event, transaction))
+                    .and_then(move |(event, transaction)| {
+                        if hosts_changed && event.hosts.is_empty() {
+                            Err((EventErrorKind::Hosts.into(), transaction))
+                        } else {
+                            Ok((event, transaction))
+                        }
+                    })
+                    .or_else(|(e, transaction)| {
+                        transaction
+                            .rollback()
+                            .or_else(|(_, connection)| Err(connection))
+                            .then(move |res| match res {
+                                Ok(connection) => Err((e, connection)),
+                                Err(connection) => Err((e, connection)),
+                            })
+                    })
+                    .and_then(|(event, transaction)| {
+                        transaction
+                            .commit()
+                            .map_err(commit_error)
+                            .map(move |connection| (event, connection))
+                    })
+            })
+    }
+}
+
+fn update_event(
+    update_event: UpdateEvent,
+    transaction: Transaction,
+) -> impl Future<Item = (Event, Transaction), Error = (EventError, Transaction)> {
+    let sql = "UPDATE events
+                SET start_date = $1, end_date = $2, title = $3, description = $4, timezone = $5,
+                    publish_at = CASE WHEN publish_at IS NULL THEN NULL ELSE $6 END,
+                    capacity = $7, location = $8, location_hint = $9, members_only = $10,
+                    latitude = $11, longitude = $12, image_url = $13, rsvp_deadline = $14,
+                    boost_threshold = $15, unlisted = $16, updated_at = now()
+                WHERE id = $17
+                RETURNING publish_at, public_id, cancelled, updated_at, pending";
+    debug!("{}", sql);
+
+    let UpdateEvent {
+        id,
+        system_id,
+        start_date,
+        end_date,
+        title,
+        description,
+        hosts: _hosts,
+        publish_at,
+        capacity,
+        boost_threshold,
+        location,
+        location_hint,
+        coordinates,
+        image_url,
+        rsvp_deadline,
+        members_only,
+        unlisted,
+        link_id: _link_id,
+    } = update_event;
+
+    let timezone = start_date.timezone();
+    let tz_name = timezone.name();
+    let sd = start_date.with_timezone(&Utc);
+    let ed = end_date.with_timezone(&Utc);
+    let pa = publish_at.map(|pa| pa.with_timezone(&Utc));
+    let rd = rsvp_deadline.map(|rd| rd.with_timezone(&Utc));
+    let latitude = coordinates.map(|c| c.latitude());
+    let longitude = coordinates.map(|c| c.longitude());
+
+    transaction
+        .prepare(sql)
+        .map_err(transaction_prepare_error)
+        .and_then(move |(s, transaction)| {
+            transaction
+                .query(
+                    &s,
+                    &[
+                        &sd,
+                        &ed,
+                        &title,
+                        &description,
+                        &tz_name,
+                        &pa,
+                        &capacity,
+                        &location,
+                        &location_hint,
+                        &members_only,
+                        &latitude,
+                        &longitude,
+                        &image_url,
+                        &rd,
+                        &boost_threshold,
+                        &unlisted,
+                        &id,
+                    ],
+                )
+                .map(|row| {
+                    let publish_at: Option<DateTime<Utc>> = row.get(0);
+                    let public_id: String = row.get(1);
+                    let cancelled: bool = row.get(2);
+                    let updated_at: Option<DateTime<Utc>> = row.get(3);
+                    let pending: bool = row.get(4);
+
+                    (publish_at, public_id, cancelled, updated_at, pending)
+                })
+                .collect()
+                .map_err(transaction_update_error)
+                .and_then(move |(mut rows, transaction)| {
+                    if rows.len() > 0 {
+                        let (publish_at, public_id, cancelled, updated_at, pending) =
+                            rows.remove(0);
+
+                        Ok((
+                            Event {
+                                id,
+                                system_id,
+                                start_date,
+                                end_date,
+                                title,
+                                description,
+                                hosts: Vec::new(),
+                                publish_at: publish_at.map(|pa| pa.with_timezone(&timezone)),
+                                capacity,
+                                location,
+                                location_hint,
+                                members_only,
+                                public_id,
+                                cancelled,
+                                updated_at: updated_at.map(|ua| ua.with_timezone(&timezone)),
+                                coordinates,
+                                image_url,
+                                rsvp_deadline,
+                                boost_threshold,
+                                pending,
+                                unlisted,
+                            },
+                            transaction,
+                        ))
+                    } else {
+                        Err((EventErrorKind::Update.into(), transaction))
+                    }
+                })
+        })
+}
+
+/// Make the users with the given database IDs the event's hosts
+///
+/// Users are only added if they belong to one of the chats linked to the event's channel.
+fn update_hosts(
+    event_id: i32,
+    hosts: Vec<i32>,
+    transaction: Transaction,
+) -> impl Future<Item = Transaction, Error = (EventError, Transaction)> {
+    if hosts.is_empty() {
+        return Either::B(Err((EventErrorKind::Hosts.into(), transaction)).into_future());
+    }
+
+    let values = (0..hosts.len())
+        .map(|index| format!("${}", index + 2))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let remove_sql = format!(
+        "DELETE FROM hosts WHERE events_id = $1 AND users_id NOT IN ({})",
+        values
+    );
+    let add_sql = format!(
+        "INSERT INTO hosts (users_id, events_id)
+            SELECT usr.id, evt.id
+            FROM users AS usr, events AS evt
+            WHERE evt.id = $1 AND usr.id IN ({}) AND NOT EXISTS (
+                SELECT 1 FROM hosts AS h WHERE h.events_id = evt.id AND h.users_id = usr.id
+            ) AND EXISTS (
+                SELECT 1
+                FROM user_chats AS uc
+                INNER JOIN chats AS ch ON ch.id = uc.chats_id
+                WHERE uc.users_id = usr.id AND ch.system_id = evt.system_id
+            )",
+        values
+    );
+
+    let hosts2 = hosts.clone();
+
+    debug!("{}", remove_sql);
+
+    Either::A(
+        transaction
+            .prepare(&remove_sql)
+            .map_err(transaction_prepare_error)
+            .and_then(move |(s, transaction)| {
+                let mut sql_args = vec![&event_id as &ToSql];
+                sql_args.extend(hosts.iter().map(|id| id as &ToSql));
+
+                transaction
+                    .execute(&s, sql_args.as_slice())
+                    .map_err(transaction_delete_error)
+            })
+            .and_then(move |(_, transaction)| {
+                debug!("{}", add_sql);
+
+                transaction
+                    .prepare(&add_sql)
+                    .map_err(transaction_prepare_error)
+            })
+            .and_then(move |(s, transaction)| {
+                let mut sql_args = vec![&event_id as &ToSql];
+                sql_args.extend(hosts2.iter().map(|id| id as &ToSql));
+
+                transaction
+                    .execute(&s, sql_args.as_slice())
+                    .map_err(transaction_insert_error)
+            })
+            .map(|(_, transaction)| transaction),
+    )
+}
+
+/// Fill in the event's hosts from the database
+fn lookup_hosts(
+    mut event: Event,
+    transaction: Transaction,
+) -> impl Future<Item = (Event, Transaction), Error = (EventError, Transaction)> {
+    let sql = format!(
+        "SELECT {}
+            FROM hosts AS h
+            INNER JOIN users AS usr ON usr.id = h.users_id
+            WHERE h.events_id = $1
+            ORDER BY usr.id",
+        HOST_COLUMNS
+    );
+    debug!("{}", sql);
+
+    let id = event.id;
+
+    transaction
+        .prepare(&sql)
+        .map_err(transaction_prepare_error)
+        .and_then(move |(s, transaction)| {
+            transaction
+                .query(&s, &[&id])
+                .map(|row| User::maybe_from_parts(row.get(0), row.get(1), row.get(2)))
+                .collect()
+                .map_err(transaction_lookup_error)
+        })
+        .map(move |(hosts, transaction): (Vec<Option<User>>, _)| {
+            event.hosts = hosts.into_iter().filter_map(|host| host).collect();
+
+            (event, transaction)
+        })
+}
+
+/// This type provides a safe way to create events in the database
+///
+/// `needs_approval` is whether the event should wait for an admin to approve it if its channel is
+/// moderated. `link_id` is the `NewEventLink` the event was made from, which is used up in the same
+/// transaction that creates the event.
+#[derive(Clone, Debug)]
+pub struct CreateEvent {
+    pub system_id: i32,
+    pub start_date: DateTime<Tz>,
     pub end_date: DateTime<Tz>,
     pub title: String,
     pub description: String,
     pub hosts: Vec<User>,
+    pub publish_at: Option<DateTime<Tz>>,
+    pub capacity: Option<i32>,
+    pub boost_threshold: Option<i32>,
+    pub location: Option<String>,
+    pub location_hint: Option<String>,
+    pub coordinates: Option<Coordinates>,
+    pub image_url: Option<String>,
+    pub rsvp_deadline: Option<DateTime<Tz>>,
+    pub members_only: bool,
+    pub unlisted: bool,
+    pub needs_approval: bool,
+    pub link_id: Option<i32>,
 }
 
 impl CreateEvent {
     /// Create a future which yields the new Event
+    ///
+    /// This yields `None` if the event's link has already been used, since the event it was used
+    /// for already exists
     pub fn create(
         self,
         connection: Connection,
-    ) -> impl Future<Item = (Event, Connection), Error = (EventError, Connection)> {
-        let sql = "INSERT INTO events (start_date, end_date, title, description, system_id, timezone) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id";
+    ) -> impl Future<Item = (Option<Event>, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO events (start_date, end_date, title, description, system_id, timezone, publish_at, capacity, location, location_hint, members_only, latitude, longitude, image_url, rsvp_deadline, boost_threshold, pending, unlisted, new_event_links_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17 AND COALESCE((SELECT sys.moderated FROM chat_systems AS sys WHERE sys.id = $5), FALSE), $18, $19) RETURNING id, public_id, pending";
         debug!("{}", sql);
 
-        let CreateEvent {
-            system_id,
-            start_date,
-            end_date,
-            title,
-            description,
-            hosts,
-        } = self;
-
         connection
             .transaction()
             .map_err(transaction_error)
             .and_then(move |transaction| {
-                insert_event(
-                    sql,
-                    system_id,
-                    start_date,
-                    end_date,
-                    title,
-                    description,
-                    hosts,
-                    transaction,
-                ).or_else(|(e, transaction)| {
-                    transaction
-                        .rollback()
-                        .or_else(|(_, connection)| Err(connection))
-                        .then(move |res| match res {
-                            Ok(connection) => Err((e, connection)),
-                            Err(connection) => Err((e, connection)),
-                        })
-                })
+                claim_link(CLAIM_NEW_EVENT_LINK, self.link_id, transaction)
+                    .and_then(move |(claimed, transaction)| {
+                        if claimed {
+                            Either::A(
+                                insert_event(sql, self, transaction)
+                                    .map(|(event, transaction)| (Some(event), transaction)),
+                            )
+                        } else {
+                            Either::B(Ok((None, transaction)).into_future())
+                        }
+                    })
+                    .or_else(|(e, transaction)| {
+                        transaction
+                            .rollback()
+                            .or_else(|(_, connection)| Err(connection))
+                            .then(move |res| match res {
+                                Ok(connection) => Err((e, connection)),
+                                Err(connection) => Err((e, connection)),
+                            })
+                    })
                     .and_then(|(event, transaction)| {
                         transaction
                             .commit()
@@ -538,18 +1795,73 @@ impl CreateEvent {
     }
 }
 
+const CLAIM_NEW_EVENT_LINK: &str =
+    "UPDATE new_event_links SET used = TRUE WHERE id = $1 AND used = FALSE";
+const CLAIM_EDIT_EVENT_LINK: &str =
+    "UPDATE edit_event_links SET used = TRUE WHERE id = $1 AND used = FALSE";
+
+/// Mark the link an event is being made or edited from as used, yielding whether it was still
+/// unused
+///
+/// A second submission of the same form waits here until the first one's transaction is done, so
+/// only one of them can claim the link.
+fn claim_link(
+    sql: &'static str,
+    link_id: Option<i32>,
+    transaction: Transaction,
+) -> impl Future<Item = (bool, Transaction), Error = (EventError, Transaction)> {
+    if let Some(link_id) = link_id {
+        debug!("{}", sql);
+
+        Either::A(
+            transaction
+                .prepare(sql)
+                .map_err(transaction_prepare_error)
+                .and_then(move |(s, transaction)| {
+                    transaction
+                        .execute(&s, &[&link_id])
+                        .map_err(transaction_update_error)
+                        .map(|(count, transaction)| (count > 0, transaction))
+                }),
+        )
+    } else {
+        Either::B(Ok((true, transaction)).into_future())
+    }
+}
+
 fn insert_event(
     sql: &str,
-    id: i32,
-    start_date: DateTime<Tz>,
-    end_date: DateTime<Tz>,
-    title: String,
-    description: String,
-    hosts: Vec<User>,
+    create_event: CreateEvent,
     transaction: Transaction,
 ) -> impl Future<Item = (Event, Transaction), Error = (EventError, Transaction)> {
+    let CreateEvent {
+        system_id,
+        start_date,
+        end_date,
+        title,
+        description,
+        hosts,
+        publish_at,
+        capacity,
+        boost_threshold,
+        location,
+        location_hint,
+        coordinates,
+        image_url,
+        rsvp_deadline,
+        members_only,
+        unlisted,
+        needs_approval,
+        link_id,
+    } = create_event;
+
     let sd = start_date.with_timezone(&Utc);
     let ed = end_date.with_timezone(&Utc);
+    let pa = publish_at.map(|pa| pa.with_timezone(&Utc));
+    let rd = rsvp_deadline.map(|rd| rd.with_timezone(&Utc));
+    let latitude = coordinates.map(|c| c.latitude());
+    let longitude = coordinates.map(|c| c.longitude());
+
     transaction
         .prepare(sql)
         .map_err(transaction_prepare_error)
@@ -562,8 +1874,21 @@ fn insert_event(
                         &ed,
                         &title,
                         &description,
-                        &id,
+                        &system_id,
                         &start_date.timezone().name(),
+                        &pa,
+                        &capacity,
+                        &location,
+                        &location_hint,
+                        &members_only,
+                        &latitude,
+                        &longitude,
+                        &image_url,
+                        &rd,
+                        &boost_threshold,
+                        &needs_approval,
+                        &unlisted,
+                        &link_id,
                     ],
                 )
                 .map(move |row| Event {
@@ -573,7 +1898,21 @@ fn insert_event(
                     title: title.clone(),
                     description: description.clone(),
                     hosts: Vec::new(),
-                    system_id: id,
+                    system_id: system_id,
+                    publish_at: publish_at,
+                    capacity: capacity,
+                    location: location.clone(),
+                    location_hint: location_hint.clone(),
+                    members_only: members_only,
+                    public_id: row.get(1),
+                    cancelled: false,
+                    updated_at: None,
+                    coordinates: coordinates,
+                    image_url: image_url.clone(),
+                    rsvp_deadline: rsvp_deadline,
+                    boost_threshold: boost_threshold,
+                    pending: row.get(2),
+                    unlisted: unlisted,
                 })
                 .collect()
                 .map_err(transaction_insert_error)
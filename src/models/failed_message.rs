@@ -0,0 +1,187 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `FailedMessage` type, and the functions for storing and replaying
+//! messages that couldn't be sent
+
+use chrono::offset::Utc;
+use chrono::DateTime;
+use futures::Future;
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
+use tokio_postgres::Connection;
+
+use error::{EventError, EventErrorKind};
+use util::*;
+
+/// How many failed messages are listed at once
+const LIST_LIMIT: i64 = 20;
+
+/// `FailedMessage` is a message the bot gave up sending, kept so it can be inspected or sent
+/// again
+///
+/// `event_id` is the event the message was about. It isn't a foreign key, since messages saying
+/// an event was deleted are kept after the event is gone. `announcement` is whether the
+/// message was an event's announcement, which is replayed by announcing the event again rather
+/// than by sending `text`.
+///
+/// ### Columns:
+/// - id SERIAL
+/// - chat_id BIGINT
+/// - event_id INTEGER
+/// - announcement BOOLEAN
+/// - text TEXT
+/// - error TEXT
+/// - failed_at TIMESTAMP WITH TIME ZONE
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FailedMessage {
+    id: i32,
+    chat_id: Integer,
+    event_id: i32,
+    announcement: bool,
+    text: String,
+    error: String,
+    failed_at: DateTime<Utc>,
+}
+
+impl FailedMessage {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Get the chat the message was for
+    pub fn chat_id(&self) -> Integer {
+        self.chat_id
+    }
+
+    /// Get the ID of the event the message was about
+    pub fn event_id(&self) -> i32 {
+        self.event_id
+    }
+
+    /// Whether the message was an event's announcement
+    pub fn announcement(&self) -> bool {
+        self.announcement
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the error the last attempt to send the message failed with
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    pub fn failed_at(&self) -> &DateTime<Utc> {
+        &self.failed_at
+    }
+
+    fn from_row(row: &Row) -> Self {
+        FailedMessage {
+            id: row.get(0),
+            chat_id: row.get(1),
+            event_id: row.get(2),
+            announcement: row.get(3),
+            text: row.get(4),
+            error: row.get(5),
+            failed_at: row.get(6),
+        }
+    }
+
+    /// Store a message the bot gave up sending
+    pub fn create(
+        chat_id: Integer,
+        event_id: i32,
+        announcement: bool,
+        text: String,
+        error: String,
+        connection: Connection,
+    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO failed_messages (chat_id, event_id, announcement, text, error)
+                    VALUES ($1, $2, $3, $4, $5)";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&chat_id, &event_id, &announcement, &text, &error])
+                    .map_err(insert_error)
+            })
+            .map(|(_, connection)| ((), connection))
+    }
+
+    /// Get the most recent failed messages, newest first
+    pub fn recent(
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<FailedMessage>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT fm.id, fm.chat_id, fm.event_id, fm.announcement, fm.text, fm.error,
+                        fm.failed_at
+                    FROM failed_messages AS fm
+                    ORDER BY fm.failed_at DESC, fm.id DESC
+                    LIMIT $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&LIST_LIMIT])
+                    .map(|row| FailedMessage::from_row(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+
+    /// Remove the failed message with the given ID so it can be sent again, returning it
+    ///
+    /// A message that fails again is stored again.
+    pub fn take(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (FailedMessage, Connection), Error = (EventError, Connection)> {
+        let sql = "DELETE FROM failed_messages AS fm
+                    WHERE fm.id = $1
+                    RETURNING fm.id, fm.chat_id, fm.event_id, fm.announcement, fm.text, fm.error,
+                        fm.failed_at";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&id])
+                    .map(|row| FailedMessage::from_row(&row))
+                    .collect()
+                    .map_err(delete_error)
+            })
+            .and_then(|(mut messages, connection)| {
+                if messages.len() > 0 {
+                    Ok((messages.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Lookup.into(), connection))
+                }
+            })
+    }
+}
@@ -0,0 +1,166 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `Features` type, which keeps track of the parts of the bot a chat
+//! system has turned on
+
+use futures::Future;
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
+use tokio_postgres::Connection;
+
+use error::{EventError, EventErrorKind};
+use util::*;
+
+/// The parts of the bot that can be turned on or off for each chat system
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Feature {
+    /// The RSVP buttons on announcements
+    Rsvp,
+    /// The weekly digest
+    Digest,
+    /// Events' public pages in the Web UI, and the channel's place in the directory
+    PublicPage,
+}
+
+/// Every feature, in the order they're listed
+pub const FEATURES: &[Feature] = &[Feature::Rsvp, Feature::Digest, Feature::PublicPage];
+
+impl Feature {
+    /// Get the feature from the name used in the `/features` command
+    pub fn from_name(name: &str) -> Option<Self> {
+        FEATURES
+            .iter()
+            .find(|feature| feature.name() == name.to_lowercase())
+            .map(|feature| *feature)
+    }
+
+    /// Get the name used for the feature in the `/features` command
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Feature::Rsvp => "rsvp",
+            Feature::Digest => "digest",
+            Feature::PublicPage => "public_page",
+        }
+    }
+}
+
+/// `Features` records which features a chat system has turned on
+///
+/// Chat systems that haven't changed their features don't have a row, and get the defaults.
+///
+/// ### Relations:
+/// - chat_system_features belongs_to chat_systems (foreign_key on chat_system_features)
+///
+/// ### Columns:
+/// - id SERIAL
+/// - chat_systems_id INTEGER REFERENCES chat_systems
+/// - rsvp_enabled BOOLEAN
+/// - digest_enabled BOOLEAN
+/// - public_page_enabled BOOLEAN
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Features {
+    rsvp: bool,
+    digest: bool,
+    public_page: bool,
+}
+
+impl Default for Features {
+    /// Every feature is on until a channel turns it off
+    fn default() -> Self {
+        Features {
+            rsvp: true,
+            digest: true,
+            public_page: true,
+        }
+    }
+}
+
+impl Features {
+    /// Check whether the feature is turned on
+    pub fn enabled(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Rsvp => self.rsvp,
+            Feature::Digest => self.digest,
+            Feature::PublicPage => self.public_page,
+        }
+    }
+
+    /// Build `Features` from three columns of a row, starting at `offset`, in the order the
+    /// features are listed in `FEATURES`
+    pub fn from_row(row: &Row, offset: usize) -> Self {
+        Features {
+            rsvp: row.get(offset),
+            digest: row.get(offset + 1),
+            public_page: row.get(offset + 2),
+        }
+    }
+
+    /// Turn a feature on or off for the `ChatSystem` with the given channel, returning its updated
+    /// features
+    pub fn set_enabled(
+        channel_id: Integer,
+        feature: Feature,
+        enabled: bool,
+        connection: Connection,
+    ) -> impl Future<Item = (Features, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO chat_system_features
+                        (chat_systems_id, rsvp_enabled, digest_enabled, public_page_enabled)
+                    SELECT sys.id, COALESCE($2, TRUE), COALESCE($3, TRUE), COALESCE($4, TRUE)
+                    FROM chat_systems AS sys
+                    WHERE sys.events_channel = $1
+                    ON CONFLICT (chat_systems_id) DO UPDATE SET
+                        rsvp_enabled = COALESCE($2, chat_system_features.rsvp_enabled),
+                        digest_enabled = COALESCE($3, chat_system_features.digest_enabled),
+                        public_page_enabled = COALESCE($4, chat_system_features.public_page_enabled)
+                    RETURNING rsvp_enabled, digest_enabled, public_page_enabled";
+        debug!("{}", sql);
+
+        let setting = |kind| {
+            if feature == kind {
+                Some(enabled)
+            } else {
+                None
+            }
+        };
+
+        let rsvp = setting(Feature::Rsvp);
+        let digest = setting(Feature::Digest);
+        let public_page = setting(Feature::PublicPage);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id, &rsvp, &digest, &public_page])
+                    .map(|row| Features::from_row(&row, 0))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut features, connection)| {
+                if features.len() > 0 {
+                    Ok((features.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+}
@@ -23,5 +23,11 @@ pub mod chat;
 pub mod chat_system;
 pub mod edit_event_link;
 pub mod event;
+pub mod failed_message;
+pub mod features;
 pub mod new_event_link;
+pub mod rsvp;
+pub mod stats;
 pub mod user;
+pub mod user_preferences;
+pub mod webhook;
@@ -19,6 +19,8 @@
 
 //! This module defines the `NewEventLink` struct and associated types and functions.
 
+use chrono::offset::Utc;
+use chrono::DateTime;
 use futures::Future;
 use futures_state_stream::StateStream;
 use tokio_postgres::Connection;
@@ -32,7 +34,8 @@ use util::*;
 /// `user_id` is the database ID of the user who asked for this link
 /// `system_id` is the database ID of the system the event is associated with
 /// `event_id` is the database ID of the event this link is associated with
-/// `secret` is a bcrypted secret used to verify that an edited event is valid
+/// `secret` is a hashed secret used to verify that an edited event is valid
+/// `expires_at` is the time after which the link can no longer be used
 ///
 /// ### Relations:
 /// - new_event_links belongs_to users (foreign_key on new_event_links)
@@ -45,12 +48,14 @@ use util::*;
 ///  - system_id INTEGER REFERENCES chat_systems
 ///  - event_id INTEGER REFERENCES events
 ///  - secret - TEXT
+///  - expires_at - TIMESTAMP WITH TIME ZONE
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NewEventLink {
     id: i32,
     user_id: i32,
     system_id: i32,
     secret: String,
+    expires_at: DateTime<Utc>,
 }
 
 impl NewEventLink {
@@ -76,6 +81,11 @@ impl NewEventLink {
         &self.secret
     }
 
+    /// Get the time the `NewEventLink` expires
+    pub fn expires_at(&self) -> &DateTime<Utc> {
+        &self.expires_at
+    }
+
     /// Insert a `NewEventLink` into the database given the associated IDs and the secret
     pub fn create(
         user_id: i32,
@@ -83,7 +93,7 @@ impl NewEventLink {
         secret: String,
         connection: Connection,
     ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
-        let sql = "INSERT INTO new_event_links (users_id, system_id, secret) VALUES ($1, $2, $3) RETURNING id";
+        let sql = "INSERT INTO new_event_links (users_id, system_id, secret) VALUES ($1, $2, $3) RETURNING id, expires_at";
         debug!("{}", sql);
 
         connection
@@ -97,6 +107,7 @@ impl NewEventLink {
                         user_id: user_id,
                         system_id: system_id,
                         secret: secret.clone(),
+                        expires_at: row.get(1),
                     })
                     .collect()
                     .map_err(insert_error)
@@ -115,9 +126,31 @@ impl NewEventLink {
         id: i32,
         connection: Connection,
     ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
-        let sql = "SELECT nel.id, nel.users_id, nel.system_id, nel.secret
+        let sql = "SELECT nel.id, nel.users_id, nel.system_id, nel.secret, nel.expires_at
+                    FROM new_event_links AS nel
+                    WHERE nel.id = $1 AND nel.used = FALSE AND nel.expires_at > NOW()";
+
+        NewEventLink::lookup(sql, id, connection)
+    }
+
+    /// Lookup a `NewEventLink` that has already been used, so a form submitted twice can find the
+    /// event it made
+    pub fn used_by_id(
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT nel.id, nel.users_id, nel.system_id, nel.secret, nel.expires_at
                     FROM new_event_links AS nel
-                    WHERE nel.id = $1 AND nel.used = FALSE";
+                    WHERE nel.id = $1 AND nel.used = TRUE";
+
+        NewEventLink::lookup(sql, id, connection)
+    }
+
+    fn lookup(
+        sql: &'static str,
+        id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Self, Connection), Error = (EventError, Connection)> {
         debug!("{}", sql);
 
         connection
@@ -131,6 +164,7 @@ impl NewEventLink {
                         user_id: row.get(1),
                         system_id: row.get(2),
                         secret: row.get(3),
+                        expires_at: row.get(4),
                     })
                     .collect()
                     .map_err(lookup_error)
@@ -144,12 +178,12 @@ impl NewEventLink {
             })
     }
 
-    /// Mark a `NewEventLink` as used
-    pub fn delete(
+    /// Check whether the `NewEventLink` with the given ID is still unused, even if it has expired
+    pub fn is_unused(
         id: i32,
         connection: Connection,
-    ) -> impl Future<Item = Connection, Error = (EventError, Connection)> {
-        let sql = "UPDATE new_event_links SET used = TRUE WHERE id = $1";
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT nel.used FROM new_event_links AS nel WHERE nel.id = $1";
         debug!("{}", sql);
 
         connection
@@ -157,15 +191,15 @@ impl NewEventLink {
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .execute(&s, &[&id])
-                    .map_err(delete_error)
-                    .and_then(|(count, connection)| {
-                        if count > 0 {
-                            Ok(connection)
-                        } else {
-                            Err((EventErrorKind::Delete.into(), connection))
-                        }
-                    })
+                    .query(&s, &[&id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(used, connection): (Vec<Option<bool>>, _)| {
+                let unused = used.into_iter().any(|used| !used.unwrap_or(false));
+
+                (unused, connection)
             })
     }
 }
@@ -0,0 +1,406 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `Rsvp` type, and the functions for joining and leaving events
+
+use chrono::offset::Utc;
+use chrono::DateTime;
+use futures::future::Either;
+use futures::{Future, IntoFuture};
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::transaction::Transaction;
+use tokio_postgres::Connection;
+
+use super::user::User;
+use error::{EventError, EventErrorKind};
+use util::*;
+
+/// `RsvpStatus` describes where a user ended up after asking to attend an event
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RsvpStatus {
+    /// The user is attending the event
+    Going,
+    /// The event is full, and the user has been placed on the waitlist
+    Waitlisted,
+    /// The user had already responded to this event
+    Unchanged,
+}
+
+/// `Rsvp` represents a user who is attending an event. Users who try to attend an event that has
+/// reached its capacity are stored in the waitlist instead, and are promoted to attendees in the
+/// order they joined when a spot opens up.
+///
+/// ### Relations:
+/// - rsvps belongs_to events (foreign_key on rsvps)
+/// - rsvps belongs_to users (foreign_key on rsvps)
+/// - waitlist belongs_to events (foreign_key on waitlist)
+/// - waitlist belongs_to users (foreign_key on waitlist)
+///
+/// ### Columns:
+/// - id SERIAL
+/// - events_id INTEGER REFERENCES events
+/// - users_id INTEGER REFERENCES users
+pub struct Rsvp;
+
+/// `UserRsvp` is an event a user is going to or waitlisted for, as seen from the user's side
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct UserRsvp {
+    event_id: i32,
+    title: String,
+    start_date: DateTime<Utc>,
+    waitlisted: bool,
+}
+
+impl UserRsvp {
+    /// Get the ID of the event
+    pub fn event_id(&self) -> i32 {
+        self.event_id
+    }
+
+    /// Get the event's title
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get when the event starts
+    pub fn start_date(&self) -> &DateTime<Utc> {
+        &self.start_date
+    }
+
+    /// Check whether the user is on the event's waitlist rather than going
+    pub fn waitlisted(&self) -> bool {
+        self.waitlisted
+    }
+}
+
+impl Rsvp {
+    /// Mark the user with database ID `user_id` as going to the event with ID `event_id`, or add
+    /// them to the waitlist if the event is full
+    pub fn going(
+        event_id: i32,
+        user_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (RsvpStatus, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO rsvps (events_id, users_id)
+                    SELECT evt.id, $2
+                    FROM events AS evt
+                    WHERE evt.id = $1 AND (
+                        evt.capacity IS NULL OR
+                        (SELECT COUNT(*) FROM rsvps AS r WHERE r.events_id = $1) < evt.capacity
+                    )
+                    ON CONFLICT DO NOTHING
+                    RETURNING id";
+
+        with_event_locked(event_id, connection, move |transaction| {
+            debug!("{}", sql);
+
+            transaction
+                .prepare(sql)
+                .map_err(transaction_prepare_error)
+                .and_then(move |(s, transaction)| {
+                    transaction
+                        .query(&s, &[&event_id, &user_id])
+                        .map(|row| row.get(0))
+                        .collect()
+                        .map_err(transaction_insert_error)
+                })
+                .and_then(move |(ids, transaction): (Vec<i32>, _)| {
+                    if ids.len() > 0 {
+                        Either::A(Ok((RsvpStatus::Going, transaction)).into_future())
+                    } else {
+                        Either::B(Rsvp::waitlist(event_id, user_id, transaction))
+                    }
+                })
+        })
+    }
+
+    /// Add the user to the waitlist for the event, unless they're already going
+    fn waitlist(
+        event_id: i32,
+        user_id: i32,
+        transaction: Transaction,
+    ) -> impl Future<Item = (RsvpStatus, Transaction), Error = (EventError, Transaction)> {
+        let sql = "INSERT INTO waitlist (events_id, users_id)
+                    SELECT evt.id, $2
+                    FROM events AS evt
+                    WHERE evt.id = $1 AND NOT EXISTS (
+                        SELECT 1 FROM rsvps AS r WHERE r.events_id = $1 AND r.users_id = $2
+                    )
+                    ON CONFLICT DO NOTHING
+                    RETURNING id";
+        debug!("{}", sql);
+
+        transaction
+            .prepare(sql)
+            .map_err(transaction_prepare_error)
+            .and_then(move |(s, transaction)| {
+                transaction
+                    .query(&s, &[&event_id, &user_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(transaction_insert_error)
+            })
+            .map(|(ids, transaction): (Vec<i32>, _)| {
+                if ids.len() > 0 {
+                    (RsvpStatus::Waitlisted, transaction)
+                } else {
+                    (RsvpStatus::Unchanged, transaction)
+                }
+            })
+    }
+
+    /// Get the events the user with the given Telegram ID is going to or waitlisted for, soonest
+    /// first
+    pub fn by_user_id(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<UserRsvp>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT evt.id, evt.title, evt.start_date, FALSE AS waitlisted
+                    FROM rsvps AS r
+                    INNER JOIN events AS evt ON evt.id = r.events_id
+                    INNER JOIN users AS usr ON usr.id = r.users_id
+                    WHERE usr.user_id = $1
+                    UNION ALL
+                    SELECT evt.id, evt.title, evt.start_date, TRUE AS waitlisted
+                    FROM waitlist AS w
+                    INNER JOIN events AS evt ON evt.id = w.events_id
+                    INNER JOIN users AS usr ON usr.id = w.users_id
+                    WHERE usr.user_id = $1
+                    ORDER BY start_date, id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&user_id])
+                    .map(|row| UserRsvp {
+                        event_id: row.get(0),
+                        title: row.get(1),
+                        start_date: row.get(2),
+                        waitlisted: row.get(3),
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+
+    /// Get the users going to the event, and the users on its waitlist in the order they joined
+    pub fn attendees(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = ((Vec<User>, Vec<User>), Connection), Error = (EventError, Connection)>
+    {
+        let sql = "SELECT usr.id, usr.user_id, usr.username, FALSE AS waitlisted, r.id AS position
+                    FROM rsvps AS r
+                    INNER JOIN users AS usr ON usr.id = r.users_id
+                    WHERE r.events_id = $1
+                    UNION ALL
+                    SELECT usr.id, usr.user_id, usr.username, TRUE AS waitlisted, w.id AS position
+                    FROM waitlist AS w
+                    INNER JOIN users AS usr ON usr.id = w.users_id
+                    WHERE w.events_id = $1
+                    ORDER BY waitlisted, position";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&event_id])
+                    .map(|row| {
+                        let waitlisted: bool = row.get(3);
+
+                        (
+                            User::maybe_from_parts(row.get(0), row.get(1), row.get(2)),
+                            waitlisted,
+                        )
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(users, connection): (Vec<(Option<User>, bool)>, _)| {
+                let (waitlisted, going): (Vec<_>, Vec<_>) =
+                    users.into_iter().partition(|&(_, waitlisted)| waitlisted);
+
+                (
+                    (
+                        going.into_iter().filter_map(|(user, _)| user).collect(),
+                        waitlisted.into_iter().filter_map(|(user, _)| user).collect(),
+                    ),
+                    connection,
+                )
+            })
+    }
+
+    /// Remove the user from the event's attendees and waitlist. If this frees up a spot, the
+    /// first user on the waitlist is promoted, and returned along with anyone else promoted.
+    pub fn cancel(
+        event_id: i32,
+        user_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        let sql = "WITH removed AS (
+                        DELETE FROM waitlist WHERE events_id = $1 AND users_id = $2
+                    )
+                    DELETE FROM rsvps WHERE events_id = $1 AND users_id = $2";
+
+        with_event_locked(event_id, connection, move |transaction| {
+            debug!("{}", sql);
+
+            transaction
+                .prepare(sql)
+                .map_err(transaction_prepare_error)
+                .and_then(move |(s, transaction)| {
+                    transaction
+                        .execute(&s, &[&event_id, &user_id])
+                        .map_err(transaction_delete_error)
+                })
+                .and_then(move |(count, transaction)| {
+                    if count > 0 {
+                        Either::A(Rsvp::promote(event_id, transaction))
+                    } else {
+                        Either::B(Ok((Vec::new(), transaction)).into_future())
+                    }
+                })
+        })
+    }
+
+    /// Promote as many users from the event's waitlist as there's room for, like after the
+    /// event's capacity is raised, returning the promoted users
+    pub fn fill(
+        event_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        with_event_locked(event_id, connection, move |transaction| {
+            Rsvp::promote(event_id, transaction)
+        })
+    }
+
+    /// Move users from the event's waitlist to its attendees, in the order they joined, until
+    /// the event is full
+    fn promote(
+        event_id: i32,
+        transaction: Transaction,
+    ) -> impl Future<Item = (Vec<User>, Transaction), Error = (EventError, Transaction)> {
+        // A NULL limit promotes everyone, for events without a capacity
+        let sql = "WITH next AS (
+                        DELETE FROM waitlist
+                        WHERE id IN (
+                            SELECT w.id
+                            FROM waitlist AS w
+                            WHERE w.events_id = $1
+                            ORDER BY w.id
+                            LIMIT (
+                                SELECT GREATEST(
+                                    evt.capacity - (
+                                        SELECT COUNT(*) FROM rsvps AS r WHERE r.events_id = $1
+                                    ),
+                                    0
+                                )
+                                FROM events AS evt
+                                WHERE evt.id = $1
+                            )
+                        )
+                        RETURNING events_id, users_id
+                    ), promoted AS (
+                        INSERT INTO rsvps (events_id, users_id)
+                        SELECT events_id, users_id FROM next
+                        RETURNING users_id
+                    )
+                    SELECT usr.id, usr.user_id, usr.username
+                    FROM promoted
+                    INNER JOIN users AS usr ON usr.id = promoted.users_id";
+        debug!("{}", sql);
+
+        transaction
+            .prepare(sql)
+            .map_err(transaction_prepare_error)
+            .and_then(move |(s, transaction)| {
+                transaction
+                    .query(&s, &[&event_id])
+                    .map(|row| User::maybe_from_parts(row.get(0), row.get(1), row.get(2)))
+                    .collect()
+                    .map_err(transaction_update_error)
+            })
+            .map(|(users, transaction): (Vec<Option<User>>, _)| {
+                (users.into_iter().filter_map(|user| user).collect(), transaction)
+            })
+    }
+}
+
+/// Run `f` in a transaction that locks the event's row first
+///
+/// Deciding whether there's room at an event counts its RSVPs, so RSVPs to the same event have to
+/// be made one at a time, or two users could both take the last spot.
+fn with_event_locked<F, R, T>(
+    event_id: i32,
+    connection: Connection,
+    f: F,
+) -> impl Future<Item = (T, Connection), Error = (EventError, Connection)>
+where
+    F: FnOnce(Transaction) -> R,
+    R: Future<Item = (T, Transaction), Error = (EventError, Transaction)>,
+{
+    let sql = "SELECT id FROM events WHERE id = $1 FOR UPDATE";
+
+    connection
+        .transaction()
+        .map_err(transaction_error)
+        .and_then(move |transaction| {
+            debug!("{}", sql);
+
+            transaction
+                .prepare(sql)
+                .map_err(transaction_prepare_error)
+                .and_then(move |(s, transaction)| {
+                    transaction
+                        .query(&s, &[&event_id])
+                        .map(|row| row.get(0))
+                        .collect()
+                        .map_err(transaction_lookup_error)
+                })
+                .and_then(|(ids, transaction): (Vec<i32>, _)| {
+                    if ids.len() > 0 {
+                        Ok(transaction)
+                    } else {
+                        Err((EventErrorKind::Lookup.into(), transaction))
+                    }
+                })
+                .and_then(f)
+                .or_else(|(e, transaction)| {
+                    transaction
+                        .rollback()
+                        .or_else(|(_, connection)| Err(connection))
+                        .then(move |res| match res {
+                            Ok(connection) => Err((e, connection)),
+                            Err(connection) => Err((e, connection)),
+                        })
+                })
+                .and_then(|(item, transaction)| {
+                    transaction
+                        .commit()
+                        .map_err(commit_error)
+                        .map(|connection| (item, connection))
+                })
+        })
+}
@@ -0,0 +1,260 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `Stats` type, a summary of a chat system's activity for the admins of
+//! its channel
+//!
+//! Events made before creation dates were recorded don't count towards the events created in a
+//! month. Events that have ended are read from `past_events`, since they're deleted once they end.
+//!
+//! It also defines `TimeSlot`, a weekday and hour when a chat system's events have drawn the most
+//! people.
+
+use chrono::offset::Utc;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use futures::Future;
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::Connection;
+
+use error::{EventError, EventErrorKind};
+use util::*;
+
+/// How many hosts are listed as the most active
+const TOP_HOSTS: i64 = 3;
+
+/// How many times are suggested to hosts
+const SUGGESTED_SLOTS: i64 = 3;
+
+/// `Stats` summarizes the events and members of the `ChatSystem` with a given channel
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    events_created: i64,
+    top_hosts: Vec<(Option<String>, i64)>,
+    average_attendance: Option<f64>,
+    chats: i64,
+    users: i64,
+}
+
+impl Stats {
+    /// Get the number of events created since the date the stats were gathered for
+    pub fn events_created(&self) -> i64 {
+        self.events_created
+    }
+
+    /// Get the usernames of the users who've hosted the most events, with how many they've hosted
+    pub fn top_hosts(&self) -> &[(Option<String>, i64)] {
+        &self.top_hosts
+    }
+
+    /// Get the average number of people who went to events that have ended, if any have
+    ///
+    /// Cancelled events aren't counted
+    pub fn average_attendance(&self) -> Option<f64> {
+        self.average_attendance
+    }
+
+    /// Get the number of chats linked to the channel
+    pub fn chats(&self) -> i64 {
+        self.chats
+    }
+
+    /// Get the number of users the bot has seen in those chats
+    pub fn users(&self) -> i64 {
+        self.users
+    }
+
+    /// Gather the stats for the `ChatSystem` with the given channel, counting the events created
+    /// since `since`
+    ///
+    /// This fails with a Lookup error if the channel hasn't been initialized
+    pub fn for_channel(
+        channel_id: Integer,
+        since: DateTime<Tz>,
+        connection: Connection,
+    ) -> impl Future<Item = (Stats, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT
+                        (SELECT COUNT(*) FROM events AS evt
+                            WHERE evt.system_id = sys.id AND evt.created_at >= $2)
+                        + (SELECT COUNT(*) FROM past_events AS past
+                            WHERE past.system_id = sys.id AND past.created_at >= $2),
+                        (SELECT AVG(attendance.going)::DOUBLE PRECISION FROM (
+                            SELECT COUNT(r.id) AS going
+                            FROM events AS evt
+                            LEFT JOIN rsvps AS r ON r.events_id = evt.id
+                            WHERE evt.system_id = sys.id AND evt.end_date < NOW()
+                                AND evt.cancelled = FALSE
+                            GROUP BY evt.id
+                            UNION ALL
+                            SELECT past.going::BIGINT AS going
+                            FROM past_events AS past
+                            WHERE past.system_id = sys.id AND past.cancelled = FALSE
+                        ) AS attendance),
+                        (SELECT COUNT(*) FROM chats AS c WHERE c.system_id = sys.id),
+                        (SELECT COUNT(DISTINCT uc.users_id) FROM user_chats AS uc
+                            INNER JOIN chats AS c ON c.id = uc.chats_id
+                            WHERE c.system_id = sys.id)
+                    FROM chat_systems AS sys
+                    WHERE sys.events_channel = $1";
+        debug!("{}", sql);
+
+        let since = since.with_timezone(&Utc);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id, &since])
+                    .map(|row| Stats {
+                        events_created: row.get(0),
+                        top_hosts: Vec::new(),
+                        average_attendance: row.get(1),
+                        chats: row.get(2),
+                        users: row.get(3),
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .and_then(|(mut stats, connection)| {
+                if stats.len() > 0 {
+                    Ok((stats.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Lookup.into(), connection))
+                }
+            })
+            .and_then(move |(stats, connection)| {
+                let sql = "SELECT usr.username, COUNT(*) AS hosted
+                            FROM (
+                                SELECT h.users_id
+                                FROM hosts AS h
+                                INNER JOIN events AS evt ON evt.id = h.events_id
+                                INNER JOIN chat_systems AS sys ON sys.id = evt.system_id
+                                WHERE sys.events_channel = $1
+                                UNION ALL
+                                SELECT UNNEST(past.host_ids) AS users_id
+                                FROM past_events AS past
+                                INNER JOIN chat_systems AS sys ON sys.id = past.system_id
+                                WHERE sys.events_channel = $1
+                            ) AS h
+                            INNER JOIN users AS usr ON usr.id = h.users_id
+                            GROUP BY usr.id, usr.username
+                            ORDER BY hosted DESC, usr.username
+                            LIMIT $2";
+                debug!("{}", sql);
+
+                connection
+                    .prepare(sql)
+                    .map_err(prepare_error)
+                    .and_then(move |(s, connection)| {
+                        connection
+                            .query(&s, &[&channel_id, &TOP_HOSTS])
+                            .map(|row| (row.get(0), row.get(1)))
+                            .collect()
+                            .map_err(lookup_error)
+                    })
+                    .map(move |(top_hosts, connection)| (Stats { top_hosts, ..stats }, connection))
+            })
+    }
+}
+
+/// `TimeSlot` is a weekday and hour that past events started at, along with how many people went
+/// to them on average
+///
+/// `weekday` counts days from Monday
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeSlot {
+    weekday: u32,
+    hour: u32,
+    average_attendance: f64,
+}
+
+impl TimeSlot {
+    /// Get the number of days after Monday the slot falls on
+    pub fn weekday(&self) -> u32 {
+        self.weekday
+    }
+
+    /// Get the hour the slot starts at
+    pub fn hour(&self) -> u32 {
+        self.hour
+    }
+
+    /// Get the average number of people who went to events in the slot
+    pub fn average_attendance(&self) -> f64 {
+        self.average_attendance
+    }
+
+    /// Find the slots where the `ChatSystem`'s events that have ended drew the most people, with
+    /// the best slot first
+    ///
+    /// Start dates are grouped in the given timezone. Cancelled events, and events still waiting
+    /// for approval, aren't counted. Most ended events have already been deleted, so they're read
+    /// from `past_events`.
+    pub fn best_for_system(
+        system_id: i32,
+        timezone: Tz,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<TimeSlot>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT
+                        (EXTRACT(ISODOW FROM attendance.start_date AT TIME ZONE $2) - 1)::INTEGER
+                            AS weekday,
+                        EXTRACT(HOUR FROM attendance.start_date AT TIME ZONE $2)::INTEGER AS hour,
+                        AVG(attendance.going)::DOUBLE PRECISION AS average
+                    FROM (
+                        SELECT evt.start_date, COUNT(r.id) AS going
+                        FROM events AS evt
+                        LEFT JOIN rsvps AS r ON r.events_id = evt.id
+                        WHERE evt.system_id = $1 AND evt.end_date < NOW()
+                            AND evt.cancelled = FALSE AND evt.pending = FALSE
+                        GROUP BY evt.id
+                        UNION ALL
+                        SELECT past.start_date, past.going::BIGINT AS going
+                        FROM past_events AS past
+                        WHERE past.system_id = $1 AND past.cancelled = FALSE
+                    ) AS attendance
+                    GROUP BY weekday, hour
+                    ORDER BY average DESC, COUNT(*) DESC
+                    LIMIT $3";
+        debug!("{}", sql);
+
+        let timezone = timezone.name();
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id, &timezone, &SUGGESTED_SLOTS])
+                    .map(|row| {
+                        let weekday: i32 = row.get(0);
+                        let hour: i32 = row.get(1);
+
+                        TimeSlot {
+                            weekday: weekday as u32,
+                            hour: hour as u32,
+                            average_attendance: row.get(2),
+                        }
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+}
@@ -19,13 +19,16 @@
 
 //! This module defines the `User` struct and associated types and functions.
 
-use futures::Future;
+use futures::future::Either;
+use futures::{Future, IntoFuture};
 use futures_state_stream::StateStream;
 use telebot::objects::Integer;
 use tokio_postgres::types::ToSql;
 use tokio_postgres::Connection;
 
 use super::chat::Chat;
+use super::event::Event;
+use super::rsvp::{Rsvp, UserRsvp};
 use error::{EventError, EventErrorKind};
 use util::*;
 
@@ -35,6 +38,7 @@ use util::*;
 ///
 /// ### Relations:
 /// - users has_many user_chats (foreign key on user_chats)
+/// - users has_one user_preferences (foreign key on user_preferences)
 ///
 /// ### Columns:
 /// - id SERIAL
@@ -149,6 +153,35 @@ impl User {
             })
     }
 
+    /// Get the Users in any of the chats belonging to a Chat System, ordered by username
+    pub fn by_system_id(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<User>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT DISTINCT usr.id, usr.user_id, usr.username
+                    FROM users AS usr
+                    INNER JOIN user_chats AS uc ON uc.users_id = usr.id
+                    INNER JOIN chats AS ch ON uc.chats_id = ch.id
+                    WHERE ch.system_id = $1
+                    ORDER BY usr.username";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id])
+                    .map(move |row| User {
+                        id: row.get(0),
+                        user_id: row.get(1),
+                        username: row.get(2),
+                    })
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+
     /// Get a vector of Users and their associated Chats
     pub fn get_with_chats(
         connection: Connection,
@@ -180,38 +213,158 @@ impl User {
             })
     }
 
-    /// Delete a User from the database
-    pub fn delete_by_user_id(
+    /// Remove a relationship between a User and a Chat, deleting the User as well if
+    /// `delete_user` is set
+    ///
+    /// Both happen in one transaction, so a failure leaves the User in the Chat rather than
+    /// half-removed.
+    pub fn remove_from_chat(
         user_id: Integer,
+        chat_id: Integer,
+        delete_user: bool,
         connection: Connection,
     ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        let sql = "DELETE FROM users AS usr WHERE usr.user_id = $1";
-        debug!("{}", sql);
+        let relation_sql = "DELETE FROM user_chats AS uc
+                    USING users AS usr, chats AS ch
+                    WHERE uc.users_id = usr.id AND uc.chats_id = ch.id AND usr.user_id = $1 AND ch.chat_id = $2";
+        let user_sql = "DELETE FROM users AS usr WHERE usr.user_id = $1";
 
         connection
-            .prepare(sql)
-            .map_err(prepare_error)
-            .and_then(move |(s, connection)| {
-                connection.execute(&s, &[&user_id]).map_err(delete_error)
+            .transaction()
+            .map_err(transaction_error)
+            .and_then(move |transaction| {
+                debug!("{}", relation_sql);
+                transaction
+                    .prepare(relation_sql)
+                    .map_err(transaction_prepare_error)
+                    .and_then(move |(s, transaction)| {
+                        transaction
+                            .execute(&s, &[&user_id, &chat_id])
+                            .map_err(transaction_delete_error)
+                    })
+                    .and_then(|(count, transaction)| {
+                        if count > 0 {
+                            Ok(transaction)
+                        } else {
+                            Err((EventErrorKind::Delete.into(), transaction))
+                        }
+                    })
+                    .and_then(move |transaction| {
+                        if !delete_user {
+                            return Either::B(Ok(transaction).into_future());
+                        }
+
+                        debug!("{}", user_sql);
+                        Either::A(
+                            transaction
+                                .prepare(user_sql)
+                                .map_err(transaction_prepare_error)
+                                .and_then(move |(s, transaction)| {
+                                    transaction
+                                        .execute(&s, &[&user_id])
+                                        .map_err(transaction_delete_error)
+                                })
+                                .and_then(|(count, transaction)| {
+                                    if count > 0 {
+                                        Ok(transaction)
+                                    } else {
+                                        Err((EventErrorKind::Delete.into(), transaction))
+                                    }
+                                }),
+                        )
+                    })
+                    .or_else(|(error, transaction)| {
+                        transaction
+                            .rollback()
+                            .or_else(|(_, connection)| Err(connection))
+                            .then(move |res| match res {
+                                Ok(connection) => Err((error, connection)),
+                                Err(connection) => Err((error, connection)),
+                            })
+                    })
             })
-            .and_then(|(count, connection)| {
-                if count > 0 {
-                    Ok(((), connection))
+            .and_then(|transaction| {
+                transaction
+                    .commit()
+                    .map_err(commit_error)
+                    .map(|connection| ((), connection))
+            })
+    }
+
+    /// Gather everything stored about the User with the given Telegram ID
+    ///
+    /// This fails with a Lookup error if the bot doesn't know the User.
+    pub fn export(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Export, Connection), Error = (EventError, Connection)> {
+        User::by_user_ids(vec![user_id], connection)
+            .and_then(|(mut users, connection)| {
+                if users.len() > 0 {
+                    Ok((users.remove(0), connection))
                 } else {
-                    Err((EventErrorKind::Delete.into(), connection))
+                    Err((EventErrorKind::Lookup.into(), connection))
                 }
             })
+            .and_then(move |(user, connection)| {
+                Chat::by_user_id(user_id, connection)
+                    .map(move |(chats, connection)| ((user, chats), connection))
+            })
+            .and_then(move |(data, connection)| {
+                Event::by_user_id(user_id, connection)
+                    .map(move |(hosted, connection)| ((data, hosted), connection))
+            })
+            .and_then(move |(data, connection)| {
+                Rsvp::by_user_id(user_id, connection)
+                    .map(move |(rsvps, connection)| ((data, rsvps), connection))
+            })
+            .map(|((((user, chats), hosted), rsvps), connection)| {
+                (
+                    Export {
+                        user,
+                        chats,
+                        hosted,
+                        rsvps,
+                    },
+                    connection,
+                )
+            })
     }
 
-    /// Remove a relationship between a User and a Chat
-    pub fn delete_relation_by_ids(
+    /// Delete everything stored about the User with the given Telegram ID, returning how much of
+    /// it there was
+    ///
+    /// The User's chats, host entries, RSVPs, waitlist spots, and event links are deleted along
+    /// with the User. Events they hosted are kept for their other hosts and attendees. This fails
+    /// with a Lookup error if the bot doesn't know the User.
+    pub fn forget(
         user_id: Integer,
-        chat_id: Integer,
         connection: Connection,
-    ) -> impl Future<Item = ((), Connection), Error = (EventError, Connection)> {
-        let sql = "DELETE FROM user_chats AS uc
-                    USING users AS usr, chats AS ch
-                    WHERE uc.users_id = usr.id AND uc.chats_id = ch.id AND usr.user_id = $1 AND ch.chat_id = $2";
+    ) -> impl Future<Item = (Forgotten, Connection), Error = (EventError, Connection)> {
+        let sql = "WITH usr AS (
+                        SELECT id FROM users WHERE user_id = $1
+                    ), chats AS (
+                        DELETE FROM user_chats WHERE users_id IN (SELECT id FROM usr) RETURNING 1
+                    ), hosted AS (
+                        DELETE FROM hosts WHERE users_id IN (SELECT id FROM usr) RETURNING 1
+                    ), going AS (
+                        DELETE FROM rsvps WHERE users_id IN (SELECT id FROM usr) RETURNING 1
+                    ), waiting AS (
+                        DELETE FROM waitlist WHERE users_id IN (SELECT id FROM usr) RETURNING 1
+                    ), new_links AS (
+                        DELETE FROM new_event_links WHERE users_id IN (SELECT id FROM usr)
+                        RETURNING 1
+                    ), edit_links AS (
+                        DELETE FROM edit_event_links WHERE users_id IN (SELECT id FROM usr)
+                        RETURNING 1
+                    ), deleted AS (
+                        DELETE FROM users WHERE id IN (SELECT id FROM usr) RETURNING 1
+                    )
+                    SELECT (SELECT COUNT(*) FROM deleted),
+                        (SELECT COUNT(*) FROM chats),
+                        (SELECT COUNT(*) FROM hosted),
+                        (SELECT COUNT(*) FROM going) + (SELECT COUNT(*) FROM waiting),
+                        (SELECT COUNT(*) FROM new_links) + (SELECT COUNT(*) FROM edit_links)";
         debug!("{}", sql);
 
         connection
@@ -219,16 +372,89 @@ impl User {
             .map_err(prepare_error)
             .and_then(move |(s, connection)| {
                 connection
-                    .execute(&s, &[&user_id, &chat_id])
-                    .map_err(delete_error)
-                    .and_then(|(count, connection)| {
-                        if count > 0 {
-                            Ok(((), connection))
-                        } else {
-                            Err((EventErrorKind::Delete.into(), connection))
-                        }
+                    .query(&s, &[&user_id])
+                    .map(|row| {
+                        let deleted: i64 = row.get(0);
+
+                        (
+                            deleted,
+                            Forgotten {
+                                chats: row.get(1),
+                                hosted: row.get(2),
+                                rsvps: row.get(3),
+                                links: row.get(4),
+                            },
+                        )
                     })
+                    .collect()
+                    .map_err(delete_error)
             })
+            .and_then(|(mut rows, connection)| match rows.pop() {
+                Some((deleted, forgotten)) if deleted > 0 => Ok((forgotten, connection)),
+                _ => Err((EventErrorKind::Lookup.into(), connection)),
+            })
+    }
+}
+
+/// `Export` is everything stored about a User, gathered when they ask for a copy of it
+#[derive(Clone, Debug)]
+pub struct Export {
+    user: User,
+    chats: Vec<Chat>,
+    hosted: Vec<Event>,
+    rsvps: Vec<UserRsvp>,
+}
+
+impl Export {
+    /// Get the User
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// Get the chats the User is known to be in
+    pub fn chats(&self) -> &[Chat] {
+        &self.chats
+    }
+
+    /// Get the events the User hosts
+    pub fn hosted(&self) -> &[Event] {
+        &self.hosted
+    }
+
+    /// Get the events the User is going to or waitlisted for
+    pub fn rsvps(&self) -> &[UserRsvp] {
+        &self.rsvps
+    }
+}
+
+/// `Forgotten` counts what was deleted when a User asked the bot to forget them
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Forgotten {
+    chats: i64,
+    hosted: i64,
+    rsvps: i64,
+    links: i64,
+}
+
+impl Forgotten {
+    /// The number of chats the User was known to be in
+    pub fn chats(&self) -> i64 {
+        self.chats
+    }
+
+    /// The number of events the User was a host of
+    pub fn hosted(&self) -> i64 {
+        self.hosted
+    }
+
+    /// The number of events the User was going to or waitlisted for
+    pub fn rsvps(&self) -> i64 {
+        self.rsvps
+    }
+
+    /// The number of links the User had for creating or editing events
+    pub fn links(&self) -> i64 {
+        self.links
     }
 }
 
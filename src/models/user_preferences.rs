@@ -0,0 +1,349 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `UserPreferences` type, which keeps track of the private messages a
+//! user has muted and the language and time format they've picked
+
+use futures::Future;
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
+use tokio_postgres::Connection;
+
+use error::{EventError, EventErrorKind};
+use event_web::locale::{Language, TimeFormat};
+use util::*;
+
+/// The private messages the bot sends on its own, which users can mute
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Notification {
+    /// The reminders sent to an event's attendees shortly before it starts
+    Reminders,
+    /// The messages hosts send to their events' attendees with `/announce`
+    Broadcasts,
+    /// Messages about events the user hosts, like being made the host of one
+    HostNudges,
+    /// The message sent when a spot opens up and the user is taken off an event's waitlist
+    WaitlistPromotions,
+}
+
+/// Every notification, in the order they're listed
+pub const NOTIFICATIONS: &[Notification] = &[
+    Notification::Reminders,
+    Notification::Broadcasts,
+    Notification::HostNudges,
+    Notification::WaitlistPromotions,
+];
+
+impl Notification {
+    /// Get the notification from the name used in the `/mute` and `/unmute` commands
+    pub fn from_name(name: &str) -> Option<Self> {
+        NOTIFICATIONS
+            .iter()
+            .find(|notification| notification.name() == name.to_lowercase())
+            .map(|notification| *notification)
+    }
+
+    /// Get the name used for the notification in the `/mute` and `/unmute` commands
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Notification::Reminders => "reminders",
+            Notification::Broadcasts => "broadcasts",
+            Notification::HostNudges => "hosting",
+            Notification::WaitlistPromotions => "waitlist",
+        }
+    }
+
+    /// The column that records whether the notification is muted
+    fn column(&self) -> &'static str {
+        match *self {
+            Notification::Reminders => "mute_reminders",
+            Notification::Broadcasts => "mute_broadcasts",
+            Notification::HostNudges => "mute_host_nudges",
+            Notification::WaitlistPromotions => "mute_waitlist",
+        }
+    }
+}
+
+/// `UserPreferences` records which private messages a user has muted, and which language the bot
+/// speaks to them in
+///
+/// Users who haven't changed their preferences don't have a row, and get every message in English.
+///
+/// ### Relations:
+/// - user_preferences belongs_to users (foreign_key on user_preferences)
+///
+/// ### Columns:
+/// - id SERIAL
+/// - users_id INTEGER REFERENCES users
+/// - mute_reminders BOOLEAN
+/// - mute_broadcasts BOOLEAN
+/// - mute_host_nudges BOOLEAN
+/// - mute_waitlist BOOLEAN
+/// - language TEXT (a code like `es`)
+/// - twelve_hour BOOLEAN (NULL follows the channel)
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UserPreferences {
+    mute_reminders: bool,
+    mute_broadcasts: bool,
+    mute_host_nudges: bool,
+    mute_waitlist: bool,
+    language: Language,
+    time_format: Option<TimeFormat>,
+}
+
+impl UserPreferences {
+    /// Check whether the user has muted the given notification
+    pub fn muted(&self, notification: Notification) -> bool {
+        match notification {
+            Notification::Reminders => self.mute_reminders,
+            Notification::Broadcasts => self.mute_broadcasts,
+            Notification::HostNudges => self.mute_host_nudges,
+            Notification::WaitlistPromotions => self.mute_waitlist,
+        }
+    }
+
+    /// Get the language the user picked, or English if they haven't picked one
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Get the time format the user picked, if they've picked one over their channels'
+    pub fn time_format(&self) -> Option<TimeFormat> {
+        self.time_format
+    }
+
+    /// Build `UserPreferences` from six columns of a row, in the order the notifications are
+    /// listed in `NOTIFICATIONS` followed by the language and time format
+    fn from_row(row: &Row) -> Self {
+        UserPreferences {
+            mute_reminders: row.get(0),
+            mute_broadcasts: row.get(1),
+            mute_host_nudges: row.get(2),
+            mute_waitlist: row.get(3),
+            language: row.get::<_, Option<String>>(4)
+                .and_then(|code| Language::from_code(&code))
+                .unwrap_or_default(),
+            time_format: row.get::<_, Option<bool>>(5).map(TimeFormat::from_twelve_hour),
+        }
+    }
+
+    /// Get the preferences of the User with the given Telegram ID
+    ///
+    /// Users who haven't changed their preferences get the defaults, which mute nothing
+    pub fn by_user_id(
+        user_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT up.mute_reminders, up.mute_broadcasts, up.mute_host_nudges,
+                        up.mute_waitlist, up.language, up.twelve_hour
+                    FROM user_preferences AS up
+                    INNER JOIN users AS usr ON usr.id = up.users_id
+                    WHERE usr.user_id = $1";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&user_id])
+                    .map(|row| UserPreferences::from_row(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(mut preferences, connection)| {
+                if preferences.len() > 0 {
+                    (preferences.remove(0), connection)
+                } else {
+                    (UserPreferences::default(), connection)
+                }
+            })
+    }
+
+    /// Mute or unmute a notification for the User with the given Telegram ID, returning their
+    /// updated preferences. `None` mutes or unmutes every notification.
+    pub fn set_muted(
+        user_id: Integer,
+        notification: Option<Notification>,
+        muted: bool,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO user_preferences
+                        (users_id, mute_reminders, mute_broadcasts, mute_host_nudges, mute_waitlist)
+                    SELECT usr.id, COALESCE($2, FALSE), COALESCE($3, FALSE), COALESCE($4, FALSE),
+                        COALESCE($5, FALSE)
+                    FROM users AS usr
+                    WHERE usr.user_id = $1
+                    ON CONFLICT (users_id) DO UPDATE SET
+                        mute_reminders = COALESCE($2, user_preferences.mute_reminders),
+                        mute_broadcasts = COALESCE($3, user_preferences.mute_broadcasts),
+                        mute_host_nudges = COALESCE($4, user_preferences.mute_host_nudges),
+                        mute_waitlist = COALESCE($5, user_preferences.mute_waitlist)
+                    RETURNING mute_reminders, mute_broadcasts, mute_host_nudges, mute_waitlist,
+                        language, twelve_hour";
+        debug!("{}", sql);
+
+        let setting = |kind| match notification {
+            Some(notification) if notification != kind => None,
+            _ => Some(muted),
+        };
+
+        let reminders = setting(Notification::Reminders);
+        let broadcasts = setting(Notification::Broadcasts);
+        let host_nudges = setting(Notification::HostNudges);
+        let waitlist = setting(Notification::WaitlistPromotions);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(
+                        &s,
+                        &[
+                            &user_id,
+                            &reminders,
+                            &broadcasts,
+                            &host_nudges,
+                            &waitlist,
+                        ],
+                    )
+                    .map(|row| UserPreferences::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut preferences, connection)| {
+                if preferences.len() > 0 {
+                    Ok((preferences.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Set the language for the User with the given Telegram ID, returning their updated
+    /// preferences
+    pub fn set_language(
+        user_id: Integer,
+        language: Language,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO user_preferences (users_id, language)
+                    SELECT usr.id, $2
+                    FROM users AS usr
+                    WHERE usr.user_id = $1
+                    ON CONFLICT (users_id) DO UPDATE SET language = $2
+                    RETURNING mute_reminders, mute_broadcasts, mute_host_nudges, mute_waitlist,
+                        language, twelve_hour";
+        debug!("{}", sql);
+
+        let code = language.code();
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&user_id, &code])
+                    .map(|row| UserPreferences::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut preferences, connection)| {
+                if preferences.len() > 0 {
+                    Ok((preferences.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Set the time format for the User with the given Telegram ID, returning their updated
+    /// preferences
+    pub fn set_time_format(
+        user_id: Integer,
+        time_format: TimeFormat,
+        connection: Connection,
+    ) -> impl Future<Item = (UserPreferences, Connection), Error = (EventError, Connection)> {
+        let sql = "INSERT INTO user_preferences (users_id, twelve_hour)
+                    SELECT usr.id, $2
+                    FROM users AS usr
+                    WHERE usr.user_id = $1
+                    ON CONFLICT (users_id) DO UPDATE SET twelve_hour = $2
+                    RETURNING mute_reminders, mute_broadcasts, mute_host_nudges, mute_waitlist,
+                        language, twelve_hour";
+        debug!("{}", sql);
+
+        let twelve_hour = time_format.is_twelve_hour();
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&user_id, &twelve_hour])
+                    .map(|row| UserPreferences::from_row(&row))
+                    .collect()
+                    .map_err(update_error)
+            })
+            .and_then(|(mut preferences, connection)| {
+                if preferences.len() > 0 {
+                    Ok((preferences.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Update.into(), connection))
+                }
+            })
+    }
+
+    /// Get the Telegram IDs of the users going to the event who haven't muted the notification
+    pub fn recipients(
+        event_id: i32,
+        notification: Notification,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Integer>, Connection), Error = (EventError, Connection)> {
+        let sql = format!(
+            "SELECT usr.user_id
+                FROM rsvps AS r
+                INNER JOIN users AS usr ON usr.id = r.users_id
+                LEFT JOIN user_preferences AS up ON up.users_id = usr.id
+                WHERE r.events_id = $1 AND COALESCE(up.{}, FALSE) = FALSE
+                ORDER BY r.id",
+            notification.column()
+        );
+        debug!("{}", sql);
+
+        connection
+            .prepare(&sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&event_id])
+                    .map(|row| row.get(0))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+            .map(|(user_ids, connection): (Vec<Option<Integer>>, _)| {
+                (
+                    user_ids.into_iter().filter_map(|user_id| user_id).collect(),
+                    connection,
+                )
+            })
+    }
+}
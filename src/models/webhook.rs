@@ -0,0 +1,231 @@
+/*
+ * This file is part of Telegram Event Bot.
+ *
+ * Copyright © 2018 Riley Trautman
+ *
+ * Telegram Event Bot is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Telegram Event Bot is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Telegram Event Bot.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! This module defines the `Webhook` type, and the functions for registering and looking up
+//! webhooks
+
+use futures::Future;
+use futures_state_stream::StateStream;
+use telebot::objects::Integer;
+use tokio_postgres::rows::Row;
+use tokio_postgres::Connection;
+
+use error::{EventError, EventErrorKind};
+use util::*;
+
+/// `Webhook` is a URL that is sent every change to the events in a Chat System
+///
+/// `secret` is the key used to sign the requests, so the receiver can check they came from the
+/// bot. It's shared by all of a Chat System's webhooks, and stored in the `webhook_secret` column
+/// of `chat_systems`. Webhooks added before secrets were shared keep their own in `secret` until
+/// the Chat System's secret is replaced. Unlike the API token, it has to be stored as-is, since
+/// it's needed to sign each request.
+///
+/// ### Relations:
+/// - webhooks belongs_to chat_systems (foreign_key on webhooks)
+///
+/// ### Columns:
+/// - id SERIAL
+/// - system_id INTEGER REFERENCES chat_systems
+/// - url TEXT
+/// - secret TEXT (only set for webhooks added before secrets were shared)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Webhook {
+    id: i32,
+    system_id: i32,
+    url: String,
+    secret: String,
+}
+
+impl Webhook {
+    /// Get the ID of the `Webhook`
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Get the ID of the associated `ChatSystem`
+    pub fn system_id(&self) -> i32 {
+        self.system_id
+    }
+
+    /// Get the URL that changes are sent to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the key the requests are signed with
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    fn from_row(row: &Row) -> Self {
+        Webhook {
+            id: row.get(0),
+            system_id: row.get(1),
+            url: row.get(2),
+            secret: row.get(3),
+        }
+    }
+
+    /// Register a webhook for the `ChatSystem` with the given channel
+    ///
+    /// The given secret is only used if the `ChatSystem` doesn't have one yet, so the returned
+    /// webhook's secret is the one it had already, if any.
+    pub fn create(
+        channel_id: Integer,
+        url: String,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (Webhook, Connection), Error = (EventError, Connection)> {
+        let sql = "WITH sys AS (
+                        UPDATE chat_systems
+                        SET webhook_secret = COALESCE(webhook_secret, $3)
+                        WHERE events_channel = $1
+                        RETURNING id, webhook_secret
+                    )
+                    INSERT INTO webhooks (system_id, url)
+                    SELECT sys.id, $2
+                    FROM sys
+                    ON CONFLICT (system_id, url) DO UPDATE SET url = EXCLUDED.url
+                    RETURNING id, system_id, url,
+                        COALESCE(secret, (SELECT webhook_secret FROM sys))";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id, &url, &secret])
+                    .map(|row| Webhook::from_row(&row))
+                    .collect()
+                    .map_err(insert_error)
+            })
+            .and_then(|(mut webhooks, connection)| {
+                if webhooks.len() > 0 {
+                    Ok((webhooks.remove(0), connection))
+                } else {
+                    Err((EventErrorKind::Insert.into(), connection))
+                }
+            })
+    }
+
+    /// Replace the signing secret of the `ChatSystem` with the given channel, returning whether
+    /// the channel has any webhooks
+    ///
+    /// Webhooks that had their own secrets are signed with the new one too.
+    pub fn rotate_secret(
+        channel_id: Integer,
+        secret: String,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "WITH cleared AS (
+                        UPDATE webhooks AS wh
+                        SET secret = NULL
+                        FROM chat_systems AS sys
+                        WHERE sys.id = wh.system_id AND sys.events_channel = $1
+                    )
+                    UPDATE chat_systems AS sys
+                    SET webhook_secret = $2
+                    WHERE sys.events_channel = $1
+                        AND EXISTS (SELECT 1 FROM webhooks AS wh WHERE wh.system_id = sys.id)";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&channel_id, &secret])
+                    .map_err(update_error)
+            })
+            .map(|(count, connection)| (count > 0, connection))
+    }
+
+    /// Remove the webhook with the given URL from the `ChatSystem` with the given channel,
+    /// returning whether it was registered
+    pub fn delete(
+        channel_id: Integer,
+        url: String,
+        connection: Connection,
+    ) -> impl Future<Item = (bool, Connection), Error = (EventError, Connection)> {
+        let sql = "DELETE FROM webhooks AS wh
+                    USING chat_systems AS sys
+                    WHERE wh.system_id = sys.id AND sys.events_channel = $1 AND wh.url = $2";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .execute(&s, &[&channel_id, &url])
+                    .map_err(delete_error)
+            })
+            .map(|(count, connection)| (count > 0, connection))
+    }
+
+    /// Get the webhooks registered for the `ChatSystem` with the given ID
+    pub fn by_system_id(
+        system_id: i32,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Webhook>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT wh.id, wh.system_id, wh.url, COALESCE(wh.secret, sys.webhook_secret)
+                    FROM webhooks AS wh
+                    INNER JOIN chat_systems AS sys ON sys.id = wh.system_id
+                    WHERE wh.system_id = $1
+                    ORDER BY wh.id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&system_id])
+                    .map(|row| Webhook::from_row(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+
+    /// Get the webhooks registered for the `ChatSystem` with the given channel
+    pub fn by_channel_id(
+        channel_id: Integer,
+        connection: Connection,
+    ) -> impl Future<Item = (Vec<Webhook>, Connection), Error = (EventError, Connection)> {
+        let sql = "SELECT wh.id, wh.system_id, wh.url, COALESCE(wh.secret, sys.webhook_secret)
+                    FROM webhooks AS wh
+                    INNER JOIN chat_systems AS sys ON sys.id = wh.system_id
+                    WHERE sys.events_channel = $1
+                    ORDER BY wh.id";
+        debug!("{}", sql);
+
+        connection
+            .prepare(sql)
+            .map_err(prepare_error)
+            .and_then(move |(s, connection)| {
+                connection
+                    .query(&s, &[&channel_id])
+                    .map(|row| Webhook::from_row(&row))
+                    .collect()
+                    .map_err(lookup_error)
+            })
+    }
+}
@@ -81,6 +81,20 @@ pub(crate) fn transaction_insert_error(
     (error.context(EventErrorKind::Insert).into(), transaction)
 }
 
+/// Convert a transaction update error into an `EventError`
+pub(crate) fn transaction_update_error(
+    (error, transaction): (TpError, Transaction),
+) -> (EventError, Transaction) {
+    (error.context(EventErrorKind::Update).into(), transaction)
+}
+
+/// Convert a transaction delete error into an `EventError`
+pub(crate) fn transaction_delete_error(
+    (error, transaction): (TpError, Transaction),
+) -> (EventError, Transaction) {
+    (error.context(EventErrorKind::Delete).into(), transaction)
+}
+
 /// Convert a transaction lookup error into an `EventError`
 pub(crate) fn transaction_lookup_error(
     (error, transaction): (TpError, Transaction),
@@ -93,6 +107,56 @@ pub(crate) fn commit_error((error, connection): (TpError, Connection)) -> (Event
     (error.context(EventErrorKind::Commit).into(), connection)
 }
 
+/// Convert an error from a request to Telegram into an `EventError`
+///
+/// Failures that users can fix themselves, like the bot not being an admin of a chat, get their own
+/// kinds so users can be told how to fix them
+pub(crate) fn telegram_error<E: Fail>(error: E) -> EventError {
+    let kind = telegram_error_kind(&error.to_string(), EventErrorKind::Telegram);
+
+    error.context(kind).into()
+}
+
+/// Convert an error from looking something up on Telegram into an `EventError`
+pub(crate) fn telegram_lookup_error<E: Fail>(error: E) -> EventError {
+    let kind = telegram_error_kind(&error.to_string(), EventErrorKind::TelegramLookup);
+
+    error.context(kind).into()
+}
+
+/// Pick the kind of a Telegram failure from the description Telegram gave for it, falling back to
+/// `default` for failures users can't do anything about
+fn telegram_error_kind(description: &str, default: EventErrorKind) -> EventErrorKind {
+    let description = description.to_lowercase();
+
+    if description.contains("chat not found") {
+        EventErrorKind::ChatNotFound
+    } else if description.contains("bot was blocked by the user")
+        || description.contains("bot can't initiate conversation")
+    {
+        EventErrorKind::Blocked
+    } else if description.contains("not enough rights")
+        || description.contains("administrator rights")
+        || description.contains("chat_admin_required")
+        || description.contains("bot is not a member")
+        || description.contains("bot was kicked")
+    {
+        EventErrorKind::NotAdmin
+    } else if description.contains("is too long") {
+        EventErrorKind::TooLong
+    } else {
+        default
+    }
+}
+
+/// Describe an error along with its cause, like `Error in telegram: Bad Request: chat not found`
+pub(crate) fn describe_error(error: &EventError) -> String {
+    match error.cause() {
+        Some(cause) => format!("{}: {}", error, cause),
+        None => error.to_string(),
+    }
+}
+
 /// Flatten the result of a call to `addr.send()` from a `Result<Result<_, _>, _>` into a
 /// `Result<_, _>` by combining the error types
 ///
@@ -106,3 +170,65 @@ pub(crate) fn flatten<T>(
         Err(e) => Err(EventError::from(e.context(EventErrorKind::Canceled)).into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_missing_chats() {
+        assert_eq!(
+            telegram_error_kind("Bad Request: chat not found", EventErrorKind::TelegramLookup),
+            EventErrorKind::ChatNotFound
+        );
+    }
+
+    #[test]
+    fn finds_blocked_users() {
+        assert_eq!(
+            telegram_error_kind("Forbidden: bot was blocked by the user", EventErrorKind::Telegram),
+            EventErrorKind::Blocked
+        );
+        assert_eq!(
+            telegram_error_kind(
+                "Forbidden: bot can't initiate conversation with a user",
+                EventErrorKind::Telegram
+            ),
+            EventErrorKind::Blocked
+        );
+    }
+
+    #[test]
+    fn finds_missing_rights() {
+        assert_eq!(
+            telegram_error_kind(
+                "Bad Request: not enough rights to pin a message",
+                EventErrorKind::Telegram
+            ),
+            EventErrorKind::NotAdmin
+        );
+        assert_eq!(
+            telegram_error_kind(
+                "Forbidden: bot is not a member of the channel chat",
+                EventErrorKind::Telegram
+            ),
+            EventErrorKind::NotAdmin
+        );
+    }
+
+    #[test]
+    fn finds_long_messages() {
+        assert_eq!(
+            telegram_error_kind("Bad Request: message is too long", EventErrorKind::Telegram),
+            EventErrorKind::TooLong
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default() {
+        assert_eq!(
+            telegram_error_kind("Too Many Requests: retry after 5", EventErrorKind::Telegram),
+            EventErrorKind::Telegram
+        );
+    }
+}